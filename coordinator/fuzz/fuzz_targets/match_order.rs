@@ -0,0 +1,81 @@
+//! Match arbitrary market orders with arbitrary books and check the invariants of the matching
+//! engine.
+//!
+//! Run with `just fuzz-matching`.
+
+#![no_main]
+
+use coordinator::orderbook::trading::invariants;
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rust_decimal::Decimal;
+use time::Duration;
+use trade::Direction;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    long: bool,
+    quantity: Quantity,
+    book: Vec<LimitOrder>,
+}
+
+#[derive(Debug, Arbitrary)]
+struct LimitOrder {
+    long: bool,
+    price: Quantity,
+    quantity: Quantity,
+    /// Seconds after the market order, so that many orders are placed at the same time.
+    delay: u8,
+}
+
+/// A positive decimal with an arbitrary scale, to cover both tiny and huge amounts.
+#[derive(Debug, Arbitrary)]
+struct Quantity {
+    mantissa: u64,
+    scale: u8,
+}
+
+impl Quantity {
+    fn to_decimal(&self) -> Option<Decimal> {
+        if self.mantissa == 0 {
+            return None;
+        }
+
+        // The scale of a decimal is at most 28.
+        let scale = u32::from(self.scale % 29);
+        Some(Decimal::from_i128_with_scale(self.mantissa.into(), scale))
+    }
+}
+
+fn direction(long: bool) -> Direction {
+    if long {
+        Direction::Long
+    } else {
+        Direction::Short
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let quantity = match input.quantity.to_decimal() {
+        Some(quantity) => quantity,
+        None => return,
+    };
+
+    let book = input
+        .book
+        .iter()
+        .filter_map(|order| {
+            Some(invariants::limit_order(
+                direction(order.long),
+                order.price.to_decimal()?,
+                order.quantity.to_decimal()?,
+                Duration::seconds(order.delay.into()),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let direction = direction(input.long);
+
+    invariants::check_sort_orders(book.clone(), direction);
+    invariants::check_match_order(&invariants::market_order(direction, quantity), book);
+});
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE
+    matches
+    ADD
+        COLUMN "failure_reason" TEXT;
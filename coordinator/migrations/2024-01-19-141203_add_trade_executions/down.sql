@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE "trade_executions";
+DROP TYPE "TradeExecutionState_Type";
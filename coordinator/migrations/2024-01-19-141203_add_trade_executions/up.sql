@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE TYPE "TradeExecutionState_Type" AS ENUM ('Pending', 'Succeeded', 'Failed');
+
+CREATE TABLE "trade_executions" (
+    id SERIAL PRIMARY KEY NOT NULL,
+    order_id UUID UNIQUE NOT NULL,
+    trader_pubkey TEXT NOT NULL,
+    trade_params TEXT NOT NULL,
+    execution_state "TradeExecutionState_Type" NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT,
+    next_attempt TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE "api_keys" (
+    id UUID PRIMARY KEY NOT NULL,
+    trader_pubkey TEXT NOT NULL,
+    label TEXT NOT NULL,
+    key_hash TEXT UNIQUE NOT NULL,
+    scopes TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS api_keys_trader_pubkey ON api_keys(trader_pubkey);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE
+    payments DROP COLUMN "expiry_timestamp",
+    DROP COLUMN "payee_pubkey",
+    DROP COLUMN "failure_reason";
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE
+    payments
+    ADD
+        COLUMN "expiry_timestamp" TIMESTAMP WITH TIME ZONE,
+    ADD
+        COLUMN "payee_pubkey" TEXT,
+    ADD
+        COLUMN "failure_reason" TEXT;
@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE "last_outbound_dlc_messages" (
+    peer_id TEXT PRIMARY KEY NOT NULL,
+    message_hash TEXT REFERENCES dlc_messages(message_hash) NOT NULL,
+    message TEXT NOT NULL,
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO "last_outbound_dlc_messages" (peer_id, message_hash, message, timestamp)
+SELECT DISTINCT ON (peer_id) peer_id, message_hash, message, timestamp
+FROM "outbound_dlc_messages"
+ORDER BY peer_id, id DESC;
+
+DROP TABLE "outbound_dlc_messages";
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE "orderbook_events" (
+    id BIGSERIAL PRIMARY KEY NOT NULL,
+    contract_symbol "ContractSymbol_Type" NOT NULL,
+    event_type TEXT NOT NULL,
+    order_id UUID NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX orderbook_events_contract_symbol_id ON "orderbook_events" (contract_symbol, id);
+
+CREATE TABLE "orderbook_snapshots" (
+    id SERIAL PRIMARY KEY NOT NULL,
+    contract_symbol "ContractSymbol_Type" NOT NULL,
+    last_event_id BIGINT NOT NULL,
+    orders TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
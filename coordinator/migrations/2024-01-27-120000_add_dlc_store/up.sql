@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE "dlc_store" (
+    kind SMALLINT NOT NULL,
+    key BYTEA NOT NULL,
+    value BYTEA NOT NULL,
+    PRIMARY KEY (kind, key)
+);
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE "device_sessions" (
+    trader_pubkey TEXT PRIMARY KEY NOT NULL,
+    device_id TEXT NOT NULL,
+    registered_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE "trade_inputs" (
+    id SERIAL PRIMARY KEY NOT NULL,
+    trade_id INTEGER UNIQUE NOT NULL REFERENCES trades(id),
+    order_id UUID NOT NULL,
+    trade_params TEXT NOT NULL,
+    coordinator_leverage REAL NOT NULL,
+    offer_collateral BIGINT NOT NULL,
+    accept_collateral BIGINT NOT NULL,
+    fee_rate BIGINT NOT NULL,
+    temporary_contract_id TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
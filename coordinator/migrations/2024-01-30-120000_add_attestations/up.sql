@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TYPE "AttestationState_Type" AS ENUM ('Pending', 'Attested', 'Settled', 'Closed');
+
+CREATE TABLE "attestations" (
+    id SERIAL PRIMARY KEY NOT NULL,
+    position_id INTEGER UNIQUE NOT NULL REFERENCES positions(id),
+    trader_pubkey TEXT NOT NULL,
+    contract_id TEXT NOT NULL,
+    oracle_pk TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    attestation_state "AttestationState_Type" NOT NULL,
+    attested_price BIGINT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Trades executed before the payout curve became configurable used the default parameters.
+ALTER TABLE "trade_inputs" ADD COLUMN "payout_curve_params" TEXT;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE "user_presence" (
+    trader_pubkey TEXT PRIMARY KEY NOT NULL,
+    -- The websocket session of the trader, if they are connected.
+    session_id UUID,
+    connected_at TIMESTAMP WITH TIME ZONE,
+    last_seen TIMESTAMP WITH TIME ZONE NOT NULL
+);
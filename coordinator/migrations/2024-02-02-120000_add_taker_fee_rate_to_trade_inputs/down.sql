@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "trade_inputs" DROP COLUMN "taker_fee_rate";
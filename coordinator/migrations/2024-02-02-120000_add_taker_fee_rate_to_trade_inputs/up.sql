@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- Trades executed before the fee schedule became configurable were charged the default fee.
+ALTER TABLE "trade_inputs" ADD COLUMN "taker_fee_rate" TEXT;
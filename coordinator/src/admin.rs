@@ -1,40 +1,131 @@
 use crate::collaborative_revert;
 use crate::db;
+use crate::db::attestations::Attestation;
+use crate::db::trade_executions::TradeExecution;
+use crate::db::trade_executions::TradeExecutionState;
+use crate::db::user_presence::UserPresence;
+use crate::node::expired_positions;
+use crate::node::rollover_scheduler::ScheduledRollover;
+use crate::node::COORDINATOR_LEVERAGE;
+use crate::openapi::ErrorResponse;
+use crate::orderbook::trading_halt::HaltReason;
 use crate::parse_dlc_channel_id;
+use crate::payout_curve;
+use crate::payout_curve::SimulatedPayoutCurve;
+use crate::routes::statement_response;
 use crate::routes::AppState;
+use crate::routes::StatementParams;
+use crate::trade::replay;
+use crate::trade::replay::TradeReplay;
 use crate::AppError;
+use ::payout_curve::PayoutCurveParams;
 use anyhow::Context;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
 use axum::Json;
 use bdk::FeeRate;
-use bdk::LocalUtxo;
 use bdk::TransactionDetails;
+use bitcoin::psbt::PartiallySignedTransaction;
 use bitcoin::secp256k1::PublicKey;
+use bitcoin::Address;
+use bitcoin::Network;
+use bitcoin::OutPoint;
+use bitcoin::Txid;
+use commons::AdjustableClock;
+use commons::Clock;
+use commons::CollaborativeRevertCoordinatorBatchRequest;
 use commons::CollaborativeRevertCoordinatorRequest;
 use dlc_manager::channel::Channel;
 use dlc_manager::contract::Contract;
+use futures::stream;
+use futures::StreamExt;
 use lightning_invoice::Bolt11Invoice;
+use ln_dlc_node::node::Fee;
 use ln_dlc_node::node::NodeInfo;
+use ln_dlc_node::ReserveStatus;
+use ln_dlc_node::WalletUtxo;
+use rust_decimal::Decimal;
 use serde::de;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::fmt;
 use std::str::FromStr;
 use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
+use trade::ContractSymbol;
+use trade::Direction;
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Middleware protecting the admin API.
+///
+/// If an admin API token is configured in the settings, every request has to provide it as a
+/// bearer token in the `Authorization` header.
+pub async fn authenticate<B>(
+    State(state): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, AppError> {
+    if !is_admin(&state, request.headers()).await {
+        tracing::warn!(uri = %request.uri(), "Rejecting unauthenticated admin request");
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Whether the `headers` carry the admin API token, or no token is configured at all.
+pub(crate) async fn is_admin(state: &AppState, headers: &HeaderMap) -> bool {
+    let expected_token = match state.settings.read().await.admin_api_token.clone() {
+        Some(expected_token) => expected_token,
+        None => return true,
+    };
+
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    matches!(token, Some(token) if constant_time_eq(token.as_bytes(), expected_token.as_bytes()))
+}
+
+/// Compare two byte slices without leaking the position of the first mismatch through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct Balance {
     pub lightning: u64,
     pub onchain: u64,
     pub dlc_channel: u64,
+    #[schema(value_type = Object)]
+    pub onchain_reserve: ReserveStatus,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/admin/wallet/balance",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "The balances of the coordinator", body = Balance),
+        (status = 401, description = "The admin token is missing or wrong"),
+    )
+)]
 pub async fn get_balance(State(state): State<Arc<AppState>>) -> Result<Json<Balance>, AppError> {
     spawn_blocking(move || {
         let lightning_balance = state.node.inner.get_ldk_balance();
@@ -53,10 +144,19 @@ pub async fn get_balance(State(state): State<Arc<AppState>>) -> Result<Json<Bala
                 AppError::InternalServerError(format!("Failed to get balance: {e:#}"))
             })?;
 
+        let onchain_reserve = state
+            .node
+            .inner
+            .get_on_chain_reserve_status()
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get reserve status: {e:#}"))
+            })?;
+
         Ok(Json(Balance {
             lightning: lightning_balance.available(),
             onchain: onchain.confirmed,
             dlc_channel: dlc_channel.to_sat(),
+            onchain_reserve,
         }))
     })
     .await
@@ -65,14 +165,261 @@ pub async fn get_balance(State(state): State<Arc<AppState>>) -> Result<Json<Bala
 
 pub async fn get_utxos(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Vec<LocalUtxo>>, AppError> {
-    let utxos = state.node.inner.ldk_wallet().get_utxos().map_err(|error| {
-        AppError::InternalServerError(format!("Failed to retrieve UTXOs {error}"))
-    })?;
+) -> Result<Json<Vec<WalletUtxo>>, AppError> {
+    let utxos = state
+        .node
+        .inner
+        .ldk_wallet()
+        .list_utxos()
+        .map_err(|error| {
+            AppError::InternalServerError(format!("Failed to retrieve UTXOs {error}"))
+        })?;
 
     Ok(Json(utxos))
 }
 
+pub async fn freeze_utxo(
+    Path(outpoint): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    let outpoint = OutPoint::from_str(&outpoint)
+        .map_err(|e| AppError::BadRequest(format!("Invalid outpoint provided: {e:#}")))?;
+
+    state
+        .node
+        .inner
+        .ldk_wallet()
+        .freeze_utxo(outpoint)
+        .map_err(|e| AppError::BadRequest(format!("Failed to freeze UTXO: {e:#}")))
+}
+
+pub async fn unfreeze_utxo(
+    Path(outpoint): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    let outpoint = OutPoint::from_str(&outpoint)
+        .map_err(|e| AppError::BadRequest(format!("Invalid outpoint provided: {e:#}")))?;
+
+    state
+        .node
+        .inner
+        .ldk_wallet()
+        .unfreeze_utxo(outpoint)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to unfreeze UTXO: {e:#}")))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendOnChainParams {
+    pub address: String,
+    /// If set to `0`, all selected UTXOs, respectively the whole wallet, are drained.
+    pub amount_sats: u64,
+    pub fee_rate_sats_vb: f32,
+    /// Spend exactly these UTXOs instead of letting the wallet select them.
+    pub utxos: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SendOnChainResponse {
+    pub txid: String,
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn send_on_chain(
+    State(state): State<Arc<AppState>>,
+    Json(params): Json<SendOnChainParams>,
+) -> Result<Json<SendOnChainResponse>, AppError> {
+    let (address, fee, utxos) = parse_send_on_chain_params(&params)?;
+
+    spawn_blocking(move || {
+        let txid = state
+            .node
+            .inner
+            .send_to_address(&address, params.amount_sats, fee, utxos)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to send funds: {e:#}")))?;
+
+        Ok(Json(SendOnChainResponse {
+            txid: txid.to_string(),
+        }))
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to send funds: {e:#}")))?
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchWithdrawalParams {
+    pub recipients: Vec<BatchRecipient>,
+    pub fee_rate_sats_vb: f32,
+    /// Spend exactly these UTXOs instead of letting the wallet select them, e.g. the ones of a
+    /// previous dry run.
+    pub utxos: Option<Vec<String>>,
+    /// Only compute the transaction and its fee, without broadcasting it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRecipient {
+    pub address: String,
+    pub amount_sats: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchWithdrawalResponse {
+    /// Not set for a dry run.
+    pub txid: Option<String>,
+    /// The sum paid to all recipients.
+    pub amount_sats: u64,
+    pub fee_sats: u64,
+    /// The UTXOs spent by the transaction.
+    pub utxos: Vec<String>,
+}
+
+/// Pay several addresses with a single transaction, which saves fees compared to individual
+/// withdrawals.
+#[instrument(skip_all, err(Debug))]
+pub async fn withdraw_batch(
+    State(state): State<Arc<AppState>>,
+    Json(params): Json<BatchWithdrawalParams>,
+) -> Result<Json<BatchWithdrawalResponse>, AppError> {
+    let recipients = params
+        .recipients
+        .iter()
+        .map(|recipient| {
+            let address = Address::from_str(&recipient.address).map_err(|e| {
+                AppError::BadRequest(format!(
+                    "Invalid address provided {}: {e:#}",
+                    recipient.address
+                ))
+            })?;
+
+            Ok((address, recipient.amount_sats))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    let utxos = parse_outpoints(params.utxos.as_deref())?;
+    let fee = Fee::FeeRate(FeeRate::from_sat_per_vb(params.fee_rate_sats_vb));
+
+    spawn_blocking(move || {
+        let wallet = state.node.inner.ldk_wallet();
+
+        let (txid, preview) = if params.dry_run {
+            let preview = wallet
+                .preview_batch_send(&recipients, fee, utxos.as_deref())
+                .map_err(|e| AppError::BadRequest(format!("Failed to build batch: {e:#}")))?;
+
+            (None, preview)
+        } else {
+            let (txid, preview) = wallet
+                .send_batch(&recipients, fee, utxos.as_deref())
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to send batch: {e:#}"))
+                })?;
+
+            (Some(txid.to_string()), preview)
+        };
+
+        Ok(Json(BatchWithdrawalResponse {
+            txid,
+            amount_sats: preview.amount_sats,
+            fee_sats: preview.fee_sats,
+            utxos: preview
+                .inputs
+                .iter()
+                .map(|outpoint| outpoint.to_string())
+                .collect(),
+        }))
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to send batch: {e:#}")))?
+}
+
+#[derive(Debug, Serialize)]
+pub struct PsbtResponse {
+    pub txid: String,
+    /// The base64 encoded PSBT.
+    pub psbt: String,
+}
+
+/// Build an unsigned PSBT for a withdrawal, to be signed by an external signer.
+#[instrument(skip_all, err(Debug))]
+pub async fn create_psbt(
+    State(state): State<Arc<AppState>>,
+    Json(params): Json<SendOnChainParams>,
+) -> Result<Json<PsbtResponse>, AppError> {
+    let (address, fee, utxos) = parse_send_on_chain_params(&params)?;
+
+    spawn_blocking(move || {
+        let psbt = state
+            .node
+            .inner
+            .ldk_wallet()
+            .create_unsigned_psbt(&address, params.amount_sats, fee, utxos)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to create PSBT: {e:#}")))?;
+
+        Ok(Json(PsbtResponse {
+            txid: psbt.unsigned_tx.txid().to_string(),
+            psbt: psbt.to_string(),
+        }))
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to create PSBT: {e:#}")))?
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BroadcastPsbtParams {
+    /// The base64 encoded PSBT, signed by the external signer.
+    pub psbt: String,
+}
+
+/// Finalize and broadcast a PSBT which has been signed by an external signer.
+#[instrument(skip_all, err(Debug))]
+pub async fn broadcast_psbt(
+    State(state): State<Arc<AppState>>,
+    Json(params): Json<BroadcastPsbtParams>,
+) -> Result<Json<SendOnChainResponse>, AppError> {
+    let psbt = PartiallySignedTransaction::from_str(&params.psbt)
+        .map_err(|e| AppError::BadRequest(format!("Invalid PSBT provided: {e:#}")))?;
+
+    spawn_blocking(move || {
+        let txid = state
+            .node
+            .inner
+            .ldk_wallet()
+            .broadcast_psbt(psbt)
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to broadcast PSBT: {e:#}"))
+            })?;
+
+        Ok(Json(SendOnChainResponse {
+            txid: txid.to_string(),
+        }))
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to broadcast PSBT: {e:#}")))?
+}
+
+fn parse_send_on_chain_params(
+    params: &SendOnChainParams,
+) -> Result<(Address, Fee, Option<Vec<OutPoint>>), AppError> {
+    let address = Address::from_str(&params.address)
+        .map_err(|e| AppError::BadRequest(format!("Invalid address provided: {e:#}")))?;
+    let utxos = parse_outpoints(params.utxos.as_deref())?;
+    let fee = Fee::FeeRate(FeeRate::from_sat_per_vb(params.fee_rate_sats_vb));
+
+    Ok((address, fee, utxos))
+}
+
+fn parse_outpoints(utxos: Option<&[String]>) -> Result<Option<Vec<OutPoint>>, AppError> {
+    utxos
+        .map(|utxos| {
+            utxos
+                .iter()
+                .map(|outpoint| OutPoint::from_str(outpoint))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(|e| AppError::BadRequest(format!("Invalid outpoint provided: {e:#}")))
+}
+
 #[derive(Serialize)]
 pub struct ChannelDetails {
     #[serde(flatten)]
@@ -313,6 +660,18 @@ pub async fn list_dlc_channels(
     Ok(Json(dlc_channels))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/admin/channels/revert",
+    tag = "collaborative revert",
+    security(("admin_token" = [])),
+    request_body = CollaborativeRevertCoordinatorRequest,
+    responses(
+        (status = 200, description = "The collaborative revert has been proposed"),
+        (status = 400, description = "The channel id is invalid", body = ErrorResponse),
+        (status = 401, description = "The admin token is missing or wrong"),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn collaborative_revert(
     State(state): State<Arc<AppState>>,
@@ -341,6 +700,107 @@ pub async fn collaborative_revert(
     Ok(())
 }
 
+/// How many collaborative reverts of a batch are proposed at the same time.
+const COLLABORATIVE_REVERT_BATCH_CONCURRENCY: usize = 10;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CollaborativeRevertResult {
+    pub channel_id: String,
+    /// The reason why the collaborative revert could not be proposed. `None` on success.
+    pub error: Option<String>,
+}
+
+/// Propose a collaborative revert for every given channel, using the same fee rate and price.
+///
+/// A failure to propose the revert of one channel does not affect the other channels. Instead, the
+/// result is reported per channel.
+#[utoipa::path(
+    post,
+    path = "/api/admin/collaborative_revert/batch",
+    tag = "collaborative revert",
+    security(("admin_token" = [])),
+    request_body = CollaborativeRevertCoordinatorBatchRequest,
+    responses(
+        (status = 200, description = "The result per channel", body = [CollaborativeRevertResult]),
+        (status = 400, description = "No or duplicate channels provided", body = ErrorResponse),
+        (status = 401, description = "The admin token is missing or wrong"),
+    )
+)]
+#[instrument(skip_all, err(Debug))]
+pub async fn collaborative_revert_batch(
+    State(state): State<Arc<AppState>>,
+    Json(revert_params): Json<CollaborativeRevertCoordinatorBatchRequest>,
+) -> Result<Json<Vec<CollaborativeRevertResult>>, AppError> {
+    if revert_params.channels.is_empty() {
+        return Err(AppError::BadRequest("No channels provided".to_string()));
+    }
+
+    let mut channel_ids = HashSet::new();
+    for channel in revert_params.channels.iter() {
+        if !channel_ids.insert(channel.channel_id.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Channel {} provided more than once",
+                channel.channel_id
+            )));
+        }
+    }
+
+    let fee_rate_sats_vb = revert_params.fee_rate_sats_vb;
+    let price = revert_params.price;
+
+    let results = stream::iter(revert_params.channels)
+        .map(|channel| {
+            let state = state.clone();
+            async move {
+                let channel_id_hex = channel.channel_id;
+
+                let result = async {
+                    let channel_id = parse_dlc_channel_id(channel_id_hex.as_str())
+                        .context("Invalid channel ID provided")?;
+
+                    collaborative_revert::propose_collaborative_revert(
+                        state.node.inner.clone(),
+                        state.pool.clone(),
+                        state.auth_users_notifier.clone(),
+                        channel_id,
+                        fee_rate_sats_vb,
+                        channel.counter_payout,
+                        price,
+                    )
+                    .await
+                }
+                .await;
+
+                let error = match result {
+                    Ok(()) => {
+                        tracing::info!(
+                            channel_id = channel_id_hex,
+                            "Proposed collaborative revert"
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            channel_id = channel_id_hex,
+                            "Could not collaboratively revert channel: {e:#}"
+                        );
+                        Some(format!("{e:#}"))
+                    }
+                };
+
+                CollaborativeRevertResult {
+                    channel_id: channel_id_hex,
+                    error,
+                }
+            }
+        })
+        .buffer_unordered(COLLABORATIVE_REVERT_BATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    Ok(Json(results))
+}
+
 #[instrument(skip_all, err(Debug))]
 pub async fn list_on_chain_transactions(
     State(state): State<Arc<AppState>>,
@@ -355,6 +815,116 @@ pub async fn list_on_chain_transactions(
     .map_err(|e| AppError::InternalServerError(format!("Failed to list transactions: {e:#}")))?
 }
 
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BumpFeeMethod {
+    /// Replace the transaction. Rejected for channel funding transactions, as it changes the txid.
+    Rbf,
+    /// Spend one of our outputs of the transaction with a higher fee.
+    #[default]
+    Cpfp,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BumpFeeParams {
+    pub fee_rate_sats_vb: f32,
+    #[serde(default)]
+    pub method: BumpFeeMethod,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BumpFeeResponse {
+    /// The txid of the replacement transaction for RBF or of the child transaction for CPFP.
+    pub txid: String,
+}
+
+#[instrument(skip_all, err(Debug))]
+pub async fn bump_fee(
+    Path(txid): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Json(params): Json<BumpFeeParams>,
+) -> Result<Json<BumpFeeResponse>, AppError> {
+    let txid = Txid::from_str(&txid)
+        .map_err(|e| AppError::BadRequest(format!("Invalid txid provided: {e:#}")))?;
+    let fee_rate = FeeRate::from_sat_per_vb(params.fee_rate_sats_vb);
+
+    spawn_blocking(move || {
+        let wallet = state.node.inner.ldk_wallet();
+
+        let new_txid = match params.method {
+            BumpFeeMethod::Rbf => wallet.bump_fee(txid, fee_rate),
+            BumpFeeMethod::Cpfp => {
+                let utxo = wallet
+                    .get_utxos()
+                    .map_err(|e| {
+                        AppError::InternalServerError(format!("Failed to retrieve UTXOs: {e:#}"))
+                    })?
+                    .into_iter()
+                    .find(|utxo| utxo.outpoint.txid == txid && !utxo.is_spent)
+                    .ok_or_else(|| {
+                        AppError::BadRequest(format!(
+                            "Transaction {txid} has no unspent output of ours"
+                        ))
+                    })?;
+
+                wallet.cpfp(utxo.outpoint, fee_rate)
+            }
+        }
+        .map_err(|e| AppError::InternalServerError(format!("Failed to bump fee: {e:#}")))?;
+
+        Ok(Json(BumpFeeResponse {
+            txid: new_txid.to_string(),
+        }))
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to bump fee: {e:#}")))?
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulatePayoutParams {
+    pub quantity: f32,
+    /// The leverage of the trader.
+    pub leverage: f32,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub entry_price: Decimal,
+    /// The direction of the trader.
+    pub direction: Direction,
+    /// Defaults to the leverage the coordinator currently uses for every trade.
+    pub coordinator_leverage: Option<f32>,
+    /// Defaults to the payout curve parameters currently configured in the settings. Allows
+    /// previewing the effect of different parameters before changing the settings.
+    pub payout_curve_params: Option<PayoutCurveParams>,
+}
+
+pub async fn simulate_payout(
+    State(state): State<Arc<AppState>>,
+    Json(params): Json<SimulatePayoutParams>,
+) -> Result<Json<SimulatedPayoutCurve>, AppError> {
+    let (payout_curve_params, taker_fee_rate) = {
+        let settings = state.settings.read().await;
+        (
+            params
+                .payout_curve_params
+                .unwrap_or(settings.payout_curve_params),
+            settings.fee_schedule.taker_fee_rate,
+        )
+    };
+
+    let curve = payout_curve::simulate_payout_curve(
+        params.entry_price,
+        params.quantity,
+        params.leverage,
+        params.coordinator_leverage.unwrap_or(COORDINATOR_LEVERAGE),
+        params.direction,
+        ContractSymbol::BtcUsd,
+        &payout_curve_params,
+        taker_fee_rate,
+    )
+    .map_err(|e| AppError::BadRequest(format!("Failed to simulate payout curve: {e:#}")))?;
+
+    Ok(Json(curve))
+}
+
 pub async fn list_peers(State(state): State<Arc<AppState>>) -> Json<Vec<PublicKey>> {
     let peers = state.node.inner.list_peers();
     Json(peers)
@@ -416,6 +986,14 @@ pub async fn open_channel(
 
     let channel_amount = channel_params.local_balance;
     let initial_send_amount = channel_params.remote_balance.unwrap_or_default();
+    state
+        .settings
+        .read()
+        .await
+        .jit_channel
+        .check_channel_size(channel_amount)
+        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
     let mut pending_channel_opening = state.node.inner.pending_channel_opening_fee_rates.lock();
     if let Some(fee_rate) = channel_params.sats_vbyte {
         pending_channel_opening.insert(pubkey, FeeRate::from_sat_per_vb(fee_rate));
@@ -443,10 +1021,11 @@ pub async fn send_payment(
     let invoice = Bolt11Invoice::from_str(invoice.as_str())
         .context("Could not parse Invoice string")
         .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+    let payment_config = state.settings.read().await.payment;
     state
         .node
         .inner
-        .pay_invoice(&invoice, None)
+        .pay_invoice(&invoice, None, payment_config)
         .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?;
 
     Ok(())
@@ -508,3 +1087,280 @@ pub async fn is_connected(
     })?;
     Ok(Json(state.node.is_connected(&target)))
 }
+
+#[derive(Serialize, ToSchema)]
+pub struct TradingHaltStatus {
+    pub halted: bool,
+    pub reason: Option<HaltReason>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/halt_trading",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Whether trading is halted", body = TradingHaltStatus),
+        (status = 401, description = "The admin token is missing or wrong"),
+    )
+)]
+pub async fn get_trading_halt(State(state): State<Arc<AppState>>) -> Json<TradingHaltStatus> {
+    let reason = state.trading_halt.get();
+
+    Json(TradingHaltStatus {
+        halted: reason.is_some(),
+        reason,
+    })
+}
+
+/// Emergency stop: reject all new market orders until trading is resumed.
+#[utoipa::path(
+    post,
+    path = "/api/admin/halt_trading",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Trading has been halted"),
+        (status = 401, description = "The admin token is missing or wrong"),
+    )
+)]
+pub async fn halt_trading(State(state): State<Arc<AppState>>) {
+    state.trading_halt.halt(HaltReason::Manual);
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/resume_trading",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Trading has been resumed"),
+        (status = 401, description = "The admin token is missing or wrong"),
+    )
+)]
+pub async fn resume_trading(State(state): State<Arc<AppState>>) {
+    state.trading_halt.resume();
+}
+
+/// Rollovers of the current rollover window which are still pending or have failed.
+pub async fn list_rollovers(State(state): State<Arc<AppState>>) -> Json<Vec<ScheduledRollover>> {
+    Json(state.rollover_scheduler.get_rollovers())
+}
+
+/// The account statement of the given trader, e.g. for accounting.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_user_statement(
+    Path(trader_pubkey): Path<String>,
+    Query(params): Query<StatementParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    statement_response(state, trader_pubkey, params).await
+}
+
+/// The keys of the backups the coordinator holds for the given trader.
+#[instrument(skip_all, err(Debug))]
+pub async fn list_user_backup_keys(
+    Path(trader_pubkey): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    let keys =
+        state.user_backup.keys(trader_pubkey).await.map_err(|e| {
+            AppError::InternalServerError(format!("Failed to load user backup: {e:#}"))
+        })?;
+
+    Ok(Json(keys))
+}
+
+/// Delete all the backups the coordinator holds for the given trader.
+///
+/// The app uploads a full backup again on its next start.
+#[instrument(skip_all, err(Debug))]
+pub async fn delete_user_backup(
+    Path(trader_pubkey): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    state
+        .user_backup
+        .delete_all(trader_pubkey)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to delete user backup: {e:#}")))
+}
+
+/// Trade executions which have failed permanently, i.e. which will not be retried anymore.
+#[instrument(skip_all, err(Debug))]
+pub async fn list_failed_trade_executions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TradeExecution>>, AppError> {
+    let executions = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        db::trade_executions::get_by_state(&mut conn, TradeExecutionState::Failed)
+    })
+    .await?
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to load failed trade executions: {e:#}"))
+    })?;
+
+    Ok(Json(executions))
+}
+
+/// Rebuild the DLC of a trade from the inputs recorded when it was executed and compare it with
+/// the DLC which was actually signed.
+#[instrument(skip_all, err(Debug))]
+pub async fn replay_trade(
+    Path(trade_id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TradeReplay>, AppError> {
+    let replay = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        let recorded = db::trade_inputs::get_by_trade_id(&mut conn, trade_id)?
+            .with_context(|| format!("No inputs recorded for trade {trade_id}"))?;
+
+        let contract = state
+            .node
+            .inner
+            .get_contract_by_temporary_id(&recorded.temporary_contract_id)?;
+
+        replay::replay_trade(
+            trade_id,
+            recorded.inputs,
+            recorded.temporary_contract_id,
+            contract.as_ref(),
+        )
+    })
+    .await?
+    .map_err(|e| AppError::BadRequest(format!("Failed to replay trade {trade_id}: {e:#}")))?;
+
+    Ok(Json(replay))
+}
+
+/// Let the trader's open position expire now and close all expired positions.
+///
+/// Only available on test networks, so that tests don't have to wait for the weekly expiry.
+#[instrument(skip_all, err(Debug))]
+pub async fn expire_position(
+    Path(trader_pubkey): Path<PublicKey>,
+    State(state): State<Arc<AppState>>,
+) -> Result<(), AppError> {
+    if state.node.inner.network == Network::Bitcoin {
+        return Err(AppError::BadRequest(
+            "Positions can't be expired on mainnet".to_string(),
+        ));
+    }
+
+    let now = state.clock.now();
+    spawn_blocking({
+        let pool = state.pool.clone();
+        move || {
+            let mut conn = pool.get()?;
+            db::positions::Position::set_expiry_of_open_position(
+                &mut conn,
+                trader_pubkey.to_string(),
+                now,
+            )
+        }
+    })
+    .await?
+    .map_err(|e| AppError::BadRequest(format!("Failed to expire position: {e:#}")))?;
+
+    tracing::info!(%trader_pubkey, "Expired position");
+
+    expired_positions::close(state.node.clone(), state.trading_sender.clone())
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to close expired positions: {e:#}"))
+        })?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct ClockStatus {
+    #[serde(with = "time::serde::rfc3339")]
+    pub now: OffsetDateTime,
+    /// How far the clock has been fast-forwarded, in seconds.
+    pub offset_seconds: i64,
+}
+
+#[derive(Deserialize)]
+pub struct AdvanceClock {
+    pub seconds: u32,
+}
+
+impl ClockStatus {
+    fn new(clock: &AdjustableClock) -> Self {
+        Self {
+            now: clock.now(),
+            offset_seconds: clock.offset().whole_seconds(),
+        }
+    }
+}
+
+/// The time the coordinator bases expiries and rollovers on.
+#[instrument(skip_all)]
+pub async fn get_clock(State(state): State<Arc<AppState>>) -> Json<ClockStatus> {
+    Json(ClockStatus::new(&state.clock))
+}
+
+/// Fast-forward the time the coordinator bases expiries and rollovers on.
+///
+/// Only available on test networks. Time can't be turned back, short of restarting the
+/// coordinator.
+#[instrument(skip_all, err(Debug))]
+pub async fn advance_clock(
+    State(state): State<Arc<AppState>>,
+    Json(advance): Json<AdvanceClock>,
+) -> Result<Json<ClockStatus>, AppError> {
+    if state.node.inner.network == Network::Bitcoin {
+        return Err(AppError::BadRequest(
+            "The clock can't be advanced on mainnet".to_string(),
+        ));
+    }
+
+    state
+        .clock
+        .advance(time::Duration::seconds(advance.seconds.into()));
+
+    let status = ClockStatus::new(&state.clock);
+    tracing::info!(now = %status.now, offset_seconds = status.offset_seconds, "Advanced clock");
+
+    Ok(Json(status))
+}
+
+/// The attestation status of every contract which has expired.
+#[instrument(skip_all, err(Debug))]
+pub async fn list_attestations(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Attestation>>, AppError> {
+    let attestations = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        db::attestations::get_all(&mut conn)
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load attestations: {e:#}")))?;
+
+    Ok(Json(attestations))
+}
+
+/// The traders who are connected to the orderbook websocket of any coordinator.
+#[instrument(skip_all, err(Debug))]
+pub async fn list_online_users(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<UserPresence>>, AppError> {
+    let users = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        db::user_presence::get_online(&mut conn, OffsetDateTime::now_utc())
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load online users: {e:#}")))?;
+
+    Ok(Json(users))
+}
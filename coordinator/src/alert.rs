@@ -0,0 +1,47 @@
+use serde_json::json;
+use std::time::Duration;
+
+/// How long we wait for the alert webhook to respond before giving up.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Raises alerts for the operators of the coordinator, about problems which need a human to look
+/// at them.
+///
+/// Every alert is logged, and posted to the alert webhook if one is configured.
+#[derive(Clone)]
+pub struct Alerter {
+    client: reqwest::Client,
+    webhook: Option<String>,
+}
+
+impl Alerter {
+    pub fn new(webhook: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook,
+        }
+    }
+
+    pub async fn alert(&self, message: &str) {
+        tracing::error!(target: "alert", "{message}");
+
+        let webhook = match &self.webhook {
+            Some(webhook) => webhook,
+            None => return,
+        };
+
+        let result = self
+            .client
+            .post(webhook)
+            .timeout(WEBHOOK_TIMEOUT)
+            .json(&json!({ "text": message }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        // The URL of the webhook is a secret, hence it must not end up in the logs.
+        if let Err(e) = result {
+            tracing::warn!("Failed to post alert to webhook: {:#}", e.without_url());
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::db;
+use crate::routes::AppState;
+use crate::AppError;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
+use axum::Json;
+use bitcoin::secp256k1::PublicKey;
+use commons::ApiKeyCreated;
+use commons::ApiKeyScope;
+use commons::CreateApiKey;
+use commons::RevokeApiKey;
+use rand::RngCore;
+use sha2::digest::FixedOutput;
+use sha2::Digest;
+use sha2::Sha256;
+use std::sync::Arc;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Prefix of all API keys, so that they can be recognised e.g. by secret scanners.
+const API_KEY_PREFIX: &str = "10101_";
+
+/// How long a signed request to issue an API key is valid.
+const CREATE_API_KEY_VALIDITY: Duration = Duration::minutes(5);
+
+/// Issue an API key for the trader who signed the request.
+#[instrument(skip_all, err(Debug))]
+pub async fn post_api_key(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateApiKey>,
+) -> Result<Json<ApiKeyCreated>, AppError> {
+    request.verify().map_err(|_| AppError::Unauthorized)?;
+
+    let age = OffsetDateTime::now_utc() - request.timestamp;
+    if age.abs() > CREATE_API_KEY_VALIDITY {
+        return Err(AppError::BadRequest(
+            "Request to issue an API key has expired".to_string(),
+        ));
+    }
+
+    if request.scopes.is_empty() {
+        return Err(AppError::BadRequest(
+            "An API key needs at least one scope".to_string(),
+        ));
+    }
+
+    let trader_pubkey = request.signature.pubkey;
+    let id = Uuid::new_v4();
+    let key = generate_api_key();
+
+    spawn_blocking({
+        let key_hash = hash_api_key(&key);
+        let scopes = request.scopes.clone();
+        move || {
+            let mut conn = state.pool.get()?;
+            db::api_keys::insert(
+                &mut conn,
+                id,
+                trader_pubkey,
+                request.label,
+                key_hash,
+                &scopes,
+            )
+        }
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to issue API key: {e:#}")))?;
+
+    tracing::info!(%trader_pubkey, %id, scopes = ?request.scopes, "Issued API key");
+
+    Ok(Json(ApiKeyCreated {
+        id,
+        key,
+        scopes: request.scopes,
+    }))
+}
+
+/// Revoke an API key of the trader who signed the request.
+#[instrument(skip_all, err(Debug))]
+pub async fn delete_api_key(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RevokeApiKey>,
+) -> Result<(), AppError> {
+    if request.id != id {
+        return Err(AppError::BadRequest(
+            "API key id does not match revocation request".to_string(),
+        ));
+    }
+
+    request.verify().map_err(|_| AppError::Unauthorized)?;
+
+    let trader_pubkey = request.signature.pubkey;
+    let revoked = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        db::api_keys::revoke(&mut conn, id, trader_pubkey)
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to revoke API key: {e:#}")))?;
+
+    if !revoked {
+        return Err(AppError::BadRequest(format!("Unknown API key {id}")));
+    }
+
+    tracing::info!(%trader_pubkey, %id, "Revoked API key");
+
+    Ok(())
+}
+
+/// Authenticate a request by the API key passed as bearer token.
+///
+/// Returns the pubkey of the trader the key is bound to, or `None` if the request does not carry
+/// an API key. Fails if the key is unknown, revoked or lacks the required `scope`.
+pub async fn authenticate(
+    state: &Arc<AppState>,
+    headers: &HeaderMap,
+    scope: ApiKeyScope,
+) -> Result<Option<PublicKey>, AppError> {
+    let key = match headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .filter(|key| key.starts_with(API_KEY_PREFIX))
+    {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+
+    let key_hash = hash_api_key(key);
+    let api_key = spawn_blocking({
+        let pool = state.pool.clone();
+        move || {
+            let mut conn = pool.get()?;
+            db::api_keys::get_active_by_key_hash(&mut conn, &key_hash)
+        }
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load API key: {e:#}")))?
+    .ok_or(AppError::Unauthorized)?;
+
+    if !api_key.scopes.contains(&scope) {
+        tracing::debug!(id = %api_key.id, ?scope, "API key lacks the required scope");
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(Some(api_key.trader_pubkey))
+}
+
+fn generate_api_key() -> String {
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    format!("{API_KEY_PREFIX}{}", hex::encode(secret))
+}
+
+/// API keys are random, hence a plain hash suffices to not store them in the clear.
+fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::new().chain_update(key).finalize_fixed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_api_keys_are_unique_and_prefixed() {
+        let key = generate_api_key();
+        let other_key = generate_api_key();
+
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_ne!(key, other_key);
+        assert_ne!(hash_api_key(&key), hash_api_key(&other_key));
+        assert_eq!(hash_api_key(&key), hash_api_key(&key));
+    }
+}
@@ -0,0 +1,97 @@
+use axum::http::header::HeaderName;
+use axum::http::header::LINK;
+use axum::http::HeaderValue;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use commons::ApiVersion;
+
+/// When the unversioned paths of the public API (`/api/...`) will stop being served, as an HTTP
+/// date.
+pub const UNVERSIONED_API_SUNSET: &str = "Mon, 01 Jul 2024 00:00:00 GMT";
+
+/// Mark the response of an endpoint as deprecated.
+///
+/// Following RFC 8594, the response carries the date from which on the endpoint will not be served
+/// anymore in the `Sunset` header, and points to the endpoint replacing it in the `Link` header.
+pub fn deprecate(response: &mut Response, sunset: &'static str, successor: &str) {
+    let headers = response.headers_mut();
+
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static("true"),
+    );
+    headers.insert(
+        HeaderName::from_static("sunset"),
+        HeaderValue::from_static(sunset),
+    );
+
+    match HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\"")) {
+        Ok(link) => {
+            headers.insert(LINK, link);
+        }
+        Err(e) => {
+            tracing::warn!(
+                successor,
+                "Could not link to successor of deprecated endpoint: {e:#}"
+            );
+        }
+    }
+}
+
+/// Middleware deprecating the unversioned paths of the public API in favour of the latest version.
+///
+/// The middleware has to be applied to the routes nested under `/api`, i.e. it sees the paths
+/// without that prefix.
+pub async fn deprecate_unversioned<B>(request: Request<B>, next: Next<B>) -> Response {
+    let successor = ApiVersion::LATEST.path(request.uri().path());
+
+    let mut response = next.run(request).await;
+    deprecate(&mut response, UNVERSIONED_API_SUNSET, &successor);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn unversioned_paths_are_deprecated() {
+        let api = Router::new().route("/version", get(|| async { StatusCode::OK.into_response() }));
+        let app = Router::new()
+            .nest(ApiVersion::V1.path_prefix(), api.clone())
+            .nest(
+                "/api",
+                api.layer(axum::middleware::from_fn(deprecate_unversioned)),
+            );
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/api/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(response.headers()["sunset"], UNVERSIONED_API_SUNSET);
+        assert_eq!(
+            response.headers()[LINK],
+            "</api/v1/version>; rel=\"successor-version\""
+        );
+
+        let response = app
+            .oneshot(Request::get("/api/v1/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("deprecation").is_none());
+    }
+}
@@ -1,12 +1,40 @@
+use crate::config::S3Bucket;
 use anyhow::Result;
+use async_trait::async_trait;
 use bitcoin::secp256k1::PublicKey;
 use commons::Backup;
+use commons::Checksum;
 use commons::DeleteBackup;
 use commons::Restore;
+use futures::TryStreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path;
+use object_store::ObjectStore;
 use sled::Db;
+use std::sync::Arc;
 
 const BACKUPS_DIRECTORY: &str = "user_backups";
 
+/// Keeps the encrypted backups of the users.
+#[async_trait]
+pub trait UserBackupStore: Send + Sync {
+    async fn restore(&self, node_id: PublicKey) -> Result<Vec<Restore>>;
+
+    async fn back_up(&self, node_id: PublicKey, backup: Backup) -> Result<()> {
+        self.back_up_all(node_id, vec![backup]).await
+    }
+
+    async fn back_up_all(&self, node_id: PublicKey, backups: Vec<Backup>) -> Result<()>;
+
+    /// The keys of all the backed up entries of the given user.
+    async fn keys(&self, node_id: PublicKey) -> Result<Vec<String>>;
+
+    /// Delete all the backups of the given user, e.g. after losing the backups of a user.
+    async fn delete_all(&self, node_id: PublicKey) -> Result<()>;
+
+    async fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()>;
+}
+
 /// Holds the user backups in a sled database
 ///
 /// TODO(holzeis): This is fine for now, once we grow we should consider moving that into a dedicate
@@ -21,35 +49,298 @@ impl SledBackup {
             db: sled::open(format!("{data_dir}/{BACKUPS_DIRECTORY}")).expect("valid path"),
         }
     }
+}
 
-    pub fn restore(&self, node_id: PublicKey) -> Result<Vec<Restore>> {
+#[async_trait]
+impl UserBackupStore for SledBackup {
+    async fn restore(&self, node_id: PublicKey) -> Result<Vec<Restore>> {
         tracing::debug!(%node_id, "Restoring backup");
         let tree = self.db.open_tree(node_id.to_string())?;
+        let checksums = self.db.open_tree(checksums_tree(node_id))?;
 
         let mut backup = vec![];
         for entry in tree.into_iter() {
-            let entry = entry?;
-            let key = String::from_utf8(entry.0.to_vec())?;
-            let value = entry.1.to_vec();
-            backup.push(Restore { key, value });
+            let (key, value) = entry?;
+            let checksum: Option<Checksum> = checksums
+                .get(&key)?
+                .map(|checksum| checksum.as_ref().try_into())
+                .transpose()?;
+            let key = String::from_utf8(key.to_vec())?;
+            let value = value.to_vec();
+            backup.push(Restore {
+                key,
+                value,
+                checksum,
+            });
         }
 
         Ok(backup)
     }
 
-    pub async fn back_up(&self, node_id: PublicKey, backup: Backup) -> Result<()> {
-        tracing::debug!(%node_id, backup.key, "Create user backup");
+    /// Backs up all the given values at once, flushing the database only once.
+    async fn back_up_all(&self, node_id: PublicKey, backups: Vec<Backup>) -> Result<()> {
         let tree = self.db.open_tree(node_id.to_string())?;
-        tree.insert(backup.key, backup.value)?;
+        let checksums = self.db.open_tree(checksums_tree(node_id))?;
+
+        for backup in backups {
+            tracing::debug!(%node_id, backup.key, "Create user backup");
+
+            // The checksum is kept next to the value, so that the user can tell whether the value
+            // has been altered since it has been backed up.
+            match backup.checksum {
+                Some(checksum) => checksums.insert(&backup.key, checksum.as_slice())?,
+                None => checksums.remove(&backup.key)?,
+            };
+            tree.insert(backup.key, backup.value)?;
+        }
+
+        checksums.flush()?;
         tree.flush()?;
         Ok(())
     }
 
-    pub fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()> {
+    async fn keys(&self, node_id: PublicKey) -> Result<Vec<String>> {
+        let tree = self.db.open_tree(node_id.to_string())?;
+
+        let mut keys = vec![];
+        for key in tree.iter().keys() {
+            keys.push(String::from_utf8(key?.to_vec())?);
+        }
+
+        Ok(keys)
+    }
+
+    async fn delete_all(&self, node_id: PublicKey) -> Result<()> {
+        tracing::warn!(%node_id, "Deleting all user backups");
+        self.db.drop_tree(node_id.to_string())?;
+        self.db.drop_tree(checksums_tree(node_id))?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    async fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()> {
         tracing::debug!(%node_id, key=backup.key, "Deleting user backup");
         let tree = self.db.open_tree(node_id.to_string())?;
-        tree.remove(backup.key)?;
+        let checksums = self.db.open_tree(checksums_tree(node_id))?;
+        tree.remove(&backup.key)?;
+        checksums.remove(&backup.key)?;
         tree.flush()?;
+        checksums.flush()?;
         Ok(())
     }
 }
+
+fn checksums_tree(node_id: PublicKey) -> String {
+    format!("{node_id}/checksums")
+}
+
+/// Holds the user backups in an S3 compatible object store.
+///
+/// The objects are named `user_backups/<node id>/values/<key>`, their checksums are kept in
+/// `user_backups/<node id>/checksums/<key>`.
+pub struct ObjectStoreBackup {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl ObjectStoreBackup {
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self { store }
+    }
+
+    /// Connects to the given bucket. The credentials are taken from the `AWS_ACCESS_KEY_ID` and
+    /// `AWS_SECRET_ACCESS_KEY` environment variables.
+    pub fn s3(bucket: &S3Bucket) -> Result<Self> {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&bucket.name)
+            .with_region(&bucket.region);
+
+        if let Some(endpoint) = &bucket.endpoint {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+
+        Ok(Self::new(Arc::new(builder.build()?)))
+    }
+
+    async fn list(&self, prefix: &Path) -> Result<Vec<Path>> {
+        let paths = self
+            .store
+            .list(Some(prefix))
+            .await?
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await?;
+
+        Ok(paths)
+    }
+
+    async fn get(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        match self.store.get(path).await {
+            Ok(result) => Ok(Some(result.bytes().await?.to_vec())),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn remove(&self, path: &Path) -> Result<()> {
+        match self.store.delete(path).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl UserBackupStore for ObjectStoreBackup {
+    async fn restore(&self, node_id: PublicKey) -> Result<Vec<Restore>> {
+        tracing::debug!(%node_id, "Restoring backup");
+
+        let mut backup = vec![];
+        for key in self.keys(node_id).await? {
+            let value = match self.get(&value_path(node_id, &key)).await? {
+                Some(value) => value,
+                // The value has been deleted in the meantime.
+                None => continue,
+            };
+            let checksum: Option<Checksum> = self
+                .get(&checksum_path(node_id, &key))
+                .await?
+                .map(|checksum| checksum.as_slice().try_into())
+                .transpose()?;
+
+            backup.push(Restore {
+                key,
+                value,
+                checksum,
+            });
+        }
+
+        Ok(backup)
+    }
+
+    async fn back_up_all(&self, node_id: PublicKey, backups: Vec<Backup>) -> Result<()> {
+        for backup in backups {
+            tracing::debug!(%node_id, backup.key, "Create user backup");
+
+            let checksum_path = checksum_path(node_id, &backup.key);
+            match backup.checksum {
+                Some(checksum) => {
+                    self.store
+                        .put(&checksum_path, checksum.to_vec().into())
+                        .await?;
+                }
+                None => self.remove(&checksum_path).await?,
+            }
+
+            self.store
+                .put(&value_path(node_id, &backup.key), backup.value.into())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn keys(&self, node_id: PublicKey) -> Result<Vec<String>> {
+        let prefix = Path::from(format!("{BACKUPS_DIRECTORY}/{node_id}/values"));
+
+        let keys = self
+            .list(&prefix)
+            .await?
+            .into_iter()
+            .filter_map(|path| {
+                path.prefix_match(&prefix)
+                    .map(|parts| parts.map(|part| part.as_ref().to_string()))
+                    .map(|parts| parts.collect::<Vec<_>>().join("/"))
+            })
+            .collect();
+
+        Ok(keys)
+    }
+
+    async fn delete_all(&self, node_id: PublicKey) -> Result<()> {
+        tracing::warn!(%node_id, "Deleting all user backups");
+
+        let prefix = Path::from(format!("{BACKUPS_DIRECTORY}/{node_id}"));
+        for path in self.list(&prefix).await? {
+            self.remove(&path).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()> {
+        tracing::debug!(%node_id, key=backup.key, "Deleting user backup");
+
+        self.remove(&value_path(node_id, &backup.key)).await?;
+        self.remove(&checksum_path(node_id, &backup.key)).await?;
+
+        Ok(())
+    }
+}
+
+fn value_path(node_id: PublicKey, key: &str) -> Path {
+    Path::from(format!("{BACKUPS_DIRECTORY}/{node_id}/values/{key}"))
+}
+
+fn checksum_path(node_id: PublicKey, key: &str) -> Path {
+    Path::from(format!("{BACKUPS_DIRECTORY}/{node_id}/checksums/{key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::secp256k1::SECP256K1;
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn object_store_backup_can_be_restored() {
+        let store = ObjectStoreBackup::new(Arc::new(InMemory::new()));
+
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+        let node_id = secret_key.public_key(SECP256K1);
+        let backup = |key: &str, value: &[u8]| Backup {
+            key: key.to_string(),
+            value: value.to_vec(),
+            checksum: Some(commons::checksum(value)),
+            signature: secret_key.sign_ecdsa(commons::create_sign_message(value.to_vec())),
+        };
+
+        store
+            .back_up_all(
+                node_id,
+                vec![
+                    backup("ln/manager", b"manager"),
+                    backup("dlc/01/abcd", b"contract"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let mut keys = store.keys(node_id).await.unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["dlc/01/abcd", "ln/manager"]);
+
+        store
+            .delete(
+                node_id,
+                DeleteBackup {
+                    key: "ln/manager".to_string(),
+                    signature: secret_key
+                        .sign_ecdsa(commons::create_sign_message(node_id.to_string().into())),
+                },
+            )
+            .await
+            .unwrap();
+
+        let restored = store.restore(node_id).await.unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].key, "dlc/01/abcd");
+        assert_eq!(restored[0].value, b"contract");
+        restored[0].verify_checksum().unwrap();
+
+        store.delete_all(node_id).await.unwrap();
+        assert!(store.restore(node_id).await.unwrap().is_empty());
+    }
+}
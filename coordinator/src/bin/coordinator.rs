@@ -1,30 +1,53 @@
 use anyhow::Context;
 use anyhow::Result;
-use bitcoin::XOnlyPublicKey;
+use commons::AdjustableClock;
+use coordinator::alert::Alerter;
+use coordinator::backup::ObjectStoreBackup;
 use coordinator::backup::SledBackup;
+use coordinator::backup::UserBackupStore;
 use coordinator::cli::Opts;
+use coordinator::config::Config;
 use coordinator::dlc_handler;
 use coordinator::dlc_handler::DlcHandler;
+use coordinator::fee_estimates::FeeEstimatesProvider;
+use coordinator::grpc;
+use coordinator::health::Health;
+use coordinator::leader::LeaderElection;
 use coordinator::logger;
 use coordinator::message::spawn_delivering_messages_to_authenticated_users;
 use coordinator::message::NewUserMessage;
 use coordinator::metrics;
 use coordinator::metrics::init_meter;
 use coordinator::node;
+use coordinator::node::attestations;
+use coordinator::node::channel_open_status;
 use coordinator::node::connection;
+use coordinator::node::execution_queue;
 use coordinator::node::expired_positions;
 use coordinator::node::rollover;
+use coordinator::node::rollover_scheduler;
+use coordinator::node::rollover_scheduler::RolloverScheduler;
 use coordinator::node::storage::NodeStorage;
+use coordinator::node::stuck_channels;
 use coordinator::node::unrealized_pnl;
 use coordinator::node::Node;
 use coordinator::notifications::NotificationService;
 use coordinator::orderbook::async_match;
 use coordinator::orderbook::collaborative_revert;
+use coordinator::orderbook::maker_notifications::MakerNotifier;
+use coordinator::orderbook::match_timeout;
 use coordinator::orderbook::trading;
+use coordinator::orderbook::trading_halt;
+use coordinator::orderbook::trading_halt::TradingHalt;
+use coordinator::pubsub;
 use coordinator::routes::router;
+use coordinator::routes::AppState;
 use coordinator::run_migration;
 use coordinator::scheduler::NotificationScheduler;
 use coordinator::settings::Settings;
+use coordinator::shutdown;
+use coordinator::shutdown::Shutdown;
+use coordinator::standby;
 use coordinator::storage::CoordinatorTenTenOneStorage;
 use diesel::r2d2;
 use diesel::r2d2::ConnectionManager;
@@ -33,19 +56,19 @@ use lightning::events::Event;
 use ln_dlc_node::node::event::NodeEventHandler;
 use ln_dlc_node::scorer;
 use ln_dlc_node::seed::Bip39Seed;
+use ln_dlc_node::ChainSourceConfig;
 use ln_dlc_node::CoordinatorEventHandler;
-use ln_dlc_node::WalletSettings;
 use rand::thread_rng;
 use rand::RngCore;
 use std::backtrace::Backtrace;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
 use std::net::SocketAddr;
-use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tokio::sync::watch;
+use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
 use tracing::metadata::LevelFilter;
 
@@ -74,12 +97,27 @@ async fn main() -> Result<()> {
     let exporter = init_meter();
 
     let opts = Opts::read();
-    let data_dir = opts.data_dir()?;
-    let address = opts.p2p_address;
-    let http_address = opts.http_address;
-    let network = opts.network();
+    let config = Config::load(&opts)?;
 
-    logger::init_tracing(LevelFilter::DEBUG, opts.json, opts.tokio_console)?;
+    if opts.print_config {
+        #[allow(clippy::print_stdout)]
+        {
+            println!("{}", config.to_redacted_toml()?);
+        }
+        return Ok(());
+    }
+
+    let data_dir = config.data_dir()?;
+    let address = config.p2p_address;
+    let http_address = config.http_address;
+    let network = config.network();
+
+    logger::init_tracing(
+        LevelFilter::DEBUG,
+        opts.json,
+        opts.tokio_console,
+        config.otlp_endpoint.as_deref(),
+    )?;
 
     let mut ephemeral_randomness = [0; 32];
     thread_rng().fill_bytes(&mut ephemeral_randomness);
@@ -99,7 +137,7 @@ async fn main() -> Result<()> {
     let settings = Settings::new(&data_dir).await?;
 
     // set up database connection pool
-    let manager = ConnectionManager::<PgConnection>::new(opts.database.clone());
+    let manager = ConnectionManager::<PgConnection>::new(config.database_url.clone());
     let pool = r2d2::Pool::builder()
         .build(manager)
         .expect("Failed to create pool.");
@@ -107,9 +145,32 @@ async fn main() -> Result<()> {
     let mut conn = pool.get()?;
     run_migration(&mut conn);
 
+    let shutdown = Shutdown::new();
+    shutdown::trigger_on_signal(shutdown.clone())?;
+
+    // Only the leader may run the node and the orderbook. Until we are the leader, we serve reads
+    // on standby.
+    let election = LeaderElection::connect(&config.database_url)?;
+    let is_leader = standby::run_until_leader(
+        &election,
+        http_address,
+        &config.database_url,
+        pool.clone(),
+        settings.clone(),
+        shutdown.clone(),
+    )
+    .await?;
+    if !is_leader {
+        tracing::info!("Coordinator stopped on standby");
+        spawn_blocking(logger::shutdown_tracing).await?;
+        return Ok(());
+    }
+    let _handle = election.monitor(shutdown.clone());
+
     let (node_event_sender, mut node_event_receiver) = watch::channel::<Option<Event>>(None);
 
-    let storage = CoordinatorTenTenOneStorage::new(data_dir.to_string_lossy().to_string());
+    let storage =
+        CoordinatorTenTenOneStorage::new(data_dir.to_string_lossy().to_string(), pool.clone())?;
 
     let node_storage = Arc::new(NodeStorage::new(pool.clone()));
 
@@ -124,21 +185,19 @@ async fn main() -> Result<()> {
         node_storage,
         address,
         SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), address.port()),
-        opts.p2p_announcement_addresses(),
-        opts.esplora.clone(),
+        config.p2p_announcement_addresses(),
+        config.chain_source_config(),
         seed,
         ephemeral_randomness,
         settings.ln_dlc.clone(),
-        WalletSettings {
-            max_allowed_tx_fee_rate_when_opening_channel: settings
-                .max_allowed_tx_fee_rate_when_opening_channel,
-            jit_channels_enabled: settings.jit_channels_enabled,
-        },
-        opts.get_oracle_infos()
+        settings.to_node_settings().to_wallet_settings(),
+        config
+            .oracles
+            .clone()
             .into_iter()
             .map(|o| o.into())
             .collect(),
-        XOnlyPublicKey::from_str(&opts.oracle_pubkey).expect("valid public key"),
+        config.default_oracle,
         node_event_handler.clone(),
     )?);
 
@@ -148,7 +207,15 @@ async fn main() -> Result<()> {
 
     let event_handler = CoordinatorEventHandler::new(node.clone(), Some(node_event_sender));
     let running = node.start(event_handler, false)?;
-    let node = Node::new(node, running, pool.clone(), settings.to_node_settings());
+    // On test networks, time can be fast-forwarded through the admin API.
+    let clock = Arc::new(AdjustableClock::new());
+    let node = Node::new(
+        node,
+        running,
+        pool.clone(),
+        settings.to_node_settings(),
+        clock.clone(),
+    );
 
     // TODO: Pass the tokio metrics into Prometheus
     if let Some(interval) = opts.tokio_metrics_interval_seconds {
@@ -165,15 +232,23 @@ async fn main() -> Result<()> {
 
     std::thread::spawn(node.inner.sync_on_chain_wallet_periodically());
 
-    tokio::spawn({
+    // DLC messages which are being processed when shutting down are processed to completion.
+    // Messages which arrive afterwards are left unacknowledged, so that our peers resend them once
+    // they reconnect.
+    let dlc_message_processing = tokio::spawn({
         let node = node.clone();
+        let shutdown = shutdown.clone();
         async move {
-            loop {
+            while !shutdown.is_triggered() {
                 let node = node.clone();
                 spawn_blocking(move || node.process_incoming_dlc_messages())
                     .await
                     .expect("To spawn blocking thread");
-                tokio::time::sleep(PROCESS_INCOMING_DLC_MESSAGES_INTERVAL).await;
+
+                tokio::select! {
+                    _ = tokio::time::sleep(PROCESS_INCOMING_DLC_MESSAGES_INTERVAL) => {}
+                    _ = shutdown.triggered() => {}
+                }
             }
         }
     });
@@ -226,7 +301,7 @@ async fn main() -> Result<()> {
 
     let (tx_price_feed, _rx) = broadcast::channel(100);
 
-    let notification_service = NotificationService::new(opts.fcm_api_key.clone());
+    let notification_service = NotificationService::new(config.fcm_api_key.clone());
 
     let (_handle, auth_users_notifier) = spawn_delivering_messages_to_authenticated_users(
         pool.clone(),
@@ -234,12 +309,41 @@ async fn main() -> Result<()> {
         tx_user_feed.clone(),
     );
 
+    let health = Health::new(
+        node.clone(),
+        pool.clone(),
+        config.chain_source_config(),
+        config.oracles.clone(),
+    );
+
+    // The fee histogram of the mempool is only available from esplora.
+    let esplora_urls = match config.chain_source_config() {
+        ChainSourceConfig::Esplora { urls } => urls,
+        ChainSourceConfig::Bitcoind { .. } => vec![],
+    };
+    let fee_estimates = FeeEstimatesProvider::new(node.clone(), esplora_urls);
+
+    let trading_halt = TradingHalt::new(tx_price_feed.clone());
+    let _handle = trading_halt::monitor(
+        pool.clone(),
+        health.clone(),
+        trading_halt.clone(),
+        node.inner.oracle_pubkey,
+    );
+
+    let maker_notifier =
+        MakerNotifier::new(auth_users_notifier.clone(), config.maker_webhooks.clone());
+
     let (_handle, trading_sender) = trading::start(
         pool.clone(),
         tx_price_feed.clone(),
         auth_users_notifier.clone(),
+        maker_notifier.clone(),
         network,
         node.inner.oracle_pubkey,
+        trading_halt.clone(),
+        config.risk_limits,
+        clock.clone(),
     );
     let _handle = async_match::monitor(
         pool.clone(),
@@ -247,6 +351,7 @@ async fn main() -> Result<()> {
         auth_users_notifier.clone(),
         network,
         node.inner.oracle_pubkey,
+        clock.clone(),
     );
     let _handle = rollover::monitor(
         pool.clone(),
@@ -255,12 +360,42 @@ async fn main() -> Result<()> {
         network,
         node.clone(),
     );
+    let shared_settings = Arc::new(RwLock::new(settings.clone()));
+    let rollover_scheduler = RolloverScheduler::new();
+    let _handle = rollover_scheduler::monitor(
+        node.clone(),
+        rollover_scheduler.clone(),
+        shared_settings.clone(),
+        network,
+    );
+    let _handle = execution_queue::monitor(node.clone(), auth_users_notifier.clone());
+    let _handle = attestations::monitor(node.clone());
+    let _handle = stuck_channels::monitor(node.clone(), Alerter::new(config.alert_webhook.clone()));
+    let _handle = channel_open_status::forward_to_traders(
+        node_event_handler.subscribe(),
+        auth_users_notifier.clone(),
+    );
+    let _handle = match_timeout::monitor(
+        pool.clone(),
+        tx_price_feed.clone(),
+        auth_users_notifier.clone(),
+        trading_sender.clone(),
+    );
     let _handle = collaborative_revert::monitor(
         pool.clone(),
         tx_user_feed.clone(),
         auth_users_notifier.clone(),
     );
 
+    // Traders may be connected to the coordinators on standby.
+    let _handle = pubsub::publish_price_feed(pool.clone(), &tx_price_feed);
+    let _handle = pubsub::serve_remote_sessions(
+        config.database_url.clone(),
+        pool.clone(),
+        tx_user_feed.clone(),
+        trading_halt.clone(),
+    );
+
     tokio::spawn({
         let node = node.clone();
         let trading_sender = trading_sender.clone();
@@ -280,25 +415,46 @@ async fn main() -> Result<()> {
         connection::keep_public_channel_peers_connected(node.inner, CONNECTION_CHECK_INTERVAL)
     });
 
-    let user_backup = SledBackup::new(data_dir.to_string_lossy().to_string());
+    let user_backup: Arc<dyn UserBackupStore> = match &config.user_backup_bucket {
+        Some(bucket) => {
+            tracing::info!(bucket = bucket.name, "Keeping user backups in S3 bucket");
+            Arc::new(ObjectStoreBackup::s3(bucket)?)
+        }
+        None => Arc::new(SledBackup::new(data_dir.to_string_lossy().to_string())),
+    };
 
-    let app = router(
-        node.clone(),
-        pool.clone(),
-        settings.clone(),
-        exporter,
-        opts.p2p_announcement_addresses(),
-        NODE_ALIAS,
-        trading_sender,
+    let app_state = Arc::new(AppState {
+        node: node.clone(),
+        pool: pool.clone(),
+        settings: shared_settings,
         tx_price_feed,
         tx_user_feed,
-        auth_users_notifier.clone(),
+        trading_sender: trading_sender.clone(),
+        exporter,
+        announcement_addresses: config.p2p_announcement_addresses(),
+        node_alias: NODE_ALIAS.to_string(),
+        auth_users_notifier: auth_users_notifier.clone(),
+        maker_notifier,
+        fee_estimates,
         user_backup,
-    );
+        health,
+        trading_halt,
+        rollover_scheduler,
+        shutdown: shutdown.clone(),
+        clock,
+    });
+
+    let app = router(app_state.clone());
+
+    let grpc_server = config.grpc_address.map(|grpc_address| {
+        tracing::debug!("Listening for gRPC on http://{grpc_address}");
+
+        tokio::spawn(grpc::serve(app_state, grpc_address))
+    });
 
     let sender = notification_service.get_sender();
     let notification_scheduler =
-        NotificationScheduler::new(sender, settings, network, node, auth_users_notifier);
+        NotificationScheduler::new(sender, settings, network, node.clone(), auth_users_notifier);
     tokio::spawn({
         let pool = pool.clone();
         let scheduler = notification_scheduler;
@@ -328,8 +484,14 @@ async fn main() -> Result<()> {
 
     tracing::debug!("Listening on http://{}", http_address);
 
+    // On shutdown, the HTTP server stops accepting connections and waits for the requests in
+    // flight, so that orders which have already been accepted are answered.
     match axum::Server::bind(&http_address)
         .serve(app.into_make_service())
+        .with_graceful_shutdown({
+            let shutdown = shutdown.clone();
+            async move { shutdown.triggered().await }
+        })
         .await
     {
         Ok(_) => {
@@ -340,5 +502,36 @@ async fn main() -> Result<()> {
         }
     }
 
+    // The HTTP server might also have stopped because of an error.
+    shutdown.trigger();
+
+    if let Some(grpc_server) = grpc_server {
+        match grpc_server.await {
+            Ok(Ok(())) => tracing::info!("gRPC server stopped running"),
+            Ok(Err(e)) => tracing::error!("gRPC server stopped running: {e:#}"),
+            Err(e) => tracing::error!("gRPC server task failed: {e:#}"),
+        }
+    }
+
+    tracing::info!("Shutting down");
+
+    if let Err(e) = trading::flush(&trading_sender).await {
+        tracing::error!("Failed to flush matching engine: {e:#}");
+    }
+
+    if let Err(e) = dlc_message_processing.await {
+        tracing::error!("Failed to finish processing DLC messages: {e:#}");
+    }
+
+    node.stop().await;
+
+    // A coordinator on standby takes over once we release the leader lock.
+    drop(election);
+
+    tracing::info!("Coordinator stopped");
+
+    // Exporting the remaining spans blocks until the collector received them.
+    spawn_blocking(logger::shutdown_tracing).await?;
+
     Ok(())
 }
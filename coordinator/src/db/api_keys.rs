@@ -0,0 +1,112 @@
+use crate::schema::api_keys;
+use anyhow::ensure;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::ApiKeyScope;
+use diesel::ExpressionMethods;
+use diesel::Insertable;
+use diesel::OptionalExtension;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::Queryable;
+use diesel::RunQueryDsl;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// An API key which allows programmatic access on behalf of a trader.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub trader_pubkey: PublicKey,
+    pub label: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub created_at: OffsetDateTime,
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = api_keys)]
+struct ApiKeyRow {
+    id: Uuid,
+    trader_pubkey: String,
+    label: String,
+    #[allow(dead_code)]
+    key_hash: String,
+    scopes: String,
+    created_at: OffsetDateTime,
+    revoked_at: Option<OffsetDateTime>,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = api_keys)]
+struct NewApiKey {
+    id: Uuid,
+    trader_pubkey: String,
+    label: String,
+    key_hash: String,
+    scopes: String,
+}
+
+/// Store a new API key. Only the hash of the key is persisted.
+pub fn insert(
+    conn: &mut PgConnection,
+    id: Uuid,
+    trader_pubkey: PublicKey,
+    label: String,
+    key_hash: String,
+    scopes: &[ApiKeyScope],
+) -> Result<()> {
+    let affected_rows = diesel::insert_into(api_keys::table)
+        .values(NewApiKey {
+            id,
+            trader_pubkey: trader_pubkey.to_string(),
+            label,
+            key_hash,
+            scopes: serde_json::to_string(scopes)?,
+        })
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not insert API key");
+
+    Ok(())
+}
+
+/// Get the API key with the given hash, unless it has been revoked.
+pub fn get_active_by_key_hash(conn: &mut PgConnection, key_hash: &str) -> Result<Option<ApiKey>> {
+    api_keys::table
+        .filter(api_keys::key_hash.eq(key_hash))
+        .filter(api_keys::revoked_at.is_null())
+        .first::<ApiKeyRow>(conn)
+        .optional()?
+        .map(ApiKey::try_from)
+        .transpose()
+}
+
+/// Revoke the API key of the given trader. Returns `false` if the trader has no active API key
+/// with the given id.
+pub fn revoke(conn: &mut PgConnection, id: Uuid, trader_pubkey: PublicKey) -> Result<bool> {
+    let affected_rows = diesel::update(api_keys::table)
+        .filter(api_keys::id.eq(id))
+        .filter(api_keys::trader_pubkey.eq(trader_pubkey.to_string()))
+        .filter(api_keys::revoked_at.is_null())
+        .set(api_keys::revoked_at.eq(Some(OffsetDateTime::now_utc())))
+        .execute(conn)?;
+
+    Ok(affected_rows > 0)
+}
+
+impl TryFrom<ApiKeyRow> for ApiKey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ApiKeyRow) -> Result<Self> {
+        Ok(ApiKey {
+            id: value.id,
+            trader_pubkey: PublicKey::from_str(&value.trader_pubkey)?,
+            label: value.label,
+            scopes: serde_json::from_str(&value.scopes)?,
+            created_at: value.created_at,
+            revoked_at: value.revoked_at,
+        })
+    }
+}
@@ -0,0 +1,244 @@
+use crate::schema::attestations;
+use crate::schema::sql_types::AttestationStateType;
+use anyhow::ensure;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::XOnlyPublicKey;
+use diesel::query_builder::QueryId;
+use diesel::AsExpression;
+use diesel::ExpressionMethods;
+use diesel::FromSqlRow;
+use diesel::Insertable;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::Queryable;
+use diesel::RunQueryDsl;
+use serde::Serialize;
+use std::any::TypeId;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromSqlRow, AsExpression, Serialize)]
+#[diesel(sql_type = AttestationStateType)]
+pub enum AttestationState {
+    /// The contract has expired, but we have not received the attestation from the oracle yet.
+    Pending,
+    /// The oracle has attested to the outcome of the contract.
+    Attested,
+    /// The DLC has been settled using the attestation.
+    Settled,
+    /// The position was closed without having to settle the DLC with the attestation.
+    Closed,
+}
+
+impl QueryId for AttestationStateType {
+    type QueryId = AttestationStateType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+/// The attestation status of the contract of an expired position.
+#[derive(Debug, Clone, Serialize)]
+pub struct Attestation {
+    pub id: i32,
+    pub position_id: i32,
+    pub trader_pubkey: PublicKey,
+    pub contract_id: String,
+    pub oracle_pk: XOnlyPublicKey,
+    pub event_id: String,
+    pub attestation_state: AttestationState,
+    /// The price the oracle attested to, once known.
+    pub attested_price: Option<u64>,
+    /// How often we have asked the oracle for the attestation.
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub next_attempt: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = attestations)]
+struct AttestationRow {
+    id: i32,
+    position_id: i32,
+    trader_pubkey: String,
+    contract_id: String,
+    oracle_pk: String,
+    event_id: String,
+    attestation_state: AttestationState,
+    attested_price: Option<i64>,
+    attempts: i32,
+    last_error: Option<String>,
+    next_attempt: OffsetDateTime,
+    created_at: OffsetDateTime,
+    updated_at: OffsetDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = attestations)]
+struct NewAttestation {
+    position_id: i32,
+    trader_pubkey: String,
+    contract_id: String,
+    oracle_pk: String,
+    event_id: String,
+    attestation_state: AttestationState,
+    next_attempt: OffsetDateTime,
+}
+
+/// Start tracking the attestation of the contract of an expired position.
+pub fn insert(
+    conn: &mut PgConnection,
+    position_id: i32,
+    trader_pubkey: PublicKey,
+    contract_id: String,
+    oracle_pk: XOnlyPublicKey,
+    event_id: String,
+) -> Result<()> {
+    let affected_rows = diesel::insert_into(attestations::table)
+        .values(NewAttestation {
+            position_id,
+            trader_pubkey: trader_pubkey.to_string(),
+            contract_id,
+            oracle_pk: oracle_pk.to_string(),
+            event_id,
+            attestation_state: AttestationState::Pending,
+            next_attempt: OffsetDateTime::now_utc(),
+        })
+        .execute(conn)?;
+
+    ensure!(
+        affected_rows > 0,
+        "Could not insert attestation for position {position_id}"
+    );
+
+    Ok(())
+}
+
+pub fn get_all(conn: &mut PgConnection) -> Result<Vec<Attestation>> {
+    attestations::table
+        .order_by(attestations::created_at.desc())
+        .load::<AttestationRow>(conn)?
+        .into_iter()
+        .map(Attestation::try_from)
+        .collect()
+}
+
+/// Get all pending attestations which are due to be requested from the oracle again.
+pub fn get_due(conn: &mut PgConnection, now: OffsetDateTime) -> Result<Vec<Attestation>> {
+    attestations::table
+        .filter(attestations::attestation_state.eq(AttestationState::Pending))
+        .filter(attestations::next_attempt.le(now))
+        .order_by(attestations::next_attempt.asc())
+        .load::<AttestationRow>(conn)?
+        .into_iter()
+        .map(Attestation::try_from)
+        .collect()
+}
+
+pub fn get_by_state(
+    conn: &mut PgConnection,
+    attestation_state: AttestationState,
+) -> Result<Vec<Attestation>> {
+    attestations::table
+        .filter(attestations::attestation_state.eq(attestation_state))
+        .order_by(attestations::next_attempt.asc())
+        .load::<AttestationRow>(conn)?
+        .into_iter()
+        .map(Attestation::try_from)
+        .collect()
+}
+
+/// The ids of all positions whose attestation we are already tracking.
+pub fn get_position_ids(conn: &mut PgConnection) -> Result<Vec<i32>> {
+    let position_ids = attestations::table
+        .select(attestations::position_id)
+        .load(conn)?;
+
+    Ok(position_ids)
+}
+
+/// Record another unsuccessful request for the attestation.
+pub fn record_failed_attempt(
+    conn: &mut PgConnection,
+    id: i32,
+    error: String,
+    next_attempt: OffsetDateTime,
+) -> Result<()> {
+    let affected_rows = diesel::update(attestations::table)
+        .filter(attestations::id.eq(id))
+        .set((
+            attestations::attempts.eq(attestations::attempts + 1),
+            attestations::last_error.eq(Some(error)),
+            attestations::next_attempt.eq(next_attempt),
+            attestations::updated_at.eq(OffsetDateTime::now_utc()),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not update attestation {id}");
+
+    Ok(())
+}
+
+pub fn set_attested(conn: &mut PgConnection, id: i32, attested_price: u64) -> Result<()> {
+    let affected_rows = diesel::update(attestations::table)
+        .filter(attestations::id.eq(id))
+        .set((
+            attestations::attestation_state.eq(AttestationState::Attested),
+            attestations::attested_price.eq(Some(attested_price as i64)),
+            attestations::attempts.eq(attestations::attempts + 1),
+            attestations::last_error.eq(None::<String>),
+            attestations::updated_at.eq(OffsetDateTime::now_utc()),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not update attestation {id}");
+
+    Ok(())
+}
+
+pub fn set_attestation_state(
+    conn: &mut PgConnection,
+    id: i32,
+    attestation_state: AttestationState,
+) -> Result<()> {
+    let affected_rows = diesel::update(attestations::table)
+        .filter(attestations::id.eq(id))
+        .set((
+            attestations::attestation_state.eq(attestation_state),
+            attestations::updated_at.eq(OffsetDateTime::now_utc()),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not update attestation {id}");
+
+    Ok(())
+}
+
+impl TryFrom<AttestationRow> for Attestation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AttestationRow) -> Result<Self> {
+        Ok(Attestation {
+            id: value.id,
+            position_id: value.position_id,
+            trader_pubkey: value.trader_pubkey.parse()?,
+            contract_id: value.contract_id,
+            oracle_pk: value.oracle_pk.parse()?,
+            event_id: value.event_id,
+            attestation_state: value.attestation_state,
+            attested_price: value.attested_price.map(|price| price as u64),
+            attempts: value.attempts,
+            last_error: value.last_error,
+            next_attempt: value.next_attempt,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        })
+    }
+}
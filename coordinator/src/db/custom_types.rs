@@ -1,9 +1,12 @@
+use crate::db::attestations::AttestationState;
 use crate::db::channels::ChannelState;
 use crate::db::dlc_messages::MessageType;
 use crate::db::payments::HtlcStatus;
 use crate::db::payments::PaymentFlow;
 use crate::db::positions::ContractSymbol;
 use crate::db::positions::PositionState;
+use crate::db::trade_executions::TradeExecutionState;
+use crate::schema::sql_types::AttestationStateType;
 use crate::schema::sql_types::ChannelStateType;
 use crate::schema::sql_types::ContractSymbolType;
 use crate::schema::sql_types::DirectionType;
@@ -11,6 +14,7 @@ use crate::schema::sql_types::HtlcStatusType;
 use crate::schema::sql_types::MessageTypeType;
 use crate::schema::sql_types::PaymentFlowType;
 use crate::schema::sql_types::PositionStateType;
+use crate::schema::sql_types::TradeExecutionStateType;
 use diesel::deserialize;
 use diesel::deserialize::FromSql;
 use diesel::pg::Pg;
@@ -205,3 +209,49 @@ impl FromSql<MessageTypeType, Pg> for MessageType {
         }
     }
 }
+
+impl ToSql<TradeExecutionStateType, Pg> for TradeExecutionState {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            TradeExecutionState::Pending => out.write_all(b"Pending")?,
+            TradeExecutionState::Succeeded => out.write_all(b"Succeeded")?,
+            TradeExecutionState::Failed => out.write_all(b"Failed")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<TradeExecutionStateType, Pg> for TradeExecutionState {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"Pending" => Ok(TradeExecutionState::Pending),
+            b"Succeeded" => Ok(TradeExecutionState::Succeeded),
+            b"Failed" => Ok(TradeExecutionState::Failed),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+impl ToSql<AttestationStateType, Pg> for AttestationState {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            AttestationState::Pending => out.write_all(b"Pending")?,
+            AttestationState::Attested => out.write_all(b"Attested")?,
+            AttestationState::Settled => out.write_all(b"Settled")?,
+            AttestationState::Closed => out.write_all(b"Closed")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<AttestationStateType, Pg> for AttestationState {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"Pending" => Ok(AttestationState::Pending),
+            b"Attested" => Ok(AttestationState::Attested),
+            b"Settled" => Ok(AttestationState::Settled),
+            b"Closed" => Ok(AttestationState::Closed),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
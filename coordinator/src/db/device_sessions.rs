@@ -0,0 +1,55 @@
+use crate::schema::device_sessions;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::Device;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use time::OffsetDateTime;
+
+#[derive(Queryable, Debug)]
+#[diesel(table_name = device_sessions)]
+struct DeviceSession {
+    #[allow(dead_code)]
+    trader_pubkey: String,
+    device_id: String,
+    registered_at: OffsetDateTime,
+    #[allow(dead_code)]
+    updated_at: OffsetDateTime,
+}
+
+/// Get the device which is currently using the node ID of the trader, if any.
+pub fn get(conn: &mut PgConnection, trader_pubkey: PublicKey) -> Result<Option<Device>> {
+    let session: Option<DeviceSession> = device_sessions::table
+        .filter(device_sessions::trader_pubkey.eq(trader_pubkey.to_string()))
+        .first(conn)
+        .optional()?;
+
+    Ok(session.map(|session| Device {
+        id: session.device_id,
+        registered_at: session.registered_at.unix_timestamp(),
+    }))
+}
+
+/// Make `device` the device which is using the node ID of the trader.
+pub fn upsert(conn: &mut PgConnection, trader_pubkey: PublicKey, device: &Device) -> Result<()> {
+    let registered_at = OffsetDateTime::from_unix_timestamp(device.registered_at)?;
+    let now = OffsetDateTime::now_utc();
+
+    diesel::insert_into(device_sessions::table)
+        .values((
+            device_sessions::trader_pubkey.eq(trader_pubkey.to_string()),
+            device_sessions::device_id.eq(&device.id),
+            device_sessions::registered_at.eq(registered_at),
+            device_sessions::updated_at.eq(now),
+        ))
+        .on_conflict(device_sessions::trader_pubkey)
+        .do_update()
+        .set((
+            device_sessions::device_id.eq(&device.id),
+            device_sessions::registered_at.eq(registered_at),
+            device_sessions::updated_at.eq(now),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
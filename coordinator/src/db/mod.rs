@@ -1,15 +1,50 @@
+use anyhow::Context;
+use anyhow::Result;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use tokio::task::spawn_blocking;
+
+pub mod api_keys;
+pub mod attestations;
 pub mod channels;
 pub mod collaborative_reverts;
 pub mod custom_types;
+pub mod device_sessions;
 pub mod dlc_messages;
-pub mod last_outbound_dlc_message;
 pub mod liquidity;
 pub mod liquidity_options;
+pub mod outbound_dlc_messages;
 pub mod payments;
 pub mod positions;
 pub mod positions_helper;
 pub mod routing_fees;
 pub mod spendable_outputs;
+pub mod trade_executions;
+pub mod trade_inputs;
 pub mod trades;
 pub mod transactions;
 pub mod user;
+pub mod user_presence;
+
+/// Run `f` with a connection from the `pool` on a thread on which blocking is acceptable.
+///
+/// Diesel is synchronous, hence async code must not query the database directly, as that would
+/// block a tokio worker thread.
+///
+/// This is a stopgap, not an async database driver: every query still occupies a blocking thread
+/// and an r2d2 connection for its whole duration. Migrating to `diesel-async` or sqlx would remove
+/// the blocking threads, but has not been done yet.
+pub async fn run<T, F>(pool: &Pool<ConnectionManager<PgConnection>>, f: F) -> Result<T>
+where
+    F: FnOnce(&mut PgConnection) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = pool.clone();
+    spawn_blocking(move || {
+        let mut conn = pool.get().context("Failed to get db connection")?;
+        f(&mut conn)
+    })
+    .await
+    .context("Database task failed")?
+}
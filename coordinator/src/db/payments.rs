@@ -7,12 +7,14 @@ use anyhow::ensure;
 use anyhow::Result;
 use bitcoin::hashes::hex::FromHex;
 use bitcoin::hashes::hex::ToHex;
+use bitcoin::secp256k1::PublicKey;
 use diesel;
 use diesel::prelude::*;
 use diesel::query_builder::QueryId;
 use diesel::AsExpression;
 use diesel::FromSqlRow;
 use std::any::TypeId;
+use std::str::FromStr;
 use time::OffsetDateTime;
 
 #[derive(Queryable, Debug, Clone)]
@@ -31,6 +33,9 @@ pub struct Payment {
     pub description: String,
     pub invoice: Option<String>,
     pub fee_msat: Option<i64>,
+    pub expiry_timestamp: Option<OffsetDateTime>,
+    pub payee_pubkey: Option<String>,
+    pub failure_reason: Option<String>,
 }
 
 pub fn get(
@@ -84,6 +89,9 @@ impl From<(lightning::ln::PaymentHash, ln_dlc_node::PaymentInfo)> for NewPayment
             payment_timestamp: info.timestamp,
             description: info.description,
             invoice: info.invoice,
+            expiry_timestamp: info.expiry,
+            payee_pubkey: info.payee.map(|payee| payee.to_string()),
+            failure_reason: info.failure_reason.map(|reason| reason.to_string()),
         }
     }
 }
@@ -117,6 +125,16 @@ impl TryFrom<Payment> for (lightning::ln::PaymentHash, ln_dlc_node::PaymentInfo)
             ln_dlc_node::MillisatAmount::new(value.amount_msat.map(|amount| amount as u64));
         let fee_msat = ln_dlc_node::MillisatAmount::new(value.fee_msat.map(|amount| amount as u64));
 
+        let failure_reason = value
+            .failure_reason
+            .map(|reason| ln_dlc_node::PaymentFailureReason::from_str(&reason))
+            .transpose()?;
+
+        let payee = value
+            .payee_pubkey
+            .map(|payee| PublicKey::from_str(&payee))
+            .transpose()?;
+
         Ok((
             payment_hash,
             ln_dlc_node::PaymentInfo {
@@ -130,6 +148,9 @@ impl TryFrom<Payment> for (lightning::ln::PaymentHash, ln_dlc_node::PaymentInfo)
                 description: value.description,
                 invoice: value.invoice,
                 funding_txid: None,
+                expiry: value.expiry_timestamp,
+                payee,
+                failure_reason,
             },
         ))
     }
@@ -181,6 +202,11 @@ pub(crate) struct NewPayment {
     pub description: String,
     #[diesel(sql_type = Nullable<Text>)]
     pub invoice: Option<String>,
+    pub expiry_timestamp: Option<OffsetDateTime>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub payee_pubkey: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub failure_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
@@ -240,6 +266,7 @@ pub fn insert(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update(
     payment_hash: lightning::ln::PaymentHash,
     htlc_status: ln_dlc_node::HTLCStatus,
@@ -247,6 +274,7 @@ pub fn update(
     fee_msat: ln_dlc_node::MillisatAmount,
     preimage: Option<lightning::ln::PaymentPreimage>,
     secret: Option<lightning::ln::PaymentSecret>,
+    failure_reason: Option<ln_dlc_node::PaymentFailureReason>,
     conn: &mut PgConnection,
 ) -> Result<OffsetDateTime> {
     let updated_at = OffsetDateTime::now_utc();
@@ -258,6 +286,7 @@ pub fn update(
     let htlc_status: HtlcStatus = htlc_status.into();
     let amount_msat = amount_msat.to_inner().map(|amt| amt as i64);
     let fee_msat = fee_msat.to_inner().map(|amt| amt as i64);
+    let failure_reason = failure_reason.map(|reason| reason.to_string());
 
     conn.transaction::<(), _, _>(|conn| {
         let affected_rows = diesel::update(payments::table)
@@ -313,6 +342,17 @@ pub fn update(
             }
         }
 
+        if let Some(failure_reason) = failure_reason {
+            let affected_rows = diesel::update(payments::table)
+                .filter(schema::payments::payment_hash.eq(&payment_hash))
+                .set(schema::payments::failure_reason.eq(failure_reason))
+                .execute(conn)?;
+
+            if affected_rows == 0 {
+                bail!("Could not update payment failure reason")
+            }
+        }
+
         let affected_rows = diesel::update(payments::table)
             .filter(schema::payments::payment_hash.eq(&payment_hash))
             .set(schema::payments::updated_at.eq(updated_at))
@@ -66,6 +66,32 @@ impl Position {
         Ok(x.map(crate::position::models::Position::from))
     }
 
+    /// Returns all positions of the trader in the given states, the most recent first.
+    pub fn get_positions_by_trader(
+        conn: &mut PgConnection,
+        trader_pubkey: PublicKey,
+        states: Vec<crate::position::models::PositionState>,
+    ) -> QueryResult<Vec<crate::position::models::Position>> {
+        let mut query = positions::table.into_boxed();
+
+        query = query.filter(positions::trader_pubkey.eq(trader_pubkey.to_string()));
+
+        if !states.is_empty() {
+            query = query.filter(
+                positions::position_state.eq_any(states.into_iter().map(PositionState::from)),
+            )
+        }
+
+        let positions = query
+            .order_by(positions::creation_timestamp.desc())
+            .load::<Position>(conn)?;
+
+        Ok(positions
+            .into_iter()
+            .map(crate::position::models::Position::from)
+            .collect())
+    }
+
     pub fn get_all_open_positions_with_expiry_before(
         conn: &mut PgConnection,
         expiry: OffsetDateTime,
@@ -98,6 +124,45 @@ impl Position {
         Ok(positions)
     }
 
+    /// Returns all positions which are in the midst of a rollover.
+    pub fn get_all_positions_in_rollover(
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<crate::position::models::Position>> {
+        let positions = positions::table
+            .filter(positions::position_state.eq(PositionState::Rollover))
+            .load::<Position>(conn)?;
+
+        let positions = positions
+            .into_iter()
+            .map(crate::position::models::Position::from)
+            .collect();
+
+        Ok(positions)
+    }
+
+    /// Returns all positions of the given trader which have been closed within `[from, to)`.
+    pub fn get_closed_positions_by_trader(
+        conn: &mut PgConnection,
+        trader_pubkey: PublicKey,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> QueryResult<Vec<crate::position::models::Position>> {
+        let positions = positions::table
+            .filter(positions::trader_pubkey.eq(trader_pubkey.to_string()))
+            .filter(positions::position_state.eq(PositionState::Closed))
+            .filter(positions::update_timestamp.ge(from))
+            .filter(positions::update_timestamp.lt(to))
+            .order_by(positions::update_timestamp.asc())
+            .load::<Position>(conn)?;
+
+        let positions = positions
+            .into_iter()
+            .map(crate::position::models::Position::from)
+            .collect();
+
+        Ok(positions)
+    }
+
     pub fn get_all_open_or_closing_positions(
         conn: &mut PgConnection,
     ) -> QueryResult<Vec<crate::position::models::Position>> {
@@ -327,6 +392,26 @@ impl Position {
         Ok(())
     }
 
+    /// Move the expiry of the trader's open position, e.g. to let it expire on regtest.
+    pub fn set_expiry_of_open_position(
+        conn: &mut PgConnection,
+        trader_pubkey: String,
+        expiry_timestamp: OffsetDateTime,
+    ) -> Result<()> {
+        let affected_rows = diesel::update(positions::table)
+            .filter(positions::trader_pubkey.eq(trader_pubkey))
+            .filter(positions::position_state.eq(PositionState::Open))
+            .set((
+                positions::expiry_timestamp.eq(expiry_timestamp),
+                positions::update_timestamp.eq(OffsetDateTime::now_utc()),
+            ))
+            .execute(conn)?;
+
+        ensure!(affected_rows > 0, "Could not set expiry of open position");
+
+        Ok(())
+    }
+
     /// inserts the given position into the db. Returns the position if successful
     pub fn insert(
         conn: &mut PgConnection,
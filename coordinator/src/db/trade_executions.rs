@@ -0,0 +1,197 @@
+use crate::schema::sql_types::TradeExecutionStateType;
+use crate::schema::trade_executions;
+use anyhow::ensure;
+use anyhow::Result;
+use commons::TradeParams;
+use diesel::query_builder::QueryId;
+use diesel::AsExpression;
+use diesel::ExpressionMethods;
+use diesel::FromSqlRow;
+use diesel::Insertable;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::Queryable;
+use diesel::RunQueryDsl;
+use serde::Serialize;
+use std::any::TypeId;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromSqlRow, AsExpression, Serialize)]
+#[diesel(sql_type = TradeExecutionStateType)]
+pub enum TradeExecutionState {
+    /// The execution failed and will be retried.
+    Pending,
+    Succeeded,
+    /// The execution failed permanently, i.e. it will not be retried anymore.
+    Failed,
+}
+
+impl QueryId for TradeExecutionStateType {
+    type QueryId = TradeExecutionStateType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+/// A trade execution which failed at least once.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeExecution {
+    pub id: i32,
+    pub order_id: Uuid,
+    pub trade_params: TradeParams,
+    pub execution_state: TradeExecutionState,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub next_attempt: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = trade_executions)]
+struct TradeExecutionRow {
+    id: i32,
+    order_id: Uuid,
+    #[allow(dead_code)]
+    trader_pubkey: String,
+    trade_params: String,
+    execution_state: TradeExecutionState,
+    attempts: i32,
+    last_error: Option<String>,
+    next_attempt: OffsetDateTime,
+    created_at: OffsetDateTime,
+    updated_at: OffsetDateTime,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = trade_executions)]
+struct NewTradeExecution {
+    order_id: Uuid,
+    trader_pubkey: String,
+    trade_params: String,
+    execution_state: TradeExecutionState,
+    attempts: i32,
+    last_error: Option<String>,
+    next_attempt: OffsetDateTime,
+}
+
+/// Queue the trade execution for a retry after it failed for the first time.
+pub fn insert(
+    conn: &mut PgConnection,
+    trade_params: &TradeParams,
+    error: String,
+    next_attempt: OffsetDateTime,
+) -> Result<()> {
+    let affected_rows = diesel::insert_into(trade_executions::table)
+        .values(NewTradeExecution {
+            order_id: trade_params.filled_with.order_id,
+            trader_pubkey: trade_params.pubkey.to_string(),
+            trade_params: serde_json::to_string(trade_params)?,
+            execution_state: TradeExecutionState::Pending,
+            attempts: 1,
+            last_error: Some(error),
+            next_attempt,
+        })
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not insert trade execution");
+
+    Ok(())
+}
+
+/// Get all pending trade executions which are due for a retry.
+pub fn get_due(conn: &mut PgConnection, now: OffsetDateTime) -> Result<Vec<TradeExecution>> {
+    trade_executions::table
+        .filter(trade_executions::execution_state.eq(TradeExecutionState::Pending))
+        .filter(trade_executions::next_attempt.le(now))
+        .order_by(trade_executions::next_attempt.asc())
+        .load::<TradeExecutionRow>(conn)?
+        .into_iter()
+        .map(TradeExecution::try_from)
+        .collect()
+}
+
+pub fn get_by_state(
+    conn: &mut PgConnection,
+    execution_state: TradeExecutionState,
+) -> Result<Vec<TradeExecution>> {
+    trade_executions::table
+        .filter(trade_executions::execution_state.eq(execution_state))
+        .order_by(trade_executions::updated_at.desc())
+        .load::<TradeExecutionRow>(conn)?
+        .into_iter()
+        .map(TradeExecution::try_from)
+        .collect()
+}
+
+/// Record another failed attempt. If `next_attempt` is `None`, the execution is not retried
+/// anymore.
+pub fn record_failed_attempt(
+    conn: &mut PgConnection,
+    id: i32,
+    error: String,
+    next_attempt: Option<OffsetDateTime>,
+) -> Result<()> {
+    let now = OffsetDateTime::now_utc();
+    let execution_state = match next_attempt {
+        Some(_) => TradeExecutionState::Pending,
+        None => TradeExecutionState::Failed,
+    };
+
+    let affected_rows = diesel::update(trade_executions::table)
+        .filter(trade_executions::id.eq(id))
+        .set((
+            trade_executions::execution_state.eq(execution_state),
+            trade_executions::attempts.eq(trade_executions::attempts + 1),
+            trade_executions::last_error.eq(Some(error)),
+            trade_executions::next_attempt.eq(next_attempt.unwrap_or(now)),
+            trade_executions::updated_at.eq(now),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not update trade execution {id}");
+
+    Ok(())
+}
+
+pub fn set_execution_state(
+    conn: &mut PgConnection,
+    id: i32,
+    execution_state: TradeExecutionState,
+) -> Result<()> {
+    let affected_rows = diesel::update(trade_executions::table)
+        .filter(trade_executions::id.eq(id))
+        .set((
+            trade_executions::execution_state.eq(execution_state),
+            trade_executions::updated_at.eq(OffsetDateTime::now_utc()),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not update trade execution {id}");
+
+    Ok(())
+}
+
+impl TryFrom<TradeExecutionRow> for TradeExecution {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TradeExecutionRow) -> Result<Self> {
+        Ok(TradeExecution {
+            id: value.id,
+            order_id: value.order_id,
+            trade_params: serde_json::from_str(&value.trade_params)?,
+            execution_state: value.execution_state,
+            attempts: value.attempts,
+            last_error: value.last_error,
+            next_attempt: value.next_attempt,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        })
+    }
+}
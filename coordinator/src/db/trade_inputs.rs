@@ -0,0 +1,136 @@
+use crate::schema::trade_inputs;
+use crate::trade::replay::TradeInputs;
+use anyhow::ensure;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use commons::default_taker_fee_rate;
+use diesel::ExpressionMethods;
+use diesel::Insertable;
+use diesel::OptionalExtension;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::Queryable;
+use diesel::RunQueryDsl;
+use dlc_manager::ContractId;
+use hex::FromHex;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The inputs recorded when executing a trade, together with the DLC they resulted in.
+#[derive(Debug, Clone)]
+pub struct RecordedTradeInputs {
+    pub trade_id: i32,
+    pub inputs: TradeInputs,
+    pub temporary_contract_id: ContractId,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Queryable, Debug, Clone)]
+#[diesel(table_name = trade_inputs)]
+struct TradeInputsRow {
+    #[allow(dead_code)]
+    id: i32,
+    trade_id: i32,
+    #[allow(dead_code)]
+    order_id: Uuid,
+    trade_params: String,
+    coordinator_leverage: f32,
+    offer_collateral: i64,
+    accept_collateral: i64,
+    fee_rate: i64,
+    temporary_contract_id: String,
+    created_at: OffsetDateTime,
+    payout_curve_params: Option<String>,
+    taker_fee_rate: Option<String>,
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = trade_inputs)]
+struct NewTradeInputs {
+    trade_id: i32,
+    order_id: Uuid,
+    trade_params: String,
+    coordinator_leverage: f32,
+    offer_collateral: i64,
+    accept_collateral: i64,
+    fee_rate: i64,
+    temporary_contract_id: String,
+    payout_curve_params: Option<String>,
+    taker_fee_rate: Option<String>,
+}
+
+pub fn insert(
+    conn: &mut PgConnection,
+    trade_id: i32,
+    inputs: &TradeInputs,
+    temporary_contract_id: ContractId,
+) -> Result<()> {
+    let affected_rows = diesel::insert_into(trade_inputs::table)
+        .values(NewTradeInputs {
+            trade_id,
+            order_id: inputs.trade_params.filled_with.order_id,
+            trade_params: serde_json::to_string(&inputs.trade_params)?,
+            coordinator_leverage: inputs.coordinator_leverage,
+            offer_collateral: inputs.offer_collateral as i64,
+            accept_collateral: inputs.accept_collateral as i64,
+            fee_rate: inputs.fee_rate as i64,
+            temporary_contract_id: temporary_contract_id.to_hex(),
+            payout_curve_params: Some(serde_json::to_string(&inputs.payout_curve_params)?),
+            taker_fee_rate: Some(inputs.taker_fee_rate.to_string()),
+        })
+        .execute(conn)?;
+
+    ensure!(
+        affected_rows > 0,
+        "Could not insert inputs of trade {trade_id}"
+    );
+
+    Ok(())
+}
+
+pub fn get_by_trade_id(
+    conn: &mut PgConnection,
+    trade_id: i32,
+) -> Result<Option<RecordedTradeInputs>> {
+    trade_inputs::table
+        .filter(trade_inputs::trade_id.eq(trade_id))
+        .first::<TradeInputsRow>(conn)
+        .optional()?
+        .map(RecordedTradeInputs::try_from)
+        .transpose()
+}
+
+impl TryFrom<TradeInputsRow> for RecordedTradeInputs {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TradeInputsRow) -> Result<Self> {
+        Ok(RecordedTradeInputs {
+            trade_id: value.trade_id,
+            inputs: TradeInputs {
+                trade_params: serde_json::from_str(&value.trade_params)?,
+                coordinator_leverage: value.coordinator_leverage,
+                offer_collateral: value.offer_collateral as u64,
+                accept_collateral: value.accept_collateral as u64,
+                fee_rate: value.fee_rate as u64,
+                // Trades executed before the payout curve became configurable used the default
+                // parameters.
+                payout_curve_params: value
+                    .payout_curve_params
+                    .map(|params| serde_json::from_str(&params))
+                    .transpose()?
+                    .unwrap_or_default(),
+                // Trades executed before the fee schedule became configurable were charged the
+                // default fee.
+                taker_fee_rate: value
+                    .taker_fee_rate
+                    .map(|rate| Decimal::from_str(&rate))
+                    .transpose()?
+                    .unwrap_or_else(default_taker_fee_rate),
+            },
+            temporary_contract_id: ContractId::from_hex(&value.temporary_contract_id)?,
+            created_at: value.created_at,
+        })
+    }
+}
@@ -65,6 +65,57 @@ pub fn get_latest_for_position(
     Ok(trade.map(crate::trade::models::Trade::from))
 }
 
+/// Returns all trades of the given contract symbol since the given timestamp, oldest first.
+pub fn get_trades_since(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+    since: OffsetDateTime,
+) -> Result<Vec<crate::trade::models::Trade>> {
+    let trades = trades::table
+        .filter(trades::contract_symbol.eq(ContractSymbol::from(contract_symbol)))
+        .filter(trades::timestamp.ge(since))
+        .order_by(trades::timestamp.asc())
+        .load::<Trade>(conn)?;
+
+    Ok(trades
+        .into_iter()
+        .map(crate::trade::models::Trade::from)
+        .collect())
+}
+
+/// Returns all trades of the given trader within `[from, to)`, oldest first.
+pub fn get_trades_by_trader(
+    conn: &mut PgConnection,
+    trader_pubkey: PublicKey,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+) -> Result<Vec<crate::trade::models::Trade>> {
+    let trades = trades::table
+        .filter(trades::trader_pubkey.eq(trader_pubkey.to_string()))
+        .filter(trades::timestamp.ge(from))
+        .filter(trades::timestamp.lt(to))
+        .order_by(trades::timestamp.asc())
+        .load::<Trade>(conn)?;
+
+    Ok(trades
+        .into_iter()
+        .map(crate::trade::models::Trade::from)
+        .collect())
+}
+
+pub fn get_latest_trade(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+) -> Result<Option<crate::trade::models::Trade>> {
+    let trade = trades::table
+        .filter(trades::contract_symbol.eq(ContractSymbol::from(contract_symbol)))
+        .order_by(trades::timestamp.desc())
+        .first::<Trade>(conn)
+        .optional()?;
+
+    Ok(trade.map(crate::trade::models::Trade::from))
+}
+
 /// Returns the position by trader pub key
 pub fn is_payment_hash_registered_as_trade_fee(
     conn: &mut PgConnection,
@@ -0,0 +1,139 @@
+use crate::schema::user_presence;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use serde::Serialize;
+use std::str::FromStr;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// How often the presence of a connected trader is refreshed.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A connected trader who has not been seen for longer is considered offline, e.g. because the
+/// coordinator serving their websocket went away without recording the disconnect.
+const MAX_PRESENCE_AGE: Duration = Duration::from_secs(3 * 60);
+
+#[derive(Queryable, Debug)]
+#[diesel(table_name = user_presence)]
+struct UserPresenceRow {
+    trader_pubkey: String,
+    /// Only needed to match heartbeats and disconnects with the session.
+    #[allow(dead_code)]
+    session_id: Option<Uuid>,
+    connected_at: Option<OffsetDateTime>,
+    last_seen: OffsetDateTime,
+}
+
+/// Whether a trader is connected to the orderbook websocket, shared by all coordinators.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserPresence {
+    pub trader_pubkey: PublicKey,
+    /// When the trader connected, if they are connected.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub connected_at: Option<OffsetDateTime>,
+    /// When the trader was last known to be connected.
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_seen: OffsetDateTime,
+}
+
+impl UserPresence {
+    pub fn is_online(&self, now: OffsetDateTime) -> bool {
+        self.connected_at.is_some() && now - self.last_seen <= MAX_PRESENCE_AGE
+    }
+}
+
+/// Record that the trader connected with a new websocket session.
+///
+/// A session of the trader which has not disconnected yet, e.g. on another device, is replaced.
+pub fn connected(
+    conn: &mut PgConnection,
+    trader_pubkey: PublicKey,
+    session_id: Uuid,
+) -> Result<()> {
+    let now = OffsetDateTime::now_utc();
+
+    diesel::insert_into(user_presence::table)
+        .values((
+            user_presence::trader_pubkey.eq(trader_pubkey.to_string()),
+            user_presence::session_id.eq(session_id),
+            user_presence::connected_at.eq(now),
+            user_presence::last_seen.eq(now),
+        ))
+        .on_conflict(user_presence::trader_pubkey)
+        .do_update()
+        .set((
+            user_presence::session_id.eq(session_id),
+            user_presence::connected_at.eq(now),
+            user_presence::last_seen.eq(now),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Record that the session of the trader is still connected.
+pub fn seen(conn: &mut PgConnection, trader_pubkey: PublicKey, session_id: Uuid) -> Result<()> {
+    diesel::update(user_presence::table)
+        .filter(user_presence::trader_pubkey.eq(trader_pubkey.to_string()))
+        .filter(user_presence::session_id.eq(session_id))
+        .set(user_presence::last_seen.eq(OffsetDateTime::now_utc()))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Record that the session of the trader disconnected.
+///
+/// Nothing changes if the session has already been replaced by a newer one.
+pub fn disconnected(
+    conn: &mut PgConnection,
+    trader_pubkey: PublicKey,
+    session_id: Uuid,
+) -> Result<()> {
+    diesel::update(user_presence::table)
+        .filter(user_presence::trader_pubkey.eq(trader_pubkey.to_string()))
+        .filter(user_presence::session_id.eq(session_id))
+        .set((
+            user_presence::session_id.eq(None::<Uuid>),
+            user_presence::connected_at.eq(None::<OffsetDateTime>),
+            user_presence::last_seen.eq(OffsetDateTime::now_utc()),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+pub fn get(conn: &mut PgConnection, trader_pubkey: PublicKey) -> Result<Option<UserPresence>> {
+    let presence: Option<UserPresenceRow> = user_presence::table
+        .filter(user_presence::trader_pubkey.eq(trader_pubkey.to_string()))
+        .first(conn)
+        .optional()?;
+
+    presence.map(UserPresence::try_from).transpose()
+}
+
+/// Get the traders who are online at `now`.
+pub fn get_online(conn: &mut PgConnection, now: OffsetDateTime) -> Result<Vec<UserPresence>> {
+    let presences: Vec<UserPresenceRow> = user_presence::table
+        .filter(user_presence::connected_at.is_not_null())
+        .filter(user_presence::last_seen.ge(now - MAX_PRESENCE_AGE))
+        .order(user_presence::connected_at.asc())
+        .load(conn)?;
+
+    presences.into_iter().map(UserPresence::try_from).collect()
+}
+
+impl TryFrom<UserPresenceRow> for UserPresence {
+    type Error = anyhow::Error;
+
+    fn try_from(row: UserPresenceRow) -> Result<Self> {
+        Ok(UserPresence {
+            trader_pubkey: PublicKey::from_str(&row.trader_pubkey)?,
+            connected_at: row.connected_at,
+            last_seen: row.last_seen,
+        })
+    }
+}
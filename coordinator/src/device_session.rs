@@ -0,0 +1,81 @@
+use crate::db;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::Device;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::Connection;
+use diesel::PgConnection;
+
+/// The outcome of a device authenticating for a node ID.
+///
+/// Only one device may use a node ID at a time, as two devices persisting the state of the same
+/// channels would corrupt it. If a seed is used on a second device, the device on which the seed
+/// has been created or restored last wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionDecision {
+    /// The device is the one using the node ID.
+    Continue,
+    /// The device takes over the node ID from another device, which has to be told to stop.
+    Supersede,
+    /// Another device has taken over the node ID from the device.
+    Reject,
+}
+
+/// Register `device` as the device using the node ID of the trader, unless a device which has
+/// been registered later is using it already.
+pub async fn register(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+    trader_id: PublicKey,
+    device: Device,
+) -> Result<SessionDecision> {
+    db::run(pool, move |conn| {
+        conn.transaction(|conn| {
+            let active = db::device_sessions::get(conn, trader_id)?;
+            let decision = decide(active.as_ref(), &device);
+
+            match decision {
+                SessionDecision::Continue | SessionDecision::Supersede => {
+                    db::device_sessions::upsert(conn, trader_id, &device)?
+                }
+                SessionDecision::Reject => {}
+            }
+
+            Ok(decision)
+        })
+    })
+    .await
+}
+
+fn decide(active: Option<&Device>, device: &Device) -> SessionDecision {
+    match active {
+        None => SessionDecision::Continue,
+        Some(active) if active.id == device.id => SessionDecision::Continue,
+        Some(active) if device.registered_at >= active.registered_at => SessionDecision::Supersede,
+        Some(_) => SessionDecision::Reject,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_registration_takes_over_session() {
+        let first = device("first", 1_000);
+        let second = device("second", 2_000);
+
+        assert_eq!(decide(None, &first), SessionDecision::Continue);
+        assert_eq!(decide(Some(&first), &first), SessionDecision::Continue);
+        assert_eq!(decide(Some(&first), &second), SessionDecision::Supersede);
+        assert_eq!(decide(Some(&second), &first), SessionDecision::Reject);
+        assert_eq!(decide(Some(&second), &second), SessionDecision::Continue);
+    }
+
+    fn device(id: &str, registered_at: i64) -> Device {
+        Device {
+            id: id.to_string(),
+            registered_at,
+        }
+    }
+}
@@ -24,7 +24,8 @@ use tokio::sync::broadcast::error::RecvError;
 /// processed. It's main purpose is to ensure the following.
 ///
 /// 1. Mark all received inbound messages as processed.
-/// 2. Save the last outbound dlc message, so it can be resend on the next reconnect.
+/// 2. Queue outbound dlc messages, so that the unacknowledged ones can be resend on the next
+///    reconnect.
 /// 3. Check if a receive message has already been processed and if so inform to skip the message.
 
 #[derive(Clone)]
@@ -43,8 +44,8 @@ impl DlcHandler {
 }
 
 /// [`spawn_handling_dlc_messages`] handles sending outbound dlc messages as well as keeping track
-/// of what dlc messages have already been processed and what was the last outbound dlc message
-/// so it can be resend on reconnect.
+/// of what dlc messages have already been processed and what outbound dlc messages have not been
+/// acknowledged yet, so they can be resend on reconnect.
 ///
 /// It does not handle the incoming dlc messages!
 pub fn spawn_handling_dlc_messages(
@@ -64,6 +65,8 @@ pub fn spawn_handling_dlc_messages(
                         tracing::error!(peer=%peer, "Failed to process end dlc message event. {e:#}");
                     }
                 }
+                Ok(NodeEvent::SyncProgress(_)) => {} // ignored
+                Ok(NodeEvent::ChannelOpenStatus { .. }) => {} // ignored
                 Err(RecvError::Lagged(skipped)) => {
                     tracing::warn!("Skipped {skipped} messages");
                 }
@@ -88,7 +91,7 @@ impl DlcHandler {
         let outbound_msg = DlcMessage::new(peer, serialized_outbound_message.clone(), false)?;
 
         db::dlc_messages::insert(&mut conn, outbound_msg)?;
-        db::last_outbound_dlc_message::upsert(&mut conn, &peer, serialized_outbound_message)?;
+        db::outbound_dlc_messages::enqueue(&mut conn, &peer, serialized_outbound_message)?;
 
         send_dlc_message(
             &self.node.dlc_message_handler,
@@ -121,20 +124,23 @@ impl DlcHandler {
         }
 
         let mut conn = self.pool.get()?;
-        let last_serialized_message = db::last_outbound_dlc_message::get(&mut conn, &peer)?;
+        let unacknowledged_messages =
+            db::outbound_dlc_messages::get_unacknowledged(&mut conn, &peer)?;
 
-        if let Some(last_serialized_message) = last_serialized_message {
-            tracing::debug!(%peer, ?last_serialized_message.message_type, "Sending last dlc message");
+        if unacknowledged_messages.is_empty() {
+            tracing::debug!(%peer, "No unacknowledged dlc messages found. Nothing todo.");
+        }
+
+        for serialized_message in unacknowledged_messages {
+            tracing::debug!(%peer, ?serialized_message.message_type, "Resending unacknowledged dlc message");
 
-            let message = Message::try_from(&last_serialized_message)?;
+            let message = Message::try_from(&serialized_message)?;
             send_dlc_message(
                 &self.node.dlc_message_handler,
                 &self.node.peer_manager,
                 peer,
                 message,
-            )
-        } else {
-            tracing::debug!(%peer, "No last dlc message found. Nothing todo.");
+            );
         }
 
         Ok(())
@@ -0,0 +1,118 @@
+use crate::node::Node;
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use commons::FeeEstimates;
+use commons::FeeHistogramBucket;
+use commons::FeeTiers;
+use lightning::chain::chaininterface::ConfirmationTarget;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// How long we wait for esplora to give us the state of the mempool.
+const MEMPOOL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Provides the [`FeeEstimates`] shown to the users when choosing the fee of an on-chain
+/// transaction.
+#[derive(Clone)]
+pub struct FeeEstimatesProvider {
+    node: Node,
+    client: Client,
+    esplora_urls: Vec<String>,
+}
+
+/// The part of esplora's `/mempool` response we are interested in.
+#[derive(Deserialize)]
+struct Mempool {
+    fee_histogram: Vec<(f32, u64)>,
+}
+
+impl FeeEstimatesProvider {
+    pub fn new(node: Node, esplora_urls: Vec<String>) -> Self {
+        let client = Client::builder()
+            .timeout(MEMPOOL_TIMEOUT)
+            .build()
+            .expect("Failed to build reqwest client");
+
+        Self {
+            node,
+            client,
+            esplora_urls,
+        }
+    }
+
+    /// The fee rates of our fee rate estimator and the fee histogram of the mempool.
+    ///
+    /// The fee rates are served from the cache of the estimator and are thus always available. If
+    /// none of the esplora backends can give us the mempool, the histogram is left empty. The same
+    /// applies if we are not using esplora at all, i.e. if `esplora_urls` is empty.
+    pub async fn get(&self) -> FeeEstimates {
+        let histogram = match self.get_fee_histogram().await {
+            Ok(histogram) => histogram,
+            Err(e) => {
+                tracing::warn!("Failed to get fee histogram of mempool: {e:#}");
+                vec![]
+            }
+        };
+
+        FeeEstimates {
+            tiers: self.get_tiers(),
+            histogram,
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn get_tiers(&self) -> FeeTiers {
+        let wallet = self.node.inner.ldk_wallet();
+        let sats_per_vbyte = |target| wallet.get_fee_rate(target).as_sat_per_vb();
+
+        FeeTiers {
+            minimum: sats_per_vbyte(ConfirmationTarget::MempoolMinimum),
+            background: sats_per_vbyte(ConfirmationTarget::Background),
+            normal: sats_per_vbyte(ConfirmationTarget::Normal),
+            high_priority: sats_per_vbyte(ConfirmationTarget::HighPriority),
+        }
+    }
+
+    async fn get_fee_histogram(&self) -> Result<Vec<FeeHistogramBucket>> {
+        if self.esplora_urls.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut result = Err(anyhow!("No esplora backend configured"));
+        for esplora_url in self.esplora_urls.iter() {
+            result = self.get_mempool(esplora_url).await;
+            if result.is_ok() {
+                break;
+            }
+        }
+
+        let histogram = result?
+            .fee_histogram
+            .into_iter()
+            .map(|(sats_per_vbyte, vsize)| FeeHistogramBucket {
+                sats_per_vbyte,
+                vsize,
+            })
+            .collect();
+
+        Ok(histogram)
+    }
+
+    async fn get_mempool(&self, esplora_url: &str) -> Result<Mempool> {
+        let mempool = self
+            .client
+            .get(format!("{esplora_url}/mempool"))
+            .send()
+            .await
+            .context("could not send request")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("could not parse mempool")?;
+
+        Ok(mempool)
+    }
+}
@@ -0,0 +1,243 @@
+//! Conversions between our domain types and the messages of the gRPC API.
+
+use ::trade::ContractSymbol;
+use ::trade::Direction;
+use bitcoin::secp256k1::PublicKey;
+use commons::NewOrder;
+use commons::Order;
+use commons::OrderState;
+use commons::OrderType;
+use commons::QuoteEvent;
+use commons::QuoteEventKind;
+use commons::TraderPosition;
+use commons::TraderPositionState;
+use coordinator_grpc::v1;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use tonic::Status;
+use uuid::Uuid;
+
+pub(super) fn new_order(
+    request: v1::SubmitOrderRequest,
+    trader_id: PublicKey,
+) -> Result<NewOrder, Status> {
+    let order_type = match request.order_type() {
+        v1::OrderType::Market => OrderType::Market,
+        v1::OrderType::Limit => OrderType::Limit,
+        v1::OrderType::Unspecified => return Err(Status::invalid_argument("Missing order type")),
+    };
+
+    let price = match order_type {
+        // Market orders do not have a price.
+        OrderType::Market => Decimal::ZERO,
+        OrderType::Limit => parse_decimal("price", &request.price)?,
+    };
+
+    let id = if request.id.is_empty() {
+        Uuid::new_v4()
+    } else {
+        parse_uuid("id", &request.id)?
+    };
+
+    let contract_symbol = match request.contract_symbol() {
+        v1::ContractSymbol::Btcusd => ContractSymbol::BtcUsd,
+        v1::ContractSymbol::Unspecified => {
+            return Err(Status::invalid_argument("Missing contract symbol"))
+        }
+    };
+
+    let direction = match request.direction() {
+        v1::Direction::Long => Direction::Long,
+        v1::Direction::Short => Direction::Short,
+        v1::Direction::Unspecified => return Err(Status::invalid_argument("Missing direction")),
+    };
+
+    let expiry = OffsetDateTime::from_unix_timestamp(request.expiry)
+        .map_err(|e| Status::invalid_argument(format!("Invalid expiry: {e}")))?;
+
+    Ok(NewOrder {
+        id,
+        contract_symbol,
+        price,
+        quantity: parse_decimal("quantity", &request.quantity)?,
+        trader_id,
+        direction,
+        leverage: request.leverage,
+        order_type,
+        expiry,
+        stable: request.stable,
+        reduce_only: request.reduce_only,
+    })
+}
+
+pub(super) fn order(order: Order) -> v1::Order {
+    let order_state = match order.order_state {
+        OrderState::Open => v1::OrderState::Open,
+        OrderState::Matched => v1::OrderState::Matched,
+        OrderState::Taken => v1::OrderState::Taken,
+        OrderState::Failed => v1::OrderState::Failed,
+    };
+
+    v1::Order {
+        id: order.id.to_string(),
+        contract_symbol: contract_symbol(order.contract_symbol) as i32,
+        price: order.price.to_string(),
+        quantity: order.quantity.to_string(),
+        trader_id: order.trader_id.to_string(),
+        direction: direction(order.direction) as i32,
+        leverage: order.leverage,
+        order_type: order_type(order.order_type) as i32,
+        order_state: order_state as i32,
+        timestamp: order.timestamp.unix_timestamp(),
+        expiry: order.expiry.unix_timestamp(),
+        stable: order.stable,
+    }
+}
+
+pub(super) fn quote_update(event: QuoteEvent) -> v1::QuoteUpdate {
+    let kind = match event.kind {
+        QuoteEventKind::Filled => v1::QuoteUpdateKind::Filled,
+        QuoteEventKind::PartiallyFilled => v1::QuoteUpdateKind::PartiallyFilled,
+        QuoteEventKind::Cancelled => v1::QuoteUpdateKind::Cancelled,
+        QuoteEventKind::Expired => v1::QuoteUpdateKind::Expired,
+    };
+
+    v1::QuoteUpdate {
+        id: event.id.to_string(),
+        order_id: event.order_id.to_string(),
+        kind: kind as i32,
+        filled_quantity: event.filled_quantity.to_string(),
+        remaining_quantity: event.remaining_quantity.to_string(),
+        execution_price: event.execution_price.map(|price| price.to_string()),
+        timestamp: event.timestamp.unix_timestamp(),
+    }
+}
+
+pub(super) fn position(position: TraderPosition) -> v1::Position {
+    let state = match position.state {
+        TraderPositionState::Open => v1::PositionState::Open,
+        TraderPositionState::Closing => v1::PositionState::Closing,
+        TraderPositionState::Rollover => v1::PositionState::Rollover,
+        TraderPositionState::Resizing => v1::PositionState::Resizing,
+    };
+
+    v1::Position {
+        id: position.id,
+        contract_symbol: contract_symbol(position.contract_symbol) as i32,
+        direction: direction(position.direction) as i32,
+        quantity: position.quantity,
+        leverage: position.leverage,
+        average_entry_price: position.average_entry_price,
+        liquidation_price: position.liquidation_price,
+        state: state as i32,
+        collateral: position.collateral,
+        expiry: position.expiry_timestamp.unix_timestamp(),
+        unrealized_pnl_sat: position.unrealized_pnl_sat,
+        stable: position.stable,
+    }
+}
+
+pub(super) fn parse_uuid(field: &str, value: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value).map_err(|e| Status::invalid_argument(format!("Invalid {field}: {e}")))
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<Decimal, Status> {
+    Decimal::from_str(value).map_err(|e| Status::invalid_argument(format!("Invalid {field}: {e}")))
+}
+
+fn contract_symbol(contract_symbol: ContractSymbol) -> v1::ContractSymbol {
+    match contract_symbol {
+        ContractSymbol::BtcUsd => v1::ContractSymbol::Btcusd,
+    }
+}
+
+fn direction(direction: Direction) -> v1::Direction {
+    match direction {
+        Direction::Long => v1::Direction::Long,
+        Direction::Short => v1::Direction::Short,
+    }
+}
+
+fn order_type(order_type: OrderType) -> v1::OrderType {
+    match order_type {
+        OrderType::Market => v1::OrderType::Market,
+        OrderType::Limit => v1::OrderType::Limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use tonic::Code;
+
+    fn trader_id() -> PublicKey {
+        PublicKey::from_str("02dd6abec97f9a748bf76ad502b004ce05d1b2d1f43a9e76bd7d85e767ffb022c9")
+            .unwrap()
+    }
+
+    fn limit_order_request() -> v1::SubmitOrderRequest {
+        v1::SubmitOrderRequest {
+            id: "02f9e7b4-3a56-4b4c-9d27-2b61f3a6e4c1".to_string(),
+            contract_symbol: v1::ContractSymbol::Btcusd as i32,
+            price: "42000.5".to_string(),
+            quantity: "100".to_string(),
+            direction: v1::Direction::Short as i32,
+            leverage: 2.0,
+            order_type: v1::OrderType::Limit as i32,
+            expiry: 1_700_000_000,
+            stable: false,
+            reduce_only: false,
+        }
+    }
+
+    #[test]
+    fn limit_order_request_is_converted() {
+        let new_order = new_order(limit_order_request(), trader_id()).unwrap();
+
+        assert_eq!(
+            new_order.id.to_string(),
+            "02f9e7b4-3a56-4b4c-9d27-2b61f3a6e4c1"
+        );
+        assert_eq!(new_order.price, dec!(42000.5));
+        assert_eq!(new_order.quantity, dec!(100));
+        assert_eq!(new_order.direction, Direction::Short);
+        assert_eq!(new_order.order_type, OrderType::Limit);
+        assert_eq!(new_order.trader_id, trader_id());
+        assert_eq!(new_order.expiry.unix_timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn market_order_ignores_price_and_gets_random_id() {
+        let request = v1::SubmitOrderRequest {
+            id: "".to_string(),
+            price: "".to_string(),
+            order_type: v1::OrderType::Market as i32,
+            ..limit_order_request()
+        };
+
+        let new_order = new_order(request, trader_id()).unwrap();
+
+        assert_eq!(new_order.price, Decimal::ZERO);
+        assert_eq!(new_order.order_type, OrderType::Market);
+    }
+
+    #[test]
+    fn invalid_order_requests_are_rejected() {
+        let unspecified_direction = v1::SubmitOrderRequest {
+            direction: v1::Direction::Unspecified as i32,
+            ..limit_order_request()
+        };
+        let invalid_quantity = v1::SubmitOrderRequest {
+            quantity: "a lot".to_string(),
+            ..limit_order_request()
+        };
+
+        for request in [unspecified_direction, invalid_quantity] {
+            let status = new_order(request, trader_id()).unwrap_err();
+
+            assert_eq!(status.code(), Code::InvalidArgument);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! The gRPC API of the coordinator, served alongside the REST API.
+//!
+//! It offers the same functionality to trading bots as the REST API and the websocket, but with
+//! typed messages and streams. The services are defined in the `coordinator-grpc` crate.
+
+use crate::admin;
+use crate::api_key;
+use crate::routes::AppState;
+use crate::AppError;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::ApiKeyScope;
+use coordinator_grpc::v1::node_server::NodeServer;
+use coordinator_grpc::v1::orderbook_server::OrderbookServer;
+use coordinator_grpc::v1::positions_server::PositionsServer;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tonic::transport::Server;
+use tonic::Request;
+use tonic::Status;
+
+mod convert;
+mod node;
+mod orderbook;
+mod positions;
+
+/// Serve the gRPC API on `address` until the coordinator shuts down.
+pub async fn serve(state: Arc<AppState>, address: SocketAddr) -> Result<()> {
+    let shutdown = state.shutdown.clone();
+
+    Server::builder()
+        .add_service(OrderbookServer::new(orderbook::OrderbookService::new(
+            state.clone(),
+        )))
+        .add_service(PositionsServer::new(positions::PositionsService::new(
+            state.clone(),
+        )))
+        .add_service(NodeServer::new(node::NodeService::new(state)))
+        .serve_with_shutdown(address, async move { shutdown.triggered().await })
+        .await?;
+
+    Ok(())
+}
+
+/// Authenticate a request by the API key in its metadata.
+///
+/// Returns the pubkey of the trader the key is bound to. Other than the REST API, the gRPC API
+/// does not accept requests signed with the trader's node key.
+async fn authenticate_trader<T>(
+    state: &Arc<AppState>,
+    request: &Request<T>,
+    scope: ApiKeyScope,
+) -> Result<PublicKey, Status> {
+    let headers = request.metadata().clone().into_headers();
+
+    api_key::authenticate(state, &headers, scope)
+        .await?
+        .ok_or_else(|| Status::unauthenticated("Missing API key"))
+}
+
+/// Authenticate a request by the admin API token in its metadata.
+async fn authenticate_admin<T>(state: &AppState, request: &Request<T>) -> Result<(), Status> {
+    let headers = request.metadata().clone().into_headers();
+
+    if !admin::is_admin(state, &headers).await {
+        tracing::warn!("Rejecting unauthenticated gRPC admin request");
+        return Err(Status::unauthenticated("Invalid admin API token"));
+    }
+
+    Ok(())
+}
+
+impl From<AppError> for Status {
+    fn from(error: AppError) -> Self {
+        match error {
+            AppError::InternalServerError(msg) => Status::internal(msg),
+            AppError::BadRequest(msg) | AppError::InvalidOrder(msg) => {
+                Status::invalid_argument(msg)
+            }
+            AppError::NoMatchFound(msg)
+            | AppError::ServiceUnavailable(msg)
+            | AppError::TradingHalted(msg) => Status::unavailable(msg),
+            AppError::Unauthorized => Status::unauthenticated(""),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::admin::get_balance;
+use crate::grpc::authenticate_admin;
+use crate::orderbook::trading_halt::HaltReason;
+use crate::routes::AppState;
+use axum::extract::State;
+use coordinator_grpc::v1;
+use coordinator_grpc::v1::node_server::Node;
+use std::sync::Arc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+/// Management of the coordinator's node, restricted to admins.
+pub struct NodeService {
+    state: Arc<AppState>,
+}
+
+impl NodeService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    fn trading_halt(&self) -> v1::TradingHalt {
+        let reason = self.state.trading_halt.get();
+
+        v1::TradingHalt {
+            halted: reason.is_some(),
+            reason: reason.map(|reason| reason.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Node for NodeService {
+    async fn get_node_info(
+        &self,
+        request: Request<v1::GetNodeInfoRequest>,
+    ) -> Result<Response<v1::NodeInfo>, Status> {
+        authenticate_admin(&self.state, &request).await?;
+
+        let node = &self.state.node.inner;
+
+        Ok(Response::new(v1::NodeInfo {
+            pubkey: node.info.pubkey.to_string(),
+            address: node.info.address.to_string(),
+            network: node.network.to_string(),
+        }))
+    }
+
+    async fn get_balance(
+        &self,
+        request: Request<v1::GetBalanceRequest>,
+    ) -> Result<Response<v1::Balance>, Status> {
+        authenticate_admin(&self.state, &request).await?;
+
+        let balance = get_balance(State(self.state.clone())).await?.0;
+
+        Ok(Response::new(v1::Balance {
+            lightning: balance.lightning,
+            onchain: balance.onchain,
+            dlc_channel: balance.dlc_channel,
+            onchain_available: balance.onchain_reserve.available_sats,
+        }))
+    }
+
+    async fn get_trading_halt(
+        &self,
+        request: Request<v1::GetTradingHaltRequest>,
+    ) -> Result<Response<v1::TradingHalt>, Status> {
+        authenticate_admin(&self.state, &request).await?;
+
+        Ok(Response::new(self.trading_halt()))
+    }
+
+    async fn halt_trading(
+        &self,
+        request: Request<v1::HaltTradingRequest>,
+    ) -> Result<Response<v1::TradingHalt>, Status> {
+        authenticate_admin(&self.state, &request).await?;
+
+        self.state.trading_halt.halt(HaltReason::Manual);
+
+        Ok(Response::new(self.trading_halt()))
+    }
+
+    async fn resume_trading(
+        &self,
+        request: Request<v1::ResumeTradingRequest>,
+    ) -> Result<Response<v1::TradingHalt>, Status> {
+        authenticate_admin(&self.state, &request).await?;
+
+        self.state.trading_halt.resume();
+
+        Ok(Response::new(self.trading_halt()))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::db;
+use crate::grpc::authenticate_trader;
+use crate::grpc::convert;
+use crate::orderbook;
+use crate::orderbook::routes::get_orders;
+use crate::orderbook::routes::submit_order;
+use crate::orderbook::routes::update_order;
+use crate::routes::AppState;
+use crate::routes::Database;
+use axum::extract::FromRef;
+use axum::extract::State;
+use commons::ApiKeyScope;
+use commons::Message;
+use commons::OrderState;
+use commons::OrderType;
+use coordinator_grpc::v1;
+use coordinator_grpc::v1::orderbook_server::Orderbook;
+use coordinator_grpc::v1::orderbook_update::Update;
+use futures::future;
+use futures::Stream;
+use futures::StreamExt;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+type UpdateStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+pub struct OrderbookService {
+    state: Arc<AppState>,
+}
+
+impl OrderbookService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl Orderbook for OrderbookService {
+    type StreamOrderbookStream = UpdateStream<v1::OrderbookUpdate>;
+    type StreamQuoteUpdatesStream = UpdateStream<v1::QuoteUpdate>;
+
+    async fn submit_order(
+        &self,
+        request: Request<v1::SubmitOrderRequest>,
+    ) -> Result<Response<v1::Order>, Status> {
+        let trader_id = authenticate_trader(&self.state, &request, ApiKeyScope::Trade).await?;
+        let new_order = convert::new_order(request.into_inner(), trader_id)?;
+
+        let order = submit_order(&self.state, new_order).await?;
+
+        Ok(Response::new(convert::order(order)))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<v1::CancelOrderRequest>,
+    ) -> Result<Response<v1::Order>, Status> {
+        let trader_id = authenticate_trader(&self.state, &request, ApiKeyScope::Trade).await?;
+        let order_id = convert::parse_uuid("order id", &request.into_inner().order_id)?;
+
+        let order = db::run(&self.state.pool, move |conn| {
+            Ok(orderbook::db::orders::get_with_id(conn, order_id)?)
+        })
+        .await
+        .map_err(|e| Status::internal(format!("Failed to load order: {e:#}")))?
+        .ok_or_else(|| Status::not_found(format!("Order not found {order_id}")))?;
+
+        // Do not reveal the existence of other traders' orders.
+        if order.trader_id != trader_id {
+            return Err(Status::not_found(format!("Order not found {order_id}")));
+        }
+
+        if order.order_type != OrderType::Limit || order.order_state != OrderState::Open {
+            return Err(Status::failed_precondition(format!(
+                "Only open limit orders can be cancelled, order {order_id} is {:?}",
+                order.order_state
+            )));
+        }
+
+        let order = update_order(&self.state, order_id, true).await?;
+
+        Ok(Response::new(convert::order(order)))
+    }
+
+    async fn get_orders(
+        &self,
+        _: Request<v1::GetOrdersRequest>,
+    ) -> Result<Response<v1::GetOrdersResponse>, Status> {
+        let orders = get_orders(State(Database::from_ref(&self.state)))
+            .await?
+            .0
+            .into_iter()
+            .map(convert::order)
+            .collect();
+
+        Ok(Response::new(v1::GetOrdersResponse { orders }))
+    }
+
+    async fn stream_orderbook(
+        &self,
+        _: Request<v1::StreamOrderbookRequest>,
+    ) -> Result<Response<Self::StreamOrderbookStream>, Status> {
+        // We subscribe before loading the snapshot, so that no update is missed in between.
+        let updates = BroadcastStream::new(self.state.tx_price_feed.subscribe());
+
+        let snapshot = get_orders(State(Database::from_ref(&self.state))).await?.0;
+        let snapshot = futures::stream::iter(snapshot).map(|order| {
+            Ok(v1::OrderbookUpdate {
+                update: Some(Update::NewOrder(convert::order(order))),
+            })
+        });
+
+        let updates = updates.filter_map(|message| {
+            let update = match message {
+                Ok(Message::NewOrder(order)) => Some(Update::NewOrder(convert::order(order))),
+                Ok(Message::Update(order)) => Some(Update::UpdatedOrder(convert::order(order))),
+                Ok(Message::DeleteOrder(order_id)) => {
+                    Some(Update::DeletedOrderId(order_id.to_string()))
+                }
+                Ok(_) => None,
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    return future::ready(Some(Err(lagged(skipped))));
+                }
+            };
+
+            future::ready(update.map(|update| {
+                Ok(v1::OrderbookUpdate {
+                    update: Some(update),
+                })
+            }))
+        });
+
+        Ok(Response::new(Box::pin(snapshot.chain(updates))))
+    }
+
+    async fn stream_quote_updates(
+        &self,
+        request: Request<v1::StreamQuoteUpdatesRequest>,
+    ) -> Result<Response<Self::StreamQuoteUpdatesStream>, Status> {
+        let trader_id = authenticate_trader(&self.state, &request, ApiKeyScope::Read).await?;
+
+        let updates =
+            BroadcastStream::new(self.state.maker_notifier.subscribe()).filter_map(move |update| {
+                let update = match update {
+                    Ok((maker_id, event)) if maker_id == trader_id => {
+                        Some(Ok(convert::quote_update(event)))
+                    }
+                    Ok(_) => None,
+                    Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(Err(lagged(skipped))),
+                };
+
+                future::ready(update)
+            });
+
+        Ok(Response::new(Box::pin(updates)))
+    }
+}
+
+/// The subscriber could not keep up, hence its view is incomplete. It has to subscribe again.
+fn lagged(skipped: u64) -> Status {
+    Status::data_loss(format!("Missed {skipped} updates, please subscribe again"))
+}
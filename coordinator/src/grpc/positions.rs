@@ -0,0 +1,52 @@
+use crate::grpc::authenticate_trader;
+use crate::grpc::convert;
+use crate::routes::close_position;
+use crate::routes::load_trader_positions;
+use crate::routes::AppState;
+use commons::ApiKeyScope;
+use coordinator_grpc::v1;
+use coordinator_grpc::v1::positions_server::Positions;
+use std::sync::Arc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+pub struct PositionsService {
+    state: Arc<AppState>,
+}
+
+impl PositionsService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl Positions for PositionsService {
+    async fn get_positions(
+        &self,
+        request: Request<v1::GetPositionsRequest>,
+    ) -> Result<Response<v1::GetPositionsResponse>, Status> {
+        let trader_id = authenticate_trader(&self.state, &request, ApiKeyScope::Read).await?;
+
+        let positions = load_trader_positions(&self.state, trader_id)
+            .await?
+            .into_iter()
+            .map(convert::position)
+            .collect();
+
+        Ok(Response::new(v1::GetPositionsResponse { positions }))
+    }
+
+    async fn close_position(
+        &self,
+        request: Request<v1::ClosePositionRequest>,
+    ) -> Result<Response<v1::Order>, Status> {
+        let trader_id = authenticate_trader(&self.state, &request, ApiKeyScope::Trade).await?;
+        let position_id = request.into_inner().position_id;
+
+        let order = close_position(&self.state, trader_id, position_id).await?;
+
+        Ok(Response::new(convert::order(order)))
+    }
+}
@@ -0,0 +1,293 @@
+use crate::node::Node;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::XOnlyPublicKey;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use diesel::RunQueryDsl;
+use ln_dlc_node::node::OracleInfo;
+use ln_dlc_node::ChainSourceConfig;
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::task::spawn_blocking;
+
+/// How long we wait for a dependency to respond before we consider it offline.
+const DEPENDENCY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Health status of a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ServiceStatus {
+    Online,
+    Offline,
+}
+
+/// Overall health of the coordinator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OverallStatus {
+    /// All dependencies are online.
+    Healthy,
+    /// A non-critical dependency is offline. The coordinator is still able to serve requests, but
+    /// some functionality might be limited, e.g. new trades could fail.
+    Degraded,
+    /// A critical dependency is offline. The coordinator is not able to serve requests.
+    Unhealthy,
+}
+
+/// Struct returned by the coordinator's health endpoint.
+#[derive(Debug, Serialize)]
+pub struct OverallCoordinatorHealth {
+    pub status: OverallStatus,
+    /// Critical: without the database the coordinator cannot do anything.
+    pub postgres: ServiceStatus,
+    /// Critical: without the chain source, i.e. esplora or bitcoind, we cannot sync our wallets.
+    pub chain_source: ServiceStatus,
+    /// Whether the chain source can give us up-to-date fee estimates.
+    pub fee_estimates: ServiceStatus,
+    /// Status per oracle public key.
+    pub oracles: HashMap<String, ServiceStatus>,
+    /// Whether we are connected to all peers we have public channels with.
+    pub ldk_peers: ServiceStatus,
+}
+
+impl OverallCoordinatorHealth {
+    pub fn is_ready(&self) -> bool {
+        self.status != OverallStatus::Unhealthy
+    }
+}
+
+/// Health checks for the dependencies of the coordinator.
+#[derive(Clone)]
+pub struct Health {
+    node: Node,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    client: Client,
+    chain_source: ChainSourceConfig,
+    oracles: Vec<OracleInfo>,
+}
+
+impl Health {
+    pub fn new(
+        node: Node,
+        pool: Pool<ConnectionManager<PgConnection>>,
+        chain_source: ChainSourceConfig,
+        oracles: Vec<OracleInfo>,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(DEPENDENCY_TIMEOUT)
+            .build()
+            .expect("Failed to build reqwest client");
+
+        Self {
+            node,
+            pool,
+            client,
+            chain_source,
+            oracles,
+        }
+    }
+
+    /// Check all dependencies of the coordinator.
+    pub async fn get_health(&self) -> OverallCoordinatorHealth {
+        let (postgres, chain_source, fee_estimates, oracles, ldk_peers) = tokio::join!(
+            self.check_postgres(),
+            self.check_chain_source(),
+            self.check_fee_estimates(),
+            self.check_oracles(),
+            self.check_ldk_peers(),
+        );
+
+        let status = if postgres == ServiceStatus::Offline || chain_source == ServiceStatus::Offline
+        {
+            OverallStatus::Unhealthy
+        } else if fee_estimates == ServiceStatus::Offline
+            || ldk_peers == ServiceStatus::Offline
+            || oracles
+                .values()
+                .any(|status| *status == ServiceStatus::Offline)
+        {
+            OverallStatus::Degraded
+        } else {
+            OverallStatus::Healthy
+        };
+
+        OverallCoordinatorHealth {
+            status,
+            postgres,
+            chain_source,
+            fee_estimates,
+            oracles,
+            ldk_peers,
+        }
+    }
+
+    async fn check_postgres(&self) -> ServiceStatus {
+        let pool = self.pool.clone();
+        let result = spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            diesel::sql_query("SELECT 1").execute(&mut conn)?;
+
+            anyhow::Ok(())
+        })
+        .await
+        .context("Postgres check did not complete")
+        .and_then(|result| result);
+
+        to_status("postgres", result)
+    }
+
+    /// With esplora, the chain source is online as long as one of the backends is reachable, as
+    /// the node fails over between them.
+    async fn check_chain_source(&self) -> ServiceStatus {
+        let result = match &self.chain_source {
+            ChainSourceConfig::Esplora { urls } => {
+                let mut result = Err(anyhow!("No esplora backend configured"));
+                for esplora_url in urls.iter() {
+                    let url = format!("{esplora_url}/blocks/tip/height");
+                    result = self.check_endpoint(&url).await;
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+
+                result
+            }
+            ChainSourceConfig::Bitcoind { .. } => {
+                let chain_source = self.node.inner.chain_source.clone();
+                spawn_blocking(move || chain_source.get_height().map(|_| ()))
+                    .await
+                    .context("Chain source check did not complete")
+                    .and_then(|result| result)
+            }
+        };
+
+        to_status("chain_source", result)
+    }
+
+    async fn check_fee_estimates(&self) -> ServiceStatus {
+        let result = match &self.chain_source {
+            ChainSourceConfig::Esplora { urls } => {
+                let mut result = Err(anyhow!("No esplora backend configured"));
+                for esplora_url in urls.iter() {
+                    result = self.check_fee_estimates_of(esplora_url).await;
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+
+                result
+            }
+            ChainSourceConfig::Bitcoind { .. } => {
+                let chain_source = self.node.inner.chain_source.clone();
+                spawn_blocking(move || chain_source.get_fee_estimates(&[1]).map(|_| ()))
+                    .await
+                    .context("Fee estimates check did not complete")
+                    .and_then(|result| result)
+            }
+        };
+
+        to_status("fee_estimates", result)
+    }
+
+    async fn check_fee_estimates_of(&self, esplora_url: &str) -> Result<()> {
+        let url = format!("{esplora_url}/fee-estimates");
+        let estimates = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .context("could not send request")?
+            .error_for_status()?
+            .json::<HashMap<String, f64>>()
+            .await?;
+
+        ensure!(!estimates.is_empty(), "No fee estimates available");
+
+        Ok(())
+    }
+
+    async fn check_oracles(&self) -> HashMap<String, ServiceStatus> {
+        let mut oracles = HashMap::new();
+        for oracle in self.oracles.iter() {
+            let status = self.check_oracle(oracle.public_key).await;
+
+            oracles.insert(oracle.public_key.to_string(), status);
+        }
+
+        oracles
+    }
+
+    /// Check if the oracle with the given public key is reachable.
+    pub async fn check_oracle(&self, public_key: XOnlyPublicKey) -> ServiceStatus {
+        let result = async {
+            let oracle = self
+                .oracles
+                .iter()
+                .find(|oracle| oracle.public_key == public_key)
+                .with_context(|| format!("Unknown oracle {public_key}"))?;
+
+            self.check_endpoint(&oracle.public_key_url()).await
+        }
+        .await;
+
+        to_status("oracle", result)
+    }
+
+    async fn check_ldk_peers(&self) -> ServiceStatus {
+        let node = self.node.inner.clone();
+        let result = spawn_blocking(move || {
+            let connected_peers = node
+                .peer_manager
+                .get_peer_node_ids()
+                .into_iter()
+                .map(|(peer, _)| peer)
+                .collect::<Vec<_>>();
+
+            let disconnected_peers = node
+                .channel_manager
+                .list_channels()
+                .iter()
+                .filter(|c| c.is_public && !connected_peers.contains(&c.counterparty.node_id))
+                .map(|c| c.counterparty.node_id)
+                .collect::<Vec<_>>();
+
+            if !disconnected_peers.is_empty() {
+                bail!("Not connected to public channel peers: {disconnected_peers:?}");
+            }
+
+            Ok(())
+        })
+        .await
+        .context("LDK peers check did not complete")
+        .and_then(|result| result);
+
+        to_status("ldk_peers", result)
+    }
+
+    async fn check_endpoint(&self, url: &str) -> Result<()> {
+        tracing::trace!(%url, "Sending request to check health");
+        self.client
+            .get(url)
+            .send()
+            .await
+            .context("could not send request")?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+fn to_status(dependency: &str, result: Result<()>) -> ServiceStatus {
+    match result {
+        Ok(()) => ServiceStatus::Online,
+        Err(e) => {
+            tracing::warn!(dependency, "Health check failed: {e:#}");
+            ServiceStatus::Offline
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Leader election between coordinators sharing the same database.
+//!
+//! Only one coordinator may run the Lightning node and the orderbook, as both keep state in memory
+//! which can't be shared. The coordinators compete for a Postgres advisory lock: the one holding it
+//! is the leader, the others wait on standby until it goes away, see [`crate::standby`].
+//!
+//! The lock is held by the session of a dedicated connection, hence Postgres releases it as soon as
+//! the leader exits or loses its connection to the database. In the latter case the leader may
+//! still be running, hence a coordinator which acquired the lock holds it for [`TAKEOVER_DELAY`]
+//! before it starts its node: within that time the previous leader notices that it lost the lock
+//! and stops, or aborts if it does not manage to stop in time.
+//!
+//! Besides the database, the coordinators have to share the data dir, e.g. on a network volume, as
+//! it holds the seed of the node.
+
+use crate::shutdown::Shutdown;
+use anyhow::Context;
+use anyhow::Result;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Bool;
+use diesel::Connection;
+use diesel::PgConnection;
+use diesel::QueryableByName;
+use diesel::RunQueryDsl;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::spawn_blocking;
+use tokio::task::JoinHandle;
+
+/// The key of the advisory lock held by the leader.
+const LEADER_LOCK_KEY: i64 = 10101;
+
+/// How often a coordinator on standby tries to become the leader.
+const ACQUIRE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the leader checks that it still holds the lock.
+pub(crate) const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the leader has to stop once it is shutting down, before the process is aborted.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a coordinator holds the lock before it acts as the leader, so that the previous leader
+/// has noticed the loss of the lock and stopped.
+pub(crate) const TAKEOVER_DELAY: Duration = CHECK_INTERVAL
+    .saturating_add(SHUTDOWN_TIMEOUT)
+    .saturating_add(Duration::from_secs(5));
+
+#[derive(QueryableByName)]
+struct AdvisoryLock {
+    #[diesel(sql_type = Bool)]
+    acquired: bool,
+}
+
+#[derive(Clone)]
+pub struct LeaderElection {
+    database_url: String,
+    conn: Arc<Mutex<PgConnection>>,
+}
+
+impl LeaderElection {
+    pub fn connect(database_url: &str) -> Result<Self> {
+        let conn = PgConnection::establish(database_url)
+            .context("Failed to connect to database for leader election")?;
+
+        Ok(Self {
+            database_url: database_url.to_string(),
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Try to acquire the leader lock, returning whether we hold it now.
+    ///
+    /// Holding the lock does not make us the leader yet, see [`LeaderElection::fence`].
+    pub async fn try_acquire(&self) -> Result<bool> {
+        let election = self.clone();
+        spawn_blocking(move || {
+            let mut conn = election.conn.lock();
+
+            let result = diesel::sql_query("SELECT pg_try_advisory_lock($1) AS acquired")
+                .bind::<BigInt, _>(LEADER_LOCK_KEY)
+                .get_result::<AdvisoryLock>(&mut *conn);
+
+            match result {
+                Ok(lock) => Ok(lock.acquired),
+                Err(e) => {
+                    // The connection may be broken, so we start over with a new one.
+                    if let Ok(new_conn) = PgConnection::establish(&election.database_url) {
+                        *conn = new_conn;
+                    }
+
+                    Err(e).context("Failed to acquire leader lock")
+                }
+            }
+        })
+        .await
+        .context("Failed to acquire leader lock")?
+    }
+
+    /// Wait until we are the leader, returning `false` if the coordinator is shut down before.
+    pub async fn wait(&self, shutdown: &Shutdown) -> bool {
+        loop {
+            match self.try_acquire().await {
+                Ok(true) => {
+                    if self.fence(shutdown).await {
+                        tracing::info!("Became the leader");
+                        return true;
+                    }
+
+                    if shutdown.is_triggered() {
+                        return false;
+                    }
+                }
+                Ok(false) => {
+                    tracing::debug!("Another coordinator is the leader");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check whether we can become the leader: {e:#}");
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(ACQUIRE_INTERVAL) => {}
+                _ = shutdown.triggered() => return false,
+            }
+        }
+    }
+
+    /// Hold the lock for [`TAKEOVER_DELAY`], returning whether we still hold it then and may act as
+    /// the leader.
+    ///
+    /// Returns `false` if the coordinator is shut down in the meantime.
+    pub async fn fence(&self, shutdown: &Shutdown) -> bool {
+        tracing::info!(
+            "Acquired the leader lock, waiting {TAKEOVER_DELAY:?} for a previous leader to stop"
+        );
+
+        tokio::select! {
+            _ = tokio::time::sleep(TAKEOVER_DELAY) => {}
+            _ = shutdown.triggered() => return false,
+        }
+
+        // The lock is reentrant, hence this succeeds if our session still holds it.
+        match self.try_acquire().await {
+            Ok(acquired) => acquired,
+            Err(e) => {
+                tracing::warn!("Lost the leader lock while waiting to take over: {e:#}");
+                false
+            }
+        }
+    }
+
+    /// Check that the connection holding the lock is still alive.
+    ///
+    /// A connection which does not answer within [`CHECK_INTERVAL`] counts as lost, as we can't
+    /// tell whether the database still sees our session.
+    async fn check(&self) -> Result<()> {
+        let conn = self.conn.clone();
+        let check =
+            spawn_blocking(move || diesel::sql_query("SELECT 1").execute(&mut *conn.lock()));
+
+        tokio::time::timeout(CHECK_INTERVAL, check)
+            .await
+            .context("Connection holding the leader lock did not respond")?
+            .context("Failed to check the leader lock")?
+            .context("Lost the connection holding the leader lock")?;
+
+        Ok(())
+    }
+
+    /// Shut down as soon as we can't be sure to still be the leader, i.e. if the connection holding
+    /// the lock is lost or does not respond, as a coordinator on standby may take over then.
+    ///
+    /// Once the coordinator is shutting down, for whatever reason, it has [`SHUTDOWN_TIMEOUT`] to
+    /// stop before the process is aborted. A coordinator which acquired the lock after we lost it
+    /// relies on this, see [`LeaderElection::fence`].
+    ///
+    /// The lock is released once the [`LeaderElection`] is dropped, unless the connection holding
+    /// it has been lost before.
+    pub fn monitor(&self, shutdown: Shutdown) -> JoinHandle<()> {
+        let election = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+                    _ = shutdown.triggered() => break,
+                }
+
+                if let Err(e) = election.check().await {
+                    tracing::error!("Lost the leader lock, shutting down: {e:#}");
+                    shutdown.trigger();
+                    break;
+                }
+            }
+
+            // The lock must not be held up by us waiting for the deadline.
+            drop(election);
+
+            tokio::time::sleep(SHUTDOWN_TIMEOUT).await;
+
+            tracing::error!("Coordinator did not stop within {SHUTDOWN_TIMEOUT:?}, aborting");
+            std::process::abort();
+        })
+    }
+}
@@ -16,28 +16,43 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde_json::json;
 use settings::Settings;
+use tokio::task::JoinError;
 
 mod collaborative_revert;
 mod payout_curve;
 
 pub mod admin;
+pub mod alert;
+pub mod api_key;
+pub mod api_version;
 pub mod backup;
 pub mod cli;
+pub mod config;
 pub mod db;
+pub mod device_session;
 pub mod dlc_handler;
+pub mod fee_estimates;
+pub mod grpc;
+pub mod health;
+pub mod leader;
 pub mod logger;
 pub mod message;
 pub mod metrics;
 pub mod node;
 pub mod notifications;
+pub mod openapi;
 pub mod orderbook;
 pub mod position;
+pub mod pubsub;
 pub mod routes;
 pub mod routing_fee;
 pub mod scheduler;
 pub mod schema;
 pub mod settings;
+pub mod shutdown;
+pub mod standby;
 pub mod storage;
+pub mod trace;
 pub mod trade;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
@@ -55,6 +70,7 @@ pub enum AppError {
     NoMatchFound(String),
     InvalidOrder(String),
     ServiceUnavailable(String),
+    TradingHalted(String),
     Unauthorized,
 }
 
@@ -66,6 +82,7 @@ impl IntoResponse for AppError {
             AppError::NoMatchFound(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             AppError::InvalidOrder(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::TradingHalted(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "".to_string()),
         };
 
@@ -77,6 +94,12 @@ impl IntoResponse for AppError {
     }
 }
 
+impl From<JoinError> for AppError {
+    fn from(e: JoinError) -> Self {
+        AppError::InternalServerError(format!("Task did not complete: {e:#}"))
+    }
+}
+
 /// Check if the liquidity is sufficient to open a JIT channel from the coordinator
 pub fn is_liquidity_sufficient(
     settings: &Settings,
@@ -1,5 +1,10 @@
 use anyhow::Context;
 use anyhow::Result;
+use opentelemetry::runtime;
+use opentelemetry::sdk::trace;
+use opentelemetry::sdk::Resource;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
 use time::macros::format_description;
 use tracing::metadata::LevelFilter;
 use tracing_subscriber::filter::Directive;
@@ -11,8 +16,18 @@ use tracing_subscriber::Layer;
 
 const RUST_LOG_ENV: &str = "RUST_LOG";
 
+/// The name under which the spans of the coordinator are exported.
+const SERVICE_NAME: &str = "coordinator";
+
 // Configure and initialise tracing subsystem
-pub fn init_tracing(level: LevelFilter, json_format: bool, tokio_console: bool) -> Result<()> {
+//
+// If an `otlp_endpoint` is given, spans are also exported to the OpenTelemetry collector behind it.
+pub fn init_tracing(
+    level: LevelFilter,
+    json_format: bool,
+    tokio_console: bool,
+    otlp_endpoint: Option<&str>,
+) -> Result<()> {
     if level == LevelFilter::OFF {
         return Ok(());
     }
@@ -48,6 +63,26 @@ pub fn init_tracing(level: LevelFilter, json_format: bool, tokio_console: bool)
         None
     };
 
+    let otel_layer = match otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", SERVICE_NAME),
+                ])))
+                .install_batch(runtime::Tokio)
+                .context("Failed to install OTLP exporter")?;
+
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+
     // Parse additional log directives from env variable
     let filter = match std::env::var_os(RUST_LOG_ENV).map(|s| s.into_string()) {
         Some(Ok(env)) => {
@@ -80,6 +115,7 @@ pub fn init_tracing(level: LevelFilter, json_format: bool, tokio_console: bool)
     tracing_subscriber::registry()
         .with(filter)
         .with(console_layer)
+        .with(otel_layer)
         .with(fmt_layer)
         .try_init()
         .context("Failed to init tracing")?;
@@ -89,6 +125,11 @@ pub fn init_tracing(level: LevelFilter, json_format: bool, tokio_console: bool)
     Ok(())
 }
 
+/// Export the spans which have not been sent to the OpenTelemetry collector yet.
+pub fn shutdown_tracing() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
 /// Initialise tracing for tests
 #[cfg(test)]
 pub(crate) fn init_tracing_for_test() {
@@ -1,4 +1,6 @@
+use crate::db;
 use crate::db::user;
+use crate::db::user_presence;
 use crate::notifications::FcmToken;
 use crate::notifications::Notification;
 use crate::notifications::NotificationKind;
@@ -14,11 +16,11 @@ use futures::FutureExt;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
-use tokio::task::spawn_blocking;
 
 /// This value is arbitrarily set to 100 and defines theff message accepted in the message
 /// channel buffer.
@@ -37,6 +39,9 @@ pub enum OrderbookMessage {
 pub struct NewUserMessage {
     pub new_user: PublicKey,
     pub sender: Sender<Message>,
+    /// The user has logged in on a new device, so the device which has been connected so far has
+    /// to be told to stop.
+    pub supersedes_session: bool,
 }
 
 pub fn spawn_delivering_messages_to_authenticated_users(
@@ -55,9 +60,18 @@ pub fn spawn_delivering_messages_to_authenticated_users(
             loop {
                 match user_feed.recv().await {
                     Ok(new_user_msg) => {
-                        traders
-                            .write()
-                            .insert(new_user_msg.new_user, new_user_msg.sender);
+                        let trader_id = new_user_msg.new_user;
+                        let previous = traders.write().insert(trader_id, new_user_msg.sender);
+
+                        if let (true, Some(previous)) = (new_user_msg.supersedes_session, previous)
+                        {
+                            if let Err(e) = previous.send(Message::SessionSuperseded).await {
+                                tracing::debug!(
+                                    %trader_id,
+                                    "Could not notify superseded device: {e:#}"
+                                );
+                            }
+                        }
                     }
                     Err(RecvError::Closed) => {
                         tracing::error!("New user message sender died! Channel closed");
@@ -102,10 +116,6 @@ async fn process_orderbook_message(
     notification_sender: &Sender<Notification>,
     notification: OrderbookMessage,
 ) -> Result<()> {
-    let mut conn = spawn_blocking(move || pool.get())
-        .await
-        .expect("task to complete")?;
-
     match notification {
         OrderbookMessage::TraderMessage {
             trader_id,
@@ -116,26 +126,49 @@ async fn process_orderbook_message(
 
             let trader = authenticated_users.read().get(&trader_id).cloned();
 
-            match trader {
-                Some(sender) => {
-                    if let Err(e) = sender.send(message).await {
+            let sent_via_websocket = match trader {
+                Some(sender) => match sender.send(message).await {
+                    Ok(()) => true,
+                    Err(e) => {
                         tracing::warn!(%trader_id, "Connection lost to trader: {e:#}");
-                    } else {
-                        tracing::trace!(
-                            %trader_id,
-                            "Skipping optional push notifications as the user was successfully \
-                             notified via the websocket"
-                        );
-                        return Ok(());
+                        false
                     }
+                },
+                None => {
+                    tracing::warn!(%trader_id, "Trader is not connected");
+                    false
                 }
-                None => tracing::warn!(%trader_id, "Trader is not connected"),
             };
 
-            let user = user::by_id(&mut conn, trader_id.to_string())
-                .context("Failed to get user by ID")?;
+            let notification_kind = match notification {
+                Some(notification_kind) => notification_kind,
+                None => return Ok(()),
+            };
+
+            let (user, presence) = db::run(&pool, move |conn| {
+                let user = user::by_id(conn, trader_id.to_string())?;
+                let presence = user_presence::get(conn, trader_id)?;
+                Ok((user, presence))
+            })
+            .await
+            .context("Failed to load user")?;
+
+            // A message is only handed over to the websocket connection, which may have been lost
+            // without us noticing yet. Hence we only rely on it if the trader has been seen
+            // recently.
+            let is_online = presence
+                .map(|presence| presence.is_online(OffsetDateTime::now_utc()))
+                .unwrap_or(false);
+            if sent_via_websocket && is_online {
+                tracing::trace!(
+                    %trader_id,
+                    "Skipping optional push notifications as the user was successfully \
+                     notified via the websocket"
+                );
+                return Ok(());
+            }
 
-            if let (Some(notification_kind), Some(user)) = (notification, user) {
+            if let Some(user) = user {
                 tracing::debug!(%trader_id, "Sending push notification to user");
 
                 let fcm_token = FcmToken::new(user.fcm_token)?;
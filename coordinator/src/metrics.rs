@@ -6,8 +6,10 @@ use dlc_manager::subchannel::SubChannelState;
 use lazy_static::lazy_static;
 use lightning::ln::channelmanager::ChannelDetails;
 use opentelemetry::global;
+use opentelemetry::metrics::Counter;
 use opentelemetry::metrics::Meter;
 use opentelemetry::metrics::ObservableGauge;
+use opentelemetry::metrics::UpDownCounter;
 use opentelemetry::sdk::export::metrics::aggregation;
 use opentelemetry::sdk::metrics::controllers;
 use opentelemetry::sdk::metrics::processors;
@@ -48,6 +50,18 @@ lazy_static! {
         .u64_observable_gauge("punished_dlc_channel_amount")
         .with_description("Number of punished DLC channels")
         .init();
+    pub static ref DLC_CHANNELS_IN_PROTOCOL: ObservableGauge<u64> = METER
+        .u64_observable_gauge("dlc_channels_in_protocol")
+        .with_description("Number of DLC channels in a state of a protocol with the trader")
+        .init();
+    pub static ref DLC_CHANNELS_STUCK: ObservableGauge<u64> = METER
+        .u64_observable_gauge("dlc_channels_stuck")
+        .with_description("Number of DLC channels in a state of a protocol for too long")
+        .init();
+    pub static ref DLC_CHANNEL_PROTOCOL_STATE_MAX_AGE_SECONDS: ObservableGauge<u64> = METER
+        .u64_observable_gauge("dlc_channel_protocol_state_max_age_seconds")
+        .with_description("Longest time a DLC channel has been in a state of a protocol")
+        .init();
 
     // general node metrics
     pub static ref CONNECTED_PEERS: ObservableGauge<u64> = METER
@@ -68,6 +82,30 @@ lazy_static! {
         .i64_observable_gauge("position_margin_sats")
         .with_description("Current open position margin in sats")
         .init();
+
+    // market metrics
+    pub static ref TRADING_VOLUME_CONTRACTS: ObservableGauge<f64> = METER
+        .f64_observable_gauge("trading_volume_contracts")
+        .with_description("Traded volume in contracts within a rolling window")
+        .init();
+
+    // order metrics
+    pub static ref MATCH_EXECUTION_TIMEOUTS: Counter<u64> = METER
+        .u64_counter("match_execution_timeouts_total")
+        .with_description("Number of matches reverted because they were not executed in time")
+        .init();
+    pub static ref NEW_ORDERS_QUEUED: UpDownCounter<i64> = METER
+        .i64_up_down_counter("orderbook_new_orders_queued")
+        .with_description("Number of new orders waiting to be processed")
+        .init();
+    pub static ref NEW_ORDERS_IN_PROGRESS: UpDownCounter<i64> = METER
+        .i64_up_down_counter("orderbook_new_orders_in_progress")
+        .with_description("Number of new orders being processed")
+        .init();
+    pub static ref NEW_ORDERS_OVERLOADED: Counter<u64> = METER
+        .u64_counter("orderbook_new_orders_overloaded_total")
+        .with_description("Number of new orders rejected because the orderbook was overloaded")
+        .init();
 }
 
 pub fn init_meter() -> PrometheusExporter {
@@ -84,6 +122,7 @@ pub fn init_meter() -> PrometheusExporter {
 pub fn collect(node: Node) {
     let cx = opentelemetry::Context::current();
     position_metrics(&cx, &node);
+    volume_metrics(&cx, &node);
 
     let inner_node = node.inner;
     if let Ok(dlc_channels) = inner_node.list_sub_channels() {
@@ -207,6 +246,53 @@ fn position_metrics(cx: &Context, node: &Node) {
     );
 }
 
+fn volume_metrics(cx: &Context, node: &Node) {
+    let mut conn = match node.pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::error!("Failed to get pool connection. Error: {e:?}");
+            return;
+        }
+    };
+
+    let mut volume_1h = 0.0;
+    let mut volume_24h = 0.0;
+    let mut volume_7d = 0.0;
+
+    for contract_symbol in [ContractSymbol::BtcUsd] {
+        let stats = match crate::trade::stats::get_market_stats(&mut conn, contract_symbol) {
+            Ok(stats) => stats,
+            Err(e) => {
+                tracing::error!(%contract_symbol, "Failed to get market stats. Error: {e:?}");
+                continue;
+            }
+        };
+
+        observe_volume(cx, &contract_symbol.label(), "1h", stats.volume_1h);
+        observe_volume(cx, &contract_symbol.label(), "24h", stats.volume_24h);
+        observe_volume(cx, &contract_symbol.label(), "7d", stats.volume_7d);
+
+        volume_1h += stats.volume_1h;
+        volume_24h += stats.volume_24h;
+        volume_7d += stats.volume_7d;
+    }
+
+    observe_volume(cx, "all", "1h", volume_1h);
+    observe_volume(cx, "all", "24h", volume_24h);
+    observe_volume(cx, "all", "7d", volume_7d);
+}
+
+fn observe_volume(cx: &Context, symbol: &str, window: &'static str, volume: f32) {
+    TRADING_VOLUME_CONTRACTS.observe(
+        cx,
+        volume as f64,
+        &[
+            KeyValue::new("symbol", symbol.to_string()),
+            KeyValue::new("window", window),
+        ],
+    );
+}
+
 fn channel_metrics(cx: &Context, channels: Vec<ChannelDetails>) {
     for channel_detail in channels {
         let key_values = [
@@ -4,36 +4,41 @@ use crate::decimal_from_f32;
 use crate::node::storage::NodeStorage;
 use crate::orderbook::db::matches;
 use crate::orderbook::db::orders;
-use crate::payout_curve;
 use crate::position::models::NewPosition;
 use crate::position::models::Position;
 use crate::position::models::PositionState;
 use crate::storage::CoordinatorTenTenOneStorage;
+use crate::trace::DlcSessions;
 use crate::trade::models::NewTrade;
+use crate::trade::replay::TradeInputs;
 use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
+use bdk::FeeRate;
 use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::PublicKey;
-use commons::order_matching_fee_taker;
+use commons::order_matching_fee;
+use commons::Clock;
 use commons::MatchState;
+use commons::Order;
 use commons::OrderState;
 use commons::TradeParams;
+use diesel::connection::AnsiTransactionManager;
+use diesel::connection::TransactionManager;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
+use diesel::r2d2::PooledConnection;
 use diesel::Connection;
 use diesel::PgConnection;
 use dlc_manager::channel::signed_channel::SignedChannel;
 use dlc_manager::channel::signed_channel::SignedChannelState;
-use dlc_manager::contract::contract_input::ContractInput;
-use dlc_manager::contract::contract_input::ContractInputInfo;
-use dlc_manager::contract::contract_input::OracleInput;
 use dlc_manager::ContractId;
 use dlc_manager::DlcChannelId;
 use dlc_messages::ChannelMessage;
 use dlc_messages::Message;
+use lightning::chain::chaininterface::ConfirmationTarget;
 use lightning::ln::ChannelId;
 use lightning::util::config::UserConfig;
 use ln_dlc_node::dlc_message::DlcMessage;
@@ -43,25 +48,33 @@ use ln_dlc_node::node::dlc_message_name;
 use ln_dlc_node::node::event::NodeEvent;
 use ln_dlc_node::node::RunningNode;
 use ln_dlc_node::WalletSettings;
+use payout_curve::PayoutCurveParams;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio::sync::RwLock;
 use tracing::instrument;
-use trade::cfd::calculate_long_liquidation_price;
-use trade::cfd::calculate_margin;
-use trade::cfd::calculate_short_liquidation_price;
-use trade::Direction;
+use tracing::Instrument;
+use trade::pricing;
 use uuid::Uuid;
 
+pub mod attestations;
+pub mod channel_open_status;
 pub mod connection;
+pub mod execution_queue;
 pub mod expired_positions;
 pub mod rollover;
+pub mod rollover_scheduler;
 pub mod routing_fees;
 pub mod storage;
+pub mod stuck_channels;
 pub mod unrealized_pnl;
 
+/// The leverage the coordinator takes on in every trade.
+pub const COORDINATOR_LEVERAGE: f32 = 2.0;
+
 #[derive(Debug, Clone)]
 pub struct NodeSettings {
     // At times, we want to disallow opening new positions (e.g. before
@@ -70,16 +83,29 @@ pub struct NodeSettings {
     pub max_allowed_tx_fee_rate_when_opening_channel: Option<u32>,
     // Defines if we want to open jit channels
     pub jit_channels_enabled: bool,
+    pub jit_channel_min_size_sats: u64,
+    pub jit_channel_max_size_sats: Option<u64>,
     /// Defines the sats/vbyte to be used for all transactions within the sub-channel
     pub contract_tx_fee_rate: u64,
+    /// Amount of on-chain funds reserved for fee-bumping.
+    pub on_chain_reserve_sats: u64,
+    pub fee_rate_overrides: HashMap<ConfirmationTarget, FeeRate>,
+    /// How the payout curves of new DLCs are discretized.
+    pub payout_curve_params: PayoutCurveParams,
+    /// The order-matching fee of the taker, as a fraction of the notional value of the order.
+    pub taker_fee_rate: Decimal,
 }
 
 impl NodeSettings {
-    fn to_wallet_settings(&self) -> WalletSettings {
+    pub fn to_wallet_settings(&self) -> WalletSettings {
         WalletSettings {
             max_allowed_tx_fee_rate_when_opening_channel: self
                 .max_allowed_tx_fee_rate_when_opening_channel,
             jit_channels_enabled: self.jit_channels_enabled,
+            jit_channel_min_size_sats: self.jit_channel_min_size_sats,
+            jit_channel_max_size_sats: self.jit_channel_max_size_sats,
+            on_chain_reserve_sats: self.on_chain_reserve_sats,
+            fee_rate_overrides: self.fee_rate_overrides.clone(),
         }
     }
 }
@@ -87,9 +113,13 @@ impl NodeSettings {
 #[derive(Clone)]
 pub struct Node {
     pub inner: Arc<node::Node<CoordinatorTenTenOneStorage, NodeStorage>>,
-    _running: Arc<RunningNode>,
+    running: Arc<RunningNode>,
     pub pool: Pool<ConnectionManager<PgConnection>>,
     settings: Arc<RwLock<NodeSettings>>,
+    /// The time expiries and rollovers are based on.
+    pub clock: Arc<dyn Clock>,
+    /// The trace IDs of the DLC protocols we are running with traders.
+    pub dlc_sessions: Arc<DlcSessions>,
 }
 
 impl Node {
@@ -98,15 +128,23 @@ impl Node {
         running: RunningNode,
         pool: Pool<ConnectionManager<PgConnection>>,
         settings: NodeSettings,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             inner,
             pool,
             settings: Arc::new(RwLock::new(settings)),
-            _running: Arc::new(running),
+            running: Arc::new(running),
+            clock,
+            dlc_sessions: Arc::new(DlcSessions::default()),
         }
     }
 
+    /// Stop the LDK background processor, which persists the channel manager one last time.
+    pub async fn stop(&self) {
+        self.running.stop().await;
+    }
+
     pub async fn update_settings(&self, settings: NodeSettings) {
         tracing::info!(?settings, "Updating node settings");
         *self.settings.write().await = settings.clone();
@@ -140,27 +178,22 @@ impl Node {
         !usable_channels.is_empty()
     }
 
-    pub async fn trade(&self, trade_params: &TradeParams) -> Result<()> {
+    /// Execute the match of the trader's order.
+    ///
+    /// If the DLC protocol could not be started, the execution is queued for a retry and
+    /// [`TradeOutcome::PendingRetry`] is returned.
+    pub async fn trade(&self, trade_params: &TradeParams) -> Result<TradeOutcome> {
         let mut connection = self.pool.get()?;
 
         let order_id = trade_params.filled_with.order_id;
         let trader_id = trade_params.pubkey;
 
-        match self.trade_internal(trade_params, &mut connection).await {
-            Ok(()) => {
-                tracing::info!(
-                    %trader_id,
-                    %order_id,
-                    "Successfully processed match, setting match to Filled"
-                );
-
-                update_order_and_match(
-                    &mut connection,
-                    order_id,
-                    MatchState::Filled,
-                    OrderState::Taken,
-                )?;
-                Ok(())
+        let execution = match MatchExecution::start(&self.pool, order_id, self.clock.now()) {
+            Ok(execution) => execution,
+            Err(e) if e.is::<MatchAlreadyExecuting>() => {
+                return Err(e).with_context(|| {
+                    format!("Failed to trade with peer {trader_id} for order {order_id}")
+                });
             }
             Err(e) => {
                 if let Err(e) = update_order_and_match(
@@ -172,39 +205,85 @@ impl Node {
                     tracing::error!(%trader_id, %order_id, "Failed to update order and match: {e}");
                 };
 
-                Err(e).with_context(|| {
+                return Err(e).with_context(|| {
                     format!("Failed to trade with peer {trader_id} for order {order_id}")
-                })
+                });
+            }
+        };
+
+        tracing::info!(%trader_id, %order_id, "Executing match");
+
+        match self
+            .execute_trade_action(&mut connection, trade_params, execution.order.stable)
+            .instrument(self.dlc_sessions.start(trader_id, Some(order_id)))
+            .await
+        {
+            Ok(()) => {
+                tracing::info!(
+                    %trader_id,
+                    %order_id,
+                    "Successfully processed match, setting match to Filled"
+                );
+
+                execution.finish(MatchState::Filled, OrderState::Taken)?;
+                Ok(TradeOutcome::Executed)
+            }
+            Err(e) => {
+                self.dlc_sessions.finish(trader_id);
+
+                // The order has to be unlocked before it can be updated with another connection.
+                drop(execution);
+
+                // The DLC protocol could not be started, e.g. because the trader went offline in
+                // the meantime. Instead of leaving the match dangling, we retry the execution.
+                tracing::warn!(%trader_id, %order_id, "Failed to execute match: {e:#}");
+
+                if let Err(enqueue_error) =
+                    execution_queue::enqueue(&mut connection, trade_params, &e)
+                {
+                    tracing::error!(%trader_id, %order_id, "Failed to queue trade execution for retry: {enqueue_error:#}");
+
+                    if let Err(e) = update_order_and_match(
+                        &mut connection,
+                        order_id,
+                        MatchState::Failed,
+                        OrderState::Failed,
+                    ) {
+                        tracing::error!(%trader_id, %order_id, "Failed to update order and match: {e}");
+                    };
+
+                    return Err(e).with_context(|| {
+                        format!("Failed to trade with peer {trader_id} for order {order_id}")
+                    });
+                }
+
+                Ok(TradeOutcome::PendingRetry)
             }
         }
     }
 
-    async fn trade_internal(
+    /// Retry the execution of a match which failed before.
+    ///
+    /// On success, the match is set to filled.
+    pub(crate) async fn retry_trade(
         &self,
-        trade_params: &TradeParams,
         connection: &mut PgConnection,
+        trade_params: &TradeParams,
     ) -> Result<()> {
         let order_id = trade_params.filled_with.order_id;
-        let trader_id = trade_params.pubkey.to_string();
-        let order = orders::get_with_id(connection, order_id)?.context("Could not find order")?;
+        let execution = MatchExecution::start(&self.pool, order_id, self.clock.now())?;
 
-        ensure!(
-            order.expiry > OffsetDateTime::now_utc(),
-            "Can't execute a trade on an expired order"
-        );
-        ensure!(
-            order.order_state == OrderState::Matched,
-            "Can't execute trade with in invalid state {:?}",
-            order.order_state
-        );
-
-        let order_id = trade_params.filled_with.order_id.to_string();
-        tracing::info!(trader_id, order_id, "Executing match");
-
-        self.execute_trade_action(connection, trade_params, order.stable)
-            .await?;
+        let trader_id = trade_params.pubkey;
+        if let Err(e) = self
+            .execute_trade_action(connection, trade_params, execution.order.stable)
+            .instrument(self.dlc_sessions.start(trader_id, Some(order_id)))
+            .await
+        {
+            self.dlc_sessions.finish(trader_id);
+            return Err(e);
+        }
 
-        Ok(())
+        execution.finish(MatchState::Filled, OrderState::Taken)
     }
 
     // For now we assume that the first position has equal margin to the size of the DLC channel to
@@ -220,22 +299,27 @@ impl Node {
     ) -> Result<()> {
         let peer_id = trade_params.pubkey;
 
-        let leverage_trader = trade_params.leverage;
         let leverage_coordinator = self.coordinator_leverage_for_trade(&trade_params.pubkey)?;
 
         let margin_trader = margin_trader(trade_params);
         let margin_coordinator = margin_coordinator(trade_params, leverage_coordinator);
 
-        let order_matching_fee = order_matching_fee_taker(
+        let (fee_rate, payout_curve_params, taker_fee_rate) = {
+            let settings = self.settings.read().await;
+            (
+                settings.contract_tx_fee_rate,
+                settings.payout_curve_params,
+                settings.taker_fee_rate,
+            )
+        };
+
+        let order_matching_fee = order_matching_fee(
             trade_params.quantity,
             trade_params.average_execution_price(),
+            taker_fee_rate,
         )
         .to_sat();
 
-        let initial_price = trade_params.filled_with.average_execution_price();
-
-        let coordinator_direction = trade_params.direction.opposite();
-
         tracing::info!(
             %peer_id,
             order_id = %trade_params.filled_with.order_id,
@@ -247,53 +331,26 @@ impl Node {
             "Opening DLC channel and position"
         );
 
-        let contract_descriptor = payout_curve::build_contract_descriptor(
-            initial_price,
-            margin_coordinator,
-            margin_trader,
-            leverage_coordinator,
-            leverage_trader,
-            coordinator_direction,
-            // The coordinator gets the `order_matching_fee` directly in the collateral reserve.
-            order_matching_fee,
-            0,
-            trade_params.quantity,
-            trade_params.contract_symbol,
-        )
-        .context("Could not build contract descriptor")?;
-
-        let contract_symbol = trade_params.contract_symbol.label();
-        let maturity_time = trade_params.filled_with.expiry_timestamp;
-        let maturity_time = maturity_time.unix_timestamp();
-
-        let fee_rate = self.settings.read().await.contract_tx_fee_rate;
-
-        // The contract input to be used for setting up the trade between the trader and the
-        // coordinator.
-        let event_id = format!("{contract_symbol}{maturity_time}");
-
-        tracing::debug!(
-            event_id,
-            oracle=%trade_params.filled_with.oracle_pk,
-            "Proposing DLC channel"
-        );
-
-        let contract_input = ContractInput {
+        let trade_inputs = TradeInputs {
+            trade_params: trade_params.clone(),
+            coordinator_leverage: leverage_coordinator,
             offer_collateral: margin_coordinator,
             // The accept party has do bring additional collateral to pay for the
             // `order_matching_fee`.
             accept_collateral: margin_trader + order_matching_fee,
             fee_rate,
-            contract_infos: vec![ContractInputInfo {
-                contract_descriptor,
-                oracles: OracleInput {
-                    public_keys: vec![trade_params.filled_with.oracle_pk],
-                    event_id,
-                    threshold: 1,
-                },
-            }],
+            payout_curve_params,
+            taker_fee_rate,
         };
 
+        tracing::debug!(
+            event_id = trade_inputs.event_id(),
+            oracle=%trade_params.filled_with.oracle_pk,
+            "Proposing DLC channel"
+        );
+
+        let contract_input = trade_inputs.contract_input()?;
+
         let temporary_contract_id = self
             .inner
             .propose_dlc_channel(contract_input, trade_params.pubkey)
@@ -310,13 +367,7 @@ impl Node {
         //
         // FIXME: We should not create a shadow representation (position) of the DLC struct, but
         // rather imply the state from the DLC.
-        self.persist_position_and_trade(
-            conn,
-            trade_params,
-            temporary_contract_id,
-            leverage_coordinator,
-            stable,
-        )
+        self.persist_position_and_trade(conn, &trade_inputs, temporary_contract_id, stable)
     }
 
     async fn open_position(
@@ -338,103 +389,49 @@ impl Node {
             "Opening position"
         );
 
-        let initial_price = trade_params.filled_with.average_execution_price();
-
         let leverage_coordinator = self.coordinator_leverage_for_trade(&trade_params.pubkey)?;
-        let leverage_trader = trade_params.leverage;
 
-        let margin_coordinator = margin_coordinator(trade_params, leverage_coordinator);
-        let margin_trader = margin_trader(trade_params);
+        let (fee_rate, payout_curve_params, taker_fee_rate) = {
+            let settings = self.settings.read().await;
+            (
+                settings.contract_tx_fee_rate,
+                settings.payout_curve_params,
+                settings.taker_fee_rate,
+            )
+        };
 
-        let order_matching_fee = order_matching_fee_taker(
-            trade_params.quantity,
-            trade_params.average_execution_price(),
-        )
-        .to_sat();
+        let trade_inputs = TradeInputs {
+            trade_params: trade_params.clone(),
+            coordinator_leverage: leverage_coordinator,
+            offer_collateral: coordinator_dlc_channel_collateral,
+            accept_collateral: trader_dlc_channel_collateral,
+            fee_rate,
+            payout_curve_params,
+            taker_fee_rate,
+        };
 
-        let coordinator_direction = trade_params.direction.opposite();
-
-        // How many coins the coordinator will keep outside of the bet. They still go in the DLC
-        // channel, but the payout will be at least this much for the coordinator.
-        let coordinator_collateral_reserve = (coordinator_dlc_channel_collateral
-            + order_matching_fee)
-            .checked_sub(margin_coordinator)
-            .with_context(|| {
-                format!(
-                    "Coordinator cannot trade with more than their total collateral in the \
-                     DLC channel: margin ({}) > collateral ({}) + order_matching_fee ({})",
-                    margin_coordinator, coordinator_dlc_channel_collateral, order_matching_fee
-                )
-            })?;
-
-        // How many coins the trader will keep outside of the bet. They still go in the DLC channel,
-        // but the payout will be at least this much for the coordinator.
-        let trader_collateral_reserve = trader_dlc_channel_collateral
-            .checked_sub(order_matching_fee)
-            .and_then(|collateral| collateral.checked_sub(margin_trader))
-            .with_context(|| {
-                format!(
-                    "Trader cannot trade with more than their total collateral in the \
-                     DLC channel: margin ({}) + order_matching_fee ({}) > collateral ({})",
-                    margin_trader, order_matching_fee, trader_dlc_channel_collateral
-                )
-            })?;
+        let coordinator_collateral_reserve = trade_inputs.coordinator_collateral_reserve()?;
+        let trader_collateral_reserve = trade_inputs.trader_collateral_reserve()?;
 
         tracing::debug!(
             %peer_id,
             order_id = %trade_params.filled_with.order_id,
             leverage_coordinator,
-            margin_coordinator_sat = %margin_coordinator,
-            margin_trader_sat = %margin_trader,
+            margin_coordinator_sat = %trade_inputs.coordinator_margin(),
+            margin_trader_sat = %trade_inputs.trader_margin(),
             coordinator_collateral_reserve_sat = %coordinator_collateral_reserve,
             trader_collateral_reserve_sat = %trader_collateral_reserve,
-            order_matching_fee_sat = %order_matching_fee,
+            order_matching_fee_sat = %trade_inputs.order_matching_fee(),
             "DLC channel update parameters"
         );
 
-        let contract_descriptor = payout_curve::build_contract_descriptor(
-            initial_price,
-            margin_coordinator,
-            margin_trader,
-            leverage_coordinator,
-            leverage_trader,
-            coordinator_direction,
-            coordinator_collateral_reserve,
-            trader_collateral_reserve,
-            trade_params.quantity,
-            trade_params.contract_symbol,
-        )
-        .context("Could not build contract descriptor")?;
-
-        let contract_symbol = trade_params.contract_symbol.label();
-        let maturity_time = trade_params.filled_with.expiry_timestamp;
-        let maturity_time = maturity_time.unix_timestamp();
-
-        let fee_rate = self.settings.read().await.contract_tx_fee_rate;
-
-        // The contract input to be used for setting up the trade between the trader and the
-        // coordinator.
-        let event_id = format!("{contract_symbol}{maturity_time}");
-
         tracing::debug!(
-            event_id,
+            event_id = trade_inputs.event_id(),
             oracle=%trade_params.filled_with.oracle_pk,
             "Proposing DLC channel update"
         );
 
-        let contract_input = ContractInput {
-            offer_collateral: coordinator_dlc_channel_collateral,
-            accept_collateral: trader_dlc_channel_collateral,
-            fee_rate,
-            contract_infos: vec![ContractInputInfo {
-                contract_descriptor,
-                oracles: OracleInput {
-                    public_keys: vec![trade_params.filled_with.oracle_pk],
-                    event_id,
-                    threshold: 1,
-                },
-            }],
-        };
+        let contract_input = trade_inputs.contract_input()?;
 
         let temporary_contract_id = self
             .inner
@@ -442,24 +439,20 @@ impl Node {
             .await
             .context("Could not propose DLC channel update")?;
 
-        self.persist_position_and_trade(
-            conn,
-            trade_params,
-            temporary_contract_id,
-            leverage_coordinator,
-            stable,
-        )
+        self.persist_position_and_trade(conn, &trade_inputs, temporary_contract_id, stable)
     }
 
     // Creates a position and a trade from the trade params
     fn persist_position_and_trade(
         &self,
         connection: &mut PgConnection,
-        trade_params: &TradeParams,
+        trade_inputs: &TradeInputs,
         temporary_contract_id: ContractId,
-        coordinator_leverage: f32,
         stable: bool,
     ) -> Result<()> {
+        let trade_params = &trade_inputs.trade_params;
+        let coordinator_leverage = trade_inputs.coordinator_leverage;
+
         let liquidation_price = liquidation_price(trade_params);
         let margin_coordinator = margin_coordinator(trade_params, coordinator_leverage);
         let margin_trader = margin_trader(trade_params);
@@ -488,7 +481,7 @@ impl Node {
 
         let position = db::positions::Position::insert(connection, new_position.clone())?;
 
-        db::trades::insert(
+        let trade = db::trades::insert(
             connection,
             NewTrade {
                 position_id: position.id,
@@ -503,6 +496,10 @@ impl Node {
             },
         )?;
 
+        // The inputs are recorded so that the DLC of the trade can be replayed in case of a
+        // dispute.
+        db::trade_inputs::insert(connection, trade.id, trade_inputs, temporary_contract_id)?;
+
         Ok(())
     }
 
@@ -517,8 +514,9 @@ impl Node {
             bail!("Underlying DLC channel not yet confirmed");
         }
 
+        let taker_fee_rate = self.settings.read().await.taker_fee_rate;
         let position_settlement_amount_coordinator =
-            position.calculate_coordinator_settlement_amount(closing_price)?;
+            position.calculate_coordinator_settlement_amount(closing_price, taker_fee_rate)?;
 
         let collateral_reserve_coordinator =
             self.inner.get_dlc_channel_usable_balance(&channel_id)?;
@@ -748,12 +746,27 @@ impl Node {
 
         for (node_id, msg) in messages {
             let msg_name = dlc_message_name(&msg);
-            if let Err(e) = self.process_dlc_message(node_id, msg) {
-                tracing::error!(
-                    from = %node_id,
-                    kind = %msg_name,
-                    "Failed to process DLC message: {e:#}"
-                );
+            let finishes_protocol = matches!(
+                msg,
+                Message::Channel(
+                    ChannelMessage::Accept(_)
+                        | ChannelMessage::RenewFinalize(_)
+                        | ChannelMessage::SettleFinalize(_)
+                )
+            );
+
+            self.dlc_sessions.span(node_id).in_scope(|| {
+                if let Err(e) = self.process_dlc_message(node_id, msg) {
+                    tracing::error!(
+                        from = %node_id,
+                        kind = %msg_name,
+                        "Failed to process DLC message: {e:#}"
+                    );
+                }
+            });
+
+            if finishes_protocol {
+                self.dlc_sessions.finish(node_id);
             }
         }
     }
@@ -794,7 +807,13 @@ impl Node {
                             tracing::debug!(%node_id, kind=%dlc_message_name(&msg), "Received message that has already been processed, skipping.");
                             return Ok(());
                         }
-                        None => inbound_msg,
+                        None => {
+                            // A new message from the peer implies that they have processed the
+                            // messages we sent them before.
+                            db::outbound_dlc_messages::acknowledge(&mut conn, &node_id)?;
+
+                            inbound_msg
+                        }
                     }
                 };
 
@@ -930,13 +949,117 @@ impl Node {
         //     None => 1.0,
         // };
 
-        let leverage_coordinator = 2.0;
+        let leverage_coordinator = COORDINATOR_LEVERAGE;
 
         Ok(leverage_coordinator)
     }
 }
 
-fn update_order_and_match(
+/// Ensure that the order can still be executed.
+fn validate_match(order: Option<Order>, now: OffsetDateTime) -> Result<Order> {
+    let order = order.context("Could not find order")?;
+
+    ensure!(
+        order.expiry > now,
+        "Can't execute a trade on an expired order"
+    );
+    ensure!(
+        order.order_state == OrderState::Matched,
+        "Can't execute trade with in invalid state {:?}",
+        order.order_state
+    );
+
+    Ok(order)
+}
+
+/// The outcome of a trade request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeOutcome {
+    /// The DLC protocol has been started with the trader.
+    Executed,
+    /// The DLC protocol could not be started, the execution will be retried.
+    PendingRetry,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Match of order {0} is already being executed")]
+pub struct MatchAlreadyExecuting(Uuid);
+
+/// A lock on a matched order, which is held while its match is executed.
+///
+/// Without it, the trade request of the trader and the execution queue could execute the same match
+/// at the same time, proposing the trade to the trader twice. The lock is a row lock within a
+/// transaction on a dedicated connection, which is rolled back if the execution is dropped without
+/// being finished.
+struct MatchExecution {
+    conn: PooledConnection<ConnectionManager<PgConnection>>,
+    order: Order,
+    finished: bool,
+}
+
+impl MatchExecution {
+    /// Lock the matched order, failing with [`MatchAlreadyExecuting`] if the match is already
+    /// being executed.
+    fn start(
+        pool: &Pool<ConnectionManager<PgConnection>>,
+        order_id: Uuid,
+        now: OffsetDateTime,
+    ) -> Result<Self> {
+        let mut conn = pool.get()?;
+
+        AnsiTransactionManager::begin_transaction(&mut *conn)?;
+
+        let order = orders::get_with_id_for_update(&mut conn, order_id)
+            .map_err(|e| match e {
+                diesel::result::Error::DatabaseError(_, ref info)
+                    if info.message().contains("could not obtain lock") =>
+                {
+                    anyhow::Error::new(MatchAlreadyExecuting(order_id))
+                }
+                e => e.into(),
+            })
+            .and_then(|order| validate_match(order, now));
+
+        match order {
+            Ok(order) => Ok(Self {
+                conn,
+                order,
+                finished: false,
+            }),
+            Err(e) => {
+                if let Err(e) = AnsiTransactionManager::rollback_transaction(&mut *conn) {
+                    tracing::error!(%order_id, "Failed to release lock on order: {e:#}");
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Set the order and its match to their final states and release the lock.
+    fn finish(mut self, match_state: MatchState, order_state: OrderState) -> Result<()> {
+        update_order_and_match(&mut self.conn, self.order.id, match_state, order_state)?;
+
+        AnsiTransactionManager::commit_transaction(&mut *self.conn)?;
+        self.finished = true;
+
+        Ok(())
+    }
+}
+
+impl Drop for MatchExecution {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if let Err(e) = AnsiTransactionManager::rollback_transaction(&mut *self.conn) {
+            tracing::error!(order_id = %self.order.id, "Failed to release lock on order: {e:#}");
+        }
+    }
+}
+
+pub(crate) fn update_order_and_match(
     connection: &mut PgConnection,
     order_id: Uuid,
     match_state: MatchState,
@@ -961,7 +1084,7 @@ pub enum TradeAction {
 }
 
 fn margin_trader(trade_params: &TradeParams) -> u64 {
-    calculate_margin(
+    pricing::margin(
         trade_params.average_execution_price(),
         trade_params.quantity,
         trade_params.leverage,
@@ -969,7 +1092,7 @@ fn margin_trader(trade_params: &TradeParams) -> u64 {
 }
 
 fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) -> u64 {
-    calculate_margin(
+    pricing::margin(
         trade_params.average_execution_price(),
         trade_params.quantity,
         coordinator_leverage,
@@ -977,13 +1100,11 @@ fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) ->
 }
 
 fn liquidation_price(trade_params: &TradeParams) -> f32 {
-    let price = trade_params.average_execution_price();
-    let leverage = Decimal::try_from(trade_params.leverage).expect("to fit into decimal");
-
-    match trade_params.direction {
-        Direction::Long => calculate_long_liquidation_price(leverage, price),
-        Direction::Short => calculate_short_liquidation_price(leverage, price),
-    }
+    pricing::liquidation_price(
+        trade_params.average_execution_price(),
+        trade_params.leverage,
+        trade_params.direction,
+    )
     .to_f32()
     .expect("to fit into f32")
 }
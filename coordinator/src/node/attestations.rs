@@ -0,0 +1,336 @@
+use crate::db;
+use crate::db::attestations;
+use crate::db::attestations::Attestation;
+use crate::db::attestations::AttestationState;
+use crate::node::expired_positions::EXPIRED_POSITION_TIMEOUT;
+use crate::node::Node;
+use crate::position::models::Position;
+use crate::position::models::PositionState;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::XOnlyPublicKey;
+use diesel::PgConnection;
+use dlc_manager::channel::signed_channel::SignedChannelState;
+use dlc_manager::contract::Contract;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+
+/// How often we check for expired contracts and their attestations.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long we wait before asking the oracle again if it has not attested yet, or if it is not
+/// reachable. The backoff doubles with every further attempt.
+const INITIAL_RETRY_BACKOFF: time::Duration = time::Duration::minutes(1);
+
+/// The longest we wait before asking the oracle again.
+const MAX_RETRY_BACKOFF: time::Duration = time::Duration::hours(1);
+
+/// Periodically request the attestations of expired contracts from the oracle and settle the DLC
+/// of a contract once it is attested.
+///
+/// An expired position is closed collaboratively with the trader first. Only if that does not
+/// happen within [`EXPIRED_POSITION_TIMEOUT`] do we settle the DLC with the attestation, by force
+/// closing the DLC channel.
+pub fn monitor(node: Node) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            if let Err(e) = check_attestations(&node).await {
+                tracing::error!("Failed to check attestations: {e:#}");
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn check_attestations(node: &Node) -> Result<()> {
+    let mut conn = node.pool.get()?;
+
+    track_expired_positions(node, &mut conn)?;
+
+    for attestation in attestations::get_due(&mut conn, OffsetDateTime::now_utc())? {
+        if let Err(e) = request_attestation(node, &mut conn, attestation).await {
+            tracing::error!("Failed to request attestation: {e:#}");
+        }
+    }
+
+    for attestation in attestations::get_by_state(&mut conn, AttestationState::Attested)? {
+        if let Err(e) = settle(node, &mut conn, attestation).await {
+            tracing::error!("Failed to settle attested contract: {e:#}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Start tracking the attestation of every position which has expired.
+fn track_expired_positions(node: &Node, conn: &mut PgConnection) -> Result<()> {
+    let tracked_positions = attestations::get_position_ids(conn)?;
+
+    let now = OffsetDateTime::now_utc();
+    let expired_positions = db::positions::Position::get_all_open_or_closing_positions(conn)?
+        .into_iter()
+        .filter(|position| position.expiry_timestamp <= now)
+        .filter(|position| !tracked_positions.contains(&position.id));
+
+    for position in expired_positions {
+        if let Err(e) = track_expired_position(node, conn, &position) {
+            tracing::warn!(
+                position_id = position.id,
+                trader_id = %position.trader,
+                "Could not track attestation of expired position: {e:#}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn track_expired_position(node: &Node, conn: &mut PgConnection, position: &Position) -> Result<()> {
+    let channel = node
+        .inner
+        .get_dlc_channel_by_counterparty(&position.trader)?
+        .context("No DLC channel with trader")?;
+    let contract_id = channel
+        .get_contract_id()
+        .context("DLC channel without contract")?;
+    let contract = node
+        .inner
+        .get_contract_by_dlc_channel_id(&channel.channel_id)?;
+
+    let (oracle_pk, event_id) = oracle_event(&contract)?;
+
+    tracing::info!(
+        position_id = position.id,
+        trader_id = %position.trader,
+        %oracle_pk,
+        event_id,
+        "Tracking attestation of expired position"
+    );
+
+    attestations::insert(
+        conn,
+        position.id,
+        position.trader,
+        contract_id.to_hex(),
+        oracle_pk,
+        event_id,
+    )
+}
+
+async fn request_attestation(
+    node: &Node,
+    conn: &mut PgConnection,
+    attestation: Attestation,
+) -> Result<()> {
+    let result = spawn_blocking({
+        let node = node.inner.clone();
+        let oracle_pk = attestation.oracle_pk;
+        let event_id = attestation.event_id.clone();
+        move || node.get_attestation(&oracle_pk, &event_id)
+    })
+    .await
+    .context("Requesting attestation did not complete")
+    .and_then(|attestation| attestation)
+    .and_then(|oracle_attestation| attested_price(&oracle_attestation.outcomes));
+
+    let attempts = attestation.attempts + 1;
+    match result {
+        Ok(price) => {
+            tracing::info!(
+                position_id = attestation.position_id,
+                event_id = attestation.event_id,
+                price,
+                attempts,
+                "Received attestation"
+            );
+
+            attestations::set_attested(conn, attestation.id, price)
+        }
+        Err(e) => {
+            // The oracle only attests once the event has matured, and it might be down. Either
+            // way, we keep asking.
+            tracing::debug!(
+                position_id = attestation.position_id,
+                event_id = attestation.event_id,
+                attempts,
+                "Attestation not available yet: {e:#}"
+            );
+
+            let next_attempt = OffsetDateTime::now_utc() + retry_backoff(attempts);
+            attestations::record_failed_attempt(
+                conn,
+                attestation.id,
+                format!("{e:#}"),
+                next_attempt,
+            )
+        }
+    }
+}
+
+/// Settle the DLC of an attested contract if the trader has not closed the expired position in
+/// time.
+async fn settle(node: &Node, conn: &mut PgConnection, attestation: Attestation) -> Result<()> {
+    let position_id = attestation.position_id;
+    let trader_id = attestation.trader_pubkey;
+
+    let position = db::positions::Position::get_positions_by_trader(
+        conn,
+        trader_id,
+        vec![
+            PositionState::Open,
+            PositionState::Closing { closing_price: 0.0 },
+        ],
+    )?
+    .into_iter()
+    .find(|position| position.id == position_id);
+
+    let position = match position {
+        Some(position) => position,
+        None => {
+            tracing::debug!(
+                position_id,
+                %trader_id,
+                "Expired position has been closed without the attestation"
+            );
+
+            return attestations::set_attestation_state(
+                conn,
+                attestation.id,
+                AttestationState::Closed,
+            );
+        }
+    };
+
+    if OffsetDateTime::now_utc() < position.expiry_timestamp + EXPIRED_POSITION_TIMEOUT {
+        // We give the trader the chance to close the expired position collaboratively.
+        return Ok(());
+    }
+
+    let channel = node
+        .inner
+        .get_dlc_channel_by_counterparty(&trader_id)?
+        .context("No DLC channel with trader")?;
+
+    let contract_id = channel
+        .get_contract_id()
+        .map(|contract_id| contract_id.to_hex());
+    if !matches!(channel.state, SignedChannelState::Established { .. })
+        || contract_id.as_ref() != Some(&attestation.contract_id)
+    {
+        tracing::warn!(
+            position_id,
+            %trader_id,
+            channel_state = %channel.state,
+            "DLC channel does not hold the attested contract anymore"
+        );
+
+        return attestations::set_attestation_state(conn, attestation.id, AttestationState::Closed);
+    }
+
+    let attested_price = attestation
+        .attested_price
+        .context("Attested contract without price")?;
+
+    tracing::info!(
+        position_id,
+        %trader_id,
+        channel_id = %channel.channel_id.to_hex(),
+        attested_price,
+        "Settling expired position with attestation"
+    );
+
+    node.inner
+        .close_dlc_channel(channel.channel_id, true)
+        .await
+        .context("Could not force close DLC channel")?;
+
+    if position.position_state == PositionState::Open {
+        db::positions::Position::set_open_position_to_closing(
+            conn,
+            trader_id.to_string(),
+            attested_price as f32,
+        )?;
+    }
+
+    attestations::set_attestation_state(conn, attestation.id, AttestationState::Settled)
+}
+
+/// The oracle and the event which the DLC of the `contract` is betting on.
+fn oracle_event(contract: &Contract) -> Result<(XOnlyPublicKey, String)> {
+    let offered_contract = match contract {
+        Contract::Signed(contract) | Contract::Confirmed(contract) => {
+            &contract.accepted_contract.offered_contract
+        }
+        _ => bail!("Contract is not signed"),
+    };
+
+    let oracle_announcement = offered_contract
+        .contract_info
+        .first()
+        .and_then(|contract_info| contract_info.oracle_announcements.first())
+        .context("Contract without oracle announcement")?;
+
+    Ok((
+        oracle_announcement.oracle_public_key,
+        oracle_announcement.oracle_event.event_id.clone(),
+    ))
+}
+
+/// The price attested to by the oracle.
+///
+/// The price is attested to as its digits in base 2, most significant digit first.
+fn attested_price(outcomes: &[String]) -> Result<u64> {
+    let digits = outcomes.concat();
+    if digits.is_empty() {
+        bail!("Attestation without outcomes");
+    }
+
+    u64::from_str_radix(&digits, 2)
+        .with_context(|| format!("Could not decode attested outcome {digits}"))
+}
+
+fn retry_backoff(attempts: i32) -> time::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+
+    (INITIAL_RETRY_BACKOFF * 2_i32.pow(exponent)).min(MAX_RETRY_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attested_price_is_decoded_from_binary_digits() {
+        // 40_000 in base 2 with 20 digits.
+        let outcomes = "00001001110001000000"
+            .chars()
+            .map(|digit| digit.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(attested_price(&outcomes).unwrap(), 40_000);
+    }
+
+    #[test]
+    fn invalid_outcomes_are_rejected() {
+        assert!(attested_price(&[]).is_err());
+        assert!(attested_price(&["2".to_string()]).is_err());
+    }
+
+    #[test]
+    fn retry_backoff_is_capped() {
+        assert_eq!(retry_backoff(1), time::Duration::minutes(1));
+        assert_eq!(retry_backoff(3), time::Duration::minutes(4));
+        assert_eq!(retry_backoff(10), time::Duration::hours(1));
+    }
+}
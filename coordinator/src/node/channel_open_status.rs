@@ -0,0 +1,76 @@
+use crate::message::OrderbookMessage;
+use bitcoin::hashes::hex::ToHex;
+use commons::Message;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use ln_dlc_node::node::event::NodeEvent;
+use ln_dlc_node::node::ChannelOpenStatus;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+/// Forward the progress of the channels we are opening to the traders, so that the app can show
+/// how far its channel is from being usable.
+pub fn forward_to_traders(
+    mut receiver: broadcast::Receiver<NodeEvent>,
+    notifier: mpsc::Sender<OrderbookMessage>,
+) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        loop {
+            match receiver.recv().await {
+                Ok(NodeEvent::ChannelOpenStatus {
+                    channel_id,
+                    counterparty,
+                    status,
+                }) => {
+                    let message = OrderbookMessage::TraderMessage {
+                        trader_id: counterparty,
+                        message: Message::ChannelOpenStatus {
+                            channel_id: channel_id.to_hex(),
+                            status: to_message_status(status),
+                        },
+                        notification: None,
+                    };
+
+                    if let Err(e) = notifier.send(message).await {
+                        tracing::error!(
+                            trader_id = %counterparty,
+                            "Failed to forward channel open status: {e:#}"
+                        );
+                    }
+                }
+                Ok(_) => {} // ignored
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Skipped {skipped} node events");
+                }
+                Err(RecvError::Closed) => {
+                    tracing::error!("Lost connection to sender!");
+                    break;
+                }
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+fn to_message_status(status: ChannelOpenStatus) -> commons::ChannelOpenStatus {
+    match status {
+        ChannelOpenStatus::Broadcast {
+            required_confirmations,
+        } => commons::ChannelOpenStatus::Broadcast {
+            required_confirmations,
+        },
+        ChannelOpenStatus::Confirming {
+            confirmations,
+            required_confirmations,
+        } => commons::ChannelOpenStatus::Confirming {
+            confirmations,
+            required_confirmations,
+        },
+        ChannelOpenStatus::Ready => commons::ChannelOpenStatus::Ready,
+    }
+}
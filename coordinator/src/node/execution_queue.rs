@@ -0,0 +1,218 @@
+use crate::db::positions;
+use crate::db::trade_executions;
+use crate::db::trade_executions::TradeExecution;
+use crate::db::trade_executions::TradeExecutionState;
+use crate::message::OrderbookMessage;
+use crate::node::update_order_and_match;
+use crate::node::Node;
+use crate::orderbook::db::orders;
+use crate::position::models::PositionState;
+use crate::trace;
+use anyhow::anyhow;
+use anyhow::Result;
+use commons::MatchState;
+use commons::Message;
+use commons::OrderState;
+use commons::TradeParams;
+use diesel::PgConnection;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
+
+/// How often we check for trade executions which are due for a retry.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often we try to execute a match before we give up.
+///
+/// Together with the backoff, this keeps the time until we give up shorter than the timeout of the
+/// app's watchdog for orders in filling.
+const MAX_EXECUTION_ATTEMPTS: i32 = 5;
+
+/// How long we wait after the first failed attempt. The backoff doubles with every further failed
+/// attempt.
+const INITIAL_RETRY_BACKOFF: time::Duration = time::Duration::seconds(10);
+
+const EXECUTION_FAILED: &str = "Match could not be executed";
+
+/// Queue the execution of a match for a retry after it failed for the first time.
+pub(crate) fn enqueue(
+    conn: &mut PgConnection,
+    trade_params: &TradeParams,
+    error: &anyhow::Error,
+) -> Result<()> {
+    let next_attempt = OffsetDateTime::now_utc() + retry_backoff(1);
+
+    trade_executions::insert(conn, trade_params, format!("{error:#}"), next_attempt)
+}
+
+/// Periodically retry trade executions which have failed before.
+///
+/// If the execution keeps failing, the order and its match are set to failed and the trader is
+/// informed that the match has been reverted.
+pub fn monitor(node: Node, notifier: mpsc::Sender<OrderbookMessage>) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            if let Err(e) = retry_due_executions(&node, &notifier).await {
+                tracing::error!("Failed to retry trade executions: {e:#}");
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn retry_due_executions(
+    node: &Node,
+    notifier: &mpsc::Sender<OrderbookMessage>,
+) -> Result<()> {
+    let executions = spawn_blocking({
+        let pool = node.pool.clone();
+        move || {
+            let mut conn = pool.get()?;
+            trade_executions::get_due(&mut conn, OffsetDateTime::now_utc())
+        }
+    })
+    .await??;
+
+    for execution in executions {
+        let retry = retry_execution(node, notifier, execution);
+        if let Err(e) = trace::with_new_trace("retry_trade_execution", retry).await {
+            tracing::error!("Failed to retry trade execution: {e:#}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn retry_execution(
+    node: &Node,
+    notifier: &mpsc::Sender<OrderbookMessage>,
+    execution: TradeExecution,
+) -> Result<()> {
+    let mut conn = node.pool.get()?;
+
+    let order_id = execution.order_id;
+    let trader_id = execution.trade_params.pubkey;
+
+    // The match might have been reverted in the meantime, e.g. by the trader.
+    let order = orders::get_with_id(&mut conn, order_id)?;
+    if order.map(|order| order.order_state) != Some(OrderState::Matched) {
+        tracing::debug!(%trader_id, %order_id, "Order is not matched anymore, giving up execution");
+
+        return trade_executions::set_execution_state(
+            &mut conn,
+            execution.id,
+            TradeExecutionState::Failed,
+        );
+    }
+
+    let result = if node.is_connected(&trader_id) {
+        node.retry_trade(&mut conn, &execution.trade_params).await
+    } else {
+        Err(anyhow!("Trader is not connected"))
+    };
+
+    let e = match result {
+        Ok(()) => {
+            tracing::info!(%trader_id, %order_id, attempts = execution.attempts + 1, "Successfully executed match on retry");
+
+            return trade_executions::set_execution_state(
+                &mut conn,
+                execution.id,
+                TradeExecutionState::Succeeded,
+            );
+        }
+        Err(e) => e,
+    };
+
+    let attempts = execution.attempts + 1;
+    if attempts < MAX_EXECUTION_ATTEMPTS {
+        tracing::warn!(%trader_id, %order_id, attempts, "Failed to execute match: {e:#}");
+
+        let next_attempt = OffsetDateTime::now_utc() + retry_backoff(attempts);
+        return trade_executions::record_failed_attempt(
+            &mut conn,
+            execution.id,
+            format!("{e:#}"),
+            Some(next_attempt),
+        );
+    }
+
+    tracing::error!(%trader_id, %order_id, attempts, "Giving up on executing match: {e:#}");
+
+    trade_executions::record_failed_attempt(&mut conn, execution.id, format!("{e:#}"), None)?;
+    revert_execution(&mut conn, &execution.trade_params)?;
+
+    let message = OrderbookMessage::TraderMessage {
+        trader_id,
+        message: Message::MatchReverted {
+            order_id,
+            reason: EXECUTION_FAILED.to_string(),
+        },
+        notification: None,
+    };
+    if let Err(e) = notifier.send(message).await {
+        tracing::warn!(%trader_id, "Failed to notify trader about reverted match: {e:#}");
+    }
+
+    Ok(())
+}
+
+/// Fail the order and its match, and fail the position if it has only been proposed.
+///
+/// A position is only created after the DLC protocol has been started, so we should not find one
+/// here. But if we do, it will never be opened.
+fn revert_execution(conn: &mut PgConnection, trade_params: &TradeParams) -> Result<()> {
+    let trader_id = trade_params.pubkey;
+
+    update_order_and_match(
+        conn,
+        trade_params.filled_with.order_id,
+        MatchState::Failed,
+        OrderState::Failed,
+    )?;
+
+    let proposed_position = positions::Position::get_position_by_trader(
+        conn,
+        trader_id,
+        vec![
+            PositionState::Proposed,
+            PositionState::ResizeOpeningSubchannelProposed,
+        ],
+    )?;
+    if proposed_position.is_some() {
+        positions::Position::update_proposed_position(
+            conn,
+            trader_id.to_string(),
+            PositionState::Failed,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn retry_backoff(attempts: i32) -> time::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+
+    INITIAL_RETRY_BACKOFF * 2_i32.pow(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_backoff_doubles_with_every_attempt() {
+        assert_eq!(retry_backoff(1), time::Duration::seconds(10));
+        assert_eq!(retry_backoff(2), time::Duration::seconds(20));
+        assert_eq!(retry_backoff(4), time::Duration::seconds(80));
+    }
+}
@@ -2,8 +2,10 @@ use crate::db;
 use crate::node::Node;
 use crate::orderbook;
 use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingMessage;
 use crate::position::models::Position;
 use crate::position::models::PositionState;
+use crate::trace;
 use anyhow::Context;
 use anyhow::Result;
 use commons::average_execution_price;
@@ -18,25 +20,22 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::ops::Add;
 use time::Duration;
-use time::OffsetDateTime;
 use tokio::sync::mpsc;
 
 /// The timeout before we give up on closing an expired position collaboratively. This value should
 /// not be larger than our refund transaction time lock.
 pub const EXPIRED_POSITION_TIMEOUT: Duration = Duration::days(7);
 
-pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) -> Result<()> {
+pub async fn close(node: Node, trading_sender: mpsc::Sender<TradingMessage>) -> Result<()> {
     let mut conn = node.pool.get()?;
 
     let positions = db::positions::Position::get_all_open_positions(&mut conn)
         .context("Failed to fetch open positions")?;
 
+    let now = node.clock.now();
     let positions = positions
         .into_iter()
-        .filter(|p| {
-            p.position_state == PositionState::Open
-                && OffsetDateTime::now_utc().ge(&p.expiry_timestamp)
-        })
+        .filter(|p| p.position_state == PositionState::Open && p.is_expired(now))
         .collect::<Vec<Position>>();
 
     for position in positions.into_iter() {
@@ -48,7 +47,7 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
             let trader_id = order.trader_id.to_string();
             let order_id = order.id.to_string();
 
-            if order.expiry < OffsetDateTime::now_utc() {
+            if order.expiry < now {
                 tracing::warn!(trader_id, order_id, "Matched order expired! Giving up on that position, looks like the corresponding dlc channel has to get force closed.");
                 orderbook::db::orders::set_order_state(&mut conn, order.id, OrderState::Failed)?;
 
@@ -92,8 +91,9 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
             // This order can basically not expire, but if the user does not come back online within
             // a certain time period we can assume the channel to be abandoned and we should force
             // close.
-            expiry: OffsetDateTime::now_utc().add(EXPIRED_POSITION_TIMEOUT),
+            expiry: now.add(EXPIRED_POSITION_TIMEOUT),
             stable: position.stable,
+            reduce_only: false,
         };
 
         let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
@@ -101,9 +101,10 @@ pub async fn close(node: Node, trading_sender: mpsc::Sender<NewOrderMessage>) ->
             new_order: new_order.clone(),
             order_reason: OrderReason::Expired,
             sender,
+            span: trace::new_span("close_expired_position"),
         };
 
-        if let Err(e) = trading_sender.send(message).await {
+        if let Err(e) = orderbook::trading::submit(&trading_sender, message).await {
             tracing::error!(order_id=%new_order.id, trader_id=%new_order.trader_id, "Failed to submit new order for closing expired position. Error: {e:#}");
             continue;
         }
@@ -12,7 +12,7 @@ use anyhow::Context;
 use anyhow::Result;
 use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::PublicKey;
-use commons::order_matching_fee_taker;
+use commons::order_matching_fee;
 use commons::TradeParams;
 use diesel::Connection;
 use diesel::PgConnection;
@@ -25,9 +25,7 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy;
 use tokio::task::block_in_place;
-use trade::cfd::calculate_long_liquidation_price;
-use trade::cfd::calculate_margin;
-use trade::cfd::calculate_short_liquidation_price;
+use trade::pricing;
 use trade::Direction;
 
 impl Node {
@@ -215,8 +213,9 @@ impl Node {
             trade.direction,
         );
 
-        // NOTE: Leverage does not change with new orders!
-        let leverage_trader = decimal_from_f32(old_position.trader_leverage);
+        // The trader's leverage is taken from the order which resized the position, so that a
+        // resize can also change the trader's margin. The coordinator's leverage does not change.
+        let leverage_trader = decimal_from_f32(trade.trader_leverage);
         let leverage_coordinator = decimal_from_f32(old_position.coordinator_leverage);
 
         let margin_coordinator = compute_margin(
@@ -228,7 +227,7 @@ impl Node {
             compute_margin(total_contracts, leverage_trader, average_execution_price);
 
         let liquidation_price_trader =
-            compute_liquidation_price(leverage_trader, average_execution_price, &direction);
+            pricing::liquidation_price(average_execution_price, trade.trader_leverage, direction);
         let expiry_timestamp = trade
             .dlc_expiry_timestamp
             .context("No expiry timestamp for resizing trade")?;
@@ -238,7 +237,10 @@ impl Node {
         let leverage_trader = f32_from_decimal(leverage_trader);
 
         let contract_input = {
-            let fee_rate = self.settings.blocking_read().contract_tx_fee_rate;
+            let (fee_rate, taker_fee_rate) = {
+                let settings = self.settings.blocking_read();
+                (settings.contract_tx_fee_rate, settings.taker_fee_rate)
+            };
 
             let contract_symbol = old_position.contract_symbol;
             let maturity_time = expiry_timestamp.unix_timestamp();
@@ -253,9 +255,12 @@ impl Node {
             // channel when first closing the DLC channel.
             //
             // Here we only need to charge for executing the order.
-            let fee =
-                order_matching_fee_taker(trade.quantity, decimal_from_f32(trade.average_price))
-                    .to_sat();
+            let fee = order_matching_fee(
+                trade.quantity,
+                decimal_from_f32(trade.average_price),
+                taker_fee_rate,
+            )
+            .to_sat();
 
             let contract_descriptor = payout_curve::build_contract_descriptor(
                 average_execution_price,
@@ -354,7 +359,7 @@ impl Node {
 }
 
 fn margin_coordinator(trade_params: &TradeParams, coordinator_leverage: f32) -> u64 {
-    calculate_margin(
+    pricing::margin(
         trade_params.average_execution_price(),
         trade_params.quantity,
         coordinator_leverage,
@@ -429,13 +434,6 @@ fn compute_average_execution_price(
     }
 }
 
-fn compute_liquidation_price(leverage: Decimal, price: Decimal, direction: &Direction) -> Decimal {
-    match direction {
-        Direction::Long => calculate_long_liquidation_price(leverage, price),
-        Direction::Short => calculate_short_liquidation_price(leverage, price),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -32,6 +32,7 @@ use tokio::sync::broadcast;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 use tokio::task::spawn_blocking;
+use tracing::Instrument;
 use trade::ContractSymbol;
 
 #[derive(Debug, Clone)]
@@ -43,7 +44,7 @@ struct Rollover {
     contract_symbol: ContractSymbol,
     oracle_pk: XOnlyPublicKey,
     contract_tx_fee_rate: u64,
-    network: Network,
+    maturity_time: OffsetDateTime,
 }
 
 pub fn monitor(
@@ -97,7 +98,8 @@ pub fn monitor(
 }
 
 impl Rollover {
-    pub fn new(contract: Contract, network: Network) -> Result<Self> {
+    /// The rolled over contract matures at the next expiry after `now`.
+    pub fn new(contract: Contract, network: Network, now: OffsetDateTime) -> Result<Self> {
         let contract = match contract {
             Contract::Confirmed(contract) => contract,
             _ => bail!(
@@ -120,7 +122,7 @@ impl Rollover {
             oracle_announcement.oracle_event.event_maturity_epoch as i64,
         )?;
 
-        if expiry_timestamp < OffsetDateTime::now_utc() {
+        if expiry_timestamp < now {
             bail!("Cannot rollover an expired position");
         }
 
@@ -138,19 +140,14 @@ impl Rollover {
                 &oracle_announcement.oracle_event.event_id[..6],
             )?,
             contract_tx_fee_rate,
-            network,
+            maturity_time: commons::calculate_next_expiry(now, network),
         })
     }
 
     pub fn event_id(&self) -> String {
-        let maturity_time = self.maturity_time().unix_timestamp();
+        let maturity_time = self.maturity_time.unix_timestamp();
         format!("{}{maturity_time}", self.contract_symbol)
     }
-
-    /// Calculates the maturity time based on the current expiry timestamp.
-    pub fn maturity_time(&self) -> OffsetDateTime {
-        commons::calculate_next_expiry(OffsetDateTime::now_utc(), self.network)
-    }
 }
 
 impl Node {
@@ -161,9 +158,7 @@ impl Node {
         trader_id: PublicKey,
         network: Network,
     ) -> Result<()> {
-        let mut conn = spawn_blocking(move || pool.get())
-            .await
-            .expect("task to complete")?;
+        let mut conn = spawn_blocking(move || pool.get()).await??;
 
         tracing::debug!(%trader_id, "Checking if the users positions is eligible for rollover");
         if let Some(position) = positions::Position::get_position_by_trader(
@@ -183,11 +178,9 @@ impl Node {
                 _ => bail!("Unexpected position state {:?}", position.position_state),
             };
 
-            if commons::is_eligible_for_rollover(OffsetDateTime::now_utc(), network)
-                && !position.is_expired()
-            {
-                let next_expiry =
-                    commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
+            let now = self.clock.now();
+            if commons::is_eligible_for_rollover(now, network) && !position.is_expired(now) {
+                let next_expiry = commons::calculate_next_expiry(now, network);
                 if position.expiry_timestamp == next_expiry && !retry_rollover {
                     tracing::trace!(%trader_id, position_id=position.id, "Position has already been rolled over");
                     return Ok(());
@@ -220,25 +213,59 @@ impl Node {
         network: Network,
     ) -> Result<()> {
         let contract = self.inner.get_contract_by_dlc_channel_id(dlc_channel_id)?;
-        let rollover = Rollover::new(contract, network)?;
+        let rollover = Rollover::new(contract, network, self.clock.now())?;
 
         tracing::debug!(node_id=%rollover.counterparty_pubkey, "Rollover dlc channel");
 
         let contract_input: ContractInput = rollover.clone().into();
 
-        self.inner
+        let trader_id = rollover.counterparty_pubkey;
+        if let Err(e) = self
+            .inner
             .propose_dlc_channel_update(dlc_channel_id, contract_input)
-            .await?;
+            .instrument(self.dlc_sessions.start(trader_id, None))
+            .await
+        {
+            self.dlc_sessions.finish(trader_id);
+            return Err(e);
+        }
 
         // Sets the position state to rollover indicating that a rollover is in progress.
         let mut connection = self.pool.get()?;
         db::positions::Position::rollover_position(
             &mut connection,
             rollover.counterparty_pubkey.to_string(),
-            &rollover.maturity_time(),
+            &rollover.maturity_time,
         )
     }
 
+    /// Resumes a rollover which was interrupted, e.g. by a restart of the coordinator.
+    ///
+    /// If the DLC channel is still in the midst of the renew protocol, it is rolled back and the
+    /// rollover is proposed again. If the protocol completed, the position is only set back to
+    /// open.
+    pub async fn retry_interrupted_rollover(
+        &self,
+        trader_id: PublicKey,
+        network: Network,
+    ) -> Result<()> {
+        let signed_channel = self.inner.get_signed_channel_by_trader_id(trader_id)?;
+
+        let rolled_back = {
+            let mut conn = self.pool.get()?;
+            let (rolled_back, _) = self.rollback_channel_if_needed(&mut conn, &signed_channel)?;
+            rolled_back
+        };
+
+        if !rolled_back {
+            tracing::info!(%trader_id, "Interrupted rollover has been completed in the meantime");
+            return self.finalize_rollover(&signed_channel.channel_id);
+        }
+
+        self.propose_rollover(&signed_channel.channel_id, network)
+            .await
+    }
+
     pub fn is_in_rollover(&self, trader_id: PublicKey) -> Result<bool> {
         let mut conn = self.pool.get()?;
         let position = db::positions::Position::get_position_by_trader(
@@ -351,7 +378,12 @@ mod tests {
     fn test_new_rollover_from_signed_contract() {
         let expiry_timestamp = OffsetDateTime::now_utc().unix_timestamp() + 10_000;
         let contract = dummy_signed_contract(200, 100, expiry_timestamp as u32);
-        let rollover = Rollover::new(Contract::Confirmed(contract), Network::Bitcoin).unwrap();
+        let rollover = Rollover::new(
+            Contract::Confirmed(contract),
+            Network::Bitcoin,
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap();
         assert_eq!(rollover.contract_symbol, ContractSymbol::BtcUsd);
         assert_eq!(rollover.margin_trader, 100);
         assert_eq!(rollover.margin_coordinator, 200);
@@ -362,7 +394,8 @@ mod tests {
         let expiry_timestamp = OffsetDateTime::now_utc().unix_timestamp() + 10_000;
         assert!(Rollover::new(
             Contract::Offered(dummy_offered_contract(200, 100, expiry_timestamp as u32)),
-            Network::Bitcoin
+            Network::Bitcoin,
+            OffsetDateTime::now_utc(),
         )
         .is_err())
     }
@@ -379,7 +412,7 @@ mod tests {
             contract_symbol: ContractSymbol::BtcUsd,
             oracle_pk: XOnlyPublicKey::from(dummy_pubkey()),
             contract_tx_fee_rate: 1,
-            network: Network::Bitcoin,
+            maturity_time: OffsetDateTime::now_utc(),
         };
 
         let contract_input: ContractInput = rollover.into();
@@ -393,7 +426,8 @@ mod tests {
         let expiry_timestamp = OffsetDateTime::now_utc().unix_timestamp() - 10_000;
         assert!(Rollover::new(
             Contract::Confirmed(dummy_signed_contract(200, 100, expiry_timestamp as u32)),
-            Network::Bitcoin
+            Network::Bitcoin,
+            OffsetDateTime::now_utc(),
         )
         .is_err())
     }
@@ -0,0 +1,314 @@
+use crate::db::positions;
+use crate::node::Node;
+use crate::position::models::Position;
+use crate::settings::RolloverMaintenanceWindow;
+use crate::settings::Settings;
+use crate::trace;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Network;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+
+/// How often we check for positions which have to be rolled over.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often we propose a rollover before we give up until the next rollover window.
+const MAX_ROLLOVER_ATTEMPTS: u32 = 5;
+
+/// How long we wait after the first failed attempt. The backoff doubles with every further failed
+/// attempt.
+const INITIAL_RETRY_BACKOFF: time::Duration = time::Duration::minutes(1);
+
+const MAX_RETRY_BACKOFF: time::Duration = time::Duration::hours(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RolloverState {
+    /// The position has to be rolled over. A rollover will be proposed as soon as the trader is
+    /// connected and the next attempt is due.
+    Pending,
+    /// Proposing the rollover failed too often. We will not try again within this rollover
+    /// window.
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledRollover {
+    pub trader_id: PublicKey,
+    pub position_id: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiry_timestamp: OffsetDateTime,
+    pub state: RolloverState,
+    pub attempts: u32,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub next_attempt: Option<OffsetDateTime>,
+    pub last_error: Option<String>,
+    /// Whether the position was left in the midst of a rollover, e.g. by a restart of the
+    /// coordinator.
+    pub interrupted: bool,
+}
+
+impl ScheduledRollover {
+    fn new(position: &Position) -> Self {
+        Self {
+            trader_id: position.trader,
+            position_id: position.id,
+            expiry_timestamp: position.expiry_timestamp,
+            state: RolloverState::Pending,
+            attempts: 0,
+            next_attempt: None,
+            last_error: None,
+            interrupted: false,
+        }
+    }
+
+    fn interrupted(position: &Position) -> Self {
+        Self {
+            interrupted: true,
+            ..Self::new(position)
+        }
+    }
+
+    fn is_due(&self, now: OffsetDateTime) -> bool {
+        self.state == RolloverState::Pending
+            && self.next_attempt.map(|next| next <= now).unwrap_or(true)
+    }
+
+    fn record_failure(&mut self, now: OffsetDateTime, error: String) {
+        self.attempts += 1;
+        self.last_error = Some(error);
+
+        if self.attempts >= MAX_ROLLOVER_ATTEMPTS {
+            self.state = RolloverState::Failed;
+            self.next_attempt = None;
+        } else {
+            self.next_attempt = Some(now + retry_backoff(self.attempts));
+        }
+    }
+}
+
+/// Keeps track of the positions which have to be rolled over within the current rollover window.
+///
+/// Rollovers are only proposed by the coordinator during the configured
+/// [`RolloverMaintenanceWindow`]. Outside of it, traders can still request a rollover themselves.
+#[derive(Clone, Default)]
+pub struct RolloverScheduler {
+    rollovers: Arc<RwLock<HashMap<PublicKey, ScheduledRollover>>>,
+}
+
+impl RolloverScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All rollovers which are either pending or have failed within the current rollover window.
+    pub fn get_rollovers(&self) -> Vec<ScheduledRollover> {
+        let mut rollovers = self.rollovers.read().values().cloned().collect::<Vec<_>>();
+        rollovers.sort_by_key(|rollover| rollover.position_id);
+
+        rollovers
+    }
+
+    /// Schedule the positions which have been left in the midst of a rollover, so that the
+    /// rollover is resumed with the trader.
+    async fn schedule_interrupted_rollovers(&self, node: &Node) -> Result<()> {
+        let positions = spawn_blocking({
+            let pool = node.pool.clone();
+            move || {
+                let mut conn = pool.get()?;
+                let positions = positions::Position::get_all_positions_in_rollover(&mut conn)?;
+
+                anyhow::Ok(positions)
+            }
+        })
+        .await??;
+
+        let mut rollovers = self.rollovers.write();
+        for position in positions {
+            tracing::info!(
+                trader_id = %position.trader,
+                position_id = position.id,
+                "Scheduling interrupted rollover"
+            );
+
+            rollovers.insert(position.trader, ScheduledRollover::interrupted(&position));
+        }
+
+        Ok(())
+    }
+
+    async fn schedule_rollovers(
+        &self,
+        node: &Node,
+        maintenance_window: RolloverMaintenanceWindow,
+        network: Network,
+    ) -> Result<()> {
+        let now = node.clock.now();
+
+        if !commons::is_eligible_for_rollover(now, network) {
+            // Positions which have not been rolled over by now will expire.
+            self.rollovers.write().clear();
+            return Ok(());
+        }
+
+        let next_expiry = commons::calculate_next_expiry(now, network);
+        let positions = spawn_blocking({
+            let pool = node.pool.clone();
+            move || {
+                let mut conn = pool.get()?;
+                let positions = positions::Position::get_all_open_positions_with_expiry_before(
+                    &mut conn,
+                    next_expiry,
+                )?;
+
+                anyhow::Ok(positions)
+            }
+        })
+        .await??;
+
+        let due_rollovers = {
+            let mut rollovers = self.rollovers.write();
+
+            // Positions which have been rolled over or closed in the meantime are not of interest
+            // anymore. Interrupted rollovers have already been given the next expiry, hence they
+            // are kept until they are resumed.
+            rollovers.retain(|trader_id, rollover| {
+                rollover.interrupted
+                    || positions
+                        .iter()
+                        .any(|position| position.trader == *trader_id)
+            });
+
+            for position in positions
+                .iter()
+                .filter(|position| !position.is_expired(now))
+            {
+                rollovers
+                    .entry(position.trader)
+                    .or_insert_with(|| ScheduledRollover::new(position));
+            }
+
+            if !maintenance_window.contains(now) {
+                return Ok(());
+            }
+
+            rollovers
+                .values()
+                .filter(|rollover| rollover.is_due(now))
+                .map(|rollover| (rollover.trader_id, rollover.interrupted))
+                .collect::<Vec<_>>()
+        };
+
+        for (trader_id, interrupted) in due_rollovers {
+            // The trader has to be online to accept the renew offer.
+            if !node.is_connected(&trader_id) {
+                continue;
+            }
+
+            tracing::info!(%trader_id, interrupted, "Proposing scheduled rollover");
+
+            let result = if interrupted {
+                trace::with_new_trace(
+                    "scheduled_rollover",
+                    node.retry_interrupted_rollover(trader_id, network),
+                )
+                .await
+            } else {
+                trace::with_new_trace(
+                    "scheduled_rollover",
+                    propose_rollover(node, trader_id, network),
+                )
+                .await
+            };
+
+            if let Err(e) = result {
+                tracing::warn!(%trader_id, "Failed to propose scheduled rollover: {e:#}");
+
+                if let Some(rollover) = self.rollovers.write().get_mut(&trader_id) {
+                    rollover.record_failure(node.clock.now(), format!("{e:#}"));
+                }
+
+                continue;
+            }
+
+            // The position is now in rollover and will be picked up again should the trader fail
+            // to finish the protocol.
+            self.rollovers.write().remove(&trader_id);
+        }
+
+        Ok(())
+    }
+}
+
+/// Periodically propose rollovers for positions which would otherwise expire at the end of the
+/// current rollover window.
+///
+/// The rollover maintenance window is read from the settings on every check, so that changes to
+/// it take effect without a restart. Rollovers which were interrupted by a restart are resumed.
+pub fn monitor(
+    node: Node,
+    rollover_scheduler: RolloverScheduler,
+    settings: Arc<tokio::sync::RwLock<Settings>>,
+    network: Network,
+) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        if let Err(e) = rollover_scheduler
+            .schedule_interrupted_rollovers(&node)
+            .await
+        {
+            tracing::error!("Failed to schedule interrupted rollovers: {e:#}");
+        }
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let maintenance_window = settings.read().await.rollover_maintenance_window;
+            if let Err(e) = rollover_scheduler
+                .schedule_rollovers(&node, maintenance_window, network)
+                .await
+            {
+                tracing::error!("Failed to schedule rollovers: {e:#}");
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn propose_rollover(node: &Node, trader_id: PublicKey, network: Network) -> Result<()> {
+    let signed_channel = node.inner.get_signed_channel_by_trader_id(trader_id)?;
+
+    node.propose_rollover(&signed_channel.channel_id, network)
+        .await
+}
+
+fn retry_backoff(attempts: u32) -> time::Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    let backoff = INITIAL_RETRY_BACKOFF * 2_i32.pow(exponent);
+
+    backoff.min(MAX_RETRY_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_backoff_doubles_until_max() {
+        assert_eq!(retry_backoff(1), time::Duration::minutes(1));
+        assert_eq!(retry_backoff(2), time::Duration::minutes(2));
+        assert_eq!(retry_backoff(3), time::Duration::minutes(4));
+        assert_eq!(retry_backoff(7), time::Duration::hours(1));
+        assert_eq!(retry_backoff(100), time::Duration::hours(1));
+    }
+}
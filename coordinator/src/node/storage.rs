@@ -17,6 +17,7 @@ use ln_dlc_node::node;
 use ln_dlc_node::transaction::Transaction;
 use ln_dlc_node::HTLCStatus;
 use ln_dlc_node::MillisatAmount;
+use ln_dlc_node::PaymentFailureReason;
 use ln_dlc_node::PaymentFlow;
 use ln_dlc_node::PaymentInfo;
 use time::OffsetDateTime;
@@ -50,6 +51,7 @@ impl node::Storage for NodeStorage {
         preimage: Option<PaymentPreimage>,
         secret: Option<PaymentSecret>,
         _: Option<Txid>,
+        failure_reason: Option<PaymentFailureReason>,
     ) -> Result<()> {
         let mut conn = self.pool.get()?;
 
@@ -62,6 +64,7 @@ impl node::Storage for NodeStorage {
                     fee_msat,
                     preimage,
                     secret,
+                    failure_reason,
                     &mut conn,
                 )?;
             }
@@ -80,6 +83,9 @@ impl node::Storage for NodeStorage {
                             description: "".to_string(),
                             invoice: None,
                             funding_txid: None,
+                            expiry: None,
+                            payee: None,
+                            failure_reason,
                         },
                     ),
                     &mut conn,
@@ -0,0 +1,290 @@
+use crate::alert::Alerter;
+use crate::metrics::DLC_CHANNELS_IN_PROTOCOL;
+use crate::metrics::DLC_CHANNELS_STUCK;
+use crate::metrics::DLC_CHANNEL_PROTOCOL_STATE_MAX_AGE_SECONDS;
+use crate::node::Node;
+use anyhow::Context;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::secp256k1::PublicKey;
+use dlc_manager::channel::signed_channel::SignedChannelState;
+use dlc_manager::channel::Channel;
+use dlc_manager::DlcChannelId;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use opentelemetry::KeyValue;
+use std::collections::HashMap;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+
+/// How often we check the states of the DLC channels.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A DLC channel which has been in the same state of a protocol for longer is considered stuck,
+/// e.g. in `RenewOffered` because the trader never answered the offer to roll over.
+const MAX_PROTOCOL_STATE_AGE: time::Duration = time::Duration::hours(1);
+
+/// The states of the protocols we run with the traders on a DLC channel.
+const PROTOCOL_STATES: [&str; 11] = [
+    "Offered",
+    "Accepted",
+    "SettledOffered",
+    "SettledReceived",
+    "SettledAccepted",
+    "SettledConfirmed",
+    "RenewOffered",
+    "RenewAccepted",
+    "RenewConfirmed",
+    "RenewFinalized",
+    "CollaborativeCloseOffered",
+];
+
+/// Periodically check for DLC channels which are stuck in a protocol with the trader, export how
+/// many channels are in each state of a protocol, and alert about every channel which got stuck.
+///
+/// dlc-manager does not record when a channel entered its state, hence we measure from when we
+/// first saw the channel in its state. After a restart, a channel is only reported as stuck once it
+/// has been in its state for [`MAX_PROTOCOL_STATE_AGE`] since the restart.
+pub fn monitor(node: Node, alerter: Alerter) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        let mut states = ProtocolStates::default();
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let channels = {
+                let node = node.clone();
+                spawn_blocking(move || node.inner.list_dlc_channels())
+                    .await
+                    .context("Listing DLC channels did not complete")
+                    .and_then(|channels| channels)
+            };
+            let channels = match channels {
+                Ok(channels) => channels,
+                Err(e) => {
+                    tracing::error!("Failed to list DLC channels: {e:#}");
+                    continue;
+                }
+            };
+
+            let now = OffsetDateTime::now_utc();
+            let stuck = states.update(
+                channels.iter().map(|channel| {
+                    (
+                        channel.get_id(),
+                        channel.get_counter_party_id(),
+                        protocol_state(channel),
+                    )
+                }),
+                now,
+            );
+
+            states.observe(now);
+
+            for channel in stuck {
+                alerter
+                    .alert(&format!(
+                        "DLC channel {} with trader {} has been stuck in {} for {} minutes",
+                        channel.dlc_channel_id.to_hex(),
+                        channel.trader_id,
+                        channel.state,
+                        channel.age.whole_minutes()
+                    ))
+                    .await;
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+/// The state of the protocol the DLC channel is in, or `None` if it is not in a protocol.
+///
+/// Channels which are closing are not in a protocol with the trader, as they are waiting for the
+/// blockchain.
+fn protocol_state(channel: &Channel) -> Option<&'static str> {
+    let state = match channel {
+        Channel::Offered(_) => "Offered",
+        Channel::Accepted(_) => "Accepted",
+        Channel::Signed(signed_channel) => match signed_channel.state {
+            SignedChannelState::SettledOffered { .. } => "SettledOffered",
+            SignedChannelState::SettledReceived { .. } => "SettledReceived",
+            SignedChannelState::SettledAccepted { .. } => "SettledAccepted",
+            SignedChannelState::SettledConfirmed { .. } => "SettledConfirmed",
+            SignedChannelState::RenewOffered { .. } => "RenewOffered",
+            SignedChannelState::RenewAccepted { .. } => "RenewAccepted",
+            SignedChannelState::RenewConfirmed { .. } => "RenewConfirmed",
+            SignedChannelState::RenewFinalized { .. } => "RenewFinalized",
+            SignedChannelState::CollaborativeCloseOffered { .. } => "CollaborativeCloseOffered",
+            SignedChannelState::Established { .. }
+            | SignedChannelState::Settled { .. }
+            | SignedChannelState::Closing { .. } => return None,
+        },
+        Channel::Closing(_)
+        | Channel::Closed(_)
+        | Channel::CounterClosed(_)
+        | Channel::ClosedPunished(_)
+        | Channel::CollaborativelyClosed(_)
+        | Channel::FailedAccept(_)
+        | Channel::FailedSign(_) => return None,
+    };
+
+    Some(state)
+}
+
+/// The DLC channels which are in a state of a protocol, and since when.
+#[derive(Default)]
+struct ProtocolStates(HashMap<DlcChannelId, TrackedState>);
+
+struct TrackedState {
+    trader_id: PublicKey,
+    state: &'static str,
+    since: OffsetDateTime,
+    /// Whether we have alerted about the channel being stuck in this state.
+    alerted: bool,
+}
+
+#[derive(Debug)]
+struct StuckChannel {
+    dlc_channel_id: DlcChannelId,
+    trader_id: PublicKey,
+    state: &'static str,
+    age: time::Duration,
+}
+
+impl ProtocolStates {
+    /// Update the tracked states with the current states of the channels, returning the channels
+    /// which got stuck since the last update.
+    fn update(
+        &mut self,
+        channels: impl Iterator<Item = (DlcChannelId, PublicKey, Option<&'static str>)>,
+        now: OffsetDateTime,
+    ) -> Vec<StuckChannel> {
+        let mut tracked = HashMap::new();
+        for (dlc_channel_id, trader_id, state) in channels {
+            let state = match state {
+                Some(state) => state,
+                None => continue,
+            };
+
+            let tracked_state = match self.0.remove(&dlc_channel_id) {
+                Some(tracked_state) if tracked_state.state == state => tracked_state,
+                _ => TrackedState {
+                    trader_id,
+                    state,
+                    since: now,
+                    alerted: false,
+                },
+            };
+            tracked.insert(dlc_channel_id, tracked_state);
+        }
+        self.0 = tracked;
+
+        let mut stuck = vec![];
+        for (dlc_channel_id, tracked_state) in self.0.iter_mut() {
+            let age = now - tracked_state.since;
+            if age > MAX_PROTOCOL_STATE_AGE && !tracked_state.alerted {
+                tracked_state.alerted = true;
+                stuck.push(StuckChannel {
+                    dlc_channel_id: *dlc_channel_id,
+                    trader_id: tracked_state.trader_id,
+                    state: tracked_state.state,
+                    age,
+                });
+            }
+        }
+
+        stuck
+    }
+
+    fn observe(&self, now: OffsetDateTime) {
+        let cx = opentelemetry::Context::current();
+
+        for state in PROTOCOL_STATES {
+            let ages = self
+                .0
+                .values()
+                .filter(|tracked_state| tracked_state.state == state)
+                .map(|tracked_state| now - tracked_state.since)
+                .collect::<Vec<_>>();
+
+            let count = ages.len() as u64;
+            let stuck = ages
+                .iter()
+                .filter(|age| **age > MAX_PROTOCOL_STATE_AGE)
+                .count() as u64;
+            let max_age = ages
+                .iter()
+                .max()
+                .map(|age| age.whole_seconds() as u64)
+                .unwrap_or_default();
+
+            let key_values = [KeyValue::new("state", state)];
+            DLC_CHANNELS_IN_PROTOCOL.observe(&cx, count, &key_values);
+            DLC_CHANNELS_STUCK.observe(&cx, stuck, &key_values);
+            DLC_CHANNEL_PROTOCOL_STATE_MAX_AGE_SECONDS.observe(&cx, max_age, &key_values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn channel_is_reported_once_when_stuck() {
+        let mut states = ProtocolStates::default();
+        let channel = ([1; 32], trader_id(), Some("RenewOffered"));
+        let start = OffsetDateTime::now_utc();
+
+        assert!(states.update([channel].into_iter(), start).is_empty());
+        assert!(states
+            .update([channel].into_iter(), start + time::Duration::minutes(30))
+            .is_empty());
+
+        let stuck = states.update([channel].into_iter(), start + time::Duration::minutes(61));
+        assert_eq!(stuck.len(), 1);
+        assert_eq!(stuck[0].state, "RenewOffered");
+        assert_eq!(stuck[0].age, time::Duration::minutes(61));
+
+        assert!(states
+            .update([channel].into_iter(), start + time::Duration::minutes(120))
+            .is_empty());
+    }
+
+    #[test]
+    fn new_state_restarts_the_clock() {
+        let mut states = ProtocolStates::default();
+        let start = OffsetDateTime::now_utc();
+
+        states.update(
+            [([1; 32], trader_id(), Some("RenewOffered"))].into_iter(),
+            start,
+        );
+        let stuck = states.update(
+            [([1; 32], trader_id(), Some("RenewAccepted"))].into_iter(),
+            start + time::Duration::minutes(61),
+        );
+        assert!(stuck.is_empty());
+
+        // The channel finished the protocol in between.
+        states.update(
+            [([1; 32], trader_id(), None)].into_iter(),
+            start + time::Duration::minutes(70),
+        );
+        let stuck = states.update(
+            [([1; 32], trader_id(), Some("RenewAccepted"))].into_iter(),
+            start + time::Duration::minutes(122),
+        );
+        assert!(stuck.is_empty());
+    }
+
+    fn trader_id() -> PublicKey {
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap()
+    }
+}
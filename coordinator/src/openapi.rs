@@ -0,0 +1,128 @@
+//! The OpenAPI description of the coordinator's REST API.
+//!
+//! The schema is generated from the handlers and the types they exchange, so that it can't drift
+//! from what is actually served. It is served at [`OPENAPI_PATH`] and can be browsed at
+//! [`SWAGGER_UI_PATH`].
+
+use crate::admin;
+use crate::orderbook;
+use crate::routes;
+use serde::Serialize;
+use utoipa::openapi::security::HttpAuthScheme;
+use utoipa::openapi::security::HttpBuilder;
+use utoipa::openapi::security::SecurityScheme;
+use utoipa::Modify;
+use utoipa::OpenApi;
+use utoipa::ToSchema;
+use utoipa_swagger_ui::SwaggerUi;
+
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+pub const SWAGGER_UI_PATH: &str = "/api/docs";
+
+/// The body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "10101 coordinator"),
+    paths(
+        orderbook::routes::get_orders,
+        orderbook::routes::post_order,
+        orderbook::routes::get_order,
+        orderbook::routes::put_order,
+        orderbook::routes::post_rollback_match,
+        routes::back_up,
+        routes::back_up_all,
+        routes::delete_backup,
+        routes::restore,
+        routes::collaborative_revert_confirm,
+        admin::get_balance,
+        admin::collaborative_revert,
+        admin::collaborative_revert_batch,
+        admin::get_trading_halt,
+        admin::halt_trading,
+        admin::resume_trading,
+    ),
+    components(schemas(
+        ErrorResponse,
+        commons::NewOrder,
+        commons::Order,
+        commons::OrderType,
+        commons::OrderState,
+        commons::OrderReason,
+        commons::RollbackMatch,
+        commons::Signature,
+        commons::Backup,
+        commons::DeleteBackup,
+        commons::Restore,
+        commons::CollaborativeRevertCoordinatorRequest,
+        commons::CollaborativeRevertCoordinatorBatchRequest,
+        commons::CollaborativeRevertChannel,
+        commons::CollaborativeRevertTraderResponse,
+        ::trade::ContractSymbol,
+        ::trade::Direction,
+        orderbook::routes::UpdateOrder,
+        admin::Balance,
+        admin::CollaborativeRevertResult,
+        admin::TradingHaltStatus,
+        orderbook::trading_halt::HaltReason,
+    )),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "orderbook", description = "Submit and manage orders"),
+        (name = "backup", description = "Back up and restore the app's data"),
+        (name = "collaborative revert", description = "Revert channels collaboratively"),
+        (name = "admin", description = "Operate the coordinator"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Both the admin token and the API keys of traders are passed as bearer tokens.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let bearer =
+            || SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build());
+
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("admin_token", bearer());
+        components.add_security_scheme("api_key", bearer());
+    }
+}
+
+/// The Swagger UI, which also serves the OpenAPI description.
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new(SWAGGER_UI_PATH).url(OPENAPI_PATH, ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openapi_describes_annotated_routes() {
+        let openapi = ApiDoc::openapi();
+
+        for path in [
+            "/api/v1/orderbook/orders",
+            "/api/v1/orderbook/orders/{order_id}",
+            "/api/v1/backup/{node_id}",
+            "/api/v1/channels/revertconfirm",
+            "/api/admin/collaborative_revert/batch",
+        ] {
+            assert!(openapi.paths.paths.contains_key(path), "{path} is missing");
+        }
+
+        let schemas = openapi.components.expect("components").schemas;
+        for schema in ["NewOrder", "Order", "Backup", "ContractSymbol"] {
+            assert!(schemas.contains_key(schema), "{schema} is missing");
+        }
+
+        // The description has to be valid JSON, as this is what we serve.
+        openapi.to_json().expect("to serialize");
+    }
+}
@@ -7,6 +7,7 @@ use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::Network;
 use bitcoin::XOnlyPublicKey;
+use commons::Clock;
 use commons::FilledWith;
 use commons::Match;
 use commons::Matches;
@@ -18,6 +19,7 @@ use diesel::r2d2::Pool;
 use diesel::PgConnection;
 use futures::future::RemoteHandle;
 use futures::FutureExt;
+use std::sync::Arc;
 use time::OffsetDateTime;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::error::RecvError;
@@ -30,6 +32,7 @@ pub fn monitor(
     notifier: mpsc::Sender<OrderbookMessage>,
     network: Network,
     oracle_pk: XOnlyPublicKey,
+    clock: Arc<dyn Clock>,
 ) -> RemoteHandle<()> {
     let mut user_feed = tx_user_feed.subscribe();
     let (fut, remote_handle) = async move {
@@ -39,6 +42,7 @@ pub fn monitor(
                     tokio::spawn({
                         let notifier = notifier.clone();
                         let pool = pool.clone();
+                        let now = clock.now();
                         async move {
                             tracing::debug!(
                                 trader_id=%new_user_msg.new_user,
@@ -50,6 +54,7 @@ pub fn monitor(
                                 new_user_msg.new_user,
                                 network,
                                 oracle_pk,
+                                now,
                             )
                             .await
                             {
@@ -85,10 +90,9 @@ async fn process_pending_match(
     trader_id: PublicKey,
     network: Network,
     oracle_pk: XOnlyPublicKey,
+    now: OffsetDateTime,
 ) -> Result<()> {
-    let mut conn = spawn_blocking(move || pool.get())
-        .await
-        .expect("task to complete")?;
+    let mut conn = spawn_blocking(move || pool.get()).await??;
 
     if let Some(order) =
         orders::get_by_trader_id_and_state(&mut conn, trader_id, OrderState::Matched)?
@@ -96,7 +100,7 @@ async fn process_pending_match(
         tracing::debug!(%trader_id, order_id=%order.id, "Notifying trader about pending match");
 
         let matches = matches::get_matches_by_order_id(&mut conn, order.id)?;
-        let filled_with = get_filled_with_from_matches(matches, network, oracle_pk)?;
+        let filled_with = get_filled_with_from_matches(matches, network, oracle_pk, now)?;
 
         let message = match order.order_reason {
             OrderReason::Manual => Message::Match(filled_with),
@@ -123,6 +127,7 @@ fn get_filled_with_from_matches(
     matches: Vec<Matches>,
     network: Network,
     oracle_pk: XOnlyPublicKey,
+    now: OffsetDateTime,
 ) -> Result<FilledWith> {
     ensure!(
         !matches.is_empty(),
@@ -134,7 +139,7 @@ fn get_filled_with_from_matches(
         .expect("to have at least one match")
         .order_id;
 
-    let expiry_timestamp = commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
+    let expiry_timestamp = commons::calculate_next_expiry(now, network);
 
     Ok(FilledWith {
         order_id,
@@ -0,0 +1,179 @@
+use crate::orderbook::db::events;
+use crate::orderbook::db::matches;
+use crate::orderbook::db::orders;
+use crate::orderbook::event_log::OrderbookEvent;
+use crate::orderbook::trading::TraderMatchParams;
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use commons::NewOrder;
+use commons::OrderReason;
+use commons::OrderState;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::Connection;
+use diesel::PgConnection;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::task::spawn_blocking;
+use trade::ContractSymbol;
+use uuid::Uuid;
+
+/// The number of write units buffered in the channel before callers have to wait.
+const WRITE_BUFFER_SIZE: usize = 100;
+
+/// The maximum number of write units which are committed in a single database transaction.
+const MAX_BATCH_SIZE: usize = 100;
+
+pub enum Write {
+    InsertOrder {
+        new_order: NewOrder,
+        order_reason: OrderReason,
+    },
+    SetOrderState {
+        order_id: Uuid,
+        order_state: OrderState,
+    },
+    InsertMatch(TraderMatchParams),
+    AppendEvent {
+        contract_symbol: ContractSymbol,
+        event: OrderbookEvent,
+    },
+}
+
+/// Writes which have to be committed atomically.
+struct Unit {
+    writes: Vec<Write>,
+    committed: oneshot::Sender<Result<()>>,
+}
+
+/// Writes the changes of the matching engine to the database in the background.
+///
+/// Units which have been queued while the previous batch was being committed are committed
+/// together in a single transaction. If that transaction fails, the units of the batch are
+/// committed one by one, so that a single bad unit does not affect the others.
+#[derive(Clone)]
+pub struct BatchWriter {
+    sender: mpsc::Sender<Unit>,
+}
+
+impl BatchWriter {
+    pub fn spawn(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<Unit>(WRITE_BUFFER_SIZE);
+
+        tokio::spawn(async move {
+            while let Some(unit) = receiver.recv().await {
+                let mut batch = vec![unit];
+                while batch.len() < MAX_BATCH_SIZE {
+                    match receiver.try_recv() {
+                        Ok(unit) => batch.push(unit),
+                        Err(_) => break,
+                    }
+                }
+
+                let pool = pool.clone();
+                if let Err(e) = spawn_blocking(move || write_batch(pool, batch)).await {
+                    tracing::error!("Failed to write batch: {e:#}");
+                }
+            }
+
+            tracing::error!("Batch writer channel closed");
+        });
+
+        Self { sender }
+    }
+
+    /// Queue `writes` to be committed atomically.
+    ///
+    /// The returned receiver resolves once the writes have been committed. Writes are committed in
+    /// the order in which they have been queued.
+    pub async fn write(&self, writes: Vec<Write>) -> Result<oneshot::Receiver<Result<()>>> {
+        let (committed, receiver) = oneshot::channel();
+
+        self.sender
+            .send(Unit { writes, committed })
+            .await
+            .map_err(|_| anyhow!("Batch writer has stopped"))?;
+
+        Ok(receiver)
+    }
+
+    /// Wait until all previously queued writes have been committed.
+    pub async fn flush(&self) -> Result<()> {
+        self.write(vec![])
+            .await?
+            .await
+            .context("Batch writer has stopped")?
+    }
+}
+
+fn write_batch(pool: Pool<ConnectionManager<PgConnection>>, batch: Vec<Unit>) {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            for unit in batch {
+                let _ = unit
+                    .committed
+                    .send(Err(anyhow!("Failed to get db connection: {e:#}")));
+            }
+            return;
+        }
+    };
+
+    if batch.len() > 1 {
+        let result = conn.transaction(|conn| {
+            for unit in batch.iter() {
+                apply(conn, &unit.writes)?;
+            }
+
+            anyhow::Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                for unit in batch {
+                    let _ = unit.committed.send(Ok(()));
+                }
+                return;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    units = batch.len(),
+                    "Failed to commit batch, committing units one by one: {e:#}"
+                );
+            }
+        }
+    }
+
+    for unit in batch {
+        let result = conn.transaction(|conn| apply(conn, &unit.writes));
+        let _ = unit.committed.send(result);
+    }
+}
+
+fn apply(conn: &mut PgConnection, writes: &[Write]) -> Result<()> {
+    for write in writes {
+        match write {
+            Write::InsertOrder {
+                new_order,
+                order_reason,
+            } => {
+                orders::insert(conn, new_order.clone(), order_reason.clone())
+                    .context("Failed to insert new order into DB")?;
+            }
+            Write::SetOrderState {
+                order_id,
+                order_state,
+            } => {
+                orders::set_order_state(conn, *order_id, order_state.clone())?;
+            }
+            Write::InsertMatch(match_params) => matches::insert(conn, match_params)?,
+            Write::AppendEvent {
+                contract_symbol,
+                event,
+            } => events::append(conn, *contract_symbol, event)?,
+        }
+    }
+
+    Ok(())
+}
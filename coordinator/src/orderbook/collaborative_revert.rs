@@ -73,9 +73,7 @@ async fn process_pending_collaborative_revert(
     notifier: mpsc::Sender<OrderbookMessage>,
     trader_id: PublicKey,
 ) -> Result<()> {
-    let mut conn = spawn_blocking(move || pool.get())
-        .await
-        .expect("task to complete")?;
+    let mut conn = spawn_blocking(move || pool.get()).await??;
 
     match collaborative_reverts::by_trader_pubkey(trader_id.to_string().as_str(), &mut conn)? {
         None => {
@@ -0,0 +1,146 @@
+use crate::db::positions::ContractSymbol;
+use crate::orderbook::event_log::OrderbookEvent;
+use crate::schema::orderbook_events;
+use crate::schema::orderbook_snapshots;
+use anyhow::Result;
+use commons::Order;
+use diesel::dsl::max;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = orderbook_events)]
+struct NewEvent {
+    contract_symbol: ContractSymbol,
+    event_type: String,
+    order_id: Uuid,
+    payload: String,
+}
+
+#[derive(Queryable, Debug)]
+struct Event {
+    id: i64,
+    #[allow(dead_code)]
+    contract_symbol: ContractSymbol,
+    #[allow(dead_code)]
+    event_type: String,
+    #[allow(dead_code)]
+    order_id: Uuid,
+    payload: String,
+    #[allow(dead_code)]
+    created_at: OffsetDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = orderbook_snapshots)]
+struct NewSnapshot {
+    contract_symbol: ContractSymbol,
+    last_event_id: i64,
+    orders: String,
+}
+
+#[derive(Queryable, Debug)]
+struct Snapshot {
+    #[allow(dead_code)]
+    id: i32,
+    #[allow(dead_code)]
+    contract_symbol: ContractSymbol,
+    last_event_id: i64,
+    orders: String,
+    #[allow(dead_code)]
+    created_at: OffsetDateTime,
+}
+
+pub fn append(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+    event: &OrderbookEvent,
+) -> Result<()> {
+    diesel::insert_into(orderbook_events::table)
+        .values(NewEvent {
+            contract_symbol: contract_symbol.into(),
+            event_type: event.event_type().to_string(),
+            order_id: event.order_id(),
+            payload: serde_json::to_string(event)?,
+        })
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Load the events of `contract_symbol` which have been appended after the event with
+/// `last_event_id`, in the order in which they have been appended.
+pub fn load_after(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+    last_event_id: i64,
+) -> Result<Vec<(i64, OrderbookEvent)>> {
+    let events: Vec<Event> = orderbook_events::table
+        .filter(orderbook_events::contract_symbol.eq(ContractSymbol::from(contract_symbol)))
+        .filter(orderbook_events::id.gt(last_event_id))
+        .order_by(orderbook_events::id.asc())
+        .load(conn)?;
+
+    events
+        .into_iter()
+        .map(|event| Ok((event.id, serde_json::from_str(&event.payload)?)))
+        .collect()
+}
+
+pub fn get_last_event_id(conn: &mut PgConnection) -> QueryResult<i64> {
+    let last_event_id = orderbook_events::table
+        .select(max(orderbook_events::id))
+        .first::<Option<i64>>(conn)?;
+
+    Ok(last_event_id.unwrap_or_default())
+}
+
+/// Block other transactions from appending events until the current transaction ends.
+///
+/// This also waits for transactions which are appending events to finish.
+pub fn lock(conn: &mut PgConnection) -> QueryResult<()> {
+    diesel::sql_query("LOCK TABLE orderbook_events IN SHARE MODE").execute(conn)?;
+
+    Ok(())
+}
+
+pub fn insert_snapshot(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+    last_event_id: i64,
+    orders: &[Order],
+) -> Result<()> {
+    diesel::insert_into(orderbook_snapshots::table)
+        .values(NewSnapshot {
+            contract_symbol: contract_symbol.into(),
+            last_event_id,
+            orders: serde_json::to_string(orders)?,
+        })
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Returns the id of the last event included in the latest snapshot of `contract_symbol`,
+/// together with the open limit orders at that point.
+pub fn get_latest_snapshot(
+    conn: &mut PgConnection,
+    contract_symbol: trade::ContractSymbol,
+) -> Result<Option<(i64, Vec<Order>)>> {
+    let snapshot: Option<Snapshot> = orderbook_snapshots::table
+        .filter(orderbook_snapshots::contract_symbol.eq(ContractSymbol::from(contract_symbol)))
+        .order_by(orderbook_snapshots::id.desc())
+        .first(conn)
+        .optional()?;
+
+    snapshot
+        .map(|snapshot| {
+            Ok((
+                snapshot.last_event_id,
+                serde_json::from_str(&snapshot.orders)?,
+            ))
+        })
+        .transpose()
+}
@@ -32,6 +32,7 @@ struct Matches {
     pub quantity: f32,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
+    pub failure_reason: Option<String>,
 }
 
 pub fn insert(conn: &mut PgConnection, match_params: &TraderMatchParams) -> Result<()> {
@@ -86,6 +87,25 @@ pub fn set_match_state_by_order_id(
     Ok(())
 }
 
+/// Sets the matches of the given order to [`MatchState::Failed`] and records why.
+pub fn set_matches_to_failed(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    failure_reason: &str,
+) -> Result<()> {
+    let affected_rows = diesel::update(matches::table)
+        .filter(matches::order_id.eq(order_id))
+        .set((
+            matches::match_state.eq(MatchState::Failed),
+            matches::failure_reason.eq(failure_reason),
+            matches::updated_at.eq(OffsetDateTime::now_utc()),
+        ))
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not update matches");
+    Ok(())
+}
+
 impl Matches {
     pub fn new(match_params: &TraderMatchParams, match_state: MatchState) -> Vec<Matches> {
         let order_id = match_params.filled_with.order_id;
@@ -107,6 +127,7 @@ impl Matches {
                 quantity: m.quantity.to_f32().expect("to fit into f32"),
                 created_at: updated_at,
                 updated_at,
+                failure_reason: None,
             })
             .collect()
     }
@@ -125,6 +146,7 @@ impl From<commons::Matches> for Matches {
             quantity: value.quantity.to_f32().expect("to fit into f32"),
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            failure_reason: None,
         }
     }
 }
@@ -1,3 +1,4 @@
 pub mod custom_types;
+pub mod events;
 pub mod matches;
 pub mod orders;
@@ -304,6 +304,52 @@ pub fn get_with_id(conn: &mut PgConnection, uid: Uuid) -> QueryResult<Option<Ord
     Ok(option)
 }
 
+/// Returns the order and locks it until the end of the current transaction.
+///
+/// Fails right away instead of waiting, if the order has been locked by another transaction.
+pub fn get_with_id_for_update(
+    conn: &mut PgConnection,
+    uid: Uuid,
+) -> QueryResult<Option<OrderbookOrder>> {
+    let order = orders::table
+        .filter(orders::trader_order_id.eq(uid))
+        .for_update()
+        .no_wait()
+        .first::<Order>(conn)
+        .optional()?;
+
+    Ok(order.map(OrderbookOrder::from))
+}
+
+/// Returns all [`OrderState::Matched`] market orders with the given reason, which have been created
+/// before `created_before`.
+pub fn get_matched_market_orders_created_before(
+    conn: &mut PgConnection,
+    order_reason: OrderBookOrderReason,
+    created_before: OffsetDateTime,
+) -> QueryResult<Vec<OrderbookOrder>> {
+    let orders: Vec<Order> = orders::table
+        .filter(orders::order_type.eq(OrderType::Market))
+        .filter(orders::order_state.eq(OrderState::Matched))
+        .filter(orders::order_reason.eq(OrderReason::from(order_reason)))
+        .filter(orders::timestamp.lt(created_before))
+        .load(conn)?;
+
+    Ok(orders.into_iter().map(OrderbookOrder::from).collect())
+}
+
+/// Returns the creation timestamp of the most recent open limit order, i.e. the last time the
+/// price feed got updated.
+pub fn get_latest_limit_order_timestamp(
+    conn: &mut PgConnection,
+) -> QueryResult<Option<OffsetDateTime>> {
+    orders::table
+        .filter(orders::order_type.eq(OrderType::Limit))
+        .filter(orders::order_state.eq(OrderState::Open))
+        .select(diesel::dsl::max(orders::timestamp))
+        .first(conn)
+}
+
 pub fn get_by_trader_id_and_state(
     conn: &mut PgConnection,
     trader_id: PublicKey,
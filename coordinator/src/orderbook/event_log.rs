@@ -0,0 +1,201 @@
+use crate::orderbook::db::events;
+use crate::orderbook::db::orders;
+use anyhow::Result;
+use commons::Order;
+use commons::OrderState;
+use commons::OrderType;
+use diesel::Connection;
+use diesel::PgConnection;
+use serde::Deserialize;
+use serde::Serialize;
+use trade::ContractSymbol;
+use uuid::Uuid;
+
+/// The number of events after which the matching engine takes a snapshot of its orderbook.
+pub const SNAPSHOT_INTERVAL: usize = 1_000;
+
+/// A change to the open limit orders of a contract symbol.
+///
+/// Every change to the orderbook is appended to the event log in the same transaction as the
+/// corresponding change to the orders table. The orderbook can thus be recovered after a crash by
+/// replaying the events on top of the latest snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderbookEvent {
+    OrderAdded(Order),
+    /// A limit order has been put back into the orderbook, e.g. because its match has been
+    /// reverted.
+    OrderAmended(Order),
+    /// A limit order has been taken out of the orderbook, either by the maker or because it has
+    /// expired.
+    OrderCancelled {
+        order_id: Uuid,
+    },
+    OrderMatched {
+        order_id: Uuid,
+    },
+}
+
+impl OrderbookEvent {
+    pub fn order_id(&self) -> Uuid {
+        match self {
+            OrderbookEvent::OrderAdded(order) | OrderbookEvent::OrderAmended(order) => order.id,
+            OrderbookEvent::OrderCancelled { order_id }
+            | OrderbookEvent::OrderMatched { order_id } => *order_id,
+        }
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            OrderbookEvent::OrderAdded(_) => "added",
+            OrderbookEvent::OrderAmended(_) => "amended",
+            OrderbookEvent::OrderCancelled { .. } => "cancelled",
+            OrderbookEvent::OrderMatched { .. } => "matched",
+        }
+    }
+
+    fn apply(self, limit_orders: &mut Vec<Order>) {
+        limit_orders.retain(|limit_order| limit_order.id != self.order_id());
+
+        match self {
+            OrderbookEvent::OrderAdded(order) | OrderbookEvent::OrderAmended(order) => {
+                limit_orders.push(order)
+            }
+            OrderbookEvent::OrderCancelled { .. } | OrderbookEvent::OrderMatched { .. } => {}
+        }
+    }
+}
+
+/// Recover the open limit orders of `contract_symbol` by replaying the events which have been
+/// appended after the latest snapshot.
+///
+/// If there is no snapshot yet, a first one is taken from the orders table.
+pub fn recover(conn: &mut PgConnection, contract_symbol: ContractSymbol) -> Result<Vec<Order>> {
+    let snapshot = match events::get_latest_snapshot(conn, contract_symbol)? {
+        Some(snapshot) => snapshot,
+        None => snapshot(conn, contract_symbol)?,
+    };
+
+    let (_, limit_orders) = replay(conn, contract_symbol, snapshot)?;
+
+    Ok(limit_orders)
+}
+
+/// Take a snapshot of the open limit orders of `contract_symbol`.
+///
+/// Appending events is blocked while the snapshot is taken. Otherwise an event could be committed
+/// after the snapshot even though its id is lower than the last event included in the snapshot,
+/// and it would never be replayed.
+pub fn snapshot(
+    conn: &mut PgConnection,
+    contract_symbol: ContractSymbol,
+) -> Result<(i64, Vec<Order>)> {
+    conn.transaction(|conn| {
+        events::lock(conn)?;
+
+        let (last_event_id, limit_orders) =
+            match events::get_latest_snapshot(conn, contract_symbol)? {
+                Some(snapshot) => replay(conn, contract_symbol, snapshot)?,
+                None => {
+                    let last_event_id = events::get_last_event_id(conn)?;
+                    let limit_orders =
+                        orders::get_all_orders(conn, OrderType::Limit, OrderState::Open, false)?
+                            .into_iter()
+                            .filter(|order| order.contract_symbol == contract_symbol)
+                            .collect::<Vec<_>>();
+
+                    (last_event_id, limit_orders)
+                }
+            };
+
+        events::insert_snapshot(conn, contract_symbol, last_event_id, &limit_orders)?;
+
+        tracing::info!(
+            %contract_symbol,
+            last_event_id,
+            limit_orders = limit_orders.len(),
+            "Took snapshot of orderbook"
+        );
+
+        Ok((last_event_id, limit_orders))
+    })
+}
+
+fn replay(
+    conn: &mut PgConnection,
+    contract_symbol: ContractSymbol,
+    (mut last_event_id, mut limit_orders): (i64, Vec<Order>),
+) -> Result<(i64, Vec<Order>)> {
+    for (event_id, event) in events::load_after(conn, contract_symbol, last_event_id)? {
+        event.apply(&mut limit_orders);
+        last_event_id = event_id;
+    }
+
+    Ok((last_event_id, limit_orders))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::PublicKey;
+    use commons::OrderReason;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+    use time::OffsetDateTime;
+    use trade::Direction;
+
+    #[test]
+    fn replaying_events_rebuilds_orderbook() {
+        let first = dummy_limit_order();
+        let second = dummy_limit_order();
+        let third = dummy_limit_order();
+
+        let events = vec![
+            OrderbookEvent::OrderAdded(first.clone()),
+            OrderbookEvent::OrderAdded(second.clone()),
+            OrderbookEvent::OrderAdded(third.clone()),
+            OrderbookEvent::OrderMatched { order_id: first.id },
+            OrderbookEvent::OrderCancelled {
+                order_id: second.id,
+            },
+            OrderbookEvent::OrderAmended(first.clone()),
+        ];
+
+        let mut limit_orders = vec![];
+        for event in events {
+            event.apply(&mut limit_orders);
+        }
+
+        assert_eq!(limit_orders, vec![third, first]);
+    }
+
+    #[test]
+    fn event_survives_serialization() {
+        let event = OrderbookEvent::OrderAdded(dummy_limit_order());
+
+        let serialized = serde_json::to_string(&event).unwrap();
+        let deserialized: OrderbookEvent = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized, event);
+    }
+
+    fn dummy_limit_order() -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            price: dec!(20_000),
+            leverage: 1.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction: Direction::Short,
+            quantity: dec!(100),
+            order_type: OrderType::Limit,
+            timestamp: OffsetDateTime::now_utc().replace_nanosecond(0).unwrap(),
+            expiry: OffsetDateTime::now_utc().replace_nanosecond(0).unwrap(),
+            order_state: OrderState::Open,
+            order_reason: OrderReason::Manual,
+            stable: false,
+        }
+    }
+}
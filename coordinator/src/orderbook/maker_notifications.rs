@@ -0,0 +1,130 @@
+use crate::config::MakerWebhook;
+use crate::message::OrderbookMessage;
+use bitcoin::secp256k1::PublicKey;
+use commons::Message;
+use commons::QuoteEvent;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+
+/// How long we wait for a maker webhook to respond before giving up on the attempt.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often we try to deliver an event to a maker webhook.
+const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+
+/// The backoff after the first failed webhook attempt, doubled after every further failure.
+const WEBHOOK_RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// How many quote updates are buffered for slow subscribers before they start missing updates.
+const QUOTE_UPDATES_CAPACITY: usize = 100;
+
+/// Notifies makers about updates of their quotes, i.e. when they are filled, partially filled,
+/// cancelled or have expired.
+///
+/// Every event is sent via the websocket, posted to all webhooks configured for the maker and
+/// published to the subscribers of the quote updates, e.g. the gRPC streams.
+/// Webhooks are called in the background, so that order processing is never held up by a slow
+/// maker endpoint. Webhook delivery is retried and thus at least once; makers have to deduplicate
+/// events by their id.
+#[derive(Clone)]
+pub struct MakerNotifier {
+    notifier: mpsc::Sender<OrderbookMessage>,
+    webhooks: Arc<Vec<MakerWebhook>>,
+    client: reqwest::Client,
+    quote_updates: broadcast::Sender<(PublicKey, QuoteEvent)>,
+}
+
+impl MakerNotifier {
+    pub fn new(notifier: mpsc::Sender<OrderbookMessage>, webhooks: Vec<MakerWebhook>) -> Self {
+        let (quote_updates, _) = broadcast::channel(QUOTE_UPDATES_CAPACITY);
+
+        Self {
+            notifier,
+            webhooks: Arc::new(webhooks),
+            client: reqwest::Client::new(),
+            quote_updates,
+        }
+    }
+
+    /// Subscribe to the updates of the quotes of all makers, together with the id of the maker.
+    pub fn subscribe(&self) -> broadcast::Receiver<(PublicKey, QuoteEvent)> {
+        self.quote_updates.subscribe()
+    }
+
+    pub async fn notify(&self, maker_id: PublicKey, event: QuoteEvent) {
+        tracing::debug!(
+            %maker_id,
+            order_id = %event.order_id,
+            kind = ?event.kind,
+            "Notifying maker about quote update"
+        );
+
+        for webhook in self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.maker_id == maker_id)
+        {
+            tokio::spawn({
+                let client = self.client.clone();
+                let url = webhook.url.clone();
+                let event = event.clone();
+                async move { deliver_webhook(client, url, event).await }
+            });
+        }
+
+        // Nobody might be subscribed.
+        let _ = self.quote_updates.send((maker_id, event.clone()));
+
+        let msg = OrderbookMessage::TraderMessage {
+            trader_id: maker_id,
+            message: Message::QuoteUpdate(event),
+            notification: None,
+        };
+
+        if let Err(e) = self.notifier.send(msg).await {
+            tracing::warn!(%maker_id, "Failed to send quote update to maker: {e:#}");
+        }
+    }
+}
+
+async fn deliver_webhook(client: reqwest::Client, url: String, event: QuoteEvent) {
+    let mut backoff = WEBHOOK_RETRY_BACKOFF;
+
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        let result = client
+            .post(&url)
+            .timeout(WEBHOOK_TIMEOUT)
+            .json(&event)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => {
+                tracing::debug!(url, event_id = %event.id, "Delivered quote update to maker webhook");
+                return;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    url,
+                    event_id = %event.id,
+                    attempt,
+                    "Failed to deliver quote update to maker webhook: {e:#}"
+                );
+            }
+        }
+
+        if attempt < WEBHOOK_MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    tracing::error!(
+        url,
+        event_id = %event.id,
+        "Giving up on delivering quote update to maker webhook"
+    );
+}
@@ -0,0 +1,230 @@
+use crate::message::OrderbookMessage;
+use crate::metrics::MATCH_EXECUTION_TIMEOUTS;
+use crate::orderbook::db::events;
+use crate::orderbook::db::matches;
+use crate::orderbook::db::orders;
+use crate::orderbook::event_log::OrderbookEvent;
+use crate::orderbook::trading;
+use crate::orderbook::trading::TradingMessage;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::Message;
+use commons::Order;
+use commons::OrderReason;
+use commons::OrderState;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::Connection;
+use diesel::PgConnection;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use opentelemetry::KeyValue;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
+
+/// How often we check for matches which have not been executed in time.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// If the trader does not execute a match within this time, the match is reverted.
+///
+/// This is deliberately longer than the timeout of the app's own watchdog, so that the app has the
+/// chance to roll back the match itself.
+const MATCH_EXECUTION_TIMEOUT: time::Duration = time::Duration::minutes(15);
+
+const MATCH_EXECUTION_TIMED_OUT: &str = "Match was not executed in time";
+
+pub(crate) struct RevertedMatch {
+    order: Order,
+    /// The maker orders which have been put back into the orderbook.
+    restored_orders: Vec<Order>,
+    makers: Vec<PublicKey>,
+}
+
+impl RevertedMatch {
+    pub(crate) fn has_restored_orders(&self) -> bool {
+        !self.restored_orders.is_empty()
+    }
+}
+
+/// Periodically revert matches of market orders which have not been executed in time.
+///
+/// Orders are only in [`OrderState::Matched`] until the trader sends the trade request, i.e. no
+/// DLC protocol has been started for these orders yet. Without reverting them, the trader would
+/// not be able to submit any further orders.
+///
+/// Matches of orders with [`OrderReason::Expired`] are not affected, as they are executed whenever
+/// the trader comes online.
+pub fn monitor(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    tx_price_feed: broadcast::Sender<Message>,
+    notifier: mpsc::Sender<OrderbookMessage>,
+    trading_sender: mpsc::Sender<TradingMessage>,
+) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            if let Err(e) =
+                revert_timed_out_matches(pool.clone(), &tx_price_feed, &notifier, &trading_sender)
+                    .await
+            {
+                tracing::error!("Failed to revert timed out matches: {e:#}");
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn revert_timed_out_matches(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    tx_price_feed: &broadcast::Sender<Message>,
+    notifier: &mpsc::Sender<OrderbookMessage>,
+    trading_sender: &mpsc::Sender<TradingMessage>,
+) -> Result<()> {
+    let reverted_matches = spawn_blocking(move || {
+        let mut conn = pool.get()?;
+
+        let created_before = OffsetDateTime::now_utc() - MATCH_EXECUTION_TIMEOUT;
+        let orders = orders::get_matched_market_orders_created_before(
+            &mut conn,
+            OrderReason::Manual,
+            created_before,
+        )?;
+
+        let mut reverted_matches = vec![];
+        for order in orders {
+            let order_id = order.id;
+            match revert_match(&mut conn, order, MATCH_EXECUTION_TIMED_OUT) {
+                Ok(reverted_match) => reverted_matches.push(reverted_match),
+                Err(e) => tracing::error!(%order_id, "Failed to revert match: {e:#}"),
+            }
+        }
+
+        anyhow::Ok(reverted_matches)
+    })
+    .await??;
+
+    if reverted_matches
+        .iter()
+        .any(RevertedMatch::has_restored_orders)
+    {
+        trading::reload_orderbook(trading_sender).await?;
+    }
+
+    for reverted_match in reverted_matches {
+        let order = &reverted_match.order;
+
+        tracing::warn!(
+            trader_id = %order.trader_id,
+            order_id = %order.id,
+            "Reverted match which was not executed within {MATCH_EXECUTION_TIMEOUT}"
+        );
+
+        MATCH_EXECUTION_TIMEOUTS.add(
+            &opentelemetry::Context::current(),
+            1,
+            &[KeyValue::new(
+                "contract_symbol",
+                order.contract_symbol.label(),
+            )],
+        );
+
+        publish_reverted_match(
+            reverted_match,
+            MATCH_EXECUTION_TIMED_OUT,
+            tx_price_feed,
+            notifier,
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+/// Broadcast the maker orders which have been put back into the orderbook and tell the trader and
+/// the makers that their match has been reverted.
+///
+/// The caller has to reload the orderbook beforehand, if any maker order has been restored.
+pub(crate) async fn publish_reverted_match(
+    reverted_match: RevertedMatch,
+    reason: &str,
+    tx_price_feed: &broadcast::Sender<Message>,
+    notifier: &mpsc::Sender<OrderbookMessage>,
+) {
+    let order = reverted_match.order;
+
+    for restored_order in reverted_match.restored_orders {
+        if let Err(e) = tx_price_feed.send(Message::Update(restored_order)) {
+            tracing::warn!("Could not update price feed: {e:#}");
+        }
+    }
+
+    let parties = std::iter::once(order.trader_id).chain(reverted_match.makers);
+    for trader_id in parties {
+        let message = OrderbookMessage::TraderMessage {
+            trader_id,
+            message: Message::MatchReverted {
+                order_id: order.id,
+                reason: reason.to_string(),
+            },
+            notification: None,
+        };
+
+        if let Err(e) = notifier.send(message).await {
+            tracing::warn!(%trader_id, "Failed to notify trader about reverted match: {e:#}");
+        }
+    }
+}
+
+/// Fail the order and its matches and put the matched maker orders back into the orderbook.
+pub(crate) fn revert_match(
+    conn: &mut PgConnection,
+    order: Order,
+    reason: &str,
+) -> Result<RevertedMatch> {
+    conn.transaction(|conn| {
+        let matches = matches::get_matches_by_order_id(conn, order.id)?;
+
+        matches::set_matches_to_failed(conn, order.id, reason)?;
+        let order = orders::set_order_state(conn, order.id, OrderState::Failed)?;
+
+        let mut restored_orders = vec![];
+        let mut makers = vec![];
+        for m in matches {
+            makers.push(m.match_trader_id);
+
+            let maker_order = orders::get_with_id(conn, m.match_order_id)?
+                .context("Could not find matched maker order")?;
+
+            matches::set_matches_to_failed(conn, maker_order.id, reason)?;
+
+            // Expired limit orders are not put back into the orderbook, the maker will have
+            // posted new orders in the meantime anyways.
+            if maker_order.expiry > OffsetDateTime::now_utc() {
+                let maker_order = orders::set_order_state(conn, maker_order.id, OrderState::Open)?;
+                events::append(
+                    conn,
+                    maker_order.contract_symbol,
+                    &OrderbookEvent::OrderAmended(maker_order.clone()),
+                )?;
+                restored_orders.push(maker_order);
+            } else {
+                orders::set_order_state(conn, maker_order.id, OrderState::Failed)?;
+            }
+        }
+
+        Ok(RevertedMatch {
+            order,
+            restored_orders,
+            makers,
+        })
+    })
+}
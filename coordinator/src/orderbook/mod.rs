@@ -1,8 +1,13 @@
 pub mod async_match;
+pub mod batch_writer;
 pub mod collaborative_revert;
 pub mod db;
+pub mod event_log;
+pub mod maker_notifications;
+pub mod match_timeout;
 pub mod routes;
 pub mod trading;
+pub mod trading_halt;
 pub mod websocket;
 
 #[cfg(test)]
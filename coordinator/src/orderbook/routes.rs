@@ -1,82 +1,160 @@
+use crate::api_key;
+use crate::db;
+use crate::openapi::ErrorResponse;
 use crate::orderbook;
+use crate::orderbook::match_timeout;
+use crate::orderbook::trading;
 use crate::orderbook::trading::NewOrderMessage;
 use crate::orderbook::trading::TradingError;
 use crate::orderbook::websocket::websocket_connection;
+use crate::orderbook::websocket::WebsocketState;
 use crate::routes::AppState;
+use crate::routes::Database;
 use crate::AppError;
 use anyhow::Context;
 use anyhow::Result;
 use axum::extract::ws::WebSocketUpgrade;
 use axum::extract::Path;
 use axum::extract::State;
+use axum::http::header::SEC_WEBSOCKET_PROTOCOL;
+use axum::http::HeaderMap;
 use axum::response::IntoResponse;
 use axum::Json;
+use commons::ApiKeyScope;
+use commons::ApiVersion;
 use commons::Message;
 use commons::NewOrder;
 use commons::Order;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
-use diesel::r2d2::ConnectionManager;
-use diesel::r2d2::PooledConnection;
-use diesel::PgConnection;
+use commons::QuoteEvent;
+use commons::QuoteEventKind;
+use commons::RollbackMatch;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Arc;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::mpsc;
 use tracing::instrument;
+use tracing::Span;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[instrument(skip_all, err(Debug))]
-fn get_db_connection(
-    state: &Arc<AppState>,
-) -> Result<PooledConnection<ConnectionManager<PgConnection>>, AppError> {
-    state
-        .pool
-        .clone()
-        .get()
-        .map_err(|e| AppError::InternalServerError(format!("Failed to get db access: {e:#}")))
-}
+const MATCH_ROLLED_BACK: &str = "Rolled back by trader";
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/orderbook/orders/{order_id}",
+    tag = "orderbook",
+    params(("order_id" = Uuid, Path, description = "The id of the order")),
+    responses(
+        (status = 200, description = "The order", body = Order),
+        (status = 400, description = "The order does not exist", body = ErrorResponse),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn get_order(
     Path(order_id): Path<Uuid>,
-    State(state): State<Arc<AppState>>,
+    State(Database(pool)): State<Database>,
 ) -> Result<Json<Order>, AppError> {
-    let mut conn = get_db_connection(&state)?;
-    let order = orderbook::db::orders::get_with_id(&mut conn, order_id)
-        .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
-        .context(format!("Order not found {order_id}"))
-        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+    let order = db::run(&pool, move |conn| {
+        Ok(orderbook::db::orders::get_with_id(conn, order_id)?)
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
+    .context(format!("Order not found {order_id}"))
+    .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
 
     Ok(Json(order))
 }
 
+/// The open limit orders of the orderbook.
+#[utoipa::path(
+    get,
+    path = "/api/v1/orderbook/orders",
+    tag = "orderbook",
+    responses((status = 200, description = "The open limit orders", body = [Order]))
+)]
 #[instrument(skip_all, err(Debug))]
-pub async fn get_orders(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Order>>, AppError> {
-    let mut conn = get_db_connection(&state)?;
-    let orders =
-        orderbook::db::orders::get_all_orders(&mut conn, OrderType::Limit, OrderState::Open, true)
-            .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?;
+pub async fn get_orders(
+    State(Database(pool)): State<Database>,
+) -> Result<Json<Vec<Order>>, AppError> {
+    let orders = db::run(&pool, |conn| {
+        Ok(orderbook::db::orders::get_all_orders(
+            conn,
+            OrderType::Limit,
+            OrderState::Open,
+            true,
+        )?)
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?;
 
     Ok(Json(orders))
 }
 
+/// Submit an order to the matching engine.
+///
+/// If the request carries an API key, it has to be bound to the trader of the order.
+#[utoipa::path(
+    post,
+    path = "/api/v1/orderbook/orders",
+    tag = "orderbook",
+    request_body = NewOrder,
+    security((), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The stored order", body = Order),
+        (status = 400, description = "The order is invalid", body = ErrorResponse),
+        (status = 401, description = "The API key is not allowed to trade for the trader"),
+        (status = 503, description = "No match was found or trading is halted", body = ErrorResponse),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn post_order(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(new_order): Json<NewOrder>,
 ) -> Result<Json<Order>, AppError> {
+    if let Some(api_key_owner) = api_key::authenticate(&state, &headers, ApiKeyScope::Trade).await?
+    {
+        if api_key_owner != new_order.trader_id {
+            return Err(AppError::Unauthorized);
+        }
+    }
+
+    let order = submit_order(&state, new_order).await?;
+
+    Ok(Json(order))
+}
+
+/// Submit `new_order` to the matching engine and wait for the order to be stored.
+pub(crate) async fn submit_order(
+    state: &Arc<AppState>,
+    new_order: NewOrder,
+) -> Result<Order, AppError> {
+    if state.shutdown.is_triggered() {
+        return Err(AppError::ServiceUnavailable(
+            "Coordinator is shutting down. Please try again later".to_string(),
+        ));
+    }
+
+    if new_order.order_type == OrderType::Market {
+        state.node.dlc_sessions.remember_order(new_order.id);
+    }
+
     let (sender, mut receiver) = mpsc::channel::<Result<Order>>(1);
 
     let message = NewOrderMessage {
         new_order,
         order_reason: OrderReason::Manual,
         sender,
+        span: Span::current(),
     };
-    state.trading_sender.send(message).await.map_err(|e| {
-        AppError::InternalServerError(format!("Failed to send new order message: {e:#}"))
+    trading::try_submit(&state.trading_sender, message).map_err(|e| match e.downcast_ref() {
+        Some(TradingError::ServiceOverloaded) => AppError::ServiceUnavailable(e.to_string()),
+        _ => AppError::InternalServerError(format!("Failed to send new order message: {e:#}")),
     })?;
 
     let result = receiver
@@ -85,13 +163,90 @@ pub async fn post_order(
         .context("Failed to receive response from trading sender")
         .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?;
 
-    let order = result.map_err(|e| match e.downcast_ref() {
+    result.map_err(|e| match e.downcast_ref() {
         Some(TradingError::InvalidOrder(reason)) => AppError::InvalidOrder(reason.to_string()),
+        Some(TradingError::Validation(e)) => AppError::InvalidOrder(e.to_string()),
         Some(TradingError::NoMatchFound(message)) => AppError::NoMatchFound(message.to_string()),
+        Some(TradingError::TradingHalted(reason)) => AppError::TradingHalted(reason.to_string()),
+        Some(TradingError::OrderbookUnavailable) => AppError::ServiceUnavailable(e.to_string()),
         _ => AppError::InternalServerError(format!("Failed to post order. Error: {e:#}")),
-    })?;
+    })
+}
 
-    Ok(Json(order))
+/// Roll back a match that the trader could not execute, e.g. because the DLC protocol got stuck.
+///
+/// Only matches which are still waiting for execution can be rolled back. The matched maker orders
+/// are put back into the orderbook, unless they have expired in the meantime.
+#[utoipa::path(
+    post,
+    path = "/api/v1/orderbook/orders/{order_id}/rollback",
+    tag = "orderbook",
+    params(("order_id" = Uuid, Path, description = "The id of the matched order")),
+    request_body = RollbackMatch,
+    responses(
+        (status = 200, description = "The match has been rolled back"),
+        (status = 400, description = "The order can't be rolled back", body = ErrorResponse),
+        (status = 401, description = "The signature is not the trader's"),
+    )
+)]
+#[instrument(skip_all, err(Debug))]
+pub async fn post_rollback_match(
+    Path(order_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Json(rollback): Json<RollbackMatch>,
+) -> Result<(), AppError> {
+    if rollback.order_id != order_id {
+        return Err(AppError::BadRequest(
+            "Order id does not match rollback request".to_string(),
+        ));
+    }
+
+    rollback.verify().map_err(|_| AppError::Unauthorized)?;
+
+    let order = db::run(&state.pool, move |conn| {
+        Ok(orderbook::db::orders::get_with_id(conn, order_id)?)
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
+    .ok_or_else(|| AppError::BadRequest(format!("Order not found {order_id}")))?;
+
+    if order.trader_id != rollback.signature.pubkey {
+        return Err(AppError::Unauthorized);
+    }
+
+    if order.order_state != OrderState::Matched {
+        return Err(AppError::BadRequest(format!(
+            "Can't roll back order {order_id} in state {:?}",
+            order.order_state
+        )));
+    }
+
+    tracing::info!(trader_id = %order.trader_id, %order_id, "Rolling back match");
+
+    let reverted_match = db::run(&state.pool, move |conn| {
+        match_timeout::revert_match(conn, order, MATCH_ROLLED_BACK)
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to roll back match: {e:#}")))?;
+
+    // The maker orders are back in the orderbook, hence they have to be matchable again.
+    if reverted_match.has_restored_orders() {
+        trading::reload_orderbook(&state.trading_sender)
+            .await
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to reload orderbook: {e:#}"))
+            })?;
+    }
+
+    match_timeout::publish_reverted_match(
+        reverted_match,
+        MATCH_ROLLED_BACK,
+        &state.tx_price_feed,
+        &state.auth_users_notifier,
+    )
+    .await;
+
+    Ok(())
 }
 
 fn update_pricefeed(pricefeed_msg: Message, sender: Sender<Message>) {
@@ -105,29 +260,146 @@ fn update_pricefeed(pricefeed_msg: Message, sender: Sender<Message>) {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct UpdateOrder {
     pub taken: bool,
 }
 
+/// Take a limit order off the orderbook, or put it back.
+#[utoipa::path(
+    put,
+    path = "/api/v1/orderbook/orders/{order_id}",
+    tag = "orderbook",
+    params(("order_id" = Uuid, Path, description = "The id of the order")),
+    request_body = UpdateOrder,
+    responses(
+        (status = 200, description = "The updated order", body = Order),
+        (status = 500, description = "The order could not be updated", body = ErrorResponse),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn put_order(
     Path(order_id): Path<Uuid>,
     State(state): State<Arc<AppState>>,
     Json(updated_order): Json<UpdateOrder>,
 ) -> Result<Json<Order>, AppError> {
-    let mut conn = get_db_connection(&state)?;
-    let order = orderbook::db::orders::set_is_taken(&mut conn, order_id, updated_order.taken)
-        .map_err(|e| AppError::InternalServerError(format!("Failed to update order: {e:#}")))?;
+    let order = update_order(&state, order_id, updated_order.taken).await?;
+
+    Ok(Json(order))
+}
+
+/// Mark an order as taken or not, which removes a limit order from the orderbook or re-adds it.
+///
+/// Limit orders are updated by the matching engine, so that a taken order is out of the orderbook
+/// before we respond.
+pub(crate) async fn update_order(
+    state: &Arc<AppState>,
+    order_id: Uuid,
+    taken: bool,
+) -> Result<Order, AppError> {
+    let order = db::run(&state.pool, move |conn| {
+        let order = orderbook::db::orders::get_with_id(conn, order_id)?;
+        anyhow::Ok(order)
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load order: {e:#}")))?
+    .ok_or_else(|| AppError::BadRequest(format!("Unknown order {order_id}")))?;
+
+    let order = match order.order_type {
+        OrderType::Limit => trading::update_order(&state.trading_sender, order, taken).await,
+        OrderType::Market => {
+            db::run(&state.pool, move |conn| {
+                let order = orderbook::db::orders::set_is_taken(conn, order_id, taken)?;
+                anyhow::Ok(order)
+            })
+            .await
+        }
+    }
+    .map_err(|e| match e.downcast_ref() {
+        Some(TradingError::OrderbookUnavailable) => AppError::ServiceUnavailable(e.to_string()),
+        _ => AppError::InternalServerError(format!("Failed to update order: {e:#}")),
+    })?;
     let sender = state.tx_price_feed.clone();
     update_pricefeed(Message::Update(order.clone()), sender);
 
-    Ok(Json(order))
+    if taken && order.order_type == OrderType::Limit {
+        state
+            .maker_notifier
+            .notify(
+                order.trader_id,
+                QuoteEvent::new(
+                    order.id,
+                    QuoteEventKind::Cancelled,
+                    Decimal::ZERO,
+                    order.quantity,
+                    None,
+                ),
+            )
+            .await;
+    }
+
+    Ok(order)
 }
 
+/// Upgrade to the orderbook websocket.
+///
+/// Clients can ask for a version of the messages by offering the websocket subprotocols of the
+/// versions they support, e.g. `Sec-WebSocket-Protocol: 10101.v1`. Clients which do not offer any
+/// subprotocol get the messages of the first version.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
-    State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(|socket| websocket_connection(socket, state))
+    State(state): State<WebsocketState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let offered_protocols = headers
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    let version = match negotiate_websocket_version(&offered_protocols) {
+        Some(version) => version,
+        None => {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported websocket protocols {offered_protocols:?}, supported are {:?}",
+                ApiVersion::ALL.map(|version| version.websocket_protocol())
+            )))
+        }
+    };
+
+    tracing::debug!(%version, "Upgrading to orderbook websocket");
+
+    let ws = ws.protocols(ApiVersion::ALL.map(|version| version.websocket_protocol()));
+
+    Ok(ws.on_upgrade(|socket| websocket_connection(socket, state)))
+}
+
+/// The latest version of the websocket messages supported by both sides, or `None` if we do not
+/// support any of the offered `protocols`.
+fn negotiate_websocket_version(protocols: &[&str]) -> Option<ApiVersion> {
+    if protocols.is_empty() {
+        return Some(ApiVersion::V1);
+    }
+
+    protocols
+        .iter()
+        .filter_map(|protocol| ApiVersion::from_websocket_protocol(protocol))
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn websocket_version_is_negotiated() {
+        assert_eq!(negotiate_websocket_version(&[]), Some(ApiVersion::V1));
+        assert_eq!(
+            negotiate_websocket_version(&["chat", "10101.v1"]),
+            Some(ApiVersion::V1)
+        );
+        assert_eq!(negotiate_websocket_version(&["10101.v0"]), None);
+    }
 }
@@ -0,0 +1,90 @@
+use crate::logger::init_tracing_for_test;
+use crate::orderbook::tests::setup_db;
+use crate::orderbook::tests::start_postgres;
+use diesel::r2d2;
+use diesel::r2d2::ConnectionManager;
+use diesel::Connection;
+use diesel::PgConnection;
+use ln_dlc_storage::postgres;
+use ln_dlc_storage::postgres::PostgresDlcStoreProvider;
+use ln_dlc_storage::DlcStoreProvider;
+use ln_dlc_storage::KeyValue;
+use testcontainers::clients::Cli;
+
+#[tokio::test]
+async fn dlc_store_reads_writes_and_deletes() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    // Runs the migrations.
+    let _conn = setup_db(conn_spec.clone());
+
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec))
+        .unwrap();
+    let store = PostgresDlcStoreProvider::new(pool);
+
+    assert!(store.is_empty().unwrap());
+
+    store.write(1, b"key".to_vec(), b"value".to_vec()).unwrap();
+    store
+        .write(1, b"key2".to_vec(), b"value2".to_vec())
+        .unwrap();
+    store.write(2, b"key".to_vec(), b"other".to_vec()).unwrap();
+
+    // Writing an existing key overwrites its value.
+    store
+        .write(1, b"key".to_vec(), b"updated".to_vec())
+        .unwrap();
+
+    let result = store.read(1, Some(b"key".to_vec())).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].value, b"updated".to_vec());
+
+    assert_eq!(store.read(1, None).unwrap().len(), 2);
+
+    store.delete(1, Some(b"key".to_vec())).unwrap();
+    assert_eq!(store.read(1, None).unwrap().len(), 1);
+
+    store.delete(1, None).unwrap();
+    assert!(store.read(1, None).unwrap().is_empty());
+    assert_eq!(store.read(2, None).unwrap().len(), 1);
+
+    store
+        .import(vec![(
+            3,
+            KeyValue {
+                key: b"imported".to_vec(),
+                value: b"value".to_vec(),
+            },
+        )])
+        .unwrap();
+    assert_eq!(store.read(3, None).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn dlc_store_writes_join_the_transaction_of_the_connection() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+    let mut conn = setup_db(conn_spec);
+
+    let result: anyhow::Result<()> = conn.transaction(|conn| {
+        postgres::write(conn, 1, b"key".to_vec(), b"value".to_vec())?;
+        assert_eq!(postgres::read(conn, 1, None)?.len(), 1);
+
+        // E.g. updating the position failed.
+        anyhow::bail!("Failed to update position")
+    });
+    assert!(result.is_err());
+
+    assert!(postgres::read(&mut conn, 1, None).unwrap().is_empty());
+
+    conn.transaction(|conn| postgres::write(conn, 1, b"key".to_vec(), b"value".to_vec()))
+        .unwrap();
+
+    assert_eq!(postgres::read(&mut conn, 1, None).unwrap().len(), 1);
+}
@@ -0,0 +1,79 @@
+use crate::leader::LeaderElection;
+use crate::leader::CHECK_INTERVAL;
+use crate::leader::TAKEOVER_DELAY;
+use crate::logger::init_tracing_for_test;
+use crate::orderbook::tests::start_postgres;
+use crate::shutdown::Shutdown;
+use diesel::Connection;
+use diesel::PgConnection;
+use diesel::RunQueryDsl;
+use std::time::Duration;
+use std::time::Instant;
+use testcontainers::clients::Cli;
+
+#[tokio::test]
+async fn standby_becomes_leader_once_leader_is_gone() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let leader = LeaderElection::connect(&conn_spec).unwrap();
+    let standby = LeaderElection::connect(&conn_spec).unwrap();
+
+    assert!(leader.try_acquire().await.unwrap());
+    assert!(!standby.try_acquire().await.unwrap());
+
+    drop(leader);
+
+    // Postgres releases the lock once it noticed that the connection of the leader is closed.
+    let became_leader = tokio::time::timeout(
+        TAKEOVER_DELAY + Duration::from_secs(30),
+        standby.wait(&Shutdown::new()),
+    )
+    .await
+    .unwrap();
+    assert!(became_leader);
+}
+
+#[tokio::test]
+async fn leader_shuts_down_before_standby_takes_over_on_connection_loss() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let leader = LeaderElection::connect(&conn_spec).unwrap();
+    let standby = LeaderElection::connect(&conn_spec).unwrap();
+
+    assert!(leader.try_acquire().await.unwrap());
+
+    let shutdown = Shutdown::new();
+    let monitor = leader.monitor(shutdown.clone());
+
+    // The leader is still running, but its connection to the database is gone.
+    let lost_at = Instant::now();
+    let mut conn = PgConnection::establish(&conn_spec).unwrap();
+    diesel::sql_query(
+        "SELECT pg_terminate_backend(pid) FROM pg_locks \
+         WHERE locktype = 'advisory' AND objid = 10101 AND granted",
+    )
+    .execute(&mut conn)
+    .unwrap();
+
+    tokio::time::timeout(CHECK_INTERVAL * 2, shutdown.triggered())
+        .await
+        .unwrap();
+
+    // Otherwise the monitor aborts the test once the shutdown timed out.
+    monitor.abort();
+
+    let became_leader = tokio::time::timeout(
+        TAKEOVER_DELAY + Duration::from_secs(30),
+        standby.wait(&Shutdown::new()),
+    )
+    .await
+    .unwrap();
+    assert!(became_leader);
+    assert!(lost_at.elapsed() >= TAKEOVER_DELAY);
+}
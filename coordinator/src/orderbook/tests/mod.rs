@@ -1,5 +1,10 @@
+mod dlc_store_test;
+mod leader_election_test;
+mod pubsub_test;
 mod registration_test;
 mod sample_test;
+mod trading_test;
+mod user_presence_test;
 
 use crate::run_migration;
 use anyhow::Result;
@@ -0,0 +1,142 @@
+use crate::logger::init_tracing_for_test;
+use crate::message::NewUserMessage;
+use crate::orderbook::tests::setup_db;
+use crate::orderbook::tests::start_postgres;
+use crate::orderbook::trading_halt::TradingHalt;
+use crate::pubsub;
+use crate::shutdown::Shutdown;
+use bitcoin::secp256k1::PublicKey;
+use commons::Message;
+use diesel::r2d2;
+use diesel::r2d2::ConnectionManager;
+use diesel::PgConnection;
+use std::str::FromStr;
+use std::time::Duration;
+use testcontainers::clients::Cli;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[tokio::test]
+async fn trader_on_standby_is_served_by_leader() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+    let _conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec.clone()))
+        .unwrap();
+
+    let (leader_price_feed, _rx) = broadcast::channel(100);
+    let (leader_user_feed, mut leader_logins) = broadcast::channel(100);
+    let _publish_handle = pubsub::publish_price_feed(pool.clone(), &leader_price_feed);
+    let _sessions_handle = pubsub::serve_remote_sessions(
+        conn_spec.clone(),
+        pool.clone(),
+        leader_user_feed,
+        TradingHalt::new(leader_price_feed.clone()),
+    );
+
+    let (standby_price_feed, mut price_feed) = broadcast::channel(100);
+    let (standby_user_feed, _rx) = broadcast::channel(100);
+    let _relay_handle = pubsub::relay_from_leader(
+        conn_spec,
+        pool,
+        standby_price_feed,
+        &standby_user_feed,
+        Shutdown::new(),
+    );
+
+    // Give the listeners time to connect, as notifications are not persisted.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    leader_price_feed
+        .send(Message::TradingHalted("halted".to_string()))
+        .unwrap();
+    let message = tokio::time::timeout(TIMEOUT, price_feed.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(message, Message::TradingHalted(reason) if reason == "halted"));
+
+    let trader_id =
+        PublicKey::from_str("02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a")
+            .unwrap();
+    let (sender, mut receiver) = mpsc::channel(100);
+    standby_user_feed
+        .send(NewUserMessage {
+            new_user: trader_id,
+            sender,
+            supersedes_session: false,
+        })
+        .unwrap();
+
+    let login = tokio::time::timeout(TIMEOUT, leader_logins.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(login.new_user, trader_id);
+
+    login.sender.send(Message::SessionSuperseded).await.unwrap();
+    let message = tokio::time::timeout(TIMEOUT, receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(message, Message::SessionSuperseded));
+}
+
+#[tokio::test]
+async fn trader_on_standby_is_announced_to_new_leader() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+    let _conn = setup_db(conn_spec.clone());
+    let pool = r2d2::Pool::builder()
+        .build(ConnectionManager::<PgConnection>::new(conn_spec.clone()))
+        .unwrap();
+
+    let (standby_price_feed, _rx) = broadcast::channel(100);
+    let (standby_user_feed, _rx) = broadcast::channel(100);
+    let _relay_handle = pubsub::relay_from_leader(
+        conn_spec.clone(),
+        pool.clone(),
+        standby_price_feed,
+        &standby_user_feed,
+        Shutdown::new(),
+    );
+
+    // Give the listener time to connect, as notifications are not persisted.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // The trader logs in while there is no leader listening, e.g. while the leader changes.
+    let trader_id =
+        PublicKey::from_str("02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a")
+            .unwrap();
+    let (sender, _receiver) = mpsc::channel(100);
+    standby_user_feed
+        .send(NewUserMessage {
+            new_user: trader_id,
+            sender,
+            supersedes_session: false,
+        })
+        .unwrap();
+
+    let (leader_price_feed, _rx) = broadcast::channel(100);
+    let (leader_user_feed, mut leader_logins) = broadcast::channel(100);
+    let _sessions_handle = pubsub::serve_remote_sessions(
+        conn_spec,
+        pool,
+        leader_user_feed,
+        TradingHalt::new(leader_price_feed),
+    );
+
+    let login = tokio::time::timeout(TIMEOUT, leader_logins.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(login.new_user, trader_id);
+    assert!(!login.supersedes_session);
+}
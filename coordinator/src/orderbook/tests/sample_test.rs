@@ -7,6 +7,10 @@ use commons::NewOrder;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
+use diesel::connection::AnsiTransactionManager;
+use diesel::connection::TransactionManager;
+use diesel::Connection;
+use diesel::PgConnection;
 use rust_decimal_macros::dec;
 use std::str::FromStr;
 use testcontainers::clients::Cli;
@@ -38,6 +42,38 @@ async fn crud_test() {
     assert_eq!(order.order_state, OrderState::Taken);
 }
 
+#[tokio::test]
+async fn locked_order_cannot_be_locked_again() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let mut other_conn = PgConnection::establish(&conn_spec).unwrap();
+
+    let order = orders::insert(
+        &mut conn,
+        dummy_order(
+            OffsetDateTime::now_utc() + Duration::minutes(1),
+            OrderType::Market,
+        ),
+        OrderReason::Manual,
+    )
+    .unwrap();
+
+    AnsiTransactionManager::begin_transaction(&mut *conn).unwrap();
+    let locked = orders::get_with_id_for_update(&mut conn, order.id).unwrap();
+    assert_eq!(locked.map(|order| order.id), Some(order.id));
+
+    assert!(orders::get_with_id_for_update(&mut other_conn, order.id).is_err());
+
+    AnsiTransactionManager::rollback_transaction(&mut *conn).unwrap();
+    assert!(orders::get_with_id_for_update(&mut other_conn, order.id)
+        .unwrap()
+        .is_some());
+}
+
 #[tokio::test]
 async fn test_all_limit_orders() {
     init_tracing_for_test();
@@ -100,5 +136,6 @@ fn dummy_order(expiry: OffsetDateTime, order_type: OrderType) -> NewOrder {
         contract_symbol: trade::ContractSymbol::BtcUsd,
         leverage: 1.0,
         stable: false,
+        reduce_only: false,
     }
 }
@@ -0,0 +1,187 @@
+use crate::config::RiskLimits;
+use crate::logger::init_tracing_for_test;
+use crate::message::OrderbookMessage;
+use crate::orderbook::db::matches;
+use crate::orderbook::maker_notifications::MakerNotifier;
+use crate::orderbook::tests::setup_db;
+use crate::orderbook::tests::start_postgres;
+use crate::orderbook::trading;
+use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingError;
+use crate::orderbook::trading::TradingMessage;
+use crate::orderbook::trading_halt::TradingHalt;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Network;
+use bitcoin::XOnlyPublicKey;
+use commons::Message;
+use commons::NewOrder;
+use commons::Order;
+use commons::OrderReason;
+use commons::OrderType;
+use commons::SystemClock;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use diesel::RunQueryDsl;
+use futures::future::RemoteHandle;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+use std::sync::Arc;
+use testcontainers::clients::Cli;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tracing::Span;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+const MAKER: &str = "03507b924dae6595cfb78492489978127c5f1e3877848564de2015cd6d41375802";
+const TAKER: &str = "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007";
+const OTHER_TAKER: &str = "02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a";
+const ORACLE: &str = "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn limit_order_is_filled_by_one_of_two_concurrent_market_orders() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let engine = Engine::start(conn_spec);
+
+    let limit_order = engine
+        .place(new_order(MAKER, Direction::Short, OrderType::Limit))
+        .await
+        .unwrap();
+
+    let (first, second) = tokio::join!(
+        engine.place(new_order(TAKER, Direction::Long, OrderType::Market)),
+        engine.place(new_order(OTHER_TAKER, Direction::Long, OrderType::Market)),
+    );
+    let results = [first, second];
+
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+
+    let rejected = results.into_iter().find_map(|result| result.err()).unwrap();
+    assert!(matches!(
+        rejected.downcast_ref::<TradingError>(),
+        Some(TradingError::NoMatchFound(_))
+    ));
+
+    let fills = matches::get_matches_by_order_id(&mut conn, limit_order.id).unwrap();
+    assert_eq!(fills.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn orders_are_rejected_until_orderbook_is_loaded() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec.clone());
+    let engine = Engine::start(conn_spec);
+
+    // Without its snapshots, the orderbook can't be recovered from the event log.
+    diesel::sql_query("ALTER TABLE orderbook_snapshots RENAME TO orderbook_snapshots_unavailable")
+        .execute(&mut conn)
+        .unwrap();
+
+    let error = engine
+        .place(new_order(MAKER, Direction::Short, OrderType::Limit))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<TradingError>(),
+        Some(&TradingError::OrderbookUnavailable)
+    );
+
+    diesel::sql_query("ALTER TABLE orderbook_snapshots_unavailable RENAME TO orderbook_snapshots")
+        .execute(&mut conn)
+        .unwrap();
+
+    engine
+        .place(new_order(MAKER, Direction::Short, OrderType::Limit))
+        .await
+        .unwrap();
+}
+
+/// The matching engine together with the receivers it needs to publish orders and matches.
+struct Engine {
+    _handle: RemoteHandle<()>,
+    trading_sender: mpsc::Sender<TradingMessage>,
+    _price_feed: broadcast::Receiver<Message>,
+    _notifications: mpsc::Receiver<OrderbookMessage>,
+}
+
+impl Engine {
+    fn start(conn_spec: String) -> Self {
+        let pool = Pool::builder()
+            .build(ConnectionManager::<PgConnection>::new(conn_spec))
+            .unwrap();
+
+        let (tx_price_feed, price_feed) = broadcast::channel(100);
+        let (notifier, notifications) = mpsc::channel(100);
+
+        let (handle, trading_sender) = trading::start(
+            pool,
+            tx_price_feed.clone(),
+            notifier.clone(),
+            MakerNotifier::new(notifier, vec![]),
+            Network::Regtest,
+            XOnlyPublicKey::from_str(ORACLE).unwrap(),
+            TradingHalt::new(tx_price_feed),
+            RiskLimits::default(),
+            Arc::new(SystemClock),
+        );
+
+        Self {
+            _handle: handle,
+            trading_sender,
+            _price_feed: price_feed,
+            _notifications: notifications,
+        }
+    }
+
+    /// Submit `new_order` and wait for the response of the matching engine.
+    async fn place(&self, new_order: NewOrder) -> Result<Order> {
+        let (sender, mut receiver) = mpsc::channel(1);
+
+        trading::submit(
+            &self.trading_sender,
+            NewOrderMessage {
+                new_order,
+                order_reason: OrderReason::Manual,
+                sender,
+                span: Span::none(),
+            },
+        )
+        .await?;
+
+        receiver
+            .recv()
+            .await
+            .context("Matching engine did not respond")?
+    }
+}
+
+fn new_order(trader_id: &str, direction: Direction, order_type: OrderType) -> NewOrder {
+    NewOrder {
+        id: Uuid::new_v4(),
+        contract_symbol: ContractSymbol::BtcUsd,
+        price: dec!(30_000),
+        quantity: dec!(100),
+        trader_id: PublicKey::from_str(trader_id).unwrap(),
+        direction,
+        leverage: 2.0,
+        order_type,
+        expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
+        stable: false,
+        reduce_only: false,
+    }
+}
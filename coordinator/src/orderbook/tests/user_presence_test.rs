@@ -0,0 +1,82 @@
+use crate::db::user_presence;
+use crate::logger::init_tracing_for_test;
+use crate::orderbook::tests::setup_db;
+use crate::orderbook::tests::start_postgres;
+use bitcoin::secp256k1::PublicKey;
+use std::str::FromStr;
+use testcontainers::clients::Cli;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn presence_follows_latest_session() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let trader_pubkey = dummy_public_key();
+    assert!(user_presence::get(&mut conn, trader_pubkey)
+        .unwrap()
+        .is_none());
+
+    let first_session = Uuid::new_v4();
+    user_presence::connected(&mut conn, trader_pubkey, first_session).unwrap();
+
+    let online = user_presence::get_online(&mut conn, OffsetDateTime::now_utc()).unwrap();
+    assert_eq!(online.len(), 1);
+    assert_eq!(online[0].trader_pubkey, trader_pubkey);
+
+    // The trader connected again on another device before the first session was closed.
+    let second_session = Uuid::new_v4();
+    user_presence::connected(&mut conn, trader_pubkey, second_session).unwrap();
+    user_presence::disconnected(&mut conn, trader_pubkey, first_session).unwrap();
+
+    let presence = user_presence::get(&mut conn, trader_pubkey)
+        .unwrap()
+        .unwrap();
+    assert!(presence.is_online(OffsetDateTime::now_utc()));
+
+    user_presence::disconnected(&mut conn, trader_pubkey, second_session).unwrap();
+
+    let presence = user_presence::get(&mut conn, trader_pubkey)
+        .unwrap()
+        .unwrap();
+    assert!(!presence.is_online(OffsetDateTime::now_utc()));
+    assert!(
+        user_presence::get_online(&mut conn, OffsetDateTime::now_utc())
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn trader_not_seen_for_a_while_is_offline() {
+    init_tracing_for_test();
+
+    let docker = Cli::default();
+    let (_container, conn_spec) = start_postgres(&docker).unwrap();
+
+    let mut conn = setup_db(conn_spec);
+
+    let trader_pubkey = dummy_public_key();
+    user_presence::connected(&mut conn, trader_pubkey, Uuid::new_v4()).unwrap();
+
+    // E.g. the coordinator serving the trader crashed without recording the disconnect.
+    let later = OffsetDateTime::now_utc() + time::Duration::minutes(10);
+
+    let presence = user_presence::get(&mut conn, trader_pubkey)
+        .unwrap()
+        .unwrap();
+    assert!(!presence.is_online(later));
+    assert!(user_presence::get_online(&mut conn, later)
+        .unwrap()
+        .is_empty());
+}
+
+fn dummy_public_key() -> PublicKey {
+    PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+        .unwrap()
+}
@@ -1,7 +1,19 @@
+use crate::config::RiskLimits;
+use crate::db;
 use crate::message::OrderbookMessage;
+use crate::metrics::NEW_ORDERS_IN_PROGRESS;
+use crate::metrics::NEW_ORDERS_OVERLOADED;
+use crate::metrics::NEW_ORDERS_QUEUED;
 use crate::notifications::NotificationKind;
-use crate::orderbook::db::matches;
+use crate::orderbook::batch_writer::BatchWriter;
+use crate::orderbook::batch_writer::Write;
 use crate::orderbook::db::orders;
+use crate::orderbook::event_log;
+use crate::orderbook::event_log::OrderbookEvent;
+use crate::orderbook::event_log::SNAPSHOT_INTERVAL;
+use crate::orderbook::maker_notifications::MakerNotifier;
+use crate::orderbook::trading_halt::TradingHalt;
+use crate::position::models::PositionState;
 use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Context;
@@ -9,6 +21,7 @@ use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::Network;
 use bitcoin::XOnlyPublicKey;
+use commons::Clock;
 use commons::FilledWith;
 use commons::Match;
 use commons::Message;
@@ -17,6 +30,8 @@ use commons::Order;
 use commons::OrderReason;
 use commons::OrderState;
 use commons::OrderType;
+use commons::QuoteEvent;
+use commons::QuoteEventKind;
 use commons::TradeParams;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
@@ -25,30 +40,76 @@ use futures::future::RemoteHandle;
 use futures::FutureExt;
 use rust_decimal::Decimal;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::atomic;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use thiserror::Error;
 use time::OffsetDateTime;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::oneshot;
 use tokio::task::spawn_blocking;
+use tracing::Instrument;
+use tracing::Span;
+use trade::validation;
+use trade::validation::OrderValidationError;
+use trade::ContractSymbol;
 use trade::Direction;
 use uuid::Uuid;
 
-/// This value is arbitrarily set to 100 and defines the number of new order messages buffered in
-/// the channel.
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod invariants;
+
+/// This value is arbitrarily set to 100 and defines the number of messages buffered in the
+/// channels of the matching engine.
 const NEW_ORDERS_BUFFER_SIZE: usize = 100;
 
+pub enum TradingMessage {
+    NewOrder(NewOrderMessage),
+    /// A maker took their limit order out of the orderbook or put it back in.
+    UpdateOrder(UpdateOrderMessage),
+    /// Limit orders have been changed outside of the matching engine, e.g. because a match has
+    /// been reverted.
+    ReloadOrderbook,
+    /// Resolves once all previously submitted orders have been processed and their writes have
+    /// been committed.
+    Flush(oneshot::Sender<()>),
+}
+
 pub struct NewOrderMessage {
     pub new_order: NewOrder,
     pub order_reason: OrderReason,
     pub sender: mpsc::Sender<Result<Order>>,
+    /// The span the order was submitted in. The order is processed in a child span of it, so that
+    /// it is logged under the same trace ID.
+    pub span: Span,
+}
+
+pub struct UpdateOrderMessage {
+    /// The limit order as currently stored.
+    pub order: Order,
+    /// Whether the order is taken out of the orderbook, or put back in.
+    pub taken: bool,
+    /// Receives the updated order once it has been committed.
+    pub sender: oneshot::Sender<Result<Order>>,
 }
 
 #[derive(Error, Debug, PartialEq)]
 pub enum TradingError {
     #[error("Invalid order: {0}")]
     InvalidOrder(String),
+    #[error("Invalid order: {0}")]
+    Validation(#[from] OrderValidationError),
     #[error("{0}")]
     NoMatchFound(String),
+    #[error("{0}")]
+    TradingHalted(String),
+    #[error("Too many orders are being processed. Please try again later")]
+    ServiceOverloaded,
+    #[error("The orderbook is currently unavailable. Please try again later")]
+    OrderbookUnavailable,
 }
 
 #[derive(Clone)]
@@ -63,42 +124,104 @@ pub struct TraderMatchParams {
     pub filled_with: FilledWith,
 }
 
-/// Spawn a task that processes [`NewOrderMessage`]s.
+/// Spawn the matching engine, which processes [`TradingMessage`]s.
+///
+/// The engine runs one [`Shard`] per contract symbol. Each shard keeps the open limit orders of
+/// its contract symbol in memory and processes new orders one after the other, so that a limit
+/// order can never be matched with two market orders. The resulting database writes are batched
+/// by a [`BatchWriter`] in the background. Traders are only notified and answered once the writes
+/// of their order have been committed.
+///
+/// Every change to the orderbook is appended to the event log as an [`OrderbookEvent`], from which
+/// the shards recover their orderbook when they are started.
 ///
-/// To feed messages to this task, the caller can use the corresponding
-/// [`mpsc::Sender<NewOrderMessage>`] returned.
+/// To feed messages to the engine, the caller can use the corresponding
+/// [`mpsc::Sender<TradingMessage>`] returned, through [`try_submit`], [`submit`],
+/// [`update_order`], [`reload_orderbook`] or [`flush`].
 pub fn start(
     pool: Pool<ConnectionManager<PgConnection>>,
     tx_price_feed: broadcast::Sender<Message>,
     notifier: mpsc::Sender<OrderbookMessage>,
+    maker_notifier: MakerNotifier,
     network: Network,
     oracle_pk: XOnlyPublicKey,
-) -> (RemoteHandle<()>, mpsc::Sender<NewOrderMessage>) {
-    let (sender, mut receiver) = mpsc::channel::<NewOrderMessage>(NEW_ORDERS_BUFFER_SIZE);
+    trading_halt: TradingHalt,
+    risk_limits: RiskLimits,
+    clock: Arc<dyn Clock>,
+) -> (RemoteHandle<()>, mpsc::Sender<TradingMessage>) {
+    let (sender, mut receiver) = mpsc::channel::<TradingMessage>(NEW_ORDERS_BUFFER_SIZE);
+
+    let engine = Engine {
+        writer: BatchWriter::spawn(pool.clone()),
+        pool,
+        tx_price_feed,
+        notifier,
+        maker_notifier,
+        network,
+        oracle_pk,
+        trading_halt,
+        risk_limits,
+        clock,
+    };
 
     let (fut, remote_handle) = async move {
-        while let Some(new_order_msg) = receiver.recv().await {
-            tokio::spawn({
-                let tx_price_feed = tx_price_feed.clone();
-                let notifier = notifier.clone();
-                let pool = pool.clone();
-                async move {
-                    let result = process_new_order(
-                        pool,
-                        notifier,
-                        tx_price_feed,
-                        new_order_msg.new_order,
-                        new_order_msg.order_reason,
-                        network,
-                        oracle_pk,
-                    )
-                    .await;
-
-                    if let Err(e) = new_order_msg.sender.send(result).await {
-                        tracing::error!("Failed to respond to NewOrderMessage: {e:#}");
+        let mut shards = HashMap::<ContractSymbol, mpsc::Sender<ShardMessage>>::new();
+
+        while let Some(message) = receiver.recv().await {
+            match message {
+                TradingMessage::NewOrder(new_order_msg) => {
+                    let contract_symbol = new_order_msg.new_order.contract_symbol;
+                    let shard = shards
+                        .entry(contract_symbol)
+                        .or_insert_with(|| Shard::spawn(contract_symbol, engine.clone()));
+
+                    // Waiting for the shard applies backpressure on the senders once the shard
+                    // cannot keep up.
+                    if shard
+                        .send(ShardMessage::NewOrder(new_order_msg))
+                        .await
+                        .is_err()
+                    {
+                        tracing::error!(%contract_symbol, "Shard has stopped");
                     }
                 }
-            });
+                TradingMessage::UpdateOrder(update_order_msg) => {
+                    let contract_symbol = update_order_msg.order.contract_symbol;
+                    let shard = shards
+                        .entry(contract_symbol)
+                        .or_insert_with(|| Shard::spawn(contract_symbol, engine.clone()));
+
+                    if shard
+                        .send(ShardMessage::UpdateOrder(update_order_msg))
+                        .await
+                        .is_err()
+                    {
+                        tracing::error!(%contract_symbol, "Shard has stopped");
+                    }
+                }
+                TradingMessage::ReloadOrderbook => {
+                    for (contract_symbol, shard) in shards.iter() {
+                        if shard.send(ShardMessage::ReloadOrderbook).await.is_err() {
+                            tracing::error!(%contract_symbol, "Shard has stopped");
+                        }
+                    }
+                }
+                TradingMessage::Flush(flushed) => {
+                    for (contract_symbol, shard) in shards.iter() {
+                        let (shard_flushed, receiver) = oneshot::channel();
+                        if shard
+                            .send(ShardMessage::Flush(shard_flushed))
+                            .await
+                            .is_err()
+                            || receiver.await.is_err()
+                        {
+                            tracing::error!(%contract_symbol, "Shard has stopped");
+                        }
+                    }
+
+                    let _ = flushed.send(());
+                }
+            }
         }
 
         tracing::error!("Channel closed");
@@ -110,111 +233,649 @@ pub fn start(
     (remote_handle, sender)
 }
 
-/// Process a [`NewOrder`].
+/// Submit a [`NewOrderMessage`] to the matching engine without waiting for space in its buffer.
 ///
-/// If the [`NewOrder`] is of [`OrderType::Limit`]: update the price feed.
+/// Fails with [`TradingError::ServiceOverloaded`] if the buffer is full, so that callers which
+/// have a client waiting for the response can reject the order right away.
+pub fn try_submit(sender: &mpsc::Sender<TradingMessage>, message: NewOrderMessage) -> Result<()> {
+    let cx = opentelemetry::Context::current();
+    let trader_id = message.new_order.trader_id;
+    let order_id = message.new_order.id;
+
+    match sender.try_send(TradingMessage::NewOrder(message)) {
+        Ok(()) => {
+            NEW_ORDERS_QUEUED.add(&cx, 1, &[]);
+            Ok(())
+        }
+        Err(TrySendError::Full(_)) => {
+            tracing::warn!(
+                %trader_id,
+                %order_id,
+                "Rejecting new order because the orderbook is overloaded"
+            );
+            NEW_ORDERS_OVERLOADED.add(&cx, 1, &[]);
+
+            bail!(TradingError::ServiceOverloaded)
+        }
+        Err(TrySendError::Closed(_)) => bail!("Trading task has stopped"),
+    }
+}
+
+/// Submit a [`NewOrderMessage`] to the matching engine, waiting for space in its buffer if needed.
+pub async fn submit(sender: &mpsc::Sender<TradingMessage>, message: NewOrderMessage) -> Result<()> {
+    sender
+        .send(TradingMessage::NewOrder(message))
+        .await
+        .map_err(|_| anyhow!("Trading task has stopped"))?;
+
+    NEW_ORDERS_QUEUED.add(&opentelemetry::Context::current(), 1, &[]);
+
+    Ok(())
+}
+
+/// Take the limit `order` out of the orderbook if it has been `taken`, or put it back in otherwise.
 ///
-/// If the [`NewOrder`] is of [`OrderType::Market`]: find match and notify traders.
+/// The order is removed from the in-memory orderbook of its shard before any order submitted after
+/// this call is matched. Returns the updated order once it has been committed.
+pub async fn update_order(
+    sender: &mpsc::Sender<TradingMessage>,
+    order: Order,
+    taken: bool,
+) -> Result<Order> {
+    let (updated, receiver) = oneshot::channel();
+
+    sender
+        .send(TradingMessage::UpdateOrder(UpdateOrderMessage {
+            order,
+            taken,
+            sender: updated,
+        }))
+        .await
+        .map_err(|_| anyhow!("Trading task has stopped"))?;
+
+    receiver.await.context("Trading task has stopped")?
+}
+
+/// Let the matching engine reload its limit orders from the database.
 ///
-/// TODO(holzeis): The limit and market order models should be separated so we can process the
-/// models independently.
-pub async fn process_new_order(
+/// Must be called after limit orders have been changed outside of the matching engine.
+pub async fn reload_orderbook(sender: &mpsc::Sender<TradingMessage>) -> Result<()> {
+    sender
+        .send(TradingMessage::ReloadOrderbook)
+        .await
+        .map_err(|_| anyhow!("Trading task has stopped"))
+}
+
+/// Wait until the matching engine has processed all orders submitted so far and their writes have
+/// been committed.
+pub async fn flush(sender: &mpsc::Sender<TradingMessage>) -> Result<()> {
+    let (flushed, receiver) = oneshot::channel();
+
+    sender
+        .send(TradingMessage::Flush(flushed))
+        .await
+        .map_err(|_| anyhow!("Trading task has stopped"))?;
+
+    receiver.await.context("Trading task has stopped")
+}
+
+/// The dependencies shared by all shards of the matching engine.
+#[derive(Clone)]
+struct Engine {
     pool: Pool<ConnectionManager<PgConnection>>,
-    notifier: mpsc::Sender<OrderbookMessage>,
+    writer: BatchWriter,
     tx_price_feed: broadcast::Sender<Message>,
-    new_order: NewOrder,
-    order_reason: OrderReason,
+    notifier: mpsc::Sender<OrderbookMessage>,
+    maker_notifier: MakerNotifier,
     network: Network,
     oracle_pk: XOnlyPublicKey,
-) -> Result<Order> {
-    tracing::info!(
-        trader_id = %new_order.trader_id,
-        order_type = ?new_order.order_type,
-        "Processing new order",
-    );
+    trading_halt: TradingHalt,
+    risk_limits: RiskLimits,
+    clock: Arc<dyn Clock>,
+}
 
-    let mut conn = spawn_blocking(move || pool.get())
-        .await
-        .expect("task to complete")?;
-
-    if new_order.order_type == OrderType::Limit && new_order.price == Decimal::ZERO {
-        return Err(TradingError::InvalidOrder(
-            "Limit orders with zero price are not allowed".to_string(),
-        ))?;
-    }
-
-    // Before processing any match we set all expired limit orders to failed, to ensure they do not
-    // get matched.
-    //
-    // TODO(holzeis): Orders should probably not have an expiry, but should either be replaced or
-    // deleted if not wanted anymore.
-    let expired_limit_orders = orders::set_expired_limit_orders_to_failed(&mut conn)?;
-    for expired_limit_order in expired_limit_orders {
-        tx_price_feed
-            .send(Message::DeleteOrder(expired_limit_order.id))
-            .map_err(|e| anyhow!(e))
-            .context("Could not update price feed")?;
-    }
-
-    let order = orders::insert(&mut conn, new_order.clone(), order_reason)
-        .map_err(|e| anyhow!(e))
-        .context("Failed to insert new order into DB")?;
-
-    if new_order.order_type == OrderType::Limit {
-        tx_price_feed
-            .send(Message::NewOrder(order.clone()))
-            .map_err(|e| anyhow!(e))
-            .context("Could not update price feed")?;
-    } else {
-        // Reject new order if there is already a matched order waiting for execution.
-        if let Some(order) =
-            orders::get_by_trader_id_and_state(&mut conn, new_order.trader_id, OrderState::Matched)?
-        {
-            bail!(TradingError::InvalidOrder(format!(
-                "trader_id={}, order_id={}. Order is currently in execution. \
-                 Can't accept new orders until the order execution is finished",
-                new_order.trader_id, order.id
-            )));
+enum ShardMessage {
+    NewOrder(NewOrderMessage),
+    UpdateOrder(UpdateOrderMessage),
+    ReloadOrderbook,
+    Flush(oneshot::Sender<()>),
+}
+
+/// The part of the matching engine which processes the orders of a single contract symbol.
+struct Shard {
+    contract_symbol: ContractSymbol,
+    /// The open limit orders of the contract symbol.
+    limit_orders: Vec<Order>,
+    /// Set if the writes of an order could not be committed, in which case the limit orders in
+    /// memory no longer reflect the event log.
+    needs_reload: Arc<AtomicBool>,
+    /// The number of events we have appended to the event log since our last snapshot.
+    events_since_snapshot: usize,
+    engine: Engine,
+}
+
+/// What is left to do for a new order once its database writes have been committed.
+struct Outcome {
+    order: Order,
+    /// The match of a market order, together with the matched limit orders.
+    matched: Option<(MatchParams, Vec<Order>)>,
+    /// Set if a market order could not be matched.
+    error: Option<anyhow::Error>,
+}
+
+impl Shard {
+    fn spawn(contract_symbol: ContractSymbol, engine: Engine) -> mpsc::Sender<ShardMessage> {
+        let (sender, receiver) = mpsc::channel::<ShardMessage>(NEW_ORDERS_BUFFER_SIZE);
+
+        // The limit orders are recovered from the event log once the first message arrives.
+        let shard = Shard {
+            contract_symbol,
+            limit_orders: vec![],
+            needs_reload: Arc::new(AtomicBool::new(true)),
+            events_since_snapshot: 0,
+            engine,
+        };
+
+        tokio::spawn(shard.run(receiver));
+
+        sender
+    }
+
+    async fn run(mut self, mut receiver: mpsc::Receiver<ShardMessage>) {
+        while let Some(message) = receiver.recv().await {
+            // Without its limit orders, the shard could match orders against limit orders which
+            // have been matched already. Hence orders are rejected until a reload succeeds.
+            let loaded = if self.needs_reload.swap(false, atomic::Ordering::SeqCst) {
+                match self.reload().await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        tracing::error!(
+                            contract_symbol = %self.contract_symbol,
+                            "Failed to load orderbook: {e:#}"
+                        );
+                        self.needs_reload.store(true, atomic::Ordering::SeqCst);
+                        false
+                    }
+                }
+            } else {
+                true
+            };
+
+            match message {
+                ShardMessage::NewOrder(new_order_msg) if !loaded => {
+                    NEW_ORDERS_QUEUED.add(&opentelemetry::Context::current(), -1, &[]);
+                    respond(
+                        new_order_msg.sender,
+                        Err(TradingError::OrderbookUnavailable.into()),
+                    )
+                    .await;
+                }
+                ShardMessage::UpdateOrder(update_order_msg) if !loaded => {
+                    let _ = update_order_msg
+                        .sender
+                        .send(Err(TradingError::OrderbookUnavailable.into()));
+                }
+                ShardMessage::NewOrder(new_order_msg) => {
+                    let cx = opentelemetry::Context::current();
+                    NEW_ORDERS_QUEUED.add(&cx, -1, &[]);
+                    NEW_ORDERS_IN_PROGRESS.add(&cx, 1, &[]);
+
+                    let span = tracing::info_span!(
+                        parent: &new_order_msg.span,
+                        "process_new_order",
+                        order_id = %new_order_msg.new_order.id,
+                    );
+                    self.process_new_order(new_order_msg).instrument(span).await;
+
+                    NEW_ORDERS_IN_PROGRESS.add(&cx, -1, &[]);
+                }
+                ShardMessage::UpdateOrder(update_order_msg) => {
+                    self.process_update_order(update_order_msg).await;
+                }
+                ShardMessage::ReloadOrderbook => {
+                    // Reloading before the next message coalesces consecutive reloads.
+                    self.needs_reload.store(true, atomic::Ordering::SeqCst);
+                }
+                ShardMessage::Flush(flushed) => {
+                    if let Err(e) = self.engine.writer.flush().await {
+                        tracing::error!(
+                            contract_symbol = %self.contract_symbol,
+                            "Failed to flush writes: {e:#}"
+                        );
+                    }
+
+                    let _ = flushed.send(());
+                }
+            }
+
+            if self.events_since_snapshot >= SNAPSHOT_INTERVAL {
+                match self.snapshot().await {
+                    Ok(()) => self.events_since_snapshot = 0,
+                    Err(e) => tracing::error!(
+                        contract_symbol = %self.contract_symbol,
+                        "Failed to take snapshot of orderbook: {e:#}"
+                    ),
+                }
+            }
         }
 
-        let opposite_direction_limit_orders = orders::all_by_direction_and_type(
-            &mut conn,
-            order.direction.opposite(),
-            OrderType::Limit,
-            true,
-        )?;
-
-        let matched_orders =
-            match match_order(&order, opposite_direction_limit_orders, network, oracle_pk) {
-                Ok(Some(matched_orders)) => matched_orders,
+        tracing::error!(contract_symbol = %self.contract_symbol, "Shard channel closed");
+    }
+
+    /// Recover the open limit orders of our contract symbol from the event log.
+    async fn reload(&mut self) -> Result<()> {
+        // Our own events have to be committed first, otherwise we would miss them.
+        self.engine.writer.flush().await?;
+
+        let pool = self.engine.pool.clone();
+        let contract_symbol = self.contract_symbol;
+        let limit_orders = spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            event_log::recover(&mut conn, contract_symbol)
+        })
+        .await??;
+
+        tracing::debug!(
+            %contract_symbol,
+            limit_orders = limit_orders.len(),
+            "Recovered orderbook"
+        );
+
+        self.limit_orders = limit_orders;
+
+        Ok(())
+    }
+
+    async fn snapshot(&mut self) -> Result<()> {
+        self.engine.writer.flush().await?;
+
+        let pool = self.engine.pool.clone();
+        let contract_symbol = self.contract_symbol;
+        spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            event_log::snapshot(&mut conn, contract_symbol)
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    /// The [`Write`] appending `event` to the event log.
+    fn event(&mut self, event: OrderbookEvent) -> Write {
+        self.events_since_snapshot += 1;
+
+        Write::AppendEvent {
+            contract_symbol: self.contract_symbol,
+            event,
+        }
+    }
+
+    /// Process a [`NewOrderMessage`].
+    ///
+    /// The in-memory orderbook is updated right away, while the response and the notifications
+    /// are sent once the writes of the order have been committed.
+    async fn process_new_order(&mut self, new_order_msg: NewOrderMessage) {
+        let NewOrderMessage {
+            new_order,
+            order_reason,
+            sender,
+            span: _,
+        } = new_order_msg;
+
+        tracing::info!(
+            order_id = %new_order.id,
+            trader_id = %new_order.trader_id,
+            order_type = ?new_order.order_type,
+            "Processing new order",
+        );
+
+        // Before processing any match we set all expired limit orders to failed, to ensure they
+        // do not get matched.
+        //
+        // TODO(holzeis): Orders should probably not have an expiry, but should either be replaced
+        // or deleted if not wanted anymore.
+        if let Err(e) = self.expire_limit_orders().await {
+            tracing::error!("Failed to expire limit orders: {e:#}");
+        }
+
+        match self.apply_new_order(new_order, order_reason).await {
+            Ok((committed, outcome)) => {
+                tokio::spawn({
+                    let engine = self.engine.clone();
+                    let needs_reload = self.needs_reload.clone();
+                    async move {
+                        let result = match wait_for_commit(committed).await {
+                            Ok(()) => engine.publish(outcome).await,
+                            Err(e) => {
+                                needs_reload.store(true, atomic::Ordering::SeqCst);
+                                Err(e.context("Failed to store order"))
+                            }
+                        };
+
+                        respond(sender, result).await;
+                    }
+                    .instrument(Span::current())
+                });
+            }
+            Err(e) => respond(sender, Err(e)).await,
+        }
+    }
+
+    /// Apply a [`NewOrder`] to the in-memory orderbook and queue the resulting database writes.
+    ///
+    /// If the [`NewOrder`] is of [`OrderType::Limit`]: add it to the orderbook.
+    ///
+    /// If the [`NewOrder`] is of [`OrderType::Market`]: find a match and take the matched limit
+    /// orders out of the orderbook. Market orders are rejected while trading is halted.
+    ///
+    /// TODO(holzeis): The limit and market order models should be separated so we can process the
+    /// models independently.
+    async fn apply_new_order(
+        &mut self,
+        mut new_order: NewOrder,
+        order_reason: OrderReason,
+    ) -> Result<(oneshot::Receiver<Result<()>>, Outcome)> {
+        let limit_price = match new_order.order_type {
+            OrderType::Limit => Some(new_order.price),
+            OrderType::Market => None,
+        };
+        validation::validate_order(
+            new_order.contract_symbol,
+            new_order.leverage,
+            new_order.quantity,
+            limit_price,
+        )
+        .map_err(TradingError::Validation)?;
+
+        if new_order.order_type == OrderType::Limit {
+            new_order.price =
+                validation::round_to_tick_size(new_order.contract_symbol, new_order.price);
+        }
+
+        if new_order.order_type == OrderType::Market {
+            if let Some(reason) = self.engine.trading_halt.get() {
+                bail!(TradingError::TradingHalted(reason.to_string()));
+            }
+
+            check_risk_limits(&new_order, self.engine.risk_limits)?;
+        }
+
+        if new_order.reduce_only {
+            let position = get_open_position(
+                self.engine.pool.clone(),
+                new_order.trader_id,
+                new_order.contract_symbol,
+            )
+            .await?;
+            check_reduce_only(&new_order, position)?;
+        }
+
+        let order = open_order(&new_order, order_reason.clone());
+        let mut writes = vec![Write::InsertOrder {
+            new_order,
+            order_reason,
+        }];
+
+        let outcome = if order.order_type == OrderType::Limit {
+            self.limit_orders.push(order.clone());
+            writes.push(self.event(OrderbookEvent::OrderAdded(order.clone())));
+
+            Outcome {
+                order,
+                matched: None,
+                error: None,
+            }
+        } else {
+            // Reject new order if there is already a matched order waiting for execution. Matches
+            // are written in the background, hence we have to wait for them to be committed.
+            self.engine.writer.flush().await?;
+            if let Some(matched_order) =
+                get_matched_order(self.engine.pool.clone(), order.trader_id).await?
+            {
+                bail!(TradingError::InvalidOrder(format!(
+                    "trader_id={}, order_id={}. Order is currently in execution. \
+                     Can't accept new orders until the order execution is finished",
+                    order.trader_id, matched_order.id
+                )));
+            }
+
+            let opposite_direction_limit_orders = self
+                .limit_orders
+                .iter()
+                .filter(|limit_order| limit_order.direction == order.direction.opposite())
+                .cloned()
+                .collect();
+
+            match match_order(
+                &order,
+                opposite_direction_limit_orders,
+                self.engine.network,
+                self.engine.oracle_pk,
+                self.engine.clock.now(),
+            ) {
+                Ok(Some(matched_orders)) => {
+                    tracing::info!(
+                        trader_id=%order.trader_id,
+                        order_id=%order.id,
+                        "Found a match with {} makers for new order",
+                        matched_orders.taker_match.filled_with.matches.len()
+                    );
+
+                    // Take the matched limit orders out of the orderbook right away, so that they
+                    // cannot be matched with another market order.
+                    let (quotes, limit_orders) = std::mem::take(&mut self.limit_orders)
+                        .into_iter()
+                        .partition::<Vec<_>, _>(|limit_order| {
+                            matched_orders
+                                .makers_matches
+                                .iter()
+                                .any(|m| m.filled_with.order_id == limit_order.id)
+                        });
+                    self.limit_orders = limit_orders;
+
+                    for quote in quotes.iter() {
+                        writes
+                            .push(self.event(OrderbookEvent::OrderMatched { order_id: quote.id }));
+                    }
+
+                    for match_param in matched_orders.matches() {
+                        writes.push(Write::InsertMatch(match_param.clone()));
+                        writes.push(Write::SetOrderState {
+                            order_id: match_param.filled_with.order_id,
+                            order_state: OrderState::Matched,
+                        });
+                    }
+
+                    Outcome {
+                        order,
+                        matched: Some((matched_orders, quotes)),
+                        error: None,
+                    }
+                }
                 Ok(None) => {
                     // TODO(holzeis): Currently we still respond to the user immediately if there
                     // has been a match or not, that's the reason why we also have to set the order
                     // to failed here. But actually we could keep the order until either expired or
                     // a match has been found and then update the state accordingly.
-
-                    orders::set_order_state(&mut conn, order.id, OrderState::Failed)?;
-                    bail!(TradingError::NoMatchFound(format!(
-                        "Could not match order {}",
-                        order.id
-                    )));
+                    writes.push(Write::SetOrderState {
+                        order_id: order.id,
+                        order_state: OrderState::Failed,
+                    });
+
+                    let error =
+                        TradingError::NoMatchFound(format!("Could not match order {}", order.id));
+
+                    Outcome {
+                        order,
+                        matched: None,
+                        error: Some(error.into()),
+                    }
                 }
                 Err(e) => {
-                    orders::set_order_state(&mut conn, order.id, OrderState::Failed)?;
-                    bail!("Failed to match order: {e:#}")
+                    writes.push(Write::SetOrderState {
+                        order_id: order.id,
+                        order_state: OrderState::Failed,
+                    });
+
+                    Outcome {
+                        order,
+                        matched: None,
+                        error: Some(anyhow!("Failed to match order: {e:#}")),
+                    }
                 }
-            };
+            }
+        };
 
-        tracing::info!(
-            trader_id=%order.trader_id,
-            order_id=%order.id,
-            "Found a match with {} makers for new order",
-            matched_orders.taker_match.filled_with.matches.len()
-        );
+        let committed = self.engine.writer.write(writes).await?;
 
-        for match_param in matched_orders.matches() {
-            matches::insert(&mut conn, match_param)?;
+        Ok((committed, outcome))
+    }
+
+    /// Process an [`UpdateOrderMessage`].
+    ///
+    /// Like for new orders, the in-memory orderbook is updated right away, while the response is
+    /// sent once the writes have been committed.
+    async fn process_update_order(&mut self, update_order_msg: UpdateOrderMessage) {
+        let UpdateOrderMessage {
+            mut order,
+            taken,
+            sender,
+        } = update_order_msg;
+
+        self.limit_orders
+            .retain(|limit_order| limit_order.id != order.id);
+
+        let event = if taken {
+            order.order_state = OrderState::Taken;
+            OrderbookEvent::OrderCancelled { order_id: order.id }
+        } else {
+            order.order_state = OrderState::Open;
+            self.limit_orders.push(order.clone());
+            OrderbookEvent::OrderAmended(order.clone())
+        };
+
+        let writes = vec![
+            Write::SetOrderState {
+                order_id: order.id,
+                order_state: order.order_state.clone(),
+            },
+            self.event(event),
+        ];
+
+        let committed = match self.engine.writer.write(writes).await {
+            Ok(committed) => committed,
+            Err(e) => {
+                self.needs_reload.store(true, atomic::Ordering::SeqCst);
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+
+        tokio::spawn({
+            let needs_reload = self.needs_reload.clone();
+            async move {
+                let result = match wait_for_commit(committed).await {
+                    Ok(()) => Ok(order),
+                    Err(e) => {
+                        needs_reload.store(true, atomic::Ordering::SeqCst);
+                        Err(e.context("Failed to update order"))
+                    }
+                };
+
+                let _ = sender.send(result);
+            }
+        });
+    }
+
+    /// Take expired limit orders out of the orderbook and set them to failed.
+    async fn expire_limit_orders(&mut self) -> Result<()> {
+        let now = self.engine.clock.now();
+        let (expired_limit_orders, limit_orders) = std::mem::take(&mut self.limit_orders)
+            .into_iter()
+            .partition::<Vec<_>, _>(|limit_order| limit_order.expiry < now);
+        self.limit_orders = limit_orders;
+
+        if expired_limit_orders.is_empty() {
+            return Ok(());
+        }
+
+        let mut writes = vec![];
+        for expired_limit_order in expired_limit_orders.iter() {
+            writes.push(Write::SetOrderState {
+                order_id: expired_limit_order.id,
+                order_state: OrderState::Failed,
+            });
+            writes.push(self.event(OrderbookEvent::OrderCancelled {
+                order_id: expired_limit_order.id,
+            }));
+        }
+        let committed = self.engine.writer.write(writes).await?;
+
+        tokio::spawn({
+            let engine = self.engine.clone();
+            let needs_reload = self.needs_reload.clone();
+            async move {
+                if let Err(e) = wait_for_commit(committed).await {
+                    tracing::error!("Failed to set expired limit orders to failed: {e:#}");
+                    needs_reload.store(true, atomic::Ordering::SeqCst);
+                    return;
+                }
+
+                for expired_limit_order in expired_limit_orders {
+                    if let Err(e) = engine
+                        .tx_price_feed
+                        .send(Message::DeleteOrder(expired_limit_order.id))
+                    {
+                        tracing::warn!("Could not update price feed: {e:#}");
+                    }
+
+                    engine
+                        .maker_notifier
+                        .notify(
+                            expired_limit_order.trader_id,
+                            QuoteEvent::new(
+                                expired_limit_order.id,
+                                QuoteEventKind::Expired,
+                                Decimal::ZERO,
+                                expired_limit_order.quantity,
+                                None,
+                            ),
+                        )
+                        .await;
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl Engine {
+    /// Update the price feed and notify the traders about the [`Outcome`] of a new order.
+    async fn publish(&self, outcome: Outcome) -> Result<Order> {
+        let Outcome {
+            order,
+            matched,
+            error,
+        } = outcome;
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        if order.order_type == OrderType::Limit {
+            self.tx_price_feed
+                .send(Message::NewOrder(order.clone()))
+                .map_err(|e| anyhow!(e))
+                .context("Could not update price feed")?;
+        }
+
+        let (matched_orders, quotes) = match matched {
+            Some(matched) => matched,
+            None => return Ok(order),
+        };
 
+        for match_param in matched_orders.matches() {
             let trader_id = match_param.trader_id;
             let order_id = match_param.filled_with.order_id.to_string();
 
@@ -239,35 +900,133 @@ pub async fn process_new_order(
                 notification,
             };
 
-            let order_state = match notifier.send(msg).await {
-                Ok(()) => {
-                    tracing::debug!(%trader_id, order_id, "Successfully notified trader");
-                    OrderState::Matched
-                }
+            match self.notifier.send(msg).await {
+                Ok(()) => tracing::debug!(%trader_id, order_id, "Successfully notified trader"),
                 Err(e) => {
-                    tracing::warn!(%trader_id, order_id, "Failed to send trader message: {e:#}");
-
-                    if order.order_type == OrderType::Limit {
-                        // FIXME: The maker is currently not connected to the WebSocket so we can't
-                        // notify him about a trade. However, trades are always accepted by the
-                        // maker at the moment so in order to not have all limit orders in order
-                        // state `Match` we are setting the order to `Taken` even if we couldn't
-                        // notify the maker.
-
-                        OrderState::Taken
-                    } else {
-                        OrderState::Matched
-                    }
+                    tracing::warn!(%trader_id, order_id, "Failed to send trader message: {e:#}")
                 }
-            };
+            }
+        }
 
-            tracing::debug!(%trader_id, order_id, "Updating the order state to {order_state:?}");
+        for maker_match in matched_orders.makers_matches.iter() {
+            let quote = match quotes
+                .iter()
+                .find(|quote| quote.id == maker_match.filled_with.order_id)
+            {
+                Some(quote) => quote,
+                None => continue,
+            };
 
-            orders::set_order_state(&mut conn, match_param.filled_with.order_id, order_state)?;
+            self.maker_notifier
+                .notify(
+                    maker_match.trader_id,
+                    quote_filled_event(quote, maker_match),
+                )
+                .await;
         }
+
+        Ok(order)
+    }
+}
+
+/// Build the [`Order`] as it is stored in the database for a [`NewOrder`].
+fn open_order(new_order: &NewOrder, order_reason: OrderReason) -> Order {
+    Order {
+        id: new_order.id,
+        price: new_order.price.round_dp(2),
+        leverage: new_order.leverage,
+        contract_symbol: new_order.contract_symbol,
+        trader_id: new_order.trader_id,
+        direction: new_order.direction,
+        quantity: new_order.quantity.round_dp(2),
+        order_type: new_order.order_type,
+        timestamp: OffsetDateTime::now_utc(),
+        expiry: new_order.expiry,
+        order_state: OrderState::Open,
+        order_reason,
+        stable: new_order.stable,
+    }
+}
+
+async fn get_matched_order(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    trader_id: PublicKey,
+) -> Result<Option<Order>> {
+    spawn_blocking(move || {
+        let mut conn = pool.get()?;
+        let order = orders::get_by_trader_id_and_state(&mut conn, trader_id, OrderState::Matched)?;
+
+        anyhow::Ok(order)
+    })
+    .await?
+}
+
+/// The direction and quantity of the trader's open position in `contract_symbol`, if any.
+async fn get_open_position(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    trader_id: PublicKey,
+    contract_symbol: ContractSymbol,
+) -> Result<Option<(Direction, Decimal)>> {
+    spawn_blocking(move || {
+        let mut conn = pool.get()?;
+        let position = db::positions::Position::get_positions_by_trader(
+            &mut conn,
+            trader_id,
+            vec![PositionState::Open],
+        )?
+        .into_iter()
+        .find(|position| position.contract_symbol == contract_symbol);
+
+        let position = match position {
+            Some(position) => Some((
+                position.direction,
+                Decimal::try_from(position.quantity).context("Failed to convert quantity")?,
+            )),
+            None => None,
+        };
+
+        anyhow::Ok(position)
+    })
+    .await?
+}
+
+async fn wait_for_commit(committed: oneshot::Receiver<Result<()>>) -> Result<()> {
+    committed.await.context("Batch writer has stopped")?
+}
+
+async fn respond(sender: mpsc::Sender<Result<Order>>, result: Result<Order>) {
+    if let Err(e) = sender.send(result).await {
+        tracing::error!("Failed to respond to NewOrderMessage: {e:#}");
     }
+}
 
-    Ok(order)
+/// Build the [`QuoteEvent`] for a maker `quote` which has been matched.
+///
+/// The quote is taken out of the orderbook as a whole, hence if it has not been filled completely
+/// the remaining quantity has to be quoted again by the maker.
+fn quote_filled_event(quote: &Order, maker_match: &TraderMatchParams) -> QuoteEvent {
+    let matched_quantity: Decimal = maker_match
+        .filled_with
+        .matches
+        .iter()
+        .map(|m| m.quantity)
+        .sum();
+    let filled_quantity = matched_quantity.min(quote.quantity);
+    let remaining_quantity = quote.quantity - filled_quantity;
+
+    let kind = if remaining_quantity > Decimal::ZERO {
+        QuoteEventKind::PartiallyFilled
+    } else {
+        QuoteEventKind::Filled
+    };
+
+    QuoteEvent::new(
+        quote.id,
+        kind,
+        filled_quantity,
+        remaining_quantity,
+        Some(quote.price),
+    )
 }
 
 /// Matches an [`Order`] of [`OrderType::Market`] with a list of [`Order`]s of [`OrderType::Limit`].
@@ -275,12 +1034,12 @@ pub async fn process_new_order(
 /// The caller is expected to provide a list of `opposite_direction_orders` of [`OrderType::Limit`]
 /// and opposite [`Direction`] to the `market_order`. We nevertheless ensure that this is the case
 /// to be on the safe side.
-
 fn match_order(
     market_order: &Order,
     opposite_direction_orders: Vec<Order>,
     network: Network,
     oracle_pk: XOnlyPublicKey,
+    now: OffsetDateTime,
 ) -> Result<Option<MatchParams>> {
     if market_order.order_type == OrderType::Limit {
         // We don't match limit orders with other limit orders at the moment.
@@ -315,7 +1074,13 @@ fn match_order(
         return Ok(None);
     }
 
-    let expiry_timestamp = commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
+    // A single limit order which is smaller than the market order must not be filled beyond its
+    // quantity.
+    if remaining_quantity > Decimal::ZERO {
+        bail!("Not enough liquidity to fill the order, please reduce order quantity");
+    }
+
+    let expiry_timestamp = commons::calculate_next_expiry(now, network);
 
     let matches = matched_orders
         .iter()
@@ -404,6 +1169,61 @@ fn sort_orders(mut limit_orders: Vec<Order>, market_order_direction: Direction)
     limit_orders
 }
 
+/// Reject market orders which exceed the configured [`RiskLimits`].
+fn check_risk_limits(new_order: &NewOrder, risk_limits: RiskLimits) -> Result<()> {
+    if new_order.leverage > risk_limits.max_leverage {
+        bail!(TradingError::InvalidOrder(format!(
+            "Leverage of {} exceeds the maximum leverage of {}",
+            new_order.leverage, risk_limits.max_leverage
+        )));
+    }
+
+    if let Some(max_quantity) = risk_limits.max_quantity {
+        let max_quantity =
+            Decimal::try_from(max_quantity).context("Failed to convert max quantity")?;
+        if new_order.quantity > max_quantity {
+            bail!(TradingError::InvalidOrder(format!(
+                "Quantity of {} exceeds the maximum quantity of {max_quantity}",
+                new_order.quantity
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure that a reduce-only order can only decrease the trader's open `position`, given by its
+/// direction and quantity.
+fn check_reduce_only(new_order: &NewOrder, position: Option<(Direction, Decimal)>) -> Result<()> {
+    if new_order.order_type != OrderType::Market {
+        bail!(TradingError::InvalidOrder(
+            "Only market orders can be reduce-only".to_string()
+        ));
+    }
+
+    let (direction, quantity) = match position {
+        Some(position) => position,
+        None => bail!(TradingError::InvalidOrder(
+            "Reduce-only order without an open position".to_string()
+        )),
+    };
+
+    if new_order.direction == direction {
+        bail!(TradingError::InvalidOrder(format!(
+            "Reduce-only order would increase the {direction:?} position"
+        )));
+    }
+
+    if new_order.quantity > quantity {
+        bail!(TradingError::InvalidOrder(format!(
+            "Reduce-only order of {} contracts exceeds the position of {quantity} contracts",
+            new_order.quantity
+        )));
+    }
+
+    Ok(())
+}
+
 impl MatchParams {
     fn matches(&self) -> Vec<&TraderMatchParams> {
         std::iter::once(&self.taker_match)
@@ -424,11 +1244,122 @@ impl From<&TradeParams> for TraderMatchParams {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use rust_decimal_macros::dec;
     use std::str::FromStr;
     use time::Duration;
     use trade::ContractSymbol;
 
+    #[test]
+    fn market_orders_exceeding_risk_limits_are_rejected() {
+        let risk_limits = RiskLimits {
+            max_leverage: 5.0,
+            max_quantity: Some(1_000.0),
+        };
+        let order = NewOrder {
+            id: Uuid::new_v4(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            price: Decimal::ZERO,
+            quantity: dec!(100),
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction: Direction::Long,
+            leverage: 2.0,
+            order_type: OrderType::Market,
+            expiry: OffsetDateTime::now_utc(),
+            stable: false,
+            reduce_only: false,
+        };
+
+        assert!(check_risk_limits(&order, risk_limits).is_ok());
+
+        let too_much_leverage = NewOrder {
+            leverage: 10.0,
+            ..order.clone()
+        };
+        assert!(check_risk_limits(&too_much_leverage, risk_limits).is_err());
+
+        let too_large = NewOrder {
+            quantity: dec!(1_001),
+            ..order
+        };
+        assert!(check_risk_limits(&too_large, risk_limits).is_err());
+    }
+
+    #[test]
+    fn reduce_only_orders_cannot_increase_position() {
+        let order = NewOrder {
+            id: Uuid::new_v4(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            price: Decimal::ZERO,
+            quantity: dec!(100),
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction: Direction::Short,
+            leverage: 2.0,
+            order_type: OrderType::Market,
+            expiry: OffsetDateTime::now_utc(),
+            stable: false,
+            reduce_only: true,
+        };
+
+        assert!(check_reduce_only(&order, Some((Direction::Long, dec!(100)))).is_ok());
+        assert!(check_reduce_only(&order, Some((Direction::Long, dec!(200)))).is_ok());
+
+        assert!(check_reduce_only(&order, None).is_err());
+        assert!(check_reduce_only(&order, Some((Direction::Short, dec!(100)))).is_err());
+        assert!(check_reduce_only(&order, Some((Direction::Long, dec!(50)))).is_err());
+
+        let limit_order = NewOrder {
+            order_type: OrderType::Limit,
+            price: dec!(30_000),
+            ..order
+        };
+        assert!(check_reduce_only(&limit_order, Some((Direction::Long, dec!(100)))).is_err());
+    }
+
+    #[test]
+    fn new_orders_are_rejected_if_buffer_is_full() {
+        let (trading_sender, _receiver) = mpsc::channel::<TradingMessage>(1);
+
+        let new_order_message = || {
+            let (sender, _) = mpsc::channel::<Result<Order>>(1);
+            NewOrderMessage {
+                new_order: NewOrder {
+                    id: Uuid::new_v4(),
+                    contract_symbol: ContractSymbol::BtcUsd,
+                    price: Decimal::ZERO,
+                    quantity: dec!(100),
+                    trader_id: PublicKey::from_str(
+                        "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+                    )
+                    .unwrap(),
+                    direction: Direction::Long,
+                    leverage: 2.0,
+                    order_type: OrderType::Market,
+                    expiry: OffsetDateTime::now_utc(),
+                    stable: false,
+                    reduce_only: false,
+                },
+                order_reason: OrderReason::Manual,
+                sender,
+                span: Span::none(),
+            }
+        };
+
+        assert!(try_submit(&trading_sender, new_order_message()).is_ok());
+
+        let error = try_submit(&trading_sender, new_order_message()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<TradingError>(),
+            Some(&TradingError::ServiceOverloaded)
+        );
+    }
+
     #[test]
     fn when_short_then_sort_desc() {
         let order1 = dummy_long_order(
@@ -574,6 +1505,7 @@ mod tests {
             all_orders,
             Network::Bitcoin,
             get_oracle_public_key(),
+            OffsetDateTime::now_utc(),
         )
         .unwrap()
         .unwrap();
@@ -655,7 +1587,8 @@ mod tests {
             &order,
             all_orders,
             Network::Bitcoin,
-            get_oracle_public_key()
+            get_oracle_public_key(),
+            OffsetDateTime::now_utc(),
         )
         .is_err());
     }
@@ -713,12 +1646,126 @@ mod tests {
             all_orders,
             Network::Bitcoin,
             get_oracle_public_key(),
+            OffsetDateTime::now_utc(),
         )
         .unwrap();
 
         assert!(matched_orders.is_none());
     }
 
+    #[test]
+    fn quote_matched_with_smaller_order_is_partially_filled() {
+        let quote = dummy_long_order(
+            dec!(20_000),
+            Uuid::new_v4(),
+            dec!(300),
+            Duration::seconds(0),
+        );
+
+        let order = Order {
+            id: Uuid::new_v4(),
+            price: Default::default(),
+            trader_id: PublicKey::from_str(
+                "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007",
+            )
+            .unwrap(),
+            direction: Direction::Short,
+            leverage: 1.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            quantity: dec!(100),
+            order_type: OrderType::Market,
+            timestamp: OffsetDateTime::now_utc(),
+            expiry: OffsetDateTime::now_utc() + Duration::minutes(1),
+            order_state: OrderState::Open,
+            order_reason: OrderReason::Manual,
+            stable: false,
+        };
+
+        let matched_orders = match_order(
+            &order,
+            vec![quote.clone()],
+            Network::Bitcoin,
+            get_oracle_public_key(),
+            OffsetDateTime::now_utc(),
+        )
+        .unwrap()
+        .unwrap();
+
+        let event = quote_filled_event(&quote, &matched_orders.makers_matches[0]);
+
+        assert_eq!(event.order_id, quote.id);
+        assert_eq!(event.kind, QuoteEventKind::PartiallyFilled);
+        assert_eq!(event.filled_quantity, dec!(100));
+        assert_eq!(event.remaining_quantity, dec!(200));
+        assert_eq!(event.execution_price, Some(dec!(20_000)));
+    }
+
+    #[test]
+    fn market_order_exceeding_single_limit_order_is_not_matched() {
+        let quote =
+            invariants::limit_order(Direction::Long, dec!(20_000), dec!(100), Duration::ZERO);
+        let order = invariants::market_order(Direction::Short, dec!(200));
+
+        assert!(match_order(
+            &order,
+            vec![quote],
+            Network::Regtest,
+            get_oracle_public_key(),
+            OffsetDateTime::now_utc(),
+        )
+        .is_err());
+    }
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop_oneof![Just(Direction::Long), Just(Direction::Short)]
+    }
+
+    /// Mostly a handful of prices, so that the book has many orders at the same price.
+    fn price() -> impl Strategy<Value = Decimal> {
+        prop_oneof![
+            8 => (39_990u32..40_010).prop_map(Decimal::from),
+            1 => any::<u64>().prop_map(Decimal::from),
+            1 => Just(Decimal::MAX),
+        ]
+    }
+
+    fn quantity() -> impl Strategy<Value = Decimal> {
+        prop_oneof![
+            8 => (1u32..1_000).prop_map(Decimal::from),
+            1 => (1u64..u64::MAX, 0u32..=28)
+                .prop_map(|(m, scale)| Decimal::from_i128_with_scale(m.into(), scale)),
+            1 => Just(Decimal::MAX),
+        ]
+    }
+
+    /// A book of limit orders in both directions, many of which are placed at the same time.
+    fn book() -> impl Strategy<Value = Vec<Order>> {
+        prop::collection::vec(
+            (direction(), price(), quantity(), 0i64..3).prop_map(
+                |(direction, price, quantity, delay)| {
+                    invariants::limit_order(direction, price, quantity, Duration::seconds(delay))
+                },
+            ),
+            0..20,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn sorted_orders_respect_price_time_priority(book in book(), direction in direction()) {
+            invariants::check_sort_orders(book, direction);
+        }
+
+        #[test]
+        fn matching_upholds_invariants(
+            book in book(),
+            direction in direction(),
+            quantity in quantity(),
+        ) {
+            invariants::check_match_order(&invariants::market_order(direction, quantity), book);
+        }
+    }
+
     fn dummy_long_order(
         price: Decimal,
         id: Uuid,
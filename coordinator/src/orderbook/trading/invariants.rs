@@ -0,0 +1,212 @@
+//! Invariants of the matching engine, shared by the property-based tests and the fuzz targets in
+//! `coordinator/fuzz`.
+//!
+//! The checks panic if an invariant is violated.
+
+use super::match_order;
+use super::sort_orders;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Network;
+use bitcoin::XOnlyPublicKey;
+use commons::Order;
+use commons::OrderReason;
+use commons::OrderState;
+use commons::OrderType;
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+use std::str::FromStr;
+use time::Duration;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+const TAKER: &str = "027f31ebc5462c1fdce1b737ecff52d37d75dea43ce11c74d25aa297165faa2007";
+const MAKER: &str = "03507b924dae6595cfb78492489978127c5f1e3877848564de2015cd6d41375802";
+const ORACLE: &str = "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0";
+
+/// Wed Aug 09 2023 09:30:23 GMT+0000, so that the checks do not depend on the time they run at.
+const NOW: i64 = 1691573423;
+
+/// A limit order of the maker, placed `delay` after [`now`].
+pub fn limit_order(
+    direction: Direction,
+    price: Decimal,
+    quantity: Decimal,
+    delay: Duration,
+) -> Order {
+    Order {
+        id: Uuid::new_v4(),
+        price,
+        leverage: 2.0,
+        contract_symbol: ContractSymbol::BtcUsd,
+        trader_id: PublicKey::from_str(MAKER).expect("valid public key"),
+        direction,
+        quantity,
+        order_type: OrderType::Limit,
+        timestamp: now() + delay,
+        expiry: now() + Duration::minutes(1),
+        order_state: OrderState::Open,
+        order_reason: OrderReason::Manual,
+        stable: false,
+    }
+}
+
+/// A market order of a trader, placed at [`now`].
+pub fn market_order(direction: Direction, quantity: Decimal) -> Order {
+    Order {
+        id: Uuid::new_v4(),
+        price: Decimal::ZERO,
+        leverage: 2.0,
+        contract_symbol: ContractSymbol::BtcUsd,
+        trader_id: PublicKey::from_str(TAKER).expect("valid public key"),
+        direction,
+        quantity,
+        order_type: OrderType::Market,
+        timestamp: now(),
+        expiry: now() + Duration::minutes(1),
+        order_state: OrderState::Open,
+        order_reason: OrderReason::Manual,
+        stable: false,
+    }
+}
+
+pub fn now() -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(NOW).expect("valid timestamp")
+}
+
+/// Check that [`sort_orders`] only reorders the `limit_orders`, best price first and, at the same
+/// price, earliest first.
+pub fn check_sort_orders(limit_orders: Vec<Order>, market_order_direction: Direction) {
+    let mut ids_before = limit_orders.iter().map(|o| o.id).collect::<Vec<_>>();
+
+    let sorted = sort_orders(limit_orders, market_order_direction);
+
+    let mut ids_after = sorted.iter().map(|o| o.id).collect::<Vec<_>>();
+    ids_before.sort();
+    ids_after.sort();
+    assert_eq!(
+        ids_before, ids_after,
+        "Sorting must not add or remove orders"
+    );
+
+    for pair in sorted.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        assert_ne!(
+            priority(a, b, market_order_direction),
+            Ordering::Greater,
+            "Order {a:?} must not be sorted before {b:?}"
+        );
+    }
+}
+
+/// Check the outcome of matching the `market_order` with the `limit_orders`:
+///
+/// - No self-cross: the market order is only ever matched with a limit order of the opposite
+/// direction.
+///
+/// - Conservation of quantity: the market order is filled completely, and no limit order beyond
+/// its quantity.
+///
+/// - Price-time priority: the market order is matched with the best limit order.
+///
+/// Since we do not support multi-matches yet, matching has to fail if the best limit order can't
+/// fill the market order on its own.
+pub fn check_match_order(market_order: &Order, limit_orders: Vec<Order>) {
+    let eligible = limit_orders
+        .iter()
+        .filter(|o| o.direction != market_order.direction)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let best = eligible
+        .iter()
+        .fold(None, |best: Option<&Order>, o| match best {
+            Some(best) if priority(best, o, market_order.direction) != Ordering::Greater => {
+                Some(best)
+            }
+            _ => Some(o),
+        });
+
+    let outcome = match_order(
+        market_order,
+        limit_orders,
+        Network::Regtest,
+        XOnlyPublicKey::from_str(ORACLE).expect("valid public key"),
+        now(),
+    );
+
+    let matched = match (best, outcome) {
+        (None, Ok(None)) => return,
+        (None, Ok(Some(_))) => panic!("Matched without an eligible limit order"),
+        (None, Err(e)) => panic!("Failed without an eligible limit order: {e:#}"),
+        (Some(best), Err(_)) if best.quantity < market_order.quantity => return,
+        (Some(best), Err(e)) => panic!("Failed to match with {best:?}: {e:#}"),
+        (Some(best), Ok(None)) => panic!("Did not match with {best:?}"),
+        (Some(best), Ok(Some(_))) if best.quantity < market_order.quantity => {
+            panic!("Matched beyond the quantity of {best:?}")
+        }
+        (Some(_), Ok(Some(matched))) => matched,
+    };
+
+    // Only one maker is matched at the moment.
+    assert_eq!(matched.makers_matches.len(), 1);
+    let maker_match = &matched.makers_matches[0];
+    let maker_order = eligible
+        .iter()
+        .find(|o| o.id == maker_match.filled_with.order_id)
+        .expect("matched limit order to be of the opposite direction");
+
+    // Price-time priority.
+    for other in eligible.iter() {
+        assert_ne!(
+            priority(maker_order, other, market_order.direction),
+            Ordering::Greater,
+            "Matched with {maker_order:?} although {other:?} takes precedence"
+        );
+    }
+
+    // Conservation of quantity.
+    let taker = &matched.taker_match;
+    assert_eq!(taker.trader_id, market_order.trader_id);
+    assert_eq!(taker.filled_with.order_id, market_order.id);
+
+    let taker_quantity: Decimal = taker.filled_with.matches.iter().map(|m| m.quantity).sum();
+    let maker_quantity: Decimal = maker_match
+        .filled_with
+        .matches
+        .iter()
+        .map(|m| m.quantity)
+        .sum();
+    assert_eq!(taker_quantity, market_order.quantity);
+    assert_eq!(maker_quantity, taker_quantity);
+    assert!(maker_quantity <= maker_order.quantity);
+
+    for m in taker.filled_with.matches.iter() {
+        assert_eq!(m.order_id, maker_order.id);
+        assert_eq!(m.pubkey, maker_order.trader_id);
+        assert_eq!(m.execution_price, maker_order.price);
+    }
+
+    for m in maker_match.filled_with.matches.iter() {
+        assert_eq!(m.order_id, market_order.id);
+        assert_eq!(m.pubkey, market_order.trader_id);
+        assert_eq!(m.execution_price, maker_order.price);
+    }
+
+    assert_eq!(
+        taker.filled_with.expiry_timestamp,
+        maker_match.filled_with.expiry_timestamp
+    );
+}
+
+/// Whether limit order `a` takes precedence over `b` when matching a market order in the given
+/// `direction`, i.e. [`Ordering::Less`] if `a` is to be matched first.
+fn priority(a: &Order, b: &Order, direction: Direction) -> Ordering {
+    let price = match direction {
+        Direction::Long => a.price.cmp(&b.price),
+        Direction::Short => b.price.cmp(&a.price),
+    };
+
+    price.then(a.timestamp.cmp(&b.timestamp))
+}
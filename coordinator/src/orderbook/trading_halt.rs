@@ -0,0 +1,182 @@
+use crate::health::Health;
+use crate::health::ServiceStatus;
+use crate::orderbook::db::orders;
+use anyhow::Result;
+use bitcoin::XOnlyPublicKey;
+use commons::Message;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tokio::task::spawn_blocking;
+use utoipa::ToSchema;
+
+/// How often we check if the oracle and the price feed are still alive.
+const STALENESS_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// If the maker has not posted a new limit order within this time, we consider the price feed to
+/// be stale.
+const MAX_PRICE_FEED_AGE: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum HaltReason {
+    /// Trading has been halted by an admin.
+    Manual,
+    /// The oracle attesting to our contracts is not reachable.
+    StaleOracle,
+    /// There have been no fresh prices for a while.
+    StalePriceFeed,
+}
+
+impl HaltReason {
+    fn is_automatic(&self) -> bool {
+        !matches!(self, HaltReason::Manual)
+    }
+}
+
+impl fmt::Display for HaltReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            HaltReason::Manual => "Trading has been halted by the coordinator",
+            HaltReason::StaleOracle => "Trading has been halted because the oracle is unavailable",
+            HaltReason::StalePriceFeed => {
+                "Trading has been halted because the price feed is outdated"
+            }
+        };
+
+        f.write_str(reason)
+    }
+}
+
+/// Circuit breaker for trading.
+///
+/// While trading is halted, no new market orders are accepted. Every change in the halt state is
+/// broadcast to all connected clients.
+#[derive(Clone)]
+pub struct TradingHalt {
+    reason: Arc<RwLock<Option<HaltReason>>>,
+    tx_price_feed: broadcast::Sender<Message>,
+}
+
+impl TradingHalt {
+    pub fn new(tx_price_feed: broadcast::Sender<Message>) -> Self {
+        Self {
+            reason: Arc::new(RwLock::new(None)),
+            tx_price_feed,
+        }
+    }
+
+    /// The reason why trading is halted, or `None` if trading is possible.
+    pub fn get(&self) -> Option<HaltReason> {
+        *self.reason.read()
+    }
+
+    /// Halt trading.
+    ///
+    /// An automatic halt never overwrites a manual halt, so that only an admin can resume trading
+    /// after having halted it.
+    pub fn halt(&self, reason: HaltReason) {
+        {
+            let mut current = self.reason.write();
+            match *current {
+                Some(current) if current == reason => return,
+                Some(HaltReason::Manual) if reason.is_automatic() => return,
+                _ => *current = Some(reason),
+            }
+        }
+
+        tracing::warn!(?reason, "Halting trading");
+
+        self.notify(Message::TradingHalted(reason.to_string()));
+    }
+
+    /// Resume trading regardless of why it was halted.
+    pub fn resume(&self) {
+        if self.reason.write().take().is_none() {
+            return;
+        }
+
+        tracing::info!("Resuming trading");
+
+        self.notify(Message::TradingResumed);
+    }
+
+    /// Resume trading if it was halted automatically.
+    fn resume_automatic(&self) {
+        if self.get().map(|reason| reason.is_automatic()) == Some(true) {
+            self.resume();
+        }
+    }
+
+    fn notify(&self, message: Message) {
+        // Sending only fails if no client is connected at the moment, which is fine, since we
+        // inform every client about the halt when they connect.
+        if let Err(e) = self.tx_price_feed.send(message) {
+            tracing::debug!("Could not notify clients about trading halt: {e:#}");
+        }
+    }
+}
+
+/// Periodically check the oracle and the price feed and halt trading if either of them is stale.
+/// Trading is resumed automatically once both are healthy again.
+pub fn monitor(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    health: Health,
+    trading_halt: TradingHalt,
+    oracle_pk: XOnlyPublicKey,
+) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        loop {
+            tokio::time::sleep(STALENESS_CHECK_INTERVAL).await;
+
+            if health.check_oracle(oracle_pk).await == ServiceStatus::Offline {
+                trading_halt.halt(HaltReason::StaleOracle);
+                continue;
+            }
+
+            match is_price_feed_stale(pool.clone()).await {
+                Ok(true) => {
+                    trading_halt.halt(HaltReason::StalePriceFeed);
+                    continue;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::error!("Failed to check if price feed is stale: {e:#}");
+                    continue;
+                }
+            }
+
+            trading_halt.resume_automatic();
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+async fn is_price_feed_stale(pool: Pool<ConnectionManager<PgConnection>>) -> Result<bool> {
+    let latest_timestamp = spawn_blocking(move || {
+        let mut conn = pool.get()?;
+        let timestamp = orders::get_latest_limit_order_timestamp(&mut conn)?;
+
+        anyhow::Ok(timestamp)
+    })
+    .await??;
+
+    let is_stale = match latest_timestamp {
+        Some(timestamp) => OffsetDateTime::now_utc() - timestamp > MAX_PRICE_FEED_AGE,
+        None => true,
+    };
+
+    Ok(is_stale)
+}
@@ -1,28 +1,83 @@
 use crate::db;
 use crate::db::user;
+use crate::db::user_presence;
+use crate::device_session;
+use crate::device_session::SessionDecision;
 use crate::message::NewUserMessage;
 use crate::orderbook::db::orders;
+use crate::orderbook::trading_halt::TradingHalt;
 use crate::routes::AppState;
+use crate::settings::Settings;
+use crate::shutdown::Shutdown;
+use crate::trace;
+use axum::extract::ws::close_code;
+use axum::extract::ws::CloseFrame;
 use axum::extract::ws::Message as WebsocketMessage;
 use axum::extract::ws::WebSocket;
+use axum::extract::FromRef;
+use bitcoin::secp256k1::PublicKey;
 use commons::create_sign_message;
 use commons::LspConfig;
 use commons::Message;
 use commons::OrderbookRequest;
 use commons::AUTH_SIGN_MESSAGE;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
 use futures::SinkExt;
 use futures::StreamExt;
+use parking_lot::Mutex;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::broadcast;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
+use tokio::sync::RwLock;
+use tracing::field;
+use tracing::Instrument;
+use tracing::Span;
+use uuid::Uuid;
 
 const WEBSOCKET_SEND_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// What the orderbook websocket needs, which is served by the leader and by the coordinators on
+/// standby, see [`crate::pubsub`].
+#[derive(Clone)]
+pub struct WebsocketState {
+    pub pool: Pool<ConnectionManager<PgConnection>>,
+    pub settings: Arc<RwLock<Settings>>,
+    pub tx_price_feed: broadcast::Sender<Message>,
+    pub tx_user_feed: broadcast::Sender<NewUserMessage>,
+    /// `None` on standby, as the leader tells the traders about a halt when they log in with it.
+    pub trading_halt: Option<TradingHalt>,
+    pub shutdown: Shutdown,
+}
+
+impl FromRef<Arc<AppState>> for WebsocketState {
+    fn from_ref(state: &Arc<AppState>) -> Self {
+        WebsocketState {
+            pool: state.pool.clone(),
+            settings: state.settings.clone(),
+            tx_price_feed: state.tx_price_feed.clone(),
+            tx_user_feed: state.tx_user_feed.clone(),
+            trading_halt: Some(state.trading_halt.clone()),
+            shutdown: state.shutdown.clone(),
+        }
+    }
+}
+
 // This function deals with a single websocket connection, i.e., a single
 // connected client / user, for which we will spawn two independent tasks (for
 // receiving / sending messages).
-pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
+pub async fn websocket_connection(stream: WebSocket, state: WebsocketState) {
+    // Everything happening on this connection is logged under the same trace ID. The trader is
+    // only known once they authenticated.
+    let span = tracing::info_span!(
+        "websocket",
+        trace_id = %trace::new_trace_id(),
+        trader_id = field::Empty,
+    );
+
     // By splitting, we can send and receive at the same time.
     let (mut sender, mut receiver) = stream.split();
 
@@ -32,8 +87,54 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
 
     let (local_sender, mut local_receiver) = mpsc::channel::<Message>(100);
 
-    let mut local_recv_task = tokio::spawn(async move {
-        while let Some(local_msg) = local_receiver.recv().await {
+    // The trader and the session under which their presence is recorded, once they authenticated.
+    let presence = Arc::new(Mutex::new(None::<(PublicKey, Uuid)>));
+    let pool = state.pool.clone();
+
+    let heartbeat_task = tokio::spawn({
+        let presence = presence.clone();
+        let pool = pool.clone();
+        async move {
+            loop {
+                tokio::time::sleep(user_presence::HEARTBEAT_INTERVAL).await;
+
+                let session = *presence.lock();
+                if let Some((trader_id, session_id)) = session {
+                    if let Err(e) = db::run(&pool, move |conn| {
+                        user_presence::seen(conn, trader_id, session_id)
+                    })
+                    .await
+                    {
+                        tracing::warn!(%trader_id, "Failed to update presence: {e:#}");
+                    }
+                }
+            }
+        }
+        .instrument(span.clone())
+    });
+
+    let shutdown = state.shutdown.clone();
+    let local_recv = async move {
+        loop {
+            let local_msg = tokio::select! {
+                local_msg = local_receiver.recv() => match local_msg {
+                    Some(local_msg) => local_msg,
+                    None => return,
+                },
+                _ = shutdown.triggered() => {
+                    let close = WebsocketMessage::Close(Some(CloseFrame {
+                        code: close_code::AWAY,
+                        reason: "Coordinator is shutting down".into(),
+                    }));
+                    if let Err(e) =
+                        tokio::time::timeout(WEBSOCKET_SEND_TIMEOUT, sender.send(close)).await
+                    {
+                        tracing::warn!("Could not close websocket: {e:#}");
+                    }
+                    return;
+                }
+            };
+
             match serde_json::to_string(&local_msg) {
                 Ok(msg) => {
                     if let Err(err) = tokio::time::timeout(
@@ -51,13 +152,14 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                 }
             }
         }
-    });
+    };
+    let mut local_recv_task = tokio::spawn(local_recv.instrument(span.clone()));
 
     // Spawn the first task that will receive broadcast messages and send
     // messages over the websocket to our client.
     let mut send_task = {
         let local_sender = local_sender.clone();
-        tokio::spawn(async move {
+        let send = async move {
             loop {
                 match price_feed.recv().await {
                     Ok(st) => {
@@ -75,38 +177,32 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                     ),
                 }
             }
-        })
+        };
+        tokio::spawn(send.instrument(span.clone()))
     };
 
     // Spawn a task that takes messages from the websocket
     let local_sender = local_sender.clone();
-    let mut recv_task = tokio::spawn(async move {
+    let recorded_presence = presence.clone();
+    let recv = async move {
         while let Some(Ok(WebsocketMessage::Text(text))) = receiver.next().await {
             match serde_json::from_str(text.as_str()) {
                 Ok(OrderbookRequest::LimitOrderFilledMatches { trader_id }) => {
-                    let mut conn = match state.pool.get() {
-                        Ok(conn) => conn,
+                    let matches = match db::run(&state.pool, move |conn| {
+                        Ok(orders::get_all_limit_order_filled_matches(conn, trader_id)?)
+                    })
+                    .await
+                    {
+                        Ok(matches) => matches,
                         Err(e) => {
                             tracing::error!(
                                 %trader_id,
-                                "Failed to get DB pool connection to get limit order filled matches: {e:#}"
+                                "Failed to get limit order filled matches from DB: {e:#}"
                             );
                             continue;
                         }
                     };
 
-                    let matches =
-                        match orders::get_all_limit_order_filled_matches(&mut conn, trader_id) {
-                            Ok(orders) => orders,
-                            Err(e) => {
-                                tracing::error!(
-                                    %trader_id,
-                                    "Failed to get limit order filled matches from DB: {e:#}"
-                                );
-                                continue;
-                            }
-                        };
-
                     if let Err(e) = local_sender
                         .send(Message::LimitOrderFilledMatches { trader_id, matches })
                         .await
@@ -120,23 +216,63 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                 Ok(OrderbookRequest::Authenticate {
                     fcm_token,
                     signature,
+                    device,
                 }) => {
                     let msg = create_sign_message(AUTH_SIGN_MESSAGE.to_vec());
                     let trader_id = signature.pubkey;
                     let signature = signature.signature;
 
-                    let mut conn = match state.pool.clone().get() {
-                        Ok(conn) => conn,
-                        Err(err) => {
-                            tracing::error!("Could not get connection to db pool {err:#}");
-                            return;
-                        }
-                    };
-
                     match signature.verify(&msg, &trader_id) {
                         Ok(_) => {
-                            let liquidity_options =
-                                db::liquidity_options::get_all(&mut conn).unwrap_or_default();
+                            Span::current().record("trader_id", field::display(trader_id));
+
+                            let supersedes_session = match device {
+                                Some(device) => {
+                                    match device_session::register(&state.pool, trader_id, device)
+                                        .await
+                                    {
+                                        Ok(SessionDecision::Continue) => false,
+                                        Ok(SessionDecision::Supersede) => {
+                                            tracing::warn!(
+                                                %trader_id,
+                                                "New device takes over the session"
+                                            );
+                                            true
+                                        }
+                                        Ok(SessionDecision::Reject) => {
+                                            tracing::warn!(
+                                                %trader_id,
+                                                "Rejecting device whose session has been superseded"
+                                            );
+                                            if let Err(e) =
+                                                local_sender.send(Message::SessionSuperseded).await
+                                            {
+                                                tracing::error!(%trader_id, "Failed to notify superseded device: {e:#}");
+                                                return;
+                                            }
+                                            continue;
+                                        }
+                                        Err(e) => {
+                                            tracing::error!(%trader_id, "Failed to register device session: {e:#}");
+                                            false
+                                        }
+                                    }
+                                }
+                                // Devices of older app versions do not identify themselves.
+                                None => false,
+                            };
+
+                            let liquidity_options = match db::run(&state.pool, |conn| {
+                                Ok(db::liquidity_options::get_all(conn)?)
+                            })
+                            .await
+                            {
+                                Ok(liquidity_options) => liquidity_options,
+                                Err(e) => {
+                                    tracing::error!(%trader_id, "Failed to load liquidity options: {e:#}");
+                                    vec![]
+                                }
+                            };
 
                             let contract_tx_fee_rate = {
                                 let settings = state.settings.read().await;
@@ -154,19 +290,55 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                                 return;
                             }
 
-                            let orders = orders::all_limit_orders(&mut conn).unwrap_or_default();
+                            let orders = match db::run(&state.pool, |conn| {
+                                Ok(orders::all_limit_orders(conn)?)
+                            })
+                            .await
+                            {
+                                Ok(orders) => orders,
+                                Err(e) => {
+                                    tracing::error!(%trader_id, "Failed to load limit orders: {e:#}");
+                                    vec![]
+                                }
+                            };
                             if let Err(e) = local_sender.send(Message::AllOrders(orders)).await {
                                 tracing::error!(%trader_id, "Failed to send all orders to user {e:#}");
                             }
 
+                            if let Some(reason) =
+                                state.trading_halt.as_ref().and_then(|halt| halt.get())
+                            {
+                                if let Err(e) = local_sender
+                                    .send(Message::TradingHalted(reason.to_string()))
+                                    .await
+                                {
+                                    tracing::error!(%trader_id, "Failed to inform user about trading halt {e:#}");
+                                }
+                            }
+
                             let token = fcm_token.unwrap_or("unavailable".to_string());
-                            if let Err(e) = user::login_user(&mut conn, trader_id, token) {
+                            if let Err(e) = db::run(&state.pool, move |conn| {
+                                user::login_user(conn, trader_id, token)
+                            })
+                            .await
+                            {
                                 tracing::error!(%trader_id, "Failed to update logged in user. Error: {e:#}")
                             }
 
+                            let session_id = Uuid::new_v4();
+                            if let Err(e) = db::run(&state.pool, move |conn| {
+                                user_presence::connected(conn, trader_id, session_id)
+                            })
+                            .await
+                            {
+                                tracing::error!(%trader_id, "Failed to record presence: {e:#}");
+                            }
+                            *presence.lock() = Some((trader_id, session_id));
+
                             let message = NewUserMessage {
                                 new_user: trader_id,
                                 sender: local_sender.clone(),
+                                supersedes_session,
                             };
                             tracing::debug!(%trader_id, "New login");
                             if let Err(e) = state.tx_user_feed.send(message) {
@@ -193,7 +365,8 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
                 }
             }
         }
-    });
+    };
+    let mut recv_task = tokio::spawn(recv.instrument(span));
 
     // If any one of the tasks run to completion, we abort the other.
     tokio::select! {
@@ -210,4 +383,16 @@ pub async fn websocket_connection(stream: WebSocket, state: Arc<AppState>) {
             send_task.abort();
         },
     };
+    heartbeat_task.abort();
+
+    let session = *recorded_presence.lock();
+    if let Some((trader_id, session_id)) = session {
+        if let Err(e) = db::run(&pool, move |conn| {
+            user_presence::disconnected(conn, trader_id, session_id)
+        })
+        .await
+        {
+            tracing::error!(%trader_id, "Failed to record disconnect: {e:#}");
+        }
+    }
 }
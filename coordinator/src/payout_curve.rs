@@ -1,7 +1,9 @@
+use anyhow::bail;
 use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::Amount;
+use commons::order_matching_fee;
 use dlc_manager::contract::numerical_descriptor::NumericalDescriptor;
 use dlc_manager::contract::ContractDescriptor;
 use dlc_manager::payout_curve::PayoutFunction;
@@ -10,11 +12,13 @@ use dlc_manager::payout_curve::PayoutPoint;
 use dlc_manager::payout_curve::PolynomialPayoutCurvePiece;
 use dlc_manager::payout_curve::RoundingInterval;
 use dlc_manager::payout_curve::RoundingIntervals;
-use payout_curve::ROUNDING_PERCENT;
+use payout_curve::PayoutCurveParams;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
+use serde::Serialize;
 use tracing::instrument;
 use trade::cfd::calculate_long_liquidation_price;
+use trade::cfd::calculate_margin;
 use trade::cfd::calculate_short_liquidation_price;
 use trade::cfd::BTCUSD_MAX_PRICE;
 use trade::ContractSymbol;
@@ -36,6 +40,7 @@ pub fn build_contract_descriptor(
     trader_collateral_reserve: u64,
     quantity: f32,
     symbol: ContractSymbol,
+    payout_curve_params: &PayoutCurveParams,
 ) -> Result<ContractDescriptor> {
     ensure!(
         symbol == ContractSymbol::BtcUsd,
@@ -55,6 +60,7 @@ pub fn build_contract_descriptor(
         trader_collateral_reserve,
         coordinator_direction,
         quantity,
+        payout_curve_params,
     )?;
 
     Ok(ContractDescriptor::Numerical(NumericalDescriptor {
@@ -68,6 +74,102 @@ pub fn build_contract_descriptor(
     }))
 }
 
+/// A range of prices for which the DLC pays out the same amounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PayoutRange {
+    /// The first price of the range.
+    pub start_price: u64,
+    /// The number of prices in the range.
+    pub count: u64,
+    pub coordinator_payout_sat: u64,
+    pub trader_payout_sat: u64,
+}
+
+/// The discretized payout curve of a DLC, as it would be used to build the CETs.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedPayoutCurve {
+    pub coordinator_margin_sat: u64,
+    pub trader_margin_sat: u64,
+    pub order_matching_fee_sat: u64,
+    pub total_collateral_sat: u64,
+    pub ranges: Vec<PayoutRange>,
+}
+
+/// Simulates the payout curve of the DLC the coordinator would propose for the given trade.
+///
+/// The parameters are from the trader's point of view and the contract is built exactly like
+/// when opening a position, so that the result can be compared with what the app computed.
+pub fn simulate_payout_curve(
+    initial_price: Decimal,
+    quantity: f32,
+    leverage_trader: f32,
+    leverage_coordinator: f32,
+    trader_direction: Direction,
+    symbol: ContractSymbol,
+    payout_curve_params: &PayoutCurveParams,
+    taker_fee_rate: Decimal,
+) -> Result<SimulatedPayoutCurve> {
+    let coordinator_margin = calculate_margin(initial_price, quantity, leverage_coordinator);
+    let trader_margin = calculate_margin(initial_price, quantity, leverage_trader);
+    let order_matching_fee = order_matching_fee(quantity, initial_price, taker_fee_rate).to_sat();
+
+    let contract_descriptor = build_contract_descriptor(
+        initial_price,
+        coordinator_margin,
+        trader_margin,
+        leverage_coordinator,
+        leverage_trader,
+        trader_direction.opposite(),
+        // The coordinator gets the `order_matching_fee` directly in the collateral reserve.
+        order_matching_fee,
+        0,
+        quantity,
+        symbol,
+        payout_curve_params,
+    )?;
+
+    // The trader brings the `order_matching_fee` on top of their margin.
+    let total_collateral = coordinator_margin + trader_margin + order_matching_fee;
+
+    let ranges = payout_ranges(&contract_descriptor, total_collateral)?;
+
+    Ok(SimulatedPayoutCurve {
+        coordinator_margin_sat: coordinator_margin,
+        trader_margin_sat: trader_margin,
+        order_matching_fee_sat: order_matching_fee,
+        total_collateral_sat: total_collateral,
+        ranges,
+    })
+}
+
+/// Discretizes the payout function of a contract descriptor built by the coordinator into the
+/// [`PayoutRange`]s which are used to build the CETs.
+pub fn payout_ranges(
+    contract_descriptor: &ContractDescriptor,
+    total_collateral: u64,
+) -> Result<Vec<PayoutRange>> {
+    let descriptor = match contract_descriptor {
+        ContractDescriptor::Numerical(descriptor) => descriptor,
+        ContractDescriptor::Enum(_) => bail!("Expected a numerical contract descriptor"),
+    };
+
+    let ranges = descriptor
+        .payout_function
+        .to_range_payouts(total_collateral, &descriptor.rounding_intervals)
+        .context("Could not discretize payout function")?
+        .into_iter()
+        .map(|range| PayoutRange {
+            start_price: range.start as u64,
+            count: range.count as u64,
+            // The coordinator is always offering.
+            coordinator_payout_sat: range.payout.offer,
+            trader_payout_sat: range.payout.accept,
+        })
+        .collect();
+
+    Ok(ranges)
+}
+
 /// Build a [`PayoutFunction`] for an inverse perpetual future e.g. BTCUSD. Perspective is always
 /// from the person who offers, i.e. in our case from the coordinator.
 ///
@@ -83,6 +185,7 @@ fn build_inverse_payout_function(
     trader_collateral_reserve: u64,
     coordinator_direction: Direction,
     quantity: f32,
+    payout_curve_params: &PayoutCurveParams,
 ) -> Result<(PayoutFunction, RoundingIntervals)> {
     let leverage_coordinator =
         Decimal::from_f32(leverage_coordinator).expect("to fit into decimal");
@@ -115,12 +218,13 @@ fn build_inverse_payout_function(
         Amount::from_sat(trader_collateral_reserve),
     );
 
-    let payout_points = payout_curve::build_inverse_payout_function(
+    let payout_points = payout_curve::build_inverse_payout_function_with_params(
         quantity,
         party_params_coordinator,
         party_params_trader,
         price_params,
         coordinator_direction,
+        payout_curve_params,
     )?;
 
     // The payout curve generation code tends to shift the liquidation prices slightly.
@@ -162,7 +266,8 @@ fn build_inverse_payout_function(
             total_margin,
             adjusted_long_liquidation_price,
             adjusted_short_liquidation_price,
-        )
+            payout_curve_params,
+        )?
     };
 
     Ok((payout_function, rounding_intervals))
@@ -192,12 +297,10 @@ pub fn create_rounding_intervals(
     total_margin: u64,
     long_liquidation_price: u64,
     short_liquidation_price: u64,
-) -> RoundingIntervals {
-    let liquidation_diff = short_liquidation_price
-        .checked_sub(long_liquidation_price)
-        .expect("short liquidation to be higher than long liquidation");
-    let low_price = long_liquidation_price + liquidation_diff / 10;
-    let high_price = short_liquidation_price - liquidation_diff / 10;
+    payout_curve_params: &PayoutCurveParams,
+) -> Result<RoundingIntervals> {
+    let (low_price, high_price) =
+        payout_curve_params.collar_prices(long_liquidation_price, short_liquidation_price)?;
 
     let mut intervals = vec![
         RoundingInterval {
@@ -209,11 +312,11 @@ pub fn create_rounding_intervals(
         // liquidation price _payout_.
         RoundingInterval {
             begin_interval: long_liquidation_price,
-            rounding_mod: (total_margin as f32 * ROUNDING_PERCENT * 0.1) as u64,
+            rounding_mod: payout_curve_params.collar_rounding(total_margin),
         },
         RoundingInterval {
             begin_interval: low_price,
-            rounding_mod: (total_margin as f32 * ROUNDING_PERCENT) as u64,
+            rounding_mod: payout_curve_params.rounding(total_margin),
         },
     ];
 
@@ -223,7 +326,7 @@ pub fn create_rounding_intervals(
             // short liquidation price _payout_.
             RoundingInterval {
                 begin_interval: high_price,
-                rounding_mod: (total_margin as f32 * ROUNDING_PERCENT * 0.1) as u64,
+                rounding_mod: payout_curve_params.collar_rounding(total_margin),
             },
         );
         intervals.push(RoundingInterval {
@@ -233,15 +336,15 @@ pub fn create_rounding_intervals(
         })
     }
 
-    RoundingIntervals { intervals }
+    Ok(RoundingIntervals { intervals })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use commons::default_taker_fee_rate;
     use commons::order_matching_fee_taker;
     use rust_decimal_macros::dec;
-    use trade::cfd::calculate_margin;
 
     #[test]
     fn payout_price_range_is_below_max_price() {
@@ -274,6 +377,7 @@ mod tests {
             trader_collateral_reserve,
             quantity,
             symbol,
+            &PayoutCurveParams::default(),
         )
         .unwrap();
 
@@ -362,7 +466,59 @@ mod tests {
             trader_collateral_reserve,
             quantity,
             symbol,
+            &PayoutCurveParams::default(),
         )
         .unwrap();
     }
+
+    #[test]
+    fn coarser_rounding_yields_fewer_payout_ranges() {
+        let simulate = |rounding_percent| {
+            simulate_payout_curve(
+                dec!(36404.5),
+                20.0,
+                3.0,
+                2.0,
+                Direction::Long,
+                ContractSymbol::BtcUsd,
+                &PayoutCurveParams {
+                    rounding_percent,
+                    ..PayoutCurveParams::default()
+                },
+                default_taker_fee_rate(),
+            )
+            .unwrap()
+        };
+
+        assert!(simulate(0.05).ranges.len() < simulate(0.01).ranges.len());
+    }
+
+    #[test]
+    fn simulated_payout_curve_distributes_total_collateral() {
+        let simulated = simulate_payout_curve(
+            dec!(36404.5),
+            20.0,
+            3.0,
+            2.0,
+            Direction::Long,
+            ContractSymbol::BtcUsd,
+            &PayoutCurveParams::default(),
+            default_taker_fee_rate(),
+        )
+        .unwrap();
+
+        assert!(!simulated.ranges.is_empty());
+        assert_eq!(
+            simulated.total_collateral_sat,
+            simulated.coordinator_margin_sat
+                + simulated.trader_margin_sat
+                + simulated.order_matching_fee_sat
+        );
+        for range in simulated.ranges {
+            assert_eq!(
+                range.coordinator_payout_sat + range.trader_payout_sat,
+                simulated.total_collateral_sat
+            );
+        }
+    }
 }
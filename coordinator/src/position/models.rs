@@ -6,8 +6,10 @@ use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::Address;
 use bitcoin::Amount;
-use commons::order_matching_fee_taker;
+use commons::order_matching_fee;
 use commons::TradeParams;
+use commons::TraderPosition;
+use commons::TraderPositionState;
 use dlc_manager::ContractId;
 use dlc_manager::DlcChannelId;
 use rust_decimal::prelude::Signed;
@@ -15,8 +17,7 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
 use trade::bitmex_client::Quote;
-use trade::cfd::calculate_margin;
-use trade::cfd::calculate_pnl;
+use trade::pricing;
 use trade::ContractSymbol;
 use trade::Direction;
 
@@ -100,9 +101,9 @@ pub struct Position {
 }
 
 impl Position {
-    // Returns true if the position is expired
-    pub fn is_expired(&self) -> bool {
-        OffsetDateTime::now_utc() >= self.expiry_timestamp
+    /// Returns true if the position is expired at `now`.
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        now >= self.expiry_timestamp
     }
 
     /// Calculates the profit and loss for the coordinator in satoshis
@@ -125,24 +126,72 @@ impl Position {
         // the position in the database is the trader's position, our direction is opposite
         let direction = self.direction.opposite();
 
-        let long_margin = calculate_margin(average_entry_price, self.quantity, long_leverage);
-        let short_margin = calculate_margin(average_entry_price, self.quantity, short_leverage);
-
-        let pnl = calculate_pnl(
+        let pnl = pricing::unrealized_pnl(
             average_entry_price,
             closing_price,
             self.quantity,
             direction,
-            long_margin,
-            short_margin,
+            long_leverage,
+            short_leverage,
         )
         .context("Failed to calculate pnl for position")?;
 
         Ok(pnl)
     }
 
+    /// The trader's view of the position, if it is still open.
+    ///
+    /// The unrealized PnL is only set if a `quote` is provided.
+    pub fn to_trader_position(&self, quote: Option<Quote>) -> Option<TraderPosition> {
+        let state = match self.position_state {
+            PositionState::Open => TraderPositionState::Open,
+            PositionState::Closing { .. } => TraderPositionState::Closing,
+            PositionState::Rollover => TraderPositionState::Rollover,
+            PositionState::Resizing | PositionState::ResizeOpeningSubchannelProposed => {
+                TraderPositionState::Resizing
+            }
+            PositionState::Proposed | PositionState::Closed { .. } | PositionState::Failed => {
+                return None
+            }
+        };
+
+        let unrealized_pnl_sat = quote.and_then(|quote| {
+            // The DLC is zero-sum, hence the trader's PnL is the opposite of ours.
+            self.calculate_coordinator_pnl(quote)
+                .map(|pnl| -pnl)
+                .map_err(|e| {
+                    tracing::warn!(
+                        position_id = self.id,
+                        "Failed to calculate trader pnl: {e:#}"
+                    )
+                })
+                .ok()
+        });
+
+        Some(TraderPosition {
+            id: self.id,
+            contract_symbol: self.contract_symbol,
+            direction: self.direction,
+            quantity: self.quantity,
+            leverage: self.trader_leverage,
+            average_entry_price: self.average_entry_price,
+            liquidation_price: self.liquidation_price,
+            state,
+            collateral: self.trader_margin.max(0) as u64,
+            expiry_timestamp: self.expiry_timestamp,
+            unrealized_pnl_sat,
+            stable: self.stable,
+        })
+    }
+
     /// Calculate the settlement amount for the coordinator when closing the _entire_ position.
-    pub fn calculate_coordinator_settlement_amount(&self, closing_price: Decimal) -> Result<u64> {
+    ///
+    /// The trader pays the order-matching fee at `taker_fee_rate` for closing the position.
+    pub fn calculate_coordinator_settlement_amount(
+        &self,
+        closing_price: Decimal,
+        taker_fee_rate: Decimal,
+    ) -> Result<u64> {
         let opening_price = Decimal::try_from(self.average_entry_price)?;
 
         let leverage_long = leverage_long(
@@ -164,6 +213,7 @@ impl Position {
             leverage_long,
             leverage_short,
             coordinator_direction,
+            taker_fee_rate,
         )
     }
 
@@ -195,20 +245,22 @@ fn calculate_coordinator_settlement_amount(
     long_leverage: f32,
     short_leverage: f32,
     coordinator_direction: Direction,
+    taker_fee_rate: Decimal,
 ) -> Result<u64> {
-    let close_position_fee = order_matching_fee_taker(quantity, closing_price).to_sat() as i64;
+    let close_position_fee =
+        order_matching_fee(quantity, closing_price, taker_fee_rate).to_sat() as i64;
 
-    let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-    let short_margin = calculate_margin(opening_price, quantity, short_leverage);
+    let long_margin = pricing::margin(opening_price, quantity, long_leverage);
+    let short_margin = pricing::margin(opening_price, quantity, short_leverage);
     let total_margin = long_margin + short_margin;
 
-    let pnl = calculate_pnl(
+    let pnl = pricing::unrealized_pnl(
         opening_price,
         closing_price,
         quantity,
         coordinator_direction,
-        long_margin,
-        short_margin,
+        long_leverage,
+        short_leverage,
     )?;
 
     let coordinator_margin = match coordinator_direction {
@@ -218,7 +270,7 @@ fn calculate_coordinator_settlement_amount(
 
     let coordinator_settlement_amount = Decimal::from(coordinator_margin) + Decimal::from(pnl);
 
-    // Double-checking that the coordinator's payout isn't negative, although `calculate_pnl` should
+    // Double-checking that the coordinator's payout isn't negative, although `unrealized_pnl` should
     // guarantee this.
     let coordinator_settlement_amount = coordinator_settlement_amount.max(Decimal::ZERO);
 
@@ -284,7 +336,7 @@ fn calculate_accept_settlement_amount_partial_close(
         position_coordinator_leverage,
     );
 
-    let position_trader_margin = calculate_margin(
+    let position_trader_margin = pricing::margin(
         decimal_from_f32(position_average_execution_price),
         position_quantity,
         position_trader_leverage,
@@ -301,16 +353,13 @@ fn calculate_accept_settlement_amount_partial_close(
 
         let opening_price = decimal_from_f32(position_average_execution_price);
 
-        let long_margin = calculate_margin(opening_price, settled_contracts, leverage_long);
-        let short_margin = calculate_margin(opening_price, settled_contracts, leverage_short);
-
-        let pnl = calculate_pnl(
+        let pnl = pricing::unrealized_pnl(
             opening_price,
             trade_average_execution_price,
             settled_contracts,
             position_direction,
-            long_margin,
-            short_margin,
+            leverage_long,
+            leverage_short,
         )?;
 
         ((position_trader_margin as i64) + pnl).max(0) as u64
@@ -324,16 +373,13 @@ fn calculate_accept_settlement_amount_partial_close(
 
         let opening_price = decimal_from_f32(position_average_execution_price);
 
-        let long_margin = calculate_margin(opening_price, settled_contracts, leverage_long);
-        let short_margin = calculate_margin(opening_price, settled_contracts, leverage_short);
-
-        let pnl = calculate_pnl(
+        let pnl = pricing::unrealized_pnl(
             opening_price,
             trade_average_execution_price,
             settled_contracts,
             position_direction,
-            long_margin,
-            short_margin,
+            leverage_long,
+            leverage_short,
         )?;
 
         ((position_trader_margin as i64) + pnl).max(0) as u64
@@ -435,6 +481,7 @@ impl std::fmt::Debug for Position {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use commons::default_taker_fee_rate;
     use rust_decimal_macros::dec;
     use std::str::FromStr;
 
@@ -465,7 +512,7 @@ mod tests {
         };
 
         let coordinator_settlement_amount = position
-            .calculate_coordinator_settlement_amount(dec!(39_000))
+            .calculate_coordinator_settlement_amount(dec!(39_000), default_taker_fee_rate())
             .unwrap();
 
         assert_eq!(coordinator_settlement_amount, 132_179);
@@ -498,7 +545,7 @@ mod tests {
         };
 
         let coordinator_settlement_amount = position
-            .calculate_coordinator_settlement_amount(dec!(39_000))
+            .calculate_coordinator_settlement_amount(dec!(39_000), default_taker_fee_rate())
             .unwrap();
 
         assert_eq!(coordinator_settlement_amount, 132_179);
@@ -531,7 +578,7 @@ mod tests {
         };
 
         let coordinator_settlement_amount = position
-            .calculate_coordinator_settlement_amount(dec!(39_000))
+            .calculate_coordinator_settlement_amount(dec!(39_000), default_taker_fee_rate())
             .unwrap();
 
         assert_eq!(coordinator_settlement_amount, 90_512);
@@ -548,7 +595,7 @@ mod tests {
         let opening_price = Decimal::from(22000);
         let closing_price = Decimal::from(23000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -557,6 +604,7 @@ mod tests {
             leverage_coordinator,
             1.0,
             Direction::Long,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -572,7 +620,7 @@ mod tests {
         let opening_price = Decimal::from(22000);
         let closing_price = Decimal::from(23000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -581,6 +629,7 @@ mod tests {
             1.0,
             leverage_coordinator,
             Direction::Short,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -596,7 +645,7 @@ mod tests {
         let opening_price = Decimal::from(23000);
         let closing_price = Decimal::from(22000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -605,6 +654,7 @@ mod tests {
             leverage_coordinator,
             1.0,
             Direction::Long,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -620,7 +670,7 @@ mod tests {
         let opening_price = Decimal::from(23000);
         let closing_price = Decimal::from(22000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -629,6 +679,7 @@ mod tests {
             1.0,
             leverage_coordinator,
             Direction::Short,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -644,7 +695,7 @@ mod tests {
         let opening_price = Decimal::from(22000);
         let closing_price = Decimal::from(23000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -653,6 +704,7 @@ mod tests {
             leverage_coordinator,
             2.0,
             Direction::Long,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -668,7 +720,7 @@ mod tests {
         let opening_price = Decimal::from(22000);
         let closing_price = Decimal::from(23000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -677,6 +729,7 @@ mod tests {
             2.0,
             leverage_coordinator,
             Direction::Short,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -692,7 +745,7 @@ mod tests {
         let opening_price = Decimal::from(23000);
         let closing_price = Decimal::from(22000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -701,6 +754,7 @@ mod tests {
             leverage_coordinator,
             1.0,
             Direction::Long,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
@@ -716,7 +770,7 @@ mod tests {
         let opening_price = Decimal::from(23000);
         let closing_price = Decimal::from(22000);
 
-        let margin_coordinator = calculate_margin(opening_price, quantity, leverage_coordinator);
+        let margin_coordinator = pricing::margin(opening_price, quantity, leverage_coordinator);
 
         let settlement_coordinator = calculate_coordinator_settlement_amount(
             opening_price,
@@ -725,6 +779,7 @@ mod tests {
             1.0,
             leverage_coordinator,
             Direction::Short,
+            default_taker_fee_rate(),
         )
         .unwrap();
 
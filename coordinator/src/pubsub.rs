@@ -0,0 +1,621 @@
+//! Serving the orderbook websocket from the coordinators on standby, see [`crate::standby`].
+//!
+//! The price feed and the messages to traders are produced by the leader only. To let traders
+//! connect to any coordinator, the leader publishes them through Postgres `NOTIFY`, and the
+//! coordinators on standby relay them to the traders connected to them:
+//!
+//! - The leader publishes every message of its price feed on [`PRICE_FEED_CHANNEL`].
+//! - A coordinator on standby announces every trader logging in with it on
+//!   [`TRADER_SESSIONS_CHANNEL`]. The leader logs the trader in as if they were connected to it,
+//!   with a session which publishes the messages to the trader on [`TRADER_MESSAGES_CHANNEL`].
+//! - A coordinator on standby re-announces all of its traders every
+//!   [`SESSION_HEARTBEAT_INTERVAL`]. The leader logs in the traders it does not know yet, and drops
+//!   the sessions which have not been announced for [`SESSION_TIMEOUT`], e.g. because the
+//!   coordinator on standby serving them went away.
+//! - The leader asks the coordinators on standby to re-announce their traders right away on
+//!   [`LEADER_CHANNEL`] whenever it starts listening, e.g. after it took over from the previous
+//!   leader.
+//!
+//! Notifications are not persisted, hence messages published while a coordinator is not listening,
+//! e.g. while it reconnects to the database, are lost. Traders who miss messages to them are
+//! notified by push notification, as if they were not connected.
+
+use crate::db;
+use crate::message::NewUserMessage;
+use crate::orderbook::trading_halt::TradingHalt;
+use crate::shutdown::Shutdown;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::Message;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::sql_types::Text;
+use diesel::PgConnection;
+use diesel::RunQueryDsl;
+use futures::future::RemoteHandle;
+use futures::FutureExt;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_postgres::AsyncMessage;
+use tokio_postgres::NoTls;
+use uuid::Uuid;
+
+pub const PRICE_FEED_CHANNEL: &str = "coordinator_price_feed";
+pub const TRADER_SESSIONS_CHANNEL: &str = "coordinator_trader_sessions";
+pub const TRADER_MESSAGES_CHANNEL: &str = "coordinator_trader_messages";
+pub const LEADER_CHANNEL: &str = "coordinator_leader";
+
+/// Postgres rejects notifications with a payload of 8000 bytes or more.
+const MAX_PAYLOAD_LEN: usize = 7999;
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many messages to a trader connected to another coordinator are buffered.
+const SESSION_BUFFER_SIZE: usize = 100;
+
+/// How many notifications are buffered until they are handled.
+const NOTIFICATION_BUFFER_SIZE: usize = 100;
+
+/// How often a coordinator on standby re-announces the traders connected to it.
+const SESSION_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A session of a trader connected to a coordinator on standby, which has not been announced for
+/// longer, is dropped by the leader.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(3 * 30);
+
+/// How many sessions are announced per notification, to stay below [`MAX_PAYLOAD_LEN`].
+const SESSIONS_PER_ANNOUNCEMENT: usize = 50;
+
+/// A trader logged in with, or disconnected from, a coordinator on standby.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SessionEvent {
+    Connected {
+        trader_id: PublicKey,
+        session_id: Uuid,
+        supersedes_session: bool,
+    },
+    Disconnected {
+        trader_id: PublicKey,
+        session_id: Uuid,
+    },
+    /// The traders which are still connected to a coordinator on standby.
+    Announced { sessions: Vec<(PublicKey, Uuid)> },
+}
+
+/// What a [`listen`]er yields.
+#[derive(Debug)]
+pub enum Notification {
+    /// We (re)started listening, hence notifications may have been missed before.
+    Listening,
+    Received {
+        channel: String,
+        payload: String,
+    },
+}
+
+/// A message from the leader to a trader connected to a coordinator on standby.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraderMessage {
+    pub session_id: Uuid,
+    pub message: Message,
+}
+
+/// Publish `payload` as JSON on `channel`.
+pub async fn publish<T: Serialize>(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+    channel: &'static str,
+    payload: &T,
+) -> Result<()> {
+    let payload = serde_json::to_string(payload)?;
+    if payload.len() > MAX_PAYLOAD_LEN {
+        bail!(
+            "Payload of {} bytes is too large to be published on {channel}",
+            payload.len()
+        );
+    }
+
+    db::run(pool, move |conn| {
+        diesel::sql_query("SELECT pg_notify($1, $2)")
+            .bind::<Text, _>(channel)
+            .bind::<Text, _>(payload)
+            .execute(conn)?;
+        Ok(())
+    })
+    .await
+}
+
+/// Listen on `channels`, yielding the channel and the payload of every notification.
+///
+/// The listener reconnects if the connection to the database is lost, and stops once the receiver
+/// is dropped. Every time it started listening it yields [`Notification::Listening`].
+///
+/// The connection does not use TLS, as the database is expected to be reachable in the private
+/// network of the coordinators only.
+pub fn listen(database_url: String, channels: &[&'static str]) -> mpsc::Receiver<Notification> {
+    let (sender, receiver) = mpsc::channel(NOTIFICATION_BUFFER_SIZE);
+    let channels = channels.to_vec();
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = listen_until_disconnected(&database_url, &channels, &sender).await {
+                tracing::warn!(?channels, "Stopped listening for notifications: {e:#}");
+            }
+
+            if sender.is_closed() {
+                return;
+            }
+
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+        }
+    });
+
+    receiver
+}
+
+async fn listen_until_disconnected(
+    database_url: &str,
+    channels: &[&'static str],
+    sender: &mpsc::Sender<Notification>,
+) -> Result<()> {
+    let (client, mut connection) = tokio_postgres::connect(database_url, NoTls)
+        .await
+        .context("Failed to connect to database")?;
+
+    // The connection has to be polled for the client to make progress, and yields the
+    // notifications.
+    let mut messages = futures::stream::poll_fn(move |cx| connection.poll_message(cx));
+    let listen = async {
+        for channel in channels {
+            client.batch_execute(&format!("LISTEN {channel}")).await?;
+        }
+        anyhow::Ok(())
+    };
+
+    tokio::pin!(listen);
+    loop {
+        tokio::select! {
+            result = &mut listen => {
+                result.context("Failed to listen")?;
+                tracing::debug!(?channels, "Listening for notifications");
+                if sender.send(Notification::Listening).await.is_err() {
+                    return Ok(());
+                }
+                break;
+            }
+            message = messages.next() => match message {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => bail!("Connection failed: {e:#}"),
+                None => bail!("Connection closed"),
+            }
+        }
+    }
+
+    while let Some(message) = messages.next().await {
+        if let AsyncMessage::Notification(notification) = message? {
+            let notification = Notification::Received {
+                channel: notification.channel().to_string(),
+                payload: notification.payload().to_string(),
+            };
+            if sender.send(notification).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    bail!("Connection closed")
+}
+
+/// Publish the price feed of the leader, so that the coordinators on standby can relay it.
+pub fn publish_price_feed(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    tx_price_feed: &broadcast::Sender<Message>,
+) -> RemoteHandle<()> {
+    let mut price_feed = tx_price_feed.subscribe();
+
+    let (fut, remote_handle) = async move {
+        loop {
+            match price_feed.recv().await {
+                Ok(message) => {
+                    if let Err(e) = publish(&pool, PRICE_FEED_CHANNEL, &message).await {
+                        tracing::error!(%message, "Failed to publish price feed: {e:#}");
+                    }
+                }
+                Err(RecvError::Closed) => {
+                    tracing::error!("Price feed sender died! Channel closed.");
+                    break;
+                }
+                Err(RecvError::Lagged(skip)) => {
+                    tracing::warn!(%skip, "Lagging behind on publishing price feed")
+                }
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+/// Log in the traders connected to the coordinators on standby with the leader.
+///
+/// Each of them gets a session like the traders connected to the leader, whose messages are
+/// published to the coordinator the trader is connected to. As the trader may have missed a halt of
+/// trading, they are told about it when they log in.
+pub fn serve_remote_sessions(
+    database_url: String,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    tx_user_feed: broadcast::Sender<NewUserMessage>,
+    trading_halt: TradingHalt,
+) -> RemoteHandle<()> {
+    let mut notifications = listen(database_url, &[TRADER_SESSIONS_CHANNEL]);
+
+    let (fut, remote_handle) = async move {
+        let mut sessions: HashMap<Uuid, RemoteSession> = HashMap::new();
+        let mut expiry = tokio::time::interval(SESSION_HEARTBEAT_INTERVAL);
+
+        loop {
+            let payload = tokio::select! {
+                notification = notifications.recv() => match notification {
+                    Some(Notification::Received { payload, .. }) => payload,
+                    Some(Notification::Listening) => {
+                        // We may have missed traders logging in, e.g. with the previous leader.
+                        if let Err(e) = publish(&pool, LEADER_CHANNEL, &()).await {
+                            tracing::warn!("Failed to ask for the traders on standby: {e:#}");
+                        }
+                        continue;
+                    }
+                    None => break,
+                },
+                _ = expiry.tick() => {
+                    sessions.retain(|session_id, session| {
+                        let alive = session.last_seen.elapsed() <= SESSION_TIMEOUT;
+                        if !alive {
+                            tracing::debug!(
+                                trader_id = %session.trader_id,
+                                %session_id,
+                                "Trader on standby has not been announced in time"
+                            );
+                        }
+                        alive
+                    });
+                    continue;
+                }
+            };
+
+            let event = match parse::<SessionEvent>(&payload) {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::error!("Invalid session event: {e:#}");
+                    continue;
+                }
+            };
+
+            match event {
+                SessionEvent::Connected {
+                    trader_id,
+                    session_id,
+                    supersedes_session,
+                } => {
+                    tracing::debug!(%trader_id, %session_id, "Trader logged in with standby");
+
+                    let session = log_in(
+                        &pool,
+                        &tx_user_feed,
+                        &trading_halt,
+                        trader_id,
+                        session_id,
+                        supersedes_session,
+                    )
+                    .await;
+                    sessions.insert(session_id, session);
+                }
+                SessionEvent::Disconnected {
+                    trader_id,
+                    session_id,
+                } => {
+                    tracing::debug!(%trader_id, %session_id, "Trader disconnected from standby");
+                    sessions.remove(&session_id);
+                }
+                SessionEvent::Announced {
+                    sessions: announced,
+                } => {
+                    for (trader_id, session_id) in announced {
+                        if let Some(session) = sessions.get_mut(&session_id) {
+                            session.last_seen = Instant::now();
+                            continue;
+                        }
+
+                        tracing::debug!(%trader_id, %session_id, "Trader announced by standby");
+
+                        let session = log_in(
+                            &pool,
+                            &tx_user_feed,
+                            &trading_halt,
+                            trader_id,
+                            session_id,
+                            false,
+                        )
+                        .await;
+                        sessions.insert(session_id, session);
+                    }
+                }
+            }
+        }
+
+        tracing::error!("Stopped serving remote sessions");
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+/// A trader connected to a coordinator on standby, as seen by the leader.
+struct RemoteSession {
+    trader_id: PublicKey,
+    last_seen: Instant,
+    /// Dropping it stops publishing the messages of the session, upon which the trader is
+    /// considered disconnected.
+    #[allow(dead_code)]
+    publisher: RemoteHandle<()>,
+}
+
+async fn log_in(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+    tx_user_feed: &broadcast::Sender<NewUserMessage>,
+    trading_halt: &TradingHalt,
+    trader_id: PublicKey,
+    session_id: Uuid,
+    supersedes_session: bool,
+) -> RemoteSession {
+    let (sender, receiver) = mpsc::channel(SESSION_BUFFER_SIZE);
+    let publisher = publish_session_messages(pool.clone(), session_id, receiver);
+
+    if let Some(reason) = trading_halt.get() {
+        let _ = sender
+            .send(Message::TradingHalted(reason.to_string()))
+            .await;
+    }
+
+    let message = NewUserMessage {
+        new_user: trader_id,
+        sender,
+        supersedes_session,
+    };
+    if let Err(e) = tx_user_feed.send(message) {
+        tracing::error!(%trader_id, "Could not send new user message. Error: {e:#}");
+    }
+
+    RemoteSession {
+        trader_id,
+        last_seen: Instant::now(),
+        publisher,
+    }
+}
+
+fn publish_session_messages(
+    pool: Pool<ConnectionManager<PgConnection>>,
+    session_id: Uuid,
+    mut receiver: mpsc::Receiver<Message>,
+) -> RemoteHandle<()> {
+    let (fut, remote_handle) = async move {
+        while let Some(message) = receiver.recv().await {
+            let message = TraderMessage {
+                session_id,
+                message,
+            };
+            if let Err(e) = publish(&pool, TRADER_MESSAGES_CHANNEL, &message).await {
+                tracing::error!(%session_id, "Failed to publish trader message: {e:#}");
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+/// Relay the price feed and the messages of the leader to the traders connected to us on standby,
+/// and log them in with the leader, until `stop` is triggered.
+pub fn relay_from_leader(
+    database_url: String,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    tx_price_feed: broadcast::Sender<Message>,
+    tx_user_feed: &broadcast::Sender<NewUserMessage>,
+    stop: Shutdown,
+) -> RemoteHandle<()> {
+    let mut user_feed = tx_user_feed.subscribe();
+    let mut notifications = listen(
+        database_url,
+        &[PRICE_FEED_CHANNEL, TRADER_MESSAGES_CHANNEL, LEADER_CHANNEL],
+    );
+
+    let (fut, remote_handle) = async move {
+        let mut sessions: HashMap<Uuid, (PublicKey, mpsc::Sender<Message>)> = HashMap::new();
+        let (closed_tx, mut closed_rx) = mpsc::channel::<Uuid>(SESSION_BUFFER_SIZE);
+        let mut heartbeat = tokio::time::interval(SESSION_HEARTBEAT_INTERVAL);
+
+        loop {
+            tokio::select! {
+                new_user = user_feed.recv() => {
+                    let new_user = match new_user {
+                        Ok(new_user) => new_user,
+                        Err(RecvError::Closed) => return,
+                        Err(RecvError::Lagged(skip)) => {
+                            tracing::warn!(%skip, "Lagging behind on new user message");
+                            continue;
+                        }
+                    };
+
+                    let trader_id = new_user.new_user;
+                    let session_id = Uuid::new_v4();
+                    sessions.insert(session_id, (trader_id, new_user.sender.clone()));
+
+                    // Closing the websocket drops the receiver of the session.
+                    tokio::spawn({
+                        let sender = new_user.sender;
+                        let closed_tx = closed_tx.clone();
+                        async move {
+                            sender.closed().await;
+                            let _ = closed_tx.send(session_id).await;
+                        }
+                    });
+
+                    let event = SessionEvent::Connected {
+                        trader_id,
+                        session_id,
+                        supersedes_session: new_user.supersedes_session,
+                    };
+                    if let Err(e) = publish(&pool, TRADER_SESSIONS_CHANNEL, &event).await {
+                        tracing::error!(%trader_id, "Failed to log in trader with leader: {e:#}");
+                    }
+                }
+                Some(session_id) = closed_rx.recv() => {
+                    if let Some((trader_id, _)) = sessions.remove(&session_id) {
+                        let event = SessionEvent::Disconnected { trader_id, session_id };
+                        if let Err(e) = publish(&pool, TRADER_SESSIONS_CHANNEL, &event).await {
+                            tracing::warn!(%trader_id, "Failed to tell leader about disconnect: {e:#}");
+                        }
+                    }
+                }
+                notification = notifications.recv() => {
+                    let (channel, payload) = match notification {
+                        Some(Notification::Received { channel, payload }) => (channel, payload),
+                        // The leader may have dropped our traders while we were not listening.
+                        Some(Notification::Listening) => {
+                            announce_sessions(&pool, &sessions).await;
+                            continue;
+                        }
+                        None => return,
+                    };
+
+                    if channel == LEADER_CHANNEL {
+                        tracing::debug!("Announcing our traders to the leader");
+                        announce_sessions(&pool, &sessions).await;
+                        continue;
+                    }
+
+                    if let Err(e) = relay(&channel, &payload, &tx_price_feed, &sessions).await {
+                        tracing::error!(channel, "Failed to relay notification: {e:#}");
+                    }
+                }
+                _ = heartbeat.tick() => announce_sessions(&pool, &sessions).await,
+                _ = stop.triggered() => return,
+            }
+        }
+    }
+    .remote_handle();
+
+    tokio::spawn(fut);
+
+    remote_handle
+}
+
+/// Announce the traders connected to us to the leader, which logs in those it does not know yet.
+async fn announce_sessions(
+    pool: &Pool<ConnectionManager<PgConnection>>,
+    sessions: &HashMap<Uuid, (PublicKey, mpsc::Sender<Message>)>,
+) {
+    let sessions = sessions
+        .iter()
+        .map(|(session_id, (trader_id, _))| (*trader_id, *session_id))
+        .collect::<Vec<_>>();
+
+    for sessions in sessions.chunks(SESSIONS_PER_ANNOUNCEMENT) {
+        let event = SessionEvent::Announced {
+            sessions: sessions.to_vec(),
+        };
+        if let Err(e) = publish(pool, TRADER_SESSIONS_CHANNEL, &event).await {
+            tracing::warn!("Failed to announce traders to leader: {e:#}");
+        }
+    }
+}
+
+async fn relay(
+    channel: &str,
+    payload: &str,
+    tx_price_feed: &broadcast::Sender<Message>,
+    sessions: &HashMap<Uuid, (PublicKey, mpsc::Sender<Message>)>,
+) -> Result<()> {
+    match channel {
+        PRICE_FEED_CHANNEL => {
+            // Sending only fails if no trader is connected at the moment.
+            let _ = tx_price_feed.send(parse::<Message>(payload)?);
+        }
+        TRADER_MESSAGES_CHANNEL => {
+            let TraderMessage {
+                session_id,
+                message,
+            } = parse(payload)?;
+
+            // The session may be served by another coordinator on standby.
+            if let Some((trader_id, sender)) = sessions.get(&session_id) {
+                if let Err(e) = sender.send(message).await {
+                    tracing::warn!(%trader_id, "Connection lost to trader: {e:#}");
+                }
+            }
+        }
+        _ => bail!("Unexpected notification"),
+    }
+
+    Ok(())
+}
+
+fn parse<T: DeserializeOwned>(payload: &str) -> Result<T> {
+    serde_json::from_str(payload).context("Failed to parse notification")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn session_event_round_trips() {
+        let trader_id = PublicKey::from_str(
+            "02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a",
+        )
+        .unwrap();
+        let session_id = Uuid::new_v4();
+
+        let event = SessionEvent::Connected {
+            trader_id,
+            session_id,
+            supersedes_session: true,
+        };
+        let event = parse::<SessionEvent>(&serde_json::to_string(&event).unwrap()).unwrap();
+
+        assert!(matches!(
+            event,
+            SessionEvent::Connected { trader_id: t, session_id: s, supersedes_session: true }
+                if t == trader_id && s == session_id
+        ));
+    }
+
+    #[test]
+    fn announced_sessions_fit_into_a_notification() {
+        let trader_id = PublicKey::from_str(
+            "02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a",
+        )
+        .unwrap();
+
+        let event = SessionEvent::Announced {
+            sessions: vec![(trader_id, Uuid::new_v4()); SESSIONS_PER_ANNOUNCEMENT],
+        };
+
+        assert!(serde_json::to_string(&event).unwrap().len() <= MAX_PAYLOAD_LEN);
+    }
+}
@@ -1,41 +1,88 @@
+use crate::admin::advance_clock;
+use crate::admin::authenticate;
+use crate::admin::broadcast_psbt;
+use crate::admin::bump_fee;
 use crate::admin::close_channel;
 use crate::admin::collaborative_revert;
+use crate::admin::collaborative_revert_batch;
 use crate::admin::connect_to_peer;
+use crate::admin::create_psbt;
+use crate::admin::delete_user_backup;
+use crate::admin::expire_position;
+use crate::admin::freeze_utxo;
 use crate::admin::get_balance;
+use crate::admin::get_clock;
+use crate::admin::get_trading_halt;
+use crate::admin::get_user_statement;
 use crate::admin::get_utxos;
+use crate::admin::halt_trading;
 use crate::admin::is_connected;
+use crate::admin::list_attestations;
 use crate::admin::list_channels;
 use crate::admin::list_dlc_channels;
+use crate::admin::list_failed_trade_executions;
 use crate::admin::list_on_chain_transactions;
+use crate::admin::list_online_users;
 use crate::admin::list_peers;
+use crate::admin::list_rollovers;
+use crate::admin::list_user_backup_keys;
 use crate::admin::open_channel;
+use crate::admin::replay_trade;
+use crate::admin::resume_trading;
+use crate::admin::send_on_chain;
 use crate::admin::send_payment;
 use crate::admin::sign_message;
-use crate::backup::SledBackup;
+use crate::admin::simulate_payout;
+use crate::admin::unfreeze_utxo;
+use crate::admin::withdraw_batch;
+use crate::api_key;
+use crate::api_key::delete_api_key;
+use crate::api_key::post_api_key;
+use crate::api_version;
+use crate::backup::UserBackupStore;
 use crate::collaborative_revert::confirm_collaborative_revert;
 use crate::db;
 use crate::db::liquidity::LiquidityRequestLog;
 use crate::db::user;
+use crate::fee_estimates::FeeEstimatesProvider;
+use crate::health::Health;
+use crate::health::OverallCoordinatorHealth;
 use crate::is_liquidity_sufficient;
 use crate::message::NewUserMessage;
 use crate::message::OrderbookMessage;
+use crate::node::rollover_scheduler::RolloverScheduler;
 use crate::node::Node;
+use crate::node::TradeOutcome;
+use crate::openapi;
+use crate::openapi::ErrorResponse;
+use crate::orderbook::maker_notifications::MakerNotifier;
 use crate::orderbook::routes::get_order;
 use crate::orderbook::routes::get_orders;
 use crate::orderbook::routes::post_order;
+use crate::orderbook::routes::post_rollback_match;
 use crate::orderbook::routes::put_order;
+use crate::orderbook::routes::submit_order;
 use crate::orderbook::routes::websocket_handler;
-use crate::orderbook::trading::NewOrderMessage;
+use crate::orderbook::trading::TradingMessage;
+use crate::orderbook::trading_halt::TradingHalt;
 use crate::parse_dlc_channel_id;
+use crate::position::models::PositionState;
 use crate::settings::Settings;
 use crate::settings::SettingsFile;
+use crate::shutdown::Shutdown;
+use crate::trace;
 use crate::AppError;
 use axum::extract::DefaultBodyLimit;
+use axum::extract::FromRef;
 use axum::extract::Path;
 use axum::extract::Query;
 use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::HeaderMap;
 use axum::http::StatusCode;
+use axum::middleware;
 use axum::response::IntoResponse;
+use axum::response::Response;
 use axum::routing::delete;
 use axum::routing::get;
 use axum::routing::post;
@@ -45,15 +92,35 @@ use bitcoin::consensus::encode::serialize_hex;
 use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::PublicKey;
+use commons::AdjustableClock;
+use commons::ApiKeyScope;
+use commons::ApiVersion;
+use commons::AppConfig;
 use commons::Backup;
 use commons::CollaborativeRevertTraderResponse;
 use commons::DeleteBackup;
+use commons::ErrorReports;
+use commons::FeeEstimates;
+use commons::ForceCloseChannel;
+use commons::JitChannelConfig;
+use commons::MarketStats;
 use commons::Message;
+use commons::NewOrder;
+use commons::NodeInfo;
 use commons::OnboardingParam;
+use commons::Order;
+use commons::OrderType;
+use commons::RecoverableChannel;
+use commons::RecoverableChannelKind;
+use commons::RecoveryInfo;
 use commons::RegisterParams;
 use commons::Restore;
 use commons::RouteHintHop;
 use commons::TradeParams;
+use commons::TraderPosition;
+use commons::VersionInfo;
+use commons::MAX_ERROR_REPORTS;
+use commons::MAX_ERROR_REPORT_MESSAGE_LEN;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::PgConnection;
@@ -61,10 +128,10 @@ use dlc_manager::DlcChannelId;
 use hex::FromHex;
 use lightning::ln::msgs::SocketAddress;
 use ln_dlc_node::channel::UserChannelId;
+use ln_dlc_node::ln::calculate_channel_value;
 use ln_dlc_node::node::peer_manager::alias_as_bytes;
 use ln_dlc_node::node::peer_manager::broadcast_node_announcement;
 use ln_dlc_node::node::LiquidityRequest;
-use ln_dlc_node::node::NodeInfo;
 use opentelemetry_prometheus::PrometheusExporter;
 use prometheus::Encoder;
 use prometheus::TextEncoder;
@@ -74,105 +141,170 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::str::FromStr;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
 use tracing::instrument;
+use trade::bitmex_client::BitmexClient;
+use trade::ContractSymbol;
+use uuid::Uuid;
 
 pub struct AppState {
     pub node: Node,
     // Channel used to send messages to all connected clients.
     pub tx_price_feed: broadcast::Sender<Message>,
     pub tx_user_feed: broadcast::Sender<NewUserMessage>,
-    pub trading_sender: mpsc::Sender<NewOrderMessage>,
+    pub trading_sender: mpsc::Sender<TradingMessage>,
     pub pool: Pool<ConnectionManager<PgConnection>>,
-    pub settings: RwLock<Settings>,
+    pub settings: Arc<RwLock<Settings>>,
     pub exporter: PrometheusExporter,
     pub announcement_addresses: Vec<SocketAddress>,
     pub node_alias: String,
     pub auth_users_notifier: mpsc::Sender<OrderbookMessage>,
-    pub user_backup: SledBackup,
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn router(
-    node: Node,
-    pool: Pool<ConnectionManager<PgConnection>>,
-    settings: Settings,
-    exporter: PrometheusExporter,
-    announcement_addresses: Vec<SocketAddress>,
-    node_alias: &str,
-    trading_sender: mpsc::Sender<NewOrderMessage>,
-    tx_price_feed: broadcast::Sender<Message>,
-    tx_user_feed: broadcast::Sender<NewUserMessage>,
-    auth_users_notifier: mpsc::Sender<OrderbookMessage>,
-    user_backup: SledBackup,
-) -> Router {
-    let app_state = Arc::new(AppState {
-        node,
-        pool,
-        settings: RwLock::new(settings),
-        tx_price_feed,
-        tx_user_feed,
-        trading_sender,
-        exporter,
-        announcement_addresses,
-        node_alias: node_alias.to_string(),
-        auth_users_notifier,
-        user_backup,
-    });
+    pub maker_notifier: MakerNotifier,
+    pub fee_estimates: FeeEstimatesProvider,
+    pub user_backup: Arc<dyn UserBackupStore>,
+    pub health: Health,
+    pub trading_halt: TradingHalt,
+    pub rollover_scheduler: RolloverScheduler,
+    pub shutdown: Shutdown,
+    /// The clock of the [`Node`], which can be fast-forwarded on test networks.
+    pub clock: Arc<AdjustableClock>,
+}
 
-    Router::new()
-        .route("/", get(index))
-        .route("/api/version", get(version))
-        .route("/api/backup/:node_id", post(back_up).delete(delete_backup))
-        .route("/api/restore/:node_id", get(restore))
+/// The database, for the endpoints which only read from it and are hence also served by a
+/// coordinator on standby, see [`crate::standby`].
+#[derive(Clone)]
+pub struct Database(pub Pool<ConnectionManager<PgConnection>>);
+
+impl FromRef<Arc<AppState>> for Database {
+    fn from_ref(state: &Arc<AppState>) -> Self {
+        Database(state.pool.clone())
+    }
+}
+
+pub fn router(app_state: Arc<AppState>) -> Router {
+    let admin = Router::new()
+        .route("/wallet/balance", get(get_balance))
+        .route("/wallet/utxos", get(get_utxos))
+        .route(
+            "/wallet/utxos/:outpoint/freeze",
+            post(freeze_utxo).delete(unfreeze_utxo),
+        )
+        .route("/wallet/send", post(send_on_chain))
+        .route("/withdraw/batch", post(withdraw_batch))
+        .route("/psbt", post(create_psbt))
+        .route("/psbt/broadcast", post(broadcast_psbt))
+        .route("/channels", get(list_channels).post(open_channel))
+        .route("/channels/:channel_id", delete(close_channel))
+        .route("/peers", get(list_peers))
+        .route("/send_payment/:invoice", post(send_payment))
+        .route("/dlc_channels", get(list_dlc_channels))
+        .route("/transactions", get(list_on_chain_transactions))
+        .route("/transactions/:txid/bump_fee", post(bump_fee))
+        .route("/sign/:msg", get(sign_message))
+        .route("/connect", post(connect_to_peer))
+        .route("/channels/revert", post(collaborative_revert))
+        .route(
+            "/collaborative_revert/batch",
+            post(collaborative_revert_batch),
+        )
+        .route("/is_connected/:target_pubkey", get(is_connected))
+        .route("/settings", get(get_settings).put(update_settings))
+        .route("/sync", post(post_sync))
+        .route("/broadcast_announcement", post(post_broadcast_announcement))
+        .route("/halt_trading", get(get_trading_halt).post(halt_trading))
+        .route("/resume_trading", post(resume_trading))
+        .route("/rollovers", get(list_rollovers))
+        .route("/simulate-payout", post(simulate_payout))
+        .route("/users/online", get(list_online_users))
+        .route("/users/:trader_pubkey/statement", get(get_user_statement))
+        .route(
+            "/users/:trader_pubkey/backup",
+            get(list_user_backup_keys).delete(delete_user_backup),
+        )
         .route(
-            "/api/prepare_onboarding_payment",
+            "/trade_executions/failed",
+            get(list_failed_trade_executions),
+        )
+        .route("/trades/:trade_id/replay", post(replay_trade))
+        .route("/positions/:trader_pubkey/expire", post(expire_position))
+        .route("/clock", get(get_clock))
+        .route("/clock/advance", post(advance_clock))
+        .route("/attestations", get(list_attestations))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            authenticate,
+        ));
+
+    // The public API, which is served under the prefix of each version.
+    let api = Router::new()
+        .route("/version", get(version))
+        .route("/app-config", get(get_app_config))
+        .route("/fee-estimates", get(get_fee_estimates))
+        .route("/lsp/config", get(get_jit_channel_config))
+        .route("/backup/:node_id", post(back_up).delete(delete_backup))
+        .route("/backups/:node_id", post(back_up_all))
+        .route("/restore/:node_id", get(restore))
+        .route(
+            "/channels/:channel_id/force-close",
+            post(force_close_channel),
+        )
+        .route("/recovery/:trader_pubkey", get(get_recovery_info))
+        .route(
+            "/prepare_onboarding_payment",
             post(prepare_onboarding_payment),
         )
-        .route("/api/newaddress", get(get_unused_address))
-        .route("/api/node", get(get_node_info))
-        .route("/api/invoice", get(get_invoice))
-        .route("/api/orderbook/orders", get(get_orders).post(post_order))
         .route(
-            "/api/orderbook/orders/:order_id",
-            get(get_order).put(put_order),
+            "/prepare_async_payment/:trader_pubkey",
+            post(prepare_async_payment),
         )
-        .route("/api/orderbook/websocket", get(websocket_handler))
-        .route("/api/trade", post(post_trade))
-        .route("/api/rollover/:dlc_channel_id", post(rollover))
-        .route("/api/register", post(post_register))
-        .route("/api/admin/wallet/balance", get(get_balance))
-        .route("/api/admin/wallet/utxos", get(get_utxos))
-        .route("/api/admin/channels", get(list_channels).post(open_channel))
-        .route("/api/admin/channels/:channel_id", delete(close_channel))
-        .route("/api/admin/peers", get(list_peers))
-        .route("/api/admin/send_payment/:invoice", post(send_payment))
-        .route("/api/admin/dlc_channels", get(list_dlc_channels))
-        .route("/api/admin/transactions", get(list_on_chain_transactions))
-        .route("/api/admin/sign/:msg", get(sign_message))
-        .route("/api/admin/connect", post(connect_to_peer))
-        .route("/api/admin/channels/revert", post(collaborative_revert))
+        .route("/newaddress", get(get_unused_address))
+        .route("/node", get(get_node_info))
+        .route("/invoice", get(get_invoice))
+        .route("/orderbook/orders", get(get_orders).post(post_order))
+        .route("/orderbook/orders/:order_id", get(get_order).put(put_order))
         .route(
-            "/api/channels/revertconfirm",
-            post(collaborative_revert_confirm),
+            "/orderbook/orders/:order_id/rollback",
+            post(post_rollback_match),
         )
-        .route("/api/admin/is_connected/:target_pubkey", get(is_connected))
+        .route("/orderbook/websocket", get(websocket_handler))
+        .route("/trade", post(post_trade))
+        .route("/rollover/:dlc_channel_id", post(rollover))
+        .route("/markets/:contract_symbol/stats", get(get_market_stats))
+        .route("/positions/:trader_pubkey", get(get_trader_positions))
         .route(
-            "/api/admin/settings",
-            get(get_settings).put(update_settings),
+            "/positions/:trader_pubkey/:position_id/close",
+            post(close_trader_position),
         )
-        .route("/api/admin/sync", post(post_sync))
+        .route("/users/:trader_pubkey/statement", get(get_statement))
+        .route("/register", post(post_register))
+        .route("/error-reports", post(post_error_reports))
+        .route("/api-keys", post(post_api_key))
+        .route("/api-keys/:id", delete(delete_api_key))
         .route(
-            "/api/admin/broadcast_announcement",
-            post(post_broadcast_announcement),
+            "/channels/revertconfirm",
+            post(collaborative_revert_confirm),
+        );
+
+    Router::new()
+        .route("/", get(index))
+        .nest(ApiVersion::V1.path_prefix(), api.clone())
+        // Clients which do not know about versioning yet still use the unversioned paths.
+        .nest(
+            "/api",
+            api.layer(middleware::from_fn(api_version::deprecate_unversioned)),
         )
+        .nest("/api/admin", admin)
+        .merge(openapi::swagger_ui())
         .route("/metrics", get(get_metrics))
         .route("/health", get(get_health))
+        .route("/health/live", get(get_liveness))
         .layer(DefaultBodyLimit::disable())
         .layer(DefaultBodyLimit::max(50 * 1024))
+        .layer(middleware::from_fn(trace::trace_request))
         .with_state(app_state)
 }
 
@@ -242,31 +374,66 @@ pub async fn prepare_onboarding_payment(
         ));
     };
 
+    let (jit_channel, liquidity_fee_ppm) = {
+        let settings = app_state.settings.read().await;
+        (settings.jit_channel, settings.jit_channel_fee_ppm())
+    };
+
     let route_hint_hop = spawn_blocking({
         let app_state = app_state.clone();
         move || {
-            let mut conn = app_state.pool.get()?;
-            let liquidity_option = db::liquidity_options::get(&mut conn, liquidity_option_id)?;
+            let liquidity_option = db::liquidity_options::get(&mut conn, liquidity_option_id)
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Could not load liquidity option: {e:#}"))
+                })?;
+            let liquidity_request = LiquidityRequest {
+                user_channel_id,
+                liquidity_option_id,
+                trader_id: target_node,
+                trade_up_to_sats: liquidity_option.trade_up_to_sats,
+                max_deposit_sats: liquidity_option.max_deposit_sats,
+                coordinator_leverage: liquidity_option.coordinator_leverage,
+                fee_sats: liquidity_option
+                    .get_fee(Decimal::from(amount_sats))
+                    .to_u64()
+                    .expect("to fit into u64"),
+                liquidity_fee_ppm,
+            };
+
+            let channel_value_sats =
+                calculate_channel_value(amount_sats * 1000, &liquidity_request);
+            jit_channel
+                .check_channel_size(channel_value_sats)
+                .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
             app_state
                 .node
                 .inner
-                .prepare_onboarding_payment(LiquidityRequest {
-                    user_channel_id,
-                    liquidity_option_id,
-                    trader_id: target_node,
-                    trade_up_to_sats: liquidity_option.trade_up_to_sats,
-                    max_deposit_sats: liquidity_option.max_deposit_sats,
-                    coordinator_leverage: liquidity_option.coordinator_leverage,
-                    fee_sats: liquidity_option
-                        .get_fee(Decimal::from(amount_sats))
-                        .to_u64()
-                        .expect("to fit into u64"),
+                .prepare_onboarding_payment(liquidity_request)
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Could not prepare payment: {e:#}"))
                 })
         }
     })
-    .await
-    .expect("task to complete")
-    .map_err(|e| AppError::InternalServerError(format!("Could not prepare payment: {e:#}")))?;
+    .await??;
+
+    Ok(Json(route_hint_hop.into()))
+}
+
+/// Prepare a payment to a trader which we hold until the trader is online to receive it.
+#[instrument(skip_all, err(Debug))]
+pub async fn prepare_async_payment(
+    Path(trader_pubkey): Path<String>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<Json<RouteHintHop>, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    let route_hint_hop = app_state
+        .node
+        .inner
+        .prepare_async_payment(trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Could not prepare async payment: {e:#}")))?;
 
     Ok(Json(route_hint_hop.into()))
 }
@@ -280,7 +447,10 @@ pub async fn get_node_info(
     State(app_state): State<Arc<AppState>>,
 ) -> Result<Json<NodeInfo>, AppError> {
     let node_info = app_state.node.inner.info;
-    Ok(Json(node_info))
+    Ok(Json(NodeInfo {
+        pubkey: node_info.pubkey,
+        address: node_info.address,
+    }))
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -322,10 +492,18 @@ pub async fn get_invoice(
 pub async fn post_trade(
     State(state): State<Arc<AppState>>,
     trade_params: Json<TradeParams>,
-) -> Result<(), AppError> {
-    state.node.trade(&trade_params.0).await.map_err(|e| {
+) -> Result<StatusCode, AppError> {
+    let outcome = state.node.trade(&trade_params.0).await.map_err(|e| {
         AppError::InternalServerError(format!("Could not handle trade request: {e:#}"))
-    })
+    })?;
+
+    // The trader has to keep waiting for the DLC protocol if the execution is retried.
+    let status = match outcome {
+        TradeOutcome::Executed => StatusCode::OK,
+        TradeOutcome::PendingRetry => StatusCode::ACCEPTED,
+    };
+
+    Ok(status)
 }
 
 #[instrument(skip_all, err(Debug))]
@@ -383,8 +561,7 @@ pub async fn post_sync(State(state): State<Arc<AppState>>) -> Result<(), AppErro
 
         anyhow::Ok(())
     })
-    .await
-    .expect("task to complete")
+    .await?
     .map_err(|e| AppError::InternalServerError(format!("Could not sync wallets: {e:#}")))?;
 
     Ok(())
@@ -398,14 +575,13 @@ pub async fn post_register(
     let register_params = params.0;
     tracing::info!(?register_params, "Registered new user");
 
-    let mut conn = state
-        .pool
-        .get()
-        .map_err(|e| AppError::InternalServerError(format!("Could not get connection: {e:#}")))?;
-
     if let Some(email) = register_params.email {
-        user::upsert_email(&mut conn, register_params.pubkey, email)
-            .map_err(|e| AppError::InternalServerError(format!("Could not upsert user: {e:#}")))?;
+        let pubkey = register_params.pubkey;
+        db::run(&state.pool, move |conn| {
+            Ok(user::upsert_email(conn, pubkey, email)?)
+        })
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Could not upsert user: {e:#}")))?;
     } else {
         tracing::warn!(trader_id=%register_params.pubkey, "Did not receive an email during registration");
     }
@@ -413,6 +589,37 @@ pub async fn post_register(
     Ok(())
 }
 
+/// Errors reported by the apps of users who opted in, logged so that we learn how the app fails in
+/// the wild.
+pub async fn post_error_reports(Json(error_reports): Json<ErrorReports>) -> Result<(), AppError> {
+    if error_reports.reports.len() > MAX_ERROR_REPORTS {
+        return Err(AppError::BadRequest(format!(
+            "At most {MAX_ERROR_REPORTS} error reports can be sent at once"
+        )));
+    }
+
+    for report in error_reports.reports {
+        let message = report
+            .message
+            .chars()
+            .take(MAX_ERROR_REPORT_MESSAGE_LEN)
+            .collect::<String>();
+
+        tracing::warn!(
+            target: "app_error_report",
+            app_version = %error_reports.app_version,
+            kind = ?report.kind,
+            source = %report.target,
+            occurred_at = %report.timestamp,
+            count = report.count,
+            ?message,
+            "App reported an error"
+        );
+    }
+
+    Ok(())
+}
+
 async fn get_settings(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let settings = state.settings.read().await;
     serde_json::to_string(&*settings).expect("to be able to serialise settings")
@@ -423,6 +630,10 @@ async fn update_settings(
     State(state): State<Arc<AppState>>,
     Json(updated_settings): Json<SettingsFile>,
 ) -> Result<(), AppError> {
+    updated_settings
+        .validate()
+        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
     let mut settings = state.settings.write().await;
 
     settings.update(updated_settings.clone());
@@ -475,27 +686,338 @@ pub async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoRespons
     (StatusCode::OK, open_telemetry_metrics)
 }
 
-pub async fn get_health() -> Result<Json<String>, AppError> {
-    // TODO: Implement any health check logic we'd need
-    // So far this just returns if the server is running
-    Ok(Json("Server is healthy".to_string()))
+#[instrument(skip_all, err(Debug))]
+pub async fn get_market_stats(
+    Path(contract_symbol): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<MarketStats>, AppError> {
+    let contract_symbol = ContractSymbol::from_str(&contract_symbol)
+        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
+    let stats = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        crate::trade::stats::get_market_stats(&mut conn, contract_symbol)
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to get market stats: {e:#}")))?;
+
+    Ok(Json(stats))
+}
+
+/// The coordinator's view of the trader's current position, e.g. to reconcile the app's state
+/// after a restore.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_trader_positions(
+    Path(trader_pubkey): Path<String>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    signature: Option<Json<Signature>>,
+) -> Result<Json<Vec<TraderPosition>>, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    authenticate_trader(
+        &state,
+        &headers,
+        signature,
+        trader_pubkey,
+        ApiKeyScope::Read,
+    )
+    .await?;
+
+    let positions = load_trader_positions(&state, trader_pubkey).await?;
+
+    Ok(Json(positions))
+}
+
+/// The trader's positions which have not been closed yet, including their unrealized PnL.
+pub(crate) async fn load_trader_positions(
+    state: &Arc<AppState>,
+    trader_pubkey: PublicKey,
+) -> Result<Vec<TraderPosition>, AppError> {
+    let positions = spawn_blocking({
+        let pool = state.pool.clone();
+        move || {
+            let mut conn = pool.get()?;
+            let positions = db::positions::Position::get_positions_by_trader(
+                &mut conn,
+                trader_pubkey,
+                vec![
+                    PositionState::Open,
+                    PositionState::Closing { closing_price: 0.0 },
+                    PositionState::Rollover,
+                    PositionState::Resizing,
+                    PositionState::ResizeOpeningSubchannelProposed,
+                ],
+            )?;
+
+            anyhow::Ok(positions)
+        }
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load positions: {e:#}")))?;
+
+    if positions.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // TODO(holzeis): we should not use the bitmex quote here, but rather our own orderbook.
+    let quote = BitmexClient::get_quote(&state.node.inner.network, &OffsetDateTime::now_utc())
+        .await
+        .map_err(|e| tracing::warn!(%trader_pubkey, "Failed to fetch quote from BitMEX: {e:#}"))
+        .ok();
+
+    let positions = positions
+        .iter()
+        .filter_map(|position| position.to_trader_position(quote.clone()))
+        .collect();
+
+    Ok(positions)
+}
+
+/// Close one of the trader's open positions with a reduce-only market order for the whole
+/// position.
+#[instrument(skip_all, err(Debug))]
+pub async fn close_trader_position(
+    Path((trader_pubkey, position_id)): Path<(String, i32)>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    signature: Option<Json<Signature>>,
+) -> Result<Json<Order>, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    authenticate_trader(
+        &state,
+        &headers,
+        signature,
+        trader_pubkey,
+        ApiKeyScope::Trade,
+    )
+    .await?;
+
+    let order = close_position(&state, trader_pubkey, position_id).await?;
+
+    Ok(Json(order))
 }
 
-#[derive(Serialize)]
-pub struct Version {
-    version: String,
-    commit_hash: String,
-    branch: String,
+/// Submit a reduce-only market order closing the trader's open position with `position_id`.
+pub(crate) async fn close_position(
+    state: &Arc<AppState>,
+    trader_pubkey: PublicKey,
+    position_id: i32,
+) -> Result<Order, AppError> {
+    let position = spawn_blocking({
+        let pool = state.pool.clone();
+        move || {
+            let mut conn = pool.get()?;
+            let position = db::positions::Position::get_positions_by_trader(
+                &mut conn,
+                trader_pubkey,
+                vec![PositionState::Open],
+            )?
+            .into_iter()
+            .find(|position| position.id == position_id);
+
+            anyhow::Ok(position)
+        }
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to load position: {e:#}")))?
+    .ok_or_else(|| {
+        AppError::BadRequest(format!("No open position with id {position_id} to close"))
+    })?;
+
+    let new_order = NewOrder {
+        id: Uuid::new_v4(),
+        contract_symbol: position.contract_symbol,
+        // Market orders do not have a price.
+        price: Decimal::ZERO,
+        quantity: Decimal::try_from(position.quantity).expect("to fit into decimal"),
+        trader_id: trader_pubkey,
+        direction: position.direction.opposite(),
+        leverage: position.trader_leverage,
+        order_type: OrderType::Market,
+        expiry: OffsetDateTime::now_utc() + time::Duration::minutes(1),
+        stable: position.stable,
+        reduce_only: true,
+    };
+
+    submit_order(state, new_order).await
 }
 
-pub async fn version() -> Result<Json<Version>, AppError> {
-    Ok(Json(Version {
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// The period of an account statement. Defaults to all bookings until now.
+#[derive(Debug, Deserialize)]
+pub struct StatementParams {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+    #[serde(default)]
+    format: StatementFormat,
+}
+
+/// The account statement of the trader, i.e. their realized PnL, fees and funding within the
+/// requested period.
+#[instrument(skip_all, err(Debug))]
+pub async fn get_statement(
+    Path(trader_pubkey): Path<String>,
+    Query(params): Query<StatementParams>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    signature: Option<Json<Signature>>,
+) -> Result<Response, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    authenticate_trader(
+        &state,
+        &headers,
+        signature,
+        trader_pubkey,
+        ApiKeyScope::Read,
+    )
+    .await?;
+
+    statement_response(state, trader_pubkey, params).await
+}
+
+/// Authenticate a request of the trader, either by an API key with the given `scope` or by a
+/// signature of the trader's pubkey using their node key.
+async fn authenticate_trader(
+    state: &Arc<AppState>,
+    headers: &HeaderMap,
+    signature: Option<Json<Signature>>,
+    trader_pubkey: PublicKey,
+    scope: ApiKeyScope,
+) -> Result<(), AppError> {
+    match api_key::authenticate(state, headers, scope).await? {
+        Some(api_key_owner) if api_key_owner == trader_pubkey => Ok(()),
+        Some(_) => Err(AppError::Unauthorized),
+        None => {
+            let signature = signature.ok_or(AppError::Unauthorized)?;
+
+            let message = trader_pubkey.to_string().as_bytes().to_vec();
+            let message = commons::create_sign_message(message);
+            signature
+                .verify(&message, &trader_pubkey)
+                .map_err(|_| AppError::Unauthorized)
+        }
+    }
+}
+
+pub(crate) async fn statement_response(
+    state: Arc<AppState>,
+    trader_pubkey: PublicKey,
+    params: StatementParams,
+) -> Result<Response, AppError> {
+    let from = params.from.unwrap_or(OffsetDateTime::UNIX_EPOCH);
+    let to = params.to.unwrap_or_else(OffsetDateTime::now_utc);
+    if from >= to {
+        return Err(AppError::BadRequest(
+            "The start of the period has to be before its end".to_string(),
+        ));
+    }
+
+    let statement = spawn_blocking(move || {
+        let mut conn = state.pool.get()?;
+        crate::trade::statement::get_statement(&mut conn, trader_pubkey, from, to)
+    })
+    .await?
+    .map_err(|e| AppError::InternalServerError(format!("Failed to create statement: {e:#}")))?;
+
+    let response = match params.format {
+        StatementFormat::Json => Json(statement).into_response(),
+        StatementFormat::Csv => {
+            let csv = statement.to_csv().map_err(|e| {
+                AppError::InternalServerError(format!("Failed to create statement: {e:#}"))
+            })?;
+
+            ([(CONTENT_TYPE, "text/csv")], csv).into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+/// Readiness check of the coordinator and its dependencies.
+///
+/// Returns 503 if any of the critical dependencies are offline. If only a non-critical dependency
+/// is offline, the coordinator is reported as degraded but still returns 200.
+pub async fn get_health(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<OverallCoordinatorHealth>) {
+    let health = state.health.get_health().await;
+
+    let status = if health.is_ready() {
+        StatusCode::OK
+    } else {
+        tracing::error!(?health, "Coordinator is unhealthy");
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(health))
+}
+
+/// Liveness check, i.e. the HTTP server is running.
+pub async fn get_liveness() -> Result<Json<String>, AppError> {
+    Ok(Json("Server is running".to_string()))
+}
+
+pub async fn version() -> Result<Json<VersionInfo>, AppError> {
+    Ok(Json(VersionInfo {
         version: env!("CARGO_PKG_VERSION").to_string(),
         commit_hash: env!("COMMIT_HASH").to_string(),
         branch: env!("BRANCH_NAME").to_string(),
+        api_versions: ApiVersion::ALL.to_vec(),
     }))
 }
 
+/// Non-secret parameters for the app, see [`AppConfig`].
+///
+/// The taker fee rate is taken from the fee schedule, unless it is overridden in the app config.
+pub async fn get_app_config(State(state): State<Arc<AppState>>) -> Json<AppConfig> {
+    let settings = state.settings.read().await;
+
+    let mut app_config = settings.app_config.clone();
+    app_config.taker_fee_rate = app_config
+        .taker_fee_rate
+        .or(Some(settings.fee_schedule.taker_fee_rate));
+
+    Json(app_config)
+}
+
+/// The terms under which the coordinator opens JIT channels, see [`JitChannelConfig`].
+pub async fn get_jit_channel_config(State(state): State<Arc<AppState>>) -> Json<JitChannelConfig> {
+    Json(state.settings.read().await.to_jit_channel_config())
+}
+
+/// The recommended on-chain fee rates and the fee histogram of the mempool, see
+/// [`FeeEstimates`].
+pub async fn get_fee_estimates(State(state): State<Arc<AppState>>) -> Json<FeeEstimates> {
+    Json(state.fee_estimates.get().await)
+}
+
+/// Confirm a collaborative revert proposed by the coordinator.
+#[utoipa::path(
+    post,
+    path = "/api/v1/channels/revertconfirm",
+    tag = "collaborative revert",
+    request_body = CollaborativeRevertTraderResponse,
+    responses(
+        (status = 200, description = "The hex-encoded revert transaction", body = String),
+        (status = 400, description = "The channel id is invalid", body = ErrorResponse),
+        (status = 500, description = "The revert could not be confirmed", body = ErrorResponse),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn collaborative_revert_confirm(
     State(state): State<Arc<AppState>>,
@@ -539,6 +1061,18 @@ pub async fn collaborative_revert_confirm(
 
 // TODO(holzeis): There is no reason the backup and restore api has to run on the coordinator. On
 // the contrary it would be much more reasonable to have the backup and restore api run separately.
+#[utoipa::path(
+    post,
+    path = "/api/v1/backup/{node_id}",
+    tag = "backup",
+    params(("node_id" = String, Path, description = "The pubkey of the app's node")),
+    request_body = Backup,
+    responses(
+        (status = 200, description = "The value has been backed up"),
+        (status = 400, description = "The node id or the checksum is invalid", body = ErrorResponse),
+        (status = 401, description = "The backup is not signed by the node"),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn back_up(
     Path(node_id): Path<String>,
@@ -552,6 +1086,10 @@ pub async fn back_up(
         .verify(&node_id)
         .map_err(|_| AppError::Unauthorized)?;
 
+    backup
+        .verify_checksum()
+        .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+
     state
         .user_backup
         .back_up(node_id, backup.0)
@@ -559,6 +1097,57 @@ pub async fn back_up(
         .map_err(|e| AppError::InternalServerError(e.to_string()))
 }
 
+/// Backs up multiple values at once, e.g. all the values an app has changed within a few seconds.
+#[utoipa::path(
+    post,
+    path = "/api/v1/backups/{node_id}",
+    tag = "backup",
+    params(("node_id" = String, Path, description = "The pubkey of the app's node")),
+    request_body = [Backup],
+    responses(
+        (status = 200, description = "The values have been backed up"),
+        (status = 400, description = "The node id or a checksum is invalid", body = ErrorResponse),
+        (status = 401, description = "A backup is not signed by the node"),
+    )
+)]
+#[instrument(skip_all, err(Debug))]
+pub async fn back_up_all(
+    Path(node_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    backups: Json<Vec<Backup>>,
+) -> Result<(), AppError> {
+    let node_id = PublicKey::from_str(&node_id)
+        .map_err(|e| AppError::BadRequest(format!("Invalid node id provided. {e:#}")))?;
+
+    for backup in backups.iter() {
+        backup
+            .verify(&node_id)
+            .map_err(|_| AppError::Unauthorized)?;
+
+        backup
+            .verify_checksum()
+            .map_err(|e| AppError::BadRequest(format!("{e:#}")))?;
+    }
+
+    state
+        .user_backup
+        .back_up_all(node_id, backups.0)
+        .await
+        .map_err(|e| AppError::InternalServerError(e.to_string()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/backup/{node_id}",
+    tag = "backup",
+    params(("node_id" = String, Path, description = "The pubkey of the app's node")),
+    request_body = DeleteBackup,
+    responses(
+        (status = 200, description = "The value has been deleted"),
+        (status = 400, description = "The node id is invalid", body = ErrorResponse),
+        (status = 401, description = "The request is not signed by the node"),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
 pub async fn delete_backup(
     Path(node_id): Path<String>,
@@ -575,11 +1164,133 @@ pub async fn delete_backup(
     state
         .user_backup
         .delete(node_id, backup.0)
+        .await
         .map_err(|e| AppError::InternalServerError(e.to_string()))
 }
 
+/// Force-closes a channel on behalf of a trader who lost the channel state and only has a static
+/// channel backup left.
+#[instrument(skip_all, err(Debug))]
+/// Tells a trader which channels the coordinator has with them, so that they can recover their
+/// funds after losing their backup.
+async fn get_recovery_info(
+    Path(trader_pubkey): Path<String>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    signature: Option<Json<Signature>>,
+) -> Result<Json<RecoveryInfo>, AppError> {
+    let trader_pubkey = PublicKey::from_str(&trader_pubkey)
+        .map_err(|e| AppError::BadRequest(format!("Invalid trader pubkey provided. {e:#}")))?;
+
+    authenticate_trader(
+        &state,
+        &headers,
+        signature,
+        trader_pubkey,
+        ApiKeyScope::Read,
+    )
+    .await?;
+
+    let node = &state.node.inner;
+
+    let mut channels = node
+        .list_channels()
+        .into_iter()
+        .filter(|channel| channel.counterparty.node_id == trader_pubkey)
+        .map(|channel| RecoverableChannel {
+            channel_id: channel.channel_id.0.to_hex(),
+            kind: RecoverableChannelKind::Lightning,
+            balance_sats: channel
+                .channel_value_sats
+                .saturating_sub(channel.balance_msat / 1_000),
+        })
+        .collect::<Vec<_>>();
+
+    let dlc_channels = node
+        .list_signed_dlc_channels()
+        .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?
+        .into_iter()
+        .filter(|channel| channel.counter_party == trader_pubkey)
+        .map(|channel| RecoverableChannel {
+            channel_id: channel.channel_id.to_hex(),
+            kind: RecoverableChannelKind::Dlc,
+            balance_sats: channel.counter_params.collateral,
+        });
+    channels.extend(dlc_channels);
+
+    tracing::info!(
+        %trader_pubkey,
+        channels = channels.len(),
+        "Trader requested recovery information"
+    );
+
+    Ok(Json(RecoveryInfo { channels }))
+}
+
+async fn force_close_channel(
+    Path(channel_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    request: Json<ForceCloseChannel>,
+) -> Result<(), AppError> {
+    request
+        .verify(&channel_id)
+        .map_err(|_| AppError::Unauthorized)?;
+
+    let trader_id = request.trader_id;
+    let channel_id_bytes = parse_dlc_channel_id(&channel_id)
+        .map_err(|_| AppError::BadRequest("Provided channel ID was invalid".to_string()))?;
+
+    let node = &state.node.inner;
+
+    if let Some(channel) = node
+        .list_channels()
+        .into_iter()
+        .find(|channel| channel.channel_id.0 == channel_id_bytes)
+    {
+        if channel.counterparty.node_id != trader_id {
+            return Err(AppError::Unauthorized);
+        }
+
+        tracing::warn!(%trader_id, %channel_id, "Force-closing LN channel on behalf of trader");
+
+        return node
+            .close_channel(channel.channel_id, true)
+            .map_err(|e| AppError::InternalServerError(format!("{e:#}")));
+    }
+
+    let dlc_channel = node
+        .list_signed_dlc_channels()
+        .map_err(|e| AppError::InternalServerError(format!("{e:#}")))?
+        .into_iter()
+        .find(|channel| channel.channel_id == channel_id_bytes)
+        .ok_or_else(|| AppError::BadRequest(format!("Unknown channel {channel_id}")))?;
+
+    if dlc_channel.counter_party != trader_id {
+        return Err(AppError::Unauthorized);
+    }
+
+    tracing::warn!(%trader_id, %channel_id, "Force-closing DLC channel on behalf of trader");
+
+    node.close_dlc_channel(channel_id_bytes, true)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("{e:#}")))
+}
+
+/// All values backed up by a node. The node has to sign its own pubkey.
+#[utoipa::path(
+    get,
+    path = "/api/v1/restore/{node_id}",
+    tag = "backup",
+    params(("node_id" = String, Path, description = "The pubkey of the app's node")),
+    request_body = Signature,
+    responses(
+        (status = 200, description = "The backed up values", body = [Restore]),
+        (status = 400, description = "The node id is invalid", body = ErrorResponse),
+        (status = 401, description = "The signature is not the node's"),
+    )
+)]
 #[instrument(skip_all, err(Debug))]
-async fn restore(
+pub async fn restore(
     Path(node_id): Path<String>,
     State(state): State<Arc<AppState>>,
     signature: Json<Signature>,
@@ -593,10 +1304,10 @@ async fn restore(
         .verify(&message, &node_id)
         .map_err(|_| AppError::Unauthorized)?;
 
-    let backup = state
-        .user_backup
-        .restore(node_id)
-        .map_err(|e| AppError::InternalServerError(format!("Failed to restore backup. {e:#}")))?;
+    let backup =
+        state.user_backup.restore(node_id).await.map_err(|e| {
+            AppError::InternalServerError(format!("Failed to restore backup. {e:#}"))
+        })?;
 
     Ok(Json(backup))
 }
@@ -9,11 +9,12 @@ use crate::settings::Settings;
 use anyhow::anyhow;
 use anyhow::Result;
 use bitcoin::Network;
+use commons::Clock;
 use commons::Message;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::PgConnection;
-use time::OffsetDateTime;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_cron_scheduler::Job;
 use tokio_cron_scheduler::JobScheduler;
@@ -56,6 +57,7 @@ impl NotificationScheduler {
     ) -> Result<()> {
         let sender = self.sender.clone();
         let schedule = self.settings.close_expired_position_scheduler.clone();
+        let clock = self.node.clock.clone();
 
         let uuid = self
             .scheduler
@@ -63,6 +65,7 @@ impl NotificationScheduler {
                 schedule.as_str(),
                 sender,
                 pool,
+                clock,
             )?)
             .await?;
         tracing::debug!(
@@ -145,7 +148,8 @@ fn build_rollover_notification_job(
         let notifier = notifier.clone();
         let mut conn = pool.get().expect("To be able to get a db connection");
 
-        if !commons::is_eligible_for_rollover(OffsetDateTime::now_utc(), network) {
+        let now = node.clock.now();
+        if !commons::is_eligible_for_rollover(now, network) {
             return Box::pin(async move {
                 tracing::warn!("Rollover window hasn't started yet. Job schedule seems to be miss-aligned with the rollover window. Skipping user notifications.");
             });
@@ -153,7 +157,7 @@ fn build_rollover_notification_job(
 
         // calculates the expiry of the next rollover window. positions which have an
         // expiry before that haven't rolled over yet, and need to be reminded.
-        let expiry = commons::calculate_next_expiry(OffsetDateTime::now_utc(), network);
+        let expiry = commons::calculate_next_expiry(now, network);
         match db::positions::Position::get_all_open_positions_with_expiry_before(&mut conn, expiry)
         {
             Ok(positions) => Box::pin({
@@ -206,6 +210,7 @@ fn build_remind_to_close_expired_position_notification_job(
     schedule: &str,
     notification_sender: mpsc::Sender<Notification>,
     pool: Pool<ConnectionManager<PgConnection>>,
+    clock: Arc<dyn Clock>,
 ) -> Result<Job, JobSchedulerError> {
     Job::new_async(schedule, move |_, _| {
         let notification_sender = notification_sender.clone();
@@ -214,7 +219,7 @@ fn build_remind_to_close_expired_position_notification_job(
         // Note, positions that are expired longer than
         // [`crate::node::expired_positions::EXPIRED_POSITION_TIMEOUT`] are set to closing, hence
         // those positions will not get notified anymore afterwards.
-        match get_all_open_positions_with_expiry_before(&mut conn, OffsetDateTime::now_utc()) {
+        match get_all_open_positions_with_expiry_before(&mut conn, clock.now()) {
             Ok(positions_with_token) => Box::pin({
                 async move {
                     for (position, fcm_token) in positions_with_token {
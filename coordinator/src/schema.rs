@@ -1,6 +1,10 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "AttestationState_Type"))]
+    pub struct AttestationStateType;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "ChannelState_Type"))]
     pub struct ChannelStateType;
@@ -44,6 +48,43 @@ pub mod sql_types {
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "PositionState_Type"))]
     pub struct PositionStateType;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "TradeExecutionState_Type"))]
+    pub struct TradeExecutionStateType;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::AttestationStateType;
+
+    attestations (id) {
+        id -> Int4,
+        position_id -> Int4,
+        trader_pubkey -> Text,
+        contract_id -> Text,
+        oracle_pk -> Text,
+        event_id -> Text,
+        attestation_state -> AttestationStateType,
+        attested_price -> Nullable<Int8>,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt -> Timestamptz,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> Uuid,
+        trader_pubkey -> Text,
+        label -> Text,
+        key_hash -> Text,
+        scopes -> Text,
+        created_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+    }
 }
 
 diesel::table! {
@@ -78,6 +119,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    device_sessions (trader_pubkey) {
+        trader_pubkey -> Text,
+        device_id -> Text,
+        registered_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::MessageTypeType;
@@ -92,11 +142,10 @@ diesel::table! {
 }
 
 diesel::table! {
-    last_outbound_dlc_messages (peer_id) {
-        peer_id -> Text,
-        message_hash -> Text,
-        message -> Text,
-        timestamp -> Timestamptz,
+    dlc_store (kind, key) {
+        kind -> Int2,
+        key -> Bytea,
+        value -> Bytea,
     }
 }
 
@@ -143,6 +192,7 @@ diesel::table! {
         quantity -> Float4,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        failure_reason -> Nullable<Text>,
     }
 }
 
@@ -172,6 +222,43 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ContractSymbolType;
+
+    orderbook_events (id) {
+        id -> Int8,
+        contract_symbol -> ContractSymbolType,
+        event_type -> Text,
+        order_id -> Uuid,
+        payload -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ContractSymbolType;
+
+    orderbook_snapshots (id) {
+        id -> Int4,
+        contract_symbol -> ContractSymbolType,
+        last_event_id -> Int8,
+        orders -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    outbound_dlc_messages (id) {
+        id -> Int4,
+        message_hash -> Text,
+        peer_id -> Text,
+        message -> Text,
+        timestamp -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::HtlcStatusType;
@@ -191,6 +278,9 @@ diesel::table! {
         description -> Text,
         invoice -> Nullable<Text>,
         fee_msat -> Nullable<Int8>,
+        expiry_timestamp -> Nullable<Timestamptz>,
+        payee_pubkey -> Nullable<Text>,
+        failure_reason -> Nullable<Text>,
     }
 }
 
@@ -243,6 +333,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TradeExecutionStateType;
+
+    trade_executions (id) {
+        id -> Int4,
+        order_id -> Uuid,
+        trader_pubkey -> Text,
+        trade_params -> Text,
+        execution_state -> TradeExecutionStateType,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt -> Timestamptz,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    trade_inputs (id) {
+        id -> Int4,
+        trade_id -> Int4,
+        order_id -> Uuid,
+        trade_params -> Text,
+        coordinator_leverage -> Float4,
+        offer_collateral -> Int8,
+        accept_collateral -> Int8,
+        fee_rate -> Int8,
+        temporary_contract_id -> Text,
+        created_at -> Timestamptz,
+        payout_curve_params -> Nullable<Text>,
+        taker_fee_rate -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::ContractSymbolType;
@@ -274,6 +399,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    user_presence (trader_pubkey) {
+        trader_pubkey -> Text,
+        session_id -> Nullable<Uuid>,
+        connected_at -> Nullable<Timestamptz>,
+        last_seen -> Timestamptz,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Int4,
@@ -286,24 +420,35 @@ diesel::table! {
     }
 }
 
-diesel::joinable!(last_outbound_dlc_messages -> dlc_messages (message_hash));
+diesel::joinable!(attestations -> positions (position_id));
 diesel::joinable!(liquidity_request_logs -> liquidity_options (liquidity_option));
+diesel::joinable!(outbound_dlc_messages -> dlc_messages (message_hash));
+diesel::joinable!(trade_inputs -> trades (trade_id));
 diesel::joinable!(trades -> positions (position_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_keys,
+    attestations,
     channels,
     collaborative_reverts,
+    device_sessions,
     dlc_messages,
-    last_outbound_dlc_messages,
+    dlc_store,
     liquidity_options,
     liquidity_request_logs,
     matches,
+    orderbook_events,
+    orderbook_snapshots,
     orders,
+    outbound_dlc_messages,
     payments,
     positions,
     routing_fees,
     spendable_outputs,
+    trade_executions,
+    trade_inputs,
     trades,
     transactions,
+    user_presence,
     users,
 );
@@ -1,19 +1,32 @@
+use crate::config::REDACTED;
 use crate::node::NodeSettings;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
+use bdk::FeeRate;
+use commons::default_taker_fee_rate;
+use commons::AppConfig;
+use commons::JitChannelConfig;
+use lightning::chain::chaininterface::ConfirmationTarget;
 use lightning::util::config::UserConfig;
 use ln_dlc_node::node::LnDlcNodeSettings;
+use ln_dlc_node::PaymentConfig;
+use payout_curve::PayoutCurveParams;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
 use std::path::PathBuf;
+use time::OffsetDateTime;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
 const SETTINGS_FILE_NAME: &str = "coordinator-settings.toml";
 
 /// Top-level settings.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Clone, Serialize)]
 pub struct Settings {
     pub jit_channels_enabled: bool,
     pub new_positions_enabled: bool,
@@ -27,6 +40,9 @@ pub struct Settings {
     //  In sats/kWU (weight unit)
     pub max_allowed_tx_fee_rate_when_opening_channel: Option<u32>,
 
+    /// The terms under which we open JIT channels.
+    pub jit_channel: JitChannelSettings,
+
     pub ln_dlc: LnDlcNodeSettings,
 
     /// We don't want the below doc block be formatted
@@ -56,9 +72,40 @@ pub struct Settings {
     /// *     *     *      *              *       *             *
     pub close_expired_position_scheduler: String,
 
+    /// The hours of the day during which the coordinator proposes rollovers on its own.
+    pub rollover_maintenance_window: RolloverMaintenanceWindow,
+
     /// Min balance to keep in on-chain wallet at all times
     pub min_liquidity_threshold_sats: u64,
 
+    /// Amount of on-chain funds to keep in the wallet for anchor outputs and fee-bumping. Channel
+    /// opens and withdrawals which would dip into the reserve are refused.
+    pub on_chain_reserve_sats: u64,
+
+    /// Fee rates used by the wallet instead of the estimated ones.
+    pub fee_rate_overrides: FeeRateOverrides,
+
+    /// Bearer token required to access the admin API. If set to `None`, the admin API is not
+    /// protected.
+    ///
+    /// The token can only be changed through the settings file, i.e. it is never exposed nor
+    /// updated via the admin API.
+    #[serde(skip_serializing)]
+    pub admin_api_token: Option<String>,
+
+    /// Parameters served to the app, so that we can tune the app without releasing a new version.
+    pub app_config: AppConfig,
+
+    /// Limits for the Lightning payments sent by the coordinator.
+    pub payment: PaymentConfig,
+
+    /// How the payout curves of new DLCs are discretized, trading off the number of CETs against
+    /// the precision of the payouts.
+    pub payout_curve_params: PayoutCurveParams,
+
+    /// The fees we charge the traders for matching their orders.
+    pub fee_schedule: FeeSchedule,
+
     // Location of the settings file in the file system.
     path: PathBuf,
 }
@@ -73,6 +120,7 @@ impl Settings {
 
         let settings =
             toml::from_str::<SettingsFile>(&data).context("Unable to parse settings file")?;
+        settings.validate().context("Invalid settings file")?;
         let settings = Self::from_file(settings, settings_path);
 
         tracing::info!(?settings, "Read settings from file system");
@@ -98,9 +146,33 @@ impl Settings {
                 .max_allowed_tx_fee_rate_when_opening_channel,
             contract_tx_fee_rate: self.contract_tx_fee_rate,
             jit_channels_enabled: self.jit_channels_enabled,
+            jit_channel_min_size_sats: self.jit_channel.min_channel_size_sats,
+            jit_channel_max_size_sats: self.jit_channel.max_channel_size_sats,
+            on_chain_reserve_sats: self.on_chain_reserve_sats,
+            fee_rate_overrides: self.fee_rate_overrides.to_wallet_overrides(),
+            payout_curve_params: self.payout_curve_params,
+            taker_fee_rate: self.fee_schedule.taker_fee_rate,
+        }
+    }
+
+    /// The JIT channel terms, as advertised to the app.
+    pub fn to_jit_channel_config(&self) -> JitChannelConfig {
+        JitChannelConfig {
+            min_channel_size_sats: self.jit_channel.min_channel_size_sats,
+            max_channel_size_sats: self.jit_channel.max_channel_size_sats,
+            liquidity_fee_ppm: self.jit_channel_fee_ppm(),
+            max_tx_fee_rate_sats_per_kwu: self.max_allowed_tx_fee_rate_when_opening_channel,
         }
     }
 
+    /// The proportional fee charged for the payment opening a JIT channel, falling back to our
+    /// regular forwarding fee.
+    pub fn jit_channel_fee_ppm(&self) -> u32 {
+        self.jit_channel
+            .liquidity_fee_ppm
+            .unwrap_or(self.ln_dlc.forwarding_fee_proportional_millionths)
+    }
+
     /// The part of the coordinator settings pertaining to the LDK node.
     pub fn to_ldk_settings(&self) -> UserConfig {
         // Since we currently have to keep the coordinator settings in sync with the tests in
@@ -116,7 +188,11 @@ impl Settings {
     }
 
     pub fn update(&mut self, file: SettingsFile) {
+        let admin_api_token = self.admin_api_token.clone();
+
         *self = Self::from_file(file, self.path.clone());
+
+        self.admin_api_token = admin_api_token;
     }
 
     fn from_file(file: SettingsFile, path: PathBuf) -> Self {
@@ -128,16 +204,82 @@ impl Settings {
             fallback_tx_fee_rate_high_priority: file.fallback_tx_fee_rate_high_priority,
             max_allowed_tx_fee_rate_when_opening_channel: file
                 .max_allowed_tx_fee_rate_when_opening_channel,
+            jit_channel: file.jit_channel,
             ln_dlc: file.ln_dlc,
             rollover_window_open_scheduler: file.rollover_window_open_scheduler,
             rollover_window_close_scheduler: file.rollover_window_close_scheduler,
             close_expired_position_scheduler: file.close_expired_position_scheduler,
+            rollover_maintenance_window: file.rollover_maintenance_window,
             min_liquidity_threshold_sats: file.min_liquidity_threshold_sats,
+            on_chain_reserve_sats: file.on_chain_reserve_sats,
+            fee_rate_overrides: file.fee_rate_overrides,
+            admin_api_token: file.admin_api_token,
+            app_config: file.app_config,
+            payment: file.payment,
+            payout_curve_params: file.payout_curve_params,
+            fee_schedule: file.fee_schedule,
             path,
         }
     }
 }
 
+// The settings are logged, hence the admin API token is redacted in the same way as the secrets of
+// the configuration, see `Config::to_redacted_toml`.
+impl fmt::Debug for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Settings")
+            .field("jit_channels_enabled", &self.jit_channels_enabled)
+            .field("new_positions_enabled", &self.new_positions_enabled)
+            .field("contract_tx_fee_rate", &self.contract_tx_fee_rate)
+            .field(
+                "fallback_tx_fee_rate_normal",
+                &self.fallback_tx_fee_rate_normal,
+            )
+            .field(
+                "fallback_tx_fee_rate_high_priority",
+                &self.fallback_tx_fee_rate_high_priority,
+            )
+            .field(
+                "max_allowed_tx_fee_rate_when_opening_channel",
+                &self.max_allowed_tx_fee_rate_when_opening_channel,
+            )
+            .field("jit_channel", &self.jit_channel)
+            .field("ln_dlc", &self.ln_dlc)
+            .field(
+                "rollover_window_open_scheduler",
+                &self.rollover_window_open_scheduler,
+            )
+            .field(
+                "rollover_window_close_scheduler",
+                &self.rollover_window_close_scheduler,
+            )
+            .field(
+                "close_expired_position_scheduler",
+                &self.close_expired_position_scheduler,
+            )
+            .field(
+                "rollover_maintenance_window",
+                &self.rollover_maintenance_window,
+            )
+            .field(
+                "min_liquidity_threshold_sats",
+                &self.min_liquidity_threshold_sats,
+            )
+            .field("on_chain_reserve_sats", &self.on_chain_reserve_sats)
+            .field("fee_rate_overrides", &self.fee_rate_overrides)
+            .field(
+                "admin_api_token",
+                &self.admin_api_token.as_ref().map(|_| REDACTED),
+            )
+            .field("app_config", &self.app_config)
+            .field("payment", &self.payment)
+            .field("payout_curve_params", &self.payout_curve_params)
+            .field("fee_schedule", &self.fee_schedule)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct SettingsFile {
     jit_channels_enabled: bool,
@@ -149,6 +291,9 @@ pub struct SettingsFile {
 
     max_allowed_tx_fee_rate_when_opening_channel: Option<u32>,
 
+    #[serde(default)]
+    jit_channel: JitChannelSettings,
+
     ln_dlc: LnDlcNodeSettings,
 
     rollover_window_open_scheduler: String,
@@ -156,7 +301,39 @@ pub struct SettingsFile {
 
     close_expired_position_scheduler: String,
 
+    #[serde(default)]
+    rollover_maintenance_window: RolloverMaintenanceWindow,
+
     min_liquidity_threshold_sats: u64,
+
+    #[serde(default)]
+    on_chain_reserve_sats: u64,
+
+    #[serde(default)]
+    fee_rate_overrides: FeeRateOverrides,
+
+    #[serde(default)]
+    admin_api_token: Option<String>,
+
+    #[serde(default)]
+    app_config: AppConfig,
+
+    #[serde(default)]
+    payment: PaymentConfig,
+
+    #[serde(default)]
+    payout_curve_params: PayoutCurveParams,
+
+    #[serde(default)]
+    fee_schedule: FeeSchedule,
+}
+
+impl SettingsFile {
+    pub fn validate(&self) -> Result<()> {
+        self.payout_curve_params
+            .validate()
+            .context("Invalid payout_curve_params")
+    }
 }
 
 impl From<Settings> for SettingsFile {
@@ -169,11 +346,130 @@ impl From<Settings> for SettingsFile {
             fallback_tx_fee_rate_high_priority: value.fallback_tx_fee_rate_high_priority,
             max_allowed_tx_fee_rate_when_opening_channel: value
                 .max_allowed_tx_fee_rate_when_opening_channel,
+            jit_channel: value.jit_channel,
             ln_dlc: value.ln_dlc,
             rollover_window_open_scheduler: value.rollover_window_open_scheduler,
             rollover_window_close_scheduler: value.rollover_window_close_scheduler,
             close_expired_position_scheduler: value.close_expired_position_scheduler,
+            rollover_maintenance_window: value.rollover_maintenance_window,
             min_liquidity_threshold_sats: value.min_liquidity_threshold_sats,
+            on_chain_reserve_sats: value.on_chain_reserve_sats,
+            fee_rate_overrides: value.fee_rate_overrides,
+            admin_api_token: value.admin_api_token,
+            app_config: value.app_config,
+            payment: value.payment,
+            payout_curve_params: value.payout_curve_params,
+            fee_schedule: value.fee_schedule,
+        }
+    }
+}
+
+/// The fees we charge the traders for matching their orders.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// The order-matching fee of the taker, as a fraction of the notional value of the order, e.g.
+    /// `0.003` for 0.3%.
+    #[serde(with = "rust_decimal::serde::float")]
+    pub taker_fee_rate: Decimal,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            taker_fee_rate: default_taker_fee_rate(),
+        }
+    }
+}
+
+/// The terms under which the coordinator opens JIT channels, i.e. channels opened upon
+/// intercepting a payment to a trader without a channel.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct JitChannelSettings {
+    pub min_channel_size_sats: u64,
+    /// If set to `None`, the channel size is only limited by our liquidity.
+    pub max_channel_size_sats: Option<u64>,
+    /// The proportional fee charged for forwarding the payment which opens the channel. If set to
+    /// `None`, our regular forwarding fee is charged.
+    pub liquidity_fee_ppm: Option<u32>,
+}
+
+impl JitChannelSettings {
+    /// Checks whether we are willing to open a channel of the given size.
+    pub fn check_channel_size(&self, channel_size_sats: u64) -> Result<()> {
+        ensure!(
+            channel_size_sats >= self.min_channel_size_sats,
+            "Channel size of {channel_size_sats} sats is below the minimum of {} sats",
+            self.min_channel_size_sats
+        );
+
+        if let Some(max_channel_size_sats) = self.max_channel_size_sats {
+            ensure!(
+                channel_size_sats <= max_channel_size_sats,
+                "Channel size of {channel_size_sats} sats exceeds the maximum of \
+                 {max_channel_size_sats} sats"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Fee rates in sats/vbyte used by the wallet instead of the estimated ones, per confirmation
+/// target. Targets without an override use the estimated fee rate.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+pub struct FeeRateOverrides {
+    pub minimum: Option<f32>,
+    pub background: Option<f32>,
+    pub normal: Option<f32>,
+    pub high_priority: Option<f32>,
+}
+
+impl FeeRateOverrides {
+    fn to_wallet_overrides(self) -> HashMap<ConfirmationTarget, FeeRate> {
+        [
+            (ConfirmationTarget::MempoolMinimum, self.minimum),
+            (ConfirmationTarget::Background, self.background),
+            (ConfirmationTarget::Normal, self.normal),
+            (ConfirmationTarget::HighPriority, self.high_priority),
+        ]
+        .into_iter()
+        .filter_map(|(target, sats_per_vbyte)| {
+            sats_per_vbyte.map(|sats_per_vbyte| (target, FeeRate::from_sat_per_vb(sats_per_vbyte)))
+        })
+        .collect()
+    }
+}
+
+/// The hours of the day (UTC) during which the coordinator proposes rollovers on its own.
+///
+/// The maintenance window only applies within the rollover window. If `start_hour` is greater than
+/// `end_hour`, the maintenance window spans midnight.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RolloverMaintenanceWindow {
+    /// Inclusive.
+    pub start_hour: u8,
+    /// Exclusive.
+    pub end_hour: u8,
+}
+
+impl RolloverMaintenanceWindow {
+    pub fn contains(&self, timestamp: OffsetDateTime) -> bool {
+        let hour = timestamp.hour();
+
+        if self.start_hour <= self.end_hour {
+            self.start_hour <= hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+impl Default for RolloverMaintenanceWindow {
+    /// Propose rollovers at any time of the day.
+    fn default() -> Self {
+        Self {
+            start_hour: 0,
+            end_hour: 24,
         }
     }
 }
@@ -182,16 +478,43 @@ impl From<Settings> for SettingsFile {
 mod tests {
     use super::*;
     use ln_dlc_node::node::GossipSourceConfig;
+    use ln_dlc_node::node::ZeroConfTrustPolicy;
+    use time::macros::datetime;
 
     #[test]
     fn toml_serde_roundtrip() {
-        let original = SettingsFile {
+        let original = dummy_settings_file();
+
+        let serialized = toml::to_string_pretty(&original).unwrap();
+
+        let deserialized = toml::from_str(&serialized).unwrap();
+
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn admin_api_token_is_redacted_in_debug_output() {
+        let settings = Settings::from_file(dummy_settings_file(), PathBuf::new());
+
+        let debug = format!("{settings:?}");
+
+        assert!(!debug.contains("secret"));
+        assert!(debug.contains("admin_api_token: Some(\"<redacted>\")"));
+    }
+
+    fn dummy_settings_file() -> SettingsFile {
+        SettingsFile {
             jit_channels_enabled: true,
             new_positions_enabled: true,
             contract_tx_fee_rate: 1,
             fallback_tx_fee_rate_normal: 2,
             fallback_tx_fee_rate_high_priority: 3,
             max_allowed_tx_fee_rate_when_opening_channel: Some(1),
+            jit_channel: JitChannelSettings {
+                min_channel_size_sats: 10_000,
+                max_channel_size_sats: Some(1_000_000),
+                liquidity_fee_ppm: None,
+            },
             ln_dlc: LnDlcNodeSettings {
                 off_chain_sync_interval: std::time::Duration::from_secs(1),
                 on_chain_sync_interval: std::time::Duration::from_secs(1),
@@ -205,17 +528,101 @@ mod tests {
                 gossip_source_config: GossipSourceConfig::RapidGossipSync {
                     server_url: "foo".to_string(),
                 },
+                mempool_space_url: Some("https://mempool.space".to_string()),
+                zero_conf_trust_policy: ZeroConfTrustPolicy::TrustPeers(vec![
+                    "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655"
+                        .parse()
+                        .unwrap(),
+                ]),
+                watchtower_url: Some("https://tower.10101.finance".to_string()),
+                async_payment_hold_timeout: std::time::Duration::from_secs(300),
             },
             rollover_window_open_scheduler: "foo".to_string(),
             rollover_window_close_scheduler: "bar".to_string(),
             close_expired_position_scheduler: "baz".to_string(),
+            rollover_maintenance_window: RolloverMaintenanceWindow {
+                start_hour: 16,
+                end_hour: 22,
+            },
             min_liquidity_threshold_sats: 2,
+            on_chain_reserve_sats: 3,
+            fee_rate_overrides: FeeRateOverrides {
+                minimum: None,
+                background: Some(1.5),
+                normal: None,
+                high_priority: Some(40.0),
+            },
+            admin_api_token: Some("secret".to_string()),
+            app_config: AppConfig {
+                health_check_interval_secs: Some(30),
+                feature_flags: [("foo".to_string(), true)].into(),
+                min_app_version: Some("1.7.4".to_string()),
+                payment_max_parts: Some(4),
+                payment_max_fee_msat: None,
+                payment_timeout_secs: Some(30),
+                taker_fee_rate: Some(Decimal::new(2, 3)),
+            },
+            payment: PaymentConfig {
+                max_parts: 8,
+                max_fee_msat: Some(100_000),
+                timeout: std::time::Duration::from_secs(60),
+            },
+            payout_curve_params: PayoutCurveParams {
+                rounding_percent: 0.02,
+                discretization_steps: 50,
+                collar_percent: 0.05,
+            },
+            fee_schedule: FeeSchedule {
+                taker_fee_rate: Decimal::new(25, 4),
+            },
+        }
+    }
+
+    #[test]
+    fn jit_channel_size_must_be_within_limits() {
+        let settings = JitChannelSettings {
+            min_channel_size_sats: 10_000,
+            max_channel_size_sats: Some(1_000_000),
+            liquidity_fee_ppm: None,
         };
 
-        let serialized = toml::to_string_pretty(&original).unwrap();
+        assert!(settings.check_channel_size(9_999).is_err());
+        assert!(settings.check_channel_size(10_000).is_ok());
+        assert!(settings.check_channel_size(1_000_000).is_ok());
+        assert!(settings.check_channel_size(1_000_001).is_err());
+    }
 
-        let deserialized = toml::from_str(&serialized).unwrap();
+    #[test]
+    fn maintenance_window_contains_hour() {
+        let window = RolloverMaintenanceWindow {
+            start_hour: 16,
+            end_hour: 22,
+        };
 
-        assert_eq!(original, deserialized);
+        assert!(!window.contains(datetime!(2024-01-19 15:59 UTC)));
+        assert!(window.contains(datetime!(2024-01-19 16:00 UTC)));
+        assert!(window.contains(datetime!(2024-01-19 21:59 UTC)));
+        assert!(!window.contains(datetime!(2024-01-19 22:00 UTC)));
+    }
+
+    #[test]
+    fn maintenance_window_spanning_midnight_contains_hour() {
+        let window = RolloverMaintenanceWindow {
+            start_hour: 22,
+            end_hour: 2,
+        };
+
+        assert!(!window.contains(datetime!(2024-01-19 21:59 UTC)));
+        assert!(window.contains(datetime!(2024-01-19 23:00 UTC)));
+        assert!(window.contains(datetime!(2024-01-20 01:59 UTC)));
+        assert!(!window.contains(datetime!(2024-01-20 02:00 UTC)));
+    }
+
+    #[test]
+    fn default_maintenance_window_contains_every_hour() {
+        let window = RolloverMaintenanceWindow::default();
+
+        assert!(window.contains(datetime!(2024-01-19 00:00 UTC)));
+        assert!(window.contains(datetime!(2024-01-19 23:59 UTC)));
     }
 }
@@ -0,0 +1,160 @@
+//! What a coordinator serves while it waits on standby to become the leader, see
+//! [`crate::leader`].
+//!
+//! A coordinator on standby has no node and no orderbook of its own, hence it only serves the reads
+//! which are answered from the database, e.g. the orders, and the orderbook websocket, whose
+//! messages are relayed from the leader, see [`crate::pubsub`]. Its health check fails, so that a
+//! load balancer routes everything else to the leader.
+
+use crate::api_version;
+use crate::leader::LeaderElection;
+use crate::orderbook::routes::get_order;
+use crate::orderbook::routes::get_orders;
+use crate::orderbook::routes::websocket_handler;
+use crate::orderbook::websocket::WebsocketState;
+use crate::pubsub;
+use crate::routes::get_liveness;
+use crate::routes::index;
+use crate::routes::version;
+use crate::routes::Database;
+use crate::settings::Settings;
+use crate::shutdown::Shutdown;
+use crate::trace;
+use anyhow::Result;
+use axum::extract::FromRef;
+use axum::http::StatusCode;
+use axum::middleware;
+use axum::routing::get;
+use axum::Json;
+use axum::Router;
+use commons::ApiVersion;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::sync::RwLock;
+
+#[derive(Clone)]
+struct StandbyState {
+    /// The websocket is closed once we became the leader, upon which the traders reconnect.
+    websocket: WebsocketState,
+}
+
+impl FromRef<StandbyState> for Database {
+    fn from_ref(state: &StandbyState) -> Self {
+        Database(state.websocket.pool.clone())
+    }
+}
+
+impl FromRef<StandbyState> for WebsocketState {
+    fn from_ref(state: &StandbyState) -> Self {
+        state.websocket.clone()
+    }
+}
+
+/// Wait until we are the leader, serving reads on `http_address` in the meantime.
+///
+/// If no other coordinator is the leader, we become the leader without serving anything. Returns
+/// `false` if the coordinator is shut down while on standby.
+pub async fn run_until_leader(
+    election: &LeaderElection,
+    http_address: SocketAddr,
+    database_url: &str,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    settings: Settings,
+    shutdown: Shutdown,
+) -> Result<bool> {
+    // Even without another coordinator around, a previous leader may have just lost its connection
+    // to the database and still be running.
+    if election.try_acquire().await? {
+        if election.fence(&shutdown).await {
+            tracing::info!("Became the leader");
+            return Ok(true);
+        }
+
+        if shutdown.is_triggered() {
+            return Ok(false);
+        }
+    }
+
+    tracing::info!("Another coordinator is the leader, waiting on standby");
+
+    let stop = Shutdown::new();
+    let (tx_user_feed, _rx) = broadcast::channel(100);
+    let (tx_price_feed, _rx) = broadcast::channel(100);
+
+    let relay = pubsub::relay_from_leader(
+        database_url.to_string(),
+        pool.clone(),
+        tx_price_feed.clone(),
+        &tx_user_feed,
+        stop.clone(),
+    );
+
+    let app = router(StandbyState {
+        websocket: WebsocketState {
+            pool,
+            settings: Arc::new(RwLock::new(settings)),
+            tx_price_feed,
+            tx_user_feed,
+            trading_halt: None,
+            shutdown: stop.clone(),
+        },
+    });
+
+    tracing::debug!("Listening on http://{http_address} on standby");
+
+    let server = tokio::spawn(
+        axum::Server::bind(&http_address)
+            .serve(app.into_make_service())
+            .with_graceful_shutdown({
+                let stop = stop.clone();
+                let shutdown = shutdown.clone();
+                async move {
+                    tokio::select! {
+                        _ = stop.triggered() => {}
+                        _ = shutdown.triggered() => {}
+                    }
+                }
+            }),
+    );
+
+    let is_leader = election.wait(&shutdown).await;
+
+    // The HTTP server has to stop before the leader can listen on the same address.
+    stop.trigger();
+    server.await??;
+    drop(relay);
+
+    Ok(is_leader)
+}
+
+fn router(state: StandbyState) -> Router {
+    let api = Router::new()
+        .route("/version", get(version))
+        .route("/orderbook/orders", get(get_orders))
+        .route("/orderbook/orders/:order_id", get(get_order))
+        .route("/orderbook/websocket", get(websocket_handler));
+
+    Router::new()
+        .route("/", get(index))
+        .nest(ApiVersion::V1.path_prefix(), api.clone())
+        .nest(
+            "/api",
+            api.layer(middleware::from_fn(api_version::deprecate_unversioned)),
+        )
+        .route("/health", get(get_health))
+        .route("/health/live", get(get_liveness))
+        .layer(middleware::from_fn(trace::trace_request))
+        .with_state(state)
+}
+
+/// A coordinator on standby is alive, but not ready to serve the app.
+async fn get_health() -> (StatusCode, Json<String>) {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json("Coordinator is on standby".to_string()),
+    )
+}
@@ -1,22 +1,31 @@
+use anyhow::Context;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
 use lightning::util::persist::KVStore;
 use lightning_persister::fs_store::FilesystemStore;
+use ln_dlc_storage::postgres::PostgresDlcStoreProvider;
 use ln_dlc_storage::sled::SledStorageProvider;
 use ln_dlc_storage::DlcStoreProvider;
 use ln_dlc_storage::KeyValue;
 use std::fs;
 use std::io::Error;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct CoordinatorTenTenOneStorage {
     pub ln_storage: Arc<FilesystemStore>,
-    pub dlc_storage: Arc<SledStorageProvider>,
+    pub dlc_storage: Arc<PostgresDlcStoreProvider>,
     pub data_dir: String,
 }
 
 impl CoordinatorTenTenOneStorage {
-    pub fn new(data_dir: String) -> CoordinatorTenTenOneStorage {
+    pub fn new(
+        data_dir: String,
+        pool: Pool<ConnectionManager<PgConnection>>,
+    ) -> anyhow::Result<CoordinatorTenTenOneStorage> {
         let data_dir = PathBuf::from(data_dir);
 
         if !data_dir.exists() {
@@ -25,15 +34,55 @@ impl CoordinatorTenTenOneStorage {
 
         let ln_storage = Arc::new(FilesystemStore::new(data_dir.clone()));
 
+        let dlc_storage = Arc::new(PostgresDlcStoreProvider::new(pool));
+        import_sled_dlc_storage(&data_dir, &dlc_storage)?;
+
         let data_dir = data_dir.to_string_lossy().to_string();
-        let dlc_storage = Arc::new(SledStorageProvider::new(&data_dir));
 
-        CoordinatorTenTenOneStorage {
+        Ok(CoordinatorTenTenOneStorage {
             ln_storage,
             dlc_storage,
             data_dir,
-        }
+        })
+    }
+}
+
+/// The dlc-manager state used to be kept in a sled database in the data dir. We import it into
+/// Postgres once, before the Postgres store is used for the first time.
+///
+/// The sled database is left untouched, so that it can still be used to roll back.
+fn import_sled_dlc_storage(
+    data_dir: &Path,
+    dlc_storage: &PostgresDlcStoreProvider,
+) -> anyhow::Result<()> {
+    // Sled keeps its data in a file named `db`.
+    if !data_dir.join("db").exists() || !dlc_storage.is_empty()? {
+        return Ok(());
     }
+
+    let sled = SledStorageProvider::new(&data_dir.to_string_lossy());
+    let entries = sled
+        .export()
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.kind,
+                KeyValue {
+                    key: entry.key,
+                    value: entry.value,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let imported = entries.len();
+    dlc_storage
+        .import(entries)
+        .context("Failed to import sled DLC storage into Postgres")?;
+
+    tracing::info!(imported, "Imported sled DLC storage into Postgres");
+
+    Ok(())
 }
 
 impl DlcStoreProvider for CoordinatorTenTenOneStorage {
@@ -0,0 +1,245 @@
+//! Trace IDs, to follow a single trade across the logs of the coordinator.
+//!
+//! Every HTTP request, websocket connection and DLC protocol session runs in a span carrying a
+//! `trace_id`. Work done on behalf of it, e.g. matching an order in the trading task or processing
+//! the DLC messages of the trader, is attached to the same span, so that filtering the logs by
+//! `trace_id` yields everything that happened to one trade.
+//!
+//! If an OpenTelemetry collector is configured, the spans are exported to it as well. The DLC
+//! messages of a protocol are processed long after the request which started it ended, hence their
+//! spans are linked to the span of the request instead of being its children.
+
+use axum::http::header::HeaderName;
+use axum::http::HeaderValue;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use bitcoin::secp256k1::PublicKey;
+use opentelemetry::trace::SpanContext;
+use opentelemetry::trace::TraceContextExt;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::Instrument;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+/// The header with which clients can pass their own trace ID, and in which we return it.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Trace IDs passed by clients longer than this are replaced, to keep the logs readable.
+const MAX_TRACE_ID_LEN: usize = 64;
+
+/// How long we wait for a market order to be executed before we stop linking the DLC protocol to
+/// its submission.
+const ORDER_LINK_TTL: Duration = Duration::from_secs(60 * 60);
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+pub fn new_trace_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// The trace ID of the request being handled by the current task, if any.
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(|trace_id| trace_id.clone()).ok()
+}
+
+/// Handle the request in a span with a trace ID.
+///
+/// The trace ID is taken from the `x-request-id` header if the client set one, and is returned in
+/// the same header of the response.
+pub async fn trace_request<B>(request: Request<B>, next: Next<B>) -> Response {
+    let trace_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_trace_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(new_trace_id);
+
+    let span = tracing::info_span!(
+        "request",
+        trace_id = %trace_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+
+    let mut response = TRACE_ID
+        .scope(trace_id.clone(), next.run(request).instrument(span))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&trace_id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+
+    response
+}
+
+fn is_valid_trace_id(trace_id: &str) -> bool {
+    !trace_id.is_empty()
+        && trace_id.len() <= MAX_TRACE_ID_LEN
+        && trace_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A span with a fresh trace ID, for work which is not triggered by a request, e.g. scheduled jobs.
+pub fn new_span(task: &'static str) -> Span {
+    tracing::info_span!("task", task, trace_id = %new_trace_id())
+}
+
+/// Run `future` under a fresh trace ID, so that DLC protocols started by it share the trace ID.
+pub async fn with_new_trace<F: Future>(task: &'static str, future: F) -> F::Output {
+    let trace_id = new_trace_id();
+    let span = tracing::info_span!("task", task, trace_id = %trace_id);
+
+    TRACE_ID.scope(trace_id, future.instrument(span)).await
+}
+
+/// The trace IDs of the DLC protocols currently running with each trader.
+///
+/// The messages of a DLC protocol arrive independently of the request which started it. Looking up
+/// the trace ID by the trader lets us log them under the trace ID of the trade, and link their
+/// spans to the span of the request when exporting them to OpenTelemetry.
+#[derive(Default)]
+pub struct DlcSessions {
+    sessions: Mutex<HashMap<PublicKey, DlcSession>>,
+    /// The spans in which market orders were submitted, to link the DLC protocol executing them.
+    orders: Mutex<HashMap<Uuid, (SpanContext, Instant)>>,
+}
+
+struct DlcSession {
+    trace_id: String,
+    /// The OpenTelemetry spans which led to the protocol, e.g. the request to execute a trade and
+    /// the request submitting the order.
+    origins: Vec<SpanContext>,
+}
+
+impl DlcSessions {
+    /// Start a DLC protocol session with `trader`, returning the span to run it in.
+    ///
+    /// The session reuses the trace ID of the current request, if there is one. Any session still
+    /// running with the trader is superseded, as only one protocol can run per channel at a time.
+    ///
+    /// If the protocol executes a market order, the span in which the order was submitted is linked
+    /// as well.
+    pub fn start(&self, trader: PublicKey, order_id: Option<Uuid>) -> Span {
+        let trace_id = current_trace_id().unwrap_or_else(new_trace_id);
+
+        let mut origins = vec![Span::current().context().span().span_context().clone()];
+        if let Some((order, _)) = order_id.and_then(|id| self.orders.lock().remove(&id)) {
+            origins.push(order);
+        }
+
+        self.sessions.lock().insert(
+            trader,
+            DlcSession {
+                trace_id: trace_id.clone(),
+                origins,
+            },
+        );
+
+        dlc_protocol_span(trader, &trace_id)
+    }
+
+    /// The span to process a DLC message from `trader` in.
+    ///
+    /// The span is linked to the span which started the session. Messages which do not belong to a
+    /// session started by us, e.g. if the trader started the protocol, get a fresh trace ID.
+    pub fn span(&self, trader: PublicKey) -> Span {
+        match self.sessions.lock().get(&trader) {
+            Some(session) => {
+                let span = dlc_protocol_span(trader, &session.trace_id);
+                for origin in session.origins.iter() {
+                    span.add_link(origin.clone());
+                }
+                span
+            }
+            None => dlc_protocol_span(trader, &new_trace_id()),
+        }
+    }
+
+    /// Remember the current span as the one in which the market order `order_id` was submitted.
+    ///
+    /// Orders which are not executed within [`ORDER_LINK_TTL`] are forgotten.
+    pub fn remember_order(&self, order_id: Uuid) {
+        let context = Span::current().context().span().span_context().clone();
+        let now = Instant::now();
+
+        let mut orders = self.orders.lock();
+        orders.retain(|_, (_, submitted)| now.duration_since(*submitted) < ORDER_LINK_TTL);
+        orders.insert(order_id, (context, now));
+    }
+
+    /// End the DLC protocol session with `trader`, once the protocol completed or failed.
+    pub fn finish(&self, trader: PublicKey) {
+        self.sessions.lock().remove(&trader);
+    }
+}
+
+fn dlc_protocol_span(trader: PublicKey, trace_id: &str) -> Span {
+    tracing::info_span!("dlc_protocol", trace_id, trader_id = %trader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const TRADER: &str = "02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a";
+
+    #[test]
+    fn only_reasonable_trace_ids_are_accepted() {
+        assert!(is_valid_trace_id(&new_trace_id()));
+        assert!(is_valid_trace_id("load_test-42"));
+
+        assert!(!is_valid_trace_id(""));
+        assert!(!is_valid_trace_id("a\nforged log line"));
+        assert!(!is_valid_trace_id(&"a".repeat(MAX_TRACE_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn dlc_session_reuses_trace_id_of_request() {
+        let trader = PublicKey::from_str(TRADER).unwrap();
+        let sessions = DlcSessions::default();
+
+        TRACE_ID
+            .scope("trade-1".to_string(), async {
+                sessions.start(trader, None)
+            })
+            .await;
+
+        assert_eq!(
+            sessions
+                .sessions
+                .lock()
+                .get(&trader)
+                .map(|session| session.trace_id.as_str()),
+            Some("trade-1")
+        );
+
+        sessions.finish(trader);
+        assert!(sessions.sessions.lock().get(&trader).is_none());
+    }
+
+    #[test]
+    fn submitted_order_is_linked_once() {
+        let trader = PublicKey::from_str(TRADER).unwrap();
+        let order_id = Uuid::new_v4();
+        let sessions = DlcSessions::default();
+
+        sessions.remember_order(order_id);
+        sessions.start(trader, Some(order_id));
+
+        assert_eq!(sessions.sessions.lock()[&trader].origins.len(), 2);
+        assert!(sessions.orders.lock().is_empty());
+    }
+}
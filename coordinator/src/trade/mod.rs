@@ -1 +1,4 @@
 pub mod models;
+pub mod replay;
+pub mod statement;
+pub mod stats;
@@ -0,0 +1,392 @@
+//! Deterministic replay of trade executions.
+//!
+//! When executing a trade we record the [`TradeInputs`] from which the DLC was built. Replaying a
+//! trade rebuilds the DLC from these inputs and compares its payouts with the DLC which was
+//! actually signed, so that disputes about settlement amounts can be resolved from data.
+
+use crate::payout_curve;
+use crate::payout_curve::PayoutRange;
+use ::payout_curve::PayoutCurveParams;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::XOnlyPublicKey;
+use commons::order_matching_fee;
+use commons::TradeParams;
+use dlc_manager::contract::contract_input::ContractInput;
+use dlc_manager::contract::contract_input::ContractInputInfo;
+use dlc_manager::contract::contract_input::OracleInput;
+use dlc_manager::contract::offered_contract::OfferedContract;
+use dlc_manager::contract::Contract;
+use dlc_manager::contract::ContractDescriptor;
+use dlc_manager::ContractId;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use trade::pricing;
+
+/// Everything the coordinator needs to deterministically rebuild the DLC of a trade.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeInputs {
+    /// The order and the matches it was filled with, which include the oracle and the expiry.
+    pub trade_params: TradeParams,
+    pub coordinator_leverage: f32,
+    /// The collateral of the coordinator in the DLC.
+    pub offer_collateral: u64,
+    /// The collateral of the trader in the DLC.
+    pub accept_collateral: u64,
+    /// The fee rate of the DLC transactions in sats/vbyte.
+    pub fee_rate: u64,
+    /// How the payout curve of the DLC was discretized.
+    pub payout_curve_params: PayoutCurveParams,
+    /// The order-matching fee rate charged to the trader.
+    pub taker_fee_rate: Decimal,
+}
+
+impl TradeInputs {
+    pub fn coordinator_margin(&self) -> u64 {
+        pricing::margin(
+            self.trade_params.average_execution_price(),
+            self.trade_params.quantity,
+            self.coordinator_leverage,
+        )
+    }
+
+    pub fn trader_margin(&self) -> u64 {
+        pricing::margin(
+            self.trade_params.average_execution_price(),
+            self.trade_params.quantity,
+            self.trade_params.leverage,
+        )
+    }
+
+    pub fn order_matching_fee(&self) -> u64 {
+        order_matching_fee(
+            self.trade_params.quantity,
+            self.trade_params.average_execution_price(),
+            self.taker_fee_rate,
+        )
+        .to_sat()
+    }
+
+    /// How many coins the coordinator will keep outside of the bet. They still go in the DLC, but
+    /// the payout will be at least this much for the coordinator.
+    ///
+    /// The coordinator gets the `order_matching_fee` directly in the collateral reserve.
+    pub fn coordinator_collateral_reserve(&self) -> Result<u64> {
+        let margin_coordinator = self.coordinator_margin();
+        let order_matching_fee = self.order_matching_fee();
+
+        (self.offer_collateral + order_matching_fee)
+            .checked_sub(margin_coordinator)
+            .with_context(|| {
+                format!(
+                    "Coordinator cannot trade with more than their total collateral in the \
+                     DLC channel: margin ({}) > collateral ({}) + order_matching_fee ({})",
+                    margin_coordinator, self.offer_collateral, order_matching_fee
+                )
+            })
+    }
+
+    /// How many coins the trader will keep outside of the bet. They still go in the DLC, but the
+    /// payout will be at least this much for the trader.
+    pub fn trader_collateral_reserve(&self) -> Result<u64> {
+        let margin_trader = self.trader_margin();
+        let order_matching_fee = self.order_matching_fee();
+
+        self.accept_collateral
+            .checked_sub(order_matching_fee)
+            .and_then(|collateral| collateral.checked_sub(margin_trader))
+            .with_context(|| {
+                format!(
+                    "Trader cannot trade with more than their total collateral in the \
+                     DLC channel: margin ({}) + order_matching_fee ({}) > collateral ({})",
+                    margin_trader, order_matching_fee, self.accept_collateral
+                )
+            })
+    }
+
+    pub fn event_id(&self) -> String {
+        let contract_symbol = self.trade_params.contract_symbol.label();
+        let maturity_time = self
+            .trade_params
+            .filled_with
+            .expiry_timestamp
+            .unix_timestamp();
+
+        format!("{contract_symbol}{maturity_time}")
+    }
+
+    pub fn contract_descriptor(&self) -> Result<ContractDescriptor> {
+        payout_curve::build_contract_descriptor(
+            self.trade_params.average_execution_price(),
+            self.coordinator_margin(),
+            self.trader_margin(),
+            self.coordinator_leverage,
+            self.trade_params.leverage,
+            // The coordinator takes the counter-position of the trader.
+            self.trade_params.direction.opposite(),
+            self.coordinator_collateral_reserve()?,
+            self.trader_collateral_reserve()?,
+            self.trade_params.quantity,
+            self.trade_params.contract_symbol,
+            &self.payout_curve_params,
+        )
+        .context("Could not build contract descriptor")
+    }
+
+    /// The contract input to be used for setting up the trade between the trader and the
+    /// coordinator.
+    pub fn contract_input(&self) -> Result<ContractInput> {
+        Ok(ContractInput {
+            offer_collateral: self.offer_collateral,
+            accept_collateral: self.accept_collateral,
+            fee_rate: self.fee_rate,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor: self.contract_descriptor()?,
+                oracles: OracleInput {
+                    public_keys: vec![self.trade_params.filled_with.oracle_pk],
+                    event_id: self.event_id(),
+                    threshold: 1,
+                },
+            }],
+        })
+    }
+}
+
+/// The terms of a DLC which determine the settlement amounts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DlcTerms {
+    pub oracle_pk: XOnlyPublicKey,
+    pub event_id: String,
+    pub offer_collateral_sat: u64,
+    pub accept_collateral_sat: u64,
+    pub fee_rate: u64,
+    pub payouts: Vec<PayoutRange>,
+}
+
+impl DlcTerms {
+    fn from_inputs(inputs: &TradeInputs) -> Result<Self> {
+        let total_collateral = inputs.offer_collateral + inputs.accept_collateral;
+
+        Ok(Self {
+            oracle_pk: inputs.trade_params.filled_with.oracle_pk,
+            event_id: inputs.event_id(),
+            offer_collateral_sat: inputs.offer_collateral,
+            accept_collateral_sat: inputs.accept_collateral,
+            fee_rate: inputs.fee_rate,
+            payouts: payout_curve::payout_ranges(&inputs.contract_descriptor()?, total_collateral)?,
+        })
+    }
+
+    fn from_offered_contract(
+        offered_contract: &OfferedContract,
+        accept_collateral: u64,
+    ) -> Result<Self> {
+        let contract_info = offered_contract
+            .contract_info
+            .first()
+            .context("Missing contract info")?;
+        let oracle_announcement = contract_info
+            .oracle_announcements
+            .first()
+            .context("Missing oracle announcement")?;
+
+        Ok(Self {
+            oracle_pk: oracle_announcement.oracle_public_key,
+            event_id: oracle_announcement.oracle_event.event_id.clone(),
+            offer_collateral_sat: offered_contract.offer_params.collateral,
+            accept_collateral_sat: accept_collateral,
+            fee_rate: offered_contract.fee_rate_per_vb,
+            payouts: payout_curve::payout_ranges(
+                &contract_info.contract_descriptor,
+                offered_contract.total_collateral,
+            )?,
+        })
+    }
+}
+
+/// The result of replaying a trade.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeReplay {
+    pub trade_id: i32,
+    pub temporary_contract_id: String,
+    pub inputs: TradeInputs,
+    /// The terms of the DLC rebuilt from the recorded inputs.
+    pub replayed: DlcTerms,
+    /// The state of the DLC in the DLC store. `None` if the DLC could not be found.
+    pub contract_state: Option<String>,
+    /// The terms of the DLC which was signed. `None` if the DLC was never signed, or if its terms
+    /// are no longer known because it has been closed.
+    pub signed: Option<DlcTerms>,
+    /// The profit and loss of the coordinator, if the DLC has been closed.
+    pub closed_pnl_sat: Option<i64>,
+    /// Whether the replayed terms match the signed ones. `None` if there is nothing to compare
+    /// with.
+    pub matches: Option<bool>,
+}
+
+/// Rebuild the DLC of a trade from its recorded `inputs` and compare it with the `contract`
+/// stored under its `temporary_contract_id`.
+pub fn replay_trade(
+    trade_id: i32,
+    inputs: TradeInputs,
+    temporary_contract_id: ContractId,
+    contract: Option<&Contract>,
+) -> Result<TradeReplay> {
+    let replayed = DlcTerms::from_inputs(&inputs)?;
+
+    let signed = match contract {
+        Some(Contract::Signed(signed_contract))
+        | Some(Contract::Confirmed(signed_contract))
+        | Some(Contract::Refunded(signed_contract)) => {
+            let accepted_contract = &signed_contract.accepted_contract;
+            Some(DlcTerms::from_offered_contract(
+                &accepted_contract.offered_contract,
+                accepted_contract.accept_params.collateral,
+            )?)
+        }
+        Some(Contract::PreClosed(pre_closed_contract)) => {
+            let accepted_contract = &pre_closed_contract.signed_contract.accepted_contract;
+            Some(DlcTerms::from_offered_contract(
+                &accepted_contract.offered_contract,
+                accepted_contract.accept_params.collateral,
+            )?)
+        }
+        _ => None,
+    };
+
+    let closed_pnl_sat = match contract {
+        Some(Contract::Closed(closed_contract)) => Some(closed_contract.pnl),
+        _ => None,
+    };
+
+    let matches = signed.as_ref().map(|signed| signed == &replayed);
+
+    Ok(TradeReplay {
+        trade_id,
+        temporary_contract_id: temporary_contract_id.to_hex(),
+        inputs,
+        replayed,
+        contract_state: contract.map(contract_state_name),
+        signed,
+        closed_pnl_sat,
+        matches,
+    })
+}
+
+fn contract_state_name(contract: &Contract) -> String {
+    match contract {
+        Contract::Offered(_) => "Offered",
+        Contract::Accepted(_) => "Accepted",
+        Contract::Signed(_) => "Signed",
+        Contract::Confirmed(_) => "Confirmed",
+        Contract::PreClosed(_) => "PreClosed",
+        Contract::Closed(_) => "Closed",
+        Contract::Refunded(_) => "Refunded",
+        Contract::FailedAccept(_) => "FailedAccept",
+        Contract::FailedSign(_) => "FailedSign",
+        Contract::Rejected(_) => "Rejected",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::PublicKey;
+    use commons::default_taker_fee_rate;
+    use commons::order_matching_fee_taker;
+    use commons::FilledWith;
+    use commons::Match;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+    use time::macros::datetime;
+    use trade::ContractSymbol;
+    use trade::Direction;
+    use uuid::Uuid;
+
+    #[test]
+    fn replaying_inputs_of_new_dlc_channel_is_deterministic() {
+        let inputs = dummy_inputs();
+
+        let first = DlcTerms::from_inputs(&inputs).unwrap();
+        let second = DlcTerms::from_inputs(&inputs).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first.event_id, "btcusd1706788800");
+        assert_eq!(
+            first.offer_collateral_sat + first.accept_collateral_sat,
+            inputs.coordinator_margin() + inputs.trader_margin() + inputs.order_matching_fee()
+        );
+    }
+
+    #[test]
+    fn reserves_of_new_dlc_channel_only_contain_order_matching_fee() {
+        let inputs = dummy_inputs();
+
+        assert_eq!(
+            inputs.coordinator_collateral_reserve().unwrap(),
+            inputs.order_matching_fee()
+        );
+        assert_eq!(inputs.trader_collateral_reserve().unwrap(), 0);
+    }
+
+    #[test]
+    fn insufficient_collateral_cannot_be_replayed() {
+        let inputs = TradeInputs {
+            accept_collateral: 1_000,
+            ..dummy_inputs()
+        };
+
+        assert!(inputs.trader_collateral_reserve().is_err());
+        assert!(inputs.contract_input().is_err());
+    }
+
+    /// The inputs of a trade which opens a new DLC channel.
+    fn dummy_inputs() -> TradeInputs {
+        let trade_params = TradeParams {
+            pubkey: PublicKey::from_str(
+                "02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a",
+            )
+            .unwrap(),
+            contract_symbol: ContractSymbol::BtcUsd,
+            leverage: 2.0,
+            quantity: 100.0,
+            direction: Direction::Long,
+            filled_with: FilledWith {
+                order_id: Uuid::new_v4(),
+                expiry_timestamp: datetime!(2024-02-01 12:00 UTC),
+                oracle_pk: XOnlyPublicKey::from_str(
+                    "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0",
+                )
+                .unwrap(),
+                matches: vec![Match {
+                    id: Uuid::new_v4(),
+                    order_id: Uuid::new_v4(),
+                    quantity: dec!(100),
+                    pubkey: PublicKey::from_str(
+                        "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+                    )
+                    .unwrap(),
+                    execution_price: dec!(40_000),
+                }],
+            },
+        };
+
+        let coordinator_leverage = 2.0;
+        let price = trade_params.average_execution_price();
+        let coordinator_margin = pricing::margin(price, 100.0, coordinator_leverage);
+        let trader_margin = pricing::margin(price, 100.0, 2.0);
+        let order_matching_fee = order_matching_fee_taker(100.0, price).to_sat();
+
+        TradeInputs {
+            trade_params,
+            coordinator_leverage,
+            offer_collateral: coordinator_margin,
+            accept_collateral: trader_margin + order_matching_fee,
+            fee_rate: 1,
+            payout_curve_params: PayoutCurveParams::default(),
+            taker_fee_rate: default_taker_fee_rate(),
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::db;
+use crate::position::models::Position;
+use crate::position::models::PositionState;
+use crate::trade::models::Trade;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use commons::default_taker_fee_rate;
+use commons::order_matching_fee;
+use diesel::PgConnection;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StatementEntryKind {
+    Trade,
+    PositionClosed,
+}
+
+/// A single booking on the account statement of a trader.
+///
+/// All amounts are from the trader's point of view.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatementEntry {
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub kind: StatementEntryKind,
+    pub position_id: i32,
+    pub contract_symbol: ContractSymbol,
+    pub direction: Direction,
+    pub quantity: f32,
+    /// The execution price of a trade or the closing price of a position.
+    pub price: Option<f32>,
+    pub realized_pnl_sat: i64,
+    pub fee_sat: u64,
+    pub funding_sat: i64,
+}
+
+/// The account statement of a trader for the period `[from, to)`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    pub trader_pubkey: PublicKey,
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    pub realized_pnl_sat: i64,
+    pub fees_sat: u64,
+    /// We do not charge funding fees yet, hence this is always zero.
+    pub funding_sat: i64,
+    pub entries: Vec<StatementEntry>,
+}
+
+impl Statement {
+    /// Serialize the entries of the statement as CSV, one row per entry.
+    pub fn to_csv(&self) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        for entry in self.entries.iter() {
+            writer
+                .serialize(entry)
+                .context("Failed to serialize statement entry")?;
+        }
+
+        let csv = writer.into_inner().context("Failed to write statement")?;
+
+        Ok(String::from_utf8(csv)?)
+    }
+}
+
+pub fn get_statement(
+    conn: &mut PgConnection,
+    trader_pubkey: PublicKey,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+) -> Result<Statement> {
+    let trades = db::trades::get_trades_by_trader(conn, trader_pubkey, from, to)?;
+    let closed_positions =
+        db::positions::Position::get_closed_positions_by_trader(conn, trader_pubkey, from, to)?;
+
+    // The fee schedule can change, hence the fee of every trade is computed with the fee rate
+    // recorded when the trade was executed.
+    let mut taker_fee_rates = HashMap::new();
+    for trade in trades.iter() {
+        if let Some(recorded) = db::trade_inputs::get_by_trade_id(conn, trade.id)? {
+            taker_fee_rates.insert(trade.id, recorded.inputs.taker_fee_rate);
+        }
+    }
+
+    Ok(compute_statement(
+        trader_pubkey,
+        from,
+        to,
+        &trades,
+        &taker_fee_rates,
+        &closed_positions,
+    ))
+}
+
+/// Trades without a recorded fee rate, i.e. trades executed before the inputs of trades were
+/// recorded, were charged the default fee rate.
+fn compute_statement(
+    trader_pubkey: PublicKey,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+    trades: &[Trade],
+    taker_fee_rates: &HashMap<i32, Decimal>,
+    closed_positions: &[Position],
+) -> Statement {
+    let trade_entries = trades.iter().map(|trade| {
+        let price = Decimal::try_from(trade.average_price).unwrap_or(Decimal::ZERO);
+        let taker_fee_rate = taker_fee_rates
+            .get(&trade.id)
+            .copied()
+            .unwrap_or_else(default_taker_fee_rate);
+        let fee = order_matching_fee(trade.quantity, price, taker_fee_rate);
+
+        StatementEntry {
+            timestamp: trade.timestamp,
+            kind: StatementEntryKind::Trade,
+            position_id: trade.position_id,
+            contract_symbol: trade.contract_symbol,
+            direction: trade.direction,
+            quantity: trade.quantity,
+            price: Some(trade.average_price),
+            realized_pnl_sat: 0,
+            fee_sat: fee.to_sat(),
+            funding_sat: 0,
+        }
+    });
+
+    let position_entries = closed_positions.iter().map(|position| {
+        // The realized pnl is stored from the coordinator's point of view. As the DLC is
+        // zero-sum, the trader's pnl is the opposite of ours.
+        let realized_pnl_sat = match position.position_state {
+            PositionState::Closed { pnl } => -pnl,
+            _ => 0,
+        };
+
+        StatementEntry {
+            timestamp: position.update_timestamp,
+            kind: StatementEntryKind::PositionClosed,
+            position_id: position.id,
+            contract_symbol: position.contract_symbol,
+            direction: position.direction,
+            quantity: position.quantity,
+            price: position.closing_price,
+            realized_pnl_sat,
+            fee_sat: 0,
+            funding_sat: 0,
+        }
+    });
+
+    let mut entries = trade_entries.chain(position_entries).collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.timestamp);
+
+    Statement {
+        trader_pubkey,
+        from,
+        to,
+        realized_pnl_sat: entries.iter().map(|entry| entry.realized_pnl_sat).sum(),
+        fees_sat: entries.iter().map(|entry| entry.fee_sat).sum(),
+        funding_sat: entries.iter().map(|entry| entry.funding_sat).sum(),
+        entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lightning::ln::PaymentHash;
+    use std::str::FromStr;
+    use time::Duration;
+
+    #[test]
+    fn statement_sums_up_pnl_and_fees() {
+        let now = OffsetDateTime::now_utc();
+        let trades = vec![
+            dummy_trade(now - Duration::hours(2), Direction::Long),
+            dummy_trade(now - Duration::hours(1), Direction::Short),
+        ];
+        let closed_positions = vec![dummy_closed_position(now - Duration::minutes(59), -5_000)];
+
+        let statement = compute_statement(
+            trader_pubkey(),
+            now - Duration::days(1),
+            now,
+            &trades,
+            &HashMap::new(),
+            &closed_positions,
+        );
+
+        assert_eq!(statement.entries.len(), 3);
+        assert_eq!(
+            statement.entries[2].kind,
+            StatementEntryKind::PositionClosed
+        );
+        assert_eq!(statement.realized_pnl_sat, 5_000);
+        assert_eq!(statement.fees_sat, 2 * 750);
+        assert_eq!(statement.funding_sat, 0);
+    }
+
+    #[test]
+    fn statement_uses_recorded_fee_rate() {
+        let now = OffsetDateTime::now_utc();
+        let trades = vec![dummy_trade(now, Direction::Long)];
+        let taker_fee_rates =
+            HashMap::from([(trades[0].id, default_taker_fee_rate() * Decimal::TWO)]);
+
+        let statement = compute_statement(
+            trader_pubkey(),
+            now - Duration::days(1),
+            now,
+            &trades,
+            &taker_fee_rates,
+            &[],
+        );
+
+        assert_eq!(statement.fees_sat, 1_500);
+    }
+
+    #[test]
+    fn statement_to_csv() {
+        let now = OffsetDateTime::now_utc();
+        let trades = vec![dummy_trade(now, Direction::Long)];
+
+        let statement = compute_statement(
+            trader_pubkey(),
+            now - Duration::days(1),
+            now,
+            &trades,
+            &HashMap::new(),
+            &[],
+        );
+        let csv = statement.to_csv().unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("timestamp,kind,position_id,contract_symbol,direction,quantity,price,realized_pnl_sat,fee_sat,funding_sat")
+        );
+        assert!(lines.next().unwrap().contains(",Trade,1,"));
+        assert_eq!(lines.next(), None);
+    }
+
+    fn trader_pubkey() -> PublicKey {
+        PublicKey::from_str("02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655")
+            .unwrap()
+    }
+
+    fn dummy_trade(timestamp: OffsetDateTime, direction: Direction) -> Trade {
+        Trade {
+            id: 0,
+            position_id: 1,
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_pubkey: trader_pubkey(),
+            quantity: 100.0,
+            trader_leverage: 2.0,
+            collateral: 1_000,
+            direction,
+            average_price: 40_000.0,
+            dlc_expiry_timestamp: None,
+            timestamp,
+            fee_payment_hash: PaymentHash([0; 32]),
+        }
+    }
+
+    fn dummy_closed_position(update_timestamp: OffsetDateTime, pnl: i64) -> Position {
+        Position {
+            id: 1,
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_leverage: 2.0,
+            quantity: 100.0,
+            direction: Direction::Long,
+            average_entry_price: 40_000.0,
+            liquidation_price: 20_000.0,
+            position_state: PositionState::Closed { pnl },
+            coordinator_margin: 125_000,
+            creation_timestamp: update_timestamp - Duration::hours(2),
+            expiry_timestamp: update_timestamp + Duration::days(7),
+            update_timestamp,
+            trader: trader_pubkey(),
+            coordinator_leverage: 2.0,
+            temporary_contract_id: None,
+            closing_price: Some(41_000.0),
+            trader_margin: 125_000,
+            stable: false,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::db;
+use crate::position::models::Position;
+use crate::trade::models::Trade;
+use anyhow::Result;
+use commons::MarketStats;
+use diesel::PgConnection;
+use time::Duration;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+
+/// Compute the [`MarketStats`] of the given contract symbol from the executed trades and the open
+/// positions.
+pub fn get_market_stats(
+    conn: &mut PgConnection,
+    contract_symbol: ContractSymbol,
+) -> Result<MarketStats> {
+    let now = OffsetDateTime::now_utc();
+
+    let trades = db::trades::get_trades_since(conn, contract_symbol, now - Duration::days(7))?;
+    let latest_trade = db::trades::get_latest_trade(conn, contract_symbol)?;
+    let open_positions = db::positions::Position::get_all_open_positions(conn)?;
+
+    Ok(compute_market_stats(
+        contract_symbol,
+        &trades,
+        latest_trade.as_ref(),
+        &open_positions,
+        now,
+    ))
+}
+
+fn compute_market_stats(
+    contract_symbol: ContractSymbol,
+    trades: &[Trade],
+    latest_trade: Option<&Trade>,
+    open_positions: &[Position],
+    now: OffsetDateTime,
+) -> MarketStats {
+    let trades = trades
+        .iter()
+        .filter(|trade| trade.contract_symbol == contract_symbol)
+        .collect::<Vec<_>>();
+
+    let trades_since = |window: Duration| {
+        trades
+            .iter()
+            .filter(move |trade| trade.timestamp >= now - window)
+    };
+
+    let volume = |window: Duration| {
+        trades_since(window)
+            .map(|trade| trade.quantity)
+            .sum::<f32>()
+    };
+
+    let high_24h = trades_since(Duration::hours(24))
+        .map(|trade| trade.average_price)
+        .reduce(f32::max);
+    let low_24h = trades_since(Duration::hours(24))
+        .map(|trade| trade.average_price)
+        .reduce(f32::min);
+
+    let open_interest = open_positions
+        .iter()
+        .filter(|position| position.contract_symbol == contract_symbol)
+        .map(|position| position.quantity)
+        .sum();
+
+    MarketStats {
+        contract_symbol,
+        volume_1h: volume(Duration::hours(1)),
+        volume_24h: volume(Duration::hours(24)),
+        volume_7d: volume(Duration::days(7)),
+        high_24h,
+        low_24h,
+        last_price: latest_trade.map(|trade| trade.average_price),
+        open_interest,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::PublicKey;
+    use lightning::ln::PaymentHash;
+    use std::str::FromStr;
+    use trade::Direction;
+
+    #[test]
+    fn market_stats_from_trades() {
+        let now = OffsetDateTime::now_utc();
+        let trades = vec![
+            dummy_trade(now - Duration::days(3), 100.0, 40_000.0),
+            dummy_trade(now - Duration::hours(5), 50.0, 42_000.0),
+            dummy_trade(now - Duration::minutes(30), 20.0, 41_000.0),
+        ];
+
+        let stats = compute_market_stats(ContractSymbol::BtcUsd, &trades, trades.last(), &[], now);
+
+        assert_eq!(stats.volume_1h, 20.0);
+        assert_eq!(stats.volume_24h, 70.0);
+        assert_eq!(stats.volume_7d, 170.0);
+        assert_eq!(stats.high_24h, Some(42_000.0));
+        assert_eq!(stats.low_24h, Some(41_000.0));
+        assert_eq!(stats.last_price, Some(41_000.0));
+        assert_eq!(stats.open_interest, 0.0);
+    }
+
+    #[test]
+    fn market_stats_without_trades() {
+        let stats = compute_market_stats(
+            ContractSymbol::BtcUsd,
+            &[],
+            None,
+            &[],
+            OffsetDateTime::now_utc(),
+        );
+
+        assert_eq!(stats.volume_7d, 0.0);
+        assert_eq!(stats.high_24h, None);
+        assert_eq!(stats.low_24h, None);
+        assert_eq!(stats.last_price, None);
+    }
+
+    fn dummy_trade(timestamp: OffsetDateTime, quantity: f32, average_price: f32) -> Trade {
+        Trade {
+            id: 0,
+            position_id: 0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            trader_pubkey: PublicKey::from_str(
+                "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+            )
+            .unwrap(),
+            quantity,
+            trader_leverage: 2.0,
+            collateral: 1_000,
+            direction: Direction::Long,
+            average_price,
+            dlc_expiry_timestamp: None,
+            timestamp,
+            fee_payment_hash: PaymentHash([0; 32]),
+        }
+    }
+}
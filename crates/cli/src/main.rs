@@ -0,0 +1,390 @@
+//! A headless 10101 app.
+//!
+//! Drives the same native API as the Flutter app, which makes it possible to trade from a terminal
+//! and to script end-to-end scenarios. Every command starts the node, waits for it to be ready and
+//! stops it again once the command has completed.
+
+// The CLI reports its results on stdout.
+#![allow(clippy::print_stdout)]
+
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use native::api;
+use native::api::ContractSymbol;
+use native::api::WalletInfo;
+use native::event::subscriber::Subscriber;
+use native::event::EventInternal;
+use native::event::EventType;
+use native::trade::order::api::NewOrder;
+use native::trade::order::api::OrderState;
+use native::trade::order::api::OrderType;
+use native::trade::position::api::Position;
+use native::trade::position::api::PositionState;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::level_filters::LevelFilter;
+
+/// How often we check whether an order or a position has reached the expected state.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Parser)]
+#[clap(
+    name = "10101-cli",
+    about = "Run the 10101 app without a user interface"
+)]
+struct Opts {
+    /// Where to store the app data and the seed.
+    #[clap(long, default_value = "./10101-cli")]
+    data_dir: PathBuf,
+
+    /// One of `bitcoin`, `testnet`, `signet` or `regtest`.
+    #[clap(long, default_value = "regtest")]
+    network: String,
+
+    #[clap(
+        long,
+        default_value = "02dd6abec97f9a748bf76ad502b004ce05d1b2d1f43a9e76bd7d85e767ffb022c9"
+    )]
+    coordinator_pubkey: String,
+
+    /// The host of the coordinator.
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+
+    #[clap(long, default_value = "9045")]
+    p2p_port: u16,
+
+    #[clap(long, default_value = "8000")]
+    http_port: u16,
+
+    #[clap(long, default_value = "http://127.0.0.1:3000")]
+    esplora: String,
+
+    #[clap(long, default_value = "http://127.0.0.1:8081")]
+    oracle_endpoint: String,
+
+    #[clap(
+        long,
+        default_value = "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0"
+    )]
+    oracle_pubkey: String,
+
+    /// The Rapid Gossip Sync server. If not set, the gossip is synced over the p2p network.
+    #[clap(long)]
+    rgs_server_url: Option<String>,
+
+    /// Unlocks the keystore if a PIN has been set.
+    #[clap(long)]
+    pin: Option<String>,
+
+    /// How long to wait for the node to be ready, and for orders to be executed, in seconds.
+    #[clap(long, default_value = "120")]
+    timeout: u64,
+
+    /// Logs are written to stderr.
+    #[clap(long, default_value = "warn")]
+    log_level: LevelFilter,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start the node, creating a new seed if there is none yet, and print its id.
+    Init,
+    /// Print the on-chain and off-chain balances.
+    Balance,
+    /// Print the open positions.
+    Positions,
+    /// Submit a market order and wait for the resulting position to be open.
+    Order {
+        #[clap(value_enum)]
+        direction: Direction,
+        /// The number of contracts.
+        quantity: f32,
+        #[clap(long, default_value = "2.0")]
+        leverage: f32,
+        /// Open a stable position.
+        #[clap(long)]
+        stable: bool,
+    },
+    /// Close a position and wait for it to be closed.
+    Close {
+        /// Can be omitted if there is only one position.
+        position_id: Option<String>,
+    },
+    /// Keep the node running until the process is terminated.
+    Run,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Direction {
+    Long,
+    Short,
+}
+
+impl From<Direction> for api::Direction {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Long => api::Direction::Long,
+            Direction::Short => api::Direction::Short,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let opts = Opts::parse();
+
+    native::logger::init_tracing(opts.log_level, false)?;
+
+    let timeout = Duration::from_secs(opts.timeout);
+    let events = start(&opts, timeout)?;
+
+    match opts.command {
+        Command::Init => {
+            println!("{}", api::get_node_id().0);
+        }
+        Command::Balance => {
+            let wallet_info = balance(&events, timeout)?;
+
+            println!("on-chain:  {} sats", wallet_info.balances.on_chain);
+            println!("off-chain: {} sats", wallet_info.balances.off_chain);
+        }
+        Command::Positions => {
+            for position in api::get_positions()? {
+                print_position(&position);
+            }
+        }
+        Command::Order {
+            direction,
+            quantity,
+            leverage,
+            stable,
+        } => {
+            let position = submit_order(direction, quantity, leverage, stable, timeout)?;
+
+            print_position(&position);
+        }
+        Command::Close { position_id } => {
+            let position_id = close_position(position_id, timeout)?;
+
+            println!("Closed position {position_id}");
+        }
+        Command::Run => loop {
+            std::thread::park();
+        },
+    }
+
+    Ok(())
+}
+
+/// Start the node and wait for it to be ready.
+///
+/// Returns the events published by the node which are relevant to the CLI.
+fn start(opts: &Opts, timeout: Duration) -> Result<mpsc::Receiver<EventInternal>> {
+    let app_dir = opts.data_dir.join("app");
+    let seed_dir = opts.data_dir.join("seed");
+    for dir in [&app_dir, &seed_dir] {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+
+    let config = native::config::api::Config {
+        coordinator_pubkey: opts.coordinator_pubkey.clone(),
+        esplora_endpoint: opts.esplora.clone(),
+        host: opts.host.clone(),
+        p2p_port: opts.p2p_port,
+        http_port: opts.http_port,
+        network: opts.network.clone(),
+        oracle_endpoint: opts.oracle_endpoint.clone(),
+        oracle_pubkey: opts.oracle_pubkey.clone(),
+        health_check_interval_secs: None,
+        rgs_server_url: opts.rgs_server_url.clone(),
+        watchtower_url: None,
+        backup_url: None,
+    };
+
+    let seed_dir = path_to_string(&seed_dir)?;
+    api::set_config(config, path_to_string(&app_dir)?, seed_dir.clone())?;
+
+    let (tx, rx) = mpsc::channel();
+    native::event::subscribe(CliSubscriber(tx));
+
+    api::run_in_test(seed_dir).context("Failed to start the node")?;
+
+    wait_for_event(&rx, timeout, |event| match event {
+        EventInternal::Init(message) if message == "10101 is ready." => Some(()),
+        _ => None,
+    })
+    .context("Node did not become ready")?;
+
+    if let Some(pin) = opts.pin.clone() {
+        api::unlock(pin)?;
+    }
+
+    Ok(rx)
+}
+
+/// Sync the wallet and wait for the updated balances.
+fn balance(events: &mpsc::Receiver<EventInternal>, timeout: Duration) -> Result<WalletInfo> {
+    // Only the balances published after the sync are up to date.
+    while events.try_recv().is_ok() {}
+
+    api::refresh_wallet_info()?;
+
+    wait_for_event(events, timeout, |event| match event {
+        EventInternal::WalletInfoUpdateNotification(wallet_info) => Some(wallet_info.clone()),
+        _ => None,
+    })
+    .context("Did not receive balances")
+}
+
+fn submit_order(
+    direction: Direction,
+    quantity: f32,
+    leverage: f32,
+    stable: bool,
+    timeout: Duration,
+) -> Result<Position> {
+    let known_positions = api::get_positions()?
+        .into_iter()
+        .map(|position| position.id)
+        .collect::<Vec<_>>();
+
+    let order_id = api::submit_order(NewOrder {
+        leverage,
+        quantity,
+        contract_symbol: ContractSymbol::BtcUsd,
+        direction: direction.into(),
+        order_type: Box::new(OrderType::Market),
+        stable,
+    })?;
+
+    tracing::info!(%order_id, "Submitted order");
+
+    poll(timeout, || {
+        let order = api::get_orders()?
+            .into_iter()
+            .find(|order| order.id == order_id)
+            .context("Submitted order not found")?;
+
+        if let OrderState::Failed | OrderState::Rejected = order.state {
+            bail!("Order {order_id} failed: {:?}", order.failure_reason);
+        }
+
+        let position = api::get_positions()?.into_iter().find(|position| {
+            !known_positions.contains(&position.id)
+                && position.position_state == PositionState::Open
+        });
+
+        Ok(position)
+    })
+    .with_context(|| format!("Order {order_id} was not executed"))
+}
+
+fn close_position(position_id: Option<String>, timeout: Duration) -> Result<String> {
+    let position_id = match position_id {
+        Some(position_id) => position_id,
+        None => {
+            let positions = api::get_positions()?;
+            match positions.as_slice() {
+                [position] => position.id.clone(),
+                [] => bail!("There is no position to close"),
+                _ => bail!("There are several positions, please specify which one to close"),
+            }
+        }
+    };
+
+    let order_id = api::close_position(position_id.clone())?;
+
+    tracing::info!(%order_id, %position_id, "Submitted closing order");
+
+    poll(timeout, || {
+        let is_closed = !api::get_positions()?
+            .iter()
+            .any(|position| position.id == position_id);
+
+        Ok(is_closed.then_some(()))
+    })
+    .with_context(|| format!("Position {position_id} was not closed"))?;
+
+    Ok(position_id)
+}
+
+fn print_position(position: &Position) {
+    println!(
+        "{} {:?} {:?} {} contracts at {} with leverage {}, liquidation at {}, {:?}",
+        position.id,
+        position.contract_symbol,
+        position.direction,
+        position.quantity,
+        position.average_entry_price,
+        position.leverage,
+        position.liquidation_price,
+        position.position_state,
+    );
+}
+
+/// Call `f` until it returns a value or fails.
+fn poll<T>(timeout: Duration, mut f: impl FnMut() -> Result<Option<T>>) -> Result<T> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(value) = f()? {
+            return Ok(value);
+        }
+
+        if Instant::now() >= deadline {
+            bail!("Timed out after {timeout:?}");
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn wait_for_event<T>(
+    events: &mpsc::Receiver<EventInternal>,
+    timeout: Duration,
+    f: impl Fn(&EventInternal) -> Option<T>,
+) -> Result<T> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let event = events
+            .recv_timeout(remaining)
+            .with_context(|| format!("Timed out after {timeout:?}"))?;
+
+        if let Some(value) = f(&event) {
+            return Ok(value);
+        }
+    }
+}
+
+fn path_to_string(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(str::to_string)
+        .with_context(|| format!("Invalid path {}", path.display()))
+}
+
+#[derive(Clone)]
+struct CliSubscriber(mpsc::Sender<EventInternal>);
+
+impl Subscriber for CliSubscriber {
+    fn notify(&self, event: &EventInternal) {
+        // The receiver is only dropped when the CLI exits.
+        let _ = self.0.send(event.clone());
+    }
+
+    fn events(&self) -> Vec<EventType> {
+        vec![EventType::Init, EventType::WalletInfoUpdateNotification]
+    }
+}
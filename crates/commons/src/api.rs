@@ -0,0 +1,121 @@
+use anyhow::bail;
+use secp256k1::PublicKey;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+/// A version of the coordinator's public REST API.
+///
+/// Every version is served under its own path prefix, e.g. `/api/v1`. The types of this crate
+/// which are exchanged with the coordinator form the contract of a version, so that clients do not
+/// depend on the coordinator's internal models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    /// The version new clients should use.
+    pub const LATEST: ApiVersion = ApiVersion::V1;
+
+    /// All versions served by the coordinator, oldest first.
+    pub const ALL: [ApiVersion; 1] = [ApiVersion::V1];
+
+    /// The path prefix of all endpoints of this version.
+    pub fn path_prefix(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+        }
+    }
+
+    /// The path of an `endpoint` of this version, e.g. `/api/v1/orderbook/orders` for
+    /// `/orderbook/orders`.
+    pub fn path(&self, endpoint: &str) -> String {
+        format!("{}{endpoint}", self.path_prefix())
+    }
+
+    /// The websocket subprotocol with which a client asks for this version of the messages sent
+    /// via the orderbook websocket.
+    pub fn websocket_protocol(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "10101.v1",
+        }
+    }
+
+    pub fn from_websocket_protocol(protocol: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|version| version.websocket_protocol() == protocol.trim())
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiVersion::V1 => "v1".fmt(f),
+        }
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" => Ok(ApiVersion::V1),
+            _ => bail!("Unknown API version: {s}"),
+        }
+    }
+}
+
+/// The version of the coordinator.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VersionInfo {
+    pub version: String,
+    pub commit_hash: String,
+    pub branch: String,
+    /// The versions of the REST API served by the coordinator.
+    #[serde(default)]
+    pub api_versions: Vec<ApiVersion>,
+}
+
+/// The identity of the coordinator's node.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct NodeInfo {
+    pub pubkey: PublicKey,
+    pub address: SocketAddr,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_path_is_prefixed_with_version() {
+        assert_eq!(
+            ApiVersion::V1.path("/orderbook/orders"),
+            "/api/v1/orderbook/orders"
+        );
+    }
+
+    #[test]
+    fn api_version_roundtrips() {
+        for version in ApiVersion::ALL {
+            assert_eq!(version.to_string().parse::<ApiVersion>().unwrap(), version);
+            assert_eq!(
+                ApiVersion::from_websocket_protocol(version.websocket_protocol()),
+                Some(version)
+            );
+            assert_eq!(
+                serde_json::to_string(&version).unwrap(),
+                format!("\"{version}\"")
+            );
+        }
+
+        assert!(ApiVersion::from_websocket_protocol("10101.v0").is_none());
+        assert!("v0".parse::<ApiVersion>().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::signature::create_sign_message;
+use crate::signature::Signature;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The permissions granted to an API key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Read the trader's position and account statement.
+    Read,
+    /// Post orders on behalf of the trader.
+    Trade,
+}
+
+/// A request to issue an API key bound to the pubkey of the trader who signed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiKey {
+    pub label: String,
+    pub scopes: Vec<ApiKeyScope>,
+    /// Requests are only accepted for a short time after they have been created, so that an
+    /// intercepted request can't be used to issue further keys.
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    /// A signature of [`CreateApiKey::message`] using the trader's node key.
+    pub signature: Signature,
+}
+
+impl CreateApiKey {
+    /// The message to be signed by the trader.
+    pub fn message(
+        label: &str,
+        scopes: &[ApiKeyScope],
+        timestamp: OffsetDateTime,
+    ) -> anyhow::Result<Vec<u8>> {
+        let scopes = serde_json::to_string(scopes)?;
+        Ok(format!("{label}|{scopes}|{}", timestamp.unix_timestamp()).into_bytes())
+    }
+
+    /// Verifies that the request was signed by the trader it is issued for.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let message = Self::message(&self.label, &self.scopes, self.timestamp)?;
+        let message = create_sign_message(message);
+        self.signature
+            .signature
+            .verify(&message, &self.signature.pubkey)?;
+        Ok(())
+    }
+}
+
+/// A newly issued API key. The key itself is only returned once and can't be recovered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyCreated {
+    pub id: Uuid,
+    pub key: String,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+/// A request to revoke an API key of the trader who signed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeApiKey {
+    pub id: Uuid,
+    /// A signature of the key id using the trader's node key.
+    pub signature: Signature,
+}
+
+impl RevokeApiKey {
+    /// Verifies that the revocation was requested by the trader who signed it.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let message = self.id.to_string().as_bytes().to_vec();
+        let message = create_sign_message(message);
+        self.signature
+            .signature
+            .verify(&message, &self.signature.pubkey)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::Secp256k1;
+    use secp256k1::SecretKey;
+
+    #[test]
+    fn create_api_key_request_covers_scopes() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[1; 32]).unwrap();
+
+        let label = "bot".to_string();
+        let scopes = vec![ApiKeyScope::Read];
+        let timestamp = OffsetDateTime::now_utc();
+        let message = CreateApiKey::message(&label, &scopes, timestamp).unwrap();
+
+        let mut request = CreateApiKey {
+            label,
+            scopes,
+            timestamp,
+            signature: Signature {
+                pubkey: secret_key.public_key(&secp),
+                signature: secp.sign_ecdsa(&create_sign_message(message), &secret_key),
+            },
+        };
+        assert!(request.verify().is_ok());
+
+        request.scopes.push(ApiKeyScope::Trade);
+        assert!(request.verify().is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Non-secret parameters of the app which the coordinator can tune without releasing a new app
+/// version.
+///
+/// Every parameter is optional. If a parameter is not set, the app keeps its own default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AppConfig {
+    /// How often the app checks the health of the coordinator and its services.
+    pub health_check_interval_secs: Option<u64>,
+    /// Features which can be turned on or off remotely, identified by their name.
+    #[serde(default)]
+    pub feature_flags: BTreeMap<String, bool>,
+    /// The oldest app version still supported by the coordinator. Older apps are asked to update.
+    pub min_app_version: Option<String>,
+    /// The maximum number of parts a Lightning payment sent by the app may be split into.
+    pub payment_max_parts: Option<u8>,
+    /// The maximum total routing fee of a Lightning payment sent by the app.
+    pub payment_max_fee_msat: Option<u64>,
+    /// For how long the app retries failed parts of a Lightning payment.
+    pub payment_timeout_secs: Option<u64>,
+    /// The order-matching fee rate the coordinator charges takers, shown to the trader before they
+    /// submit an order.
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub taker_fee_rate: Option<Decimal>,
+}
+
+impl AppConfig {
+    /// Whether the feature with the given name is enabled. Unknown features are disabled.
+    pub fn is_feature_enabled(&self, feature: &str) -> bool {
+        self.feature_flags.get(feature).copied().unwrap_or(false)
+    }
+}
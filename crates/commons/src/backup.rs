@@ -1,22 +1,52 @@
 use crate::signature::create_sign_message;
+use anyhow::ensure;
 use secp256k1::ecdsa::Signature;
 use secp256k1::PublicKey;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::digest::FixedOutput;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// The SHA256 checksum of a backed up value, as it has been uploaded.
+pub type Checksum = [u8; 32];
+
+pub fn checksum(value: &[u8]) -> Checksum {
+    Sha256::new().chain_update(value).finalize_fixed().into()
+}
 
 /// A message to restore a key with its value.
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Restore {
     pub key: String,
     pub value: Vec<u8>,
+    /// The checksum of the value when it was backed up, [`None`] if the value has been backed up
+    /// without a checksum.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Vec<u8>>))]
+    pub checksum: Option<Checksum>,
+}
+
+impl Restore {
+    /// Verifies that the value has not been altered since it was backed up.
+    pub fn verify_checksum(&self) -> anyhow::Result<()> {
+        verify_checksum(&self.key, &self.value, self.checksum)
+    }
 }
 
 /// A message to backup a key with its value.
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Backup {
     pub key: String,
     pub value: Vec<u8>,
+    /// The checksum of the value. Optional, so that backups of older apps are still accepted.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Vec<u8>>))]
+    pub checksum: Option<Checksum>,
     /// A signature of the value using the nodes private key
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub signature: Signature,
 }
 
@@ -27,13 +57,69 @@ impl Backup {
         self.signature.verify(&message, node_id)?;
         Ok(())
     }
+
+    /// Verifies that the value has not been altered on the way.
+    pub fn verify_checksum(&self) -> anyhow::Result<()> {
+        verify_checksum(&self.key, &self.value, self.checksum)
+    }
+}
+
+fn verify_checksum(key: &str, value: &[u8], expected: Option<Checksum>) -> anyhow::Result<()> {
+    if let Some(expected) = expected {
+        ensure!(checksum(value) == expected, "Checksum mismatch for {key}");
+    }
+
+    Ok(())
+}
+
+/// A request to force-close a channel, sent by a user who lost the channel state and recovers
+/// from a static channel backup.
+#[derive(Serialize, Deserialize)]
+pub struct ForceCloseChannel {
+    pub trader_id: PublicKey,
+    /// A signature of the hex encoded channel id using the nodes private key
+    pub signature: Signature,
+}
+
+impl ForceCloseChannel {
+    /// Verifies if the request for the given channel was signed by the trader
+    pub fn verify(&self, channel_id: &str) -> anyhow::Result<()> {
+        let message = create_sign_message(channel_id.as_bytes().to_vec());
+        self.signature.verify(&message, &self.trader_id)?;
+        Ok(())
+    }
+}
+
+/// The coordinator's view of the channels it has with a user.
+///
+/// Lets a user who has lost their backup find out which of their funds can still be recovered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryInfo {
+    pub channels: Vec<RecoverableChannel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverableChannel {
+    /// The hex encoded ID of the channel.
+    pub channel_id: String,
+    pub kind: RecoverableChannelKind,
+    /// The funds of the user in the channel, as far as the coordinator knows.
+    pub balance_sats: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecoverableChannelKind {
+    Lightning,
+    Dlc,
 }
 
 /// A message to delete a backup of a key
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct DeleteBackup {
     pub key: String,
     /// A signature of the requesting node id using the nodes private key
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub signature: Signature,
 }
 
@@ -0,0 +1,107 @@
+use std::sync::Mutex;
+use time::Duration;
+use time::OffsetDateTime;
+
+/// The source of the current time for everything which depends on it, e.g. expiries and
+/// rollovers.
+///
+/// Passing a clock around instead of calling [`OffsetDateTime::now_utc`] lets tests control time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> OffsetDateTime;
+}
+
+/// The system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// The system time, which can be fast-forwarded.
+///
+/// Time keeps passing as usual, hence this can be used by a running service on a test network.
+#[derive(Debug, Default)]
+pub struct AdjustableClock {
+    offset: Mutex<Duration>,
+}
+
+impl AdjustableClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Jump forward in time by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().expect("lock not to be poisoned") += duration;
+    }
+
+    /// How far this clock is ahead of the system time.
+    pub fn offset(&self) -> Duration {
+        *self.offset.lock().expect("lock not to be poisoned")
+    }
+}
+
+impl Clock for AdjustableClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc() + self.offset()
+    }
+}
+
+/// A clock which stands still unless it is moved, for deterministic tests.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<OffsetDateTime>,
+}
+
+impl MockClock {
+    pub fn new(now: OffsetDateTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: OffsetDateTime) {
+        *self.now.lock().expect("lock not to be poisoned") = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().expect("lock not to be poisoned") += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().expect("lock not to be poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjustable_clock_runs_ahead_of_system_time() {
+        let clock = AdjustableClock::new();
+        clock.advance(Duration::days(3));
+
+        let ahead = clock.now() - OffsetDateTime::now_utc();
+
+        assert!(ahead > Duration::days(3) - Duration::minutes(1));
+        assert!(ahead <= Duration::days(3));
+    }
+
+    #[test]
+    fn mock_clock_only_moves_when_told() {
+        // Wed Aug 09 2023 09:30:23 GMT+0000
+        let start = OffsetDateTime::from_unix_timestamp(1691573423).unwrap();
+        let clock = MockClock::new(start);
+
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::hours(1));
+        assert_eq!(clock.now(), start + Duration::hours(1));
+    }
+}
@@ -6,6 +6,7 @@ use serde::Serialize;
 
 /// The information needed for the coordinator to kickstart the collaborative revert protocol.
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CollaborativeRevertCoordinatorRequest {
     /// Channel to collaboratively revert.
     pub channel_id: String,
@@ -18,16 +19,46 @@ pub struct CollaborativeRevertCoordinatorRequest {
     /// The price at which the position has been closed
     ///
     /// Note: this is just for informative purposes and is not used in any calculations
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
     pub price: Decimal,
 }
 
+/// The information needed for the coordinator to kickstart the collaborative revert protocol for
+/// many channels at once, e.g. to recover from an incident affecting many users.
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CollaborativeRevertCoordinatorBatchRequest {
+    /// Channels to collaboratively revert.
+    pub channels: Vec<CollaborativeRevertChannel>,
+    /// Fee rate for all collaborative revert transactions.
+    pub fee_rate_sats_vb: u64,
+    /// The price at which the positions have been closed
+    ///
+    /// Note: this is just for informative purposes and is not used in any calculations
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    pub price: Decimal,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CollaborativeRevertChannel {
+    pub channel_id: String,
+    /// Amount to be paid out to the counterparty in sats.
+    ///
+    /// Note: the tx fee will be subtracted evenly between both parties
+    pub counter_payout: u64,
+}
+
 /// The information provided by the trader in response to a collaborative revert proposal.
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CollaborativeRevertTraderResponse {
     /// Channel to collaboratively revert.
     pub channel_id: String,
     /// The unsigned collaborative revert transaction.
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
     pub transaction: Transaction,
     /// The trader's signature on the collaborative revert transaction.
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub signature: Signature,
 }
@@ -0,0 +1,40 @@
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// The most reports the app sends in one batch.
+pub const MAX_ERROR_REPORTS: usize = 50;
+
+/// Messages of a report longer than this are cut off.
+pub const MAX_ERROR_REPORT_MESSAGE_LEN: usize = 1000;
+
+/// A batch of errors which occurred in the app of a user who opted in to report them.
+///
+/// The reports are not tied to the user, and keys, invoices and addresses are removed from them
+/// before they leave the app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorReports {
+    pub app_version: String,
+    pub reports: Vec<ErrorReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorReport {
+    pub kind: ErrorReportKind,
+    /// When the error first occurred.
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    /// The module which logged the error, or the location of the panic.
+    pub target: String,
+    pub message: String,
+    /// How many times in a row the same error occurred.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorReportKind {
+    /// An error logged by the app.
+    Error,
+    Panic,
+}
@@ -0,0 +1,83 @@
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// The fee rates recommended for on-chain transactions together with the current state of the
+/// mempool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeEstimates {
+    pub tiers: FeeTiers,
+    /// The fee histogram of the mempool, ordered by descending fee rate.
+    ///
+    /// Empty if the mempool could not be queried.
+    pub histogram: Vec<FeeHistogramBucket>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// The fee rates used by the wallet per confirmation target, in sats/vbyte.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct FeeTiers {
+    pub minimum: f32,
+    pub background: f32,
+    pub normal: f32,
+    pub high_priority: f32,
+}
+
+/// The transactions in the mempool paying at least `sats_per_vbyte`, but less than the fee rate
+/// of the previous bucket.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct FeeHistogramBucket {
+    pub sats_per_vbyte: f32,
+    pub vsize: u64,
+}
+
+impl FeeEstimates {
+    /// The virtual size of all the transactions in the mempool paying a higher fee rate than
+    /// `sats_per_vbyte`, i.e. of the transactions which will be mined before a transaction paying
+    /// `sats_per_vbyte`.
+    pub fn vsize_ahead_of(&self, sats_per_vbyte: f32) -> u64 {
+        self.histogram
+            .iter()
+            .take_while(|bucket| bucket.sats_per_vbyte > sats_per_vbyte)
+            .map(|bucket| bucket.vsize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vsize_ahead_of_sums_all_higher_paying_buckets() {
+        let estimates = FeeEstimates {
+            tiers: FeeTiers {
+                minimum: 1.0,
+                background: 2.0,
+                normal: 10.0,
+                high_priority: 20.0,
+            },
+            histogram: vec![
+                FeeHistogramBucket {
+                    sats_per_vbyte: 30.0,
+                    vsize: 100_000,
+                },
+                FeeHistogramBucket {
+                    sats_per_vbyte: 15.0,
+                    vsize: 200_000,
+                },
+                FeeHistogramBucket {
+                    sats_per_vbyte: 5.0,
+                    vsize: 400_000,
+                },
+            ],
+            timestamp: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        assert_eq!(estimates.vsize_ahead_of(20.0), 100_000);
+        assert_eq!(estimates.vsize_ahead_of(10.0), 300_000);
+        assert_eq!(estimates.vsize_ahead_of(1.0), 700_000);
+        assert_eq!(estimates.vsize_ahead_of(50.0), 0);
+    }
+}
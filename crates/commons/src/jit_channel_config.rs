@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The terms under which the coordinator opens just-in-time channels to the app.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JitChannelConfig {
+    /// The smallest channel the coordinator opens.
+    pub min_channel_size_sats: u64,
+    /// The largest channel the coordinator opens. If `None`, the size is only limited by the
+    /// coordinator's liquidity.
+    pub max_channel_size_sats: Option<u64>,
+    /// The fee charged for forwarding the payment which opens the channel, in millionths of the
+    /// payment amount.
+    pub liquidity_fee_ppm: u32,
+    /// Channels are only opened while the on-chain fee rate is at most this value, in sats/kWU.
+    /// If `None`, channels are opened regardless of the fee rate.
+    pub max_tx_fee_rate_sats_per_kwu: Option<u32>,
+}
@@ -3,31 +3,52 @@ use secp256k1::PublicKey;
 use serde::Deserialize;
 use serde::Serialize;
 
+mod api;
+mod api_key;
+mod app_config;
 mod backup;
+mod clock;
 mod collab_revert;
+mod error_report;
+mod fee_estimates;
+mod jit_channel_config;
 mod liquidity_option;
+mod market_stats;
 mod message;
 mod order;
-mod order_matching_fee;
+mod position;
 mod price;
+mod quote;
 mod rollover;
 mod route;
 mod signature;
 mod trade;
 
+pub use crate::api::*;
+pub use crate::api_key::*;
+pub use crate::app_config::*;
 pub use crate::backup::*;
+pub use crate::clock::*;
 pub use crate::collab_revert::*;
+pub use crate::error_report::*;
+pub use crate::fee_estimates::*;
+pub use crate::jit_channel_config::*;
 pub use crate::liquidity_option::*;
+pub use crate::market_stats::*;
 pub use crate::message::*;
 pub use crate::order::*;
-pub use crate::order_matching_fee::order_matching_fee_taker;
+pub use crate::position::*;
 pub use crate::price::best_current_price;
 pub use crate::price::Price;
 pub use crate::price::Prices;
+pub use crate::quote::*;
 pub use crate::rollover::*;
 pub use crate::route::*;
 pub use crate::signature::*;
 pub use crate::trade::*;
+pub use ::trade::pricing::default_taker_fee_rate;
+pub use ::trade::pricing::order_matching_fee;
+pub use ::trade::pricing::order_matching_fee_taker;
 
 pub const AUTH_SIGN_MESSAGE: &[u8; 19] = b"Hello it's me Mario";
 
@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use serde::Serialize;
+use trade::ContractSymbol;
+
+/// Trading statistics of a single market.
+///
+/// Volumes and open interest are denominated in contracts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MarketStats {
+    pub contract_symbol: ContractSymbol,
+    pub volume_1h: f32,
+    pub volume_24h: f32,
+    pub volume_7d: f32,
+    /// The highest execution price within the last 24 hours.
+    pub high_24h: Option<f32>,
+    /// The lowest execution price within the last 24 hours.
+    pub low_24h: Option<f32>,
+    /// The execution price of the latest trade.
+    pub last_price: Option<f32>,
+    /// The total quantity of all open positions.
+    pub open_interest: f32,
+}
@@ -1,4 +1,5 @@
 use crate::order::Order;
+use crate::quote::QuoteEvent;
 use crate::signature::Signature;
 use crate::trade::FilledWith;
 use crate::LiquidityOption;
@@ -44,6 +45,38 @@ pub enum Message {
         #[serde(with = "rust_decimal::serde::float")]
         execution_price: Decimal,
     },
+    /// The match of the order has been reverted, because it was not executed in time.
+    MatchReverted {
+        order_id: Uuid,
+        reason: String,
+    },
+    /// New market orders are rejected until trading is resumed. Contains the reason for the halt.
+    TradingHalted(String),
+    TradingResumed,
+    /// An update about a quote of a maker, e.g. that it has been filled or has expired.
+    QuoteUpdate(QuoteEvent),
+    /// The progress of the channel the coordinator is opening to the trader.
+    ChannelOpenStatus {
+        channel_id: String,
+        status: ChannelOpenStatus,
+    },
+    /// Another device has started using the same node ID. The device receiving this message must
+    /// stop persisting its state, as it would otherwise corrupt the state of the other device.
+    SessionSuperseded,
+}
+
+/// How far a channel has progressed towards being usable.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelOpenStatus {
+    /// The funding transaction has been broadcast, but is not confirmed yet.
+    Broadcast {
+        required_confirmations: u32,
+    },
+    Confirming {
+        confirmations: u32,
+        required_confirmations: u32,
+    },
+    Ready,
 }
 
 #[derive(Serialize, Clone, Deserialize, Debug)]
@@ -54,11 +87,23 @@ pub struct LspConfig {
     pub liquidity_options: Vec<LiquidityOption>,
 }
 
+/// The installation of the app which is using a node ID.
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq, Eq)]
+pub struct Device {
+    pub id: String,
+    /// When the seed has been created or restored on the device, as unix timestamp.
+    ///
+    /// If two devices use the same node ID, the device with the later registration wins.
+    pub registered_at: i64,
+}
+
 #[derive(Serialize, Clone, Deserialize, Debug)]
 pub enum OrderbookRequest {
     Authenticate {
         fcm_token: Option<String>,
         signature: Signature,
+        #[serde(default)]
+        device: Option<Device>,
     },
     LimitOrderFilledMatches {
         trader_id: PublicKey,
@@ -110,6 +155,24 @@ impl Display for Message {
             Message::CollaborativeRevert { .. } => {
                 write!(f, "CollaborativeRevert")
             }
+            Message::MatchReverted { .. } => {
+                write!(f, "MatchReverted")
+            }
+            Message::TradingHalted(_) => {
+                write!(f, "TradingHalted")
+            }
+            Message::TradingResumed => {
+                write!(f, "TradingResumed")
+            }
+            Message::QuoteUpdate(_) => {
+                write!(f, "QuoteUpdate")
+            }
+            Message::ChannelOpenStatus { .. } => {
+                write!(f, "ChannelOpenStatus")
+            }
+            Message::SessionSuperseded => {
+                write!(f, "SessionSuperseded")
+            }
         }
     }
 }
@@ -1,3 +1,5 @@
+use crate::signature::create_sign_message;
+use crate::signature::Signature;
 use rust_decimal::Decimal;
 use secp256k1::PublicKey;
 use serde::Deserialize;
@@ -8,22 +10,33 @@ use trade::Direction;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct NewOrder {
     pub id: Uuid,
     pub contract_symbol: ContractSymbol,
     #[serde(with = "rust_decimal::serde::float")]
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
     pub price: Decimal,
     #[serde(with = "rust_decimal::serde::float")]
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
     pub quantity: Decimal,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub trader_id: PublicKey,
     pub direction: Direction,
     pub leverage: f32,
     pub order_type: OrderType,
+    /// Serialized as the tuple of its date and time components.
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<i64>))]
     pub expiry: OffsetDateTime,
     pub stable: bool,
+    /// If set, the order is rejected unless it reduces the trader's open position, i.e. it is
+    /// opposite to the position and not larger than it.
+    #[serde(default)]
+    pub reduce_only: bool,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum OrderType {
     #[allow(dead_code)]
     Market,
@@ -43,6 +56,7 @@ pub struct OrderResponse {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum OrderState {
     Open,
     Matched,
@@ -51,21 +65,26 @@ pub enum OrderState {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum OrderReason {
     Manual,
     Expired,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Order {
     pub id: Uuid,
     #[serde(with = "rust_decimal::serde::float")]
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
     pub price: Decimal,
     pub leverage: f32,
     pub contract_symbol: ContractSymbol,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub trader_id: PublicKey,
     pub direction: Direction,
     #[serde(with = "rust_decimal::serde::float")]
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
     pub quantity: Decimal,
     pub order_type: OrderType,
     #[serde(with = "time::serde::rfc3339")]
@@ -76,3 +95,24 @@ pub struct Order {
     pub order_reason: OrderReason,
     pub stable: bool,
 }
+
+/// A request from the trader to roll back a match which could not be executed.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RollbackMatch {
+    pub order_id: Uuid,
+    /// A signature of the order id using the trader's node key
+    pub signature: Signature,
+}
+
+impl RollbackMatch {
+    /// Verifies that the rollback was requested by the trader who signed it.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let message = self.order_id.to_string().as_bytes().to_vec();
+        let message = create_sign_message(message);
+        self.signature
+            .signature
+            .verify(&message, &self.signature.pubkey)?;
+        Ok(())
+    }
+}
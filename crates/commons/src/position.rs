@@ -0,0 +1,38 @@
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+
+/// The coordinator's view of a trader's position.
+///
+/// All values are from the trader's point of view.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraderPosition {
+    /// The coordinator's identifier of the position.
+    pub id: i32,
+    pub contract_symbol: ContractSymbol,
+    pub direction: Direction,
+    pub quantity: f32,
+    pub leverage: f32,
+    pub average_entry_price: f32,
+    pub liquidation_price: f32,
+    pub state: TraderPositionState,
+    /// The margin of the trader in sats.
+    pub collateral: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiry_timestamp: OffsetDateTime,
+    /// The unrealized PnL of the trader in sats.
+    ///
+    /// `None` if the current price could not be determined.
+    pub unrealized_pnl_sat: Option<i64>,
+    pub stable: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TraderPositionState {
+    Open,
+    Closing,
+    Rollover,
+    Resizing,
+}
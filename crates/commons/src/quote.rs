@@ -0,0 +1,56 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QuoteEventKind {
+    /// The whole quantity of the quote has been matched.
+    Filled,
+    /// Only parts of the quantity have been matched. The remaining quantity is not kept in the
+    /// orderbook, i.e. the maker has to post a new quote for it.
+    PartiallyFilled,
+    /// The quote has been removed from the orderbook on behalf of the maker.
+    Cancelled,
+    /// The quote has expired before it was matched.
+    Expired,
+}
+
+/// An update about a quote (limit order) of a maker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuoteEvent {
+    /// Unique id of the event. Events may be delivered more than once, hence makers should use
+    /// the id to deduplicate them.
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub kind: QuoteEventKind,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub filled_quantity: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub remaining_quantity: Decimal,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub execution_price: Option<Decimal>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl QuoteEvent {
+    pub fn new(
+        order_id: Uuid,
+        kind: QuoteEventKind,
+        filled_quantity: Decimal,
+        remaining_quantity: Decimal,
+        execution_price: Option<Decimal>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            order_id,
+            kind,
+            filled_quantity,
+            remaining_quantity,
+            execution_price,
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -51,9 +51,9 @@ pub fn is_eligible_for_rollover(timestamp: OffsetDateTime, network: Network) ->
             Weekday::Sunday => timestamp.time() < time!(15:00),
             _ => false,
         },
-        // Returns true if the timestamp is less than 8 hours from now
+        // Returns true if the timestamp is less than 8 hours before the following midnight
         _ => {
-            let midnight = (OffsetDateTime::now_utc().date() + Duration::days(1))
+            let midnight = (timestamp.date() + Duration::days(1))
                 .midnight()
                 .assume_utc();
             (midnight - timestamp) < Duration::hours(8)
@@ -112,6 +112,17 @@ mod test {
         assert!(!is_eligible_for_rollover(expiry, Network::Bitcoin));
     }
 
+    #[test]
+    fn test_regtest_rollover_eligibility_depends_only_on_timestamp() {
+        // Wed Aug 09 2023 16:00:01 GMT+0000
+        let eligible = OffsetDateTime::from_unix_timestamp(1691596801).unwrap();
+        assert!(is_eligible_for_rollover(eligible, Network::Regtest));
+
+        // Wed Aug 09 2023 16:00:00 GMT+0000
+        let not_eligible = OffsetDateTime::from_unix_timestamp(1691596800).unwrap();
+        assert!(!is_eligible_for_rollover(not_eligible, Network::Regtest));
+    }
+
     #[test]
     fn test_expiry_timestamp_before_friday_15pm() {
         // Wed Aug 09 2023 09:30:23 GMT+0000
@@ -7,8 +7,11 @@ use sha2::Digest;
 use sha2::Sha256;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Signature {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub pubkey: PublicKey,
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
     pub signature: secp256k1::ecdsa::Signature,
 }
 
@@ -0,0 +1,8 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use a vendored `protoc`, so that building does not depend on a system installation.
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::configure().compile(&["proto/coordinator.proto"], &["proto"])?;
+
+    Ok(())
+}
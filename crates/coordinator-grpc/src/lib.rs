@@ -0,0 +1,13 @@
+//! The gRPC API of the coordinator.
+//!
+//! The API is defined in `proto/coordinator.proto`, which is shared with market makers, so that
+//! they can generate clients in the language of their choice. This crate provides the generated
+//! server and client code for Rust.
+//!
+//! Requests are authenticated with an API key, passed as `authorization: Bearer <key>` metadata.
+//! The `Node` service expects the admin API token instead.
+
+#[allow(clippy::all, clippy::unwrap_used, unused_qualifications)]
+pub mod v1 {
+    tonic::include_proto!("tentenone.coordinator.v1");
+}
@@ -0,0 +1,228 @@
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bdk::bitcoincore_rpc::bitcoincore_rpc_json::EstimateMode;
+use bdk::bitcoincore_rpc::jsonrpc;
+use bdk::bitcoincore_rpc::Auth;
+use bdk::bitcoincore_rpc::Client;
+use bdk::bitcoincore_rpc::RpcApi;
+use bdk::blockchain::rpc::Auth as RpcAuth;
+use bdk::blockchain::rpc::RpcBlockchain;
+use bdk::blockchain::rpc::RpcConfig;
+use bdk::blockchain::ConfigurableBlockchain;
+use bitcoin::BlockHash;
+use bitcoin::Network;
+use bitcoin::OutPoint;
+use bitcoin::Txid;
+use lightning::chain::Confirm;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// How many blocks we go back to look for transactions if the block we last synced to has been
+/// re-orged out of the best chain.
+const REORG_SAFETY_DEPTH: u32 = 6;
+
+/// The RPC error code of bitcoind if the requested transaction is unknown.
+const RPC_INVALID_ADDRESS_OR_KEY: i32 = -5;
+
+/// A chain source backed by the RPC interface of bitcoind.
+///
+/// Looking up arbitrary transactions requires bitcoind to run with `txindex=1`.
+pub struct Bitcoind {
+    client: Client,
+    rpc_url: String,
+    rpc_user: String,
+    rpc_password: String,
+}
+
+impl Bitcoind {
+    pub fn new(rpc_url: String, rpc_user: String, rpc_password: String) -> Result<Self> {
+        let client = Client::new(
+            &rpc_url,
+            Auth::UserPass(rpc_user.clone(), rpc_password.clone()),
+        )
+        .with_context(|| format!("Failed to create bitcoind RPC client for {rpc_url}"))?;
+
+        Ok(Self {
+            client,
+            rpc_url,
+            rpc_user,
+            rpc_password,
+        })
+    }
+
+    /// A [`RpcBlockchain`] syncing the on-chain wallet through a watch-only wallet with the given
+    /// name in bitcoind.
+    pub fn wallet_blockchain(
+        &self,
+        wallet_name: String,
+        network: Network,
+    ) -> Result<RpcBlockchain> {
+        let config = RpcConfig {
+            url: self.rpc_url.clone(),
+            auth: RpcAuth::UserPass {
+                username: self.rpc_user.clone(),
+                password: self.rpc_password.clone(),
+            },
+            network,
+            wallet_name,
+            sync_params: None,
+        };
+
+        RpcBlockchain::from_config(&config).context("Failed to set up wallet in bitcoind")
+    }
+
+    pub fn get_height(&self) -> Result<u32> {
+        let height = self.client.get_block_count()?;
+
+        Ok(height as u32)
+    }
+
+    /// Estimate the fee rates for the given confirmation targets via `estimatesmartfee`.
+    ///
+    /// The estimates are in sats/vbyte, keyed by the confirmation target, just like esplora's
+    /// `/fee-estimates`.
+    pub fn get_fee_estimates(&self, targets: &[usize]) -> Result<HashMap<String, f64>> {
+        let mut estimates = HashMap::new();
+        for target in targets {
+            let estimate = self
+                .client
+                .estimate_smart_fee(*target as u16, Some(EstimateMode::Conservative))?;
+
+            // The fee rate is in BTC/kvB.
+            if let Some(fee_rate) = estimate.fee_rate {
+                estimates.insert(target.to_string(), fee_rate.to_sat() as f64 / 1000.0);
+            }
+        }
+
+        if estimates.is_empty() {
+            bail!("bitcoind has no fee estimates yet");
+        }
+
+        Ok(estimates)
+    }
+
+    /// The height of the block the transaction was confirmed in, if it is confirmed.
+    pub fn get_tx_confirmation_height(&self, txid: &Txid) -> Result<Option<u32>> {
+        let tx = match self.client.get_raw_transaction_info(txid, None) {
+            Ok(tx) => tx,
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let block_hash = match tx.blockhash {
+            Some(block_hash) => block_hash,
+            None => return Ok(None),
+        };
+
+        let header = self.client.get_block_header_info(&block_hash)?;
+
+        Ok(Some(header.height as u32))
+    }
+
+    pub fn get_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<bitcoin::Block>> {
+        match self.client.get_block(block_hash) {
+            Ok(block) => Ok(Some(block)),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether the output is unspent, including by transactions in the mempool.
+    ///
+    /// bitcoind only knows about unspent outputs, so we cannot tell when a spent output was spent.
+    pub fn is_unspent(&self, outpoint: &OutPoint) -> Result<bool> {
+        let output = self
+            .client
+            .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))?;
+
+        Ok(output.is_some())
+    }
+
+    fn is_in_best_chain(&self, block_hash: &BlockHash) -> Result<bool> {
+        // Blocks which are not part of the best chain have -1 confirmations.
+        let header = self.client.get_block_header_info(block_hash)?;
+
+        Ok(header.confirmations >= 0)
+    }
+}
+
+/// Syncs the Lightning wallet by feeding the blocks from bitcoind to the [`Confirm`]ables.
+pub struct BitcoindSync {
+    bitcoind: Arc<Bitcoind>,
+    /// The block we last synced to.
+    best_block: Mutex<Option<(u32, BlockHash)>>,
+}
+
+impl BitcoindSync {
+    pub fn new(bitcoind: Arc<Bitcoind>) -> Self {
+        Self {
+            bitcoind,
+            best_block: Mutex::new(None),
+        }
+    }
+
+    /// Continue syncing from the given block, e.g. the best block known to the channel manager.
+    pub fn start_from(&self, height: u32, block_hash: BlockHash) {
+        let mut best_block = self.best_block.lock();
+        if best_block.is_none() {
+            *best_block = Some((height, block_hash));
+        }
+    }
+
+    pub fn sync(&self, confirmables: Vec<&(dyn Confirm + Sync + Send)>) -> Result<()> {
+        let bitcoind = &self.bitcoind;
+
+        // Transactions confirmed in blocks which have been re-orged out are unconfirmed again.
+        for confirmable in confirmables.iter() {
+            for (txid, block_hash) in confirmable.get_relevant_txids() {
+                if let Some(block_hash) = block_hash {
+                    if !bitcoind.is_in_best_chain(&block_hash)? {
+                        tracing::info!(%txid, %block_hash, "Transaction got re-orged out");
+                        confirmable.transaction_unconfirmed(&txid);
+                    }
+                }
+            }
+        }
+
+        let tip_height = bitcoind.get_height()?;
+
+        let mut best_block = self.best_block.lock();
+        let start_height = match *best_block {
+            Some((height, block_hash)) if bitcoind.is_in_best_chain(&block_hash)? => height + 1,
+            Some((height, _)) => height.saturating_sub(REORG_SAFETY_DEPTH),
+            None => tip_height,
+        };
+
+        for height in start_height..=tip_height {
+            let block_hash = bitcoind.client.get_block_hash(height as u64)?;
+            let block = bitcoind.client.get_block(&block_hash)?;
+
+            let txdata = block.txdata.iter().enumerate().collect::<Vec<_>>();
+            for confirmable in confirmables.iter() {
+                confirmable.transactions_confirmed(&block.header, &txdata, height);
+            }
+
+            *best_block = Some((height, block_hash));
+        }
+
+        let tip_hash = bitcoind.client.get_block_hash(tip_height as u64)?;
+        let tip_header = bitcoind.client.get_block_header(&tip_hash)?;
+        for confirmable in confirmables.iter() {
+            confirmable.best_block_updated(&tip_header, tip_height);
+        }
+
+        Ok(())
+    }
+}
+
+fn is_not_found(error: &bdk::bitcoincore_rpc::Error) -> bool {
+    matches!(
+        error,
+        bdk::bitcoincore_rpc::Error::JsonRpc(jsonrpc::Error::Rpc(jsonrpc::error::RpcError {
+            code: RPC_INVALID_ADDRESS_OR_KEY,
+            ..
+        }))
+    )
+}
@@ -0,0 +1,332 @@
+use crate::bitcoind::Bitcoind;
+use crate::bitcoind::BitcoindSync;
+use crate::esplora::EsploraBackends;
+use crate::esplora::FailoverBlockchain;
+use crate::esplora::FailoverSyncClient;
+use crate::ln::TracingLogger;
+use anyhow::Result;
+use bdk::blockchain::rpc::RpcBlockchain;
+use bdk::blockchain::Blockchain;
+use bdk::blockchain::Capability;
+use bdk::blockchain::GetBlockHash;
+use bdk::blockchain::GetHeight;
+use bdk::blockchain::GetTx;
+use bdk::blockchain::Progress;
+use bdk::blockchain::WalletSync;
+use bdk::database::BatchDatabase;
+use bdk::FeeRate;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+use bitcoin::Network;
+use bitcoin::OutPoint;
+use bitcoin::Script;
+use bitcoin::Transaction;
+use bitcoin::Txid;
+use esplora_client::TxStatus;
+use lightning::chain::Confirm;
+use lightning::chain::Filter;
+use lightning::chain::WatchedOutput;
+use serde::Deserialize;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Where the node gets its view of the blockchain from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChainSourceConfig {
+    /// Esplora backends in order of priority, see [`EsploraBackends`].
+    Esplora { urls: Vec<String> },
+    /// The RPC interface of bitcoind, which has to run with `txindex=1`.
+    Bitcoind {
+        rpc_url: String,
+        rpc_user: String,
+        rpc_password: String,
+    },
+}
+
+/// The source of all chain data of the node: syncing the on-chain and the Lightning wallet, fee
+/// estimation and broadcasting.
+pub enum ChainSource {
+    Esplora(Arc<EsploraBackends>),
+    Bitcoind(Arc<Bitcoind>),
+}
+
+/// The status of an output as far as the [`ChainSource`] can tell.
+pub enum OutputStatus {
+    Unspent,
+    Spent {
+        /// The height of the block the spending transaction was confirmed in, if known.
+        confirmation_height: Option<u32>,
+    },
+}
+
+impl ChainSource {
+    pub fn new(config: ChainSourceConfig) -> Result<Self> {
+        let chain_source = match config {
+            ChainSourceConfig::Esplora { urls } => {
+                ChainSource::Esplora(Arc::new(EsploraBackends::new(urls)?))
+            }
+            ChainSourceConfig::Bitcoind {
+                rpc_url,
+                rpc_user,
+                rpc_password,
+            } => ChainSource::Bitcoind(Arc::new(Bitcoind::new(rpc_url, rpc_user, rpc_password)?)),
+        };
+
+        Ok(chain_source)
+    }
+
+    pub fn get_height(&self) -> Result<u32> {
+        match self {
+            ChainSource::Esplora(backends) => {
+                Ok(backends.call("get block height", |client| client.get_height())?)
+            }
+            ChainSource::Bitcoind(bitcoind) => bitcoind.get_height(),
+        }
+    }
+
+    /// Fee rate estimates in sats/vbyte, keyed by the confirmation target in blocks.
+    pub fn get_fee_estimates(&self, targets: &[usize]) -> Result<HashMap<String, f64>> {
+        match self {
+            ChainSource::Esplora(backends) => {
+                Ok(backends.call("get fee estimates", |client| client.get_fee_estimates())?)
+            }
+            ChainSource::Bitcoind(bitcoind) => bitcoind.get_fee_estimates(targets),
+        }
+    }
+
+    pub fn get_output_status(&self, outpoint: &OutPoint) -> Result<OutputStatus> {
+        let status = match self {
+            ChainSource::Esplora(backends) => {
+                let status = backends.call("get output status", |client| {
+                    client.get_output_status(&outpoint.txid, outpoint.vout as u64)
+                })?;
+
+                match status {
+                    Some(status) if status.spent => OutputStatus::Spent {
+                        confirmation_height: status.status.and_then(confirmation_height),
+                    },
+                    _ => OutputStatus::Unspent,
+                }
+            }
+            ChainSource::Bitcoind(bitcoind) => match bitcoind.is_unspent(outpoint)? {
+                true => OutputStatus::Unspent,
+                false => OutputStatus::Spent {
+                    confirmation_height: None,
+                },
+            },
+        };
+
+        Ok(status)
+    }
+
+    /// Check whether the chain source is reachable, switching to a preferred esplora backend if
+    /// possible.
+    pub fn check_health(&self) {
+        match self {
+            ChainSource::Esplora(backends) => backends.check_health(),
+            ChainSource::Bitcoind(bitcoind) => {
+                if let Err(e) = bitcoind.get_height() {
+                    tracing::error!("bitcoind is unhealthy: {e:#}");
+                }
+            }
+        }
+    }
+
+    pub(crate) fn wallet_blockchain(
+        &self,
+        wallet_name: String,
+        network: Network,
+        stop_gap: usize,
+        concurrency: u8,
+    ) -> Result<WalletBlockchain> {
+        let blockchain =
+            match self {
+                ChainSource::Esplora(backends) => WalletBlockchain::Esplora(
+                    FailoverBlockchain::new(backends.clone(), stop_gap, concurrency),
+                ),
+                ChainSource::Bitcoind(bitcoind) => WalletBlockchain::Bitcoind {
+                    blockchain: bitcoind.wallet_blockchain(wallet_name, network)?,
+                    bitcoind: bitcoind.clone(),
+                },
+            };
+
+        Ok(blockchain)
+    }
+
+    pub(crate) fn lightning_sync(&self, logger: Arc<TracingLogger>) -> LightningSync {
+        match self {
+            ChainSource::Esplora(backends) => {
+                LightningSync::Esplora(FailoverSyncClient::new(backends.clone(), logger))
+            }
+            ChainSource::Bitcoind(bitcoind) => {
+                LightningSync::Bitcoind(BitcoindSync::new(bitcoind.clone()))
+            }
+        }
+    }
+}
+
+/// The [`Blockchain`] of the on-chain wallet.
+pub enum WalletBlockchain {
+    Esplora(FailoverBlockchain),
+    Bitcoind {
+        blockchain: RpcBlockchain,
+        bitcoind: Arc<Bitcoind>,
+    },
+}
+
+impl WalletBlockchain {
+    pub fn get_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<Block>> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => Ok(blockchain.get_block_by_hash(block_hash)?),
+            WalletBlockchain::Bitcoind { bitcoind, .. } => bitcoind.get_block_by_hash(block_hash),
+        }
+    }
+
+    /// The height of the block the transaction was confirmed in, if it is confirmed.
+    pub fn get_tx_confirmation_height(&self, txid: &Txid) -> Result<Option<u32>> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => Ok(blockchain
+                .get_tx_status(txid)?
+                .and_then(confirmation_height)),
+            WalletBlockchain::Bitcoind { bitcoind, .. } => {
+                bitcoind.get_tx_confirmation_height(txid)
+            }
+        }
+    }
+}
+
+impl WalletSync for WalletBlockchain {
+    fn wallet_setup<D: BatchDatabase>(
+        &self,
+        database: &RefCell<D>,
+        progress_update: Box<dyn Progress>,
+    ) -> Result<(), bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => {
+                blockchain.wallet_setup(database, progress_update)
+            }
+            WalletBlockchain::Bitcoind { blockchain, .. } => {
+                blockchain.wallet_setup(database, progress_update)
+            }
+        }
+    }
+
+    fn wallet_sync<D: BatchDatabase>(
+        &self,
+        database: &RefCell<D>,
+        progress_update: Box<dyn Progress>,
+    ) -> Result<(), bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => {
+                blockchain.wallet_sync(database, progress_update)
+            }
+            WalletBlockchain::Bitcoind { blockchain, .. } => {
+                blockchain.wallet_sync(database, progress_update)
+            }
+        }
+    }
+}
+
+impl GetHeight for WalletBlockchain {
+    fn get_height(&self) -> Result<u32, bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => blockchain.get_height(),
+            WalletBlockchain::Bitcoind { blockchain, .. } => blockchain.get_height(),
+        }
+    }
+}
+
+impl GetTx for WalletBlockchain {
+    fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => blockchain.get_tx(txid),
+            WalletBlockchain::Bitcoind { blockchain, .. } => blockchain.get_tx(txid),
+        }
+    }
+}
+
+impl GetBlockHash for WalletBlockchain {
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => blockchain.get_block_hash(height),
+            WalletBlockchain::Bitcoind { blockchain, .. } => blockchain.get_block_hash(height),
+        }
+    }
+}
+
+impl Blockchain for WalletBlockchain {
+    fn get_capabilities(&self) -> HashSet<Capability> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => blockchain.get_capabilities(),
+            WalletBlockchain::Bitcoind { blockchain, .. } => blockchain.get_capabilities(),
+        }
+    }
+
+    fn broadcast(&self, tx: &Transaction) -> Result<(), bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => blockchain.broadcast(tx),
+            WalletBlockchain::Bitcoind { blockchain, .. } => blockchain.broadcast(tx),
+        }
+    }
+
+    fn estimate_fee(&self, target: usize) -> Result<FeeRate, bdk::Error> {
+        match self {
+            WalletBlockchain::Esplora(blockchain) => blockchain.estimate_fee(target),
+            WalletBlockchain::Bitcoind { blockchain, .. } => blockchain.estimate_fee(target),
+        }
+    }
+}
+
+/// Keeps the Lightning wallet in sync with the [`ChainSource`].
+pub enum LightningSync {
+    Esplora(FailoverSyncClient),
+    Bitcoind(BitcoindSync),
+}
+
+impl LightningSync {
+    pub fn sync(&self, confirmables: Vec<&(dyn Confirm + Sync + Send)>) -> Result<()> {
+        match self {
+            LightningSync::Esplora(client) => Ok(client.sync(confirmables)?),
+            LightningSync::Bitcoind(sync) => sync.sync(confirmables),
+        }
+    }
+
+    /// Set the block from which syncing continues, i.e. the best block known to the channel
+    /// manager.
+    ///
+    /// Only needed for bitcoind, as the esplora sync looks up the relevant transactions directly.
+    pub fn start_from(&self, height: u32, block_hash: BlockHash) {
+        if let LightningSync::Bitcoind(sync) = self {
+            sync.start_from(height, block_hash);
+        }
+    }
+}
+
+/// With bitcoind we process every transaction of every block, so there is nothing to register.
+impl Filter for LightningSync {
+    fn register_tx(&self, txid: &Txid, script_pubkey: &Script) {
+        if let LightningSync::Esplora(client) = self {
+            client.register_tx(txid, script_pubkey);
+        }
+    }
+
+    fn register_output(&self, output: WatchedOutput) {
+        if let LightningSync::Esplora(client) = self {
+            client.register_output(output);
+        }
+    }
+}
+
+fn confirmation_height(status: TxStatus) -> Option<u32> {
+    match status {
+        TxStatus {
+            confirmed: true,
+            block_height,
+            ..
+        } => block_height,
+        _ => None,
+    }
+}
@@ -104,9 +104,9 @@ pub fn coordinator_config() -> UserConfig {
         // This config is needed to forward payments to the 10101 app, which only have private
         // channels with the coordinator.
         accept_forwards_to_priv_channels: true,
-        // The coordinator automatically accepts any inbound channels if they adhere to its channel
-        // preferences.
-        manually_accept_inbound_channels: false,
+        // The coordinator accepts any inbound channels which adhere to its channel preferences, but
+        // needs to decide whether to accept them as zero-conf channels.
+        manually_accept_inbound_channels: true,
         ..Default::default()
     }
 }
@@ -0,0 +1,337 @@
+use crate::ln::TracingLogger;
+use anyhow::ensure;
+use anyhow::Result;
+use bdk::blockchain::Blockchain;
+use bdk::blockchain::Capability;
+use bdk::blockchain::EsploraBlockchain;
+use bdk::blockchain::GetBlockHash;
+use bdk::blockchain::GetHeight;
+use bdk::blockchain::GetTx;
+use bdk::blockchain::Progress;
+use bdk::blockchain::WalletSync;
+use bdk::database::BatchDatabase;
+use bdk::FeeRate;
+use bitcoin::Block;
+use bitcoin::BlockHash;
+use bitcoin::Script;
+use bitcoin::Transaction;
+use bitcoin::Txid;
+use esplora_client::BlockingClient;
+use esplora_client::TxStatus;
+use lightning::chain::Confirm;
+use lightning::chain::Filter;
+use lightning::chain::WatchedOutput;
+use lightning_transaction_sync::EsploraSyncClient;
+use lightning_transaction_sync::TxSyncError;
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long we wait for a response of an esplora backend before considering it unreachable.
+const ESPLORA_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A prioritised list of esplora backends.
+///
+/// Every request is sent to the _active_ backend first. If it fails, the remaining backends are
+/// tried in order and the first one to succeed becomes the active backend. The periodic
+/// [`EsploraBackends::check_health`] moves back to the backend with the highest priority as soon
+/// as it is reachable again.
+pub struct EsploraBackends {
+    urls: Vec<String>,
+    clients: Vec<BlockingClient>,
+    active: AtomicUsize,
+}
+
+impl EsploraBackends {
+    /// Constructor for the [`EsploraBackends`], ordered by priority.
+    pub fn new(urls: Vec<String>) -> Result<Self> {
+        ensure!(!urls.is_empty(), "At least one esplora backend is required");
+
+        let clients = urls
+            .iter()
+            .map(|url| {
+                let agent = ureq::AgentBuilder::new().timeout(ESPLORA_TIMEOUT).build();
+                BlockingClient::from_agent(url.clone(), agent)
+            })
+            .collect();
+
+        Ok(Self {
+            urls,
+            clients,
+            active: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn active_url(&self) -> &str {
+        &self.urls[self.active.load(Ordering::Relaxed)]
+    }
+
+    /// Send `request` to the active backend, failing over to the other ones if it fails.
+    pub fn call<T, E: Display>(
+        &self,
+        description: &str,
+        request: impl Fn(&BlockingClient) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.with_failover(description, |index| request(&self.clients[index]))
+    }
+
+    /// Query the height of every backend in order of priority and activate the first one which
+    /// responds.
+    pub fn check_health(&self) {
+        for (index, client) in self.clients.iter().enumerate() {
+            match client.get_height() {
+                Ok(_) => {
+                    self.activate(index);
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(url = %self.urls[index], "Esplora backend is unhealthy: {e:#}");
+                }
+            }
+        }
+
+        tracing::error!("All esplora backends are unhealthy");
+    }
+
+    /// Call `request` with the index of every backend, starting with the active one, until it
+    /// succeeds. The error of the last attempt is returned if all of them fail.
+    fn with_failover<T, E: Display>(
+        &self,
+        description: &str,
+        mut request: impl FnMut(usize) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let active = self.active.load(Ordering::Relaxed);
+        let n = self.urls.len();
+
+        let mut last_error = None;
+        for index in (0..n).map(|i| (active + i) % n) {
+            match request(index) {
+                Ok(value) => {
+                    self.activate(index);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    tracing::warn!(url = %self.urls[index], "Failed to {description}: {e:#}");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one backend"))
+    }
+
+    fn activate(&self, index: usize) {
+        let previous = self.active.swap(index, Ordering::Relaxed);
+        if previous != index {
+            tracing::info!(
+                from = %self.urls[previous],
+                to = %self.urls[index],
+                "Switched esplora backend"
+            );
+        }
+    }
+}
+
+/// An on-chain [`Blockchain`] which fails over between the [`EsploraBackends`].
+pub struct FailoverBlockchain {
+    backends: Arc<EsploraBackends>,
+    blockchains: Vec<EsploraBlockchain>,
+}
+
+impl FailoverBlockchain {
+    pub fn new(backends: Arc<EsploraBackends>, stop_gap: usize, concurrency: u8) -> Self {
+        let blockchains = backends
+            .clients
+            .iter()
+            .map(|client| {
+                EsploraBlockchain::from_client(client.clone(), stop_gap)
+                    .with_concurrency(concurrency)
+            })
+            .collect();
+
+        Self {
+            backends,
+            blockchains,
+        }
+    }
+
+    pub fn get_block_by_hash(
+        &self,
+        block_hash: &BlockHash,
+    ) -> Result<Option<Block>, esplora_client::Error> {
+        self.backends
+            .call("get block", |client| client.get_block_by_hash(block_hash))
+    }
+
+    pub fn get_tx_status(&self, txid: &Txid) -> Result<Option<TxStatus>, esplora_client::Error> {
+        self.backends.call("get transaction status", |client| {
+            client.get_tx_status(txid)
+        })
+    }
+}
+
+impl WalletSync for FailoverBlockchain {
+    fn wallet_setup<D: BatchDatabase>(
+        &self,
+        database: &RefCell<D>,
+        progress_update: Box<dyn Progress>,
+    ) -> Result<(), bdk::Error> {
+        let progress_update = SharedProgress(Arc::new(Mutex::new(progress_update)));
+
+        self.backends
+            .with_failover("sync on-chain wallet", |index| {
+                // Esplora does not report any progress itself, so we at least report which backend
+                // we are syncing with. This also gives the caller the chance to abort the sync.
+                progress_update.update(
+                    0.0,
+                    Some(format!("Syncing with {}", self.backends.urls[index])),
+                )?;
+
+                self.blockchains[index].wallet_setup(database, Box::new(progress_update.clone()))
+            })
+    }
+}
+
+/// Forwards the progress of every sync attempt to the same [`Progress`].
+#[derive(Clone)]
+struct SharedProgress(Arc<Mutex<Box<dyn Progress>>>);
+
+impl Progress for SharedProgress {
+    fn update(&self, progress: f32, message: Option<String>) -> Result<(), bdk::Error> {
+        self.0.lock().update(progress, message)
+    }
+}
+
+impl GetHeight for FailoverBlockchain {
+    fn get_height(&self) -> Result<u32, bdk::Error> {
+        self.backends.with_failover("get block height", |index| {
+            self.blockchains[index].get_height()
+        })
+    }
+}
+
+impl GetTx for FailoverBlockchain {
+    fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, bdk::Error> {
+        self.backends.with_failover("get transaction", |index| {
+            self.blockchains[index].get_tx(txid)
+        })
+    }
+}
+
+impl GetBlockHash for FailoverBlockchain {
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, bdk::Error> {
+        self.backends.with_failover("get block hash", |index| {
+            self.blockchains[index].get_block_hash(height)
+        })
+    }
+}
+
+impl Blockchain for FailoverBlockchain {
+    fn get_capabilities(&self) -> HashSet<Capability> {
+        self.blockchains[0].get_capabilities()
+    }
+
+    fn broadcast(&self, tx: &Transaction) -> Result<(), bdk::Error> {
+        self.backends
+            .with_failover("broadcast transaction", |index| {
+                self.blockchains[index].broadcast(tx)
+            })
+    }
+
+    fn estimate_fee(&self, target: usize) -> Result<FeeRate, bdk::Error> {
+        self.backends.with_failover("estimate fee", |index| {
+            self.blockchains[index].estimate_fee(target)
+        })
+    }
+}
+
+/// Syncs the Lightning wallet against the [`EsploraBackends`], failing over between them.
+///
+/// Every backend has its own [`EsploraSyncClient`], all of them watching the same transactions and
+/// outputs, so that any of them can take over at any time.
+pub struct FailoverSyncClient {
+    backends: Arc<EsploraBackends>,
+    clients: Vec<EsploraSyncClient<Arc<TracingLogger>>>,
+}
+
+impl FailoverSyncClient {
+    pub fn new(backends: Arc<EsploraBackends>, logger: Arc<TracingLogger>) -> Self {
+        let clients = backends
+            .clients
+            .iter()
+            .map(|client| EsploraSyncClient::from_client(client.clone(), logger.clone()))
+            .collect();
+
+        Self { backends, clients }
+    }
+
+    pub fn sync(&self, confirmables: Vec<&(dyn Confirm + Sync + Send)>) -> Result<(), TxSyncError> {
+        self.backends
+            .with_failover("sync Lightning wallet", |index| {
+                self.clients[index].sync(confirmables.clone())
+            })
+    }
+}
+
+impl Filter for FailoverSyncClient {
+    fn register_tx(&self, txid: &Txid, script_pubkey: &Script) {
+        for client in self.clients.iter() {
+            client.register_tx(txid, script_pubkey);
+        }
+    }
+
+    fn register_output(&self, output: WatchedOutput) {
+        for client in self.clients.iter() {
+            client.register_output(output.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn fails_over_to_next_backend_and_keeps_it_active() {
+        let backends = EsploraBackends::new(vec![
+            "http://primary".to_string(),
+            "http://secondary".to_string(),
+            "http://tertiary".to_string(),
+        ])
+        .unwrap();
+
+        let mut attempts = vec![];
+        let result = backends.with_failover("test", |index| {
+            attempts.push(index);
+            match index {
+                1 => Ok(index),
+                _ => Err(anyhow!("unreachable")),
+            }
+        });
+
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(attempts, vec![0, 1]);
+        assert_eq!(backends.active_url(), "http://secondary");
+
+        let mut attempts = vec![];
+        let result = backends.with_failover("test", |index| {
+            attempts.push(index);
+            Err::<(), _>(anyhow!("unreachable"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, vec![1, 2, 0]);
+        assert_eq!(backends.active_url(), "http://secondary");
+    }
+
+    #[test]
+    fn requires_at_least_one_backend() {
+        assert!(EsploraBackends::new(vec![]).is_err());
+    }
+}
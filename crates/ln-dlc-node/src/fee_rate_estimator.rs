@@ -1,3 +1,6 @@
+use crate::chain_source::ChainSource;
+use crate::mempool_space::MempoolSpace;
+use anyhow::anyhow;
 use anyhow::Result;
 use bdk::FeeRate;
 use lightning::chain::chaininterface::ConfirmationTarget;
@@ -5,6 +8,7 @@ use lightning::chain::chaininterface::FeeEstimator;
 use lightning::chain::chaininterface::FEERATE_FLOOR_SATS_PER_KW;
 use parking_lot::RwLock;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 const CONFIRMATION_TARGETS: [(ConfirmationTarget, usize); 4] = [
     // We choose an extremely high background confirmation target to avoid force-closing channels
@@ -21,17 +25,52 @@ const CONFIRMATION_TARGETS: [(ConfirmationTarget, usize); 4] = [
 /// us up-to-date values.
 ///
 /// In sats/kwu.
-const FEE_RATE_DEFAULTS: [(ConfirmationTarget, u32); 3] = [
+const FEE_RATE_DEFAULTS: [(ConfirmationTarget, u32); 4] = [
+    (
+        ConfirmationTarget::MempoolMinimum,
+        FEERATE_FLOOR_SATS_PER_KW,
+    ),
     (ConfirmationTarget::Background, FEERATE_FLOOR_SATS_PER_KW),
     (ConfirmationTarget::Normal, 2000),
     (ConfirmationTarget::HighPriority, 5000),
 ];
 
 pub struct FeeRateEstimator {
-    client: esplora_client::BlockingClient,
+    /// The providers we get fee rates from, in order of preference.
+    providers: Vec<Box<dyn FeeRateProvider>>,
     fee_rate_cache: RwLock<HashMap<ConfirmationTarget, FeeRate>>,
 }
 
+/// A source of fee rate estimates for all our [`CONFIRMATION_TARGETS`].
+trait FeeRateProvider: Send + Sync {
+    fn name(&self) -> String;
+
+    fn get_fee_rates(&self) -> Result<HashMap<ConfirmationTarget, FeeRate>>;
+}
+
+impl FeeRateProvider for MempoolSpace {
+    fn name(&self) -> String {
+        format!("mempool.space ({})", self.url())
+    }
+
+    fn get_fee_rates(&self) -> Result<HashMap<ConfirmationTarget, FeeRate>> {
+        MempoolSpace::get_fee_rates(self, &CONFIRMATION_TARGETS)
+    }
+}
+
+impl FeeRateProvider for Arc<ChainSource> {
+    fn name(&self) -> String {
+        match self.as_ref() {
+            ChainSource::Esplora(_) => "esplora".to_string(),
+            ChainSource::Bitcoind(_) => "bitcoind".to_string(),
+        }
+    }
+
+    fn get_fee_rates(&self) -> Result<HashMap<ConfirmationTarget, FeeRate>> {
+        get_fee_rates(self)
+    }
+}
+
 pub trait EstimateFeeRate {
     fn estimate(&self, target: ConfirmationTarget) -> FeeRate;
 }
@@ -44,19 +83,22 @@ impl EstimateFeeRate for FeeRateEstimator {
 
 impl FeeRateEstimator {
     /// Constructor for the [`FeeRateEstimator`].
-    pub fn new(esplora_url: String) -> Self {
-        let client = esplora_client::BlockingClient::from_agent(esplora_url, ureq::agent());
-
-        let initial_fee_rates = match client.get_fee_estimates() {
-            Ok(estimates) => {
-                HashMap::from_iter(CONFIRMATION_TARGETS.into_iter().map(|(target, n_blocks)| {
-                    let fee_rate = esplora_client::convert_fee_rate(n_blocks, estimates.clone())
-                        .expect("fee rates for our confirmation targets");
-                    let fee_rate = FeeRate::from_sat_per_vb(fee_rate);
-
-                    (target, fee_rate)
-                }))
-            }
+    ///
+    /// Fee rates are taken from mempool.space if a `mempool_space_url` is given, falling back to
+    /// the [`ChainSource`]. If neither can give us fee rates on startup, we use static defaults.
+    pub fn new(chain_source: Arc<ChainSource>, mempool_space_url: Option<String>) -> Self {
+        let mut providers: Vec<Box<dyn FeeRateProvider>> = Vec::new();
+        if let Some(url) = mempool_space_url {
+            providers.push(Box::new(MempoolSpace::new(url)));
+        }
+        providers.push(Box::new(chain_source));
+
+        Self::from_providers(providers)
+    }
+
+    fn from_providers(providers: Vec<Box<dyn FeeRateProvider>>) -> Self {
+        let initial_fee_rates = match get_fee_rates_with_fallback(&providers) {
+            Ok(fee_rates) => fee_rates,
             Err(e) => {
                 tracing::warn!(defaults = ?FEE_RATE_DEFAULTS, "Initializing fee rate cache with default values: {e:#}");
 
@@ -71,7 +113,7 @@ impl FeeRateEstimator {
         let fee_rate_cache = RwLock::new(initial_fee_rates);
 
         Self {
-            client,
+            providers,
             fee_rate_cache,
         }
     }
@@ -84,18 +126,16 @@ impl FeeRateEstimator {
             .expect("to have entries for all confirmation targets")
     }
 
+    /// Update the fee rate cache with the fee rates of the first provider able to give us fee
+    /// rates. If none can, we keep the previous fee rates.
     pub(crate) async fn update(&self) -> Result<()> {
-        let estimates = self.client.get_fee_estimates()?;
+        let fee_rates = get_fee_rates_with_fallback(&self.providers)?;
 
         let mut locked_fee_rate_cache = self.fee_rate_cache.write();
-        for (target, n_blocks) in CONFIRMATION_TARGETS {
-            let fee_rate = esplora_client::convert_fee_rate(n_blocks, estimates.clone())?;
-
-            let fee_rate = FeeRate::from_sat_per_vb(fee_rate);
-
+        for (target, fee_rate) in fee_rates {
             locked_fee_rate_cache.insert(target, fee_rate);
             tracing::trace!(
-                n_blocks_confirmation = %n_blocks,
+                ?target,
                 sats_per_kwu = %fee_rate.fee_wu(1000),
                 "Updated fee rate estimate",
             );
@@ -105,8 +145,109 @@ impl FeeRateEstimator {
     }
 }
 
+/// Get the fee rates for all our [`CONFIRMATION_TARGETS`] from the first of the `providers` able
+/// to give us all of them.
+fn get_fee_rates_with_fallback(
+    providers: &[Box<dyn FeeRateProvider>],
+) -> Result<HashMap<ConfirmationTarget, FeeRate>> {
+    for provider in providers {
+        let fee_rates = provider.get_fee_rates().and_then(|fee_rates| {
+            match CONFIRMATION_TARGETS
+                .iter()
+                .find(|(target, _)| !fee_rates.contains_key(target))
+            {
+                Some((target, _)) => Err(anyhow!("Missing fee rate for {target:?}")),
+                None => Ok(fee_rates),
+            }
+        });
+
+        match fee_rates {
+            Ok(fee_rates) => return Ok(fee_rates),
+            Err(e) => {
+                tracing::warn!(
+                    provider = %provider.name(),
+                    "Failed to get fee rates: {e:#}"
+                );
+            }
+        }
+    }
+
+    Err(anyhow!("No fee rate provider could give us fee rates"))
+}
+
+/// Get the fee rates for all our [`CONFIRMATION_TARGETS`] from the [`ChainSource`].
+fn get_fee_rates(chain_source: &ChainSource) -> Result<HashMap<ConfirmationTarget, FeeRate>> {
+    let n_blocks = CONFIRMATION_TARGETS.map(|(_, n_blocks)| n_blocks);
+    let estimates = chain_source.get_fee_estimates(&n_blocks)?;
+
+    CONFIRMATION_TARGETS
+        .into_iter()
+        .map(|(target, n_blocks)| {
+            let fee_rate = esplora_client::convert_fee_rate(n_blocks, estimates.clone())?;
+
+            Ok((target, FeeRate::from_sat_per_vb(fee_rate)))
+        })
+        .collect()
+}
+
 impl FeeEstimator for FeeRateEstimator {
     fn get_est_sat_per_1000_weight(&self, confirmation_target: ConfirmationTarget) -> u32 {
         (self.estimate(confirmation_target).fee_wu(1000) as u32).max(FEERATE_FLOOR_SATS_PER_KW)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+
+    struct FixedFeeRates(Option<f32>);
+
+    impl FeeRateProvider for FixedFeeRates {
+        fn name(&self) -> String {
+            "fixed".to_string()
+        }
+
+        fn get_fee_rates(&self) -> Result<HashMap<ConfirmationTarget, FeeRate>> {
+            let sats_per_vbyte = match self.0 {
+                Some(sats_per_vbyte) => sats_per_vbyte,
+                None => bail!("unavailable"),
+            };
+
+            Ok(CONFIRMATION_TARGETS
+                .into_iter()
+                .map(|(target, _)| (target, FeeRate::from_sat_per_vb(sats_per_vbyte)))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn falls_back_to_next_provider() {
+        let estimator = FeeRateEstimator::from_providers(vec![
+            Box::new(FixedFeeRates(None)),
+            Box::new(FixedFeeRates(Some(12.0))),
+        ]);
+
+        assert_eq!(
+            estimator.estimate(ConfirmationTarget::Normal),
+            FeeRate::from_sat_per_vb(12.0)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_defaults_for_every_target() {
+        let estimator = FeeRateEstimator::from_providers(vec![Box::new(FixedFeeRates(None))]);
+
+        for (target, fee_rate) in FEE_RATE_DEFAULTS {
+            assert_eq!(
+                estimator.estimate(target),
+                FeeRate::from_sat_per_kwu(fee_rate as f32)
+            );
+        }
+
+        // Must not panic because of a missing default.
+        for (target, _) in CONFIRMATION_TARGETS {
+            estimator.estimate(target);
+        }
+    }
+}
@@ -1,17 +1,22 @@
 use crate::fee_rate_estimator::EstimateFeeRate;
 use crate::node::Fee;
 use crate::node::Storage;
+use crate::on_chain_wallet::FrozenUtxos;
 use anyhow::anyhow;
 use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bdk::blockchain::Blockchain;
 use bdk::blockchain::GetBlockHash;
 use bdk::blockchain::GetHeight;
+use bdk::blockchain::Progress;
 use bdk::database::BatchDatabase;
+use bdk::database::Database;
 use bdk::psbt::PsbtUtils;
 use bdk::wallet::AddressIndex;
 use bdk::FeeRate;
+use bdk::KeychainKind;
 use bdk::SignOptions;
 use bdk::SyncOptions;
 use bdk::TransactionDetails;
@@ -30,10 +35,13 @@ use lightning::chain::chaininterface::BroadcasterInterface;
 use lightning::chain::chaininterface::ConfirmationTarget;
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
+use parking_lot::RwLock;
 use rust_bitcoin_coin_selection::select_coins;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::RwLock;
 
 /// Taken from mempool.space
 const AVG_SEGWIT_TX_WEIGHT_VB: usize = 140;
@@ -51,6 +59,8 @@ where
     settings: RwLock<WalletSettings>,
     fee_rate_estimator: Arc<F>,
     locked_outpoints: Mutex<Vec<OutPoint>>,
+    /// UTXOs which were frozen manually and must never be selected by the wallet.
+    frozen_utxos: FrozenUtxos,
     node_storage: Arc<N>,
 }
 
@@ -58,6 +68,17 @@ where
 pub struct WalletSettings {
     pub max_allowed_tx_fee_rate_when_opening_channel: Option<u32>,
     pub jit_channels_enabled: bool,
+    /// JIT channels smaller than this are not opened.
+    pub jit_channel_min_size_sats: u64,
+    /// JIT channels larger than this are not opened. If `None`, there is no upper limit.
+    pub jit_channel_max_size_sats: Option<u64>,
+    /// Amount of on-chain funds which must stay in the wallet at all times, so that we are always
+    /// able to fee-bump transactions, e.g. via anchor outputs or CPFP.
+    ///
+    /// Channel opens and withdrawals which would dip into the reserve are refused.
+    pub on_chain_reserve_sats: u64,
+    /// Fee rates used by the wallet instead of the estimated ones, per confirmation target.
+    pub fee_rate_overrides: HashMap<ConfirmationTarget, FeeRate>,
 }
 
 impl Default for WalletSettings {
@@ -65,10 +86,100 @@ impl Default for WalletSettings {
         Self {
             max_allowed_tx_fee_rate_when_opening_channel: None,
             jit_channels_enabled: true,
+            jit_channel_min_size_sats: 0,
+            jit_channel_max_size_sats: None,
+            on_chain_reserve_sats: 0,
+            fee_rate_overrides: HashMap::new(),
         }
     }
 }
 
+/// An unspent output of the wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletUtxo {
+    pub outpoint: OutPoint,
+    pub amount_sats: u64,
+    pub address: Option<Address>,
+    pub is_confirmed: bool,
+    /// The UTXO was frozen manually and will not be spent by the wallet.
+    pub is_frozen: bool,
+    /// The UTXO is temporarily locked by an ongoing protocol, e.g. a channel funding.
+    pub is_locked: bool,
+}
+
+/// A receive address handed out by the wallet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletAddress {
+    pub address: Address,
+    /// The index of the address in the external keychain of the wallet.
+    pub derivation_index: u32,
+    /// The value of the unspent outputs paying to the address.
+    pub balance_sats: u64,
+    /// The number of transactions which paid to the address.
+    pub usage_count: usize,
+}
+
+/// The transaction the wallet would create to send funds on-chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SendPreview {
+    /// The amount received by the recipient. When draining, this is what is left after the fee.
+    pub amount_sats: u64,
+    /// The absolute fee of the transaction.
+    pub fee_sats: u64,
+    pub fee_rate: FeeRate,
+    /// The UTXOs spent by the transaction.
+    pub inputs: Vec<OutPoint>,
+}
+
+/// The state of the on-chain reserve of the wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveStatus {
+    /// The configured amount of on-chain funds to be kept in the wallet.
+    pub reserve_sats: u64,
+    /// The on-chain funds which are neither spent nor locked by an ongoing protocol.
+    pub spendable_sats: u64,
+    /// The on-chain funds which can be spent without dipping into the reserve.
+    pub available_sats: u64,
+    /// Whether the spendable funds have already fallen below the reserve.
+    pub is_below_reserve: bool,
+}
+
+impl ReserveStatus {
+    fn new(reserve_sats: u64, spendable_sats: u64) -> Self {
+        Self {
+            reserve_sats,
+            spendable_sats,
+            available_sats: spendable_sats.saturating_sub(reserve_sats),
+            is_below_reserve: spendable_sats < reserve_sats,
+        }
+    }
+
+    /// Fail if spending `amount_sats` (including fees) would dip into the reserve.
+    fn ensure_can_spend(&self, amount_sats: u64) -> Result<()> {
+        ensure!(
+            amount_sats <= self.available_sats,
+            "Spending {amount_sats} sats would dip into the on-chain reserve of {} sats; \
+             only {} sats are available",
+            self.reserve_sats,
+            self.available_sats
+        );
+
+        Ok(())
+    }
+
+    /// Fail if the whole wallet would be drained while a reserve is configured. Draining
+    /// manually selected UTXOs is fine, as long as they fit into the available funds.
+    fn ensure_can_drain(&self, amount_sat_or_drain: u64, utxos: Option<&[OutPoint]>) -> Result<()> {
+        ensure!(
+            amount_sat_or_drain > 0 || utxos.is_some() || self.reserve_sats == 0,
+            "Refusing to drain the wallet while an on-chain reserve of {} sats is configured",
+            self.reserve_sats
+        );
+
+        Ok(())
+    }
+}
+
 impl<D, B, F, N> Wallet<D, B, F, N>
 where
     D: BatchDatabase,
@@ -80,6 +191,7 @@ where
         blockchain: B,
         wallet: bdk::Wallet<D>,
         fee_rate_estimator: Arc<F>,
+        frozen_utxos: FrozenUtxos,
         node_storage: Arc<N>,
         settings: WalletSettings,
     ) -> Self {
@@ -92,6 +204,7 @@ where
             settings,
             fee_rate_estimator,
             locked_outpoints: Mutex::new(vec![]),
+            frozen_utxos,
             node_storage,
         }
     }
@@ -102,22 +215,59 @@ where
 
     pub async fn update_settings(&self, settings: WalletSettings) {
         tracing::info!(?settings, "Updating wallet settings");
-        *self.settings.write().await = settings;
+        *self.settings.write() = settings;
     }
 
     pub async fn settings(&self) -> WalletSettings {
-        self.settings.read().await.clone()
+        self.settings.read().clone()
+    }
+
+    /// Get the state of the on-chain reserve, taking into account the outpoints that are
+    /// currently locked.
+    pub fn reserve_status(&self) -> Result<ReserveStatus> {
+        let locked_utxos = self.locked_outpoints.lock();
+        self.reserve_status_excluding(&locked_utxos)
+    }
+
+    fn reserve_status_excluding(&self, locked_utxos: &[OutPoint]) -> Result<ReserveStatus> {
+        let unspendable_outpoints = self.unspendable_outpoints(locked_utxos)?;
+        let spendable_sats = self
+            .get_utxos()?
+            .iter()
+            .filter(|utxo| !utxo.is_spent)
+            .filter(|utxo| !unspendable_outpoints.contains(&utxo.outpoint))
+            .map(|utxo| utxo.txout.value)
+            .sum();
+
+        Ok(ReserveStatus::new(
+            self.settings.read().on_chain_reserve_sats,
+            spendable_sats,
+        ))
     }
 
     /// Update the internal BDK wallet database with the blockchain.
     pub fn sync(&self) -> Result<()> {
+        self.sync_with_options(SyncOptions::default())
+    }
+
+    /// Update the internal BDK wallet database with the blockchain, reporting the progress of the
+    /// sync to `progress`.
+    ///
+    /// The sync is aborted if `progress` returns an error.
+    pub(crate) fn sync_with_progress(&self, progress: impl Progress) -> Result<()> {
+        self.sync_with_options(SyncOptions {
+            progress: Some(Box::new(progress)),
+        })
+    }
+
+    fn sync_with_options(&self, sync_options: SyncOptions) -> Result<()> {
         let wallet_lock = self.bdk_lock();
 
         let now = Instant::now();
 
         tracing::info!("Started on-chain sync");
 
-        wallet_lock.sync(&self.blockchain, SyncOptions::default())?;
+        wallet_lock.sync(&self.blockchain, sync_options)?;
 
         tracing::info!(
             duration = now.elapsed().as_millis(),
@@ -129,8 +279,17 @@ where
         Ok(())
     }
 
+    /// The fee rate for the given confirmation target, unless it is overridden in the
+    /// [`WalletSettings`].
     pub fn get_fee_rate(&self, confirmation_target: ConfirmationTarget) -> FeeRate {
-        self.fee_rate_estimator.estimate(confirmation_target)
+        let fee_rate_override = self
+            .settings
+            .read()
+            .fee_rate_overrides
+            .get(&confirmation_target)
+            .copied();
+
+        fee_rate_override.unwrap_or_else(|| self.fee_rate_estimator.estimate(confirmation_target))
     }
 
     pub(crate) async fn create_funding_transaction(
@@ -144,9 +303,17 @@ where
             output_script,
             value_sats,
             Fee::FeeRate(fee_rate),
-            locked_utxos.clone(),
+            self.unspendable_outpoints(&locked_utxos)?,
+            None,
         )?;
 
+        let fee_sats = psbt
+            .fee_amount()
+            .context("Fee info could not be calculated")?;
+        self.reserve_status_excluding(&locked_utxos)?
+            .ensure_can_spend(value_sats + fee_sats)
+            .context("Refusing to open channel")?;
+
         let transaction = psbt.extract_tx();
 
         let prev_outpoints = transaction
@@ -167,6 +334,20 @@ where
             .address)
     }
 
+    /// The height of the chain tip at the time of the last successful sync, if any.
+    pub(crate) fn last_sync_height(&self) -> Result<Option<u32>> {
+        let sync_time = self.bdk_lock().database().get_sync_time()?;
+
+        Ok(sync_time.map(|sync_time| sync_time.block_time.height))
+    }
+
+    /// The number of scripts watched by the wallet, i.e. the scripts scanned by a sync.
+    pub(crate) fn script_count(&self) -> Result<usize> {
+        let scripts = self.bdk_lock().database().iter_script_pubkeys(None)?;
+
+        Ok(scripts.len())
+    }
+
     pub fn is_mine(&self, script: &Script) -> Result<bool> {
         Ok(self.bdk_lock().is_mine(script)?)
     }
@@ -180,6 +361,124 @@ where
         Ok(utxos)
     }
 
+    /// List the unspent outputs of the wallet, including whether they are frozen or locked.
+    pub fn list_utxos(&self) -> Result<Vec<WalletUtxo>> {
+        let frozen_utxos = self.frozen_utxos.all()?;
+        let locked_utxos = self.locked_outpoints.lock().clone();
+
+        let locked_wallet = self.bdk_lock();
+        let network = locked_wallet.network();
+
+        locked_wallet
+            .list_unspent()?
+            .into_iter()
+            .filter(|utxo| !utxo.is_spent)
+            .map(|utxo| -> Result<WalletUtxo> {
+                let is_confirmed = locked_wallet
+                    .get_tx(&utxo.outpoint.txid, false)?
+                    .map(|tx| tx.confirmation_time.is_some())
+                    .unwrap_or_default();
+
+                Ok(WalletUtxo {
+                    outpoint: utxo.outpoint,
+                    amount_sats: utxo.txout.value,
+                    address: Address::from_script(&utxo.txout.script_pubkey, network).ok(),
+                    is_confirmed,
+                    is_frozen: frozen_utxos.contains(&utxo.outpoint),
+                    is_locked: locked_utxos.contains(&utxo.outpoint),
+                })
+            })
+            .collect()
+    }
+
+    /// List the receive addresses which have been handed out by the wallet, ordered by their
+    /// derivation index.
+    pub fn list_receive_addresses(&self) -> Result<Vec<WalletAddress>> {
+        let locked_wallet = self.bdk_lock();
+        let network = locked_wallet.network();
+        let unspent = locked_wallet.list_unspent()?;
+        let transactions = locked_wallet.list_transactions(true)?;
+
+        let database = locked_wallet.database();
+
+        // The wallet derives addresses ahead of the last revealed index to discover incoming
+        // payments, but those have never been shown to anyone.
+        let last_index = match database.get_last_index(KeychainKind::External)? {
+            Some(last_index) => last_index,
+            None => return Ok(vec![]),
+        };
+
+        let mut addresses = vec![];
+        for script in database.iter_script_pubkeys(Some(KeychainKind::External))? {
+            let derivation_index = match database.get_path_from_script_pubkey(&script)? {
+                Some((_, index)) if index <= last_index => index,
+                _ => continue,
+            };
+
+            let address = Address::from_script(&script, network)
+                .context("Wallet script is not a valid address")?;
+
+            let balance_sats = unspent
+                .iter()
+                .filter(|utxo| !utxo.is_spent && utxo.txout.script_pubkey == script)
+                .map(|utxo| utxo.txout.value)
+                .sum();
+
+            let usage_count = transactions
+                .iter()
+                .filter_map(|details| details.transaction.as_ref())
+                .filter(|tx| {
+                    tx.output
+                        .iter()
+                        .any(|output| output.script_pubkey == script)
+                })
+                .count();
+
+            addresses.push(WalletAddress {
+                address,
+                derivation_index,
+                balance_sats,
+                usage_count,
+            });
+        }
+
+        addresses.sort_by_key(|address| address.derivation_index);
+
+        Ok(addresses)
+    }
+
+    /// Freeze the given UTXO, so that the wallet does not spend it until it is unfrozen again.
+    pub fn freeze_utxo(&self, outpoint: OutPoint) -> Result<()> {
+        let is_ours = self
+            .get_utxos()?
+            .iter()
+            .any(|utxo| utxo.outpoint == outpoint && !utxo.is_spent);
+        ensure!(is_ours, "Unknown UTXO {outpoint}");
+
+        self.frozen_utxos.freeze(outpoint)?;
+
+        tracing::info!(%outpoint, "Froze UTXO");
+
+        Ok(())
+    }
+
+    pub fn unfreeze_utxo(&self, outpoint: OutPoint) -> Result<()> {
+        self.frozen_utxos.unfreeze(outpoint)?;
+
+        tracing::info!(%outpoint, "Unfroze UTXO");
+
+        Ok(())
+    }
+
+    /// The outpoints the wallet must not select on its own: the `locked_utxos` of ongoing
+    /// protocols and the frozen UTXOs.
+    fn unspendable_outpoints(&self, locked_utxos: &[OutPoint]) -> Result<Vec<OutPoint>> {
+        let mut unspendable_outpoints = self.frozen_utxos.all()?;
+        unspendable_outpoints.extend_from_slice(locked_utxos);
+
+        Ok(unspendable_outpoints)
+    }
+
     pub fn get_utxos_for_amount(
         &self,
         amount: u64,
@@ -190,11 +489,17 @@ where
         // get temporarily reserved utxo from in-memory storage
         let mut reserved_outpoints = self.locked_outpoints.lock();
 
-        // filter reserved utxos from all known utxos to not accidentally double spend and those who
-        // have actually been spent already
+        self.reserve_status_excluding(&reserved_outpoints)?
+            .ensure_can_spend(amount)
+            .context("Refusing to fund channel")?;
+
+        let unspendable_outpoints = self.unspendable_outpoints(&reserved_outpoints)?;
+
+        // filter reserved and frozen utxos from all known utxos to not accidentally double spend
+        // and those who have actually been spent already
         let utxos = utxos
             .iter()
-            .filter(|utxo| !reserved_outpoints.contains(&utxo.outpoint))
+            .filter(|utxo| !unspendable_outpoints.contains(&utxo.outpoint))
             .filter(|utxo| !utxo.is_spent)
             .collect::<Vec<_>>();
 
@@ -235,34 +540,90 @@ where
     }
 
     /// Build the PSBT for sending funds to a given script and signs it
+    ///
+    /// If `utxos` is set, only those UTXOs are spent. Otherwise the wallet selects the UTXOs on
+    /// its own, skipping the `unspendable_outpoints`.
     fn build_psbt(
         &self,
         recipient: Script,
         amount_sat_or_drain: u64,
         fee: Fee,
-        locked_utxos: Vec<OutPoint>,
+        unspendable_outpoints: Vec<OutPoint>,
+        utxos: Option<&[OutPoint]>,
     ) -> Result<PartiallySignedTransaction> {
         let locked_wallet = self.bdk_lock();
+        let mut psbt = self.build_unsigned_psbt(
+            &locked_wallet,
+            recipient,
+            amount_sat_or_drain,
+            fee,
+            unspendable_outpoints,
+            utxos,
+        )?;
+
+        match locked_wallet.sign(&mut psbt, SignOptions::default()) {
+            Ok(finalized) => {
+                if !finalized {
+                    bail!("On chain creation failed");
+                }
+            }
+            Err(err) => {
+                bail!(err)
+            }
+        }
+
+        Ok(psbt)
+    }
+
+    /// Build the unsigned PSBT for sending funds to a given script.
+    ///
+    /// See [`Wallet::build_psbt`] for the UTXO selection.
+    fn build_unsigned_psbt(
+        &self,
+        locked_wallet: &bdk::Wallet<D>,
+        recipient: Script,
+        amount_sat_or_drain: u64,
+        fee: Fee,
+        unspendable_outpoints: Vec<OutPoint>,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<PartiallySignedTransaction> {
         let mut tx_builder = locked_wallet.build_tx();
 
-        for outpoint in locked_utxos.iter() {
-            tx_builder.add_unspendable(*outpoint);
+        match utxos {
+            Some(utxos) => {
+                if let Some(outpoint) = utxos
+                    .iter()
+                    .find(|outpoint| unspendable_outpoints.contains(outpoint))
+                {
+                    bail!("UTXO {outpoint} is frozen or locked");
+                }
+
+                tx_builder.add_utxos(utxos)?.manually_selected_only();
+            }
+            None => {
+                for outpoint in unspendable_outpoints.iter() {
+                    tx_builder.add_unspendable(*outpoint);
+                }
+            }
         }
 
         if amount_sat_or_drain > 0 {
             tx_builder
                 .add_recipient(recipient, amount_sat_or_drain)
                 .enable_rbf();
+        } else if utxos.is_some() {
+            // Only drain the manually selected UTXOs.
+            tx_builder.drain_to(recipient).enable_rbf();
         } else {
             tx_builder.drain_wallet().drain_to(recipient).enable_rbf();
         }
 
         match fee {
-            Fee::Priority(target) => tx_builder.fee_rate(self.fee_rate_estimator.estimate(target)),
+            Fee::Priority(target) => tx_builder.fee_rate(self.get_fee_rate(target)),
             Fee::FeeRate(fee_rate) => tx_builder.fee_rate(fee_rate),
         };
 
-        let mut psbt = match tx_builder.finish() {
+        let psbt = match tx_builder.finish() {
             Ok((psbt, _)) => {
                 tracing::trace!("Created PSBT: {:?}", psbt);
                 psbt
@@ -272,17 +633,6 @@ where
             }
         };
 
-        match locked_wallet.sign(&mut psbt, SignOptions::default()) {
-            Ok(finalized) => {
-                if !finalized {
-                    bail!("On chain creation failed");
-                }
-            }
-            Err(err) => {
-                bail!(err)
-            }
-        }
-
         Ok(psbt)
     }
 
@@ -298,7 +648,8 @@ where
             address.script_pubkey(),
             amount_sat_or_drain,
             Fee::Priority(confirmation_target),
-            locked_utxos.clone(),
+            self.unspendable_outpoints(&locked_utxos)?,
+            None,
         );
 
         let fee_sat = match psbt {
@@ -306,7 +657,7 @@ where
                 .fee_amount()
                 .context("Fee info could not be calculated")?,
             Err(_) => {
-                let rate = self.fee_rate_estimator.estimate(confirmation_target);
+                let rate = self.get_fee_rate(confirmation_target);
                 rate.fee_vb(AVG_SEGWIT_TX_WEIGHT_VB)
             }
         };
@@ -317,22 +668,35 @@ where
     /// Send funds to the given address.
     ///
     /// If `amount_sat_or_drain` is `0` the wallet will be drained, i.e., all available funds
-    /// will be spent.
+    /// will be spent. If `utxos` is set, only those UTXOs are spent, respectively drained.
     pub(crate) fn send_to_address(
         &self,
         address: &Address,
         amount_sat_or_drain: u64,
         fee: Fee,
+        utxos: Option<Vec<OutPoint>>,
     ) -> Result<Txid> {
         let mut locked_utxos = self.locked_outpoints.lock();
-        let tx = self
-            .build_psbt(
-                address.script_pubkey(),
-                amount_sat_or_drain,
-                fee,
-                locked_utxos.clone(),
-            )?
-            .extract_tx();
+        let reserve_status = self.reserve_status_excluding(&locked_utxos)?;
+        reserve_status.ensure_can_drain(amount_sat_or_drain, utxos.as_deref())?;
+
+        let psbt = self.build_psbt(
+            address.script_pubkey(),
+            amount_sat_or_drain,
+            fee,
+            self.unspendable_outpoints(&locked_utxos)?,
+            utxos.as_deref(),
+        )?;
+
+        self.ensure_reserve_allows(
+            &reserve_status,
+            &psbt,
+            amount_sat_or_drain,
+            utxos.as_deref(),
+        )
+        .context("Refusing to send funds")?;
+
+        let tx = psbt.extract_tx();
 
         let prev_outpoints = tx
             .input
@@ -362,6 +726,450 @@ where
         Ok(txid)
     }
 
+    /// Build an unsigned PSBT sending funds to the given address, to be signed by an external
+    /// signer and passed back to [`Wallet::broadcast_psbt`].
+    ///
+    /// The UTXO selection and the on-chain reserve are handled as in
+    /// [`Wallet::send_to_address`]. The selected UTXOs are not locked, as the PSBT may never come
+    /// back; if they are spent in the meantime, broadcasting the PSBT fails.
+    pub fn create_unsigned_psbt(
+        &self,
+        address: &Address,
+        amount_sat_or_drain: u64,
+        fee: Fee,
+        utxos: Option<Vec<OutPoint>>,
+    ) -> Result<PartiallySignedTransaction> {
+        let psbt = self
+            .build_checked_unsigned_psbt(address, amount_sat_or_drain, fee, utxos.as_deref())
+            .context("Refusing to create PSBT")?;
+
+        tracing::info!(
+            txid = %psbt.unsigned_tx.txid(),
+            %address,
+            amount_sat_or_drain,
+            "Created unsigned PSBT"
+        );
+
+        Ok(psbt)
+    }
+
+    /// Compute the transaction [`Wallet::send_to_address`] would create, without signing or
+    /// broadcasting it.
+    ///
+    /// The inputs of the preview can be passed to [`Wallet::send_to_address`] to send exactly the
+    /// previewed transaction, as the coin selection might otherwise pick different UTXOs.
+    pub fn preview_send(
+        &self,
+        address: &Address,
+        amount_sat_or_drain: u64,
+        fee: Fee,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<SendPreview> {
+        let psbt = self.build_checked_unsigned_psbt(address, amount_sat_or_drain, fee, utxos)?;
+
+        self.send_preview(&psbt, &[address.script_pubkey()], fee)
+    }
+
+    /// Compute the transaction [`Wallet::send_batch`] would create, without signing or
+    /// broadcasting it.
+    pub fn preview_batch_send(
+        &self,
+        recipients: &[(Address, u64)],
+        fee: Fee,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<SendPreview> {
+        let locked_utxos = self.locked_outpoints.lock();
+        let psbt = {
+            let locked_wallet = self.bdk_lock();
+            self.build_unsigned_batch_psbt(&locked_wallet, &locked_utxos, recipients, fee, utxos)?
+        };
+
+        self.send_preview(&psbt, &recipient_scripts(recipients), fee)
+    }
+
+    /// Pay all `recipients` with a single transaction, with at most one change output back to the
+    /// wallet.
+    ///
+    /// If `utxos` is set, exactly those UTXOs are spent, e.g. the inputs of a
+    /// [`Wallet::preview_batch_send`].
+    pub fn send_batch(
+        &self,
+        recipients: &[(Address, u64)],
+        fee: Fee,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<(Txid, SendPreview)> {
+        let mut locked_utxos = self.locked_outpoints.lock();
+        let psbt = {
+            let locked_wallet = self.bdk_lock();
+            let mut psbt = self.build_unsigned_batch_psbt(
+                &locked_wallet,
+                &locked_utxos,
+                recipients,
+                fee,
+                utxos,
+            )?;
+
+            let finalized = locked_wallet
+                .sign(&mut psbt, SignOptions::default())
+                .context("Failed to sign batch transaction")?;
+            ensure!(finalized, "Batch transaction could not be finalized");
+
+            psbt
+        };
+
+        let preview = self.send_preview(&psbt, &recipient_scripts(recipients), fee)?;
+
+        let tx = psbt.extract_tx();
+        locked_utxos.extend(tx.input.iter().map(|input| input.previous_output));
+
+        let txid = self.broadcast_transaction(&tx)?;
+
+        tracing::info!(
+            %txid,
+            recipients = recipients.len(),
+            amount_sats = preview.amount_sats,
+            fee_sats = preview.fee_sats,
+            "Sent batch transaction"
+        );
+
+        Ok((txid, preview))
+    }
+
+    /// Build the unsigned PSBT paying all `recipients`, ensuring that it does not spend the
+    /// on-chain reserve.
+    fn build_unsigned_batch_psbt(
+        &self,
+        locked_wallet: &bdk::Wallet<D>,
+        locked_utxos: &[OutPoint],
+        recipients: &[(Address, u64)],
+        fee: Fee,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<PartiallySignedTransaction> {
+        ensure!(
+            !recipients.is_empty(),
+            "A batch needs at least one recipient"
+        );
+        if let Some((address, _)) = recipients.iter().find(|(_, amount)| *amount == 0) {
+            bail!("Cannot drain the wallet in a batch, but got no amount for {address}");
+        }
+
+        let unspendable_outpoints = self.unspendable_outpoints(locked_utxos)?;
+
+        let mut tx_builder = locked_wallet.build_tx();
+
+        match utxos {
+            Some(utxos) => {
+                if let Some(outpoint) = utxos
+                    .iter()
+                    .find(|outpoint| unspendable_outpoints.contains(outpoint))
+                {
+                    bail!("UTXO {outpoint} is frozen or locked");
+                }
+
+                tx_builder.add_utxos(utxos)?.manually_selected_only();
+            }
+            None => {
+                for outpoint in unspendable_outpoints.iter() {
+                    tx_builder.add_unspendable(*outpoint);
+                }
+            }
+        }
+
+        tx_builder
+            .set_recipients(
+                recipients
+                    .iter()
+                    .map(|(address, amount)| (address.script_pubkey(), *amount))
+                    .collect(),
+            )
+            .enable_rbf();
+
+        match fee {
+            Fee::Priority(target) => tx_builder.fee_rate(self.get_fee_rate(target)),
+            Fee::FeeRate(fee_rate) => tx_builder.fee_rate(fee_rate),
+        };
+
+        let (psbt, _) = tx_builder
+            .finish()
+            .context("Failed to build batch transaction")?;
+
+        let amount_sats = recipients.iter().map(|(_, amount)| amount).sum();
+        let reserve_status = self.reserve_status_excluding(locked_utxos)?;
+        self.ensure_reserve_allows(&reserve_status, &psbt, amount_sats, None)
+            .context("Refusing to send batch")?;
+
+        Ok(psbt)
+    }
+
+    fn send_preview(
+        &self,
+        psbt: &PartiallySignedTransaction,
+        recipients: &[Script],
+        fee: Fee,
+    ) -> Result<SendPreview> {
+        let amount_sats = psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .filter(|output| recipients.contains(&output.script_pubkey))
+            .map(|output| output.value)
+            .sum();
+        let fee_sats = psbt
+            .fee_amount()
+            .context("Fee info could not be calculated")?;
+        let fee_rate = match fee {
+            Fee::Priority(target) => self.get_fee_rate(target),
+            Fee::FeeRate(fee_rate) => fee_rate,
+        };
+        let inputs = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .collect();
+
+        Ok(SendPreview {
+            amount_sats,
+            fee_sats,
+            fee_rate,
+            inputs,
+        })
+    }
+
+    /// Build the unsigned PSBT for sending funds to `address`, ensuring that it does not spend the
+    /// on-chain reserve.
+    fn build_checked_unsigned_psbt(
+        &self,
+        address: &Address,
+        amount_sat_or_drain: u64,
+        fee: Fee,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<PartiallySignedTransaction> {
+        let locked_utxos = self.locked_outpoints.lock();
+        let reserve_status = self.reserve_status_excluding(&locked_utxos)?;
+        reserve_status.ensure_can_drain(amount_sat_or_drain, utxos)?;
+
+        let psbt = {
+            let locked_wallet = self.bdk_lock();
+            self.build_unsigned_psbt(
+                &locked_wallet,
+                address.script_pubkey(),
+                amount_sat_or_drain,
+                fee,
+                self.unspendable_outpoints(&locked_utxos)?,
+                utxos,
+            )?
+        };
+
+        self.ensure_reserve_allows(&reserve_status, &psbt, amount_sat_or_drain, utxos)?;
+
+        Ok(psbt)
+    }
+
+    /// Finalize a PSBT signed by an external signer and broadcast its transaction.
+    pub fn broadcast_psbt(&self, mut psbt: PartiallySignedTransaction) -> Result<Txid> {
+        let mut locked_utxos = self.locked_outpoints.lock();
+        if let Some(input) = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .find(|input| locked_utxos.contains(&input.previous_output))
+        {
+            bail!(
+                "Output {} is locked by an ongoing protocol",
+                input.previous_output
+            );
+        }
+
+        let finalized = self
+            .bdk_lock()
+            .finalize_psbt(&mut psbt, SignOptions::default())
+            .context("Failed to finalize PSBT")?;
+        ensure!(finalized, "PSBT is not fully signed");
+
+        let tx = psbt.extract_tx();
+
+        locked_utxos.extend(tx.input.iter().map(|input| input.previous_output));
+
+        let txid = self.broadcast_transaction(&tx)?;
+
+        tracing::info!(%txid, "Broadcast externally signed PSBT");
+
+        Ok(txid)
+    }
+
+    /// Ensure that spending the funds of the given PSBT keeps the on-chain reserve.
+    fn ensure_reserve_allows(
+        &self,
+        reserve_status: &ReserveStatus,
+        psbt: &PartiallySignedTransaction,
+        amount_sat_or_drain: u64,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<()> {
+        let fee_sats = psbt
+            .fee_amount()
+            .context("Fee info could not be calculated")?;
+        let spent_sats = match utxos {
+            // When draining the selected UTXOs, all of them are spent.
+            Some(utxos) if amount_sat_or_drain == 0 => self
+                .get_utxos()?
+                .iter()
+                .filter(|utxo| utxos.contains(&utxo.outpoint))
+                .map(|utxo| utxo.txout.value)
+                .sum(),
+            _ => amount_sat_or_drain + fee_sats,
+        };
+
+        reserve_status.ensure_can_spend(spent_sats)
+    }
+
+    /// Replace the unconfirmed transaction `txid` with a version paying the given `fee_rate`
+    /// (RBF).
+    ///
+    /// Replacing a transaction changes its txid, hence funding transactions of known channels are
+    /// rejected. Use [`Wallet::cpfp`] for those instead.
+    ///
+    /// Fee bumping is what the on-chain reserve is kept for, hence it may be spent here.
+    pub fn bump_fee(&self, txid: Txid, fee_rate: FeeRate) -> Result<Txid> {
+        let funds_channel = self
+            .node_storage
+            .all_non_pending_channels()?
+            .iter()
+            .any(|channel| channel.funding_txid == Some(txid));
+        ensure!(
+            !funds_channel,
+            "Transaction {txid} funds a channel, its fee can only be bumped with CPFP"
+        );
+
+        let mut locked_utxos = self.locked_outpoints.lock();
+        let psbt = {
+            let locked_wallet = self.bdk_lock();
+
+            let mut tx_builder = locked_wallet
+                .build_fee_bump(txid)
+                .with_context(|| format!("Failed to bump fee of transaction {txid}"))?;
+            tx_builder.fee_rate(fee_rate).enable_rbf();
+
+            let (mut psbt, _) = tx_builder
+                .finish()
+                .with_context(|| format!("Failed to bump fee of transaction {txid}"))?;
+
+            let finalized = locked_wallet.sign(&mut psbt, SignOptions::default())?;
+            ensure!(
+                finalized,
+                "Failed to sign replacement of transaction {txid}"
+            );
+
+            psbt
+        };
+
+        let tx = psbt.extract_tx();
+
+        locked_utxos.extend(tx.input.iter().map(|input| input.previous_output));
+
+        let replacement_txid = self.broadcast_transaction(&tx)?;
+
+        tracing::info!(
+            %txid,
+            %replacement_txid,
+            fee_rate = fee_rate.as_sat_per_vb(),
+            "Replaced transaction to bump its fee"
+        );
+
+        Ok(replacement_txid)
+    }
+
+    /// Spend our unconfirmed `outpoint` back to ourselves so that the parent transaction and the
+    /// child together pay the given `fee_rate` (CPFP).
+    ///
+    /// Fee bumping is what the on-chain reserve is kept for, hence it may be spent here.
+    pub fn cpfp(&self, outpoint: OutPoint, fee_rate: FeeRate) -> Result<Txid> {
+        let mut locked_utxos = self.locked_outpoints.lock();
+        ensure!(
+            !locked_utxos.contains(&outpoint),
+            "Output {outpoint} is locked by an ongoing protocol"
+        );
+
+        let psbt = {
+            let locked_wallet = self.bdk_lock();
+
+            let parent = locked_wallet
+                .get_tx(&outpoint.txid, true)?
+                .with_context(|| format!("Unknown transaction {}", outpoint.txid))?;
+            ensure!(
+                parent.confirmation_time.is_none(),
+                "Transaction {} is already confirmed",
+                outpoint.txid
+            );
+            let parent_vsize = parent
+                .transaction
+                .context("Missing raw parent transaction")?
+                .vsize();
+            // The fee is unknown if the parent spends inputs which are not ours, e.g. a channel
+            // close published by our counterparty. In that case we pay for the whole package.
+            let parent_fee = parent.fee.unwrap_or_default();
+
+            let drain_script = locked_wallet
+                .get_address(AddressIndex::New)?
+                .script_pubkey();
+
+            // Build the child once at the target fee rate to learn its size, then again paying
+            // for the fee the parent is missing.
+            let child_vsize = {
+                let mut tx_builder = locked_wallet.build_tx();
+                tx_builder
+                    .add_utxo(outpoint)?
+                    .manually_selected_only()
+                    .drain_to(drain_script.clone())
+                    .fee_rate(fee_rate)
+                    .enable_rbf();
+
+                let (mut psbt, _) = tx_builder
+                    .finish()
+                    .with_context(|| format!("Failed to spend output {outpoint}"))?;
+                locked_wallet.sign(&mut psbt, SignOptions::default())?;
+
+                psbt.extract_tx().vsize()
+            };
+            let package_fee = fee_rate.fee_vb(parent_vsize + child_vsize);
+            let child_fee = package_fee
+                .saturating_sub(parent_fee)
+                .max(fee_rate.fee_vb(child_vsize));
+
+            let mut tx_builder = locked_wallet.build_tx();
+            tx_builder
+                .add_utxo(outpoint)?
+                .manually_selected_only()
+                .drain_to(drain_script)
+                .fee_absolute(child_fee)
+                .enable_rbf();
+
+            let (mut psbt, _) = tx_builder.finish().with_context(|| {
+                format!("Output {outpoint} is too small to pay a fee of {child_fee} sats")
+            })?;
+
+            let finalized = locked_wallet.sign(&mut psbt, SignOptions::default())?;
+            ensure!(finalized, "Failed to sign CPFP transaction");
+
+            psbt
+        };
+
+        let tx = psbt.extract_tx();
+
+        locked_utxos.push(outpoint);
+
+        let txid = self.broadcast_transaction(&tx)?;
+
+        tracing::info!(
+            %outpoint,
+            %txid,
+            fee_rate = fee_rate.as_sat_per_vb(),
+            "Published CPFP transaction"
+        );
+
+        Ok(txid)
+    }
+
     pub fn tip(&self) -> Result<(u32, BlockHash)> {
         let height = self.blockchain.get_height()?;
         let hash = self.blockchain.get_block_hash(height as u64)?;
@@ -429,12 +1237,22 @@ impl rust_bitcoin_coin_selection::Utxo for UtxoWrap {
     }
 }
 
+fn recipient_scripts(recipients: &[(Address, u64)]) -> Vec<Script> {
+    recipients
+        .iter()
+        .map(|(address, _)| address.script_pubkey())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::channel::Channel;
+    use crate::channel::ChannelState;
+    use crate::channel::UserChannelId;
     use crate::fee_rate_estimator::EstimateFeeRate;
     use crate::ldk_node_wallet::Wallet;
+    use crate::node::InMemoryStore;
     use anyhow::Result;
     use bdk::blockchain::Blockchain;
     use bdk::blockchain::Capability;
@@ -463,6 +1281,7 @@ mod tests {
     use rand::RngCore;
     use std::cell::RefCell;
     use std::collections::HashSet;
+    use std::str::FromStr;
     use std::sync::Arc;
 
     #[tokio::test]
@@ -473,6 +1292,7 @@ mod tests {
             DummyEsplora,
             test_wallet,
             Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
             Arc::new(DummyNodeStorage),
             WalletSettings::default(),
         );
@@ -504,6 +1324,277 @@ mod tests {
             .is_err());
     }
 
+    #[tokio::test]
+    async fn wallet_should_refuse_to_fund_channel_dipping_into_reserve() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 2).unwrap();
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            Arc::new(DummyNodeStorage),
+            WalletSettings {
+                on_chain_reserve_sats: Amount::from_btc(0.5).unwrap().to_sat(),
+                ..WalletSettings::default()
+            },
+        );
+
+        let reserve_status = wallet.reserve_status().unwrap();
+        assert_eq!(
+            reserve_status.available_sats,
+            Amount::from_btc(1.5).unwrap().to_sat()
+        );
+        assert!(!reserve_status.is_below_reserve);
+
+        let fee_rate = FeeRate::from_sat_per_vb(10.0);
+        let _ = wallet
+            .create_funding_transaction(
+                Script::new(),
+                Amount::from_btc(0.5).unwrap().to_sat(),
+                fee_rate,
+            )
+            .await
+            .unwrap();
+        assert!(wallet
+            .create_funding_transaction(
+                Script::new(),
+                Amount::from_btc(0.5).unwrap().to_sat(),
+                fee_rate,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn wallet_should_not_spend_frozen_utxos() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 2).unwrap();
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            Arc::new(DummyNodeStorage),
+            WalletSettings::default(),
+        );
+
+        let frozen_outpoint = wallet.get_utxos().unwrap()[0].outpoint;
+        wallet.freeze_utxo(frozen_outpoint).unwrap();
+
+        let utxos = wallet.list_utxos().unwrap();
+        assert_eq!(utxos.len(), 2);
+        assert!(utxos
+            .iter()
+            .all(|utxo| utxo.is_frozen == (utxo.outpoint == frozen_outpoint)));
+
+        let fee_rate = FeeRate::from_sat_per_vb(10.0);
+        let transaction = wallet
+            .create_funding_transaction(
+                Script::new(),
+                Amount::from_btc(0.5).unwrap().to_sat(),
+                fee_rate,
+            )
+            .await
+            .unwrap();
+        assert!(transaction
+            .input
+            .iter()
+            .all(|input| input.previous_output != frozen_outpoint));
+        assert!(wallet
+            .create_funding_transaction(
+                Script::new(),
+                Amount::from_btc(0.5).unwrap().to_sat(),
+                fee_rate,
+            )
+            .await
+            .is_err());
+
+        wallet.unfreeze_utxo(frozen_outpoint).unwrap();
+        let _ = wallet
+            .create_funding_transaction(
+                Script::new(),
+                Amount::from_btc(0.5).unwrap().to_sat(),
+                fee_rate,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn wallet_should_only_broadcast_signed_psbt() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 2).unwrap();
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            Arc::new(DummyNodeStorage),
+            WalletSettings::default(),
+        );
+
+        let address = wallet.get_last_unused_address().unwrap();
+        let fee = Fee::FeeRate(FeeRate::from_sat_per_vb(10.0));
+        let mut psbt = wallet
+            .create_unsigned_psbt(&address, Amount::from_btc(0.5).unwrap().to_sat(), fee, None)
+            .unwrap();
+
+        assert!(psbt
+            .inputs
+            .iter()
+            .all(|input| input.partial_sigs.is_empty() && input.final_script_witness.is_none()));
+        assert!(wallet.broadcast_psbt(psbt.clone()).is_err());
+
+        // Sign without finalizing, like an external signer would.
+        wallet
+            .bdk_lock()
+            .sign(
+                &mut psbt,
+                SignOptions {
+                    try_finalize: false,
+                    ..SignOptions::default()
+                },
+            )
+            .unwrap();
+
+        let txid = wallet.broadcast_psbt(psbt.clone()).unwrap();
+        assert_eq!(txid, psbt.unsigned_tx.txid());
+    }
+
+    #[test]
+    fn wallet_should_refuse_to_replace_channel_funding_transaction() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 1).unwrap();
+        let node_storage = Arc::new(InMemoryStore::default());
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            node_storage.clone(),
+            WalletSettings::default(),
+        );
+
+        let txid = wallet.on_chain_transaction_list().unwrap()[0].txid;
+
+        let counterparty = PublicKey::from_str(
+            "02bd998ebd176715fe92b7467cf6b1df8023950a4dd911db4c94dfc89cc9f5a655",
+        )
+        .unwrap();
+        let mut channel = Channel::new(UserChannelId::new(), 0, 100_000, counterparty);
+        channel.channel_state = ChannelState::Open;
+        channel.funding_txid = Some(txid);
+        node_storage.upsert_channel(channel).unwrap();
+
+        let error = wallet
+            .bump_fee(txid, FeeRate::from_sat_per_vb(10.0))
+            .unwrap_err();
+
+        assert!(error.to_string().contains("funds a channel"));
+    }
+
+    #[test]
+    fn preview_of_draining_the_wallet_pays_the_balance_minus_the_fee() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 2).unwrap();
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            Arc::new(DummyNodeStorage),
+            WalletSettings::default(),
+        );
+
+        let address = wallet.get_last_unused_address().unwrap();
+        let fee = Fee::FeeRate(FeeRate::from_sat_per_vb(10.0));
+
+        let preview = wallet.preview_send(&address, 0, fee, None).unwrap();
+
+        assert_eq!(preview.inputs.len(), 2);
+        assert!(preview.fee_sats > 0);
+        assert_eq!(
+            preview.amount_sats + preview.fee_sats,
+            Amount::from_btc(2.0).unwrap().to_sat()
+        );
+
+        // Spending the previewed inputs results in the same transaction.
+        let same_preview = wallet
+            .preview_send(&address, 0, fee, Some(&preview.inputs))
+            .unwrap();
+        assert_eq!(same_preview, preview);
+    }
+
+    #[test]
+    fn batch_pays_all_recipients_in_one_transaction() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 2).unwrap();
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            Arc::new(DummyNodeStorage),
+            WalletSettings::default(),
+        );
+
+        let fee = Fee::FeeRate(FeeRate::from_sat_per_vb(10.0));
+        let recipients = (0..3)
+            .map(|_| {
+                let address = wallet.bdk_lock().get_address(AddressIndex::New).unwrap();
+                (address.address, 40_000_000)
+            })
+            .collect::<Vec<_>>();
+
+        let preview = wallet.preview_batch_send(&recipients, fee, None).unwrap();
+        assert_eq!(preview.amount_sats, 120_000_000);
+        assert_eq!(preview.inputs.len(), 2);
+        assert!(preview.fee_sats > 0);
+
+        // Spending the previewed inputs results in the same transaction.
+        let same_preview = wallet
+            .preview_batch_send(&recipients, fee, Some(&preview.inputs))
+            .unwrap();
+        assert_eq!(same_preview, preview);
+
+        assert!(wallet.preview_batch_send(&[], fee, None).is_err());
+    }
+
+    #[test]
+    fn fee_rate_override_takes_precedence_over_estimate() {
+        let mut rng = thread_rng();
+        let test_wallet = new_test_wallet(&mut rng, Amount::from_btc(1.0).unwrap(), 2).unwrap();
+        let wallet = Wallet::new(
+            DummyEsplora,
+            test_wallet,
+            Arc::new(DummyFeeRateEstimator),
+            dummy_frozen_utxos(),
+            Arc::new(DummyNodeStorage),
+            WalletSettings {
+                fee_rate_overrides: HashMap::from([(
+                    ConfirmationTarget::HighPriority,
+                    FeeRate::from_sat_per_vb(25.0),
+                )]),
+                ..WalletSettings::default()
+            },
+        );
+
+        assert_eq!(
+            wallet.get_fee_rate(ConfirmationTarget::HighPriority),
+            FeeRate::from_sat_per_vb(25.0)
+        );
+        assert_eq!(
+            wallet.get_fee_rate(ConfirmationTarget::Normal),
+            FeeRate::from_sat_per_vb(1.0)
+        );
+    }
+
+    fn dummy_frozen_utxos() -> FrozenUtxos {
+        let db = bdk::sled::Config::new().temporary(true).open().unwrap();
+        FrozenUtxos::new(db.open_tree("frozen_utxos").unwrap())
+    }
+
     fn new_test_wallet(
         rng: &mut (impl RngCore + CryptoRng),
         utxo_amount: Amount,
@@ -617,6 +1708,7 @@ mod tests {
             _preimage: Option<lightning::ln::PaymentPreimage>,
             _secret: Option<lightning::ln::PaymentSecret>,
             _funding_txid: Option<Txid>,
+            _failure_reason: Option<crate::PaymentFailureReason>,
         ) -> Result<()> {
             unimplemented!();
         }
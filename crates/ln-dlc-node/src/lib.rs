@@ -2,6 +2,7 @@ use crate::ln::TracingLogger;
 use crate::node::SubChannelManager;
 use crate::node::TenTenOneOnionMessageHandler;
 use bitcoin::hashes::hex::ToHex;
+use bitcoin::secp256k1::PublicKey;
 use bitcoin::Txid;
 use dlc_custom_signer::CustomKeysManager;
 use dlc_custom_signer::CustomSigner;
@@ -24,11 +25,16 @@ use ln_dlc_wallet::LnDlcWallet;
 use std::fmt;
 use std::sync::Arc;
 use time::OffsetDateTime;
+use watchtower::WatchtowerPersister;
 
+mod bitcoind;
+mod chain_source;
 mod dlc_custom_signer;
+mod esplora;
 mod fee_rate_estimator;
 mod ldk_node_wallet;
 mod ln_dlc_wallet;
+mod mempool_space;
 mod on_chain_wallet;
 mod shadow;
 
@@ -42,9 +48,16 @@ pub mod seed;
 pub mod storage;
 pub mod transaction;
 pub mod util;
+pub mod watchtower;
 
+pub use chain_source::ChainSource;
+pub use chain_source::ChainSourceConfig;
 pub use config::CONFIRMATION_TARGET;
+pub use ldk_node_wallet::ReserveStatus;
+pub use ldk_node_wallet::SendPreview;
+pub use ldk_node_wallet::WalletAddress;
 pub use ldk_node_wallet::WalletSettings;
+pub use ldk_node_wallet::WalletUtxo;
 pub use lightning;
 pub use lightning_invoice;
 pub use ln::AppEventHandler;
@@ -55,6 +68,8 @@ pub use ln::DlcChannelDetails;
 pub use ln::EventHandlerTrait;
 pub use ln::EventSender;
 pub use node::invoice::HTLCStatus;
+pub use node::invoice::PaymentConfig;
+pub use node::invoice::PaymentFailureReason;
 
 #[cfg(test)]
 mod tests;
@@ -65,7 +80,7 @@ type ChainMonitor<S, N> = chainmonitor::ChainMonitor<
     Arc<LnDlcWallet<S, N>>,
     Arc<FeeRateEstimator>,
     Arc<TracingLogger>,
-    Arc<S>,
+    Arc<WatchtowerPersister<S, N>>,
 >;
 
 pub type PeerManager<S, N> = lightning::ln::peer_handler::PeerManager<
@@ -120,6 +135,12 @@ pub struct PaymentInfo {
     /// If the payment was used to open an inbound channel, this tx id refers the funding
     /// transaction for opening the channel.
     pub funding_txid: Option<Txid>,
+    /// When the invoice of the payment expires.
+    pub expiry: Option<OffsetDateTime>,
+    /// The node receiving the payment.
+    pub payee: Option<PublicKey>,
+    /// Why the payment failed, if it did.
+    pub failure_reason: Option<PaymentFailureReason>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -166,6 +187,17 @@ impl From<Bolt11Invoice> for PaymentInfo {
             },
             invoice: Some(value.to_string()),
             funding_txid: None,
+            expiry: value
+                .timestamp()
+                .checked_add(value.expiry_time())
+                .map(OffsetDateTime::from),
+            payee: Some(
+                value
+                    .payee_pub_key()
+                    .copied()
+                    .unwrap_or_else(|| value.recover_payee_pub_key()),
+            ),
+            failure_reason: None,
         }
     }
 }
@@ -136,13 +136,22 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> EventHan
                     None => UserChannelId::new(),
                 };
 
-                handle_open_channel_request_0_conf(
+                let zero_conf = self
+                    .node
+                    .settings
+                    .read()
+                    .await
+                    .zero_conf_trust_policy
+                    .is_trusted(&counterparty_node_id);
+
+                handle_open_channel_request(
                     &self.node.channel_manager,
                     counterparty_node_id,
                     funding_satoshis,
                     push_msat,
                     temporary_channel_id,
                     user_channel_id,
+                    zero_conf,
                 )?;
             }
             Event::PaymentPathSuccessful {
@@ -157,8 +166,12 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> EventHan
                     payment_hash = %payment_hash.0.to_hex(),
                 "Payment path failed");
             }
-            Event::PaymentFailed { payment_hash, .. } => {
-                common_handlers::handle_payment_failed(&self.node, payment_hash);
+            Event::PaymentFailed {
+                payment_hash,
+                reason,
+                ..
+            } => {
+                common_handlers::handle_payment_failed(&self.node, payment_hash, reason);
             }
             Event::PaymentForwarded {
                 prev_channel_id,
@@ -384,13 +397,14 @@ pub(crate) fn handle_fees_on_claimable_payment<S: TenTenOneStorage, N: Storage +
     Ok(())
 }
 
-pub(crate) fn handle_open_channel_request_0_conf<S: TenTenOneStorage, N: Storage + Sync + Send>(
+pub(crate) fn handle_open_channel_request<S: TenTenOneStorage, N: Storage + Sync + Send>(
     channel_manager: &Arc<ChannelManager<S, N>>,
     counterparty_node_id: PublicKey,
     funding_satoshis: u64,
     push_msat: u64,
     temporary_channel_id: ChannelId,
     user_channel_id: UserChannelId,
+    zero_conf: bool,
 ) -> Result<()> {
     let counterparty = counterparty_node_id.to_string();
     tracing::info!(
@@ -398,16 +412,29 @@ pub(crate) fn handle_open_channel_request_0_conf<S: TenTenOneStorage, N: Storage
         counterparty,
         funding_satoshis,
         push_msat,
+        zero_conf,
         "Accepting open channel request"
     );
 
-    channel_manager
-        .accept_inbound_channel_from_trusted_peer_0conf(
-            &temporary_channel_id,
-            &counterparty_node_id,
-            user_channel_id.to_u128(),
-        )
-        .map_err(|e| anyhow!("{e:?}"))
-        .context("To be able to accept a 0-conf channel")?;
+    if zero_conf {
+        channel_manager
+            .accept_inbound_channel_from_trusted_peer_0conf(
+                &temporary_channel_id,
+                &counterparty_node_id,
+                user_channel_id.to_u128(),
+            )
+            .map_err(|e| anyhow!("{e:?}"))
+            .context("To be able to accept a 0-conf channel")?;
+    } else {
+        channel_manager
+            .accept_inbound_channel(
+                &temporary_channel_id,
+                &counterparty_node_id,
+                user_channel_id.to_u128(),
+            )
+            .map_err(|e| anyhow!("{e:?}"))
+            .context("To be able to accept a channel")?;
+    }
+
     Ok(())
 }
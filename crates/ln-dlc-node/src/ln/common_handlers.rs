@@ -9,6 +9,7 @@ use crate::node::Storage;
 use crate::storage::TenTenOneStorage;
 use crate::util;
 use crate::MillisatAmount;
+use crate::PaymentFailureReason;
 use crate::PaymentFlow;
 use crate::PaymentInfo;
 use anyhow::anyhow;
@@ -168,6 +169,7 @@ pub fn handle_payment_sent<S: TenTenOneStorage, N: Storage>(
                 Some(payment_preimage),
                 None,
                 None,
+                None,
             ) {
                 bail!(
                     "Failed to update sent payment: {e:#}, hash: {payment_hash}",
@@ -193,6 +195,9 @@ pub fn handle_payment_sent<S: TenTenOneStorage, N: Storage>(
                     description: "".to_string(),
                     invoice: None,
                     funding_txid: None,
+                    expiry: None,
+                    payee: None,
+                    failure_reason: None,
                 },
             ) {
                 tracing::error!(
@@ -335,6 +340,7 @@ pub fn handle_payment_claimed<S: TenTenOneStorage, N: Storage>(
         payment_preimage,
         payment_secret,
         funding_txid,
+        None,
     ) {
         tracing::error!(
             payment_hash = %payment_hash.0.to_hex(),
@@ -346,12 +352,18 @@ pub fn handle_payment_claimed<S: TenTenOneStorage, N: Storage>(
 pub fn handle_payment_failed<S: TenTenOneStorage, N: Storage>(
     node: &Arc<Node<S, N>>,
     payment_hash: PaymentHash,
+    reason: Option<lightning::events::PaymentFailureReason>,
 ) {
     tracing::warn!(
         payment_hash = %payment_hash.0.to_hex(),
+        ?reason,
         "Failed to send payment to payment hash: exhausted payment retry attempts",
     );
 
+    let failure_reason = reason
+        .map(PaymentFailureReason::from)
+        .unwrap_or(PaymentFailureReason::UnexpectedError);
+
     let amount_msat = MillisatAmount(None);
     if let Err(e) = node.node_storage.merge_payment(
         &payment_hash,
@@ -362,6 +374,7 @@ pub fn handle_payment_failed<S: TenTenOneStorage, N: Storage>(
         None,
         None,
         None,
+        Some(failure_reason),
     ) {
         tracing::error!(
             payment_hash = %payment_hash.0.to_hex(),
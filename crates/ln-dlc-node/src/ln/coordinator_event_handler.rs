@@ -118,12 +118,21 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Send + Sync + 'static> EventHan
                 push_msat,
                 ..
             } => {
+                let zero_conf = self
+                    .node
+                    .settings
+                    .read()
+                    .await
+                    .zero_conf_trust_policy
+                    .is_trusted(&counterparty_node_id);
+
                 handle_open_channel_request(
                     &self.node.channel_manager,
                     counterparty_node_id,
                     funding_satoshis,
                     push_msat,
                     temporary_channel_id,
+                    zero_conf,
                 )?;
             }
             Event::PaymentPathSuccessful {
@@ -138,8 +147,12 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Send + Sync + 'static> EventHan
                     payment_hash = %payment_hash.0.to_hex(),
                 "Payment path failed");
             }
-            Event::PaymentFailed { payment_hash, .. } => {
-                common_handlers::handle_payment_failed(&self.node, payment_hash);
+            Event::PaymentFailed {
+                payment_hash,
+                reason,
+                ..
+            } => {
+                common_handlers::handle_payment_failed(&self.node, payment_hash, reason);
             }
             Event::PaymentForwarded {
                 prev_channel_id,
@@ -361,27 +374,86 @@ fn handle_open_channel_request<S: TenTenOneStorage, N: Storage>(
     funding_satoshis: u64,
     push_msat: u64,
     temporary_channel_id: ChannelId,
+    zero_conf: bool,
 ) -> Result<()> {
     let counterparty = counterparty_node_id.to_string();
     tracing::info!(
         counterparty,
         funding_satoshis,
         push_msat,
+        zero_conf,
         "Accepting open channel request"
     );
     let user_channel_id = 0;
-    channel_manager
-        .accept_inbound_channel(
-            &temporary_channel_id,
-            &counterparty_node_id,
-            user_channel_id,
-        )
-        .map_err(|e| anyhow!("{e:?}"))
-        .context("To be able to accept a 0-conf channel")?;
+
+    if zero_conf {
+        channel_manager
+            .accept_inbound_channel_from_trusted_peer_0conf(
+                &temporary_channel_id,
+                &counterparty_node_id,
+                user_channel_id,
+            )
+            .map_err(|e| anyhow!("{e:?}"))
+            .context("To be able to accept a 0-conf channel")?;
+    } else {
+        channel_manager
+            .accept_inbound_channel(
+                &temporary_channel_id,
+                &counterparty_node_id,
+                user_channel_id,
+            )
+            .map_err(|e| anyhow!("{e:?}"))
+            .context("To be able to accept a channel")?;
+    }
+
     Ok(())
 }
 
 #[allow(clippy::too_many_arguments)]
+/// Forward an HTLC held for a peer as soon as we have a usable channel with them, i.e. they came
+/// online within the configured timeout.
+async fn forward_async_payment<S: TenTenOneStorage, N: Storage>(
+    node: &Arc<Node<S, N>>,
+    intercept_id: InterceptId,
+    payment_hash: &str,
+    trader_id: PublicKey,
+    expected_outbound_amount_msat: u64,
+) -> Result<()> {
+    let timeout = node.settings.read().await.async_payment_hold_timeout;
+
+    tracing::info!(%trader_id, payment_hash, ?timeout, "Holding HTLC until peer is online");
+
+    let channel = tokio::time::timeout(timeout, async {
+        loop {
+            if let Some(channel) = node
+                .channel_manager
+                .list_usable_channels()
+                .into_iter()
+                .find(|channel| channel.counterparty.node_id == trader_id)
+            {
+                return channel;
+            }
+
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    })
+    .await
+    .context("Timed out waiting for peer to come online")?;
+
+    node.channel_manager
+        .forward_intercepted_htlc(
+            intercept_id,
+            &channel.channel_id,
+            trader_id,
+            expected_outbound_amount_msat,
+        )
+        .map_err(|e| anyhow!("Failed to forward intercepted HTLC: {e:?}"))?;
+
+    tracing::info!(%trader_id, payment_hash, "Forwarded held HTLC");
+
+    Ok(())
+}
+
 /// Handle an [`Event::HTLCIntercepted`].
 pub(crate) async fn handle_intercepted_htlc<S: TenTenOneStorage, N: Storage>(
     node: &Arc<Node<S, N>>,
@@ -424,6 +496,34 @@ pub(crate) async fn handle_intercepted_htlc_internal<S: TenTenOneStorage, N: Sto
     let intercept_id_str = intercept_id.0.to_hex();
     let payment_hash = payment_hash.0.to_hex();
 
+    let async_payment_target = node
+        .async_payments
+        .lock()
+        .get(&requested_next_hop_scid)
+        .copied();
+    if let Some(trader_id) = async_payment_target {
+        // Holding the HTLC must not block the processing of other events.
+        tokio::spawn({
+            let node = node.clone();
+            async move {
+                if let Err(e) = forward_async_payment(
+                    &node,
+                    intercept_id,
+                    &payment_hash,
+                    trader_id,
+                    expected_outbound_amount_msat,
+                )
+                .await
+                {
+                    tracing::error!(%trader_id, payment_hash, "Failed to forward async payment: {e:#}");
+                    fail_intercepted_htlc(&node.channel_manager, &intercept_id);
+                }
+            }
+        });
+
+        return Ok(());
+    }
+
     let liquidity_request = {
         node.fake_channel_payments
             .lock()
@@ -508,23 +608,12 @@ pub(crate) async fn handle_intercepted_htlc_internal<S: TenTenOneStorage, N: Sto
          max_counterparty_fund_amount_msat: {max_counterparty_fund_amount_msat}"
     );
 
-    if !node
-        .wallet
-        .ldk_wallet()
-        .settings()
-        .await
-        .jit_channels_enabled
-    {
+    let wallet_settings = node.wallet.ldk_wallet().settings().await;
+    if !wallet_settings.jit_channels_enabled {
         bail!("Opening jit channels is disabled. Rejecting attempt to open a JIT channel.");
     }
 
-    let opt_max_allowed_fee = node
-        .wallet
-        .ldk_wallet()
-        .settings()
-        .await
-        .max_allowed_tx_fee_rate_when_opening_channel;
-    if let Some(max_allowed_tx_fee) = opt_max_allowed_fee {
+    if let Some(max_allowed_tx_fee) = wallet_settings.max_allowed_tx_fee_rate_when_opening_channel {
         let current_fee = node
             .fee_rate_estimator
             .get_est_sat_per_1000_weight(CONFIRMATION_TARGET);
@@ -538,6 +627,20 @@ pub(crate) async fn handle_intercepted_htlc_internal<S: TenTenOneStorage, N: Sto
     let channel_value_sats =
         calculate_channel_value(expected_outbound_amount_msat, &liquidity_request);
 
+    ensure!(
+        channel_value_sats >= wallet_settings.jit_channel_min_size_sats,
+        "Not opening JIT channel because it is too small, channel_value_sats: \
+         {channel_value_sats} < {}",
+        wallet_settings.jit_channel_min_size_sats
+    );
+    if let Some(max_size_sats) = wallet_settings.jit_channel_max_size_sats {
+        ensure!(
+            channel_value_sats <= max_size_sats,
+            "Not opening JIT channel because it is too large, channel_value_sats: \
+             {channel_value_sats} > {max_size_sats}"
+        );
+    }
+
     let user_channel_id = liquidity_request.user_channel_id;
     let mut shadow_channel = node
         .node_storage
@@ -629,6 +732,7 @@ mod tests {
                 max_deposit_sats: capacity * i,
                 coordinator_leverage: i as f32,
                 fee_sats: 5_000,
+                liquidity_fee_ppm: 50,
             };
 
             let channel_value_sat = calculate_channel_value(10_000_000, &request);
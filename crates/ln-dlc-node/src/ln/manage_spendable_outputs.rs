@@ -1,13 +1,12 @@
+use crate::chain_source::ChainSource;
+use crate::chain_source::OutputStatus;
 use crate::dlc_custom_signer::CustomKeysManager;
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ln_dlc_wallet::LnDlcWallet;
 use crate::node::Storage;
 use crate::storage::TenTenOneStorage;
-use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
-use esplora_client::OutputStatus;
-use esplora_client::TxStatus;
 use lightning::chain::chaininterface::BroadcasterInterface;
 use lightning::chain::chaininterface::ConfirmationTarget;
 use lightning::chain::chaininterface::FeeEstimator;
@@ -27,7 +26,7 @@ const REQUIRED_CONFIRMATIONS: u32 = 6;
 /// Determine what to do with a [`SpendableOutputDescriptor`] and do it.
 pub fn manage_spendable_outputs<S: TenTenOneStorage, N: Storage>(
     node_storage: Arc<N>,
-    esplora_client: impl Borrow<esplora_client::BlockingClient>,
+    chain_source: impl Borrow<ChainSource>,
     wallet: impl Borrow<LnDlcWallet<S, N>>,
     fee_rate_estimator: impl Borrow<FeeRateEstimator>,
     keys_manager: impl Borrow<CustomKeysManager<S, N>>,
@@ -36,7 +35,7 @@ pub fn manage_spendable_outputs<S: TenTenOneStorage, N: Storage>(
 
     let spendable_outputs = &node_storage.all_spendable_outputs()?;
     for output in spendable_outputs.iter() {
-        let action = match choose_spendable_output_action(esplora_client.borrow(), output) {
+        let action = match choose_spendable_output_action(chain_source.borrow(), output) {
             Ok(action) => action,
             Err(e) => {
                 tracing::error!(
@@ -89,7 +88,7 @@ enum Action {
 /// Decide on which [`Action`] should be performed based on the characteristics and status of a
 /// [`SpendableOutputDescriptor`].
 fn choose_spendable_output_action(
-    esplora_client: &esplora_client::BlockingClient,
+    chain_source: &ChainSource,
     output: &SpendableOutputDescriptor,
 ) -> Result<Action> {
     use SpendableOutputDescriptor::*;
@@ -100,25 +99,19 @@ fn choose_spendable_output_action(
         StaticOutput { outpoint, .. } => return Ok(Action::Forget(*outpoint)),
     };
 
-    let output_status = esplora_client
-        .get_output_status(&outpoint.txid, outpoint.index.into())
+    let output_status = chain_source
+        .get_output_status(&outpoint.into_bitcoin_outpoint())
         .context("Could not get spendable output status")?;
 
     match output_status {
-        Some(OutputStatus { spent: false, .. }) | None => {
+        OutputStatus::Unspent => {
             tracing::debug!(?output, "Spendable output not yet spent");
             Ok(Action::Spend)
         }
-        Some(OutputStatus {
-            status:
-                Some(TxStatus {
-                    confirmed: true,
-                    block_height: Some(confirmation_height),
-                    ..
-                }),
-            ..
-        }) => {
-            let current_height = esplora_client.get_height()?;
+        OutputStatus::Spent {
+            confirmation_height: Some(confirmation_height),
+        } => {
+            let current_height = chain_source.get_height()?;
 
             let confirmations = current_height
                 .checked_sub(confirmation_height)
@@ -150,8 +143,11 @@ fn choose_spendable_output_action(
                 Ok(Action::Monitor)
             }
         }
-        Some(_) => {
-            bail!("Spendable output in unexpected state: {output:?}");
+        OutputStatus::Spent {
+            confirmation_height: None,
+        } => {
+            tracing::debug!(?output, "Spendable output spent, but not yet confirmed");
+            Ok(Action::Monitor)
         }
     }
 }
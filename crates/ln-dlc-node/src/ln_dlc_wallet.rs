@@ -1,13 +1,15 @@
+use crate::chain_source::WalletBlockchain;
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ldk_node_wallet;
 use crate::node::Storage;
+use crate::on_chain_wallet::OnChainWallet;
 use crate::storage::TenTenOneStorage;
-use crate::TracingLogger;
 use crate::WalletSettings;
 use anyhow::anyhow;
 use anyhow::Result;
-use bdk::blockchain::EsploraBlockchain;
-use bdk::esplora_client::TxStatus;
+use bdk::blockchain::GetBlockHash;
+use bdk::blockchain::GetHeight;
+use bdk::blockchain::GetTx;
 use bdk::sled;
 use bdk::SignOptions;
 use bdk::TransactionDetails;
@@ -29,16 +31,16 @@ use dlc_manager::Blockchain;
 use dlc_manager::Signer;
 use dlc_manager::Utxo;
 use lightning::chain::chaininterface::BroadcasterInterface;
-use lightning_transaction_sync::EsploraSyncClient;
 use ln_dlc_storage::DlcStorageProvider;
 use ln_dlc_storage::WalletStorage;
 use parking_lot::RwLock;
 use std::sync::Arc;
+use tracing::instrument;
 
 /// This is a wrapper type introduced to be able to implement traits from `rust-dlc` on the
 /// `ldk_node::LightningWallet`.
 pub struct LnDlcWallet<S, N> {
-    ln_wallet: Arc<ldk_node_wallet::Wallet<sled::Tree, EsploraBlockchain, FeeRateEstimator, N>>,
+    ln_wallet: Arc<ldk_node_wallet::Wallet<sled::Tree, WalletBlockchain, FeeRateEstimator, N>>,
     dlc_storage: Arc<DlcStorageProvider<S>>,
     secp: Secp256k1<All>,
     network: Network,
@@ -51,27 +53,21 @@ pub struct LnDlcWallet<S, N> {
 }
 
 impl<S: TenTenOneStorage, N: Storage> LnDlcWallet<S, N> {
-    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        esplora_client: Arc<EsploraSyncClient<Arc<TracingLogger>>>,
-        on_chain_wallet: bdk::Wallet<bdk::sled::Tree>,
+        blockchain: WalletBlockchain,
+        on_chain_wallet: OnChainWallet,
         fee_rate_estimator: Arc<FeeRateEstimator>,
         dlc_storage: Arc<DlcStorageProvider<S>>,
         node_storage: Arc<N>,
-        bdk_client_stop_gap: usize,
-        bdk_client_concurrency: u8,
         settings: WalletSettings,
     ) -> Self {
-        let blockchain =
-            EsploraBlockchain::from_client(esplora_client.client().clone(), bdk_client_stop_gap)
-                .with_concurrency(bdk_client_concurrency);
-
-        let network = on_chain_wallet.network();
+        let network = on_chain_wallet.inner.network();
 
         let wallet = Arc::new(ldk_node_wallet::Wallet::new(
             blockchain,
-            on_chain_wallet,
+            on_chain_wallet.inner,
             fee_rate_estimator,
+            on_chain_wallet.frozen_utxos,
             node_storage,
             settings,
         ));
@@ -91,7 +87,7 @@ impl<S: TenTenOneStorage, N: Storage> LnDlcWallet<S, N> {
 
     pub fn ldk_wallet(
         &self,
-    ) -> Arc<ldk_node_wallet::Wallet<sled::Tree, EsploraBlockchain, FeeRateEstimator, N>> {
+    ) -> Arc<ldk_node_wallet::Wallet<sled::Tree, WalletBlockchain, FeeRateEstimator, N>> {
         self.ln_wallet.clone()
     }
 
@@ -126,6 +122,7 @@ impl<S: TenTenOneStorage, N: Storage> LnDlcWallet<S, N> {
         self.ldk_wallet().is_mine(script)
     }
 
+    #[instrument(skip_all)]
     pub fn sync_and_update_address_cache(&self) -> Result<()> {
         self.ldk_wallet().sync()?;
 
@@ -142,7 +139,7 @@ impl<S: TenTenOneStorage, N: Storage> LnDlcWallet<S, N> {
             .ok_or_else(|| anyhow!("Transaction {txid} not found on-chain"))
     }
 
-    fn update_address_cache(&self) -> Result<()> {
+    pub(crate) fn update_address_cache(&self) -> Result<()> {
         let address = self.ldk_wallet().get_last_unused_address()?;
         *self.address_cache.write() = address;
 
@@ -176,7 +173,7 @@ impl<S: TenTenOneStorage, N: Storage> Blockchain for LnDlcWallet<S, N> {
         let block_hash = self
             .ln_wallet
             .blockchain
-            .get_block_hash(height as u32)
+            .get_block_hash(height)
             .map_err(|e| {
                 Error::BlockchainError(format!("Could not find block at height {height}: {e:#}"))
             })?;
@@ -204,14 +201,11 @@ impl<S: TenTenOneStorage, N: Storage> Blockchain for LnDlcWallet<S, N> {
         let confirmation_height = match self
             .ln_wallet
             .blockchain
-            .get_tx_status(txid)
-            .map_err(|e| Error::BlockchainError(e.to_string()))?
+            .get_tx_confirmation_height(txid)
+            .map_err(|e| Error::BlockchainError(format!("{e:#}")))?
         {
-            Some(TxStatus {
-                block_height: Some(height),
-                ..
-            }) => height,
-            _ => return Ok(0),
+            Some(height) => height,
+            None => return Ok(0),
         };
 
         let tip = self
@@ -0,0 +1,155 @@
+use anyhow::Context;
+use anyhow::Result;
+use bdk::FeeRate;
+use lightning::chain::chaininterface::ConfirmationTarget;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How long we wait for mempool.space to answer a request.
+const MEMPOOL_SPACE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum virtual size of a block.
+const BLOCK_VSIZE: u64 = 1_000_000;
+
+/// A client for the fee estimation endpoints of the mempool.space REST API.
+pub struct MempoolSpace {
+    agent: ureq::Agent,
+    url: String,
+}
+
+/// The response of `/api/v1/fees/recommended`, in sats/vbyte.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecommendedFees {
+    half_hour_fee: f32,
+    hour_fee: f32,
+    economy_fee: f32,
+    minimum_fee: f32,
+}
+
+/// The part of the `/api/mempool` response we are interested in.
+#[derive(Debug, Deserialize)]
+struct Mempool {
+    /// Pairs of fee rate in sats/vbyte and the virtual size of the transactions paying it.
+    fee_histogram: Vec<(f32, u64)>,
+}
+
+impl MempoolSpace {
+    pub fn new(url: String) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(MEMPOOL_SPACE_TIMEOUT)
+            .build();
+
+        Self {
+            agent,
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the fee rates for the given confirmation targets, where each target is paired with the
+    /// number of blocks we want the transaction to confirm within.
+    ///
+    /// The recommended fees of mempool.space are raised to the fee rate needed to get into the
+    /// next `n_blocks` according to the fee histogram of the mempool, as the recommendations can
+    /// lag behind a quickly filling mempool.
+    pub fn get_fee_rates(
+        &self,
+        targets: &[(ConfirmationTarget, usize)],
+    ) -> Result<HashMap<ConfirmationTarget, FeeRate>> {
+        let recommended = self.get_recommended_fees()?;
+        let histogram = self.get_fee_histogram()?;
+
+        let fee_rates = targets
+            .iter()
+            .map(|(target, n_blocks)| {
+                let recommended = recommended_fee_rate(&recommended, *target);
+                let sats_per_vbyte = match fee_rate_for_blocks(&histogram, *n_blocks) {
+                    Some(needed) => recommended.max(needed),
+                    None => recommended,
+                };
+
+                (*target, FeeRate::from_sat_per_vb(sats_per_vbyte))
+            })
+            .collect();
+
+        Ok(fee_rates)
+    }
+
+    fn get_recommended_fees(&self) -> Result<RecommendedFees> {
+        let fees = self
+            .agent
+            .get(&format!("{}/api/v1/fees/recommended", self.url))
+            .call()
+            .context("could not fetch recommended fees")?
+            .into_json()
+            .context("could not parse recommended fees")?;
+
+        Ok(fees)
+    }
+
+    fn get_fee_histogram(&self) -> Result<Vec<(f32, u64)>> {
+        let mempool: Mempool = self
+            .agent
+            .get(&format!("{}/api/mempool", self.url))
+            .call()
+            .context("could not fetch mempool")?
+            .into_json()
+            .context("could not parse mempool")?;
+
+        Ok(mempool.fee_histogram)
+    }
+}
+
+fn recommended_fee_rate(fees: &RecommendedFees, target: ConfirmationTarget) -> f32 {
+    match target {
+        ConfirmationTarget::HighPriority => fees.half_hour_fee,
+        ConfirmationTarget::Normal => fees.hour_fee,
+        ConfirmationTarget::Background => fees.economy_fee,
+        ConfirmationTarget::MempoolMinimum => fees.minimum_fee,
+    }
+}
+
+/// The fee rate a transaction has to pay to be included in the next `n_blocks` blocks, assuming
+/// no new transactions enter the mempool.
+///
+/// Returns `None` if all the transactions in the mempool fit into `n_blocks` blocks.
+fn fee_rate_for_blocks(histogram: &[(f32, u64)], n_blocks: usize) -> Option<f32> {
+    let mut histogram = histogram.to_vec();
+    histogram.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let capacity = n_blocks as u64 * BLOCK_VSIZE;
+
+    let mut vsize_ahead = 0;
+    for (sats_per_vbyte, vsize) in histogram {
+        vsize_ahead += vsize;
+        if vsize_ahead >= capacity {
+            return Some(sats_per_vbyte);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_rate_for_blocks_is_the_rate_filling_the_blocks() {
+        let histogram = vec![(5.0, 1_500_000), (30.0, 400_000), (15.0, 800_000)];
+
+        assert_eq!(fee_rate_for_blocks(&histogram, 1), Some(15.0));
+        assert_eq!(fee_rate_for_blocks(&histogram, 2), Some(5.0));
+        assert_eq!(fee_rate_for_blocks(&histogram, 3), None);
+    }
+
+    #[test]
+    fn fee_rate_for_blocks_of_empty_mempool_is_none() {
+        assert_eq!(fee_rate_for_blocks(&[], 1), None);
+    }
+}
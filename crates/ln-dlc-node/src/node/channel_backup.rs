@@ -0,0 +1,123 @@
+use crate::node::Node;
+use crate::node::Storage as LnDlcStorage;
+use crate::storage::TenTenOneStorage;
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use lightning::util::persist::KVStore;
+use lightning::util::persist::CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE;
+use lightning::util::persist::CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE;
+use lightning::util::ser::Writeable;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A backup of all our channels, which lets a user who only has their seed ask their
+/// counterparties to force-close the channels.
+///
+/// Unlike the channel state itself, the backup does not need to be updated with every channel
+/// update. The channel monitors in the backup are stale after the next update, but we never
+/// broadcast from them: we only use them to sweep the outputs paying to us once the counterparty
+/// has force-closed the channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticChannelBackup {
+    pub channels: Vec<ChannelBackup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelBackup {
+    /// The hex encoded ID of the channel.
+    pub channel_id: String,
+    pub counterparty: PublicKey,
+    pub kind: ChannelBackupKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChannelBackupKind {
+    Lightning {
+        /// The key under which the channel monitor is stored, i.e. `<txid>_<vout>` of the
+        /// funding output.
+        monitor_key: String,
+        /// The serialized channel monitor.
+        monitor: Vec<u8>,
+    },
+    /// The payout of a DLC channel goes to our on-chain wallet, so we only need to know the
+    /// channel to ask for it to be force-closed.
+    Dlc,
+}
+
+impl<S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send + 'static> Node<S, N> {
+    pub fn static_channel_backup(&self) -> Result<StaticChannelBackup> {
+        let mut channels = vec![];
+
+        for funding_txo in self.chain_monitor.list_monitors() {
+            let monitor = self
+                .chain_monitor
+                .get_monitor(funding_txo)
+                .map_err(|_| anyhow!("Missing channel monitor for {funding_txo:?}"))?;
+            let counterparty = monitor
+                .get_counterparty_node_id()
+                .with_context(|| format!("Unknown counterparty of channel {funding_txo:?}"))?;
+
+            channels.push(ChannelBackup {
+                channel_id: hex::encode(funding_txo.to_channel_id().0),
+                counterparty,
+                kind: ChannelBackupKind::Lightning {
+                    monitor_key: format!("{}_{}", funding_txo.txid, funding_txo.index),
+                    monitor: monitor.encode(),
+                },
+            });
+        }
+
+        for channel in self.list_signed_dlc_channels()? {
+            channels.push(ChannelBackup {
+                channel_id: hex::encode(channel.channel_id),
+                counterparty: channel.counter_party,
+                kind: ChannelBackupKind::Dlc,
+            });
+        }
+
+        Ok(StaticChannelBackup { channels })
+    }
+}
+
+/// Writes the channel monitors of the backup to `storage`, so that a node started on top of it
+/// sweeps the outputs paying to us once our counterparties force-close the channels.
+///
+/// Channel monitors which are already in `storage` are never overwritten, as they are at least as
+/// recent as the ones in the backup.
+pub fn restore_channel_monitors<K: KVStore>(
+    storage: &K,
+    backup: &StaticChannelBackup,
+) -> Result<()> {
+    let existing_monitors = storage.list(
+        CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+        CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+    )?;
+
+    for channel in backup.channels.iter() {
+        let (monitor_key, monitor) = match &channel.kind {
+            ChannelBackupKind::Lightning {
+                monitor_key,
+                monitor,
+            } => (monitor_key, monitor),
+            ChannelBackupKind::Dlc => continue,
+        };
+
+        if existing_monitors.contains(monitor_key) {
+            tracing::debug!(channel_id = %channel.channel_id, "Keeping existing channel monitor");
+            continue;
+        }
+
+        tracing::info!(channel_id = %channel.channel_id, "Restoring channel monitor from backup");
+
+        storage.write(
+            CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+            CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+            monitor_key,
+            monitor,
+        )?;
+    }
+
+    Ok(())
+}
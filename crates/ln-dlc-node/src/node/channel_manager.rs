@@ -1,4 +1,6 @@
+use crate::chain_source::LightningSync;
 use crate::dlc_custom_signer::CustomKeysManager;
+use crate::dlc_custom_signer::CustomSigner;
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ln::TracingLogger;
 use crate::ln_dlc_wallet::LnDlcWallet;
@@ -9,6 +11,7 @@ use crate::Router;
 use anyhow::anyhow;
 use anyhow::Result;
 use bitcoin::BlockHash;
+use lightning::chain::channelmonitor::ChannelMonitor;
 use lightning::chain::BestBlock;
 use lightning::chain::ChannelMonitorUpdateStatus;
 use lightning::chain::Watch;
@@ -21,7 +24,6 @@ use lightning::util::persist::CHANNEL_MANAGER_PERSISTENCE_KEY;
 use lightning::util::persist::CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE;
 use lightning::util::persist::CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE;
 use lightning::util::ser::ReadableArgs;
-use lightning_transaction_sync::EsploraSyncClient;
 use std::sync::Arc;
 
 pub type ChannelManager<S, N> = lightning::ln::channelmanager::ChannelManager<
@@ -40,7 +42,7 @@ pub(crate) fn build<S: TenTenOneStorage, N: Storage>(
     keys_manager: Arc<CustomKeysManager<S, N>>,
     ln_dlc_wallet: Arc<LnDlcWallet<S, N>>,
     fee_rate_estimator: Arc<FeeRateEstimator>,
-    explora_client: Arc<EsploraSyncClient<Arc<TracingLogger>>>,
+    lightning_sync: Arc<LightningSync>,
     logger: Arc<TracingLogger>,
     chain_monitor: Arc<ChainMonitor<S, N>>,
     ldk_config: UserConfig,
@@ -62,6 +64,19 @@ pub(crate) fn build<S: TenTenOneStorage, N: Storage>(
             tracing::info!("Did not find channel manager data. {e:#}");
             tracing::info!("Initializing new channel manager");
 
+            // Channel monitors without a channel manager have been restored from a static
+            // channel backup. We only watch them to sweep our outputs once the counterparty
+            // force-closes the channels.
+            let channelmonitors =
+                read_channel_monitors(persister, keys_manager.clone(), keys_manager.clone())?;
+            if !channelmonitors.is_empty() {
+                tracing::info!(
+                    channels = channelmonitors.len(),
+                    "Watching channel monitors restored from backup"
+                );
+            }
+            watch_channel_monitors(&chain_monitor, &lightning_sync, channelmonitors)?;
+
             let (height, block_hash) = ln_dlc_wallet.tip()?;
             return Ok(ChannelManager::new(
                 fee_rate_estimator,
@@ -109,10 +124,20 @@ pub(crate) fn build<S: TenTenOneStorage, N: Storage>(
             .map_err(|e| anyhow!(e))?
             .1;
 
+    watch_channel_monitors(&chain_monitor, &lightning_sync, channelmonitors)?;
+
+    Ok(channel_manager)
+}
+
+fn watch_channel_monitors<S: TenTenOneStorage, N: Storage>(
+    chain_monitor: &ChainMonitor<S, N>,
+    lightning_sync: &Arc<LightningSync>,
+    mut channelmonitors: Vec<(BlockHash, ChannelMonitor<CustomSigner>)>,
+) -> Result<()> {
     // Make sure our filter is initialized with all the txs and outputs
     // that we need to be watching based on our set of channel monitors
     for (_, monitor) in channelmonitors.iter() {
-        monitor.load_outputs_to_watch(&explora_client.clone());
+        monitor.load_outputs_to_watch(lightning_sync);
     }
 
     for (_, monitor) in channelmonitors.drain(..) {
@@ -132,5 +157,5 @@ pub(crate) fn build<S: TenTenOneStorage, N: Storage>(
         );
     }
 
-    Ok(channel_manager)
+    Ok(())
 }
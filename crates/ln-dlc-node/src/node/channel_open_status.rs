@@ -0,0 +1,118 @@
+use crate::node::event::NodeEvent;
+use crate::node::event::NodeEventHandler;
+use crate::node::ChannelManager;
+use crate::node::Storage;
+use crate::storage::TenTenOneStorage;
+use bitcoin::hashes::hex::ToHex;
+use lightning::ln::channelmanager::ChannelDetails;
+use lightning::ln::ChannelId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often we check the channels which are being opened for new confirmations.
+const CHANNEL_OPEN_STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How far a channel has progressed towards being usable, published as
+/// [`NodeEvent::ChannelOpenStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelOpenStatus {
+    /// The funding transaction has been broadcast, but is not confirmed yet.
+    Broadcast { required_confirmations: u32 },
+    /// The funding transaction has confirmed, but not as often as required for the channel to
+    /// become usable.
+    Confirming {
+        confirmations: u32,
+        required_confirmations: u32,
+    },
+    /// The channel can be used.
+    Ready,
+}
+
+impl ChannelOpenStatus {
+    /// The status of the given channel, or `None` if its funding transaction has not been
+    /// created yet.
+    pub fn from_channel_details(channel: &ChannelDetails) -> Option<Self> {
+        if channel.is_channel_ready {
+            return Some(Self::Ready);
+        }
+
+        channel.funding_txo?;
+
+        let confirmations = channel.confirmations.unwrap_or_default();
+        let required_confirmations = channel.confirmations_required.unwrap_or(confirmations);
+
+        let status = match confirmations {
+            0 => Self::Broadcast {
+                required_confirmations,
+            },
+            confirmations => Self::Confirming {
+                confirmations,
+                required_confirmations,
+            },
+        };
+
+        Some(status)
+    }
+}
+
+/// Publish a [`NodeEvent::ChannelOpenStatus`] whenever a channel progresses towards being usable.
+///
+/// The confirmations of the funding transactions are known to the channel manager once the
+/// Lightning wallet has been synced, so we only have to compare the channels against their last
+/// known status.
+pub(crate) async fn track_channel_open_status<S: TenTenOneStorage, N: Storage>(
+    channel_manager: Arc<ChannelManager<S, N>>,
+    event_handler: Arc<NodeEventHandler>,
+) {
+    // The channels which were already usable when we started are not of interest.
+    let mut known_statuses = channel_manager
+        .list_channels()
+        .iter()
+        .filter_map(|channel| {
+            ChannelOpenStatus::from_channel_details(channel)
+                .filter(|status| *status == ChannelOpenStatus::Ready)
+                .map(|status| (channel.channel_id, status))
+        })
+        .collect::<HashMap<ChannelId, ChannelOpenStatus>>();
+
+    loop {
+        let channels = channel_manager.list_channels();
+
+        known_statuses.retain(|channel_id, _| {
+            channels
+                .iter()
+                .any(|channel| channel.channel_id == *channel_id)
+        });
+
+        for channel in channels {
+            let status = match ChannelOpenStatus::from_channel_details(&channel) {
+                Some(status) => status,
+                None => continue,
+            };
+
+            if known_statuses.get(&channel.channel_id) == Some(&status) {
+                continue;
+            }
+
+            tracing::info!(
+                channel_id = %channel.channel_id.to_hex(),
+                counterparty = %channel.counterparty.node_id,
+                ?status,
+                "Channel open status update"
+            );
+
+            known_statuses.insert(channel.channel_id, status);
+
+            if let Err(e) = event_handler.publish(NodeEvent::ChannelOpenStatus {
+                channel_id: channel.channel_id,
+                counterparty: channel.counterparty.node_id,
+                status,
+            }) {
+                tracing::debug!("Nobody is listening to channel open status updates: {e:#}");
+            }
+        }
+
+        tokio::time::sleep(CHANNEL_OPEN_STATUS_INTERVAL).await;
+    }
+}
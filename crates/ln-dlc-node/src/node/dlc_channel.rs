@@ -2,6 +2,7 @@ use crate::node::event::NodeEvent;
 use crate::node::Node;
 use crate::node::Storage as LnDlcStorage;
 use crate::storage::TenTenOneStorage;
+use crate::util::spawn_blocking;
 use crate::DlcMessageHandler;
 use crate::PeerManager;
 use crate::ToHex;
@@ -18,6 +19,7 @@ use dlc_manager::channel::Channel;
 use dlc_manager::contract::contract_input::ContractInput;
 use dlc_manager::contract::Contract;
 use dlc_manager::contract::ContractDescriptor;
+use dlc_manager::ContractId;
 use dlc_manager::DlcChannelId;
 use dlc_manager::Oracle;
 use dlc_manager::Storage;
@@ -25,7 +27,6 @@ use dlc_messages::channel::Reject;
 use dlc_messages::ChannelMessage;
 use dlc_messages::Message;
 use time::OffsetDateTime;
-use tokio::task::spawn_blocking;
 
 impl<S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send + 'static> Node<S, N> {
     pub async fn propose_dlc_channel(
@@ -54,16 +55,16 @@ impl<S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send + 'static> Nod
         }
 
         spawn_blocking({
-            let p2pd_oracles = self.oracles.clone();
+            let oracle_clients = self.oracles.clone();
 
             let dlc_manager = self.dlc_manager.clone();
             let oracles = contract_input.contract_infos[0].oracles.clone();
             let event_id = oracles.event_id;
             let event_handler = self.event_handler.clone();
             move || {
-                let announcements: Vec<_> = p2pd_oracles
+                let announcements: Vec<_> = oracle_clients
                     .into_iter()
-                    .filter(|o| oracles.public_keys.contains(&o.public_key))
+                    .filter(|o| oracles.public_keys.contains(&o.get_public_key()))
                     .filter_map(|oracle| oracle.get_announcement(&event_id).ok())
                     .collect();
 
@@ -406,6 +407,21 @@ impl<S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send + 'static> Nod
             })
     }
 
+    /// Fetch the [`Contract`] with the given temporary [`ContractId`], regardless of its state.
+    pub fn get_contract_by_temporary_id(
+        &self,
+        temporary_contract_id: &ContractId,
+    ) -> Result<Option<Contract>> {
+        let contract = self
+            .dlc_manager
+            .get_store()
+            .get_contracts()?
+            .into_iter()
+            .find(|contract| contract.get_temporary_id() == *temporary_contract_id);
+
+        Ok(contract)
+    }
+
     pub fn get_established_dlc_channel(&self, pubkey: &PublicKey) -> Result<Option<SignedChannel>> {
         let matcher = |dlc_channel: &&SignedChannel| {
             dlc_channel.counter_party == *pubkey
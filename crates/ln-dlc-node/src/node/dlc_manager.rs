@@ -1,5 +1,6 @@
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ln_dlc_wallet::LnDlcWallet;
+use crate::node::oracle::OracleClient;
 use crate::node::Node;
 use crate::node::Storage;
 use crate::storage::TenTenOneStorage;
@@ -9,10 +10,10 @@ use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::PublicKey;
 use dlc_manager::channel::signed_channel::SignedChannel;
 use dlc_manager::channel::signed_channel::SignedChannelState;
+use dlc_manager::Oracle;
 use dlc_manager::Storage as DlcStorage;
 use dlc_manager::SystemTimeProvider;
 use ln_dlc_storage::DlcStorageProvider;
-use p2pd_oracle_client::P2PDOracleClient;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -22,7 +23,7 @@ pub type DlcManager<S, N> = dlc_manager::manager::Manager<
     Arc<LnDlcWallet<S, N>>,
     Arc<LnDlcWallet<S, N>>,
     Arc<DlcStorageProvider<S>>,
-    Arc<P2PDOracleClient>,
+    Arc<OracleClient>,
     Arc<SystemTimeProvider>,
     Arc<FeeRateEstimator>,
 >;
@@ -31,15 +32,15 @@ pub fn build<S: TenTenOneStorage, N: Storage>(
     data_dir: &Path,
     ln_dlc_wallet: Arc<LnDlcWallet<S, N>>,
     dlc_storage: Arc<DlcStorageProvider<S>>,
-    p2pdoracles: Vec<Arc<P2PDOracleClient>>,
+    oracle_clients: Vec<Arc<OracleClient>>,
     fee_rate_estimator: Arc<FeeRateEstimator>,
 ) -> Result<DlcManager<S, N>> {
     let offers_path = data_dir.join("offers");
     fs::create_dir_all(offers_path)?;
 
     let mut oracles = HashMap::new();
-    for oracle in p2pdoracles.into_iter() {
-        oracles.insert(oracle.public_key, oracle);
+    for oracle in oracle_clients.into_iter() {
+        oracles.insert(oracle.get_public_key(), oracle);
     }
 
     // FIXME: We need to do this to ensure that we can upgrade `Node`s from LDK 0.0.114 to 0.0.116.
@@ -1,14 +1,36 @@
+use crate::node::ChannelOpenStatus;
+use crate::node::SyncProgress;
 use anyhow::anyhow;
 use anyhow::Result;
 use bitcoin::secp256k1::PublicKey;
 use dlc_messages::Message;
+use lightning::ln::ChannelId;
+use std::cell::RefCell;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::Receiver;
 
+thread_local! {
+    /// Events published on the current thread while staging, together with the channel they are
+    /// meant for.
+    static STAGED_EVENTS: RefCell<Option<Vec<(broadcast::Sender<NodeEvent>, NodeEvent)>>> =
+        RefCell::new(None);
+}
+
 #[derive(Clone, Debug)]
 pub enum NodeEvent {
-    Connected { peer: PublicKey },
-    SendDlcMessage { peer: PublicKey, msg: Message },
+    Connected {
+        peer: PublicKey,
+    },
+    SendDlcMessage {
+        peer: PublicKey,
+        msg: Message,
+    },
+    SyncProgress(SyncProgress),
+    ChannelOpenStatus {
+        channel_id: ChannelId,
+        counterparty: PublicKey,
+        status: ChannelOpenStatus,
+    },
 }
 
 #[derive(Clone)]
@@ -33,9 +55,46 @@ impl NodeEventHandler {
         self.sender.subscribe()
     }
 
+    /// Publish `event` to all subscribers.
+    ///
+    /// If events are staged on the current thread, the event is held back until
+    /// [`publish_staged_events`] is called.
     pub fn publish(&self, event: NodeEvent) -> Result<()> {
-        self.sender.send(event).map_err(|e| anyhow!("{e:#}"))?;
+        let event = STAGED_EVENTS.with(|staged_events| match staged_events.borrow_mut().as_mut() {
+            Some(staged_events) => {
+                staged_events.push((self.sender.clone(), event));
+                None
+            }
+            None => Some(event),
+        });
+
+        if let Some(event) = event {
+            self.sender.send(event).map_err(|e| anyhow!("{e:#}"))?;
+        }
 
         Ok(())
     }
 }
+
+/// Hold back the events published on the current thread until [`publish_staged_events`] or
+/// [`discard_staged_events`] is called.
+pub fn stage_events() {
+    STAGED_EVENTS.with(|staged_events| *staged_events.borrow_mut() = Some(vec![]));
+}
+
+/// Publish the events held back on the current thread in the order in which they were published.
+pub fn publish_staged_events() {
+    let staged_events = STAGED_EVENTS
+        .with(|staged_events| staged_events.borrow_mut().take())
+        .unwrap_or_default();
+
+    for (sender, event) in staged_events {
+        if let Err(e) = sender.send(event) {
+            tracing::error!("Failed to publish staged node event: {e:#}");
+        }
+    }
+}
+
+pub fn discard_staged_events() {
+    STAGED_EVENTS.with(|staged_events| staged_events.borrow_mut().take());
+}
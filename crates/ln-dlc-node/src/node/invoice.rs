@@ -9,6 +9,7 @@ use crate::PaymentFlow;
 use crate::PaymentInfo;
 use anyhow::anyhow;
 use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::hashes::hex::ToHex;
@@ -31,14 +32,20 @@ use lightning::routing::router::PaymentParameters;
 use lightning::routing::router::RouteHint;
 use lightning::routing::router::RouteHintHop;
 use lightning::routing::router::RouteParameters;
+use lightning::routing::router::DEFAULT_MAX_PATH_COUNT;
 use lightning_invoice::payment::preflight_probe_invoice;
 use lightning_invoice::payment::preflight_probe_zero_value_invoice;
 use lightning_invoice::Bolt11Invoice;
 use lightning_invoice::Bolt11InvoiceDescription;
 use lightning_invoice::Currency;
 use lightning_invoice::InvoiceBuilder;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_with::serde_as;
+use serde_with::DurationSeconds;
 use std::fmt;
 use std::fmt::Formatter;
+use std::str::FromStr;
 use std::time::Duration;
 use std::time::SystemTime;
 use time::OffsetDateTime;
@@ -159,9 +166,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             short_channel_id: intercept_scid,
             fees: RoutingFees {
                 base_msat: ldk_config.channel_config.forwarding_fee_base_msat,
-                proportional_millionths: ldk_config
-                    .channel_config
-                    .forwarding_fee_proportional_millionths,
+                proportional_millionths: liquidity_request.liquidity_fee_ppm,
             },
             cltv_expiry_delta: MIN_CLTV_EXPIRY_DELTA,
             htlc_minimum_msat: None,
@@ -190,6 +195,51 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
         Ok(route_hint_hop)
     }
 
+    /// Prepare a payment to a peer which might be offline when the payment arrives.
+    ///
+    /// We register the peer's ID with a newly generated intercept SCID. An HTLC for this SCID is
+    /// held until the peer comes online, instead of failing right away.
+    ///
+    /// # Returns
+    ///
+    /// A [`RouteHintHop`] to be used as the last hop of the peer's invoice.
+    pub fn prepare_async_payment(&self, trader_id: PublicKey) -> Result<RouteHintHop> {
+        ensure!(
+            self.channel_manager
+                .list_channels()
+                .iter()
+                .any(|channel| channel.counterparty.node_id == trader_id),
+            "Cannot prepare async payment without a channel with {trader_id}"
+        );
+
+        let intercept_scid = self.channel_manager.get_intercept_scid();
+        self.async_payments.lock().insert(intercept_scid, trader_id);
+
+        let ldk_config = self.ldk_config.read();
+
+        let route_hint_hop = RouteHintHop {
+            src_node_id: self.info.pubkey,
+            short_channel_id: intercept_scid,
+            fees: RoutingFees {
+                base_msat: ldk_config.channel_config.forwarding_fee_base_msat,
+                proportional_millionths: ldk_config
+                    .channel_config
+                    .forwarding_fee_proportional_millionths,
+            },
+            cltv_expiry_delta: MIN_CLTV_EXPIRY_DELTA,
+            htlc_minimum_msat: None,
+            htlc_maximum_msat: None,
+        };
+
+        tracing::info!(
+            %trader_id,
+            interceptable_route_hint_hop = ?route_hint_hop,
+            "Registered interest to hold payments for offline peer"
+        );
+
+        Ok(route_hint_hop)
+    }
+
     pub fn prepare_payment_with_route_hint(&self, hop_node_id: PublicKey) -> Result<RouteHintHop> {
         let channels = self.channel_manager.list_channels();
         let channel = channels
@@ -233,7 +283,12 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
 
     /// Pay a [`Bolt11Invoice`]. If an [`Amount`] is supplied, we assume that it is a zero-value
     /// invoice.
-    pub fn pay_invoice(&self, invoice: &Bolt11Invoice, amount: Option<Amount>) -> Result<()> {
+    pub fn pay_invoice(
+        &self,
+        invoice: &Bolt11Invoice,
+        amount: Option<Amount>,
+        config: PaymentConfig,
+    ) -> Result<()> {
         let amount_msat = match amount {
             Some(amount) => amount.to_sat() * 1_000,
             None => invoice
@@ -241,22 +296,24 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
                 .context("Invoice amount not set")?,
         };
 
-        let (payment_id, payment_hash, recipient_onion, route_params) =
+        let (payment_id, payment_hash, recipient_onion, mut route_params) =
             invoice_parameters(invoice, amount_msat);
+        route_params.payment_params.max_path_count = config.max_parts;
+        route_params.max_total_routing_fee_msat = config.max_fee_msat;
+
+        let payee_pubkey = match invoice.payee_pub_key() {
+            Some(pubkey) => *pubkey,
+            None => invoice.recover_payee_pub_key(),
+        };
 
         let (status, err) = match self.channel_manager.send_payment(
             payment_hash,
             recipient_onion,
             payment_id,
             route_params,
-            Retry::Attempts(10),
+            Retry::Timeout(config.timeout),
         ) {
             Ok(()) => {
-                let payee_pubkey = match invoice.payee_pub_key() {
-                    Some(pubkey) => *pubkey,
-                    None => invoice.recover_payee_pub_key(),
-                };
-
                 tracing::info!(
                     peer_id = %payee_pubkey,
                     %amount_msat,
@@ -269,9 +326,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
 
             Err(err) => {
                 tracing::error!(?err, "Failed to send payment");
-                let failure_reason = retryable_send_failure_to_string(err);
-
-                (HTLCStatus::Failed, Some(failure_reason))
+                (HTLCStatus::Failed, Some(PaymentFailureReason::from(err)))
             }
         };
 
@@ -293,6 +348,12 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
                 description,
                 invoice: Some(format!("{invoice}")),
                 funding_txid: None,
+                expiry: invoice
+                    .timestamp()
+                    .checked_add(invoice.expiry_time())
+                    .map(OffsetDateTime::from),
+                payee: Some(payee_pubkey),
+                failure_reason: err,
             },
         )?;
 
@@ -509,10 +570,102 @@ impl fmt::Display for HTLCStatus {
     }
 }
 
-fn retryable_send_failure_to_string(failure: RetryableSendFailure) -> &'static str {
-    match failure {
-        RetryableSendFailure::DuplicatePayment => "Duplicate payment",
-        RetryableSendFailure::PaymentExpired => "Payment expired",
-        RetryableSendFailure::RouteNotFound => "Route not found",
+/// How hard we try to pay an invoice.
+#[serde_as]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PaymentConfig {
+    /// The maximum number of parts a payment may be split into.
+    pub max_parts: u8,
+    /// The maximum total routing fee. If set to `None`, LDK's default of 1% of the amount plus 50
+    /// sats applies.
+    pub max_fee_msat: Option<u64>,
+    /// For how long failed parts of a payment are retried.
+    #[serde_as(as = "DurationSeconds")]
+    pub timeout: Duration,
+}
+
+impl Default for PaymentConfig {
+    fn default() -> Self {
+        Self {
+            max_parts: DEFAULT_MAX_PATH_COUNT,
+            max_fee_msat: None,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Why an outbound payment failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentFailureReason {
+    /// The recipient rejected the payment, e.g. because the invoice is unknown to them.
+    RecipientRejected,
+    /// We abandoned the payment.
+    UserAbandoned,
+    /// No route succeeded within the [`PaymentConfig`]'s timeout.
+    RetriesExhausted,
+    /// The invoice expired before the payment succeeded.
+    PaymentExpired,
+    /// No route to the recipient within the [`PaymentConfig`]'s limits was found.
+    RouteNotFound,
+    /// The invoice has already been paid or is being paid.
+    DuplicatePayment,
+    UnexpectedError,
+}
+
+impl fmt::Display for PaymentFailureReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentFailureReason::RecipientRejected => "RecipientRejected".fmt(f),
+            PaymentFailureReason::UserAbandoned => "UserAbandoned".fmt(f),
+            PaymentFailureReason::RetriesExhausted => "RetriesExhausted".fmt(f),
+            PaymentFailureReason::PaymentExpired => "PaymentExpired".fmt(f),
+            PaymentFailureReason::RouteNotFound => "RouteNotFound".fmt(f),
+            PaymentFailureReason::DuplicatePayment => "DuplicatePayment".fmt(f),
+            PaymentFailureReason::UnexpectedError => "UnexpectedError".fmt(f),
+        }
+    }
+}
+
+impl FromStr for PaymentFailureReason {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let reason = match s {
+            "RecipientRejected" => PaymentFailureReason::RecipientRejected,
+            "UserAbandoned" => PaymentFailureReason::UserAbandoned,
+            "RetriesExhausted" => PaymentFailureReason::RetriesExhausted,
+            "PaymentExpired" => PaymentFailureReason::PaymentExpired,
+            "RouteNotFound" => PaymentFailureReason::RouteNotFound,
+            "DuplicatePayment" => PaymentFailureReason::DuplicatePayment,
+            "UnexpectedError" => PaymentFailureReason::UnexpectedError,
+            _ => bail!("Unknown payment failure reason: {s}"),
+        };
+
+        Ok(reason)
+    }
+}
+
+impl From<lightning::events::PaymentFailureReason> for PaymentFailureReason {
+    fn from(value: lightning::events::PaymentFailureReason) -> Self {
+        use lightning::events::PaymentFailureReason::*;
+
+        match value {
+            RecipientRejected => PaymentFailureReason::RecipientRejected,
+            UserAbandoned => PaymentFailureReason::UserAbandoned,
+            RetriesExhausted => PaymentFailureReason::RetriesExhausted,
+            PaymentExpired => PaymentFailureReason::PaymentExpired,
+            RouteNotFound => PaymentFailureReason::RouteNotFound,
+            UnexpectedError => PaymentFailureReason::UnexpectedError,
+        }
+    }
+}
+
+impl From<RetryableSendFailure> for PaymentFailureReason {
+    fn from(value: RetryableSendFailure) -> Self {
+        match value {
+            RetryableSendFailure::PaymentExpired => PaymentFailureReason::PaymentExpired,
+            RetryableSendFailure::RouteNotFound => PaymentFailureReason::RouteNotFound,
+            RetryableSendFailure::DuplicatePayment => PaymentFailureReason::DuplicatePayment,
+        }
     }
 }
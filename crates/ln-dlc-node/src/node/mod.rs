@@ -1,3 +1,6 @@
+use crate::chain_source::ChainSource;
+use crate::chain_source::ChainSourceConfig;
+use crate::chain_source::LightningSync;
 use crate::channel::UserChannelId;
 use crate::dlc_custom_signer::CustomKeysManager;
 use crate::fee_rate_estimator::FeeRateEstimator;
@@ -13,12 +16,16 @@ use crate::on_chain_wallet::OnChainWallet;
 use crate::seed::Bip39Seed;
 use crate::shadow::Shadow;
 use crate::storage::TenTenOneStorage;
+use crate::watchtower;
+use crate::watchtower::Watchtower;
+use crate::watchtower::WatchtowerPersister;
 use crate::ChainMonitor;
 use crate::EventHandlerTrait;
 use crate::NetworkGraph;
 use crate::P2pGossipSync;
 use crate::PeerManager;
 use crate::RapidGossipSync;
+use crate::SendPreview;
 use crate::WalletSettings;
 use anyhow::anyhow;
 use anyhow::Context;
@@ -28,6 +35,7 @@ use bitcoin::hashes::hex::ToHex;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::Amount;
 use bitcoin::Network;
+use bitcoin::OutPoint;
 use bitcoin::Txid;
 use bitcoin::XOnlyPublicKey;
 use dlc_messages::message_handler::MessageHandler as DlcMessageHandler;
@@ -47,9 +55,7 @@ use lightning::sign::EntropySource;
 use lightning::sign::KeysManager;
 use lightning::util::config::UserConfig;
 use lightning_background_processor::process_events_async;
-use lightning_transaction_sync::EsploraSyncClient;
 use ln_dlc_storage::DlcStorageProvider;
-use p2pd_oracle_client::P2PDOracleClient;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_with::serde_as;
@@ -65,10 +71,14 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
+use tokio::sync::watch;
 use tokio::sync::RwLock;
 use tokio::task::spawn_blocking;
+use tracing::instrument;
 
+mod channel_backup;
 mod channel_manager;
+mod channel_open_status;
 mod connection;
 mod dlc_manager;
 mod ln_channel;
@@ -76,6 +86,7 @@ mod oracle;
 mod storage;
 mod sub_channel_manager;
 mod wallet;
+mod wallet_sync;
 
 pub(crate) mod invoice;
 pub(crate) mod sub_channel;
@@ -88,10 +99,19 @@ pub use crate::node::connection::TenTenOneOnionMessageHandler;
 pub use crate::node::dlc_manager::signed_channel_state_name;
 pub use crate::node::dlc_manager::DlcManager;
 use crate::node::event::NodeEventHandler;
+pub use crate::node::oracle::OracleClient;
 pub use crate::node::oracle::OracleInfo;
+pub use crate::node::oracle::OracleProtocol;
 pub use ::dlc_manager as rust_dlc_manager;
+pub use channel_backup::restore_channel_monitors;
+pub use channel_backup::ChannelBackup;
+pub use channel_backup::ChannelBackupKind;
+pub use channel_backup::StaticChannelBackup;
 pub use channel_manager::ChannelManager;
+pub use channel_open_status::ChannelOpenStatus;
 pub use invoice::HTLCStatus;
+pub use invoice::PaymentConfig;
+pub use invoice::PaymentFailureReason;
 use lightning::ln::msgs::SocketAddress;
 use lightning::util::persist::KVStore;
 use lightning::util::persist::NETWORK_GRAPH_PERSISTENCE_KEY;
@@ -105,6 +125,9 @@ pub use sub_channel::send_sub_channel_message;
 pub use sub_channel::sub_channel_message_name;
 pub use sub_channel_manager::SubChannelManager;
 pub use wallet::PaymentDetails;
+pub use wallet_sync::SyncCancellation;
+pub use wallet_sync::SyncKind;
+pub use wallet_sync::SyncProgress;
 
 /// The interval at which the [`lightning::ln::msgs::NodeAnnouncement`] is broadcast.
 ///
@@ -119,13 +142,17 @@ const MANAGE_SPENDABLE_OUTPUTS_INTERVAL: Duration = Duration::from_secs(30 * 60)
 /// Value taken from `ldk-node` project.
 const RGS_SYNC_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
-type Scorer = ProbabilisticScorer<Arc<NetworkGraph>, Arc<TracingLogger>>;
+/// The interval at which we check whether the chain source is reachable, switching back to a more
+/// preferred esplora backend if possible.
+const CHAIN_SOURCE_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 
-type NodeEsploraClient = EsploraSyncClient<Arc<TracingLogger>>;
+type Scorer = ProbabilisticScorer<Arc<NetworkGraph>, Arc<TracingLogger>>;
 
 type RequestedScid = u64;
 // TODO(holzeis): Move to coordinator
 type FakeChannelPaymentRequests = Arc<parking_lot::Mutex<HashMap<RequestedScid, LiquidityRequest>>>;
+// TODO(holzeis): Move to coordinator
+type AsyncPaymentRequests = Arc<parking_lot::Mutex<HashMap<RequestedScid, PublicKey>>>;
 
 #[derive(Clone, Debug)]
 pub struct LiquidityRequest {
@@ -136,6 +163,8 @@ pub struct LiquidityRequest {
     pub max_deposit_sats: u64,
     pub coordinator_leverage: f32,
     pub fee_sats: u64,
+    /// The proportional fee charged for forwarding the payment which opens the channel.
+    pub liquidity_fee_ppm: u32,
 }
 
 /// An LN-DLC node.
@@ -156,12 +185,13 @@ pub struct Node<S: TenTenOneStorage, N: Storage> {
 
     pub info: NodeInfo,
     pub(crate) fake_channel_payments: FakeChannelPaymentRequests,
+    pub(crate) async_payments: AsyncPaymentRequests,
 
     pub dlc_manager: Arc<DlcManager<S, N>>,
     pub sub_channel_manager: Arc<SubChannelManager<S, N>>,
 
     /// All oracles clients the node is aware of.
-    oracles: Vec<Arc<P2PDOracleClient>>,
+    oracles: Vec<Arc<OracleClient>>,
     pub dlc_message_handler: Arc<DlcMessageHandler>,
     pub ldk_config: Arc<parking_lot::RwLock<UserConfig>>,
 
@@ -169,6 +199,8 @@ pub struct Node<S: TenTenOneStorage, N: Storage> {
     pub oracle_pubkey: XOnlyPublicKey,
 
     pub event_handler: Arc<NodeEventHandler>,
+    pub watchtower: Arc<Watchtower>,
+    on_chain_sync_cancellation: SyncCancellation,
 
     // storage
     // TODO(holzeis): The node storage should get extracted to the corresponding application
@@ -183,13 +215,14 @@ pub struct Node<S: TenTenOneStorage, N: Storage> {
     pub(crate) alias: String,
     pub(crate) announcement_addresses: Vec<SocketAddress>,
     pub scorer: Arc<std::sync::RwLock<Scorer>>,
-    esplora_server_url: String,
-    esplora_client: Arc<NodeEsploraClient>,
+    pub chain_source: Arc<ChainSource>,
+    lightning_sync: Arc<LightningSync>,
     pub pending_channel_opening_fee_rates: Arc<parking_lot::Mutex<HashMap<PublicKey, FeeRate>>>,
     pub probes: Probes,
 }
 
 /// An on-chain network fee for a transaction
+#[derive(Debug, Clone, Copy)]
 pub enum Fee {
     /// A fee given by the transaction's priority
     Priority(ConfirmationTarget),
@@ -206,6 +239,24 @@ pub struct NodeInfo {
 /// Node is running until this struct is dropped
 pub struct RunningNode {
     _handles: Vec<RemoteHandle<()>>,
+    background_processor: parking_lot::Mutex<Option<RemoteHandle<()>>>,
+    stop_background_processor: watch::Sender<bool>,
+}
+
+impl RunningNode {
+    /// Stop the background processor and wait for it to finish.
+    ///
+    /// On exit, the background processor handles the pending events and persists the channel
+    /// manager one last time, so that no channel state is lost when the process exits afterwards.
+    pub async fn stop(&self) {
+        let _ = self.stop_background_processor.send(true);
+
+        let background_processor = self.background_processor.lock().take();
+        if let Some(background_processor) = background_processor {
+            background_processor.await;
+            tracing::info!("Background processor stopped");
+        }
+    }
 }
 
 #[serde_as]
@@ -246,6 +297,57 @@ pub struct LnDlcNodeSettings {
 
     /// XXX: Requires restart of the node to take effect
     pub gossip_source_config: GossipSourceConfig,
+
+    /// The mempool.space instance preferred for fee rate estimation, e.g.
+    /// `https://mempool.space`. If not set or not available, fee rates are taken from the chain
+    /// source.
+    /// XXX: Requires restart of the node to take effect
+    #[serde(default)]
+    pub mempool_space_url: Option<String>,
+
+    /// The peers from which we accept inbound channels before their funding transaction
+    /// confirms.
+    #[serde(default)]
+    pub zero_conf_trust_policy: ZeroConfTrustPolicy,
+
+    /// The watchtower we upload the justice transactions for our channels to.
+    /// XXX: Requires restart of the node to take effect
+    #[serde(default)]
+    pub watchtower_url: Option<String>,
+
+    /// How long we hold a payment to an offline peer, waiting for them to come online to receive
+    /// it.
+    #[serde_as(as = "DurationSeconds")]
+    #[serde(default = "default_async_payment_hold_timeout")]
+    pub async_payment_hold_timeout: Duration,
+}
+
+fn default_async_payment_hold_timeout() -> Duration {
+    Duration::from_secs(600)
+}
+
+/// Which peers we trust to open zero-conf channels to us.
+///
+/// A zero-conf channel can be used as soon as it is opened. Until the funding transaction
+/// confirms, the peer could double-spend it, so we only accept zero-conf channels from peers we
+/// trust.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub enum ZeroConfTrustPolicy {
+    /// Every inbound channel needs a confirmed funding transaction.
+    #[default]
+    TrustNobody,
+    TrustPeers(Vec<PublicKey>),
+    TrustEverybody,
+}
+
+impl ZeroConfTrustPolicy {
+    pub fn is_trusted(&self, peer: &PublicKey) -> bool {
+        match self {
+            ZeroConfTrustPolicy::TrustNobody => false,
+            ZeroConfTrustPolicy::TrustPeers(peers) => peers.contains(peer),
+            ZeroConfTrustPolicy::TrustEverybody => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -255,6 +357,18 @@ pub enum GossipSourceConfig {
 }
 
 impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S, N> {
+    /// Registers with the watchtower at `url`, which from then on receives the justice
+    /// transactions for all our channels.
+    pub async fn register_watchtower(&self, url: String) -> Result<()> {
+        let watchtower = self.watchtower.clone();
+        let node_id = self.info.pubkey;
+        tokio::task::spawn_blocking(move || watchtower.register(&url, node_id)).await??;
+
+        self.settings.write().await.watchtower_url = self.watchtower.url();
+
+        Ok(())
+    }
+
     pub async fn update_settings(&self, new_settings: LnDlcNodeSettings) {
         tracing::info!(?new_settings, "Updating LnDlcNode settings");
         *self.settings.write().await = new_settings;
@@ -273,12 +387,12 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
         announcement_address: SocketAddr,
         listen_address: SocketAddr,
         announcement_addresses: Vec<SocketAddress>,
-        esplora_server_url: String,
+        chain_source: ChainSourceConfig,
         seed: Bip39Seed,
         ephemeral_randomness: [u8; 32],
         settings: LnDlcNodeSettings,
         wallet_settings: WalletSettings,
-        oracle_clients: Vec<P2PDOracleClient>,
+        oracle_clients: Vec<OracleClient>,
         oracle_pubkey: XOnlyPublicKey,
         node_event_handler: Arc<NodeEventHandler>,
     ) -> Result<Self>
@@ -297,34 +411,47 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
         let on_chain_wallet =
             OnChainWallet::new(on_chain_dir.as_path(), network, seed.wallet_seed())?;
 
-        let esplora_client = Arc::new(EsploraSyncClient::new(
-            esplora_server_url.clone(),
-            logger.clone(),
-        ));
+        let chain_source = Arc::new(ChainSource::new(chain_source)?);
+        let lightning_sync = Arc::new(chain_source.lightning_sync(logger.clone()));
 
         let dlc_storage = Arc::new(DlcStorageProvider::new(storage.clone()));
         let ln_storage = Arc::new(storage);
 
-        let fee_rate_estimator = Arc::new(FeeRateEstimator::new(esplora_server_url.clone()));
+        let fee_rate_estimator = Arc::new(FeeRateEstimator::new(
+            chain_source.clone(),
+            settings.mempool_space_url.clone(),
+        ));
         let ln_dlc_wallet = {
+            let blockchain = chain_source.wallet_blockchain(
+                on_chain_wallet.wallet_name.clone(),
+                network,
+                settings.bdk_client_stop_gap,
+                settings.bdk_client_concurrency,
+            )?;
+
             Arc::new(LnDlcWallet::new(
-                esplora_client.clone(),
-                on_chain_wallet.inner,
+                blockchain,
+                on_chain_wallet,
                 fee_rate_estimator.clone(),
                 dlc_storage.clone(),
                 node_storage.clone(),
-                settings.bdk_client_stop_gap,
-                settings.bdk_client_concurrency,
                 wallet_settings,
             ))
         };
 
+        let watchtower = Arc::new(Watchtower::new());
+
         let chain_monitor: Arc<ChainMonitor<S, N>> = Arc::new(chainmonitor::ChainMonitor::new(
-            Some(esplora_client.clone()),
+            Some(lightning_sync.clone()),
             ln_dlc_wallet.clone(),
             logger.clone(),
             fee_rate_estimator.clone(),
-            ln_storage.clone(),
+            Arc::new(WatchtowerPersister::new(
+                ln_storage.clone(),
+                watchtower.clone(),
+                ln_dlc_wallet.clone(),
+                fee_rate_estimator.clone(),
+            )),
         ));
 
         let keys_manager = {
@@ -384,7 +511,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             keys_manager.clone(),
             ln_dlc_wallet.clone(),
             fee_rate_estimator.clone(),
-            esplora_client.clone(),
+            lightning_sync.clone(),
             logger.clone(),
             chain_monitor.clone(),
             *ldk_config.read(),
@@ -393,6 +520,9 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             router,
         )?;
 
+        let best_block = channel_manager.current_best_block();
+        lightning_sync.start_from(best_block.height(), best_block.block_hash());
+
         let channel_manager = Arc::new(channel_manager);
 
         let gossip_source = match &settings.gossip_source_config {
@@ -416,7 +546,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             }
         };
 
-        let oracle_clients: Vec<Arc<P2PDOracleClient>> =
+        let oracle_clients: Vec<Arc<OracleClient>> =
             oracle_clients.into_iter().map(Arc::new).collect();
 
         let dlc_manager = dlc_manager::build(
@@ -467,6 +597,8 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
 
         let fake_channel_payments: FakeChannelPaymentRequests =
             Arc::new(parking_lot::Mutex::new(HashMap::new()));
+        let async_payments: AsyncPaymentRequests =
+            Arc::new(parking_lot::Mutex::new(HashMap::new()));
 
         let node_info = NodeInfo {
             pubkey: channel_manager.get_our_node_id(),
@@ -487,6 +619,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             channel_manager: channel_manager.clone(),
             info: node_info,
             fake_channel_payments,
+            async_payments,
             sub_channel_manager,
             oracles: oracle_clients,
             dlc_message_handler,
@@ -503,12 +636,14 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             alias: alias.to_string(),
             announcement_addresses,
             scorer,
-            esplora_server_url,
-            esplora_client,
+            chain_source,
+            lightning_sync,
             pending_channel_opening_fee_rates: Arc::new(parking_lot::Mutex::new(HashMap::new())),
             oracle_pubkey,
             probes: Probes::default(),
             event_handler: node_event_handler,
+            watchtower,
+            on_chain_sync_cancellation: SyncCancellation::default(),
         })
     }
 
@@ -536,7 +671,22 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             self.channel_manager.clone(),
             self.chain_monitor.clone(),
             self.settings.clone(),
-            self.esplora_client.clone(),
+            self.lightning_sync.clone(),
+        ));
+
+        tokio::spawn(channel_open_status::track_channel_open_status(
+            self.channel_manager.clone(),
+            self.event_handler.clone(),
+        ));
+
+        tokio::spawn(watchtower::register_configured_watchtower(
+            self.settings.clone(),
+            self.watchtower.clone(),
+            self.info.pubkey,
+        ));
+
+        tokio::spawn(check_chain_source_health_periodically(
+            self.chain_source.clone(),
         ));
 
         tokio::spawn(update_fee_rate_estimates(
@@ -544,7 +694,8 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             self.fee_rate_estimator.clone(),
         ));
 
-        handles.push(spawn_background_processor(
+        let (stop_background_processor, stop_signal) = watch::channel(false);
+        let background_processor = spawn_background_processor(
             self.peer_manager.clone(),
             self.channel_manager.clone(),
             self.chain_monitor.clone(),
@@ -554,7 +705,8 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             self.gossip_source.clone(),
             self.scorer.clone(),
             mobile_interruptable_platform,
-        ));
+            stop_signal,
+        );
 
         handles.push(spawn_broadcast_node_annoucements(
             &self.alias,
@@ -580,7 +732,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
         ));
 
         tokio::spawn(manage_spendable_outputs_task(
-            self.esplora_server_url.clone(),
+            self.chain_source.clone(),
             self.node_storage.clone(),
             self.wallet.clone(),
             self.fee_rate_estimator.clone(),
@@ -589,7 +741,11 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
 
         tracing::info!("Lightning node started with node ID {}", self.info);
 
-        Ok(RunningNode { _handles: handles })
+        Ok(RunningNode {
+            _handles: handles,
+            background_processor: parking_lot::Mutex::new(Some(background_processor)),
+            stop_background_processor,
+        })
     }
 
     pub fn update_ldk_settings(&self, ldk_config: UserConfig) {
@@ -656,7 +812,7 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
         lightning_wallet_sync(
             &self.channel_manager,
             &self.chain_monitor,
-            &self.esplora_client,
+            &self.lightning_sync,
         )
     }
 
@@ -673,16 +829,45 @@ impl<S: TenTenOneStorage + 'static, N: Storage + Sync + Send + 'static> Node<S,
             .calculate_fee(address, amount_sats, fee)
     }
 
+    /// Preview the transaction [`Node::send_to_address`] would create, including its exact fee.
+    pub fn preview_send_to_address(
+        &self,
+        address: &bitcoin::Address,
+        amount_sats: u64,
+        fee: Fee,
+        utxos: Option<&[OutPoint]>,
+    ) -> Result<SendPreview> {
+        self.wallet
+            .ldk_wallet()
+            .preview_send(address, amount_sats, fee, utxos)
+    }
+
     /// Send the given `amount_sats` sats to the given `address` on-chain.
+    ///
+    /// If `utxos` is set, only those UTXOs are spent.
     pub fn send_to_address(
         &self,
         address: &bitcoin::Address,
         amount_sats: u64,
         fee: Fee,
+        utxos: Option<Vec<OutPoint>>,
     ) -> Result<Txid> {
         self.wallet
             .ldk_wallet()
-            .send_to_address(address, amount_sats, fee)
+            .send_to_address(address, amount_sats, fee, utxos)
+    }
+}
+
+async fn check_chain_source_health_periodically(chain_source: Arc<ChainSource>) {
+    loop {
+        spawn_blocking({
+            let chain_source = chain_source.clone();
+            move || chain_source.check_health()
+        })
+        .await
+        .expect("task to complete");
+
+        tokio::time::sleep(CHAIN_SOURCE_HEALTH_CHECK_INTERVAL).await;
     }
 }
 
@@ -714,6 +899,7 @@ fn spawn_background_processor<S: TenTenOneStorage + 'static, N: Storage + Sync +
     gossip_source: Arc<GossipSource>,
     scorer: Arc<std::sync::RwLock<Scorer>>,
     mobile_interruptable_platform: bool,
+    stop_signal: watch::Receiver<bool>,
 ) -> RemoteHandle<()> {
     tracing::info!("Starting background processor");
     let (fut, remote_handle) = async move {
@@ -727,9 +913,16 @@ fn spawn_background_processor<S: TenTenOneStorage + 'static, N: Storage + Sync +
             logger,
             Some(scorer),
             |d| {
+                let mut stop_signal = stop_signal.clone();
                 Box::pin(async move {
-                    tokio::time::sleep(d).await;
-                    false
+                    if *stop_signal.borrow() {
+                        return true;
+                    }
+
+                    tokio::select! {
+                        _ = tokio::time::sleep(d) => false,
+                        _ = stop_signal.changed() => true,
+                    }
                 })
             },
             mobile_interruptable_platform,
@@ -748,10 +941,10 @@ async fn periodic_lightning_wallet_sync<S: TenTenOneStorage, N: Storage + Sync +
     channel_manager: Arc<ChannelManager<S, N>>,
     chain_monitor: Arc<ChainMonitor<S, N>>,
     settings: Arc<RwLock<LnDlcNodeSettings>>,
-    esplora_client: Arc<EsploraSyncClient<Arc<TracingLogger>>>,
+    lightning_sync: Arc<LightningSync>,
 ) {
     loop {
-        if let Err(e) = lightning_wallet_sync(&channel_manager, &chain_monitor, &esplora_client) {
+        if let Err(e) = lightning_wallet_sync(&channel_manager, &chain_monitor, &lightning_sync) {
             tracing::error!("Background sync of Lightning wallet failed: {e:#}")
         }
 
@@ -763,17 +956,18 @@ async fn periodic_lightning_wallet_sync<S: TenTenOneStorage, N: Storage + Sync +
     }
 }
 
+#[instrument(skip_all)]
 fn lightning_wallet_sync<S: TenTenOneStorage, N: Storage + Sync + Send>(
     channel_manager: &ChannelManager<S, N>,
     chain_monitor: &ChainMonitor<S, N>,
-    esplora_client: &EsploraSyncClient<Arc<TracingLogger>>,
+    lightning_sync: &LightningSync,
 ) -> Result<()> {
     let now = Instant::now();
     let confirmables = vec![
         channel_manager as &(dyn Confirm + Sync + Send),
         chain_monitor as &(dyn Confirm + Sync + Send),
     ];
-    esplora_client
+    lightning_sync
         .sync(confirmables)
         .context("Lightning wallet sync failed")?;
 
@@ -891,19 +1085,15 @@ async fn manage_spendable_outputs_task<
     S: TenTenOneStorage + 'static,
     N: Storage + Sync + Send + 'static,
 >(
-    esplora_server_url: String,
+    chain_source: Arc<ChainSource>,
     node_storage: Arc<N>,
     ln_dlc_wallet: Arc<LnDlcWallet<S, N>>,
     fee_rate_estimator: Arc<FeeRateEstimator>,
     keys_manager: Arc<CustomKeysManager<S, N>>,
 ) {
-    let client = Arc::new(esplora_client::BlockingClient::from_agent(
-        esplora_server_url,
-        ureq::agent(),
-    ));
     loop {
         if let Err(e) = spawn_blocking({
-            let client = client.clone();
+            let chain_source = chain_source.clone();
             let node_storage = node_storage.clone();
             let ln_dlc_wallet = ln_dlc_wallet.clone();
             let fee_rate_estimator = fee_rate_estimator.clone();
@@ -911,7 +1101,7 @@ async fn manage_spendable_outputs_task<
             move || {
                 manage_spendable_outputs(
                     node_storage,
-                    client,
+                    chain_source,
                     ln_dlc_wallet,
                     fee_rate_estimator,
                     keys_manager,
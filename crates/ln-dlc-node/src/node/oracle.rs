@@ -1,23 +1,123 @@
 use crate::node::Node;
 use crate::node::Storage;
 use crate::storage::TenTenOneStorage;
+use anyhow::Context;
+use anyhow::Result;
 use bitcoin::secp256k1::XOnlyPublicKey;
 use dlc_manager::Oracle;
+use dlc_messages::oracle_msgs::OracleAnnouncement;
+use dlc_messages::oracle_msgs::OracleAttestation;
 use p2pd_oracle_client::P2PDOracleClient;
 use serde::Deserialize;
 use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+mod tlv;
+
+pub use tlv::TlvOracleClient;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OracleInfo {
     pub endpoint: String,
     pub public_key: XOnlyPublicKey,
+    /// The protocol spoken by the oracle. Defaults to the p2p-derivatives oracle.
+    #[serde(default)]
+    pub protocol: OracleProtocol,
+}
+
+/// The REST schema an oracle serves its announcements and attestations with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OracleProtocol {
+    /// The [p2p-derivatives oracle](https://github.com/p2pderivatives/p2pderivatives-oracle),
+    /// which serves announcements and attestations as JSON.
+    #[default]
+    P2pd,
+    /// An oracle serving announcements and attestations in their DLC specification encoding, as
+    /// hex-encoded TLVs.
+    Tlv,
+}
+
+impl OracleInfo {
+    /// The URL under which the oracle serves its public key, which can be used to check if the
+    /// oracle is reachable.
+    pub fn public_key_url(&self) -> String {
+        let endpoint = self.endpoint.trim_end_matches('/');
+        match self.protocol {
+            OracleProtocol::P2pd => format!("{endpoint}/oracle/publickey"),
+            OracleProtocol::Tlv => format!("{endpoint}/pubkey"),
+        }
+    }
+}
+
+impl fmt::Display for OracleProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OracleProtocol::P2pd => "p2pd",
+            OracleProtocol::Tlv => "tlv",
+        }
+        .fmt(f)
+    }
+}
+
+impl FromStr for OracleProtocol {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "p2pd" => Ok(OracleProtocol::P2pd),
+            "tlv" => Ok(OracleProtocol::Tlv),
+            _ => anyhow::bail!("Unknown oracle protocol {s}"),
+        }
+    }
+}
+
+/// A client for any of the oracle protocols we support.
+pub enum OracleClient {
+    P2pd(P2PDOracleClient),
+    Tlv(TlvOracleClient),
+}
+
+impl Oracle for OracleClient {
+    fn get_public_key(&self) -> XOnlyPublicKey {
+        match self {
+            OracleClient::P2pd(client) => client.get_public_key(),
+            OracleClient::Tlv(client) => client.get_public_key(),
+        }
+    }
+
+    fn get_announcement(
+        &self,
+        event_id: &str,
+    ) -> Result<OracleAnnouncement, dlc_manager::error::Error> {
+        match self {
+            OracleClient::P2pd(client) => client.get_announcement(event_id),
+            OracleClient::Tlv(client) => client.get_announcement(event_id),
+        }
+    }
+
+    fn get_attestation(
+        &self,
+        event_id: &str,
+    ) -> Result<OracleAttestation, dlc_manager::error::Error> {
+        match self {
+            OracleClient::P2pd(client) => client.get_attestation(event_id),
+            OracleClient::Tlv(client) => client.get_attestation(event_id),
+        }
+    }
 }
 
-impl From<OracleInfo> for P2PDOracleClient {
+impl From<OracleInfo> for OracleClient {
     fn from(oracle: OracleInfo) -> Self {
-        P2PDOracleClient {
-            host: oracle.endpoint + "/",
-            public_key: oracle.public_key,
+        match oracle.protocol {
+            OracleProtocol::P2pd => OracleClient::P2pd(P2PDOracleClient {
+                host: oracle.endpoint + "/",
+                public_key: oracle.public_key,
+            }),
+            OracleProtocol::Tlv => {
+                OracleClient::Tlv(TlvOracleClient::new(oracle.endpoint, oracle.public_key))
+            }
         }
     }
 }
@@ -30,4 +130,62 @@ impl<S: TenTenOneStorage, N: Storage> Node<S, N> {
             .map(|oracle| oracle.get_public_key())
             .collect()
     }
+
+    /// Fetch the attestation of the event with the given `event_id` from the oracle with the given
+    /// public key.
+    ///
+    /// This function blocks until the oracle has responded.
+    pub fn get_attestation(
+        &self,
+        oracle_pk: &XOnlyPublicKey,
+        event_id: &str,
+    ) -> Result<OracleAttestation> {
+        let oracle = self
+            .oracles
+            .iter()
+            .find(|oracle| oracle.get_public_key() == *oracle_pk)
+            .with_context(|| format!("Unknown oracle {oracle_pk}"))?;
+
+        let attestation = oracle
+            .get_attestation(event_id)
+            .with_context(|| format!("Could not get attestation for event {event_id}"))?;
+
+        Ok(attestation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_key_url_depends_on_protocol() {
+        let public_key = XOnlyPublicKey::from_str(
+            "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0",
+        )
+        .unwrap();
+
+        let mut oracle = OracleInfo {
+            endpoint: "http://localhost:8081/".to_string(),
+            public_key,
+            protocol: OracleProtocol::P2pd,
+        };
+        assert_eq!(
+            oracle.public_key_url(),
+            "http://localhost:8081/oracle/publickey"
+        );
+
+        oracle.protocol = OracleProtocol::Tlv;
+        assert_eq!(oracle.public_key_url(), "http://localhost:8081/pubkey");
+    }
+
+    #[test]
+    fn oracle_protocol_roundtrips_through_string() {
+        for protocol in [OracleProtocol::P2pd, OracleProtocol::Tlv] {
+            assert_eq!(
+                OracleProtocol::from_str(&protocol.to_string()).unwrap(),
+                protocol
+            );
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use bitcoin::secp256k1::XOnlyPublicKey;
+use dlc_manager::error::Error as DlcManagerError;
+use dlc_manager::Oracle;
+use dlc_messages::oracle_msgs::OracleAnnouncement;
+use dlc_messages::oracle_msgs::OracleAttestation;
+use lightning::util::ser::Readable;
+use std::io::Cursor;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A client for oracles serving their announcements and attestations in the encoding defined by
+/// the DLC specification.
+///
+/// The oracle is expected to serve
+///
+/// - `GET {host}/announcement/{event_id}` and
+/// - `GET {host}/attestation/{event_id}`,
+///
+/// each responding with the hex-encoded serialization of the respective message.
+pub struct TlvOracleClient {
+    host: String,
+    public_key: XOnlyPublicKey,
+    agent: ureq::Agent,
+}
+
+impl TlvOracleClient {
+    pub fn new(host: String, public_key: XOnlyPublicKey) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+
+        Self {
+            host: host.trim_end_matches('/').to_string(),
+            public_key,
+            agent,
+        }
+    }
+
+    fn get<T: Readable>(&self, path: &str) -> Result<T, DlcManagerError> {
+        let url = format!("{}/{path}", self.host);
+
+        let response = self
+            .agent
+            .get(&url)
+            .call()
+            .map_err(|e| oracle_error(format!("Request to {url} failed: {e}")))?
+            .into_string()
+            .map_err(|e| oracle_error(format!("Could not read response from {url}: {e}")))?;
+
+        let bytes = hex::decode(response.trim())
+            .map_err(|e| oracle_error(format!("Response from {url} is not hex-encoded: {e}")))?;
+
+        Readable::read(&mut Cursor::new(bytes))
+            .map_err(|e| oracle_error(format!("Could not decode response from {url}: {e:?}")))
+    }
+}
+
+impl Oracle for TlvOracleClient {
+    fn get_public_key(&self) -> XOnlyPublicKey {
+        self.public_key
+    }
+
+    fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, DlcManagerError> {
+        let announcement: OracleAnnouncement = self.get(&format!("announcement/{event_id}"))?;
+
+        if announcement.oracle_public_key != self.public_key {
+            return Err(oracle_error(format!(
+                "Announcement of event {event_id} is signed by unexpected oracle {}",
+                announcement.oracle_public_key
+            )));
+        }
+
+        if announcement.oracle_event.event_id != event_id {
+            return Err(oracle_error(format!(
+                "Requested announcement of event {event_id}, got {}",
+                announcement.oracle_event.event_id
+            )));
+        }
+
+        Ok(announcement)
+    }
+
+    fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, DlcManagerError> {
+        let attestation: OracleAttestation = self.get(&format!("attestation/{event_id}"))?;
+
+        if attestation.oracle_public_key != self.public_key {
+            return Err(oracle_error(format!(
+                "Attestation of event {event_id} is signed by unexpected oracle {}",
+                attestation.oracle_public_key
+            )));
+        }
+
+        Ok(attestation)
+    }
+}
+
+fn oracle_error(message: String) -> DlcManagerError {
+    DlcManagerError::OracleError(message)
+}
@@ -3,6 +3,7 @@ use crate::channel::ChannelState;
 use crate::transaction::Transaction;
 use crate::HTLCStatus;
 use crate::MillisatAmount;
+use crate::PaymentFailureReason;
 use crate::PaymentFlow;
 use crate::PaymentInfo;
 use anyhow::Result;
@@ -40,6 +41,7 @@ pub trait Storage {
         preimage: Option<PaymentPreimage>,
         secret: Option<PaymentSecret>,
         funding_txid: Option<Txid>,
+        failure_reason: Option<PaymentFailureReason>,
     ) -> Result<()>;
     /// Get a payment based on its payment hash.
     ///
@@ -126,6 +128,7 @@ impl Storage for InMemoryStore {
         preimage: Option<PaymentPreimage>,
         secret: Option<PaymentSecret>,
         funding_txid: Option<Txid>,
+        failure_reason: Option<PaymentFailureReason>,
     ) -> Result<()> {
         let mut payments = self.payments.lock();
         match payments.get_mut(payment_hash) {
@@ -151,6 +154,10 @@ impl Storage for InMemoryStore {
                 if let Some(funding_txid) = funding_txid {
                     payment.funding_txid = Some(funding_txid);
                 }
+
+                if let Some(failure_reason) = failure_reason {
+                    payment.failure_reason = Some(failure_reason);
+                }
             }
             None => {
                 payments.insert(
@@ -166,6 +173,9 @@ impl Storage for InMemoryStore {
                         description: "".to_string(),
                         invoice: None,
                         funding_txid,
+                        expiry: None,
+                        payee: None,
+                        failure_reason,
                     },
                 );
             }
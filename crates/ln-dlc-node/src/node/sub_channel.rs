@@ -45,16 +45,16 @@ impl<S: TenTenOneStorage + 'static, N: LnDlcStorage + Sync + Send + 'static> Nod
         );
 
         spawn_blocking({
-            let p2pd_oracles = self.oracles.clone();
+            let oracle_clients = self.oracles.clone();
             let sub_channel_manager = self.sub_channel_manager.clone();
             let oracles = contract_input.contract_infos[0].oracles.clone();
             let event_id = oracles.event_id;
             let dlc_message_handler = self.dlc_message_handler.clone();
             let peer_manager = self.peer_manager.clone();
             move || {
-                let announcements: Vec<_> = p2pd_oracles
+                let announcements: Vec<_> = oracle_clients
                     .into_iter()
-                    .filter(|o| oracles.public_keys.contains(&o.public_key))
+                    .filter(|o| oracles.public_keys.contains(&o.get_public_key()))
                     .filter_map(|oracle| oracle.get_announcement(&event_id).ok())
                     .collect();
 
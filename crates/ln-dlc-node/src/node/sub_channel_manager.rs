@@ -3,6 +3,7 @@ use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ln_dlc_wallet::LnDlcWallet;
 use crate::node::channel_manager::ChannelManager;
 use crate::node::dlc_manager::DlcManager;
+use crate::node::oracle::OracleClient;
 use crate::node::Storage;
 use crate::storage::TenTenOneStorage;
 use crate::ChainMonitor;
@@ -11,7 +12,6 @@ use anyhow::Result;
 use dlc_manager::sub_channel_manager;
 use dlc_manager::SystemTimeProvider;
 use ln_dlc_storage::DlcStorageProvider;
-use p2pd_oracle_client::P2PDOracleClient;
 use std::sync::Arc;
 
 pub type SubChannelManager<S, N> = sub_channel_manager::SubChannelManager<
@@ -20,7 +20,7 @@ pub type SubChannelManager<S, N> = sub_channel_manager::SubChannelManager<
     Arc<ChainMonitor<S, N>>,
     Arc<DlcStorageProvider<S>>,
     Arc<LnDlcWallet<S, N>>,
-    Arc<P2PDOracleClient>,
+    Arc<OracleClient>,
     Arc<SystemTimeProvider>,
     Arc<FeeRateEstimator>,
     Arc<DlcManager<S, N>>,
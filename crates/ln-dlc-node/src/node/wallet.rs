@@ -1,15 +1,17 @@
+use crate::chain_source::WalletBlockchain;
 use crate::fee_rate_estimator::FeeRateEstimator;
 use crate::ldk_node_wallet;
 use crate::ln_dlc_wallet::LnDlcWallet;
 use crate::node::HTLCStatus;
 use crate::node::Node;
+use crate::node::PaymentFailureReason;
 use crate::node::Storage;
 use crate::storage::TenTenOneStorage;
 use crate::PaymentFlow;
+use crate::ReserveStatus;
 use crate::ToHex;
 use anyhow::Context;
 use anyhow::Result;
-use bdk::blockchain::EsploraBlockchain;
 use bdk::sled;
 use bitcoin::secp256k1::SecretKey;
 use bitcoin::Address;
@@ -51,7 +53,7 @@ impl<S: TenTenOneStorage, N: Storage> Node<S, N> {
 
     pub fn ldk_wallet(
         &self,
-    ) -> Arc<ldk_node_wallet::Wallet<sled::Tree, EsploraBlockchain, FeeRateEstimator, N>> {
+    ) -> Arc<ldk_node_wallet::Wallet<sled::Tree, WalletBlockchain, FeeRateEstimator, N>> {
         self.wallet.ldk_wallet()
     }
 
@@ -72,6 +74,13 @@ impl<S: TenTenOneStorage, N: Storage> Node<S, N> {
             .context("Failed to get on-chain balance")
     }
 
+    pub fn get_on_chain_reserve_status(&self) -> Result<ReserveStatus> {
+        self.wallet
+            .ldk_wallet()
+            .reserve_status()
+            .context("Failed to get on-chain reserve status")
+    }
+
     pub fn node_key(&self) -> SecretKey {
         self.keys_manager.get_node_secret_key()
     }
@@ -147,6 +156,9 @@ impl<S: TenTenOneStorage, N: Storage> Node<S, N> {
                 preimage: info.preimage.map(|preimage| preimage.0.to_hex()),
                 invoice: info.invoice.clone(),
                 funding_txid: info.funding_txid.map(|txid| txid.to_string()),
+                expiry: info.expiry,
+                payee: info.payee.map(|payee| payee.to_string()),
+                failure_reason: info.failure_reason,
             })
             .collect::<Vec<_>>();
 
@@ -168,6 +180,9 @@ pub struct PaymentDetails {
     pub preimage: Option<String>,
     pub invoice: Option<String>,
     pub funding_txid: Option<String>,
+    pub expiry: Option<OffsetDateTime>,
+    pub payee: Option<String>,
+    pub failure_reason: Option<PaymentFailureReason>,
 }
 
 impl fmt::Display for PaymentDetails {
@@ -181,11 +196,12 @@ impl fmt::Display for PaymentDetails {
         let description = self.description.clone();
         let invoice = self.invoice.clone();
         let funding_txid = self.funding_txid.clone();
+        let failure_reason = self.failure_reason;
 
         write!(
             f,
-            "payment_hash {}, status {}, flow {}, amount_msat {}, fee_msat {}, timestamp {}, description {}, invoice {:?}, funding_txid {:?}",
-            payment_hash, status, flow, amount_msat, fee_msat, timestamp, description, invoice, funding_txid
+            "payment_hash {}, status {}, flow {}, amount_msat {}, fee_msat {}, timestamp {}, description {}, invoice {:?}, funding_txid {:?}, failure_reason {:?}",
+            payment_hash, status, flow, amount_msat, fee_msat, timestamp, description, invoice, funding_txid, failure_reason
         )
     }
 }
@@ -0,0 +1,185 @@
+use crate::node::event::NodeEvent;
+use crate::node::event::NodeEventHandler;
+use crate::node::Node;
+use crate::node::Storage;
+use crate::storage::TenTenOneStorage;
+use anyhow::Result;
+use bdk::blockchain::GetHeight;
+use bdk::blockchain::Progress;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// The kind of on-chain wallet sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncKind {
+    /// Only rescan the wallet if a block has been mined since the last sync.
+    ///
+    /// This is cheap, as it only fetches the height of the chain tip, but it misses unconfirmed
+    /// transactions.
+    TipCheck,
+    /// Rescan the history of all the scripts of the wallet.
+    Full,
+}
+
+/// The progress of an on-chain wallet sync, published as [`NodeEvent::SyncProgress`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncProgress {
+    Started {
+        kind: SyncKind,
+    },
+    /// Progress reported by the chain source while scanning the scripts of the wallet.
+    Scanning {
+        kind: SyncKind,
+        /// Between 0 and 100.
+        percent: f32,
+        message: Option<String>,
+    },
+    Finished {
+        kind: SyncKind,
+        /// The height of the chain tip the wallet was synced to.
+        height: u32,
+        scripts_scanned: usize,
+    },
+    /// The tip check did not find a new block, so the wallet was already up to date.
+    UpToDate {
+        height: u32,
+    },
+    Cancelled {
+        kind: SyncKind,
+    },
+    Failed {
+        kind: SyncKind,
+        error: String,
+    },
+}
+
+/// Cancels a running on-chain wallet sync.
+///
+/// The cancellation takes effect as soon as the chain source reports progress. With esplora this
+/// is only the case before every attempt to sync with one of the backends.
+#[derive(Clone, Default)]
+pub struct SyncCancellation(Arc<AtomicBool>);
+
+impl SyncCancellation {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+impl<S: TenTenOneStorage, N: Storage> Node<S, N> {
+    /// Sync the on-chain wallet, publishing its progress as [`NodeEvent::SyncProgress`].
+    ///
+    /// A cancelled sync is not considered an error.
+    #[instrument(skip(self))]
+    pub fn sync_on_chain_wallet_with_progress(&self, kind: SyncKind) -> Result<()> {
+        let cancellation = self.on_chain_sync_cancellation.clone();
+        cancellation.reset();
+
+        self.publish_sync_progress(SyncProgress::Started { kind });
+
+        match self.run_on_chain_sync(kind, &cancellation) {
+            Ok(progress) => {
+                self.publish_sync_progress(progress);
+                Ok(())
+            }
+            Err(_) if cancellation.is_cancelled() => {
+                tracing::info!(?kind, "On-chain sync cancelled");
+                self.publish_sync_progress(SyncProgress::Cancelled { kind });
+                Ok(())
+            }
+            Err(e) => {
+                self.publish_sync_progress(SyncProgress::Failed {
+                    kind,
+                    error: format!("{e:#}"),
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Cancel the currently running on-chain sync started with
+    /// [`Node::sync_on_chain_wallet_with_progress`], if any.
+    pub fn cancel_on_chain_sync(&self) {
+        self.on_chain_sync_cancellation.cancel();
+    }
+
+    fn run_on_chain_sync(
+        &self,
+        kind: SyncKind,
+        cancellation: &SyncCancellation,
+    ) -> Result<SyncProgress> {
+        let ldk_wallet = self.wallet.ldk_wallet();
+
+        if kind == SyncKind::TipCheck {
+            let tip = ldk_wallet.blockchain.get_height()?;
+            if ldk_wallet.last_sync_height()? == Some(tip) {
+                return Ok(SyncProgress::UpToDate { height: tip });
+            }
+        }
+
+        ldk_wallet.sync_with_progress(PublishingProgress {
+            kind,
+            cancellation: cancellation.clone(),
+            event_handler: self.event_handler.clone(),
+        })?;
+        self.wallet.update_address_cache()?;
+
+        let height = match ldk_wallet.last_sync_height()? {
+            Some(height) => height,
+            None => ldk_wallet.blockchain.get_height()?,
+        };
+
+        Ok(SyncProgress::Finished {
+            kind,
+            height,
+            scripts_scanned: ldk_wallet.script_count()?,
+        })
+    }
+
+    fn publish_sync_progress(&self, progress: SyncProgress) {
+        publish_sync_progress(&self.event_handler, progress);
+    }
+}
+
+/// Publishes the progress reported by the chain source and aborts the sync once it is cancelled.
+struct PublishingProgress {
+    kind: SyncKind,
+    cancellation: SyncCancellation,
+    event_handler: Arc<NodeEventHandler>,
+}
+
+impl Progress for PublishingProgress {
+    fn update(&self, percent: f32, message: Option<String>) -> Result<(), bdk::Error> {
+        if self.cancellation.is_cancelled() {
+            return Err(bdk::Error::Generic("On-chain sync cancelled".to_string()));
+        }
+
+        publish_sync_progress(
+            &self.event_handler,
+            SyncProgress::Scanning {
+                kind: self.kind,
+                percent,
+                message,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+fn publish_sync_progress(event_handler: &NodeEventHandler, progress: SyncProgress) {
+    // Publishing only fails if nobody is interested in the progress.
+    if let Err(e) = event_handler.publish(NodeEvent::SyncProgress(progress)) {
+        tracing::trace!("Did not publish on-chain sync progress: {e:#}");
+    }
+}
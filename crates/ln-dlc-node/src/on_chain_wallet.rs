@@ -5,10 +5,15 @@ use bdk::bitcoin::secp256k1::Secp256k1;
 use bdk::sled;
 use bdk::wallet::wallet_name_from_descriptor;
 use bdk::KeychainKind;
+use bitcoin::OutPoint;
 use std::path::Path;
+use std::str::FromStr;
 
 pub struct OnChainWallet {
     pub inner: bdk::Wallet<sled::Tree>,
+    pub frozen_utxos: FrozenUtxos,
+    /// The name of the wallet, derived from its descriptors.
+    pub wallet_name: String,
 }
 
 impl OnChainWallet {
@@ -37,7 +42,8 @@ impl OnChainWallet {
 
         // Create a database (using default sled type) to store wallet data
         let db = bdk::sled::open(data_dir.join("wallet"))?;
-        let db = db.open_tree(wallet_name)?;
+        let frozen_utxos = FrozenUtxos::new(db.open_tree(format!("{wallet_name}_frozen_utxos"))?);
+        let db = db.open_tree(&wallet_name)?;
 
         let bdk_wallet = bdk::Wallet::new(
             bdk::template::Bip84(ext_priv_key, KeychainKind::External),
@@ -46,6 +52,52 @@ impl OnChainWallet {
             db,
         )?;
 
-        Ok(OnChainWallet { inner: bdk_wallet })
+        Ok(OnChainWallet {
+            inner: bdk_wallet,
+            frozen_utxos,
+            wallet_name,
+        })
+    }
+}
+
+/// The UTXOs which must not be spent by the wallet, unless they are explicitly unfrozen again.
+///
+/// The outpoints are persisted next to the wallet data, so that they stay frozen across restarts.
+#[derive(Clone)]
+pub struct FrozenUtxos {
+    tree: sled::Tree,
+}
+
+impl FrozenUtxos {
+    pub fn new(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+
+    pub fn freeze(&self, outpoint: OutPoint) -> Result<()> {
+        self.tree
+            .insert(outpoint.to_string(), vec![])
+            .with_context(|| format!("Failed to freeze UTXO {outpoint}"))?;
+
+        Ok(())
+    }
+
+    pub fn unfreeze(&self, outpoint: OutPoint) -> Result<()> {
+        self.tree
+            .remove(outpoint.to_string())
+            .with_context(|| format!("Failed to unfreeze UTXO {outpoint}"))?;
+
+        Ok(())
+    }
+
+    pub fn all(&self) -> Result<Vec<OutPoint>> {
+        self.tree
+            .iter()
+            .keys()
+            .map(|key| {
+                let key = key.context("Failed to read frozen UTXO")?;
+                let outpoint = std::str::from_utf8(&key).context("Invalid frozen UTXO key")?;
+                OutPoint::from_str(outpoint).context("Invalid frozen UTXO")
+            })
+            .collect()
     }
 }
@@ -85,10 +85,25 @@ impl Bip39Seed {
         }
     }
 
+    /// The key with which the node's storage is encrypted at rest.
+    pub fn storage_encryption_key(&self) -> StorageEncryptionKey {
+        let mut key = [0u8; 32];
+
+        Hkdf::<Sha256>::new(None, &self.seed())
+            .expand(b"STORAGE_ENCRYPTION_KEY", &mut key)
+            .expect("array is of correct length");
+        key
+    }
+
     pub fn get_seed_phrase(&self) -> Vec<String> {
         self.mnemonic.word_iter().map(|word| word.into()).collect()
     }
 
+    /// The entropy from which the mnemonic is generated, see [`Bip39Seed::try_from`].
+    pub fn to_entropy(&self) -> Vec<u8> {
+        self.mnemonic.to_entropy()
+    }
+
     // Read the entropy used to generate Mnemonic from disk
     fn read_from(path: &Path) -> Result<Self> {
         let bytes = std::fs::read(path)?;
@@ -136,6 +151,8 @@ impl From<Mnemonic> for Bip39Seed {
 
 pub type LightningSeed = [u8; 32];
 
+pub type StorageEncryptionKey = [u8; 32];
+
 #[cfg(test)]
 mod tests {
     use crate::seed::Bip39Seed;
@@ -10,12 +10,15 @@ use crate::node::LnDlcNodeSettings;
 use crate::node::Node;
 use crate::node::NodeInfo;
 use crate::node::OracleInfo;
+use crate::node::OracleProtocol;
 use crate::node::RunningNode;
+use crate::node::ZeroConfTrustPolicy;
 use crate::scorer;
 use crate::seed::Bip39Seed;
 use crate::storage::TenTenOneInMemoryStorage;
 use crate::util;
 use crate::AppEventHandler;
+use crate::ChainSourceConfig;
 use crate::CoordinatorEventHandler;
 use crate::EventHandlerTrait;
 use crate::EventSender;
@@ -98,6 +101,7 @@ impl Node<TenTenOneInMemoryStorage, InMemoryStore> {
             OracleInfo {
                 endpoint: ORACLE_ORIGIN.to_string(),
                 public_key: XOnlyPublicKey::from_str(ORACLE_PUBKEY)?,
+                protocol: OracleProtocol::P2pd,
             },
             Arc::new(InMemoryStore::default()),
             ln_dlc_node_settings_app(),
@@ -132,6 +136,7 @@ impl Node<TenTenOneInMemoryStorage, InMemoryStore> {
             OracleInfo {
                 endpoint: ORACLE_ORIGIN.to_string(),
                 public_key: XOnlyPublicKey::from_str(ORACLE_PUBKEY)?,
+                protocol: OracleProtocol::P2pd,
             },
             storage,
             settings,
@@ -182,7 +187,9 @@ impl Node<TenTenOneInMemoryStorage, InMemoryStore> {
             address,
             address,
             util::into_socket_addresses(address),
-            esplora_origin,
+            ChainSourceConfig::Esplora {
+                urls: vec![esplora_origin],
+            },
             seed,
             ephemeral_randomness,
             settings,
@@ -208,6 +215,8 @@ impl Node<TenTenOneInMemoryStorage, InMemoryStore> {
                             );
                         }
                         Ok(NodeEvent::Connected { .. }) => {} // ignored
+                        Ok(NodeEvent::SyncProgress(_)) => {}  // ignored
+                        Ok(NodeEvent::ChannelOpenStatus { .. }) => {} // ignored
                         Err(_) => {
                             tracing::error!(
                                 "Failed to receive message from node event handler channel."
@@ -421,6 +430,10 @@ fn ln_dlc_node_settings_coordinator() -> LnDlcNodeSettings {
         bdk_client_stop_gap: 20,
         bdk_client_concurrency: 4,
         gossip_source_config: GossipSourceConfig::P2pNetwork,
+        mempool_space_url: None,
+        zero_conf_trust_policy: ZeroConfTrustPolicy::TrustNobody,
+        watchtower_url: None,
+        async_payment_hold_timeout: Duration::from_secs(600),
     }
 }
 
@@ -436,6 +449,11 @@ fn ln_dlc_node_settings_app() -> LnDlcNodeSettings {
         bdk_client_stop_gap: 20,
         bdk_client_concurrency: 4,
         gossip_source_config: GossipSourceConfig::P2pNetwork,
+        mempool_space_url: None,
+        // The test apps only open channels with the test coordinator.
+        zero_conf_trust_policy: ZeroConfTrustPolicy::TrustEverybody,
+        watchtower_url: None,
+        async_payment_hold_timeout: Duration::from_secs(600),
     }
 }
 
@@ -1,6 +1,8 @@
 use lightning::ln::msgs::SocketAddress;
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use tokio::task::JoinHandle;
+use tracing::Span;
 
 #[inline]
 pub fn hex_str(value: &[u8]) -> String {
@@ -29,3 +31,14 @@ pub fn build_socket_address(ip: IpAddr, port: u16) -> SocketAddress {
 pub fn into_socket_addresses(address: SocketAddr) -> Vec<SocketAddress> {
     vec![build_socket_address(address.ip(), address.port())]
 }
+
+/// Like [`tokio::task::spawn_blocking`], but runs `f` in the current span, so that what happens in
+/// the blocking task is traced as part of the caller.
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
@@ -0,0 +1,397 @@
+use crate::dlc_custom_signer::CustomSigner;
+use crate::fee_rate_estimator::FeeRateEstimator;
+use crate::ln_dlc_wallet::LnDlcWallet;
+use crate::node::LnDlcNodeSettings;
+use crate::node::Storage;
+use crate::storage::TenTenOneStorage;
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::consensus::encode::serialize;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::hashes::sha256;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::PackedLockTime;
+use bitcoin::Script;
+use bitcoin::Sequence;
+use bitcoin::Transaction;
+use bitcoin::TxIn;
+use bitcoin::TxOut;
+use bitcoin::Txid;
+use bitcoin::Witness;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::Key;
+use chacha20poly1305::KeyInit;
+use chacha20poly1305::Nonce;
+use lightning::chain::chaininterface::ConfirmationTarget;
+use lightning::chain::chaininterface::FeeEstimator;
+use lightning::chain::chainmonitor::MonitorUpdateId;
+use lightning::chain::chainmonitor::Persist;
+use lightning::chain::channelmonitor::ChannelMonitor;
+use lightning::chain::channelmonitor::ChannelMonitorUpdate;
+use lightning::chain::transaction::OutPoint;
+use lightning::chain::ChannelMonitorUpdateStatus;
+use lightning::ln::chan_utils::CommitmentTransaction;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long we wait for the watchtower to answer a request.
+const WATCHTOWER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The weight of the witness spending a revoked `to_local` output: the number of witness items,
+/// the signature, the selector for the revocation path and the witness script.
+const REVOKED_OUTPUT_WITNESS_WEIGHT: u64 = 1 + 1 + 73 + 1 + 1 + 1 + 77;
+
+/// A client for a watchtower, which punishes our counterparty on our behalf if they broadcast a
+/// revoked commitment transaction while we are offline.
+///
+/// The tower only learns about a justice transaction once the commitment transaction it spends
+/// shows up on-chain, as the justice transaction is encrypted with the commitment txid.
+pub struct Watchtower {
+    agent: ureq::Agent,
+    /// The tower we are registered with, if any.
+    url: RwLock<Option<String>>,
+}
+
+/// An encrypted justice transaction.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Appointment {
+    /// The first half of the txid of the revoked commitment transaction, which tells the tower
+    /// which transaction to look out for.
+    pub locator: String,
+    /// The justice transaction, encrypted with the hash of the revoked commitment txid.
+    pub encrypted_blob: String,
+}
+
+#[derive(Serialize)]
+struct RegisterRequest {
+    node_id: String,
+}
+
+impl Watchtower {
+    pub fn new() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(WATCHTOWER_TIMEOUT)
+            .build();
+
+        Self {
+            agent,
+            url: RwLock::new(None),
+        }
+    }
+
+    pub fn url(&self) -> Option<String> {
+        self.url.read().clone()
+    }
+
+    /// Registers our node with the tower at `url` and sends all further appointments to it.
+    pub fn register(&self, url: &str, node_id: PublicKey) -> Result<()> {
+        let url = url.trim_end_matches('/').to_string();
+
+        self.agent
+            .post(&format!("{url}/register"))
+            .send_json(RegisterRequest {
+                node_id: node_id.to_string(),
+            })
+            .with_context(|| format!("Failed to register with watchtower at {url}"))?;
+
+        tracing::info!(%url, "Registered with watchtower");
+
+        *self.url.write() = Some(url);
+
+        Ok(())
+    }
+
+    /// Uploads the appointment to the tower in the background.
+    fn send_appointment(&self, appointment: Appointment) {
+        let url = match self.url() {
+            Some(url) => url,
+            None => return,
+        };
+
+        let agent = self.agent.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = agent
+                .post(&format!("{url}/appointment"))
+                .send_json(&appointment)
+            {
+                tracing::error!(
+                    locator = appointment.locator,
+                    "Failed to send appointment to watchtower: {e:#}"
+                );
+            }
+        });
+    }
+}
+
+impl Default for Watchtower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Registers with the watchtower configured in the node settings, if any.
+pub(crate) async fn register_configured_watchtower(
+    settings: Arc<tokio::sync::RwLock<LnDlcNodeSettings>>,
+    watchtower: Arc<Watchtower>,
+    node_id: PublicKey,
+) {
+    let url = match settings.read().await.watchtower_url.clone() {
+        Some(url) => url,
+        None => return,
+    };
+
+    let result = tokio::task::spawn_blocking(move || watchtower.register(&url, node_id)).await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::error!("{e:#}"),
+        Err(e) => tracing::error!("Failed to register with watchtower: {e:#}"),
+    }
+}
+
+/// A justice transaction which can only be signed once our counterparty has revoked the
+/// commitment transaction it spends.
+struct PendingJusticeTx {
+    commitment_txid: Txid,
+    commitment_number: u64,
+    /// The value of the revoked output.
+    value: u64,
+    tx: Transaction,
+}
+
+/// Persists channel monitors to the underlying storage and hands the justice transactions for
+/// every revoked counterparty commitment transaction to the [`Watchtower`].
+pub struct WatchtowerPersister<S, N> {
+    storage: Arc<S>,
+    watchtower: Arc<Watchtower>,
+    wallet: Arc<LnDlcWallet<S, N>>,
+    fee_rate_estimator: Arc<FeeRateEstimator>,
+    /// Justice transactions for commitment transactions which have not been revoked yet.
+    ///
+    /// These are only kept in memory, so after a restart we cannot upload the justice transaction
+    /// for the latest commitment transaction of each channel.
+    pending: Mutex<HashMap<OutPoint, Vec<PendingJusticeTx>>>,
+}
+
+impl<S: TenTenOneStorage, N: Storage> WatchtowerPersister<S, N> {
+    pub(crate) fn new(
+        storage: Arc<S>,
+        watchtower: Arc<Watchtower>,
+        wallet: Arc<LnDlcWallet<S, N>>,
+        fee_rate_estimator: Arc<FeeRateEstimator>,
+    ) -> Self {
+        Self {
+            storage,
+            watchtower,
+            wallet,
+            fee_rate_estimator,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn watch(
+        &self,
+        funding_txo: OutPoint,
+        monitor: &ChannelMonitor<CustomSigner>,
+        commitment_txs: Vec<CommitmentTransaction>,
+    ) {
+        let mut pending = self.pending.lock();
+        let pending = pending.entry(funding_txo).or_default();
+
+        for commitment_tx in commitment_txs {
+            match self.build_justice_tx(&commitment_tx) {
+                Ok(Some(justice_tx)) => pending.push(justice_tx),
+                Ok(None) => {}
+                Err(e) => tracing::error!(
+                    commitment_number = commitment_tx.commitment_number(),
+                    "Failed to build justice transaction: {e:#}"
+                ),
+            }
+        }
+
+        // Signing only succeeds once we know the revocation secret of the commitment transaction.
+        pending.retain(|justice_tx| {
+            let signed_tx = match monitor.sign_to_local_justice_tx(
+                justice_tx.tx.clone(),
+                0,
+                justice_tx.value,
+                justice_tx.commitment_number,
+            ) {
+                Ok(signed_tx) => signed_tx,
+                Err(()) => return true,
+            };
+
+            match encrypt_justice_tx(&justice_tx.commitment_txid, &signed_tx) {
+                Ok(appointment) => self.watchtower.send_appointment(appointment),
+                Err(e) => tracing::error!("{e:#}"),
+            }
+
+            false
+        });
+    }
+
+    /// Builds an unsigned transaction sweeping the `to_local` output of our counterparty's
+    /// commitment transaction to our on-chain wallet.
+    ///
+    /// Returns `None` if the commitment transaction has no such output or it is not worth
+    /// sweeping.
+    fn build_justice_tx(
+        &self,
+        commitment_tx: &CommitmentTransaction,
+    ) -> Result<Option<PendingJusticeTx>> {
+        let trusted_tx = commitment_tx.trust();
+        let output_index = match trusted_tx.revokeable_output_index() {
+            Some(output_index) => output_index,
+            None => return Ok(None),
+        };
+
+        let built_tx = trusted_tx.built_transaction();
+        let value = built_tx.transaction.output[output_index].value;
+
+        let destination = self
+            .wallet
+            .ldk_wallet()
+            .get_last_unused_address()?
+            .script_pubkey();
+
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: bitcoin::OutPoint::new(built_tx.txid, output_index as u32),
+                script_sig: Script::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value,
+                script_pubkey: destination,
+            }],
+        };
+
+        let fee_rate =
+            self.fee_rate_estimator
+                .get_est_sat_per_1000_weight(ConfirmationTarget::HighPriority) as u64;
+        let weight = tx.weight() as u64 + REVOKED_OUTPUT_WITNESS_WEIGHT;
+        let fee = fee_rate * weight / 1000;
+
+        let dust_limit = tx.output[0].script_pubkey.dust_value().to_sat();
+        tx.output[0].value = match value.checked_sub(fee) {
+            Some(amount) if amount >= dust_limit => amount,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(PendingJusticeTx {
+            commitment_txid: built_tx.txid,
+            commitment_number: commitment_tx.commitment_number(),
+            value,
+            tx,
+        }))
+    }
+}
+
+impl<S: TenTenOneStorage, N: Storage> Persist<CustomSigner> for WatchtowerPersister<S, N> {
+    fn persist_new_channel(
+        &self,
+        funding_txo: OutPoint,
+        monitor: &ChannelMonitor<CustomSigner>,
+        update_id: MonitorUpdateId,
+    ) -> ChannelMonitorUpdateStatus {
+        let status = self
+            .storage
+            .persist_new_channel(funding_txo, monitor, update_id);
+
+        if self.watchtower.url().is_some() {
+            if let Some(commitment_tx) = monitor.initial_counterparty_commitment_tx() {
+                self.watch(funding_txo, monitor, vec![commitment_tx]);
+            }
+        }
+
+        status
+    }
+
+    fn update_persisted_channel(
+        &self,
+        funding_txo: OutPoint,
+        update: Option<&ChannelMonitorUpdate>,
+        monitor: &ChannelMonitor<CustomSigner>,
+        update_id: MonitorUpdateId,
+    ) -> ChannelMonitorUpdateStatus {
+        let status = self
+            .storage
+            .update_persisted_channel(funding_txo, update, monitor, update_id);
+
+        if let Some(update) = update {
+            if self.watchtower.url().is_some() {
+                let commitment_txs = monitor.counterparty_commitment_txs_from_update(update);
+                self.watch(funding_txo, monitor, commitment_txs);
+            }
+        }
+
+        status
+    }
+}
+
+fn encrypt_justice_tx(commitment_txid: &Txid, justice_tx: &Transaction) -> Result<Appointment> {
+    let key = sha256::Hash::hash(commitment_txid.as_ref());
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+
+    // Every key is only used once, so we can get away with a fixed nonce.
+    let encrypted_blob = cipher
+        .encrypt(&Nonce::default(), serialize(justice_tx).as_slice())
+        .map_err(|_| anyhow!("Failed to encrypt justice transaction"))?;
+
+    Ok(Appointment {
+        locator: commitment_txid.as_ref()[..16].to_hex(),
+        encrypted_blob: encrypted_blob.to_hex(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::consensus::encode::deserialize;
+    use std::str::FromStr;
+
+    #[test]
+    fn tower_can_decrypt_justice_tx_once_it_knows_the_commitment_txid() {
+        let commitment_txid =
+            Txid::from_str("3c7d2a1d2c58e6c5bfbd0c5bd6f0a6e3b4a3e5c1f9f4d2e6a7b8c9d0e1f2a3b4")
+                .unwrap();
+        let justice_tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: bitcoin::OutPoint::new(commitment_txid, 0),
+                script_sig: Script::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 10_000,
+                script_pubkey: Script::new(),
+            }],
+        };
+
+        let appointment = encrypt_justice_tx(&commitment_txid, &justice_tx).unwrap();
+
+        assert_eq!(appointment.locator, commitment_txid.as_ref()[..16].to_hex());
+
+        let key = sha256::Hash::hash(commitment_txid.as_ref());
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+        let decrypted = cipher
+            .decrypt(
+                &Nonce::default(),
+                hex::decode(appointment.encrypted_blob).unwrap().as_slice(),
+            )
+            .unwrap();
+
+        assert_eq!(deserialize::<Transaction>(&decrypted).unwrap(), justice_tx);
+    }
+}
@@ -32,6 +32,7 @@ use lightning::util::ser::Readable;
 use lightning::util::ser::Writeable;
 use secp256k1_zkp::PublicKey;
 use secp256k1_zkp::SecretKey;
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::io::Cursor;
 use std::io::Read;
@@ -39,6 +40,8 @@ use std::io::Seek;
 use std::io::SeekFrom;
 use std::string::ToString;
 
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod sled;
 
 // Kinds.
@@ -266,6 +269,38 @@ impl<K: DlcStoreProvider> DlcStorageProvider<K> {
 
         Ok(contracts)
     }
+
+    /// Removes contracts which have failed or have been rejected and are not referenced by any
+    /// channel. Such contracts are never used again, but would otherwise be kept forever.
+    ///
+    /// Returns the number of removed contracts.
+    pub fn remove_orphaned_contracts(&self) -> Result<usize, Error> {
+        // The contracts have to be read before the channels. Otherwise a contract stored together
+        // with a new channel in the meantime could be considered orphaned.
+        let contracts = dlc_manager::Storage::get_contracts(self)?;
+
+        let referenced = dlc_manager::Storage::get_channels(self)?
+            .iter()
+            .filter_map(|channel| channel.get_contract_id())
+            .collect::<HashSet<_>>();
+
+        let mut removed = 0;
+        for contract in contracts {
+            let is_final = matches!(
+                contract,
+                Contract::FailedAccept(_) | Contract::FailedSign(_) | Contract::Rejected(_)
+            );
+            let is_referenced = referenced.contains(&contract.get_id())
+                || referenced.contains(&contract.get_temporary_id());
+
+            if is_final && !is_referenced {
+                dlc_manager::Storage::delete_contract(self, &contract.get_id())?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
 }
 
 impl<K: DlcStoreProvider> dlc_manager::Storage for DlcStorageProvider<K> {
@@ -1060,6 +1095,30 @@ mod tests {
         assert_eq!(6, contracts.len());
     }
 
+    #[test]
+    fn remove_orphaned_contracts_removes_only_rejected_contract() {
+        let mut storage = DlcStorageProvider::new(InMemoryDlcStoreProvider::new());
+        insert_offered_signed_and_confirmed(&mut storage);
+
+        let serialized = include_bytes!("../test_files/Offered");
+        let rejected_contract = Contract::Rejected(deserialize_object(serialized));
+        storage
+            .update_contract(&rejected_contract)
+            .expect("Error updating contract");
+
+        let removed = storage
+            .remove_orphaned_contracts()
+            .expect("Error removing orphaned contracts");
+        assert_eq!(1, removed);
+
+        let contracts = storage.get_contracts().expect("Error retrieving contracts");
+        assert_eq!(5, contracts.len());
+        assert!(storage
+            .get_contract(&rejected_contract.get_id())
+            .expect("Error retrieving contract")
+            .is_none());
+    }
+
     #[test]
     fn get_offered_channels_only_offered() {
         let mut storage = DlcStorageProvider::new(InMemoryDlcStoreProvider::new());
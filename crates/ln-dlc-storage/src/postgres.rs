@@ -0,0 +1,117 @@
+use crate::DlcStoreProvider;
+use crate::KeyValue;
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::PgConnection;
+
+diesel::table! {
+    dlc_store (kind, key) {
+        kind -> Int2,
+        key -> Bytea,
+        value -> Bytea,
+    }
+}
+
+/// Keeps the dlc-manager state in the `dlc_store` table of a Postgres database.
+///
+/// The table has to be created by the migrations of the application using this store.
+///
+/// Every call of [`DlcStoreProvider`] uses its own connection from the pool. To change the DLC
+/// state in the same transaction as other data, e.g. a position, use [`read`], [`write`] and
+/// [`delete`] with the connection of that transaction instead.
+#[derive(Clone)]
+pub struct PostgresDlcStoreProvider {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl PostgresDlcStoreProvider {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        PostgresDlcStoreProvider { pool }
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        let mut conn = self.pool.get()?;
+        let count: i64 = dlc_store::table.count().get_result(&mut conn)?;
+
+        Ok(count == 0)
+    }
+
+    /// Writes all key value pairs in a single transaction, e.g. to import the contents of another
+    /// store.
+    pub fn import(&self, entries: Vec<(u8, KeyValue)>) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        conn.transaction(|conn| {
+            for (kind, KeyValue { key, value }) in entries {
+                write(conn, kind, key, value)?;
+            }
+
+            anyhow::Ok(())
+        })
+    }
+}
+
+impl DlcStoreProvider for PostgresDlcStoreProvider {
+    fn read(&self, kind: u8, key: Option<Vec<u8>>) -> Result<Vec<KeyValue>> {
+        let mut conn = self.pool.get()?;
+        read(&mut conn, kind, key)
+    }
+
+    fn write(&self, kind: u8, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        write(&mut conn, kind, key, value)
+    }
+
+    fn delete(&self, kind: u8, key: Option<Vec<u8>>) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        delete(&mut conn, kind, key)
+    }
+}
+
+/// Read the entries of `kind`, or only the one with `key`, using the given connection.
+pub fn read(conn: &mut PgConnection, kind: u8, key: Option<Vec<u8>>) -> Result<Vec<KeyValue>> {
+    let mut query = dlc_store::table
+        .filter(dlc_store::kind.eq(i16::from(kind)))
+        .select((dlc_store::key, dlc_store::value))
+        .into_boxed();
+
+    if let Some(key) = key {
+        query = query.filter(dlc_store::key.eq(key));
+    }
+
+    let result = query
+        .load::<(Vec<u8>, Vec<u8>)>(conn)?
+        .into_iter()
+        .map(|(key, value)| KeyValue { key, value })
+        .collect();
+
+    Ok(result)
+}
+
+/// Insert or overwrite the entry of `kind` with `key`, using the given connection.
+pub fn write(conn: &mut PgConnection, kind: u8, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+    diesel::insert_into(dlc_store::table)
+        .values((
+            dlc_store::kind.eq(i16::from(kind)),
+            dlc_store::key.eq(key),
+            dlc_store::value.eq(&value),
+        ))
+        .on_conflict((dlc_store::kind, dlc_store::key))
+        .do_update()
+        .set(dlc_store::value.eq(&value))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Delete the entries of `kind`, or only the one with `key`, using the given connection.
+pub fn delete(conn: &mut PgConnection, kind: u8, key: Option<Vec<u8>>) -> Result<()> {
+    let query = dlc_store::table.filter(dlc_store::kind.eq(i16::from(kind)));
+    match key {
+        Some(key) => diesel::delete(query.filter(dlc_store::key.eq(key))).execute(conn)?,
+        None => diesel::delete(query).execute(conn)?,
+    };
+
+    Ok(())
+}
@@ -1,12 +1,33 @@
+use crate::DlcStorageProvider;
 use crate::DlcStoreProvider;
 use crate::KeyValue;
+use crate::ACTION;
+use crate::ADDRESS;
+use crate::CHAIN_MONITOR;
+use crate::CHANNEL;
+use crate::CONTRACT;
+use crate::KEY_PAIR;
+use crate::SUB_CHANNEL;
+use crate::UTXO;
+use anyhow::anyhow;
+use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
 use parking_lot::RwLock;
 use sled::Db;
+use sled::Tree;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// The version of the layout of the sled database.
+///
+/// - 0: Every kind is kept in a tree named after the kind.
+/// - 1: Every kind is kept in a tree named after its namespace, e.g. `dlc/contracts`.
+const SCHEMA_VERSION: u32 = 1;
+
+/// The key of the schema version in the default tree.
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
 #[derive(Clone)]
 pub struct SledStorageProvider {
     db: Db,
@@ -18,11 +39,49 @@ pub struct SledStorageExport {
     pub value: Vec<u8>,
 }
 
+#[derive(Debug)]
+pub struct SledStorageStats {
+    pub size_on_disk: u64,
+    pub trees: Vec<TreeStats>,
+}
+
+#[derive(Debug)]
+pub struct TreeStats {
+    pub name: String,
+    pub entries: usize,
+    /// The size of all keys and values in the tree.
+    pub bytes: u64,
+}
+
+#[derive(Debug)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub dry_run: bool,
+    pub trees: Vec<MigratedTree>,
+}
+
+#[derive(Debug)]
+pub struct MigratedTree {
+    pub kind: u8,
+    /// The name of the tree the entries are migrated to.
+    pub target: String,
+    pub entries: usize,
+}
+
 impl SledStorageProvider {
     pub fn new(path: &str) -> Self {
-        SledStorageProvider {
-            db: sled::open(path).expect("valid path"),
-        }
+        let db = sled::open(path).expect("valid path");
+        migrate(&db, false).expect("sled database to be migrated");
+
+        SledStorageProvider { db }
+    }
+
+    /// Reports what would be migrated when opening the sled database at `path`, without migrating
+    /// it.
+    pub fn dry_run_migration(path: &str) -> Result<MigrationReport> {
+        let db = sled::open(path)?;
+        migrate(&db, true)
     }
 
     /// Exports all key value pairs from the sled storage
@@ -32,27 +91,219 @@ impl SledStorageProvider {
             if collection_type != b"tree" {
                 continue;
             }
+
+            let kind = match kind_of_tree(&collection_name) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
             for mut kv in collection_iter {
                 let value = kv.pop().expect("failed to get value from tree export");
                 let key = kv.pop().expect("failed to get key from tree export");
-                let kind = collection_name
-                    .first()
-                    .expect("failed to get kind from tree export");
 
-                export.push(SledStorageExport {
-                    kind: *kind,
-                    key,
-                    value,
-                });
+                export.push(SledStorageExport { kind, key, value });
             }
         }
         export
     }
+
+    /// Removes orphaned contract data and flushes the database.
+    ///
+    /// Sled reclaims the space of removed entries in the background, so this can be run while the
+    /// node is running. Returns the number of removed contracts.
+    pub fn compact(&self) -> Result<usize> {
+        let removed = DlcStorageProvider::new(self.clone())
+            .remove_orphaned_contracts()
+            .map_err(|e| anyhow!("{e}"))?;
+
+        self.db.flush()?;
+
+        tracing::info!(removed, "Compacted DLC storage");
+
+        Ok(removed)
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<SledStorageStats> {
+        let mut trees = vec![];
+        for name in self.db.tree_names() {
+            let tree = self.db.open_tree(&name)?;
+
+            let mut bytes = 0;
+            for entry in tree.iter() {
+                let (key, value) = entry?;
+                bytes += (key.len() + value.len()) as u64;
+            }
+
+            trees.push(TreeStats {
+                name: String::from_utf8_lossy(&name).to_string(),
+                entries: tree.len(),
+                bytes,
+            });
+        }
+
+        Ok(SledStorageStats {
+            size_on_disk: self.db.size_on_disk()?,
+            trees,
+        })
+    }
+
+    fn tree(&self, kind: u8) -> Result<Tree> {
+        Ok(self.db.open_tree(tree_name(kind))?)
+    }
+}
+
+/// The name of the tree in which the given kind is kept.
+fn tree_name(kind: u8) -> String {
+    let namespace = match kind {
+        CONTRACT => "contracts",
+        CHANNEL => "channels",
+        CHAIN_MONITOR => "chain_monitor",
+        UTXO => "utxos",
+        KEY_PAIR => "key_pairs",
+        SUB_CHANNEL => "sub_channels",
+        ADDRESS => "addresses",
+        ACTION => "actions",
+        kind => return format!("dlc/{kind}"),
+    };
+
+    format!("dlc/{namespace}")
+}
+
+/// The kind kept in the tree with the given name, if any.
+fn kind_of_tree(name: &[u8]) -> Option<u8> {
+    (0..=u8::MAX).find(|kind| tree_name(*kind).as_bytes() == name)
+}
+
+/// Migrate the sled database to the latest [`SCHEMA_VERSION`].
+///
+/// In a dry run, the entries which would be migrated are counted, but nothing is written.
+fn migrate(db: &Db, dry_run: bool) -> Result<MigrationReport> {
+    let version = match db.get(SCHEMA_VERSION_KEY)? {
+        Some(version) => u32::from_be_bytes(
+            version
+                .as_ref()
+                .try_into()
+                .context("Invalid schema version")?,
+        ),
+        None => 0,
+    };
+
+    if version > SCHEMA_VERSION {
+        bail!("Unsupported schema version {version} of sled database");
+    }
+
+    let mut report = MigrationReport {
+        from_version: version,
+        to_version: SCHEMA_VERSION,
+        dry_run,
+        trees: vec![],
+    };
+
+    if version < 1 {
+        for name in legacy_tree_names(db) {
+            let legacy = db.open_tree(&name)?;
+            let target = tree_name(name[0]);
+
+            tracing::info!(
+                kind = name[0],
+                %target,
+                entries = legacy.len(),
+                dry_run,
+                "Migrating sled tree"
+            );
+
+            if !dry_run {
+                copy_tree(&legacy, &db.open_tree(&target)?)?;
+                verify_copy(&legacy, &db.open_tree(&target)?)?;
+            }
+
+            report.trees.push(MigratedTree {
+                kind: name[0],
+                target,
+                entries: legacy.len(),
+            });
+        }
+
+        if dry_run {
+            return Ok(report);
+        }
+
+        db.insert(SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_be_bytes())?;
+        db.flush()?;
+
+        tracing::info!(
+            from = version,
+            to = SCHEMA_VERSION,
+            "Migrated sled database"
+        );
+    }
+
+    if dry_run {
+        return Ok(report);
+    }
+
+    // The legacy trees are only dropped once the migration has been persisted. If we crashed in
+    // between, they are dropped on the next start.
+    for name in legacy_tree_names(db) {
+        db.drop_tree(name)?;
+    }
+
+    Ok(report)
+}
+
+/// The number of entries after which the progress of copying a tree is logged.
+const PROGRESS_INTERVAL: usize = 1_000;
+
+fn copy_tree(from: &Tree, to: &Tree) -> Result<()> {
+    for (copied, entry) in from.iter().enumerate() {
+        let (key, value) = entry?;
+        to.insert(key, value)?;
+
+        if (copied + 1) % PROGRESS_INTERVAL == 0 {
+            tracing::info!(copied = copied + 1, total = from.len(), "Copying sled tree");
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that every entry of `from` has been copied to `to`.
+fn verify_copy(from: &Tree, to: &Tree) -> Result<()> {
+    if to.len() < from.len() {
+        bail!(
+            "Migrated tree has {} entries, but the legacy tree has {}",
+            to.len(),
+            from.len()
+        );
+    }
+
+    for entry in from.iter() {
+        let (key, value) = entry?;
+        if to.get(&key)?.as_ref() != Some(&value) {
+            bail!("Entry {} has not been migrated", hex::encode(key));
+        }
+    }
+
+    Ok(())
+}
+
+/// The trees of schema version 0, which are named after the kind.
+fn legacy_tree_names(db: &Db) -> Vec<sled::IVec> {
+    db.tree_names()
+        .into_iter()
+        .filter(|name| name.len() == 1)
+        .collect()
 }
 
 impl DlcStoreProvider for SledStorageProvider {
     fn read(&self, kind: u8, key: Option<Vec<u8>>) -> Result<Vec<KeyValue>> {
-        let tree = self.db.open_tree([kind])?;
+        let tree = self.tree(kind)?;
 
         if let Some(key) = key {
             let result = match tree.get(key.clone())? {
@@ -82,13 +333,13 @@ impl DlcStoreProvider for SledStorageProvider {
     }
 
     fn write(&self, kind: u8, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
-        self.db.open_tree([kind])?.insert(key, value)?;
+        self.tree(kind)?.insert(key, value)?;
         self.db.flush()?;
         Ok(())
     }
 
     fn delete(&self, kind: u8, key: Option<Vec<u8>>) -> Result<()> {
-        let tree = self.db.open_tree([kind])?;
+        let tree = self.tree(kind)?;
 
         if let Some(key) = key {
             tree.remove(key)?;
@@ -198,6 +449,35 @@ mod tests {
         };
     }
 
+    #[test]
+    fn legacy_trees_are_migrated() {
+        let path = "test_files/sleddb/legacy_trees_are_migrated";
+        {
+            let db = sled::open(path).unwrap();
+            let legacy = db.open_tree([1]).unwrap();
+            legacy.insert(b"key", b"test").unwrap();
+            legacy.insert(b"key2", b"test2").unwrap();
+            db.flush().unwrap();
+        }
+
+        let report = SledStorageProvider::dry_run_migration(path).unwrap();
+        assert_eq!(0, report.from_version);
+        assert_eq!(1, report.trees.len());
+        assert_eq!(2, report.trees[0].entries);
+
+        {
+            let storage = SledStorageProvider::new(path);
+            assert_eq!(2, storage.read(1, None).unwrap().len());
+            assert!(!storage.db.tree_names().iter().any(|name| name.len() == 1));
+        }
+
+        let report = SledStorageProvider::dry_run_migration(path).unwrap();
+        assert_eq!(1, report.from_version);
+        assert!(report.trees.is_empty());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
     sled_test!(write_key_and_value, |storage: SledStorageProvider| {
         let result = storage.write(
             1,
@@ -14,7 +14,7 @@ async fn main() -> Result<Never> {
     let secret_key =
         SecretKey::from_slice(&b"bring sally up, bring sally down"[..]).expect("valid secret key");
 
-    let url = "ws://localhost:8000/api/orderbook/websocket".to_string();
+    let url = "ws://localhost:8000/api/v1/orderbook/websocket".to_string();
 
     let authenticate = move |msg| {
         let signature = secret_key.sign_ecdsa(msg);
@@ -26,7 +26,7 @@ async fn main() -> Result<Never> {
 
     loop {
         let (_, mut stream) =
-            orderbook_client::subscribe_with_authentication(url.clone(), &authenticate, None)
+            orderbook_client::subscribe_with_authentication(url.clone(), &authenticate, None, None)
                 .await?;
 
         loop {
@@ -3,6 +3,7 @@ use anyhow::Context;
 use anyhow::Result;
 use async_stream::stream;
 use commons::create_sign_message;
+use commons::Device;
 use commons::OrderbookRequest;
 use commons::Signature;
 use commons::AUTH_SIGN_MESSAGE;
@@ -25,7 +26,7 @@ pub async fn subscribe(
     SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>,
     impl Stream<Item = Result<String, anyhow::Error>> + Unpin,
 )> {
-    subscribe_impl(None, url, None).await
+    subscribe_impl(None, url, None, None).await
 }
 
 /// Connects to the orderbook WebSocket API with authentication.
@@ -35,12 +36,13 @@ pub async fn subscribe_with_authentication(
     url: String,
     authenticate: impl Fn(Message) -> Signature,
     fcm_token: Option<String>,
+    device: Option<Device>,
 ) -> Result<(
     SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>,
     impl Stream<Item = Result<String, anyhow::Error>> + Unpin,
 )> {
     let signature = create_auth_message_signature(authenticate);
-    subscribe_impl(Some(signature), url, fcm_token).await
+    subscribe_impl(Some(signature), url, fcm_token, device).await
 }
 
 pub fn create_auth_message_signature(authenticate: impl Fn(Message) -> Signature) -> Signature {
@@ -52,6 +54,7 @@ async fn subscribe_impl(
     signature: Option<Signature>,
     url: String,
     fcm_token: Option<String>,
+    device: Option<Device>,
 ) -> Result<(
     SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>,
     impl Stream<Item = Result<String>> + Unpin,
@@ -70,6 +73,7 @@ async fn subscribe_impl(
                 OrderbookRequest::Authenticate {
                     fcm_token,
                     signature,
+                    device,
                 },
             )?)
             .await;
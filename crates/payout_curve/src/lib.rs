@@ -1,7 +1,9 @@
+use anyhow::bail;
 use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::Amount;
+use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::Deserialize;
@@ -24,6 +26,90 @@ pub const ROUNDING_PERCENT: f32 = 0.01;
 /// are $1 away from each other.
 const PAYOUT_CURVE_DISCRETIZATION_STEPS: u64 = 20;
 
+/// Share of the price range between the two liquidation prices, next to each liquidation price,
+/// within which payouts are rounded more finely.
+const COLLAR_PERCENT: f32 = 0.1;
+
+/// Parameters trading off the number of CETs against the precision of the payouts.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PayoutCurveParams {
+    /// Factor by which we multiply the total margin to get the rounding of the payouts between the
+    /// collars. See [`ROUNDING_PERCENT`].
+    pub rounding_percent: f32,
+    /// The price distance between two points of the payout curve, i.e. the lower, the more CETs.
+    pub discretization_steps: u64,
+    /// Share of the price range between the two liquidation prices, next to each liquidation
+    /// price, within which payouts are rounded ten times more finely. This prevents the rounding
+    /// from crossing the liquidation payouts.
+    pub collar_percent: f32,
+}
+
+impl Default for PayoutCurveParams {
+    fn default() -> Self {
+        Self {
+            rounding_percent: ROUNDING_PERCENT,
+            discretization_steps: PAYOUT_CURVE_DISCRETIZATION_STEPS,
+            collar_percent: COLLAR_PERCENT,
+        }
+    }
+}
+
+impl PayoutCurveParams {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.rounding_percent > 0.0 && self.rounding_percent < 1.0) {
+            bail!(
+                "rounding_percent must be between 0 and 1, got {}",
+                self.rounding_percent
+            );
+        }
+
+        if self.discretization_steps == 0 {
+            bail!("discretization_steps must be greater than 0");
+        }
+
+        if !(self.collar_percent > 0.0 && self.collar_percent < 0.5) {
+            bail!(
+                "collar_percent must be between 0 and 0.5, got {}",
+                self.collar_percent
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The rounding of the payouts between the collars, in sats.
+    pub fn rounding(&self, total_margin: u64) -> u64 {
+        (total_margin as f32 * self.rounding_percent) as u64
+    }
+
+    /// The rounding of the payouts within the collars, in sats.
+    pub fn collar_rounding(&self, total_margin: u64) -> u64 {
+        (total_margin as f32 * self.rounding_percent * 0.1) as u64
+    }
+
+    /// The prices at which the collars next to the long and the short liquidation price end.
+    pub fn collar_prices(
+        &self,
+        long_liquidation_price: u64,
+        short_liquidation_price: u64,
+    ) -> Result<(u64, u64)> {
+        let liquidation_diff = short_liquidation_price
+            .checked_sub(long_liquidation_price)
+            .context("Short liquidation price must not be lower than long liquidation price")?;
+        let collar_percent =
+            Decimal::from_f32(self.collar_percent).context("Invalid collar_percent")?;
+        let collar = (Decimal::from(liquidation_diff) * collar_percent)
+            .floor()
+            .to_u64()
+            .context("Collar to fit into u64")?;
+
+        Ok((
+            long_liquidation_price + collar,
+            short_liquidation_price - collar,
+        ))
+    }
+}
+
 /// A payout point representing a payout for a given outcome.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PayoutPoint {
@@ -132,6 +218,29 @@ pub fn build_inverse_payout_function(
     price_params: PriceParams,
     offer_party_direction: Direction,
 ) -> Result<Vec<(PayoutPoint, PayoutPoint)>> {
+    build_inverse_payout_function_with_params(
+        quantity,
+        offer_party,
+        accept_party,
+        price_params,
+        offer_party_direction,
+        &PayoutCurveParams::default(),
+    )
+}
+
+/// Like [`build_inverse_payout_function`], but discretizing the payout curve according to the given
+/// [`PayoutCurveParams`].
+pub fn build_inverse_payout_function_with_params(
+    // The number of contracts.
+    quantity: f32,
+    offer_party: PartyParams,
+    accept_party: PartyParams,
+    price_params: PriceParams,
+    offer_party_direction: Direction,
+    params: &PayoutCurveParams,
+) -> Result<Vec<(PayoutPoint, PayoutPoint)>> {
+    params.validate()?;
+
     let mut pieces = vec![];
 
     let total_collateral = offer_party.total_collateral() + accept_party.total_collateral();
@@ -170,6 +279,7 @@ pub fn build_inverse_payout_function(
             .expect("to fit dec into u64"),
         offer_party_direction,
         quantity,
+        params.discretization_steps,
     )?;
 
     let (_, mid_range_interval_end_payout_point) = mid_range
@@ -266,6 +376,7 @@ fn calculate_mid_range_payouts(
     short_liquidation_price: u64,
     offer_direction: Direction,
     quantity: f32,
+    discretization_steps: u64,
 ) -> Result<Vec<(PayoutPoint, PayoutPoint)>> {
     let long_liquidation_price = long_liquidation_interval_end_payout.event_outcome;
 
@@ -280,7 +391,7 @@ fn calculate_mid_range_payouts(
     };
 
     let pieces = (long_liquidation_price..short_liquidation_price)
-        .step_by(PAYOUT_CURVE_DISCRETIZATION_STEPS as usize)
+        .step_by(discretization_steps as usize)
         .map(|interval_start_price| {
             // Interval start payout point.
 
@@ -319,7 +430,7 @@ fn calculate_mid_range_payouts(
             // Interval end payout point.
 
             let interval_end_price =
-                (interval_start_price + PAYOUT_CURVE_DISCRETIZATION_STEPS).min(BTCUSD_MAX_PRICE);
+                (interval_start_price + discretization_steps).min(BTCUSD_MAX_PRICE);
 
             let interval_end_payout = {
                 let pnl = calculate_pnl(
@@ -414,6 +525,69 @@ mod tests {
     use trade::cfd::calculate_margin;
     use trade::cfd::calculate_short_liquidation_price;
 
+    #[test]
+    fn default_payout_curve_params_are_valid() {
+        PayoutCurveParams::default().validate().unwrap();
+    }
+
+    #[test]
+    fn invalid_payout_curve_params_are_rejected() {
+        let params = PayoutCurveParams {
+            discretization_steps: 0,
+            ..PayoutCurveParams::default()
+        };
+        assert!(params.validate().is_err());
+
+        let params = PayoutCurveParams {
+            collar_percent: 0.5,
+            ..PayoutCurveParams::default()
+        };
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn collar_prices_are_relative_to_liquidation_prices() {
+        let params = PayoutCurveParams::default();
+
+        let (low_price, high_price) = params.collar_prices(20_000, 45_000).unwrap();
+
+        assert_eq!(low_price, 22_500);
+        assert_eq!(high_price, 42_500);
+    }
+
+    #[test]
+    fn coarser_discretization_yields_fewer_payout_points() {
+        let initial_price = dec!(30_000);
+        let quantity = 1_000.0;
+        let leverage = Decimal::ONE;
+
+        let price_params = PriceParams::new_btc_usd(
+            initial_price,
+            calculate_long_liquidation_price(leverage, initial_price),
+            calculate_short_liquidation_price(leverage, initial_price),
+        )
+        .unwrap();
+        let margin = calculate_margin(initial_price, quantity, 1.0);
+        let party_params = PartyParams::new(Amount::from_sat(margin), Amount::ZERO);
+
+        let build = |discretization_steps| {
+            build_inverse_payout_function_with_params(
+                quantity,
+                party_params,
+                party_params,
+                price_params,
+                Direction::Long,
+                &PayoutCurveParams {
+                    discretization_steps,
+                    ..PayoutCurveParams::default()
+                },
+            )
+            .unwrap()
+        };
+
+        assert!(build(100).len() < build(20).len());
+    }
+
     /// set this to true to export test data to csv files
     /// An example gnuplot file has been provided in [`payout_curve.gp`]
     const PRINT_CSV: bool = false;
@@ -573,6 +747,7 @@ mod tests {
                 short_liquidation_price.to_u64().unwrap(),
                 offer_direction,
                 quantity,
+                PAYOUT_CURVE_DISCRETIZATION_STEPS,
             )
             .expect("To be able to compute mid range")
         };
@@ -594,6 +769,7 @@ mod tests {
                 short_liquidation_price.to_u64().unwrap(),
                 offer_direction,
                 quantity,
+                PAYOUT_CURVE_DISCRETIZATION_STEPS,
             )
             .expect("To be able to compute mid range")
         };
@@ -708,6 +884,7 @@ mod tests {
             short_liquidation_price.to_u64().unwrap(),
             offer_direction,
             quantity,
+            PAYOUT_CURVE_DISCRETIZATION_STEPS,
         )
         .expect("To be able to compute mid range");
 
@@ -1013,6 +1190,7 @@ mod tests {
                 short_liquidation_price.to_u64().unwrap(),
                 offer_direction,
                 quantity,
+                PAYOUT_CURVE_DISCRETIZATION_STEPS,
             )
             .expect("To be able to compute mid range");
 
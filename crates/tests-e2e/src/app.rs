@@ -20,6 +20,13 @@ impl AppHandle {
 }
 
 pub async fn run_app(seed_phrase: Option<Vec<String>>) -> AppHandle {
+    run_app_with_config(seed_phrase, test_config()).await
+}
+
+pub async fn run_app_with_config(
+    seed_phrase: Option<Vec<String>>,
+    config: native::config::api::Config,
+) -> AppHandle {
     let app_dir = TempDir::new().unwrap();
     let seed_dir = TempDir::new().unwrap();
     let _app_handle = {
@@ -28,17 +35,22 @@ pub async fn run_app(seed_phrase: Option<Vec<String>>) -> AppHandle {
         let app_dir = as_string(&app_dir);
         let seed_dir = as_string(&seed_dir);
 
-        native::api::set_config(test_config(), app_dir, seed_dir.clone()).unwrap();
+        native::api::set_config(config, app_dir, seed_dir.clone()).unwrap();
 
         if let Some(seed_phrase) = seed_phrase {
             tokio::task::spawn_blocking({
                 let seed_dir = seed_dir.clone();
                 move || {
-                    api::restore_from_seed_phrase(
+                    let summary = api::restore_from_seed_phrase(
                         seed_phrase.join(" "),
                         format!("{seed_dir}/regtest/seed"),
                     )
                     .unwrap();
+                    assert!(
+                        summary.failures.is_empty(),
+                        "Failed to restore backup: {:?}",
+                        summary.failures
+                    );
                 }
             })
             .await
@@ -94,8 +106,10 @@ pub fn force_close_dlc_channel() {
     block_in_place(move || api::force_close_channel().unwrap());
 }
 
-// Values mostly taken from `environment.dart`
-fn test_config() -> native::config::api::Config {
+/// The configuration of an app connected to the local services.
+///
+/// Values mostly taken from `environment.dart`.
+pub fn test_config() -> native::config::api::Config {
     native::config::api::Config {
         coordinator_pubkey: "02dd6abec97f9a748bf76ad502b004ce05d1b2d1f43a9e76bd7d85e767ffb022c9"
             .to_string(),
@@ -107,7 +121,9 @@ fn test_config() -> native::config::api::Config {
         oracle_endpoint: "http://127.0.0.1:8081".to_string(),
         oracle_pubkey: "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0"
             .to_string(),
-        health_check_interval_secs: 1, // We want to measure health more often in tests
+        health_check_interval_secs: Some(1), // We want to measure health more often in tests
         rgs_server_url: None,
+        watchtower_url: None,
+        backup_url: None,
     }
 }
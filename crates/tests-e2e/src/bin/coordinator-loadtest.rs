@@ -0,0 +1,423 @@
+//! Simulates many traders connected to the coordinator's websocket, submitting randomized order
+//! flow at a fixed rate, and reports the latency of the order submissions and of the match
+//! notifications.
+//!
+//! The traders do not have a Lightning node, so matched market orders will not be executed. This
+//! exercises the order submission and matching path of the coordinator, not the DLC protocol. Only
+//! run it against a local or regtest coordinator.
+
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::secp256k1::SecretKey;
+use clap::Parser;
+use commons::Message;
+use commons::NewOrder;
+use commons::OrderType;
+use commons::Signature;
+use futures::StreamExt;
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::Client;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use time::OffsetDateTime;
+use trade::ContractSymbol;
+use trade::Direction;
+use uuid::Uuid;
+
+/// How often we log intermediate results while the load test is running.
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long to wait for the match notifications of the last orders.
+const MATCH_NOTIFICATION_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Parser)]
+pub struct Opts {
+    /// Coordinator address
+    #[clap(long, default_value = "http://localhost:8000")]
+    pub coordinator: String,
+
+    /// Number of simulated traders, each with their own websocket connection
+    #[clap(long, alias = "users", default_value = "1000")]
+    pub traders: usize,
+
+    /// Orders submitted per second, across all traders
+    #[clap(long, default_value = "10")]
+    pub rate: f64,
+
+    /// How long to submit orders for, in seconds
+    #[clap(long, default_value = "60")]
+    pub duration: u64,
+
+    /// The share of market orders in the order flow, between 0 and 1. The other orders are limit
+    /// orders, which provide the liquidity for the market orders.
+    #[clap(long, default_value = "0.5")]
+    pub market_order_ratio: f64,
+
+    /// The price around which limit orders are placed
+    #[clap(long, default_value = "30000")]
+    pub price: f64,
+
+    /// How far limit orders are placed from the price at most, in percent
+    #[clap(long, default_value = "1")]
+    pub spread: f64,
+
+    /// The minimum quantity of an order in contracts
+    #[clap(long, default_value = "10")]
+    pub min_quantity: u64,
+
+    /// The maximum quantity of an order in contracts
+    #[clap(long, default_value = "1000")]
+    pub max_quantity: u64,
+
+    /// Exit with an error if the 99th percentile of the match notification latency exceeds this
+    /// many milliseconds, e.g. to catch performance regressions in CI
+    #[clap(long)]
+    pub max_p99_match_latency: Option<u64>,
+}
+
+struct Trader {
+    pubkey: PublicKey,
+    /// Whether the trader has a matched market order waiting for execution, in which case the
+    /// coordinator rejects their market orders until the match is reverted.
+    in_execution: AtomicBool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter("info,orderbook_client=warn")
+        .init();
+
+    let opts = Opts::parse();
+    ensure!(opts.traders > 0, "Need at least one trader");
+    ensure!(opts.rate > 0.0, "The rate must be positive");
+    ensure!(
+        (0.0..=1.0).contains(&opts.market_order_ratio),
+        "The market order ratio must be between 0 and 1"
+    );
+    ensure!(
+        0 < opts.min_quantity && opts.min_quantity <= opts.max_quantity,
+        "The quantity range must not be empty"
+    );
+
+    let stats = Arc::new(Stats::default());
+
+    let traders = connect_traders(&opts, stats.clone()).await;
+    tracing::info!(
+        connected = traders.len(),
+        failed = opts.traders - traders.len(),
+        "Connected traders"
+    );
+    ensure!(!traders.is_empty(), "Could not connect any trader");
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("Failed to build reqwest client")?;
+
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / opts.rate));
+    let start = Instant::now();
+    let end = start + Duration::from_secs(opts.duration);
+    let mut last_report = start;
+    let mut requests = vec![];
+
+    loop {
+        interval.tick().await;
+        if Instant::now() >= end {
+            break;
+        }
+
+        let trader = traders[rand::thread_rng().gen_range(0..traders.len())].clone();
+        let order = new_order(&opts, &trader);
+        requests.push(tokio::spawn({
+            let client = client.clone();
+            let url = format!("{}/api/v1/orderbook/orders", opts.coordinator);
+            let stats = stats.clone();
+            async move { submit_order(&client, &url, order, &stats).await }
+        }));
+
+        if last_report.elapsed() >= REPORT_INTERVAL {
+            stats.report(start.elapsed());
+            last_report = Instant::now();
+        }
+    }
+
+    tracing::info!("Waiting for pending requests");
+    futures::future::join_all(requests).await;
+    tokio::time::sleep(MATCH_NOTIFICATION_GRACE_PERIOD).await;
+
+    let p99_match_latency = stats.report(start.elapsed());
+
+    if let Some(max) = opts.max_p99_match_latency {
+        ensure!(
+            p99_match_latency <= Duration::from_millis(max),
+            "The 99th percentile of the match notification latency of {}ms exceeds {max}ms",
+            p99_match_latency.as_millis()
+        );
+    }
+
+    Ok(())
+}
+
+/// Connect all traders to the websocket concurrently, returning the traders which could connect.
+async fn connect_traders(opts: &Opts, stats: Arc<Stats>) -> Vec<Arc<Trader>> {
+    let url = format!(
+        "{}/api/v1/orderbook/websocket",
+        opts.coordinator.replacen("http", "ws", 1)
+    );
+
+    let connections = (0..opts.traders).map(|_| {
+        let url = url.clone();
+        let stats = stats.clone();
+        async move {
+            let secp = Secp256k1::new();
+            let secret_key = SecretKey::from_slice(&rand::random::<[u8; 32]>())?;
+            let pubkey = secret_key.public_key(&secp);
+
+            let authenticate = move |msg| Signature {
+                pubkey,
+                signature: secp.sign_ecdsa(&msg, &secret_key),
+            };
+
+            let (sink, mut stream) =
+                orderbook_client::subscribe_with_authentication(url, authenticate, None, None)
+                    .await?;
+
+            let trader = Arc::new(Trader {
+                pubkey,
+                in_execution: AtomicBool::new(false),
+            });
+
+            // Keep reading, so that the coordinator never blocks on a slow consumer.
+            tokio::spawn({
+                let trader = trader.clone();
+                async move {
+                    let _sink = sink;
+                    while let Some(msg) = stream.next().await {
+                        match msg {
+                            Ok(msg) => stats.on_message(&trader, &msg),
+                            Err(e) => {
+                                tracing::warn!(%pubkey, "Websocket connection failed: {e:#}");
+                                stats.ws_disconnects.fetch_add(1, Ordering::Relaxed);
+                                break;
+                            }
+                        };
+                    }
+                }
+            });
+
+            anyhow::Ok(trader)
+        }
+    });
+
+    futures::future::join_all(connections)
+        .await
+        .into_iter()
+        .filter_map(|connection| match connection {
+            Ok(trader) => Some(trader),
+            Err(e) => {
+                tracing::warn!("Failed to connect trader: {e:#}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// A random order of the `trader`.
+///
+/// Traders whose market order is waiting for execution only submit limit orders.
+fn new_order(opts: &Opts, trader: &Trader) -> NewOrder {
+    let mut rng = rand::thread_rng();
+
+    let direction = if rng.gen_bool(0.5) {
+        Direction::Long
+    } else {
+        Direction::Short
+    };
+
+    let is_market_order =
+        !trader.in_execution.load(Ordering::Relaxed) && rng.gen_bool(opts.market_order_ratio);
+
+    let (order_type, price) = if is_market_order {
+        (OrderType::Market, Decimal::ZERO)
+    } else {
+        // Long limit orders are placed below and short limit orders above the price, so that
+        // they do not cross.
+        let offset = rng.gen_range(0.0..=opts.spread) / 100.0;
+        let price = match direction {
+            Direction::Long => opts.price * (1.0 - offset),
+            Direction::Short => opts.price * (1.0 + offset),
+        };
+
+        (OrderType::Limit, Decimal::from_f64(price).expect("to fit"))
+    };
+
+    NewOrder {
+        id: Uuid::new_v4(),
+        contract_symbol: ContractSymbol::BtcUsd,
+        price,
+        quantity: Decimal::from(rng.gen_range(opts.min_quantity..=opts.max_quantity)),
+        trader_id: trader.pubkey,
+        direction,
+        leverage: 2.0,
+        order_type,
+        expiry: OffsetDateTime::now_utc() + time::Duration::minutes(1),
+        stable: false,
+        reduce_only: false,
+    }
+}
+
+async fn submit_order(client: &Client, url: &str, order: NewOrder, stats: &Stats) {
+    let order_id = order.id;
+    let order_type = order.order_type;
+
+    let start = Instant::now();
+    if order_type == OrderType::Market {
+        // The match notification can arrive before the response.
+        stats.pending_matches.lock().insert(order_id, start);
+    }
+
+    let result = client.post(url).json(&order).send().await;
+    let latency = start.elapsed();
+
+    let outcome = match result {
+        Ok(response) if response.status().is_success() => None,
+        Ok(response) => Some(format!(
+            "{order_type:?} order: HTTP {}",
+            response.status().as_u16()
+        )),
+        Err(e) if e.is_timeout() => Some(format!("{order_type:?} order: timeout")),
+        Err(_) => Some(format!("{order_type:?} order: connection error")),
+    };
+
+    if outcome.is_some() {
+        stats.pending_matches.lock().remove(&order_id);
+    }
+
+    stats.record(latency, outcome);
+}
+
+#[derive(Default)]
+struct Stats {
+    /// The latencies of all the order submissions, including failed ones.
+    latencies: Mutex<Vec<Duration>>,
+    /// The latencies from submitting a market order until the trader is notified about its match.
+    match_latencies: Mutex<Vec<Duration>>,
+    /// When the market orders which have not been matched yet were submitted.
+    pending_matches: Mutex<HashMap<Uuid, Instant>>,
+    /// The number of failed order submissions per kind of error.
+    errors: Mutex<BTreeMap<String, u64>>,
+    /// The number of matches of limit orders.
+    limit_order_matches: AtomicU64,
+    reverted_matches: AtomicU64,
+    ws_messages: AtomicU64,
+    ws_disconnects: AtomicU64,
+}
+
+impl Stats {
+    fn record(&self, latency: Duration, error: Option<String>) {
+        self.latencies.lock().push(latency);
+
+        if let Some(error) = error {
+            *self.errors.lock().entry(error).or_default() += 1;
+        }
+    }
+
+    fn on_message(&self, trader: &Trader, msg: &str) {
+        self.ws_messages.fetch_add(1, Ordering::Relaxed);
+
+        match serde_json::from_str(msg) {
+            Ok(Message::Match(filled_with)) => {
+                let submitted = self.pending_matches.lock().remove(&filled_with.order_id);
+                match submitted {
+                    Some(submitted) => {
+                        trader.in_execution.store(true, Ordering::Relaxed);
+                        self.match_latencies.lock().push(submitted.elapsed());
+                    }
+                    None => {
+                        self.limit_order_matches.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+            Ok(Message::MatchReverted { .. }) => {
+                trader.in_execution.store(false, Ordering::Relaxed);
+                self.reverted_matches.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::debug!("Failed to parse websocket message: {e:#}"),
+        }
+    }
+
+    /// Log the results so far, returning the 99th percentile of the match notification latency.
+    fn report(&self, elapsed: Duration) -> Duration {
+        let mut latencies = self.latencies.lock().clone();
+        latencies.sort();
+
+        let mut match_latencies = self.match_latencies.lock().clone();
+        match_latencies.sort();
+
+        let errors = self.errors.lock().clone();
+        let total = latencies.len();
+        let failed = errors.values().sum::<u64>();
+        let error_rate = match total {
+            0 => 0.0,
+            total => failed as f64 / total as f64 * 100.0,
+        };
+
+        let p99_match_latency = percentile(&match_latencies, 99.0);
+
+        tracing::info!(
+            elapsed_secs = elapsed.as_secs(),
+            orders = total,
+            orders_per_sec = %format!("{:.1}", total as f64 / elapsed.as_secs_f64()),
+            error_rate = %format!("{error_rate:.2}%"),
+            ?errors,
+            p50_ms = percentile(&latencies, 50.0).as_millis(),
+            p90_ms = percentile(&latencies, 90.0).as_millis(),
+            p99_ms = percentile(&latencies, 99.0).as_millis(),
+            max_ms = latencies.last().copied().unwrap_or_default().as_millis(),
+            "Order submission"
+        );
+
+        tracing::info!(
+            matches = match_latencies.len(),
+            limit_order_matches = self.limit_order_matches.load(Ordering::Relaxed),
+            reverted_matches = self.reverted_matches.load(Ordering::Relaxed),
+            p50_ms = percentile(&match_latencies, 50.0).as_millis(),
+            p90_ms = percentile(&match_latencies, 90.0).as_millis(),
+            p99_ms = p99_match_latency.as_millis(),
+            max_ms = match_latencies
+                .last()
+                .copied()
+                .unwrap_or_default()
+                .as_millis(),
+            ws_messages = self.ws_messages.load(Ordering::Relaxed),
+            ws_disconnects = self.ws_disconnects.load(Ordering::Relaxed),
+            "Match notification"
+        );
+
+        p99_match_latency
+    }
+}
+
+/// The latency below which `p` percent of the sorted `latencies` lie.
+fn percentile(latencies: &[Duration], p: f64) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let index = ((p / 100.0) * (latencies.len() - 1) as f64).round() as usize;
+    latencies[index]
+}
@@ -0,0 +1,120 @@
+//! Fault injection into the DLC protocol between the app and the coordinator.
+//!
+//! DLC messages are sent over the encrypted Lightning connection, hence they can't be told apart
+//! by the [`Proxy`]. Instead, [`Chaos`] hooks into the app's DLC message handling and uses the
+//! proxies only to sever the connection.
+
+use crate::proxy::Proxy;
+use bitcoin::secp256k1::PublicKey;
+use native::ln_dlc::interceptor::DlcMessageInterceptor;
+use native::ln_dlc::interceptor::MessageDirection;
+use native::ln_dlc::interceptor::Verdict;
+use parking_lot::Mutex;
+use rand::Rng;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Fault {
+    /// Drop the given percentage of DLC messages, in both directions.
+    DropMessages { percent: u8 },
+    /// Sever the connection to the coordinator when the next DLC message of the `kind` (e.g.
+    /// `ChannelRenewConfirm`) is about to be sent or processed by the app. The message is lost.
+    ///
+    /// For example, severing at the inbound `ChannelRenewConfirm` interrupts the renew protocol
+    /// between `RenewAccept` and `RenewConfirm`.
+    SeverAt {
+        direction: MessageDirection,
+        kind: &'static str,
+    },
+}
+
+/// Injects [`Fault`]s into the DLC messages of the app.
+pub struct Chaos {
+    faults: Mutex<Vec<Fault>>,
+    /// The number of messages which have been dropped.
+    dropped: Mutex<usize>,
+    coordinator: Vec<Arc<Proxy>>,
+}
+
+impl Chaos {
+    /// The `coordinator` proxies are taken offline to sever the connection to the coordinator.
+    pub fn new(coordinator: Vec<Arc<Proxy>>) -> Self {
+        Self {
+            faults: Mutex::new(Vec::new()),
+            dropped: Mutex::new(0),
+            coordinator,
+        }
+    }
+
+    pub fn inject(&self, fault: Fault) {
+        tracing::info!(?fault, "Injecting fault");
+        self.faults.lock().push(fault);
+    }
+
+    pub fn clear(&self) {
+        self.faults.lock().clear();
+    }
+
+    pub fn dropped(&self) -> usize {
+        *self.dropped.lock()
+    }
+
+    /// Whether all [`Fault::SeverAt`] faults have been triggered.
+    pub fn has_severed(&self) -> bool {
+        !self
+            .faults
+            .lock()
+            .iter()
+            .any(|fault| matches!(fault, Fault::SeverAt { .. }))
+    }
+
+    fn sever(&self) {
+        for proxy in self.coordinator.iter() {
+            proxy.go_offline();
+        }
+    }
+}
+
+impl DlcMessageInterceptor for Chaos {
+    fn intercept(&self, peer: PublicKey, direction: MessageDirection, kind: &str) -> Verdict {
+        let mut faults = self.faults.lock();
+
+        let sever_at = faults.iter().position(|fault| {
+            matches!(
+                fault,
+                Fault::SeverAt { direction: d, kind: k } if *d == direction && *k == kind
+            )
+        });
+
+        let verdict = if let Some(index) = sever_at {
+            // Severing happens only once, the connection can be restored afterwards.
+            faults.remove(index);
+
+            tracing::info!(%peer, ?direction, kind, "Severing connection to coordinator");
+            self.sever();
+
+            Verdict::Drop
+        } else {
+            let drop_percent = faults
+                .iter()
+                .filter_map(|fault| match fault {
+                    Fault::DropMessages { percent } => Some(*percent),
+                    Fault::SeverAt { .. } => None,
+                })
+                .max()
+                .unwrap_or(0);
+
+            if rand::thread_rng().gen_range(0..100) < drop_percent {
+                Verdict::Drop
+            } else {
+                Verdict::Deliver
+            }
+        };
+
+        if verdict == Verdict::Drop {
+            *self.dropped.lock() += 1;
+        }
+
+        verdict
+    }
+}
@@ -1,8 +1,13 @@
 use anyhow::Context;
 use anyhow::Result;
 use bitcoin::Address;
+use commons::CollaborativeRevertCoordinatorRequest;
 use reqwest::Client;
 use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+use time::OffsetDateTime;
 
 /// A wrapper over the coordinator HTTP API.
 ///
@@ -35,7 +40,12 @@ impl Coordinator {
     }
 
     pub async fn get_new_address(&self) -> Result<Address> {
-        Ok(self.get("/api/newaddress").await?.text().await?.parse()?)
+        Ok(self
+            .get("/api/v1/newaddress")
+            .await?
+            .text()
+            .await?
+            .parse()?)
     }
 
     pub async fn get_dlc_channels(&self) -> Result<Vec<DlcChannelDetails>> {
@@ -43,10 +53,73 @@ impl Coordinator {
     }
 
     pub async fn rollover(&self, dlc_channel_id: &str) -> Result<reqwest::Response> {
-        self.post(format!("/api/rollover/{dlc_channel_id}").as_str())
+        self.post(format!("/api/v1/rollover/{dlc_channel_id}").as_str())
             .await
     }
 
+    pub async fn collaborative_revert(
+        &self,
+        request: CollaborativeRevertCoordinatorRequest,
+    ) -> Result<()> {
+        self.post_json("/api/admin/channels/revert", &request)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_balance(&self) -> Result<Balance> {
+        Ok(self.get("/api/admin/wallet/balance").await?.json().await?)
+    }
+
+    pub async fn get_statement(&self, trader_pubkey: &str) -> Result<Statement> {
+        Ok(self
+            .get(format!("/api/admin/users/{trader_pubkey}/statement").as_str())
+            .await?
+            .json()
+            .await?)
+    }
+
+    /// The keys of the backups the coordinator holds for the given trader.
+    pub async fn get_user_backup_keys(&self, trader_pubkey: &str) -> Result<Vec<String>> {
+        Ok(self
+            .get(format!("/api/admin/users/{trader_pubkey}/backup").as_str())
+            .await?
+            .json()
+            .await?)
+    }
+
+    /// Delete all the backups the coordinator holds for the given trader.
+    pub async fn delete_user_backup(&self, trader_pubkey: &str) -> Result<()> {
+        self.delete(format!("/api/admin/users/{trader_pubkey}/backup").as_str())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Let the trader's open position expire now, and have the coordinator close it.
+    ///
+    /// Only available on regtest.
+    pub async fn expire_position(&self, trader_pubkey: &str) -> Result<()> {
+        self.post(format!("/api/admin/positions/{trader_pubkey}/expire").as_str())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_clock(&self) -> Result<ClockStatus> {
+        Ok(self.get("/api/admin/clock").await?.json().await?)
+    }
+
+    /// Fast-forward the coordinator's clock by `duration`, which is rounded down to whole seconds.
+    pub async fn advance_clock(&self, duration: Duration) -> Result<ClockStatus> {
+        let body = json!({ "seconds": duration.as_secs() });
+
+        Ok(self
+            .post_json("/api/admin/clock/advance", &body)
+            .await?
+            .json()
+            .await?)
+    }
+
     async fn get(&self, path: &str) -> Result<reqwest::Response> {
         self.client
             .get(format!("{0}{path}", self.host))
@@ -66,6 +139,63 @@ impl Coordinator {
             .error_for_status()
             .context("Coordinator did not return 200 OK")
     }
+
+    async fn delete(&self, path: &str) -> Result<reqwest::Response> {
+        self.client
+            .delete(format!("{0}{path}", self.host))
+            .send()
+            .await
+            .context("Could not send DELETE request to coordinator")?
+            .error_for_status()
+            .context("Coordinator did not return 200 OK")
+    }
+
+    async fn post_json(&self, path: &str, body: &impl Serialize) -> Result<reqwest::Response> {
+        self.client
+            .post(format!("{0}{path}", self.host))
+            .json(body)
+            .send()
+            .await
+            .context("Could not send POST request to coordinator")?
+            .error_for_status()
+            .context("Coordinator did not return 200 OK")
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ClockStatus {
+    #[serde(with = "time::serde::rfc3339")]
+    pub now: OffsetDateTime,
+    /// How far the coordinator's clock has been fast-forwarded, in seconds.
+    pub offset_seconds: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Balance {
+    pub lightning: u64,
+    /// The confirmed on-chain balance.
+    pub onchain: u64,
+    pub dlc_channel: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Statement {
+    pub realized_pnl_sat: i64,
+    pub fees_sat: u64,
+    pub entries: Vec<StatementEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StatementEntry {
+    pub kind: StatementEntryKind,
+    pub position_id: i32,
+    pub price: Option<f32>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub enum StatementEntryKind {
+    Trade,
+    PositionClosed,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,345 @@
+//! A regtest environment for integration tests, with the app running in the test process and
+//! connected to the local services.
+//!
+//! The services (bitcoind, coordinator, maker and oracle) have to be running, e.g. via
+//! `just services`. The app reaches the coordinator and the oracle through a [`Proxy`] each, so
+//! that a test can take them away from the app, slow them down and bring them back. Faults in
+//! the DLC protocol are injected with [`Chaos`].
+//!
+//! ```ignore
+//! let harness = TestHarness::new_with_open_position().await;
+//!
+//! harness.disconnect_coordinator();
+//! harness.wait_for_coordinator_status(ServiceStatus::Offline).await;
+//! harness.reconnect_coordinator();
+//!
+//! harness.expire_position().await;
+//! harness.wait_for_position_closed().await;
+//!
+//! // Jump into the next rollover window, for both the app and the coordinator.
+//! harness.advance_time(Duration::from_secs(7 * 24 * 60 * 60)).await;
+//!
+//! harness.inject_fault(Fault::SeverAt {
+//!     direction: MessageDirection::Inbound,
+//!     kind: "ChannelRenewConfirm",
+//! });
+//! ```
+
+use crate::app::refresh_wallet_info;
+use crate::app::run_app_with_config;
+use crate::app::sync_dlc_channels;
+use crate::app::test_config;
+use crate::app::AppHandle;
+use crate::bitcoind::Bitcoind;
+use crate::chaos::Chaos;
+use crate::chaos::Fault;
+use crate::coordinator::Coordinator;
+use crate::http::init_reqwest;
+use crate::logger::init_tracing;
+use crate::maker::Maker;
+use crate::proxy::Proxy;
+use crate::wait_until;
+use bitcoin::Amount;
+use commons::AdjustableClock;
+use commons::SystemClock;
+use native::api;
+use native::api::ContractSymbol;
+use native::health::Service;
+use native::health::ServiceStatus;
+use native::ln_dlc::interceptor;
+use native::trade::order::api::NewOrder;
+use native::trade::order::api::OrderType;
+use native::trade::position::PositionState;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::spawn_blocking;
+
+const COORDINATOR_HTTP: &str = "127.0.0.1:8000";
+const COORDINATOR_P2P: &str = "127.0.0.1:9045";
+const ORACLE_HTTP: &str = "127.0.0.1:8081";
+
+pub struct TestHarness {
+    pub app: AppHandle,
+    pub coordinator: Coordinator,
+    pub maker: Maker,
+    pub bitcoind: Bitcoind,
+    pub chaos: Arc<Chaos>,
+    /// The app's clock, kept in step with the coordinator's.
+    clock: Arc<AdjustableClock>,
+    coordinator_http: Arc<Proxy>,
+    coordinator_p2p: Arc<Proxy>,
+    oracle: Arc<Proxy>,
+}
+
+impl TestHarness {
+    /// Start a new app with an empty wallet, after making sure that the coordinator can trade.
+    pub async fn start() -> Self {
+        init_tracing();
+
+        let client = init_reqwest();
+        let bitcoind = Bitcoind::new_local(client.clone());
+        let coordinator = Coordinator::new_local(client.clone());
+        let maker = Maker::new_local(client);
+
+        assert!(coordinator.is_running().await);
+
+        // Ensure that the coordinator has a free UTXO available.
+        let address = coordinator.get_new_address().await.unwrap();
+        bitcoind
+            .send_to_address(&address, Amount::ONE_BTC)
+            .await
+            .unwrap();
+        bitcoind.mine(1).await.unwrap();
+        coordinator.sync_node().await.unwrap();
+
+        let coordinator_http = start_proxy(COORDINATOR_HTTP).await;
+        let coordinator_p2p = start_proxy(COORDINATOR_P2P).await;
+        let oracle = start_proxy(ORACLE_HTTP).await;
+
+        let config = native::config::api::Config {
+            http_port: coordinator_http.port(),
+            p2p_port: coordinator_p2p.port(),
+            oracle_endpoint: format!("http://127.0.0.1:{}", oracle.port()),
+            ..test_config()
+        };
+
+        // The coordinator's clock may have been advanced by a previous test.
+        let clock = Arc::new(AdjustableClock::new());
+        let coordinator_clock = coordinator.get_clock().await.unwrap();
+        clock.advance(time::Duration::seconds(coordinator_clock.offset_seconds));
+        native::state::set_clock(clock.clone());
+
+        let chaos = Arc::new(Chaos::new(vec![
+            coordinator_http.clone(),
+            coordinator_p2p.clone(),
+        ]));
+        interceptor::set(chaos.clone());
+
+        let app = run_app_with_config(None, config).await;
+
+        let balances = app.rx.wallet_info().unwrap().balances;
+        assert_eq!(
+            balances.on_chain, 0,
+            "App should start with empty on-chain wallet"
+        );
+        assert_eq!(
+            balances.off_chain, 0,
+            "App should start with empty off-chain wallet"
+        );
+
+        Self {
+            app,
+            coordinator,
+            maker,
+            bitcoind,
+            chaos,
+            clock,
+            coordinator_http,
+            coordinator_p2p,
+            oracle,
+        }
+    }
+
+    /// Start test with a running app and a funded wallet.
+    pub async fn new_after_funding() -> Self {
+        let harness = Self::start().await;
+        harness.fund_app(Amount::ONE_BTC).await;
+
+        harness
+    }
+
+    /// Start test with a running app with a funded wallet and an open position.
+    pub async fn new_with_open_position() -> Self {
+        let harness = Self::new_after_funding().await;
+        harness.open_position(dummy_order()).await;
+
+        harness
+    }
+
+    /// Send `amount` to the app's on-chain wallet and wait until it shows up in its balance.
+    pub async fn fund_app(&self, amount: Amount) {
+        let balance_before = self.app.rx.wallet_info().unwrap().balances.on_chain;
+
+        let address = api::get_unused_address();
+        let address = &address.0.parse().unwrap();
+
+        self.bitcoind
+            .send_to_address(address, amount)
+            .await
+            .unwrap();
+        self.bitcoind.mine(1).await.unwrap();
+
+        wait_until!({
+            refresh_wallet_info();
+            self.app.rx.wallet_info().unwrap().balances.on_chain == balance_before + amount.to_sat()
+        });
+
+        tracing::info!(%amount, "Successfully funded app");
+    }
+
+    /// Mine `n` blocks and let the app and the coordinator process them.
+    pub async fn mine(&self, n: u16) {
+        self.bitcoind.mine(n).await.unwrap();
+
+        self.coordinator.sync_node().await.unwrap();
+        refresh_wallet_info();
+        sync_dlc_channels();
+    }
+
+    /// Submit the `order` and wait until the resulting position is open and its DLC confirmed.
+    pub async fn open_position(&self, order: NewOrder) {
+        tracing::info!(?order, "Opening a position");
+        self.submit_order(order).await;
+
+        wait_until!(self.app.rx.order().is_some());
+        self.wait_for_position_state(PositionState::Open).await;
+
+        // Wait for coordinator to open position.
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        self.bitcoind.mine(6).await.unwrap();
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        sync_dlc_channels();
+        self.coordinator.sync_node().await.unwrap();
+    }
+
+    /// Close the current position with a market order and wait until it is closed.
+    pub async fn close_position(&self) {
+        let position_id = self.app.rx.position().unwrap().id.to_string();
+
+        tracing::info!(%position_id, "Closing position");
+        spawn_blocking(move || api::close_position(position_id).unwrap())
+            .await
+            .unwrap();
+
+        self.wait_for_position_closed().await;
+    }
+
+    pub async fn submit_order(&self, order: NewOrder) {
+        spawn_blocking(move || api::submit_order(order).unwrap())
+            .await
+            .unwrap();
+    }
+
+    /// Travel to the expiry of the app's position, which makes the coordinator close it.
+    pub async fn expire_position(&self) {
+        let trader_pubkey = api::get_node_id().0;
+
+        tracing::info!(%trader_pubkey, "Expiring position");
+        self.coordinator
+            .expire_position(&trader_pubkey)
+            .await
+            .unwrap();
+    }
+
+    /// Fast-forward the time of both the app and the coordinator by `duration`, e.g. into the next
+    /// rollover window.
+    ///
+    /// Time can't be turned back, later tests will start in the future as well.
+    pub async fn advance_time(&self, duration: Duration) {
+        tracing::info!(?duration, "Advancing time");
+
+        let coordinator_clock = self.coordinator.advance_clock(duration).await.unwrap();
+
+        let offset =
+            time::Duration::seconds(coordinator_clock.offset_seconds) - self.clock.offset();
+        self.clock.advance(offset);
+    }
+
+    /// Cut the app off from the coordinator, both its HTTP API and its Lightning node.
+    pub fn disconnect_coordinator(&self) {
+        tracing::info!("Disconnecting app from coordinator");
+        self.coordinator_http.go_offline();
+        self.coordinator_p2p.go_offline();
+    }
+
+    pub fn reconnect_coordinator(&self) {
+        tracing::info!("Reconnecting app to coordinator");
+        self.coordinator_http.go_online();
+        self.coordinator_p2p.go_online();
+    }
+
+    /// Cut the app off from the oracle. The coordinator can still reach it.
+    pub fn disconnect_oracle(&self) {
+        tracing::info!("Disconnecting app from oracle");
+        self.oracle.go_offline();
+    }
+
+    pub fn reconnect_oracle(&self) {
+        tracing::info!("Reconnecting app to oracle");
+        self.oracle.go_online();
+    }
+
+    /// Delay all traffic between the app and the coordinator by `latency`, in both directions.
+    pub fn set_coordinator_latency(&self, latency: Duration) {
+        tracing::info!(?latency, "Setting latency between app and coordinator");
+        self.coordinator_http.set_latency(latency);
+        self.coordinator_p2p.set_latency(latency);
+    }
+
+    pub fn inject_fault(&self, fault: Fault) {
+        self.chaos.inject(fault);
+    }
+
+    /// Stop injecting faults. Severed connections stay severed until they are restored with
+    /// [`TestHarness::reconnect_coordinator`].
+    pub fn clear_faults(&self) {
+        self.chaos.clear();
+    }
+
+    pub async fn wait_for_position_state(&self, state: PositionState) {
+        wait_until!(self
+            .app
+            .rx
+            .position()
+            .is_some_and(|position| position.position_state == state));
+    }
+
+    pub async fn wait_for_position_closed(&self) {
+        wait_until!(self.app.rx.position_close().is_some() && self.app.rx.position().is_none());
+    }
+
+    /// Wait until the app considers the coordinator to be in the given `status`.
+    pub async fn wait_for_coordinator_status(&self, status: ServiceStatus) {
+        wait_until!(self.app.rx.status(Service::Coordinator) == status);
+    }
+
+    /// Assert that the app's off-chain balance is `expected`, give or take `tolerance` to account
+    /// for price movements.
+    pub fn assert_off_chain_balance(&self, expected: Amount, tolerance: Amount) {
+        let balance = self.app.rx.wallet_info().unwrap().balances.off_chain;
+
+        assert!(
+            balance.abs_diff(expected.to_sat()) <= tolerance.to_sat(),
+            "Expected off-chain balance of {} sats, give or take {} sats, but got {balance} sats",
+            expected.to_sat(),
+            tolerance.to_sat()
+        );
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        interceptor::clear();
+        native::state::set_clock(Arc::new(SystemClock));
+    }
+}
+
+async fn start_proxy(target: &str) -> Arc<Proxy> {
+    let target: SocketAddr = target.parse().unwrap();
+    Arc::new(Proxy::start(target).await.unwrap())
+}
+
+pub fn dummy_order() -> NewOrder {
+    NewOrder {
+        leverage: 2.0,
+        contract_symbol: ContractSymbol::BtcUsd,
+        direction: api::Direction::Long,
+        quantity: 1000.0,
+        order_type: Box::new(OrderType::Market),
+        stable: false,
+    }
+}
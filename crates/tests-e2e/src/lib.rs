@@ -2,10 +2,12 @@
 
 pub mod app;
 pub mod bitcoind;
+pub mod chaos;
 pub mod coordinator;
+pub mod harness;
 pub mod http;
 pub mod logger;
 pub mod maker;
-pub mod setup;
+pub mod proxy;
 pub mod test_flow;
 pub mod test_subscriber;
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// A TCP proxy between the app and one of the services it connects to.
+///
+/// Taking the proxy offline drops all connections through it and refuses new ones, which looks to
+/// the app as if the service went down. The service itself keeps running for everyone else.
+///
+/// The proxy can also delay everything it forwards, to simulate a slow network.
+pub struct Proxy {
+    address: SocketAddr,
+    online: watch::Sender<bool>,
+    latency: watch::Sender<Duration>,
+    task: JoinHandle<()>,
+}
+
+impl Proxy {
+    /// Start forwarding connections to a random local port to the `target`.
+    pub async fn start(target: SocketAddr) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+
+        let (online, online_rx) = watch::channel(true);
+        let (latency, latency_rx) = watch::channel(Duration::ZERO);
+
+        let task = tokio::spawn(async move {
+            loop {
+                let inbound = match listener.accept().await {
+                    Ok((inbound, _)) => inbound,
+                    Err(e) => {
+                        tracing::warn!(%target, "Proxy failed to accept connection: {e:#}");
+                        continue;
+                    }
+                };
+
+                if !*online_rx.borrow() {
+                    tracing::debug!(%target, "Proxy is offline, refusing connection");
+                    continue;
+                }
+
+                let mut online_rx = online_rx.clone();
+                online_rx.borrow_and_update();
+
+                let latency_rx = latency_rx.clone();
+                tokio::spawn(async move {
+                    let outbound = match TcpStream::connect(target).await {
+                        Ok(outbound) => outbound,
+                        Err(e) => {
+                            tracing::warn!(%target, "Proxy failed to connect: {e:#}");
+                            return;
+                        }
+                    };
+
+                    let (inbound_read, inbound_write) = inbound.into_split();
+                    let (outbound_read, outbound_write) = outbound.into_split();
+
+                    tokio::select! {
+                        _ = forward(inbound_read, outbound_write, latency_rx.clone()) => {}
+                        _ = forward(outbound_read, inbound_write, latency_rx) => {}
+                        _ = went_offline(online_rx) => {
+                            tracing::debug!(%target, "Proxy went offline, dropping connection");
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            address,
+            online,
+            latency,
+            task,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.address.port()
+    }
+
+    /// Drop all connections and refuse new ones until [`Proxy::go_online`] is called.
+    pub fn go_offline(&self) {
+        self.online
+            .send_if_modified(|online| std::mem::replace(online, false));
+    }
+
+    pub fn go_online(&self) {
+        self.online
+            .send_if_modified(|online| !std::mem::replace(online, true));
+    }
+
+    /// Delay everything forwarded from now on by `latency`, in both directions.
+    pub fn set_latency(&self, latency: Duration) {
+        self.latency.send_replace(latency);
+    }
+}
+
+impl Drop for Proxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Forward everything read from `from` to `to`, until either side closes the connection.
+async fn forward(
+    mut from: OwnedReadHalf,
+    mut to: OwnedWriteHalf,
+    latency: watch::Receiver<Duration>,
+) {
+    let mut buf = vec![0; 8 * 1024];
+
+    loop {
+        let n = match from.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+
+        let latency = *latency.borrow();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        if to.write_all(&buf[..n]).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn went_offline(mut online: watch::Receiver<bool>) {
+    // Only going offline and coming back online are published, and there are no connections while
+    // the proxy is offline. Hence, any change means that the proxy went offline, even if it came
+    // back online already.
+    let _ = online.changed().await;
+}
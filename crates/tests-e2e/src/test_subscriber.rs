@@ -181,8 +181,9 @@ impl Senders {
             native::event::EventInternal::PositionUpdateNotification(position) => {
                 self.position.send(Some(position.clone()))?;
             }
-            native::event::EventInternal::PositionCloseNotification(contract_symbol) => {
-                self.position_close.send(Some(*contract_symbol))?;
+            native::event::EventInternal::PositionCloseNotification(position) => {
+                self.position_close.send(Some(position.contract_symbol))?;
+                self.position.send(None)?;
             }
             native::event::EventInternal::PriceUpdateNotification(prices) => {
                 self.prices.send(Some(prices.clone()))?;
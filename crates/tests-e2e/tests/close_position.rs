@@ -5,8 +5,8 @@ use native::api::ContractSymbol;
 use native::trade::order::api::NewOrder;
 use native::trade::order::api::OrderType;
 use native::trade::position::PositionState;
-use tests_e2e::setup;
-use tests_e2e::setup::dummy_order;
+use tests_e2e::harness::dummy_order;
+use tests_e2e::harness::TestHarness;
 use tests_e2e::wait_until;
 use tokio::task::spawn_blocking;
 
@@ -16,7 +16,7 @@ use tokio::task::spawn_blocking;
 #[tokio::test(flavor = "multi_thread")]
 #[ignore = "need to be run with 'just e2e' command"]
 async fn can_open_close_open_close_position() {
-    let test = setup::TestSetup::new_with_open_position().await;
+    let test = TestHarness::new_with_open_position().await;
 
     // - App margin is 1_250_000 sats.
     // - Opening fee of 7_500 paid to coordinator collateral reserve from app on-chain balance.
@@ -57,12 +57,9 @@ async fn can_open_close_open_close_position() {
         stable: false,
     };
 
-    spawn_blocking({
-        let order = order.clone();
-        move || api::submit_order(order).unwrap()
-    })
-    .await
-    .unwrap();
+    spawn_blocking(move || api::submit_order(order).unwrap())
+        .await
+        .unwrap();
 
     wait_until!(test.app.rx.position().is_some());
     wait_until!(test.app.rx.position().unwrap().position_state == PositionState::Open);
@@ -78,12 +75,9 @@ async fn can_open_close_open_close_position() {
 
     tracing::info!("Closing second position");
 
-    let closing_order = NewOrder {
-        direction: api::Direction::Short,
-        ..order
-    };
+    let position_id = test.app.rx.position().unwrap().id.to_string();
 
-    spawn_blocking(move || api::submit_order(closing_order).unwrap())
+    spawn_blocking(move || api::close_position(position_id).unwrap())
         .await
         .unwrap();
 
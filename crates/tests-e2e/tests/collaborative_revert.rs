@@ -0,0 +1,96 @@
+#![allow(clippy::unwrap_used)]
+
+use bitcoin::Amount;
+use commons::CollaborativeRevertCoordinatorRequest;
+use native::api;
+use rust_decimal_macros::dec;
+use tests_e2e::app::refresh_wallet_info;
+use tests_e2e::coordinator::ChannelState;
+use tests_e2e::coordinator::StatementEntryKind;
+use tests_e2e::harness::TestHarness;
+use tests_e2e::wait_until;
+
+/// The fee of the revert transaction is split between both parties, so each of them ends up with
+/// slightly less than the proposed amount.
+const MAX_REVERT_TX_FEE_SHARE_SATS: u64 = 1_000;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn can_revert_channel() {
+    let test = TestHarness::new_with_open_position().await;
+    let coordinator = &test.coordinator;
+    let app_pubkey = api::get_node_id().0;
+
+    let dlc_channel = coordinator
+        .get_dlc_channels()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|chan| chan.counter_party == app_pubkey)
+        .unwrap();
+
+    let app_on_chain_balance_before = test.app.rx.wallet_info().unwrap().balances.on_chain;
+    let coordinator_on_chain_balance_before = coordinator.get_balance().await.unwrap().onchain;
+
+    // The trader gets their margin back, as if the position had been closed at the entry price.
+    let trader_payout = Amount::from_sat(test.app.rx.position().unwrap().collateral);
+
+    tracing::info!(%trader_payout, "Proposing collaborative revert");
+
+    coordinator
+        .collaborative_revert(CollaborativeRevertCoordinatorRequest {
+            channel_id: dlc_channel.dlc_channel_id.clone().unwrap(),
+            fee_rate_sats_vb: 1,
+            counter_payout: trader_payout.to_sat(),
+            price: dec!(40_000),
+        })
+        .await
+        .unwrap();
+
+    // The app accepts the revert automatically and closes its position.
+    wait_until!(test.app.rx.position_close().is_some());
+
+    test.bitcoind.mine(1).await.unwrap();
+    coordinator.sync_node().await.unwrap();
+
+    let app_on_chain_balance_after = {
+        wait_until!({
+            refresh_wallet_info();
+            test.app.rx.wallet_info().unwrap().balances.on_chain > app_on_chain_balance_before
+        });
+
+        test.app.rx.wallet_info().unwrap().balances.on_chain
+    };
+
+    let app_received = Amount::from_sat(app_on_chain_balance_after - app_on_chain_balance_before);
+    tracing::info!(%app_received, "App received its share of the reverted channel");
+
+    assert!(app_received <= trader_payout);
+    assert!(app_received >= trader_payout - Amount::from_sat(MAX_REVERT_TX_FEE_SHARE_SATS));
+
+    let coordinator_on_chain_balance_after = coordinator.get_balance().await.unwrap().onchain;
+    assert!(
+        coordinator_on_chain_balance_after > coordinator_on_chain_balance_before,
+        "Coordinator should have received its share of the reverted channel"
+    );
+
+    // The coordinator has closed the channel and the position of the trader.
+
+    let dlc_channel = coordinator
+        .get_dlc_channels()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|chan| chan.dlc_channel_id == dlc_channel.dlc_channel_id)
+        .unwrap();
+    assert!(matches!(
+        dlc_channel.channel_state,
+        ChannelState::CollaborativelyClosed
+    ));
+
+    let statement = coordinator.get_statement(&app_pubkey).await.unwrap();
+    assert!(statement
+        .entries
+        .iter()
+        .any(|entry| entry.kind == StatementEntryKind::PositionClosed));
+}
@@ -0,0 +1,93 @@
+use bitcoin::Network;
+use native::api;
+use native::ln_dlc::interceptor::MessageDirection;
+use native::trade::position::PositionState;
+use std::time::Duration;
+use tests_e2e::chaos::Fault;
+use tests_e2e::harness::TestHarness;
+use tests_e2e::wait_until;
+use time::OffsetDateTime;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn rollover_recovers_from_connection_loss_between_renew_accept_and_renew_confirm() {
+    let harness = TestHarness::new_with_open_position().await;
+
+    harness.inject_fault(Fault::SeverAt {
+        direction: MessageDirection::Inbound,
+        kind: "ChannelRenewConfirm",
+    });
+
+    let app_pubkey = api::get_node_id().0;
+    let dlc_channel = harness
+        .coordinator
+        .get_dlc_channels()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|channel| channel.counter_party == app_pubkey)
+        .unwrap();
+
+    let new_expiry = commons::calculate_next_expiry(OffsetDateTime::now_utc(), Network::Regtest);
+
+    harness
+        .coordinator
+        .rollover(&dlc_channel.dlc_channel_id.unwrap())
+        .await
+        .unwrap();
+
+    wait_until!(harness.chaos.has_severed());
+    harness
+        .wait_for_position_state(PositionState::Rollover)
+        .await;
+
+    // The coordinator resends the `RenewConfirm` once the app is back.
+    harness.reconnect_coordinator();
+
+    harness.wait_for_position_state(PositionState::Open).await;
+    assert_eq!(
+        harness.app.rx.position().unwrap().expiry.unix_timestamp(),
+        new_expiry.unix_timestamp()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn position_can_be_closed_over_slow_connection() {
+    let harness = TestHarness::new_with_open_position().await;
+
+    harness.set_coordinator_latency(Duration::from_millis(500));
+
+    harness.close_position().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn position_is_closed_despite_lost_dlc_messages() {
+    let harness = TestHarness::new_with_open_position().await;
+
+    harness.inject_fault(Fault::DropMessages { percent: 30 });
+
+    let position_id = harness.app.rx.position().unwrap().id.to_string();
+    tokio::task::spawn_blocking(move || api::close_position(position_id).unwrap())
+        .await
+        .unwrap();
+
+    // Unacknowledged messages are only resent on reconnect, hence we keep reconnecting until the
+    // protocol has made it through.
+    for _ in 0..10 {
+        if harness.app.rx.position().is_none() {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        harness.disconnect_coordinator();
+        harness.reconnect_coordinator();
+    }
+
+    harness.clear_faults();
+    harness.wait_for_position_closed().await;
+
+    tracing::info!(dropped = harness.chaos.dropped(), "Closed position");
+}
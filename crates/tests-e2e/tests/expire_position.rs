@@ -0,0 +1,32 @@
+use native::health::ServiceStatus;
+use tests_e2e::harness::TestHarness;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn coordinator_closes_expired_position() {
+    let harness = TestHarness::new_with_open_position().await;
+
+    harness.expire_position().await;
+
+    harness.wait_for_position_closed().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn expired_position_is_closed_once_app_reconnects() {
+    let harness = TestHarness::new_with_open_position().await;
+
+    harness.disconnect_coordinator();
+    harness
+        .wait_for_coordinator_status(ServiceStatus::Offline)
+        .await;
+
+    harness.expire_position().await;
+
+    harness.reconnect_coordinator();
+    harness
+        .wait_for_coordinator_status(ServiceStatus::Online)
+        .await;
+
+    harness.wait_for_position_closed().await;
+}
@@ -5,7 +5,7 @@ use native::health::ServiceStatus;
 use native::trade::order::api::NewOrder;
 use native::trade::order::api::OrderType;
 use native::trade::position::PositionState;
-use tests_e2e::setup::TestSetup;
+use tests_e2e::harness::TestHarness;
 use tests_e2e::wait_until;
 use tokio::task::spawn_blocking;
 
@@ -23,7 +23,7 @@ fn dummy_order() -> NewOrder {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore = "need to be run with 'just e2e' command"]
 async fn can_open_position() {
-    let test = TestSetup::new_after_funding().await;
+    let test = TestHarness::new_after_funding().await;
     let app = &test.app;
 
     let order = dummy_order();
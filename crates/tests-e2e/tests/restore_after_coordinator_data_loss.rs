@@ -0,0 +1,76 @@
+use native::api;
+use std::collections::HashSet;
+use tests_e2e::app::run_app;
+use tests_e2e::harness::TestHarness;
+use tests_e2e::logger::init_tracing;
+use tokio::task::spawn_blocking;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "need to be run with 'just e2e' command"]
+async fn app_can_be_restored_after_coordinator_lost_backup() {
+    init_tracing();
+
+    let test = TestHarness::new_with_open_position().await;
+    let coordinator = &test.coordinator;
+
+    let app_pubkey = api::get_node_id().0;
+    let seed_phrase = api::get_seed_phrase();
+
+    let off_chain = test.app.rx.wallet_info().unwrap().balances.off_chain;
+    let positions_before = spawn_blocking(|| api::get_positions().unwrap())
+        .await
+        .unwrap();
+
+    let keys_before = coordinator
+        .get_user_backup_keys(&app_pubkey)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert!(!keys_before.is_empty());
+
+    tracing::info!("Deleting the user backup on the coordinator");
+    coordinator.delete_user_backup(&app_pubkey).await.unwrap();
+    assert!(coordinator
+        .get_user_backup_keys(&app_pubkey)
+        .await
+        .unwrap()
+        .is_empty());
+
+    tracing::info!("Re-uploading a full backup");
+    spawn_blocking(|| api::full_backup().unwrap())
+        .await
+        .unwrap();
+
+    // A full backup must produce the same keys as the incremental backups, otherwise the restore
+    // would not find the data where it expects it.
+    let keys_after = coordinator
+        .get_user_backup_keys(&app_pubkey)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let missing_keys = keys_before.difference(&keys_after).collect::<Vec<_>>();
+    assert!(
+        missing_keys.is_empty(),
+        "Full backup is missing keys: {missing_keys:?}"
+    );
+
+    // kill the app and install it again
+    test.app.stop();
+    tracing::info!("Shutting down app!");
+
+    let app = run_app(Some(seed_phrase.0)).await;
+
+    assert_eq!(api::get_node_id().0, app_pubkey);
+    assert_eq!(app.rx.wallet_info().unwrap().balances.off_chain, off_chain);
+
+    let positions_after = spawn_blocking(|| api::get_positions().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(positions_after.len(), positions_before.len());
+    assert_eq!(
+        positions_after[0].position_state,
+        positions_before[0].position_state
+    );
+}
@@ -1,9 +1,9 @@
 use native::api;
 use native::trade::position::PositionState;
 use tests_e2e::app::run_app;
+use tests_e2e::harness::dummy_order;
+use tests_e2e::harness::TestHarness;
 use tests_e2e::logger::init_tracing;
-use tests_e2e::setup;
-use tests_e2e::setup::dummy_order;
 use tests_e2e::wait_until;
 use tokio::task::spawn_blocking;
 
@@ -12,7 +12,7 @@ use tokio::task::spawn_blocking;
 async fn app_can_be_restored_from_a_backup() {
     init_tracing();
 
-    let test = setup::TestSetup::new_with_open_position().await;
+    let test = TestHarness::new_with_open_position().await;
 
     let seed_phrase = api::get_seed_phrase();
 
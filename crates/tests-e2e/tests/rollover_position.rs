@@ -5,14 +5,14 @@ use native::api;
 use native::trade::position;
 use position::PositionState;
 use tests_e2e::app::AppHandle;
-use tests_e2e::setup;
+use tests_e2e::harness::TestHarness;
 use tests_e2e::wait_until;
 use time::OffsetDateTime;
 
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn can_rollover_position() {
-    let test = setup::TestSetup::new_with_open_position().await;
+    let test = TestHarness::new_with_open_position().await;
     let coordinator = &test.coordinator;
     let dlc_channels = coordinator.get_dlc_channels().await.unwrap();
     let app_pubkey = api::get_node_id().0;
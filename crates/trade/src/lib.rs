@@ -7,8 +7,11 @@ use std::str::FromStr;
 
 pub mod bitmex_client;
 pub mod cfd;
+pub mod pricing;
+pub mod validation;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum ContractSymbol {
     BtcUsd,
 }
@@ -22,6 +25,7 @@ impl ContractSymbol {
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Direction {
     Long,
     Short,
@@ -0,0 +1,265 @@
+//! The pricing of a position, used by both the coordinator and the app so that the two sides can
+//! never disagree on it.
+
+use crate::cfd;
+use crate::Direction;
+use crate::Price;
+use anyhow::Result;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+
+/// The order-matching fee per cent for the taker.
+const TAKER_FEE: (i64, u32) = (30, 4);
+
+/// The price at which a position in `direction` can currently be closed, i.e. the best bid for a
+/// long position and the best ask for a short position.
+pub fn mark_price(price: Price, direction: Direction) -> Decimal {
+    price.get_price_for_direction(direction.opposite())
+}
+
+/// The margin in sats of a party entering a position of `quantity` contracts at `price` with
+/// `leverage`.
+pub fn margin(price: Decimal, quantity: f32, leverage: f32) -> u64 {
+    cfd::calculate_margin(price, quantity, leverage)
+}
+
+/// The price at which the margin of the party in `direction` is used up.
+pub fn liquidation_price(price: Decimal, leverage: f32, direction: Direction) -> Decimal {
+    let leverage = Decimal::try_from(leverage).expect("leverage to fit into decimal");
+
+    match direction {
+        Direction::Long => cfd::calculate_long_liquidation_price(leverage, price),
+        Direction::Short => cfd::calculate_short_liquidation_price(leverage, price),
+    }
+}
+
+/// The profit or loss in sats of the party in `direction`, if the position was closed at
+/// `mark_price`.
+///
+/// The PnL is capped by the margins of both parties, which are derived from their leverages.
+pub fn unrealized_pnl(
+    opening_price: Decimal,
+    mark_price: Decimal,
+    quantity: f32,
+    direction: Direction,
+    long_leverage: f32,
+    short_leverage: f32,
+) -> Result<i64> {
+    let long_margin = margin(opening_price, quantity, long_leverage);
+    let short_margin = margin(opening_price, quantity, short_leverage);
+
+    cfd::calculate_pnl(
+        opening_price,
+        mark_price,
+        quantity,
+        direction,
+        long_margin,
+        short_margin,
+    )
+}
+
+/// The order-matching fee rate of the taker, unless the coordinator is configured otherwise.
+pub fn default_taker_fee_rate() -> Decimal {
+    Decimal::new(TAKER_FEE.0, TAKER_FEE.1)
+}
+
+/// The fee the taker pays for an order of `quantity` contracts matched at `price`.
+pub fn order_matching_fee_taker(quantity: f32, price: Decimal) -> bitcoin::Amount {
+    order_matching_fee(quantity, price, default_taker_fee_rate())
+}
+
+/// The fee for an order of `quantity` contracts matched at `price`, charged at `fee_per_cent`.
+pub fn order_matching_fee(quantity: f32, price: Decimal, fee_per_cent: Decimal) -> bitcoin::Amount {
+    let quantity = Decimal::from_f32(quantity).expect("quantity to fit in Decimal");
+
+    let fee: f64 = match price != Decimal::ZERO {
+        true => {
+            let fee = quantity * (Decimal::ONE / price) * fee_per_cent;
+            fee.round_dp_with_strategy(8, RoundingStrategy::MidpointAwayFromZero)
+                .to_f64()
+                .expect("fee to fit in f64")
+        }
+        false => 0.0,
+    };
+
+    bitcoin::Amount::from_btc(fee).expect("fee to fit in bitcoin::Amount")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn price() -> impl Strategy<Value = Decimal> {
+        (1_000u32..200_000).prop_map(Decimal::from)
+    }
+
+    fn quantity() -> impl Strategy<Value = f32> {
+        (1u32..100_000).prop_map(|quantity| quantity as f32)
+    }
+
+    fn leverage() -> impl Strategy<Value = f32> {
+        prop_oneof![Just(1.0f32), Just(2.0), Just(3.0), Just(5.0), Just(10.0)]
+    }
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop_oneof![Just(Direction::Long), Just(Direction::Short)]
+    }
+
+    proptest! {
+        #[test]
+        fn pnl_is_zero_sum(
+            opening_price in price(),
+            mark_price in price(),
+            quantity in quantity(),
+            long_leverage in leverage(),
+            short_leverage in leverage(),
+        ) {
+            let long = unrealized_pnl(
+                opening_price,
+                mark_price,
+                quantity,
+                Direction::Long,
+                long_leverage,
+                short_leverage,
+            )
+            .unwrap();
+            let short = unrealized_pnl(
+                opening_price,
+                mark_price,
+                quantity,
+                Direction::Short,
+                long_leverage,
+                short_leverage,
+            )
+            .unwrap();
+
+            prop_assert_eq!(long, -short);
+        }
+
+        #[test]
+        fn pnl_is_capped_by_margins(
+            opening_price in price(),
+            mark_price in price(),
+            quantity in quantity(),
+            direction in direction(),
+            long_leverage in leverage(),
+            short_leverage in leverage(),
+        ) {
+            let pnl = unrealized_pnl(
+                opening_price,
+                mark_price,
+                quantity,
+                direction,
+                long_leverage,
+                short_leverage,
+            )
+            .unwrap();
+
+            let long_margin = margin(opening_price, quantity, long_leverage) as i64;
+            let short_margin = margin(opening_price, quantity, short_leverage) as i64;
+            let (own_margin, counterparty_margin) = match direction {
+                Direction::Long => (long_margin, short_margin),
+                Direction::Short => (short_margin, long_margin),
+            };
+
+            prop_assert!(pnl >= -own_margin);
+            prop_assert!(pnl <= counterparty_margin);
+        }
+
+        #[test]
+        fn pnl_is_zero_at_opening_price(
+            opening_price in price(),
+            quantity in quantity(),
+            direction in direction(),
+            long_leverage in leverage(),
+            short_leverage in leverage(),
+        ) {
+            let pnl = unrealized_pnl(
+                opening_price,
+                opening_price,
+                quantity,
+                direction,
+                long_leverage,
+                short_leverage,
+            )
+            .unwrap();
+
+            prop_assert_eq!(pnl, 0);
+        }
+
+        #[test]
+        fn margin_is_lost_at_liquidation_price(
+            opening_price in price(),
+            quantity in quantity(),
+            leverage in leverage(),
+        ) {
+            let liquidation_price = liquidation_price(opening_price, leverage, Direction::Long);
+            let pnl = unrealized_pnl(
+                opening_price,
+                liquidation_price,
+                quantity,
+                Direction::Long,
+                leverage,
+                1.0,
+            )
+            .unwrap();
+
+            let margin = margin(opening_price, quantity, leverage) as i64;
+            // Allow for the rounding to sats.
+            prop_assert!((pnl + margin).abs() <= 1, "pnl {pnl}, margin {margin}");
+        }
+
+        #[test]
+        fn liquidation_price_is_on_the_losing_side(
+            opening_price in price(),
+            leverage in leverage(),
+        ) {
+            let long = liquidation_price(opening_price, leverage, Direction::Long);
+            let short = liquidation_price(opening_price, leverage, Direction::Short);
+
+            prop_assert!(long < opening_price);
+            prop_assert!(short > opening_price);
+        }
+
+        #[test]
+        fn higher_leverage_requires_less_margin(
+            price in price(),
+            quantity in quantity(),
+        ) {
+            prop_assert!(margin(price, quantity, 2.0) <= margin(price, quantity, 1.0));
+            prop_assert!(margin(price, quantity, 10.0) <= margin(price, quantity, 2.0));
+        }
+    }
+
+    #[test]
+    fn mark_price_is_the_closing_side_of_the_quote() {
+        let price = Price {
+            bid: Decimal::from(29_990),
+            ask: Decimal::from(30_010),
+        };
+
+        assert_eq!(mark_price(price, Direction::Long), price.bid);
+        assert_eq!(mark_price(price, Direction::Short), price.ask);
+    }
+
+    #[test]
+    fn calculate_order_matching_fee() {
+        let price = Decimal::new(30209, 0);
+
+        let fee = order_matching_fee(50.0, price, Decimal::new(TAKER_FEE.0, TAKER_FEE.1));
+
+        assert_eq!(fee.to_sat(), 497);
+    }
+
+    #[test]
+    fn calculate_order_matching_fee_with_0() {
+        let price = Decimal::new(0, 0);
+
+        let fee = order_matching_fee(50.0, price, Decimal::new(TAKER_FEE.0, TAKER_FEE.1));
+
+        assert_eq!(fee.to_sat(), 0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Validation of new orders, shared by the coordinator and the app so that an order which passes
+//! in the app is not rejected by the coordinator for the same reason.
+
+use crate::ContractSymbol;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+use rust_decimal_macros::dec;
+
+/// The trading rules of a contract.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContractSpecification {
+    pub min_leverage: f32,
+    pub max_leverage: f32,
+    /// The smallest number of contracts which can be traded.
+    pub min_quantity: Decimal,
+    /// The smallest step by which the price of the contract can change.
+    pub tick_size: Decimal,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum OrderValidationError {
+    #[error("Leverage of {leverage} is below the minimum leverage of {min}")]
+    LeverageTooLow { leverage: f32, min: f32 },
+    #[error("Leverage of {leverage} exceeds the maximum leverage of {max}")]
+    LeverageTooHigh { leverage: f32, max: f32 },
+    #[error("Quantity of {quantity} is below the minimum quantity of {min}")]
+    QuantityTooSmall { quantity: Decimal, min: Decimal },
+    #[error("Limit orders with a price of {0} are not allowed")]
+    InvalidPrice(Decimal),
+}
+
+impl ContractSymbol {
+    pub fn specification(&self) -> ContractSpecification {
+        match self {
+            ContractSymbol::BtcUsd => ContractSpecification {
+                min_leverage: 1.0,
+                max_leverage: 5.0,
+                min_quantity: Decimal::ONE,
+                tick_size: dec!(0.5),
+            },
+        }
+    }
+}
+
+/// Checks the parameters of a new order against the [`ContractSpecification`] of its contract.
+///
+/// The `limit_price` is only set for limit orders, as the price of a market order is determined
+/// by the match.
+pub fn validate_order(
+    contract_symbol: ContractSymbol,
+    leverage: f32,
+    quantity: Decimal,
+    limit_price: Option<Decimal>,
+) -> Result<(), OrderValidationError> {
+    let specification = contract_symbol.specification();
+
+    if leverage < specification.min_leverage {
+        return Err(OrderValidationError::LeverageTooLow {
+            leverage,
+            min: specification.min_leverage,
+        });
+    }
+
+    if leverage > specification.max_leverage {
+        return Err(OrderValidationError::LeverageTooHigh {
+            leverage,
+            max: specification.max_leverage,
+        });
+    }
+
+    if quantity < specification.min_quantity {
+        return Err(OrderValidationError::QuantityTooSmall {
+            quantity,
+            min: specification.min_quantity,
+        });
+    }
+
+    if let Some(price) = limit_price {
+        if price <= Decimal::ZERO {
+            return Err(OrderValidationError::InvalidPrice(price));
+        }
+    }
+
+    Ok(())
+}
+
+/// Rounds `price` to the nearest multiple of the tick size of the contract.
+pub fn round_to_tick_size(contract_symbol: ContractSymbol, price: Decimal) -> Decimal {
+    let tick_size = contract_symbol.specification().tick_size;
+
+    (price / tick_size).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+        * tick_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_within_specification_is_valid() {
+        assert_eq!(
+            validate_order(ContractSymbol::BtcUsd, 2.0, dec!(100), None),
+            Ok(())
+        );
+        assert_eq!(
+            validate_order(ContractSymbol::BtcUsd, 5.0, dec!(1), Some(dec!(30_000))),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn order_outside_specification_is_rejected() {
+        assert_eq!(
+            validate_order(ContractSymbol::BtcUsd, 0.5, dec!(100), None),
+            Err(OrderValidationError::LeverageTooLow {
+                leverage: 0.5,
+                min: 1.0
+            })
+        );
+        assert_eq!(
+            validate_order(ContractSymbol::BtcUsd, 10.0, dec!(100), None),
+            Err(OrderValidationError::LeverageTooHigh {
+                leverage: 10.0,
+                max: 5.0
+            })
+        );
+        assert_eq!(
+            validate_order(ContractSymbol::BtcUsd, 2.0, dec!(0.5), None),
+            Err(OrderValidationError::QuantityTooSmall {
+                quantity: dec!(0.5),
+                min: dec!(1)
+            })
+        );
+        assert_eq!(
+            validate_order(ContractSymbol::BtcUsd, 2.0, dec!(100), Some(Decimal::ZERO)),
+            Err(OrderValidationError::InvalidPrice(Decimal::ZERO))
+        );
+    }
+
+    #[test]
+    fn price_is_rounded_to_tick_size() {
+        let round = |price| round_to_tick_size(ContractSymbol::BtcUsd, price);
+
+        assert_eq!(round(dec!(30_000.2)), dec!(30_000));
+        assert_eq!(round(dec!(30_000.25)), dec!(30_000.5));
+        assert_eq!(round(dec!(30_000.74)), dec!(30_000.5));
+        assert_eq!(round(dec!(30_000.75)), dec!(30_001));
+    }
+}
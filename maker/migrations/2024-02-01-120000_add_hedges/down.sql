@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS "hedges";
+DROP TYPE IF EXISTS "ContractSymbol_Type";
+DROP TYPE IF EXISTS "Exchange_Type";
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TYPE "Exchange_Type" AS ENUM ('Bitmex', 'Deribit');
+CREATE TYPE "ContractSymbol_Type" AS ENUM ('BtcUsd');
+
+CREATE TABLE "hedges" (
+    id SERIAL PRIMARY KEY NOT NULL,
+    exchange "Exchange_Type" NOT NULL,
+    contract_symbol "ContractSymbol_Type" NOT NULL,
+    -- Positive if we bought, negative if we sold.
+    contracts INTEGER NOT NULL,
+    -- Set if the order has been placed.
+    exchange_order_id TEXT,
+    -- Set if the order could not be placed.
+    error TEXT,
+    dry_run BOOLEAN NOT NULL,
+    -- The positions we were hedging.
+    tentenone_contracts INTEGER NOT NULL,
+    exchange_contracts INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -7,9 +7,15 @@ use diesel::PgConnection;
 use ln_dlc_node::node::event::NodeEventHandler;
 use ln_dlc_node::node::InMemoryStore;
 use ln_dlc_node::seed::Bip39Seed;
+use ln_dlc_node::ChainSourceConfig;
 use ln_dlc_node::WalletSettings;
 use maker::cli::Opts;
 use maker::health;
+use maker::hedging::bitmex::Bitmex;
+use maker::hedging::deribit::Deribit;
+use maker::hedging::Exchange;
+use maker::hedging::ExchangeClient;
+use maker::hedging::Hedger;
 use maker::ln::ldk_config;
 use maker::ln::EventHandler;
 use maker::ln_dlc_node_settings;
@@ -23,6 +29,8 @@ use maker::routes::router;
 use maker::run_migration;
 use maker::storage::MakerTenTenOneStorage;
 use maker::trading;
+use maker::trading::quoter::Quoter;
+use maker::trading::quoter::QuotingStatus;
 use rand::thread_rng;
 use rand::RngCore;
 use std::backtrace::Backtrace;
@@ -31,6 +39,8 @@ use std::net::Ipv4Addr;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::task::spawn_blocking;
 use tracing::metadata::LevelFilter;
 
@@ -39,6 +49,11 @@ const PROCESS_PROMETHEUS_METRICS: Duration = Duration::from_secs(10);
 /// Interval after which we'll try to reconnect to the pricefeed again
 const PRICEFEED_RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
 
+/// How many updates about our quotes may be waiting for the quoter.
+const QUOTE_UPDATES_BUFFER: usize = 100;
+
+const DLC_STORAGE_COMPACTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[tokio::main]
 async fn main() -> Result<()> {
     std::panic::set_hook(
@@ -84,6 +99,7 @@ async fn main() -> Result<()> {
     let node_alias = "maker";
 
     let storage = MakerTenTenOneStorage::new(data_dir.to_string_lossy().to_string());
+    let dlc_storage = storage.dlc_storage.clone();
     let node_storage = Arc::new(InMemoryStore::default());
 
     let node = Arc::new(ln_dlc_node::node::Node::new(
@@ -97,7 +113,9 @@ async fn main() -> Result<()> {
         address,
         SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), address.port()),
         announcement_addresses.clone(),
-        opts.esplora.clone(),
+        ChainSourceConfig::Esplora {
+            urls: opts.esplora_urls(),
+        },
         seed,
         ephemeral_randomness,
         ln_dlc_node_settings(opts.rgs_server_url.clone()),
@@ -114,48 +132,100 @@ async fn main() -> Result<()> {
 
     let (health, health_tx) = health::Health::new();
 
-    let bitmex_http_client = bitmex_client::client::Client::new(match network {
-        Network::Bitcoin => bitmex_client::models::Network::Mainnet,
-        _ => bitmex_client::models::Network::Testnet,
-    });
+    let manager = ConnectionManager::<PgConnection>::new(opts.database.clone());
+    let pool = r2d2::Pool::builder()
+        .build(manager)
+        .expect("Failed to create pool.");
 
-    let bitmex_http_client = match (bitmex_api_key.clone(), bitmex_api_secret.clone()) {
-        (Some(bitmex_api_key), Some(bitmex_secret)) => {
-            tracing::info!("BitMEX credentials provided");
-            bitmex_http_client.with_credentials(bitmex_api_key, bitmex_secret)
+    let mut conn = pool.get().expect("to get connection from pool");
+    run_migration(&mut conn);
+
+    let exchange_client: Arc<dyn ExchangeClient> = match opts.hedging_exchange {
+        Exchange::Bitmex => {
+            let bitmex_http_client = bitmex_client::client::Client::new(match network {
+                Network::Bitcoin => bitmex_client::models::Network::Mainnet,
+                _ => bitmex_client::models::Network::Testnet,
+            });
+
+            let bitmex_http_client = match (bitmex_api_key.clone(), bitmex_api_secret.clone()) {
+                (Some(bitmex_api_key), Some(bitmex_secret)) => {
+                    tracing::info!("BitMEX credentials provided");
+                    bitmex_http_client.with_credentials(bitmex_api_key, bitmex_secret)
+                }
+                _ => {
+                    tracing::info!("BitMEX credentials not provided");
+                    bitmex_http_client
+                }
+            };
+
+            Arc::new(Bitmex::new(bitmex_http_client))
         }
-        _ => {
-            tracing::info!("BitMEX credentials not provided");
-            bitmex_http_client
+        Exchange::Deribit => {
+            let deribit = Deribit::new(network);
+
+            let deribit = match (
+                opts.deribit_client_id.clone(),
+                opts.deribit_client_secret.clone(),
+            ) {
+                (Some(client_id), Some(client_secret)) => {
+                    tracing::info!("Deribit credentials provided");
+                    deribit.with_credentials(client_id, client_secret)
+                }
+                _ => {
+                    tracing::info!("Deribit credentials not provided");
+                    deribit
+                }
+            };
+
+            Arc::new(deribit)
         }
     };
 
+    let hedger = Hedger::new(
+        exchange_client,
+        opts.hedging_dry_run,
+        pool.clone(),
+        opts.hedge_drift_alert_threshold,
+        health_tx.hedging,
+    )?;
+
     let (position_manager, mailbox) = xtra::Mailbox::unbounded();
-    tokio::spawn(xtra::run(
-        mailbox,
-        position::Manager::new(bitmex_http_client),
+    tokio::spawn(xtra::run(mailbox, position::Manager::new(hedger)));
+
+    // We only follow our BitMEX position if that is where we actually hedge.
+    let (bitmex_api_key, bitmex_api_secret) =
+        if opts.hedging_exchange == Exchange::Bitmex && !opts.hedging_dry_run {
+            (bitmex_api_key, bitmex_api_secret)
+        } else {
+            (None, None)
+        };
+
+    let (index_price_tx, index_price_rx) = watch::channel(None);
+    tokio::spawn(trading::run(
+        network,
+        index_price_tx,
+        health_tx.bitmex_pricefeed,
+        position_manager.clone(),
+        bitmex_api_key,
+        bitmex_api_secret,
+        PRICEFEED_RECONNECT_INTERVAL,
     ));
 
     let node_pubkey = node.info.pubkey;
-    tokio::spawn({
-        let orderbook_url = opts.orderbook.clone();
-        let position_manager = position_manager.clone();
-        async move {
-            trading::run(
-                &orderbook_url,
-                node_pubkey,
-                network,
-                opts.concurrent_orders,
-                time::Duration::seconds(opts.order_expiry_after_seconds as i64),
-                health_tx.bitmex_pricefeed,
-                position_manager,
-                bitmex_api_key,
-                bitmex_api_secret,
-                PRICEFEED_RECONNECT_INTERVAL,
-            )
-            .await;
-        }
-    });
+    let quoting_params = opts.quoting_params();
+    let (quoting_status_tx, quoting_status_rx) = watch::channel(QuotingStatus::new(quoting_params));
+    let (quote_updates_tx, quote_updates_rx) = mpsc::channel(QUOTE_UPDATES_BUFFER);
+    tokio::spawn(
+        Quoter::new(
+            quoting_params,
+            opts.orderbook.clone(),
+            node_pubkey,
+            time::Duration::seconds(opts.order_expiry_after_seconds as i64),
+            position_manager.clone(),
+            quoting_status_tx,
+        )
+        .run(index_price_rx, quote_updates_rx),
+    );
 
     let _monitor_coordinator_status = tokio::spawn({
         let endpoint = opts.orderbook.clone();
@@ -181,13 +251,24 @@ async fn main() -> Result<()> {
         }
     });
 
-    let manager = ConnectionManager::<PgConnection>::new(opts.database);
-    let pool = r2d2::Pool::builder()
-        .build(manager)
-        .expect("Failed to create pool.");
+    let _compact_dlc_storage = tokio::spawn(async move {
+        loop {
+            let dlc_storage = dlc_storage.clone();
+            let result = spawn_blocking(move || {
+                dlc_storage.compact()?;
+                dlc_storage.stats()
+            })
+            .await
+            .expect("To spawn blocking thread");
+
+            match result {
+                Ok(stats) => tracing::info!(?stats, "DLC storage stats"),
+                Err(e) => tracing::error!("Failed to compact DLC storage: {e:#}"),
+            }
 
-    let mut conn = pool.get().expect("to get connection from pool");
-    run_migration(&mut conn);
+            tokio::time::sleep(DLC_STORAGE_COMPACTION_INTERVAL).await;
+        }
+    });
 
     orderbook_ws::Client::new(
         opts.orderbook,
@@ -195,6 +276,7 @@ async fn main() -> Result<()> {
         node.node_key(),
         position_manager.clone(),
         health_tx.orderbook,
+        quote_updates_tx,
     )
     .spawn_supervised_connection();
 
@@ -207,6 +289,7 @@ async fn main() -> Result<()> {
         health,
         announcement_addresses.clone(),
         node_alias,
+        quoting_status_rx,
     );
 
     let addr = SocketAddr::from((http_address.ip(), http_address.port()));
@@ -1,7 +1,11 @@
+use crate::hedging::Exchange;
+use crate::trading::strategy::QuotingParams;
 use anyhow::Result;
 use clap::Parser;
 use ln_dlc_node::node::OracleInfo;
+use ln_dlc_node::node::OracleProtocol;
 use reqwest::Url;
+use rust_decimal::Decimal;
 use std::env::current_dir;
 use std::net::SocketAddr;
 use std::path::PathBuf;
@@ -38,11 +42,17 @@ pub struct Opts {
     #[clap(long, default_value = "http://localhost:3000")]
     pub esplora: String,
 
+    /// Esplora server endpoints to fail over to if the primary one is unreachable, in order of
+    /// priority.
+    #[clap(long)]
+    pub esplora_fallback: Vec<String>,
+
     /// If enabled logs will be in JSON format.
     #[clap(short, long)]
     pub json: bool,
 
-    /// Amount of concurrent orders (buy,sell) that the maker will create at a time.
+    /// Amount of concurrent orders (buy,sell) that the maker will create at a time, i.e. the
+    /// number of quotes on each side.
     #[clap(long, default_value = "5")]
     pub concurrent_orders: usize,
 
@@ -50,6 +60,29 @@ pub struct Opts {
     #[clap(long, default_value = "60")]
     pub order_expiry_after_seconds: u64,
 
+    /// The distance between the best bid and the best ask, in basis points of the index price.
+    /// Every further quote is half a spread further away from the index price.
+    #[clap(long, default_value = "10")]
+    pub spread_bps: Decimal,
+
+    /// The number of contracts of every quote while the maker is not exposed.
+    #[clap(long, default_value = "5000")]
+    pub order_size: Decimal,
+
+    /// The number of contracts the maker is willing to be exposed to, in either direction. The
+    /// side which would increase the exposure is not quoted anymore once this has been reached.
+    #[clap(long, default_value = "100000")]
+    pub max_inventory: Decimal,
+
+    /// How far the quotes are shifted away from the index price at the maximum inventory, in basis
+    /// points.
+    #[clap(long, default_value = "10")]
+    pub skew_bps: Decimal,
+
+    /// How far the index price has to move before the maker quotes again, in basis points.
+    #[clap(long, default_value = "5")]
+    pub requote_threshold_bps: Decimal,
+
     /// The oracle endpoint.
     #[clap(long, default_value = "http://localhost:8081")]
     oracle_endpoint: String,
@@ -69,6 +102,27 @@ pub struct Opts {
     #[clap(long)]
     pub bitmex_api_secret: Option<String>,
 
+    /// The exchange on which the maker hedges its 10101 position.
+    #[clap(long, value_enum, default_value = "bitmex")]
+    pub hedging_exchange: Exchange,
+
+    /// If enabled hedging orders are only recorded, but not placed on the exchange.
+    #[clap(long)]
+    pub hedging_dry_run: bool,
+
+    /// Deribit API client ID.
+    #[clap(long)]
+    pub deribit_client_id: Option<String>,
+
+    /// Deribit API client secret.
+    #[clap(long)]
+    pub deribit_client_secret: Option<String>,
+
+    /// By how many contracts the position on the hedging exchange may differ from the 10101
+    /// position, before the maker raises an alert.
+    #[clap(long, default_value = "1000")]
+    pub hedge_drift_alert_threshold: u32,
+
     /// RGS server URL.
     #[clap(long)]
     pub rgs_server_url: Option<String>,
@@ -113,6 +167,24 @@ impl Opts {
         Ok(data_dir)
     }
 
+    /// The esplora endpoints in order of priority.
+    pub fn esplora_urls(&self) -> Vec<String> {
+        std::iter::once(self.esplora.clone())
+            .chain(self.esplora_fallback.iter().cloned())
+            .collect()
+    }
+
+    pub fn quoting_params(&self) -> QuotingParams {
+        QuotingParams {
+            spread_bps: self.spread_bps,
+            levels: self.concurrent_orders,
+            order_size: self.order_size,
+            max_inventory: self.max_inventory,
+            skew_bps: self.skew_bps,
+            requote_threshold_bps: self.requote_threshold_bps,
+        }
+    }
+
     pub fn get_oracle_info(&self) -> OracleInfo {
         OracleInfo {
             endpoint: self.oracle_endpoint.clone(),
@@ -121,6 +193,7 @@ impl Opts {
                 .as_str()
                 .parse()
                 .expect("Valid oracle public key"),
+            protocol: OracleProtocol::P2pd,
         }
     }
 }
@@ -0,0 +1,2 @@
+pub mod custom_types;
+pub mod hedges;
@@ -0,0 +1,71 @@
+use crate::hedging::Exchange;
+use crate::position::ContractSymbol;
+use crate::schema::sql_types::ContractSymbolType;
+use crate::schema::sql_types::ExchangeType;
+use diesel::deserialize;
+use diesel::deserialize::FromSql;
+use diesel::pg::Pg;
+use diesel::pg::PgValue;
+use diesel::query_builder::QueryId;
+use diesel::serialize;
+use diesel::serialize::IsNull;
+use diesel::serialize::Output;
+use diesel::serialize::ToSql;
+use std::any::TypeId;
+use std::io::Write;
+
+impl QueryId for ExchangeType {
+    type QueryId = ExchangeType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+impl QueryId for ContractSymbolType {
+    type QueryId = ContractSymbolType;
+    const HAS_STATIC_QUERY_ID: bool = false;
+
+    fn query_id() -> Option<TypeId> {
+        None
+    }
+}
+
+impl ToSql<ExchangeType, Pg> for Exchange {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            Exchange::Bitmex => out.write_all(b"Bitmex")?,
+            Exchange::Deribit => out.write_all(b"Deribit")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<ExchangeType, Pg> for Exchange {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"Bitmex" => Ok(Exchange::Bitmex),
+            b"Deribit" => Ok(Exchange::Deribit),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+impl ToSql<ContractSymbolType, Pg> for ContractSymbol {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            ContractSymbol::BtcUsd => out.write_all(b"BtcUsd")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<ContractSymbolType, Pg> for ContractSymbol {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"BtcUsd" => Ok(ContractSymbol::BtcUsd),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
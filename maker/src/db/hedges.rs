@@ -0,0 +1,58 @@
+use crate::hedging::Exchange;
+use crate::position::ContractSymbol;
+use crate::schema::hedges;
+use anyhow::ensure;
+use anyhow::Result;
+use diesel::dsl::sum;
+use diesel::ExpressionMethods;
+use diesel::Insertable;
+use diesel::PgConnection;
+use diesel::QueryDsl;
+use diesel::RunQueryDsl;
+
+/// An order we have placed, or tried to place, on an exchange to hedge our 10101 position.
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = hedges)]
+pub struct NewHedge {
+    pub exchange: Exchange,
+    pub contract_symbol: ContractSymbol,
+    /// Positive if we bought, negative if we sold.
+    pub contracts: i32,
+    pub exchange_order_id: Option<String>,
+    pub error: Option<String>,
+    pub dry_run: bool,
+    /// The 10101 position we were hedging.
+    pub tentenone_contracts: i32,
+    /// Our position on the exchange before the order.
+    pub exchange_contracts: i32,
+}
+
+pub fn insert(conn: &mut PgConnection, hedge: NewHedge) -> Result<()> {
+    let affected_rows = diesel::insert_into(hedges::table)
+        .values(hedge)
+        .execute(conn)?;
+
+    ensure!(affected_rows > 0, "Could not insert hedge");
+
+    Ok(())
+}
+
+/// The position we would have on the `exchange`, had all the orders placed in dry-run mode been
+/// executed.
+pub fn get_dry_run_position(
+    conn: &mut PgConnection,
+    exchange: Exchange,
+    contract_symbol: ContractSymbol,
+) -> Result<i32> {
+    let contracts: Option<i64> = hedges::table
+        .filter(hedges::exchange.eq(exchange))
+        .filter(hedges::contract_symbol.eq(contract_symbol))
+        .filter(hedges::dry_run.eq(true))
+        .filter(hedges::exchange_order_id.is_not_null())
+        .select(sum(hedges::contracts))
+        .first(conn)?;
+
+    let contracts = i32::try_from(contracts.unwrap_or_default())?;
+
+    Ok(contracts)
+}
@@ -31,6 +31,8 @@ pub struct Health {
     orderbook_rx: watch::Receiver<ServiceStatus>,
     /// Bitmex pricefeed stream status
     bitmex_pricefeed_rx: watch::Receiver<ServiceStatus>,
+    /// Whether our hedge is within the drift threshold
+    hedging_rx: watch::Receiver<ServiceStatus>,
 }
 
 /// Transmitters that need to be plugged in the services that need to publish their health status.
@@ -38,6 +40,7 @@ pub struct Tx {
     pub orderbook: watch::Sender<ServiceStatus>,
     pub coordinator: watch::Sender<ServiceStatus>,
     pub bitmex_pricefeed: watch::Sender<ServiceStatus>,
+    pub hedging: watch::Sender<ServiceStatus>,
 }
 
 /// Struct returned by maker's health endpoint.
@@ -46,6 +49,8 @@ pub struct OverallMakerHealth {
     coordinator: ServiceStatus,
     orderbook: ServiceStatus,
     bitmex_pricefeed: ServiceStatus,
+    /// Not part of the maker's health, as the maker can still serve its purpose without hedging.
+    hedging: ServiceStatus,
 }
 
 impl OverallMakerHealth {
@@ -61,17 +66,20 @@ impl Health {
         let (orderbook_tx, orderbook_rx) = watch::channel(ServiceStatus::Unknown);
         let (coordinator_tx, coordinator_rx) = watch::channel(ServiceStatus::Unknown);
         let (bitmex_pricefeed_tx, bitmex_pricefeed_rx) = watch::channel(ServiceStatus::Unknown);
+        let (hedging_tx, hedging_rx) = watch::channel(ServiceStatus::Unknown);
 
         (
             Self {
                 coordinator_rx,
                 orderbook_rx,
                 bitmex_pricefeed_rx,
+                hedging_rx,
             },
             Tx {
                 orderbook: orderbook_tx,
                 coordinator: coordinator_tx,
                 bitmex_pricefeed: bitmex_pricefeed_tx,
+                hedging: hedging_tx,
             },
         )
     }
@@ -81,6 +89,7 @@ impl Health {
             coordinator: self.get_coordinator_status(),
             orderbook: self.get_orderbook_status(),
             bitmex_pricefeed: self.get_bitmex_pricefeed_status(),
+            hedging: self.get_hedging_status(),
         };
 
         match health_info.is_healthy() {
@@ -102,6 +111,10 @@ impl Health {
     pub fn get_bitmex_pricefeed_status(&self) -> ServiceStatus {
         *self.bitmex_pricefeed_rx.borrow()
     }
+
+    pub fn get_hedging_status(&self) -> ServiceStatus {
+        *self.hedging_rx.borrow()
+    }
 }
 
 /// Simple way of checking if a service is online or offline
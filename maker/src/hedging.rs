@@ -0,0 +1,206 @@
+//! Hedging of the maker's 10101 position on an external exchange.
+//!
+//! Every order placed to hedge is recorded in the database, together with the positions it was
+//! based on. In dry-run mode no order reaches the exchange: the orders are only recorded, and the
+//! position they would have resulted in is simulated.
+
+use crate::db;
+use crate::db::hedges::NewHedge;
+use crate::health::ServiceStatus;
+use crate::position::ContractSymbol;
+use crate::schema::sql_types::ExchangeType;
+use anyhow::Result;
+use async_trait::async_trait;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::Pool;
+use diesel::AsExpression;
+use diesel::FromSqlRow;
+use diesel::PgConnection;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::task::spawn_blocking;
+
+pub mod bitmex;
+pub mod deribit;
+mod dry_run;
+
+/// The exchanges we can hedge on.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, clap::ValueEnum, FromSqlRow, AsExpression,
+)]
+#[diesel(sql_type = ExchangeType)]
+pub enum Exchange {
+    Bitmex,
+    Deribit,
+}
+
+/// A client of an exchange we can hedge on.
+#[async_trait]
+pub trait ExchangeClient: Send + Sync {
+    fn exchange(&self) -> Exchange;
+
+    /// Place a market order for `contracts`: buy if positive, sell if negative.
+    ///
+    /// Returns the ID the exchange assigned to the order.
+    async fn create_order(&self, contract_symbol: ContractSymbol, contracts: i32)
+        -> Result<String>;
+
+    /// Our position on the exchange: positive if we are long, negative if we are short.
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32>;
+}
+
+/// Mirrors the maker's 10101 position on an exchange.
+pub struct Hedger {
+    client: Arc<dyn ExchangeClient>,
+    dry_run: bool,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    /// By how many contracts our position on the exchange may differ from our 10101 position,
+    /// before we raise an alert.
+    drift_alert_threshold: u32,
+    status: watch::Sender<ServiceStatus>,
+}
+
+impl Hedger {
+    pub fn new(
+        client: Arc<dyn ExchangeClient>,
+        dry_run: bool,
+        pool: Pool<ConnectionManager<PgConnection>>,
+        drift_alert_threshold: u32,
+        status: watch::Sender<ServiceStatus>,
+    ) -> Result<Self> {
+        let client: Arc<dyn ExchangeClient> = if dry_run {
+            let exchange = client.exchange();
+
+            let mut conn = pool.get()?;
+            let btc_usd =
+                db::hedges::get_dry_run_position(&mut conn, exchange, ContractSymbol::BtcUsd)?;
+
+            tracing::info!(?exchange, %btc_usd, "Hedging in dry-run mode");
+
+            Arc::new(dry_run::DryRun::new(
+                exchange,
+                [(ContractSymbol::BtcUsd, btc_usd)],
+            ))
+        } else {
+            client
+        };
+
+        Ok(Self {
+            client,
+            dry_run,
+            pool,
+            drift_alert_threshold,
+            status,
+        })
+    }
+
+    pub fn exchange(&self) -> Exchange {
+        self.client.exchange()
+    }
+
+    /// Our position on the exchange, as reported by the exchange.
+    pub async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32> {
+        self.client.position(contract_symbol).await
+    }
+
+    /// Place an order for `contracts` on the exchange and record it, whether it succeeds or not.
+    ///
+    /// `tentenone` and `exchange` are the positions the order is based on.
+    pub async fn hedge(
+        &self,
+        contract_symbol: ContractSymbol,
+        contracts: i32,
+        tentenone: i32,
+        exchange: i32,
+    ) -> Result<()> {
+        tracing::info!(
+            exchange_name = ?self.exchange(),
+            ?contract_symbol,
+            %contracts,
+            %tentenone,
+            %exchange,
+            dry_run = %self.dry_run,
+            "Placing hedging order"
+        );
+
+        let result = self.client.create_order(contract_symbol, contracts).await;
+
+        let (exchange_order_id, error) = match &result {
+            Ok(order_id) => (Some(order_id.clone()), None),
+            Err(e) => (None, Some(format!("{e:#}"))),
+        };
+
+        let hedge = NewHedge {
+            exchange: self.exchange(),
+            contract_symbol,
+            contracts,
+            exchange_order_id,
+            error,
+            dry_run: self.dry_run,
+            tentenone_contracts: tentenone,
+            exchange_contracts: exchange,
+        };
+
+        let pool = self.pool.clone();
+        let inserted = spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            db::hedges::insert(&mut conn, hedge)
+        })
+        .await
+        .expect("task to complete");
+
+        // The order has been placed regardless, hence we only log the failure.
+        if let Err(e) = inserted {
+            tracing::error!(?contract_symbol, %contracts, "Failed to record hedge: {e:#}");
+        }
+
+        result.map(|_| ())
+    }
+
+    /// Raise an alert if our position on the exchange differs from our 10101 position by more
+    /// than the configured threshold.
+    pub fn check_drift(&self, contract_symbol: ContractSymbol, tentenone: i32, exchange: i32) {
+        let status = if is_drift_exceeded(self.drift_alert_threshold, tentenone, exchange) {
+            tracing::error!(
+                exchange_name = ?self.exchange(),
+                ?contract_symbol,
+                %tentenone,
+                %exchange,
+                threshold = %self.drift_alert_threshold,
+                "Hedge drift exceeds threshold"
+            );
+
+            ServiceStatus::Offline
+        } else {
+            ServiceStatus::Online
+        };
+
+        self.status.send_replace(status);
+    }
+}
+
+fn is_drift_exceeded(threshold: u32, tentenone: i32, exchange: i32) -> bool {
+    let drift = (i64::from(tentenone) - i64::from(exchange)).unsigned_abs();
+
+    drift > u64::from(threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_within_threshold() {
+        assert!(!is_drift_exceeded(1000, 0, 0));
+        assert!(!is_drift_exceeded(1000, 1099, 100));
+        assert!(!is_drift_exceeded(1000, -500, 500));
+    }
+
+    #[test]
+    fn drift_beyond_threshold() {
+        assert!(is_drift_exceeded(1000, 1101, 100));
+        assert!(is_drift_exceeded(1000, -501, 500));
+        assert!(is_drift_exceeded(0, 0, 100));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::hedging::Exchange;
+use crate::hedging::ExchangeClient;
+use crate::position::ContractSymbol;
+use anyhow::Result;
+use async_trait::async_trait;
+use bitmex_client::models::Side;
+
+pub struct Bitmex {
+    client: bitmex_client::client::Client,
+}
+
+impl Bitmex {
+    pub fn new(client: bitmex_client::client::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for Bitmex {
+    fn exchange(&self) -> Exchange {
+        Exchange::Bitmex
+    }
+
+    async fn create_order(
+        &self,
+        contract_symbol: ContractSymbol,
+        contracts: i32,
+    ) -> Result<String> {
+        let side = if contracts.is_positive() {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+
+        let order = self
+            .client
+            .create_order(symbol(contract_symbol), contracts.abs(), side, None)
+            .await?;
+
+        Ok(order.order_id.to_string())
+    }
+
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32> {
+        let contracts = self
+            .client
+            .positions()
+            .await?
+            .into_iter()
+            .find(|position| position.symbol == symbol(contract_symbol))
+            .and_then(|position| position.current_qty)
+            .unwrap_or_default();
+
+        let contracts = i32::try_from(contracts)?;
+
+        Ok(contracts)
+    }
+}
+
+fn symbol(contract_symbol: ContractSymbol) -> bitmex_client::models::ContractSymbol {
+    match contract_symbol {
+        ContractSymbol::BtcUsd => bitmex_client::models::ContractSymbol::XbtUsd,
+    }
+}
@@ -0,0 +1,231 @@
+//! A minimal client of Deribit's [JSON-RPC over HTTP API](https://docs.deribit.com/), covering
+//! what we need to hedge.
+
+use crate::hedging::Exchange;
+use crate::hedging::ExchangeClient;
+use crate::position::ContractSymbol;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::Network;
+use serde::de::DeserializeOwned;
+
+const MAINNET_URL: &str = "https://www.deribit.com";
+const TESTNET_URL: &str = "https://test.deribit.com";
+
+pub struct Deribit {
+    client: reqwest::Client,
+    url: &'static str,
+    credentials: Option<Credentials>,
+}
+
+struct Credentials {
+    client_id: String,
+    client_secret: String,
+}
+
+impl Deribit {
+    pub fn new(network: Network) -> Self {
+        let url = match network {
+            Network::Bitcoin => MAINNET_URL,
+            _ => TESTNET_URL,
+        };
+
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            credentials: None,
+        }
+    }
+
+    pub fn with_credentials(self, client_id: String, client_secret: String) -> Self {
+        Self {
+            credentials: Some(Credentials {
+                client_id,
+                client_secret,
+            }),
+            ..self
+        }
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        let credentials = self
+            .credentials
+            .as_ref()
+            .context("Deribit credentials not provided")?;
+
+        let auth: wire::Auth = self
+            .call(
+                "public/auth",
+                &[
+                    ("grant_type", "client_credentials".to_string()),
+                    ("client_id", credentials.client_id.clone()),
+                    ("client_secret", credentials.client_secret.clone()),
+                ],
+                None,
+            )
+            .await
+            .context("Failed to authenticate")?;
+
+        Ok(auth.access_token)
+    }
+
+    async fn call_private<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<T> {
+        let access_token = self.access_token().await?;
+
+        self.call(&format!("private/{method}"), params, Some(access_token))
+            .await
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+        access_token: Option<String>,
+    ) -> Result<T> {
+        let mut request = self
+            .client
+            .get(format!("{}/api/v2/{method}", self.url))
+            .query(params);
+
+        if let Some(access_token) = access_token {
+            request = request.bearer_auth(access_token);
+        }
+
+        // Deribit reports errors in the body, regardless of the status code.
+        let response = request.send().await?.text().await?;
+
+        parse_response(&response).with_context(|| format!("Failed to call {method}"))
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for Deribit {
+    fn exchange(&self) -> Exchange {
+        Exchange::Deribit
+    }
+
+    async fn create_order(
+        &self,
+        contract_symbol: ContractSymbol,
+        contracts: i32,
+    ) -> Result<String> {
+        let method = if contracts.is_positive() {
+            "buy"
+        } else {
+            "sell"
+        };
+
+        // The amount of perpetual contracts is denominated in USD, just like ours.
+        let order: wire::OrderResponse = self
+            .call_private(
+                method,
+                &[
+                    ("instrument_name", instrument_name(contract_symbol)),
+                    ("amount", contracts.abs().to_string()),
+                    ("type", "market".to_string()),
+                ],
+            )
+            .await?;
+
+        Ok(order.order.order_id)
+    }
+
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32> {
+        let position: wire::Position = self
+            .call_private(
+                "get_position",
+                &[("instrument_name", instrument_name(contract_symbol))],
+            )
+            .await?;
+
+        Ok(position.size.round() as i32)
+    }
+}
+
+fn instrument_name(contract_symbol: ContractSymbol) -> String {
+    match contract_symbol {
+        ContractSymbol::BtcUsd => "BTC-PERPETUAL".to_string(),
+    }
+}
+
+fn parse_response<T: DeserializeOwned>(response: &str) -> Result<T> {
+    let response: wire::Response<T> = serde_json::from_str(response).context("Invalid response")?;
+
+    match (response.result, response.error) {
+        (Some(result), None) => Ok(result),
+        (_, Some(error)) => bail!("Deribit error {}: {}", error.code, error.message),
+        (None, None) => bail!("Neither result nor error in response"),
+    }
+}
+
+mod wire {
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    pub struct Response<T> {
+        pub result: Option<T>,
+        pub error: Option<Error>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Error {
+        pub code: i64,
+        pub message: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Auth {
+        pub access_token: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct OrderResponse {
+        pub order: Order,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Order {
+        pub order_id: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Position {
+        /// Positive if long, negative if short, in USD for perpetuals.
+        pub size: f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_order_response() {
+        let order: wire::OrderResponse = parse_response(r#"{"jsonrpc":"2.0","id":1,"result":{"trades":[],"order":{"order_id":"4008965646","order_state":"filled","instrument_name":"BTC-PERPETUAL","direction":"sell","amount":100.0,"order_type":"market"}},"usIn":1590000000000000,"usOut":1590000000000100,"usDiff":100,"testnet":true}"#).unwrap();
+
+        assert_eq!(order.order.order_id, "4008965646");
+    }
+
+    #[test]
+    fn parse_position_response() {
+        let position: wire::Position = parse_response(r#"{"jsonrpc":"2.0","id":2,"result":{"size":-200.0,"instrument_name":"BTC-PERPETUAL","direction":"sell","kind":"future","average_price":42000.0}}"#).unwrap();
+
+        assert_eq!(position.size, -200.0);
+    }
+
+    #[test]
+    fn parse_error_response() {
+        let error = parse_response::<wire::Position>(
+            r#"{"jsonrpc":"2.0","id":3,"error":{"code":13009,"message":"unauthorized"}}"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.to_string(), "Deribit error 13009: unauthorized");
+    }
+}
@@ -0,0 +1,70 @@
+use crate::hedging::Exchange;
+use crate::hedging::ExchangeClient;
+use crate::position::ContractSymbol;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Pretends to place orders on an exchange, keeping track of the position they would result in.
+pub struct DryRun {
+    exchange: Exchange,
+    positions: Mutex<HashMap<ContractSymbol, i32>>,
+}
+
+impl DryRun {
+    pub fn new(
+        exchange: Exchange,
+        positions: impl IntoIterator<Item = (ContractSymbol, i32)>,
+    ) -> Self {
+        Self {
+            exchange,
+            positions: Mutex::new(HashMap::from_iter(positions)),
+        }
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for DryRun {
+    fn exchange(&self) -> Exchange {
+        self.exchange
+    }
+
+    async fn create_order(
+        &self,
+        contract_symbol: ContractSymbol,
+        contracts: i32,
+    ) -> Result<String> {
+        let mut positions = self.positions.lock().expect("mutex not to be poisoned");
+        *positions.entry(contract_symbol).or_default() += contracts;
+
+        Ok(format!("dry-run-{}", Uuid::new_v4()))
+    }
+
+    async fn position(&self, contract_symbol: ContractSymbol) -> Result<i32> {
+        let positions = self.positions.lock().expect("mutex not to be poisoned");
+
+        Ok(positions.get(&contract_symbol).copied().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn orders_update_simulated_position() {
+        let dry_run = DryRun::new(Exchange::Deribit, [(ContractSymbol::BtcUsd, 200)]);
+
+        dry_run
+            .create_order(ContractSymbol::BtcUsd, -500)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            dry_run.position(ContractSymbol::BtcUsd).await.unwrap(),
+            -300
+        );
+    }
+}
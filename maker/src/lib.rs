@@ -4,10 +4,13 @@ use diesel_migrations::EmbeddedMigrations;
 use diesel_migrations::MigrationHarness;
 use ln_dlc_node::node::GossipSourceConfig;
 use ln_dlc_node::node::LnDlcNodeSettings;
+use ln_dlc_node::node::ZeroConfTrustPolicy;
 use std::time::Duration;
 
 pub mod cli;
+pub mod db;
 pub mod health;
+pub mod hedging;
 pub mod ln;
 pub mod logger;
 pub mod metrics;
@@ -43,5 +46,10 @@ pub fn ln_dlc_node_settings(rgs_server_url: Option<String>) -> LnDlcNodeSettings
         bdk_client_stop_gap: 20,
         bdk_client_concurrency: 4,
         gossip_source_config,
+        mempool_space_url: None,
+        // The maker accepts every inbound channel as zero-conf channel.
+        zero_conf_trust_policy: ZeroConfTrustPolicy::TrustEverybody,
+        watchtower_url: None,
+        async_payment_hold_timeout: Duration::from_secs(600),
     }
 }
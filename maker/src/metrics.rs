@@ -34,6 +34,10 @@ lazy_static! {
         .with_description("Bitmex pricefeed status")
         .init();
 
+    pub static ref HEDGING_STATUS: ObservableGauge<u64> = METER.u64_observable_gauge("hedging_status")
+        .with_description("Hedging status, offline if the hedge drifts beyond the threshold")
+        .init();
+
     // channel details metrics
     pub static ref CHANNEL_BALANCE_SATOSHI: ObservableGauge<u64> = METER
         .u64_observable_gauge("channel_balance_satoshi")
@@ -91,6 +95,7 @@ fn health_metrics(cx: &Context, health: &Health) {
         health.get_bitmex_pricefeed_status(),
         &BITMEX_PRICEFEED_STATUS,
     );
+    update_health_metric(cx, health.get_hedging_status(), &HEDGING_STATUS);
 }
 
 /// Updates the health metric given a service status
@@ -11,12 +11,17 @@ use bitcoin::secp256k1::SECP256K1;
 use commons::FilledWith;
 use commons::Message;
 use commons::OrderbookRequest;
+use commons::QuoteEvent;
+use commons::QuoteEventKind;
 use futures::FutureExt;
 use futures::SinkExt;
 use futures::TryStreamExt;
 use reqwest::Url;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use tokio::sync::watch;
+use tokio::sync::Notify;
 use tokio_tungstenite::tungstenite;
 
 const RECONNECT_TIMEOUT: Duration = Duration::from_secs(2);
@@ -35,6 +40,8 @@ pub struct Client {
     position_manager: xtra::Address<position::Manager>,
     /// Where to send the current status of the orderbook (for system health)
     orderbook_status: watch::Sender<ServiceStatus>,
+    /// Where to forward the updates about our quotes.
+    quote_updates: mpsc::Sender<QuoteEvent>,
 }
 
 impl Client {
@@ -44,11 +51,12 @@ impl Client {
         auth_sk: SecretKey,
         position_manager: xtra::Address<position::Manager>,
         orderbook_status: watch::Sender<ServiceStatus>,
+        quote_updates: mpsc::Sender<QuoteEvent>,
     ) -> Self {
         endpoint
             .set_scheme("ws")
             .expect("To be able to change to ws");
-        endpoint.set_path("/api/orderbook/websocket");
+        endpoint.set_path("/api/v1/orderbook/websocket");
         let url = endpoint.to_string();
 
         Self {
@@ -57,12 +65,14 @@ impl Client {
             auth_sk,
             position_manager,
             orderbook_status,
+            quote_updates,
         }
     }
 
     /// Spawn a task which subscribes to the orderbook's WebSocket API.
     ///
-    /// The maker uses this to learn about the orders which resulted in a match.
+    /// The maker uses this to learn about the orders which resulted in a match, and about updates
+    /// of its quotes.
     ///
     /// The task will attempt to reconnect to the WebSocket API if it encounters any errors.
     pub fn spawn_supervised_connection(self) {
@@ -71,6 +81,10 @@ impl Client {
         let url = self.url.clone();
         let position_manager = self.position_manager;
         let orderbook_status = self.orderbook_status;
+        let quote_updates = self.quote_updates;
+        // Lets us ask for the filled matches as soon as one of our quotes has been filled, so that
+        // the position is up to date before the next quotes are posted.
+        let filled = Arc::new(Notify::new());
 
         tokio::spawn(async move {
             let auth_pk = auth_sk.public_key(SECP256K1);
@@ -85,10 +99,13 @@ impl Client {
             loop {
                 let url = url.clone();
                 let authenticate = auth_fn;
-                match orderbook_client::subscribe_with_authentication(url, authenticate, None).await
+                match orderbook_client::subscribe_with_authentication(url, authenticate, None, None)
+                    .await
                 {
                     Ok((mut sink, mut stream)) => {
-                        // We request the filled matches for all our limit orders periodically.
+                        // We request the filled matches for all our limit orders periodically, and
+                        // whenever one of them has been filled.
+                        let filled_matches_requested = filled.clone();
                         let (task, _handle) = async move {
                             loop {
                                 if let Err(e) = sink
@@ -105,7 +122,10 @@ impl Client {
                                     );
                                 };
 
-                                tokio::time::sleep(REQUEST_FILLED_MATCHES_INTERVAL).await;
+                                tokio::select! {
+                                    _ = tokio::time::sleep(REQUEST_FILLED_MATCHES_INTERVAL) => {}
+                                    _ = filled_matches_requested.notified() => {}
+                                }
                             }
                         }
                         .remote_handle();
@@ -118,6 +138,8 @@ impl Client {
                                 &position_manager,
                                 &trader_id,
                                 &orderbook_status,
+                                &quote_updates,
+                                &filled,
                             )
                             .await
                             {
@@ -148,6 +170,8 @@ async fn process_message(
     position_manager: &xtra::Address<position::Manager>,
     maker_trader_id: &PublicKey,
     orderbook_status: &watch::Sender<ServiceStatus>,
+    quote_updates: &mpsc::Sender<QuoteEvent>,
+    filled: &Notify,
 ) -> Result<()> {
     tracing::trace!(%msg, "New message from orderbook");
 
@@ -190,13 +214,36 @@ async fn process_message(
         Message::InvalidAuthentication(e) => {
             tracing::error!("Orderbook authentication failed: {e}");
         }
+        Message::QuoteUpdate(event) => {
+            tracing::info!(
+                order_id = %event.order_id,
+                kind = ?event.kind,
+                filled_quantity = %event.filled_quantity,
+                remaining_quantity = %event.remaining_quantity,
+                "Quote updated"
+            );
+
+            if let QuoteEventKind::Filled | QuoteEventKind::PartiallyFilled = event.kind {
+                filled.notify_one();
+            }
+
+            quote_updates
+                .send(event)
+                .await
+                .context("Quoter stopped listening to quote updates")?;
+        }
         Message::AllOrders(_)
         | Message::NewOrder(_)
         | Message::DeleteOrder(_)
         | Message::Update(_)
         | Message::AsyncMatch { .. }
         | Message::Rollover { .. }
-        | Message::CollaborativeRevert { .. } => {
+        | Message::CollaborativeRevert { .. }
+        | Message::MatchReverted { .. }
+        | Message::TradingHalted(_)
+        | Message::TradingResumed
+        | Message::ChannelOpenStatus { .. }
+        | Message::SessionSuperseded => {
             // Nothing to do.
         }
     }
@@ -1,5 +1,9 @@
+use crate::hedging::Hedger;
+use crate::schema::sql_types::ContractSymbolType;
 use anyhow::Result;
 use async_trait::async_trait;
+use diesel::AsExpression;
+use diesel::FromSqlRow;
 use hedging::derive_hedging_action;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
@@ -14,13 +18,13 @@ use std::time::Duration;
 use uuid::Uuid;
 use xtra::Mailbox;
 
-mod bitmex;
+mod exchange;
 mod hedging;
 mod tentenone;
 
 pub struct Manager {
     position: Position,
-    bitmex_http_client: bitmex_client::client::Client,
+    hedger: Hedger,
 }
 
 #[async_trait]
@@ -32,7 +36,7 @@ impl xtra::Actor for Manager {
             let mailbox = mailbox.clone();
             async move {
                 loop {
-                    // We sleep first to allow the 10101 and BitMEX positions to be up-to-date
+                    // We sleep first to allow the 10101 and exchange positions to be up-to-date
                     // before we start hedging.
                     tokio::time::sleep(Duration::from_secs(60)).await;
 
@@ -48,16 +52,28 @@ impl xtra::Actor for Manager {
 }
 
 impl Manager {
-    pub fn new(bitmex_http_client: bitmex_client::client::Client) -> Self {
+    pub fn new(hedger: Hedger) -> Self {
         Self {
             position: Position::new(),
-            bitmex_http_client,
+            hedger,
         }
     }
 
-    /// Adjust hedging on _BitMEX_ based on the balance between the [`bitmex::Position`] and the
-    /// [`tentenone::Position`].
-    async fn hedge(&self, contract_symbol: &ContractSymbol) {
+    /// Adjust hedging on the exchange based on the balance between the [`exchange::Position`] and
+    /// the [`tentenone::Position`].
+    async fn hedge(&mut self, contract_symbol: &ContractSymbol) {
+        // We do not only rely on the position updates streamed by the exchange, as they might
+        // have been missed.
+        match self.hedger.position(*contract_symbol).await {
+            Ok(contracts) => self.position.update_exchange(*contract_symbol, contracts),
+            Err(e) => {
+                tracing::warn!(
+                    ?contract_symbol,
+                    "Failed to reconcile position with exchange: {e:#}"
+                );
+            }
+        }
+
         let tentenone = self.position.get_tentenone(contract_symbol);
 
         // For the purposes of hedging we have to round to the number of 10101 contracts to the
@@ -67,43 +83,32 @@ impl Manager {
             .to_i32()
             .expect("10101 position to fit in i32");
 
-        let bitmex = self.position.get_bitmex(contract_symbol);
-
-        let action = derive_hedging_action(tentenone, bitmex);
-
-        if let Err(e) = self.create_bitmex_order(&action, contract_symbol).await {
-            tracing::error!(
-                ?action,
-                "Failed to create order on BitMEX based on required hedging action: {e:#}"
-            )
+        let exchange = self.position.get_exchange(contract_symbol);
+
+        let action = derive_hedging_action(tentenone, exchange);
+
+        if action.contracts() != 0 {
+            match self
+                .hedger
+                .hedge(*contract_symbol, action.contracts(), tentenone, exchange)
+                .await
+            {
+                // Until the exchange tells us otherwise, we assume that the order was filled.
+                Ok(()) => self
+                    .position
+                    .update_exchange(*contract_symbol, exchange + action.contracts()),
+                Err(e) => tracing::error!(
+                    ?action,
+                    "Failed to create order on exchange based on required hedging action: {e:#}"
+                ),
+            }
         }
-    }
 
-    async fn create_bitmex_order(
-        &self,
-        action: &hedging::Action,
-        contract_symbol: &ContractSymbol,
-    ) -> Result<()> {
-        let (contracts, side) = match action.contracts() {
-            0 => return Ok(()),
-            n @ 1..=i32::MAX => (n.abs(), bitmex_client::models::Side::Buy),
-            n @ i32::MIN..=-1 => (n.abs(), bitmex_client::models::Side::Sell),
-        };
-
-        tracing::info!(
-            ?action,
-            "Creating BitMEX order based on required hedging action"
+        self.hedger.check_drift(
+            *contract_symbol,
+            tentenone,
+            self.position.get_exchange(contract_symbol),
         );
-
-        let contract_symbol = match contract_symbol {
-            ContractSymbol::BtcUsd => bitmex_client::models::ContractSymbol::XbtUsd,
-        };
-
-        self.bitmex_http_client
-            .create_order(contract_symbol, contracts, side, None)
-            .await?;
-
-        Ok(())
     }
 }
 
@@ -129,7 +134,8 @@ impl OrderTenTenOne {
     }
 }
 
-pub struct PositionUpdateBitmex {
+/// An update of our position on the exchange we hedge on.
+pub struct PositionUpdateExchange {
     pub contract_symbol: ContractSymbol,
     pub contracts: i32,
 }
@@ -164,16 +170,16 @@ impl xtra::Handler<PositionUpdateTenTenOne> for Manager {
 }
 
 #[async_trait]
-impl xtra::Handler<PositionUpdateBitmex> for Manager {
+impl xtra::Handler<PositionUpdateExchange> for Manager {
     type Return = ();
 
     async fn handle(
         &mut self,
-        update: PositionUpdateBitmex,
+        update: PositionUpdateExchange,
         _: &mut xtra::Context<Self>,
     ) -> Self::Return {
         self.position
-            .update_bitmex(update.contract_symbol, update.contracts);
+            .update_exchange(update.contract_symbol, update.contracts);
     }
 }
 
@@ -208,14 +214,14 @@ impl xtra::Handler<Hedge> for Manager {
 #[derive(Debug)]
 struct Position {
     tentenone: HashSet<tentenone::Position>,
-    bitmex: HashSet<bitmex::Position>,
+    exchange: HashSet<exchange::Position>,
 }
 
 impl Position {
     pub fn new() -> Self {
         Self {
             tentenone: HashSet::from_iter([tentenone::Position::new(ContractSymbol::BtcUsd)]),
-            bitmex: HashSet::from_iter([bitmex::Position::new(ContractSymbol::BtcUsd)]),
+            exchange: HashSet::from_iter([exchange::Position::new(ContractSymbol::BtcUsd)]),
         }
     }
 
@@ -236,16 +242,16 @@ impl Position {
         self.tentenone.replace(position);
     }
 
-    fn update_bitmex(&mut self, contract_symbol: ContractSymbol, contracts: i32) {
+    fn update_exchange(&mut self, contract_symbol: ContractSymbol, contracts: i32) {
         let mut position = self
-            .bitmex
+            .exchange
             .get(&contract_symbol)
             .cloned()
-            .unwrap_or(bitmex::Position::new(ContractSymbol::BtcUsd));
+            .unwrap_or(exchange::Position::new(ContractSymbol::BtcUsd));
 
         position.update(contracts);
 
-        self.bitmex.replace(position);
+        self.exchange.replace(position);
     }
 
     fn get_tentenone(&self, contract_symbol: &ContractSymbol) -> Decimal {
@@ -259,15 +265,16 @@ impl Position {
         }
     }
 
-    fn get_bitmex(&self, contract_symbol: &ContractSymbol) -> i32 {
-        match self.bitmex.get(contract_symbol) {
+    fn get_exchange(&self, contract_symbol: &ContractSymbol) -> i32 {
+        match self.exchange.get(contract_symbol) {
             Some(position) => position.contracts(),
             None => 0,
         }
     }
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Serialize, FromSqlRow, AsExpression)]
+#[diesel(sql_type = ContractSymbolType)]
 pub enum ContractSymbol {
     BtcUsd,
 }
@@ -3,7 +3,7 @@ use std::borrow::Borrow;
 use std::hash::Hash;
 use std::hash::Hasher;
 
-/// The maker's position on BitMEX.
+/// The maker's position on the exchange it hedges on.
 #[derive(Clone, Eq, Debug)]
 pub struct Position {
     contract_symbol: ContractSymbol,
@@ -42,7 +42,7 @@ impl Position {
                 contract_symbol = ?self.contract_symbol,
                 %before,
                 %after,
-                "Updated BitMEX position"
+                "Updated exchange position"
             );
         }
     }
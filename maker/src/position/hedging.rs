@@ -9,21 +9,21 @@ pub enum Action {
     Sell { hundreds_of_contracts: NonZeroU32 },
 }
 
-pub fn derive_hedging_action(tentenone: i32, bitmex: i32) -> Action {
-    fn derive_hedging_action_rec(tentenone: i32, bitmex: i32, action_acc: Action) -> Action {
-        let diff = tentenone - bitmex;
+pub fn derive_hedging_action(tentenone: i32, exchange: i32) -> Action {
+    fn derive_hedging_action_rec(tentenone: i32, exchange: i32, action_acc: Action) -> Action {
+        let diff = tentenone - exchange;
         let diff_hundreds = diff / 100;
 
         if 1 <= diff_hundreds {
             derive_hedging_action_rec(
                 tentenone,
-                bitmex + 100,
+                exchange + 100,
                 action_acc + Action::buy_one_hundred(),
             )
         } else if diff_hundreds <= -1 {
             derive_hedging_action_rec(
                 tentenone,
-                bitmex - 100,
+                exchange - 100,
                 action_acc + Action::sell_one_hundred(),
             )
         } else {
@@ -31,7 +31,7 @@ pub fn derive_hedging_action(tentenone: i32, bitmex: i32) -> Action {
         }
     }
 
-    derive_hedging_action_rec(tentenone, bitmex, Action::StandPat)
+    derive_hedging_action_rec(tentenone, exchange, Action::StandPat)
 }
 
 impl Action {
@@ -124,8 +124,8 @@ mod tests {
     // `derive_hedging_action_singleton` again always leads to `Action::StandPat`.
 
     #[track_caller]
-    fn check(tentenone: i32, bitmex: i32, expected: Action) {
-        let actual = derive_hedging_action(tentenone, bitmex);
+    fn check(tentenone: i32, exchange: i32, expected: Action) {
+        let actual = derive_hedging_action(tentenone, exchange);
         assert_eq!(expected, actual);
     }
 }
@@ -4,6 +4,7 @@ use crate::position;
 use crate::position::ContractSymbol;
 use crate::position::GetPosition;
 use crate::storage::MakerTenTenOneStorage;
+use crate::trading::quoter::QuotingStatus;
 use axum::extract::Path;
 use axum::extract::State;
 use axum::http::StatusCode;
@@ -21,6 +22,7 @@ use ln_dlc_node::node::InMemoryStore;
 use ln_dlc_node::node::Node;
 use ln_dlc_node::node::NodeInfo;
 use ln_dlc_node::ChannelDetails;
+use ln_dlc_node::PaymentConfig;
 use opentelemetry_prometheus::PrometheusExporter;
 use prometheus::Encoder;
 use prometheus::TextEncoder;
@@ -33,6 +35,7 @@ use std::hash::Hash;
 use std::hash::Hasher;
 use std::str::FromStr;
 use std::sync::Arc;
+use tokio::sync::watch;
 use tokio::task::spawn_blocking;
 
 pub struct AppState {
@@ -42,6 +45,7 @@ pub struct AppState {
     announcement_addresses: Vec<SocketAddress>,
     node_alias: String,
     health: Health,
+    quoting_status: watch::Receiver<QuotingStatus>,
 }
 
 pub fn router(
@@ -51,6 +55,7 @@ pub fn router(
     health: Health,
     announcement_addresses: Vec<SocketAddress>,
     node_alias: &str,
+    quoting_status: watch::Receiver<QuotingStatus>,
 ) -> Router {
     let app_state = Arc::new(AppState {
         node,
@@ -59,6 +64,7 @@ pub fn router(
         health,
         announcement_addresses,
         node_alias: node_alias.to_string(),
+        quoting_status,
     });
 
     Router::new()
@@ -71,6 +77,7 @@ pub fn router(
         .route("/api/pay-invoice/:invoice", post(pay_invoice))
         .route("/api/sync", post(sync))
         .route("/api/position", get(get_position))
+        .route("/api/quoting", get(get_quoting_status))
         .route("/api/node", get(get_node_info))
         .route("/metrics", get(get_metrics))
         .route("/health", get(get_health))
@@ -255,7 +262,7 @@ pub async fn pay_invoice(
         .map_err(|e| AppError::BadRequest(format!("Invalid invoice provided {e:#}")))?;
     state
         .node
-        .pay_invoice(&invoice, None)
+        .pay_invoice(&invoice, None, PaymentConfig::default())
         .map_err(|e| AppError::InternalServerError(format!("Could not pay invoice {e:#}")))?;
     Ok(())
 }
@@ -317,6 +324,11 @@ pub async fn get_position(State(state): State<Arc<AppState>>) -> Result<Json<Pos
     }))
 }
 
+/// What the maker is currently quoting around which index price, given its inventory.
+pub async fn get_quoting_status(State(state): State<Arc<AppState>>) -> Json<QuotingStatus> {
+    Json(state.quoting_status.borrow().clone())
+}
+
 pub async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let exporter = state.exporter.clone();
     let encoder = TextEncoder::new();
@@ -1 +1,30 @@
 // @generated automatically by Diesel CLI.
+
+pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "ContractSymbol_Type"))]
+    pub struct ContractSymbolType;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "Exchange_Type"))]
+    pub struct ExchangeType;
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExchangeType;
+    use super::sql_types::ContractSymbolType;
+
+    hedges (id) {
+        id -> Int4,
+        exchange -> ExchangeType,
+        contract_symbol -> ContractSymbolType,
+        contracts -> Int4,
+        exchange_order_id -> Nullable<Text>,
+        error -> Nullable<Text>,
+        dry_run -> Bool,
+        tentenone_contracts -> Int4,
+        exchange_contracts -> Int4,
+        created_at -> Timestamptz,
+    }
+}
@@ -19,14 +19,14 @@ pub async fn stream(
         let mut stream = match credentials {
             Some(credentials) => {
                 bitmex_stream::subscribe_with_credentials(
-                    ["quoteBin1m:XBTUSD".to_owned(), "position:XBTUSD".to_owned()],
+                    ["instrument:.BXBT".to_owned(), "position:XBTUSD".to_owned()],
                     network,
                     credentials
                 ).boxed()
             }
             None => {
                 bitmex_stream::subscribe(
-                    ["quoteBin1m:XBTUSD".to_owned()],
+                    ["instrument:.BXBT".to_owned()],
                     network,
                 ).boxed()
             }
@@ -64,18 +64,17 @@ pub async fn stream(
 
 #[derive(Debug, Clone)]
 pub enum Event {
-    Quote(Quote),
+    Index(IndexPrice),
     Position(Position),
 }
 
 impl From<wire::TableUpdate> for Event {
     fn from(value: wire::TableUpdate) -> Self {
         match value {
-            wire::TableUpdate::QuoteBin1m(quote) => Self::Quote(Quote {
-                contract_symbol: quote.symbol.into(),
-                bid: quote.bid_price,
-                ask: quote.ask_price,
-                timestamp: quote.timestamp,
+            wire::TableUpdate::Instrument(index) => Self::Index(IndexPrice {
+                contract_symbol: index.symbol.into(),
+                price: index.last_price,
+                timestamp: index.timestamp,
             }),
             wire::TableUpdate::Position(position) => Self::Position(Position {
                 contract_symbol: position.symbol.into(),
@@ -86,44 +85,34 @@ impl From<wire::TableUpdate> for Event {
     }
 }
 
+/// The price of BitMEX's index, which is composed of the prices of several spot exchanges.
 #[derive(Clone, Copy)]
-pub struct Quote {
+pub struct IndexPrice {
     pub contract_symbol: ContractSymbol,
-    pub bid: Decimal,
-    pub ask: Decimal,
+    pub price: Decimal,
     pub timestamp: OffsetDateTime,
 }
 
-impl fmt::Debug for Quote {
+impl fmt::Debug for IndexPrice {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let rfc3339_timestamp = self
             .timestamp
             .format(&time::format_description::well_known::Rfc3339)
             .expect("Timestamp to be formatted");
 
-        f.debug_struct("Quote")
+        f.debug_struct("IndexPrice")
             .field("timestamp", &rfc3339_timestamp)
-            .field("bid", &self.bid)
-            .field("ask", &self.ask)
+            .field("price", &self.price)
             .field("contract_symbol", &self.contract_symbol)
             .finish()
     }
 }
 
-impl Quote {
-    pub fn bid(&self) -> Decimal {
-        self.bid
-    }
-
-    pub fn ask(&self) -> Decimal {
-        self.ask
-    }
-
-    #[allow(dead_code)]
+impl IndexPrice {
     pub fn is_older_than(&self, duration: time::Duration) -> bool {
-        let required_quote_timestamp = (OffsetDateTime::now_utc() - duration).unix_timestamp();
+        let required_timestamp = (OffsetDateTime::now_utc() - duration).unix_timestamp();
 
-        self.timestamp.unix_timestamp() < required_quote_timestamp
+        self.timestamp.unix_timestamp() < required_timestamp
     }
 }
 
@@ -158,20 +147,19 @@ mod wire {
 
     #[derive(Debug)]
     pub enum TableUpdate {
-        QuoteBin1m(QuoteData),
+        Instrument(IndexData),
         Position(PositionData),
     }
 
+    /// An update of the `instrument` table for an index.
+    ///
+    /// Updates which do not carry a new price do not deserialize into this.
     #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
     #[serde(rename_all = "camelCase")]
-    pub struct QuoteData {
-        pub bid_size: u64,
-        pub ask_size: u64,
+    pub struct IndexData {
+        pub symbol: IndexSymbol,
         #[serde(with = "rust_decimal::serde::float")]
-        pub bid_price: Decimal,
-        #[serde(with = "rust_decimal::serde::float")]
-        pub ask_price: Decimal,
-        pub symbol: ContractSymbol,
+        pub last_price: Decimal,
         #[serde(with = "time::serde::rfc3339")]
         pub timestamp: OffsetDateTime,
     }
@@ -199,6 +187,20 @@ mod wire {
         }
     }
 
+    #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+    pub enum IndexSymbol {
+        #[serde(rename = ".BXBT")]
+        Bxbt,
+    }
+
+    impl From<IndexSymbol> for trade::ContractSymbol {
+        fn from(value: IndexSymbol) -> Self {
+            match value {
+                IndexSymbol::Bxbt => Self::BtcUsd,
+            }
+        }
+    }
+
     impl<'de> Deserialize<'de> for TableUpdate {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
@@ -210,7 +212,7 @@ mod wire {
                 type Value = TableUpdate;
 
                 fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                    formatter.write_str("either a `QuoteBin1m` or a `Position` table update")
+                    formatter.write_str("either an `Instrument` or a `Position` table update")
                 }
 
                 fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
@@ -219,7 +221,7 @@ mod wire {
                 {
                     #[derive(Debug)]
                     enum TableUpdateKind {
-                        QuoteBin1m,
+                        Instrument,
                         Position,
                     }
 
@@ -234,7 +236,7 @@ mod wire {
                                 }
 
                                 let value = match map.next_value()? {
-                                    "quoteBin1m" => TableUpdateKind::QuoteBin1m,
+                                    "instrument" => TableUpdateKind::Instrument,
                                     "position" => TableUpdateKind::Position,
                                     _ => return Err(serde::de::Error::custom("unexpected table")),
                                 };
@@ -263,10 +265,10 @@ mod wire {
                     // Now that we know the type of table we're dealing with we can choose between
                     // the variants we support
                     let value = match table {
-                        TableUpdateKind::QuoteBin1m => TableUpdate::QuoteBin1m(
-                            serde_json::from_str::<[QuoteData; 1]>(data.get()).map_err(|e| {
+                        TableUpdateKind::Instrument => TableUpdate::Instrument(
+                            serde_json::from_str::<[IndexData; 1]>(data.get()).map_err(|e| {
                                 serde::de::Error::custom(format!(
-                                    "could not deserialize quote data: {e}"
+                                    "could not deserialize index data: {e}"
                                 ))
                             })?[0]
                                 .clone(),
@@ -297,29 +299,32 @@ mod tests {
     use time::ext::NumericalDuration;
 
     #[test]
-    fn can_deserialize_quote_update() {
-        let table_update = serde_json::from_str(r#"{"table":"quoteBin1m","action":"insert","data":[{"timestamp":"2021-09-21T02:40:00.000Z","symbol":"XBTUSD","bidSize":50200,"bidPrice":42640.5,"askPrice":42641,"askSize":363600}]}"#).unwrap();
+    fn can_deserialize_index_update() {
+        let table_update = serde_json::from_str(r#"{"table":"instrument","action":"update","data":[{"symbol":".BXBT","lastPrice":42640.53,"timestamp":"2021-09-21T02:40:00.000Z"}]}"#).unwrap();
 
         match table_update {
-            wire::TableUpdate::QuoteBin1m(wire::QuoteData {
-                bid_size,
-                ask_size,
-                bid_price,
-                ask_price,
+            wire::TableUpdate::Instrument(wire::IndexData {
                 symbol,
+                last_price,
                 timestamp,
             }) => {
-                assert_eq!(symbol, wire::ContractSymbol::XbtUsd);
-                assert_eq!(bid_size, 50200);
-                assert_eq!(ask_size, 363600);
-                assert_eq!(bid_price, dec!(42640.5));
-                assert_eq!(ask_price, dec!(42641));
+                assert_eq!(symbol, wire::IndexSymbol::Bxbt);
+                assert_eq!(last_price, dec!(42640.53));
                 assert_eq!(timestamp.unix_timestamp(), 1632192000);
             }
             _ => panic!("Unexpected table update"),
         }
     }
 
+    #[test]
+    fn index_update_without_price_is_not_deserialized() {
+        let table_update = serde_json::from_str::<wire::TableUpdate>(
+            r#"{"table":"instrument","action":"update","data":[{"symbol":".BXBT","timestamp":"2021-09-21T02:40:00.000Z"}]}"#,
+        );
+
+        assert!(table_update.is_err());
+    }
+
     #[test]
     fn can_deserialize_position_update() {
         let table_update = serde_json::from_str(r#"{"table":"position","action":"update","data":[{"account":396867,"symbol":"XBTUSD","currency":"XBt","currentQty":100,"markPrice":27452.26,"markValue":-364269,"riskValue":364269,"homeNotional":0.00364269,"maintMargin":65585,"unrealisedPnl":-6327,"unrealisedPnlPcnt":-0.0177,"unrealisedRoePcnt":-0.0884,"liquidationPrice":23349.5,"timestamp":"2023-10-05T17:36:45.781Z"}]}"#).unwrap();
@@ -339,28 +344,27 @@ mod tests {
     }
 
     #[test]
-    fn quote_from_now_is_not_old() {
-        let quote = dummy_quote_at(OffsetDateTime::now_utc());
+    fn index_price_from_now_is_not_old() {
+        let index_price = dummy_index_price_at(OffsetDateTime::now_utc());
 
-        let is_older = quote.is_older_than(1.minutes());
+        let is_older = index_price.is_older_than(1.minutes());
 
         assert!(!is_older)
     }
 
     #[test]
-    fn quote_from_one_hour_ago_is_old() {
-        let quote = dummy_quote_at(OffsetDateTime::now_utc() - 1.hours());
+    fn index_price_from_one_hour_ago_is_old() {
+        let index_price = dummy_index_price_at(OffsetDateTime::now_utc() - 1.hours());
 
-        let is_older = quote.is_older_than(1.minutes());
+        let is_older = index_price.is_older_than(1.minutes());
 
         assert!(is_older)
     }
 
-    fn dummy_quote_at(timestamp: OffsetDateTime) -> Quote {
-        Quote {
+    fn dummy_index_price_at(timestamp: OffsetDateTime) -> IndexPrice {
+        IndexPrice {
             timestamp,
-            bid: dec!(10),
-            ask: dec!(10),
+            price: dec!(10),
             contract_symbol: trade::ContractSymbol::BtcUsd,
         }
     }
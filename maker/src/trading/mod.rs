@@ -1,42 +1,30 @@
 use crate::health::ServiceStatus;
 use crate::position;
-use crate::position::PositionUpdateBitmex;
+use crate::position::PositionUpdateExchange;
 use crate::trading::bitmex_ws_client::Event;
-use bitcoin::secp256k1::PublicKey;
 use bitcoin::Network;
 use bitmex_stream::Credentials;
-use commons::NewOrder;
-use commons::OrderResponse;
-use commons::OrderType;
 use futures::TryStreamExt;
-use orderbook_http_client::OrderbookClient;
-use reqwest::Url;
-use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
 use std::time::Duration;
-use time::OffsetDateTime;
 use tokio::sync::watch;
-use trade::ContractSymbol;
-use trade::Direction;
-use uuid::Uuid;
+
+pub use bitmex_ws_client::IndexPrice;
 
 mod bitmex_ws_client;
 mod orderbook_http_client;
+pub mod quoter;
+pub mod strategy;
 
-/// Perform trading related actions based on a subscription to BitMEX's WebSocket API. Specifically:
+/// Subscribe to BitMEX's WebSocket API. Specifically:
 ///
-/// - Create orders based on relevant price updates from BitMEX.
-/// - Forward updates about all BitMEX positions.
+/// - Publish the index price, which the [`quoter::Quoter`] quotes around.
+/// - Forward updates about our BitMEX positions, if we hedge on BitMEX and hence have credentials.
 ///
 /// In the unlikely event that the stream is closed, the function will continue to try to reconnect
 /// after the [`Duration`] specified by `reconnect_after`.
-#[allow(clippy::too_many_arguments)]
 pub async fn run(
-    orderbook_url: &Url,
-    maker_id: PublicKey,
     network: Network,
-    concurrent_orders: usize,
-    order_expiry_after: time::Duration,
+    index_price_tx: watch::Sender<Option<IndexPrice>>,
     bitmex_pricefeed_tx: watch::Sender<ServiceStatus>,
     position_manager: xtra::Address<position::Manager>,
     bitmex_api_key: Option<String>,
@@ -48,23 +36,6 @@ pub async fn run(
         _ => bitmex_stream::Network::Testnet,
     };
 
-    let orderbook_client = OrderbookClient::new();
-
-    let mut orders: Vec<OrderResponse> = Vec::new();
-
-    // Closure to avoid repeating the same code
-    let add_new_10101_order = |price, direction| {
-        add_10101_order(
-            &orderbook_client,
-            orderbook_url,
-            price,
-            direction,
-            maker_id,
-            dec!(5000),
-            OffsetDateTime::now_utc() + order_expiry_after,
-        )
-    };
-
     let credentials = match (bitmex_api_key, bitmex_api_secret) {
         (Some(api_key), Some(secret)) => Some(Credentials { api_key, secret }),
         _ => None,
@@ -74,28 +45,15 @@ pub async fn run(
         let mut stream = bitmex_ws_client::stream(network, credentials.clone()).await;
         loop {
             match stream.try_next().await {
-                Ok(Some(Event::Quote(quote))) => {
+                Ok(Some(Event::Index(index_price))) => {
                     let _ = bitmex_pricefeed_tx.send(ServiceStatus::Online);
-                    tracing::debug!("Received new quote {quote:?}");
+                    tracing::debug!("Received new index price {index_price:?}");
 
-                    orders.clear();
-
-                    for _i in 0..concurrent_orders {
-                        if let Some(order) = add_new_10101_order(quote.bid(), Direction::Long).await
-                        {
-                            orders.push(order)
-                        };
-
-                        if let Some(order) =
-                            add_new_10101_order(quote.ask(), Direction::Short).await
-                        {
-                            orders.push(order)
-                        };
-                    }
+                    index_price_tx.send_replace(Some(index_price));
                 }
                 Ok(Some(Event::Position(position))) => {
                     let _ = position_manager
-                        .send(PositionUpdateBitmex {
+                        .send(PositionUpdateExchange {
                             contract_symbol: position.contract_symbol.into(),
                             contracts: position.contracts,
                         })
@@ -119,36 +77,3 @@ pub async fn run(
         tokio::time::sleep(reconnect_after).await;
     }
 }
-
-async fn add_10101_order(
-    orderbook_client: &OrderbookClient,
-    orderbook_url: &Url,
-    price: Decimal,
-    direction: Direction,
-    maker_id: PublicKey,
-    quantity: Decimal,
-    expiry: OffsetDateTime,
-) -> Option<OrderResponse> {
-    orderbook_client
-        .post_new_order(
-            orderbook_url,
-            NewOrder {
-                id: Uuid::new_v4(),
-                contract_symbol: ContractSymbol::BtcUsd,
-                price,
-                quantity,
-                trader_id: maker_id,
-                direction,
-                leverage: 1.0,
-                order_type: OrderType::Limit,
-                expiry,
-                stable: false,
-            },
-        )
-        .await
-        .map_err(|err| {
-            tracing::error!("Failed posting new order {err:#}");
-            err
-        })
-        .ok()
-}
@@ -3,6 +3,8 @@ use anyhow::Result;
 use commons::NewOrder;
 use commons::OrderResponse;
 use reqwest::Url;
+use serde_json::json;
+use uuid::Uuid;
 
 pub struct OrderbookClient {
     client: reqwest::Client,
@@ -19,7 +21,7 @@ impl OrderbookClient {
     }
 
     pub async fn post_new_order(&self, url: &Url, order: NewOrder) -> Result<OrderResponse> {
-        let url = url.join("/api/orderbook/orders")?;
+        let url = url.join("/api/v1/orderbook/orders")?;
 
         let response = self.client.post(url).json(&order).send().await?;
 
@@ -31,4 +33,25 @@ impl OrderbookClient {
             bail!("Could not create new order ")
         }
     }
+
+    /// Take a limit order off the orderbook.
+    pub async fn cancel_order(&self, url: &Url, order_id: Uuid) -> Result<()> {
+        let url = url.join(&format!("/api/v1/orderbook/orders/{order_id}"))?;
+
+        let response = self
+            .client
+            .put(url)
+            .json(&json!({ "taken": true }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            bail!(
+                "Could not cancel order {order_id}: {}",
+                response.text().await?
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,327 @@
+use crate::position;
+use crate::position::ContractSymbol;
+use crate::position::GetPosition;
+use crate::trading::orderbook_http_client::OrderbookClient;
+use crate::trading::strategy;
+use crate::trading::strategy::Quote;
+use crate::trading::strategy::QuotingParams;
+use crate::trading::IndexPrice;
+use bitcoin::secp256k1::PublicKey;
+use commons::NewOrder;
+use commons::OrderType;
+use commons::QuoteEvent;
+use commons::QuoteEventKind;
+use reqwest::Url;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+/// How often we check whether our quotes are still good, in the absence of any other updates.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// We stop quoting if the index price has not been updated for this long.
+const MAX_INDEX_PRICE_AGE: time::Duration = time::Duration::minutes(1);
+
+/// We replace our quotes this long before they expire, so that there is no gap in the orderbook.
+const REQUOTE_BEFORE_EXPIRY: time::Duration = time::Duration::seconds(10);
+
+/// Keeps two-sided quotes in the orderbook around the index price.
+///
+/// The quotes are replaced whenever one of them is filled or has otherwise left the orderbook, the
+/// maker's inventory changes, the index price moves beyond the configured threshold or the quotes
+/// are about to expire.
+pub struct Quoter {
+    params: QuotingParams,
+    orderbook_url: Url,
+    maker_id: PublicKey,
+    order_expiry_after: time::Duration,
+    orderbook_client: OrderbookClient,
+    position_manager: xtra::Address<position::Manager>,
+    status: watch::Sender<QuotingStatus>,
+    /// Our quotes which are currently in the orderbook.
+    quotes: Vec<ActiveQuote>,
+    /// The index price and the inventory our current quotes are based on.
+    quoted: Option<(Decimal, Decimal)>,
+    last_requote: Option<OffsetDateTime>,
+    fills: u64,
+}
+
+/// A quote the maker has posted to the orderbook.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveQuote {
+    pub order_id: Uuid,
+    #[serde(flatten)]
+    pub quote: Quote,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiry: OffsetDateTime,
+}
+
+/// What the maker is currently quoting, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotingStatus {
+    pub state: QuotingState,
+    pub params: QuotingParams,
+    pub index_price: Option<Decimal>,
+    /// The number of contracts the maker is exposed to: positive if it is long, negative if it is
+    /// short.
+    pub inventory: Decimal,
+    pub quotes: Vec<ActiveQuote>,
+    /// The number of times one of our quotes has been filled, fully or partially.
+    pub fills: u64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_requote: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum QuotingState {
+    WaitingForIndexPrice,
+    Quoting,
+    /// The index price has not been updated for a while, hence we have withdrawn our quotes.
+    StaleIndexPrice,
+}
+
+impl QuotingStatus {
+    pub fn new(params: QuotingParams) -> Self {
+        Self {
+            state: QuotingState::WaitingForIndexPrice,
+            params,
+            index_price: None,
+            inventory: Decimal::ZERO,
+            quotes: Vec::new(),
+            fills: 0,
+            last_requote: None,
+        }
+    }
+}
+
+impl Quoter {
+    pub fn new(
+        params: QuotingParams,
+        orderbook_url: Url,
+        maker_id: PublicKey,
+        order_expiry_after: time::Duration,
+        position_manager: xtra::Address<position::Manager>,
+        status: watch::Sender<QuotingStatus>,
+    ) -> Self {
+        Self {
+            params,
+            orderbook_url,
+            maker_id,
+            order_expiry_after,
+            orderbook_client: OrderbookClient::new(),
+            position_manager,
+            status,
+            quotes: Vec::new(),
+            quoted: None,
+            last_requote: None,
+            fills: 0,
+        }
+    }
+
+    /// Quote based on the `index_price` until the index price feed is closed.
+    ///
+    /// The `quote_updates` are the updates about our quotes sent by the orderbook.
+    pub async fn run(
+        mut self,
+        mut index_price: watch::Receiver<Option<IndexPrice>>,
+        mut quote_updates: mpsc::Receiver<QuoteEvent>,
+    ) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            let force_requote = tokio::select! {
+                changed = index_price.changed() => {
+                    if changed.is_err() {
+                        tracing::error!("Index price feed closed, withdrawing quotes");
+                        self.cancel_quotes().await;
+                        return;
+                    }
+
+                    false
+                }
+                Some(event) = quote_updates.recv() => self.on_quote_update(event),
+                _ = interval.tick() => false,
+            };
+
+            let current_index_price = *index_price.borrow();
+            self.update(current_index_price, force_requote).await;
+        }
+    }
+
+    /// Returns whether the update concerns one of our current quotes, which then has to be
+    /// replaced.
+    fn on_quote_update(&mut self, event: QuoteEvent) -> bool {
+        let position = match self
+            .quotes
+            .iter()
+            .position(|quote| quote.order_id == event.order_id)
+        {
+            Some(position) => position,
+            // Either not one of our current quotes or a duplicate.
+            None => return false,
+        };
+
+        // Even a partially filled quote does not stay in the orderbook.
+        let quote = self.quotes.remove(position);
+
+        if let QuoteEventKind::Filled | QuoteEventKind::PartiallyFilled = event.kind {
+            self.fills += 1;
+
+            tracing::info!(
+                order_id = %quote.order_id,
+                direction = ?quote.quote.direction,
+                price = %quote.quote.price,
+                filled_quantity = %event.filled_quantity,
+                "Quote filled"
+            );
+        }
+
+        true
+    }
+
+    async fn update(&mut self, index_price: Option<IndexPrice>, force_requote: bool) {
+        let index_price = match index_price {
+            Some(index_price) if !index_price.is_older_than(MAX_INDEX_PRICE_AGE) => {
+                index_price.price
+            }
+            Some(index_price) => {
+                if !self.quotes.is_empty() {
+                    tracing::warn!(?index_price, "Index price is stale, withdrawing quotes");
+                    self.cancel_quotes().await;
+                }
+
+                self.publish(QuotingState::StaleIndexPrice, Some(index_price.price), None);
+                return;
+            }
+            None => {
+                self.publish(QuotingState::WaitingForIndexPrice, None, None);
+                return;
+            }
+        };
+
+        let inventory = match self.position_manager.send(GetPosition).await {
+            Ok(position) => position
+                .tentenone
+                .get(&ContractSymbol::BtcUsd)
+                .copied()
+                .unwrap_or_default(),
+            Err(e) => {
+                tracing::error!("Failed to get inventory: {e:#}");
+                return;
+            }
+        };
+
+        if force_requote || self.needs_requote(index_price, inventory) {
+            self.requote(index_price, inventory).await;
+        }
+
+        self.publish(QuotingState::Quoting, Some(index_price), Some(inventory));
+    }
+
+    fn needs_requote(&self, index_price: Decimal, inventory: Decimal) -> bool {
+        let (quoted_index_price, quoted_inventory) = match self.quoted {
+            Some(quoted) => quoted,
+            None => return true,
+        };
+
+        let expire_soon = self
+            .quotes
+            .iter()
+            .any(|quote| quote.expiry <= OffsetDateTime::now_utc() + REQUOTE_BEFORE_EXPIRY);
+
+        self.quotes.is_empty()
+            || expire_soon
+            || inventory != quoted_inventory
+            || !strategy::is_price_within_threshold(&self.params, quoted_index_price, index_price)
+    }
+
+    async fn requote(&mut self, index_price: Decimal, inventory: Decimal) {
+        // We withdraw the old quotes first, so that we are never exposed to both at once.
+        self.cancel_quotes().await;
+
+        let expiry = OffsetDateTime::now_utc() + self.order_expiry_after;
+
+        for quote in strategy::quotes(&self.params, index_price, inventory) {
+            let order_id = Uuid::new_v4();
+
+            let result = self
+                .orderbook_client
+                .post_new_order(
+                    &self.orderbook_url,
+                    NewOrder {
+                        id: order_id,
+                        contract_symbol: trade::ContractSymbol::BtcUsd,
+                        price: quote.price,
+                        quantity: quote.quantity,
+                        trader_id: self.maker_id,
+                        direction: quote.direction,
+                        leverage: 1.0,
+                        order_type: OrderType::Limit,
+                        expiry,
+                        stable: false,
+                        reduce_only: false,
+                    },
+                )
+                .await;
+
+            match result {
+                Ok(_) => self.quotes.push(ActiveQuote {
+                    order_id,
+                    quote,
+                    expiry,
+                }),
+                Err(e) => tracing::error!(?quote, "Failed to post quote: {e:#}"),
+            }
+        }
+
+        tracing::debug!(
+            %index_price,
+            %inventory,
+            quotes = self.quotes.len(),
+            "Requoted"
+        );
+
+        self.quoted = Some((index_price, inventory));
+        self.last_requote = Some(OffsetDateTime::now_utc());
+    }
+
+    async fn cancel_quotes(&mut self) {
+        for quote in std::mem::take(&mut self.quotes) {
+            if let Err(e) = self
+                .orderbook_client
+                .cancel_order(&self.orderbook_url, quote.order_id)
+                .await
+            {
+                // The quote will still expire eventually.
+                tracing::warn!(order_id = %quote.order_id, "Failed to cancel quote: {e:#}");
+            }
+        }
+
+        self.quoted = None;
+    }
+
+    fn publish(
+        &self,
+        state: QuotingState,
+        index_price: Option<Decimal>,
+        inventory: Option<Decimal>,
+    ) {
+        let previous_inventory = self.status.borrow().inventory;
+
+        self.status.send_replace(QuotingStatus {
+            state,
+            params: self.params,
+            index_price,
+            inventory: inventory.unwrap_or(previous_inventory),
+            quotes: self.quotes.clone(),
+            fills: self.fills,
+            last_requote: self.last_requote,
+        });
+    }
+}
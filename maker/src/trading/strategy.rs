@@ -0,0 +1,240 @@
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+use serde::Serialize;
+use trade::Direction;
+
+/// How the maker quotes around the index price.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QuotingParams {
+    /// The distance between the best bid and the best ask, in basis points of the index price.
+    ///
+    /// Every further level is half a spread further away from the index price.
+    pub spread_bps: Decimal,
+    /// The number of quotes on each side.
+    pub levels: usize,
+    /// The number of contracts of every quote while the maker is not exposed.
+    pub order_size: Decimal,
+    /// The number of contracts the maker is willing to be exposed to, in either direction.
+    ///
+    /// The side which would increase the exposure is quoted smaller the closer the maker gets to
+    /// this limit, and not at all once it has been reached.
+    pub max_inventory: Decimal,
+    /// How far the quotes are shifted away from the index price at the maximum inventory, in basis
+    /// points.
+    ///
+    /// The quotes are shifted in proportion to the inventory, so that the maker is more likely to
+    /// be filled on the side which reduces its exposure.
+    pub skew_bps: Decimal,
+    /// How far the index price has to move from the price the current quotes are based on, before
+    /// the maker quotes again, in basis points.
+    pub requote_threshold_bps: Decimal,
+}
+
+/// An order the maker wants to have in the orderbook.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Quote {
+    /// [`Direction::Long`] for a bid, [`Direction::Short`] for an ask.
+    pub direction: Direction,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+const BPS: Decimal = Decimal::from_parts(10_000, 0, 0, false, 0);
+
+/// The quotes the maker should have in the orderbook, given the `index_price` and its `inventory`.
+///
+/// The `inventory` is the number of contracts the maker is exposed to: positive if it is long,
+/// negative if it is short.
+pub fn quotes(params: &QuotingParams, index_price: Decimal, inventory: Decimal) -> Vec<Quote> {
+    let inventory_ratio = if params.max_inventory.is_zero() {
+        Decimal::ZERO
+    } else {
+        (inventory / params.max_inventory).clamp(-Decimal::ONE, Decimal::ONE)
+    };
+
+    // Being long, we quote lower to sell more easily and to buy less eagerly. And vice versa.
+    let mid_price = index_price * (Decimal::ONE - inventory_ratio * params.skew_bps / BPS);
+
+    let bid_quantity =
+        whole_contracts(params.order_size * (Decimal::ONE - inventory_ratio.max(Decimal::ZERO)));
+    let ask_quantity =
+        whole_contracts(params.order_size * (Decimal::ONE + inventory_ratio.min(Decimal::ZERO)));
+
+    let half_spread = params.spread_bps / Decimal::TWO / BPS;
+
+    let mut quotes = Vec::with_capacity(params.levels * 2);
+    for level in 0..params.levels {
+        let distance = half_spread * Decimal::from(level + 1);
+
+        if !bid_quantity.is_zero() {
+            quotes.push(Quote {
+                direction: Direction::Long,
+                // We round away from the index price, so that the spread never gets tighter.
+                price: (mid_price * (Decimal::ONE - distance))
+                    .round_dp_with_strategy(0, RoundingStrategy::ToNegativeInfinity),
+                quantity: bid_quantity,
+            });
+        }
+
+        if !ask_quantity.is_zero() {
+            quotes.push(Quote {
+                direction: Direction::Short,
+                price: (mid_price * (Decimal::ONE + distance))
+                    .round_dp_with_strategy(0, RoundingStrategy::ToPositiveInfinity),
+                quantity: ask_quantity,
+            });
+        }
+    }
+
+    quotes
+}
+
+/// Whether quotes based on `quoted_price` are still good for the current `index_price`.
+pub fn is_price_within_threshold(
+    params: &QuotingParams,
+    quoted_price: Decimal,
+    index_price: Decimal,
+) -> bool {
+    if quoted_price.is_zero() {
+        return false;
+    }
+
+    let deviation_bps = ((index_price - quoted_price) / quoted_price).abs() * BPS;
+
+    deviation_bps < params.requote_threshold_bps
+}
+
+fn whole_contracts(contracts: Decimal) -> Decimal {
+    contracts.round_dp_with_strategy(0, RoundingStrategy::ToZero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn params() -> QuotingParams {
+        QuotingParams {
+            spread_bps: dec!(20),
+            levels: 2,
+            order_size: dec!(1000),
+            max_inventory: dec!(10000),
+            skew_bps: dec!(10),
+            requote_threshold_bps: dec!(5),
+        }
+    }
+
+    fn bids(quotes: &[Quote]) -> Vec<Quote> {
+        quotes
+            .iter()
+            .filter(|quote| quote.direction == Direction::Long)
+            .copied()
+            .collect()
+    }
+
+    fn asks(quotes: &[Quote]) -> Vec<Quote> {
+        quotes
+            .iter()
+            .filter(|quote| quote.direction == Direction::Short)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn quotes_are_symmetric_without_inventory() {
+        let quotes = quotes(&params(), dec!(40000), Decimal::ZERO);
+
+        assert_eq!(
+            bids(&quotes),
+            vec![
+                Quote {
+                    direction: Direction::Long,
+                    price: dec!(39960),
+                    quantity: dec!(1000),
+                },
+                Quote {
+                    direction: Direction::Long,
+                    price: dec!(39920),
+                    quantity: dec!(1000),
+                },
+            ]
+        );
+        assert_eq!(
+            asks(&quotes),
+            vec![
+                Quote {
+                    direction: Direction::Short,
+                    price: dec!(40040),
+                    quantity: dec!(1000),
+                },
+                Quote {
+                    direction: Direction::Short,
+                    price: dec!(40080),
+                    quantity: dec!(1000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn long_inventory_skews_quotes_down_and_shrinks_bids() {
+        let quotes = quotes(&params(), dec!(40000), dec!(5000));
+
+        let best_bid = bids(&quotes)[0];
+        let best_ask = asks(&quotes)[0];
+
+        // Shifted by half of the skew, i.e. 5 bps.
+        assert_eq!(best_bid.price, dec!(39940));
+        assert_eq!(best_ask.price, dec!(40020));
+        assert_eq!(best_bid.quantity, dec!(500));
+        assert_eq!(best_ask.quantity, dec!(1000));
+    }
+
+    #[test]
+    fn short_inventory_skews_quotes_up_and_shrinks_asks() {
+        let quotes = quotes(&params(), dec!(40000), dec!(-5000));
+
+        let best_bid = bids(&quotes)[0];
+        let best_ask = asks(&quotes)[0];
+
+        assert_eq!(best_bid.price, dec!(39979));
+        assert_eq!(best_ask.price, dec!(40061));
+        assert_eq!(best_bid.quantity, dec!(1000));
+        assert_eq!(best_ask.quantity, dec!(500));
+    }
+
+    #[test]
+    fn no_bids_at_maximum_long_inventory() {
+        let quotes = quotes(&params(), dec!(40000), dec!(12000));
+
+        assert!(bids(&quotes).is_empty());
+        assert_eq!(asks(&quotes).len(), 2);
+    }
+
+    #[test]
+    fn no_asks_at_maximum_short_inventory() {
+        let quotes = quotes(&params(), dec!(40000), dec!(-10000));
+
+        assert!(asks(&quotes).is_empty());
+        assert_eq!(bids(&quotes).len(), 2);
+    }
+
+    #[test]
+    fn requote_once_price_moves_beyond_threshold() {
+        assert!(is_price_within_threshold(
+            &params(),
+            dec!(40000),
+            dec!(40019)
+        ));
+        assert!(!is_price_within_threshold(
+            &params(),
+            dec!(40000),
+            dec!(40020)
+        ));
+        assert!(!is_price_within_threshold(
+            &params(),
+            dec!(40000),
+            dec!(39980)
+        ));
+    }
+}
@@ -45,12 +45,27 @@ Future<void> setConfig() async {
   }
 
   rust.api.setConfig(config: config, appDir: appDir, seedDir: seedDir);
+  rust.api.setZeroConfChannelsEnabled(
+      enabled: await Preferences.instance.isZeroConfChannelsEnabled());
+  await setErrorReportingEnabled(await Preferences.instance.isErrorReportingEnabled());
+
+  try {
+    await rust.api.refreshAppConfig();
+  } catch (error) {
+    logger.w("Failed to fetch app config from coordinator, using defaults: $error");
+  }
 }
 
 Future<void> fullBackup() async {
   rust.api.fullBackup();
 }
 
+/// Report errors and panics of the app to 10101, tagged with the version of the app.
+Future<void> setErrorReportingEnabled(bool enabled) async {
+  PackageInfo packageInfo = await PackageInfo.fromPlatform();
+  rust.api.setErrorReportingEnabled(enabled: enabled, appVersion: packageInfo.version);
+}
+
 /// Run the backend and retry a number of times if it fails for whatever reason
 Future<void> runBackend(BuildContext context) async {
   final orderChangeNotifier = context.read<OrderChangeNotifier>();
@@ -1,14 +1,13 @@
 import 'package:flutter/material.dart';
 import 'package:get_10101/bridge_generated/bridge_definitions.dart' as bridge;
 import 'package:get_10101/common/application/event_service.dart';
-import 'package:get_10101/ffi.dart';
 import 'package:get_10101/logger/logger.dart';
 
 /// Sends channel status notifications to subscribers.
 ///
 /// Subscribers can learn about the latest [bridge.ChannelStatus] of the LN-DLC channel.
 class ChannelStatusNotifier extends ChangeNotifier implements Subscriber {
-  bridge.ChannelStatus latest = ChannelStatus.Unknown;
+  bridge.ChannelStatus latest = const bridge.ChannelStatus.unknown();
 
   ChannelStatusNotifier();
 
@@ -18,8 +17,8 @@ class ChannelStatusNotifier extends ChangeNotifier implements Subscriber {
   }
 
   bool hasDlcChannel() {
-    return getChannelStatus() != ChannelStatus.Unknown ||
-        getChannelStatus() != ChannelStatus.NotOpen;
+    return getChannelStatus() is! bridge.ChannelStatus_Unknown ||
+        getChannelStatus() is! bridge.ChannelStatus_NotOpen;
   }
 
   bool canForceClose() {
@@ -30,11 +29,11 @@ class ChannelStatusNotifier extends ChangeNotifier implements Subscriber {
   bool isClosing() {
     final status = getChannelStatus();
 
-    return status == ChannelStatus.Closing;
+    return status is bridge.ChannelStatus_Closing;
   }
 
   void subscribe(EventService eventService) {
-    eventService.subscribe(this, const bridge.Event.channelStatusUpdate(ChannelStatus.Unknown));
+    eventService.subscribe(this, const bridge.Event.channelStatusUpdate(bridge.ChannelStatus.unknown()));
   }
 
   @override
@@ -54,6 +53,11 @@ class ChannelStatusNotifier extends ChangeNotifier implements Subscriber {
   }
 
   bool hasOpenPosition() {
-    return getChannelStatus() == ChannelStatus.WithPosition;
+    return getChannelStatus() is bridge.ChannelStatus_WithPosition;
+  }
+
+  /// Whether the channel with 10101 is waiting for its funding transaction to confirm.
+  bool isOpening() {
+    return getChannelStatus() is bridge.ChannelStatus_Opening;
   }
 }
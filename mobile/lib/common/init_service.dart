@@ -2,6 +2,7 @@ import 'package:flutter/material.dart';
 import 'package:get_10101/common/application/lsp_change_notifier.dart';
 import 'package:get_10101/common/domain/lsp_config.dart';
 import 'package:get_10101/features/trade/candlestick_change_notifier.dart';
+import 'package:get_10101/features/trade/market_stats_change_notifier.dart';
 import 'package:get_10101/features/trade/order_change_notifier.dart';
 import 'package:get_10101/features/trade/position_change_notifier.dart';
 import 'package:get_10101/common/amount_denomination_change_notifier.dart';
@@ -17,6 +18,7 @@ import 'package:get_10101/features/wallet/payment_claimed_change_notifier.dart';
 import 'package:get_10101/features/wallet/wallet_change_notifier.dart';
 import 'package:get_10101/features/trade/submit_order_change_notifier.dart';
 import 'package:get_10101/features/trade/application/candlestick_service.dart';
+import 'package:get_10101/features/trade/application/market_stats_service.dart';
 import 'package:get_10101/features/trade/application/order_service.dart';
 import 'package:get_10101/features/trade/application/position_service.dart';
 import 'package:get_10101/features/trade/application/trade_values_service.dart';
@@ -53,6 +55,8 @@ List<SingleChildWidget> createProviders() {
     ChangeNotifierProvider(create: (context) => WalletChangeNotifier(const WalletService())),
     ChangeNotifierProvider(
         create: (context) => CandlestickChangeNotifier(const CandlestickService()).initialize()),
+    ChangeNotifierProvider(
+        create: (context) => MarketStatsChangeNotifier(const MarketStatsService()).initialize()),
     ChangeNotifierProvider(create: (context) => ServiceStatusNotifier()),
     ChangeNotifierProvider(create: (context) => ChannelStatusNotifier()),
     ChangeNotifierProvider(create: (context) => AsyncOrderChangeNotifier(OrderService())),
@@ -103,11 +107,14 @@ void subscribeToNotifiers(BuildContext context) {
   eventService.subscribe(
       positionChangeNotifier,
       const bridge.Event.positionClosedNotification(
-          bridge.PositionClosed(contractSymbol: bridge.ContractSymbol.BtcUsd)));
+          bridge.PositionClosed(id: '', contractSymbol: bridge.ContractSymbol.BtcUsd)));
 
   eventService.subscribe(
       walletChangeNotifier, bridge.Event.walletInfoUpdateNotification(WalletInfo.apiDummy()));
 
+  eventService.subscribe(walletChangeNotifier,
+      const bridge.Event.syncProgress(bridge.SyncProgress.upToDate(height: 0)));
+
   eventService.subscribe(
       tradeValuesChangeNotifier, bridge.Event.priceUpdateNotification(Price.apiDummy()));
 
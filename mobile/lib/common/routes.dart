@@ -19,6 +19,7 @@ import 'package:get_10101/features/wallet/domain/wallet_type.dart';
 import 'package:get_10101/features/wallet/onboarding/onboarding_screen.dart';
 import 'package:get_10101/features/wallet/receive_screen.dart';
 import 'package:get_10101/features/wallet/scanner_screen.dart';
+import 'package:get_10101/features/wallet/utxo_screen.dart';
 import 'package:get_10101/features/welcome/seed_import_screen.dart';
 import 'package:get_10101/common/settings/seed_screen.dart';
 import 'package:get_10101/features/wallet/wallet_screen.dart';
@@ -170,7 +171,7 @@ GoRouter createRoutes() {
                   // Use root navigator so the screen overlays the application shell
                   parentNavigatorKey: rootNavigatorKey,
                   builder: (BuildContext context, GoRouterState state) {
-                    return SendLightningScreen(destination: state.extra as LightningInvoice);
+                    return SendLightningScreen(destination: state.extra as Destination);
                   },
                 ),
                 GoRoute(
@@ -197,6 +198,13 @@ GoRouter createRoutes() {
                     return const ScannerScreen();
                   },
                 ),
+                GoRoute(
+                  path: UtxoScreen.subRouteName,
+                  parentNavigatorKey: rootNavigatorKey,
+                  builder: (BuildContext context, GoRouterState state) {
+                    return const UtxoScreen();
+                  },
+                ),
               ],
             ),
             GoRoute(
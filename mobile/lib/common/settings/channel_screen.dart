@@ -1,8 +1,11 @@
 import 'package:flutter/material.dart';
 import 'package:get_10101/bridge_generated/bridge_definitions.dart';
+import 'package:get_10101/common/application/switch.dart';
 import 'package:get_10101/common/channel_status_notifier.dart';
 import 'package:get_10101/common/settings/settings_screen.dart';
 import 'package:get_10101/common/value_data_row.dart';
+import 'package:get_10101/ffi.dart' as rust;
+import 'package:get_10101/util/preferences.dart';
 import 'package:go_router/go_router.dart';
 import 'package:provider/provider.dart';
 
@@ -25,7 +28,8 @@ class _ChannelScreenState extends State<ChannelScreen> {
   Widget build(BuildContext context) {
     ChannelStatusNotifier channelStatusNotifier = context.watch<ChannelStatusNotifier>();
 
-    final channelStatus = channelStatusToString(channelStatusNotifier.getChannelStatus());
+    final status = channelStatusNotifier.getChannelStatus();
+    final channelStatus = channelStatusToString(status);
 
     return Scaffold(
       body: Container(
@@ -84,6 +88,47 @@ class _ChannelScreenState extends State<ChannelScreen> {
                           valueTextStyle:
                               const TextStyle(fontWeight: FontWeight.bold, fontSize: 18),
                         ),
+                        if (status is ChannelStatus_Opening)
+                          Padding(
+                            padding: const EdgeInsets.only(top: 10),
+                            child: LinearProgressIndicator(
+                                value: status.requiredConfirmations == 0
+                                    ? null
+                                    : status.confirmations / status.requiredConfirmations),
+                          ),
+                        const SizedBox(height: 20),
+                        Row(
+                          mainAxisAlignment: MainAxisAlignment.spaceBetween,
+                          children: [
+                            const Expanded(
+                              child: Text(
+                                "Trade before the channel is confirmed",
+                                style: TextStyle(fontSize: 18),
+                              ),
+                            ),
+                            FutureBuilder(
+                                future: Preferences.instance.isZeroConfChannelsEnabled(),
+                                builder: (BuildContext context, AsyncSnapshot<bool> snapshot) {
+                                  if (!snapshot.hasData) {
+                                    return Container();
+                                  }
+
+                                  return TenTenOneSwitch(
+                                      value: snapshot.data ?? true,
+                                      onChanged: (value) {
+                                        setState(() {
+                                          Preferences.instance.setZeroConfChannelsEnabled(value);
+                                          rust.api.setZeroConfChannelsEnabled(enabled: value);
+                                        });
+                                      });
+                                }),
+                          ],
+                        ),
+                        const SizedBox(height: 10),
+                        const Text(
+                          "Your channel with 10101 can be used right away, instead of waiting for the channel opening transaction to confirm. Until then, you trust 10101 not to double-spend it.",
+                          style: TextStyle(color: Colors.grey),
+                        ),
                       ],
                     )),
                 Visibility(
@@ -147,19 +192,15 @@ RichText getForceCloseChannelText(ChannelStatusNotifier channelStatusNotifier) {
 }
 
 String channelStatusToString(ChannelStatus status) {
-  switch (status) {
-    case ChannelStatus.NotOpen:
-      return "Not open";
-    case ChannelStatus.WithPosition:
-      return "With Position";
-    case ChannelStatus.Renewing:
-    case ChannelStatus.Settling:
-      return "Pending";
-    case ChannelStatus.Closing:
-      return "Closing";
-    case ChannelStatus.Unknown:
-      return "Unknown";
-    case ChannelStatus.Open:
-      return "Open";
-  }
+  return status.when(
+    opening: (confirmations, requiredConfirmations) =>
+        "Opening ($confirmations/$requiredConfirmations)",
+    notOpen: () => "Not open",
+    open: () => "Open",
+    withPosition: () => "With Position",
+    settling: () => "Pending",
+    renewing: () => "Pending",
+    closing: () => "Closing",
+    unknown: () => "Unknown",
+  );
 }
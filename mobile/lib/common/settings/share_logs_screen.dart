@@ -2,6 +2,7 @@ import 'dart:convert';
 import 'dart:io';
 
 import 'package:flutter/material.dart';
+import 'package:get_10101/backend.dart';
 import 'package:get_10101/common/application/switch.dart';
 import 'package:get_10101/common/color.dart';
 import 'package:get_10101/common/settings/settings_screen.dart';
@@ -159,6 +160,47 @@ class _ShareLogsScreenState extends State<ShareLogsScreen> {
                       ),
                     ),
                     const SizedBox(height: 20),
+                    Container(
+                      padding: const EdgeInsets.symmetric(vertical: 10, horizontal: 20),
+                      decoration: BoxDecoration(
+                          color: Colors.white, borderRadius: BorderRadius.circular(15)),
+                      child: Row(
+                        mainAxisAlignment: MainAxisAlignment.spaceBetween,
+                        children: [
+                          Expanded(
+                            child: Text(
+                              "Report errors to 10101",
+                              style: TextStyle(
+                                  color: tenTenOnePurple.shade800,
+                                  fontSize: 16,
+                                  fontWeight: FontWeight.w500),
+                            ),
+                          ),
+                          FutureBuilder(
+                              future: Preferences.instance.isErrorReportingEnabled(),
+                              builder: (BuildContext context, AsyncSnapshot<bool> snapshot) {
+                                if (!snapshot.hasData) {
+                                  return Container();
+                                }
+
+                                return TenTenOneSwitch(
+                                    value: snapshot.data ?? false,
+                                    onChanged: (value) {
+                                      setState(() {
+                                        Preferences.instance.setErrorReportingEnabled(value);
+                                        setErrorReportingEnabled(value);
+                                      });
+                                    });
+                              }),
+                        ],
+                      ),
+                    ),
+                    const SizedBox(height: 10),
+                    const Text(
+                      "Errors and crashes are sent to 10101 without your keys, invoices or addresses, to help us fix them.",
+                      style: TextStyle(color: Colors.grey),
+                    ),
+                    const SizedBox(height: 20),
                     Visibility(
                       visible: changedLogLevel,
                       child: Container(
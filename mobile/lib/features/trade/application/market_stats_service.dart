@@ -0,0 +1,12 @@
+import 'package:get_10101/features/trade/domain/contract_symbol.dart';
+import 'package:get_10101/features/trade/domain/market_stats.dart';
+import 'package:get_10101/ffi.dart' as rust;
+
+class MarketStatsService {
+  const MarketStatsService();
+
+  Future<MarketStats> fetchMarketStats(ContractSymbol contractSymbol) async {
+    final stats = await rust.api.getMarketStats(contractSymbol: contractSymbol.toApi());
+    return MarketStats.fromApi(stats);
+  }
+}
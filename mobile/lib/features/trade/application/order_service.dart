@@ -19,6 +19,11 @@ class OrderService {
     return await rust.api.submitOrder(order: order);
   }
 
+  /// Closes the open position with the given id with a market order for the whole position.
+  Future<String> closePosition(String positionId) async {
+    return await rust.api.closePosition(positionId: positionId);
+  }
+
   Future<List<Order>> fetchOrders() async {
     List<rust.Order> apiOrders = await rust.api.getOrders();
     List<Order> orders = apiOrders.map((order) => Order.fromApi(order)).toList();
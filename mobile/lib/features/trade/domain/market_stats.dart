@@ -0,0 +1,42 @@
+import 'package:get_10101/features/trade/domain/contract_symbol.dart';
+import 'package:get_10101/ffi.dart' as rust;
+
+class MarketStats {
+  final ContractSymbol contractSymbol;
+
+  /// Traded volume in contracts
+  final double volumeHour;
+  final double volumeDay;
+  final double volumeWeek;
+
+  final double? highDay;
+  final double? lowDay;
+  final double? lastPrice;
+
+  /// Quantity of all open positions in contracts
+  final double openInterest;
+
+  MarketStats({
+    required this.contractSymbol,
+    required this.volumeHour,
+    required this.volumeDay,
+    required this.volumeWeek,
+    required this.highDay,
+    required this.lowDay,
+    required this.lastPrice,
+    required this.openInterest,
+  });
+
+  static MarketStats fromApi(rust.MarketStats stats) {
+    return MarketStats(
+      contractSymbol: ContractSymbol.fromApi(stats.contractSymbol),
+      volumeHour: stats.volumeHour,
+      volumeDay: stats.volumeDay,
+      volumeWeek: stats.volumeWeek,
+      highDay: stats.highDay,
+      lowDay: stats.lowDay,
+      lastPrice: stats.lastPrice,
+      openInterest: stats.openInterest,
+    );
+  }
+}
@@ -49,7 +49,9 @@ enum FailureReasonType {
   protocolError,
   failed,
   timeout,
+  fillingTimeout,
   rejected,
+  aborted,
   unknown;
 }
 
@@ -66,8 +68,13 @@ class FailureReason {
   static const FailureReason timeout = FailureReason._(
       failureType: FailureReasonType.timeout,
       details: "The order timed out before finding a match");
+  static const FailureReason fillingTimeout = FailureReason._(
+      failureType: FailureReasonType.fillingTimeout,
+      details: "The order could not be executed in time and has been cancelled.");
   static const FailureReason rejected =
       FailureReason._(failureType: FailureReasonType.rejected, details: "The order was rejected.");
+  static const FailureReason aborted =
+      FailureReason._(failureType: FailureReasonType.aborted, details: "The order was aborted.");
   static const FailureReason unknown = FailureReason._(
       failureType: FailureReasonType.unknown, details: "An unknown error occurred.");
 
@@ -89,8 +96,12 @@ class FailureReason {
             failureType: FailureReasonType.protocolError, details: failureReason.field0);
       case bridge.FailureReason_TimedOut():
         return timeout;
+      case bridge.FailureReason_FillingTimedOut():
+        return fillingTimeout;
       case bridge.FailureReason_OrderRejected():
         return rejected;
+      case bridge.FailureReason_Aborted():
+        return aborted;
       case bridge.FailureReason_Unknown():
         return unknown;
     }
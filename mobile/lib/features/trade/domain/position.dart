@@ -25,6 +25,7 @@ enum PositionState {
 }
 
 class Position {
+  final String id;
   final Leverage leverage;
   final Amount quantity;
   final ContractSymbol contractSymbol;
@@ -40,7 +41,8 @@ class Position {
   final DateTime expiry;
 
   Position(
-      {required this.averageEntryPrice,
+      {required this.id,
+      required this.averageEntryPrice,
       required this.liquidationPrice,
       required this.leverage,
       required this.quantity,
@@ -64,6 +66,7 @@ class Position {
 
   static Position fromApi(bridge.Position position) {
     return Position(
+      id: position.id,
       leverage: Leverage(position.leverage),
       quantity: Amount(position.quantity.ceil()),
       contractSymbol: ContractSymbol.fromApi(position.contractSymbol),
@@ -79,6 +82,7 @@ class Position {
 
   static bridge.Position apiDummy() {
     return const bridge.Position(
+      id: '',
       leverage: 0,
       quantity: 0,
       contractSymbol: bridge.ContractSymbol.BtcUsd,
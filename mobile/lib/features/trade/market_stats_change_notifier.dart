@@ -0,0 +1,42 @@
+import 'dart:async';
+
+import 'package:flutter/material.dart';
+import 'package:get_10101/features/trade/application/market_stats_service.dart';
+import 'package:get_10101/features/trade/domain/contract_symbol.dart';
+import 'package:get_10101/features/trade/domain/market_stats.dart';
+import 'package:get_10101/logger/logger.dart';
+
+class MarketStatsChangeNotifier extends ChangeNotifier {
+  Map<ContractSymbol, MarketStats> stats = {};
+
+  final MarketStatsService _marketStatsService;
+  Timer? timer;
+
+  MarketStatsChangeNotifier(this._marketStatsService);
+
+  MarketStatsChangeNotifier initialize() {
+    refresh();
+
+    timer = Timer.periodic(const Duration(seconds: 60), (Timer t) => refresh());
+
+    return this;
+  }
+
+  Future<void> refresh() async {
+    for (final contractSymbol in ContractSymbol.values) {
+      try {
+        stats[contractSymbol] = await _marketStatsService.fetchMarketStats(contractSymbol);
+      } catch (error) {
+        logger.w("Failed to fetch market stats for ${contractSymbol.label}: $error");
+      }
+    }
+
+    notifyListeners();
+  }
+
+  @override
+  void dispose() {
+    timer?.cancel();
+    super.dispose();
+  }
+}
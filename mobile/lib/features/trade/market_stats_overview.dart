@@ -0,0 +1,56 @@
+import 'package:flutter/material.dart';
+import 'package:get_10101/features/trade/domain/contract_symbol.dart';
+import 'package:get_10101/features/trade/market_stats_change_notifier.dart';
+import 'package:intl/intl.dart';
+import 'package:provider/provider.dart';
+
+/// Overview of the trading statistics of a market, e.g. the traded volume of the last 24 hours.
+class MarketStatsOverview extends StatelessWidget {
+  final ContractSymbol contractSymbol;
+
+  const MarketStatsOverview({super.key, required this.contractSymbol});
+
+  @override
+  Widget build(BuildContext context) {
+    final stats = context.watch<MarketStatsChangeNotifier>().stats[contractSymbol];
+
+    final priceFormatter = NumberFormat("\$ #,###,##0.00", "en");
+    final contractsFormatter = NumberFormat.compact(locale: "en");
+
+    String formatPrice(double? price) => price == null ? "-" : priceFormatter.format(price);
+    String formatContracts(double? contracts) =>
+        contracts == null ? "-" : contractsFormatter.format(contracts);
+
+    return Padding(
+      padding: const EdgeInsets.symmetric(vertical: 5),
+      child: Row(
+        mainAxisAlignment: MainAxisAlignment.spaceBetween,
+        children: [
+          _MarketStat(label: "Last", value: formatPrice(stats?.lastPrice)),
+          _MarketStat(label: "24h High", value: formatPrice(stats?.highDay)),
+          _MarketStat(label: "24h Low", value: formatPrice(stats?.lowDay)),
+          _MarketStat(label: "24h Volume", value: formatContracts(stats?.volumeDay)),
+          _MarketStat(label: "Open Interest", value: formatContracts(stats?.openInterest)),
+        ],
+      ),
+    );
+  }
+}
+
+class _MarketStat extends StatelessWidget {
+  final String label;
+  final String value;
+
+  const _MarketStat({required this.label, required this.value});
+
+  @override
+  Widget build(BuildContext context) {
+    return Column(
+      crossAxisAlignment: CrossAxisAlignment.start,
+      children: [
+        Text(label, style: const TextStyle(color: Colors.grey, fontSize: 11)),
+        Text(value, style: const TextStyle(fontSize: 12, fontWeight: FontWeight.bold)),
+      ],
+    );
+  }
+}
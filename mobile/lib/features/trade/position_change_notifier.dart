@@ -14,14 +14,25 @@ import 'package:get_10101/features/trade/domain/price.dart';
 class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
   final PositionService _positionService;
 
-  Map<ContractSymbol, Position> positions = {};
+  /// The open positions, keyed by their id.
+  Map<String, Position> positions = {};
 
   Price? price;
 
+  /// The position in the given contract, if any.
+  Position? positionFor(ContractSymbol contractSymbol) {
+    for (Position position in positions.values) {
+      if (position.contractSymbol == contractSymbol) {
+        return position;
+      }
+    }
+    return null;
+  }
+
   /// Amount of stabilised bitcoin in terms of USD (fiat)
   double getStableUSDAmountInFiat() {
     if (hasStableUSD()) {
-      final positionUsd = positions[ContractSymbol.btcusd];
+      final positionUsd = positionFor(ContractSymbol.btcusd);
       return positionUsd!.quantity.asDouble();
     } else {
       return 0.0;
@@ -30,7 +41,7 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
 
   Amount getStableUSDAmountInSats() {
     if (hasStableUSD()) {
-      final positionUsd = positions[ContractSymbol.btcusd];
+      final positionUsd = positionFor(ContractSymbol.btcusd);
       return positionUsd!.getAmountWithUnrealizedPnl();
     } else {
       return Amount(0);
@@ -38,12 +49,12 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
   }
 
   bool hasStableUSD() {
-    final positionUsd = positions[ContractSymbol.btcusd];
+    final positionUsd = positionFor(ContractSymbol.btcusd);
     return positionUsd != null && positionUsd.stable;
   }
 
   Amount marginUsableForTrade(Direction tradeDirection) {
-    final position = positions[ContractSymbol.btcusd];
+    final position = positionFor(ContractSymbol.btcusd);
 
     if (position == null ||
         // The margin can only be used in another trade if the trade reduces the position, by going
@@ -61,7 +72,7 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
   }
 
   Amount coordinatorMarginUsableForTrade(Leverage coordinatorLeverage, Direction tradeDirection) {
-    final position = positions[ContractSymbol.btcusd];
+    final position = positionFor(ContractSymbol.btcusd);
 
     if (position == null ||
         // The coordinator margin can only be used in another trade if the trade reduces the
@@ -81,7 +92,7 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
   Future<void> initialize() async {
     List<Position> positions = await _positionService.fetchPositions();
     for (Position position in positions) {
-      this.positions[position.contractSymbol] = position;
+      this.positions[position.id] = position;
     }
 
     notifyListeners();
@@ -100,7 +111,7 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
       } else {
         position.unrealizedPnl = null;
       }
-      positions[position.contractSymbol] = position;
+      positions[position.id] = position;
 
       if (position.isStable()) {
         Preferences.instance.setOpenStablePosition();
@@ -110,20 +121,17 @@ class PositionChangeNotifier extends ChangeNotifier implements Subscriber {
 
       notifyListeners();
     } else if (event is bridge.Event_PositionClosedNotification) {
-      ContractSymbol contractSymbol = ContractSymbol.fromApi(event.field0.contractSymbol);
-      positions.remove(contractSymbol);
+      positions.remove(event.field0.id);
 
       Preferences.instance.unsetOpenPosition();
 
       notifyListeners();
     } else if (event is bridge.Event_PriceUpdateNotification) {
       price = Price.fromApi(event.field0);
-      for (ContractSymbol symbol in positions.keys) {
+      for (Position position in positions.values) {
         if (price != null) {
-          if (positions[symbol] != null) {
-            final pnl = _positionService.calculatePnl(positions[symbol]!, price!);
-            positions[symbol]!.unrealizedPnl = pnl != null ? Amount(pnl) : null;
-          }
+          final pnl = _positionService.calculatePnl(position, price!);
+          position.unrealizedPnl = pnl != null ? Amount(pnl) : null;
         }
       }
 
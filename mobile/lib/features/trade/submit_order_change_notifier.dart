@@ -46,16 +46,24 @@ class SubmitOrderChangeNotifier extends ChangeNotifier implements Subscriber {
   SubmitOrderChangeNotifier(this.orderService);
 
   submitPendingOrder(TradeValues tradeValues, PositionAction positionAction,
-      {Amount? pnl, bool stable = false}) async {
+      {Amount? pnl, bool stable = false, String? positionId}) async {
     _pendingOrder = PendingOrder(tradeValues, positionAction, pnl);
 
     // notify listeners about pending order in state "pending"
     notifyListeners();
 
     try {
-      assert(tradeValues.quantity != null, 'Quantity cannot be null when submitting order');
-      _pendingOrder!.id = await orderService.submitMarketOrder(tradeValues.leverage,
-          tradeValues.quantity!, ContractSymbol.btcusd, tradeValues.direction, stable);
+      switch (positionAction) {
+        case PositionAction.open:
+          assert(tradeValues.quantity != null, 'Quantity cannot be null when submitting order');
+          _pendingOrder!.id = await orderService.submitMarketOrder(tradeValues.leverage,
+              tradeValues.quantity!, ContractSymbol.btcusd, tradeValues.direction, stable);
+          break;
+        case PositionAction.close:
+          assert(positionId != null, 'Position id cannot be null when closing a position');
+          _pendingOrder!.id = await orderService.closePosition(positionId!);
+          break;
+      }
       _pendingOrder!.state = PendingOrderState.submittedSuccessfully;
     } on FfiException catch (exception) {
       logger.e("Failed to submit order: $exception");
@@ -110,7 +118,8 @@ class SubmitOrderChangeNotifier extends ChangeNotifier implements Subscriber {
             tradeValuesService: TradeValuesService()),
         PositionAction.close,
         pnl: position.unrealizedPnl,
-        stable: stable);
+        stable: stable,
+        positionId: position.id);
   }
 
   PendingOrder? get pendingOrder => _pendingOrder;
@@ -96,9 +96,9 @@ class TradeBottomSheetConfirmation extends StatelessWidget {
         ? Amount(tradeValues.fee!.sats + tradeValues.margin!.sats)
         : Amount(0);
     Amount pnl = Amount(0);
-    if (context.read<PositionChangeNotifier>().positions.containsKey(ContractSymbol.btcusd)) {
-      final position = context.read<PositionChangeNotifier>().positions[ContractSymbol.btcusd];
-      pnl = position!.unrealizedPnl != null ? position.unrealizedPnl! : Amount(0);
+    final position = context.read<PositionChangeNotifier>().positionFor(ContractSymbol.btcusd);
+    if (position != null) {
+      pnl = position.unrealizedPnl != null ? position.unrealizedPnl! : Amount(0);
     }
 
     TextStyle dataRowStyle = const TextStyle(fontSize: 14);
@@ -182,12 +182,12 @@ class _TradeBottomSheetTabState extends State<TradeBottomSheetTab> {
       BuildContext context, ChannelInfoService channelInfoService, GlobalKey<FormState> formKey) {
     final tradeValues = context.read<TradeValuesChangeNotifier>().fromDirection(direction);
 
-    bool hasPosition = positionChangeNotifier.positions.containsKey(contractSymbol);
+    final position = positionChangeNotifier.positionFor(contractSymbol);
+    bool hasPosition = position != null;
 
     double? positionLeverage;
-    if (hasPosition) {
-      final position = context.read<PositionChangeNotifier>().positions[contractSymbol];
-      positionLeverage = position!.leverage.leverage;
+    if (position != null) {
+      positionLeverage = position.leverage.leverage;
     }
 
     int usableBalance = channelTradeConstraints.maxLocalMarginSats;
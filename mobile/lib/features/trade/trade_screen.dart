@@ -6,6 +6,7 @@ import 'package:get_10101/features/trade/domain/contract_symbol.dart';
 import 'package:get_10101/features/trade/domain/direction.dart';
 import 'package:get_10101/features/trade/domain/order.dart';
 import 'package:get_10101/features/trade/domain/position.dart';
+import 'package:get_10101/features/trade/market_stats_overview.dart';
 import 'package:get_10101/features/trade/order_change_notifier.dart';
 import 'package:get_10101/features/trade/order_list_item.dart';
 import 'package:get_10101/features/trade/position_change_notifier.dart';
@@ -62,6 +63,7 @@ class TradeScreen extends StatelessWidget {
                 mainAxisAlignment: MainAxisAlignment.center,
                 children: [const ContractSymbolIcon(), Text(ContractSymbol.btcusd.label)],
               ),
+              const MarketStatsOverview(contractSymbol: ContractSymbol.btcusd),
               Column(
                 crossAxisAlignment: CrossAxisAlignment.stretch,
                 children: [
@@ -28,6 +28,11 @@ class WalletService {
     }
   }
 
+  /// Returns `limit` items of the wallet activity, skipping the `offset` newest ones.
+  rust.ActivityPage getActivity(int offset, int limit) {
+    return rust.api.getActivity(offset: offset, limit: limit);
+  }
+
   /// Throws an exception if coordinator cannot provide required liquidity.
   Future<String?> createOnboardingInvoice(
       Amount amount, int liquidityOptionId, Amount feeSats) async {
@@ -68,6 +73,8 @@ class WalletService {
         return OnChainAddress.fromApi(result);
       } else if (result is rust.Destination_OnChainAddress) {
         return OnChainAddress.fromAddress(result);
+      } else if (result is rust.Destination_LightningAddress) {
+        return LightningAddress.fromApi(result);
       } else {
         return null;
       }
@@ -90,6 +97,11 @@ class WalletService {
   }
 
   Future<int> estimateFeeMsat(Destination destination, Amount? amount, Fee? fee) async {
+    if (destination is LightningAddress) {
+      // The invoice to probe is only requested from the service once the payment is sent.
+      return 0;
+    }
+
     return switch (fee) {
       null ||
       PriorityFee() =>
@@ -98,23 +110,51 @@ class WalletService {
     };
   }
 
-  Future<void> sendPayment(Destination destination, Amount? amount, {Fee? fee}) async {
+  /// Computes the exact fee of an on-chain payment and the amount received by the recipient. An
+  /// amount of zero sends all the funds of the wallet.
+  Future<rust.SendPreview> estimateSendFee(String address, Amount amount, Fee fee) async {
+    final sendAmount =
+        amount.sats == 0 ? const rust.SendAmount.max() : rust.SendAmount.sats(amount: amount.sats);
+    return await rust.api.estimateSendFee(address: address, amount: sendAmount, fee: fee.toAPI());
+  }
+
+  /// Sends a payment. For on-chain payments, the `inputs` of a [rust.SendPreview] ensure that
+  /// exactly the previewed transaction is sent.
+  Future<void> sendPayment(Destination destination, Amount? amount,
+      {Fee? fee, List<String>? inputs}) async {
     logger.i("Sending payment of $amount");
-    await rust.api.sendPayment(payment: _createPayment(destination, amount, fee: fee));
+    await rust.api
+        .sendPayment(payment: _createPayment(destination, amount, fee: fee, inputs: inputs));
   }
 
   String getUnusedAddress() {
     return rust.api.getUnusedAddress();
   }
+
+  /// Returns the usage of the given address if it is one of our own receive addresses which has
+  /// already been paid to.
+  Future<rust.AddressReuse?> checkAddressReuse(String address) async {
+    try {
+      return await rust.api.checkAddressReuse(address: address);
+    } catch (error) {
+      logger.w("Failed to check address reuse: $error");
+      return null;
+    }
+  }
 }
 
-rust.SendPayment _createPayment(Destination destination, Amount? amount, {Fee? fee}) {
+rust.SendPayment _createPayment(Destination destination, Amount? amount,
+    {Fee? fee, List<String>? inputs}) {
+  if (destination is LightningAddress) {
+    return rust.SendPayment_LightningAddress(address: destination.raw, amount: amount!.sats);
+  }
+
   switch (destination.getWalletType()) {
     case WalletType.lightning:
       return rust.SendPayment_Lightning(invoice: destination.raw, amount: amount?.sats);
     case WalletType.onChain:
       return rust.SendPayment_OnChain(
-          address: destination.raw, amount: amount!.sats, fee: fee!.toAPI());
+          address: destination.raw, amount: amount!.sats, fee: fee!.toAPI(), inputs: inputs);
     default:
       throw Exception("unsupported wallet type: ${destination.getWalletType().name}");
   }
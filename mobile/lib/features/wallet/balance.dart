@@ -17,7 +17,7 @@ class Balance extends StatelessWidget {
     final walletChangeNotifier = context.watch<WalletChangeNotifier>();
     Amount total = walletChangeNotifier.total();
     PositionChangeNotifier positionChangeNotifier = context.watch<PositionChangeNotifier>();
-    final position = positionChangeNotifier.positions[ContractSymbol.btcusd];
+    final position = positionChangeNotifier.positionFor(ContractSymbol.btcusd);
     if (position != null && position.isStable()) {
       total = total.add(position.getAmountWithUnrealizedPnl());
     }
@@ -71,3 +71,32 @@ class LightningInvoice extends Destination {
     return WalletType.lightning;
   }
 }
+
+class LightningAddress extends Destination {
+  final Amount minSendable;
+  final Amount maxSendable;
+  final int commentAllowed;
+
+  LightningAddress(
+      {required super.description,
+      required super.raw,
+      required this.minSendable,
+      required this.maxSendable,
+      required this.commentAllowed})
+      : super(amount: Amount.zero(), payee: raw);
+
+  static fromApi(rust.Destination_LightningAddress address) {
+    return LightningAddress(
+      description: address.description,
+      raw: address.address,
+      minSendable: Amount(address.minSendableSats),
+      maxSendable: Amount(address.maxSendableSats),
+      commentAllowed: address.commentAllowed,
+    );
+  }
+
+  @override
+  WalletType getWalletType() {
+    return WalletType.lightning;
+  }
+}
@@ -5,8 +5,13 @@ class FeeEstimation {
   final Amount perVbyte;
   final Amount total;
 
-  FeeEstimation({required this.perVbyte, required this.total});
+  /// The virtual size of the transactions in the mempool which will be mined first, if known.
+  final int? mempoolVsizeAhead;
 
-  static FeeEstimation fromAPI(rust.FeeEstimation fee) =>
-      FeeEstimation(perVbyte: Amount(fee.satsPerVbyte), total: Amount(fee.totalSats));
+  FeeEstimation({required this.perVbyte, required this.total, this.mempoolVsizeAhead});
+
+  static FeeEstimation fromAPI(rust.FeeEstimation fee) => FeeEstimation(
+      perVbyte: Amount(fee.satsPerVbyte),
+      total: Amount(fee.totalSats),
+      mempoolVsizeAhead: fee.mempoolVsizeAhead);
 }
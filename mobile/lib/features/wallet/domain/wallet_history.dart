@@ -106,7 +106,18 @@ abstract class WalletHistoryItemData {
         feeMsats: type.feeMsat,
         expiry: expiry,
         invoice: type.invoice,
-        fundingTxid: type.fundingTxid);
+        fundingTxid: type.fundingTxid,
+        payee: type.payee,
+        failureReason: switch (type.failureReason) {
+          null => null,
+          rust.PaymentFailureReason.recipientRejected => "Rejected by the recipient",
+          rust.PaymentFailureReason.userAbandoned => "Abandoned",
+          rust.PaymentFailureReason.retriesExhausted => "No route succeeded in time",
+          rust.PaymentFailureReason.paymentExpired => "Invoice expired",
+          rust.PaymentFailureReason.routeNotFound => "No route found within the fee limit",
+          rust.PaymentFailureReason.duplicatePayment => "Invoice already paid",
+          rust.PaymentFailureReason.unexpectedError => "Unexpected error",
+        });
   }
 }
 
@@ -118,6 +129,8 @@ class LightningPaymentData extends WalletHistoryItemData {
   final DateTime? expiry;
   final int? feeMsats;
   final String? fundingTxid;
+  final String? payee;
+  final String? failureReason;
 
   LightningPaymentData(
       {required super.flow,
@@ -130,7 +143,9 @@ class LightningPaymentData extends WalletHistoryItemData {
       required this.expiry,
       required this.feeMsats,
       required this.fundingTxid,
-      required this.paymentHash});
+      required this.paymentHash,
+      this.payee,
+      this.failureReason});
 
   @override
   WalletHistoryItem toWidget() {
@@ -14,6 +14,7 @@ import 'package:get_10101/features/wallet/domain/wallet_type.dart';
 import 'package:get_10101/features/wallet/send/execute_payment_modal.dart';
 import 'package:get_10101/features/wallet/send/payment_sent_change_notifier.dart';
 import 'package:get_10101/features/wallet/wallet_change_notifier.dart';
+import 'package:get_10101/ffi.dart' as rust;
 import 'package:get_10101/logger/logger.dart';
 import 'package:go_router/go_router.dart';
 import 'package:intl/intl.dart';
@@ -88,6 +89,12 @@ class ConfirmPayment extends StatelessWidget {
       tradeValues.updateMargin(destination.amount);
     }
 
+    // On-chain payments are previewed, so that the exact fee and amount are shown and sent.
+    final Future<rust.SendPreview>? onChainPreview =
+        destination.getWalletType() == WalletType.onChain && fee != null
+            ? walletService.estimateSendFee(destination.raw, amt, fee!)
+            : null;
+
     return SafeArea(
       child: Container(
         color: Colors.white,
@@ -113,8 +120,18 @@ class ConfirmPayment extends StatelessWidget {
                       const SizedBox(height: 5),
                       Visibility(
                           visible: payWithUsdp,
-                          replacement: Text(amt.sats == 0 ? "Max" : amt.toString(),
-                              style: const TextStyle(fontSize: 16)),
+                          replacement: FutureBuilder(
+                              future: onChainPreview,
+                              builder: (BuildContext context,
+                                  AsyncSnapshot<rust.SendPreview> preview) {
+                                if (preview.hasData) {
+                                  return AmountText(
+                                      amount: Amount(preview.data!.amountSats),
+                                      textStyle: const TextStyle(fontSize: 16));
+                                }
+                                return Text(amt.sats == 0 ? "Max" : amt.toString(),
+                                    style: const TextStyle(fontSize: 16));
+                              }),
                           child: Row(
                             mainAxisAlignment: MainAxisAlignment.spaceBetween,
                             children: [
@@ -149,7 +166,8 @@ class ConfirmPayment extends StatelessWidget {
                           ],
                         ),
                         FutureBuilder(
-                            future: walletService.estimateFeeMsat(destination, amt, fee),
+                            future: onChainPreview?.then((preview) => preview.feeSats * 1000) ??
+                                walletService.estimateFeeMsat(destination, amt, fee),
                             builder: (BuildContext context, AsyncSnapshot<int> feeMsat) {
                               final msat = feeMsat.data ?? 0;
 
@@ -199,7 +217,10 @@ class ConfirmPayment extends StatelessWidget {
                     }
                     showExecuteUsdpPaymentModal(context, destination, amt, payWithUsdp);
                   } else {
-                    walletService.sendPayment(destination, amt, fee: fee).then((value) {
+                    Future.value(onChainPreview)
+                        .then((preview) => walletService.sendPayment(destination, amt,
+                            fee: fee, inputs: preview?.inputs))
+                        .then((value) {
                       GoRouter.of(context).pop();
                     }).catchError((error) {
                       logger.e("Failed to send payment: $error");
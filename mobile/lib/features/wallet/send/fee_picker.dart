@@ -122,6 +122,13 @@ class _FeePickerModalState extends State<_FeePickerModal> {
               Column(crossAxisAlignment: CrossAxisAlignment.start, children: [
                 Text(target.toString()),
                 Text(target.toTimeEstimate(), style: const TextStyle(color: Color(0xff878787))),
+                ...switch (widget.feeEstimates?[target]?.mempoolVsizeAhead) {
+                  null => [],
+                  var vsize => [
+                      Text("${(vsize / 1000000).toStringAsFixed(1)} vMB ahead in mempool",
+                          style: const TextStyle(color: Color(0xff878787), fontSize: 12)),
+                    ],
+                },
               ]),
               const Spacer(),
               feeWidget(widget.feeEstimates, PriorityFee(target)),
@@ -28,7 +28,7 @@ class SendLightningScreen extends StatefulWidget {
   static const route = "${WalletScreen.route}/$subRouteName";
   static const subRouteName = "send-lightning";
 
-  final LightningInvoice destination;
+  final Destination destination;
 
   const SendLightningScreen({super.key, required this.destination});
 
@@ -306,6 +306,13 @@ class _SendLightningScreenState extends State<SendLightningScreen> {
             return "Amount cannot be negative";
           }
 
+          final destination = widget.destination;
+          if (destination is LightningAddress &&
+              (amount.sats < destination.minSendable.sats ||
+                  amount.sats > destination.maxSendable.sats)) {
+            return "Amount must be between ${formatSats(destination.minSendable)} and ${formatSats(destination.maxSendable)}";
+          }
+
           if (amount.sats > usdpBalance) {
             return "Not enough funds.";
           }
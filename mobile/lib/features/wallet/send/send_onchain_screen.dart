@@ -15,6 +15,7 @@ import 'package:get_10101/features/wallet/send/confirm_payment_modal.dart';
 import 'package:get_10101/features/wallet/send/fee_picker.dart';
 import 'package:get_10101/features/wallet/wallet_change_notifier.dart';
 import 'package:get_10101/features/wallet/wallet_screen.dart';
+import 'package:get_10101/ffi.dart' as rust;
 import 'package:provider/provider.dart';
 
 class SendOnChainScreen extends StatefulWidget {
@@ -37,6 +38,7 @@ class _SendOnChainScreenState extends State<SendOnChainScreen> {
   Amount _amount = Amount.zero();
   Fee _fee = PriorityFee(ConfirmationTarget.normal);
   Map<ConfirmationTarget, FeeEstimation>? _feeEstimates;
+  rust.AddressReuse? _addressReuse;
   late WalletService _walletService;
 
   final TextEditingController _controller = TextEditingController();
@@ -59,9 +61,11 @@ class _SendOnChainScreenState extends State<SendOnChainScreen> {
     channelInfo = await channelInfoService.getChannelInfo();
     final fees = await _walletService.calculateFeesForOnChain(
         widget.destination.address, widget.destination.amount);
+    final addressReuse = await _walletService.checkAddressReuse(widget.destination.address);
 
     setState(() {
       _feeEstimates = fees;
+      _addressReuse = addressReuse;
       _amount = widget.destination.amount;
       _controller.text = _amount.formatted();
     });
@@ -127,6 +131,16 @@ class _SendOnChainScreenState extends State<SendOnChainScreen> {
                         ])
                       ]),
                     ),
+                    Visibility(
+                      visible: _addressReuse != null,
+                      child: Padding(
+                        padding: const EdgeInsets.only(top: 10),
+                        child: Text(
+                          "This is one of your own receive addresses and it has already been paid ${_addressReuse?.usageCount} time(s). Reusing addresses harms your privacy.",
+                          style: const TextStyle(fontSize: 14, color: Colors.red),
+                        ),
+                      ),
+                    ),
                     const SizedBox(height: 25),
                     const Text(
                       "Enter amount (0 to send the maximum)",
@@ -0,0 +1,96 @@
+import 'package:flutter/material.dart';
+import 'package:get_10101/common/amount_text.dart';
+import 'package:get_10101/common/custom_app_bar.dart';
+import 'package:get_10101/common/domain/model.dart';
+import 'package:get_10101/common/snack_bar.dart';
+import 'package:get_10101/features/wallet/wallet_screen.dart';
+import 'package:get_10101/ffi.dart' as rust;
+
+/// Lists the on-chain UTXOs of the wallet and allows to freeze them, so that they are never spent
+/// by the wallet.
+class UtxoScreen extends StatefulWidget {
+  static const route = "${WalletScreen.route}/$subRouteName";
+  static const subRouteName = "utxos";
+
+  const UtxoScreen({super.key});
+
+  @override
+  State<UtxoScreen> createState() => _UtxoScreenState();
+}
+
+class _UtxoScreenState extends State<UtxoScreen> {
+  List<rust.Utxo> utxos = [];
+
+  @override
+  void initState() {
+    super.initState();
+    _loadUtxos();
+  }
+
+  Future<void> _loadUtxos() async {
+    final messenger = ScaffoldMessenger.of(context);
+    try {
+      final utxos = await rust.api.listUtxos();
+      setState(() => this.utxos = utxos);
+    } catch (e) {
+      showSnackBar(messenger, "Failed to load UTXOs. Error: $e");
+    }
+  }
+
+  Future<void> _setFrozen(rust.Utxo utxo, bool frozen) async {
+    final messenger = ScaffoldMessenger.of(context);
+    try {
+      if (frozen) {
+        await rust.api.freezeUtxo(outpoint: utxo.outpoint);
+      } else {
+        await rust.api.unfreezeUtxo(outpoint: utxo.outpoint);
+      }
+      await _loadUtxos();
+    } catch (e) {
+      showSnackBar(messenger, "Failed to update UTXO. Error: $e");
+    }
+  }
+
+  @override
+  Widget build(BuildContext context) {
+    return Scaffold(
+      body: SafeArea(
+        child: Container(
+          padding: const EdgeInsets.only(top: 20, left: 10, right: 10),
+          child: Column(
+            crossAxisAlignment: CrossAxisAlignment.start,
+            children: [
+              const TenTenOneAppBar(title: "UTXOs"),
+              const Padding(
+                padding: EdgeInsets.all(10),
+                child: Text(
+                    "Frozen UTXOs are not spent by the wallet, neither for payments nor for opening channels.",
+                    style: TextStyle(fontSize: 16)),
+              ),
+              Expanded(
+                child: RefreshIndicator(
+                  onRefresh: _loadUtxos,
+                  child: ListView(
+                    physics: const AlwaysScrollableScrollPhysics(),
+                    children: utxos
+                        .map((utxo) => SwitchListTile(
+                              title: AmountText(amount: Amount(utxo.amountSats)),
+                              subtitle: Text(
+                                  "${utxo.outpoint}${utxo.isConfirmed ? "" : " (unconfirmed)"}${utxo.isLocked ? " (locked)" : ""}",
+                                  overflow: TextOverflow.ellipsis),
+                              secondary: Icon(utxo.isFrozen ? Icons.ac_unit : Icons.toll),
+                              value: utxo.isFrozen,
+                              onChanged:
+                                  utxo.isLocked ? null : (frozen) => _setFrozen(utxo, frozen),
+                            ))
+                        .toList(),
+                  ),
+                ),
+              ),
+            ],
+          ),
+        ),
+      ),
+    );
+  }
+}
@@ -1,4 +1,5 @@
 import 'dart:async';
+import 'dart:math';
 
 import 'package:flutter/material.dart' hide Flow;
 import 'package:get_10101/bridge_generated/bridge_definitions.dart' as bridge;
@@ -6,17 +7,26 @@ import 'package:get_10101/common/application/event_service.dart';
 import 'package:get_10101/common/domain/model.dart';
 import 'package:get_10101/features/wallet/application/wallet_service.dart';
 import 'package:get_10101/features/wallet/domain/wallet_balances.dart';
+import 'package:get_10101/features/wallet/domain/wallet_history.dart';
 import 'package:get_10101/logger/logger.dart';
 import 'package:get_10101/features/wallet/domain/wallet_info.dart';
 
 class WalletChangeNotifier extends ChangeNotifier implements Subscriber {
   final WalletService service;
+  static const activityPageSize = 20;
+
   WalletInfo walletInfo = WalletInfo(
     balances: WalletBalances(onChain: Amount(0), offChain: Amount(0)),
-    history: List.empty(),
   );
+
+  /// The loaded part of the wallet activity, ordered from newest to oldest.
+  List<WalletHistoryItemData> activity = List.empty();
+  bool hasMoreActivity = false;
   bool syncing = true;
 
+  /// The progress of the running on-chain sync in percent, if any.
+  double? onChainSyncProgress;
+
   WalletChangeNotifier(this.service);
 
   void update(WalletInfo? walletInfo) {
@@ -31,6 +41,23 @@ class WalletChangeNotifier extends ChangeNotifier implements Subscriber {
     super.notifyListeners();
   }
 
+  /// Reload the activity, keeping as many items as have been loaded so far.
+  void loadActivity() {
+    final page = service.getActivity(0, max(activity.length, activityPageSize));
+    activity = page.items.map(WalletHistoryItemData.fromApi).toList();
+    hasMoreActivity = page.hasMore;
+
+    super.notifyListeners();
+  }
+
+  void loadMoreActivity() {
+    final page = service.getActivity(activity.length, activityPageSize);
+    activity = [...activity, ...page.items.map(WalletHistoryItemData.fromApi)];
+    hasMoreActivity = page.hasMore;
+
+    super.notifyListeners();
+  }
+
   Future<void> refreshLightningWallet() async {
     await service.refreshLightningWallet();
   }
@@ -50,12 +77,29 @@ class WalletChangeNotifier extends ChangeNotifier implements Subscriber {
   void notify(bridge.Event event) {
     if (event is bridge.Event_WalletInfoUpdateNotification) {
       update(WalletInfo.fromApi(event.field0));
+      loadActivity();
+    } else if (event is bridge.Event_SyncProgress) {
+      // The wallet info is only updated after the sync has finished.
+      updateOnChainSyncProgress(event.field0);
+      return;
     } else {
       logger.w("Received unexpected event: ${event.toString()}");
     }
     syncing = false;
   }
 
+  void updateOnChainSyncProgress(bridge.SyncProgress progress) {
+    if (progress is bridge.SyncProgress_Started) {
+      onChainSyncProgress = 0;
+    } else if (progress is bridge.SyncProgress_Scanning) {
+      onChainSyncProgress = progress.percent;
+    } else {
+      onChainSyncProgress = null;
+    }
+
+    super.notifyListeners();
+  }
+
   Future<void> waitForSyncToComplete() async {
     final completer = Completer<void>();
 
@@ -303,6 +303,11 @@ class LightningPaymentHistoryItem extends WalletHistoryItem {
   @override
   List<Widget> getDetails() {
     return [
+      Visibility(
+        visible: data.failureReason != null,
+        child: HistoryDetail(
+            label: "Failure reason", value: data.failureReason ?? '', truncate: false),
+      ),
       Visibility(
         visible: data.feeMsats != null,
         child: HistoryDetail(
@@ -329,6 +334,10 @@ class LightningPaymentHistoryItem extends WalletHistoryItem {
         child: HistoryDetail(label: "Lightning invoice", value: data.invoice ?? ''),
       ),
       HistoryDetail(label: "Invoice description", value: data.description),
+      Visibility(
+        visible: data.payee != null,
+        child: HistoryDetail(label: "Payee", value: data.payee ?? ''),
+      ),
       HistoryDetail(label: "Payment hash", value: data.paymentHash),
       Visibility(
         visible: data.preimage != null,
@@ -6,6 +6,7 @@ import 'package:get_10101/common/secondary_action_button.dart';
 import 'package:get_10101/features/wallet/balance.dart';
 import 'package:get_10101/features/wallet/receive_screen.dart';
 import 'package:get_10101/features/wallet/scanner_screen.dart';
+import 'package:get_10101/features/wallet/utxo_screen.dart';
 import 'package:get_10101/features/wallet/wallet_change_notifier.dart';
 import 'package:go_router/go_router.dart';
 import 'package:provider/provider.dart';
@@ -58,6 +59,13 @@ class WalletScreen extends StatelessWidget {
                       onPressed: () => GoRouter.of(context).go(ScannerScreen.route),
                       icon: FontAwesomeIcons.arrowUp,
                       title: 'Send',
+                    )),
+                    const SizedBox(width: 10.0),
+                    Expanded(
+                        child: SecondaryActionButton(
+                      onPressed: () => GoRouter.of(context).go(UtxoScreen.route),
+                      icon: FontAwesomeIcons.coins,
+                      title: 'UTXOs',
                     ))
                   ])),
               const SizedBox(
@@ -74,9 +82,14 @@ class WalletScreen extends StatelessWidget {
                       margin: const EdgeInsets.all(0.0),
                       elevation: 1,
                       child: Column(
-                        children: walletChangeNotifier.walletInfo.history
-                            .map((e) => e.toWidget())
-                            .toList(),
+                        children: [
+                          ...walletChangeNotifier.activity.map((e) => e.toWidget()),
+                          if (walletChangeNotifier.hasMoreActivity)
+                            TextButton(
+                              onPressed: () => walletChangeNotifier.loadMoreActivity(),
+                              child: const Text("Load more"),
+                            ),
+                        ],
                       ),
                     ),
                   ),
@@ -4,7 +4,13 @@ import 'dart:io';
 import 'package:logger/logger.dart';
 import 'package:path_provider/path_provider.dart';
 
+/// The size at which the log file is moved aside, so that the logs don't eat up the storage.
+const int maxLogFileSize = 5 * 1024 * 1024;
+
 /// Writes the log output to a file.
+///
+/// Once the file exceeds [maxLogFileSize] on start up, it replaces the previous log file, so at
+/// most two log files are kept.
 class HybridOutput extends LogOutput {
   final bool overrideExisting;
   final Encoding encoding;
@@ -18,6 +24,7 @@ class HybridOutput extends LogOutput {
   @override
   Future<void> init() async {
     File logFile = await logFilePath();
+    await _rotate(logFile);
 
     _sink = logFile.openWrite(
       mode: overrideExisting ? FileMode.writeOnly : FileMode.writeOnlyAppend,
@@ -33,6 +40,14 @@ class HybridOutput extends LogOutput {
     return logFile;
   }
 
+  static Future<void> _rotate(File logFile) async {
+    if (!await logFile.exists() || await logFile.length() < maxLogFileSize) {
+      return;
+    }
+
+    await logFile.rename('${logFile.path}.1');
+  }
+
   @override
   void output(OutputEvent event) {
     _sink?.writeAll(event.lines, '\n');
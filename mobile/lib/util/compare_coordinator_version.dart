@@ -2,6 +2,7 @@ import 'dart:convert';
 import 'package:get_10101/bridge_generated/bridge_definitions.dart' as bridge;
 import 'package:flutter/material.dart';
 import 'package:get_10101/common/global_keys.dart';
+import 'package:get_10101/ffi.dart' as rust;
 import 'package:get_10101/logger/logger.dart';
 import 'package:get_10101/util/coordinator_version.dart';
 import 'package:http/http.dart' as http;
@@ -17,14 +18,29 @@ Future<void> compareCoordinatorVersion(bridge.Config config) async {
   PackageInfo packageInfo = await PackageInfo.fromPlatform();
   try {
     final response = await http.get(
-      Uri.parse('http://${config.host}:${config.httpPort}/api/version'),
+      Uri.parse('http://${config.host}:${config.httpPort}/api/v1/version'),
     );
 
     final clientVersion = Version.parse(packageInfo.version);
     final coordinatorVersion = CoordinatorVersion.fromJson(jsonDecode(response.body));
     logger.i("Coordinator version: ${coordinatorVersion.version.toString()}");
 
-    if (coordinatorVersion.version > clientVersion) {
+    final minAppVersion = rust.api.getMinAppVersion();
+    if (minAppVersion != null && Version.parse(minAppVersion) > clientVersion) {
+      logger.w("Client is not supported anymore. Minimum version: $minAppVersion");
+      showDialog(
+          context: rootNavigatorKey.currentContext!,
+          builder: (context) => AlertDialog(
+                  title: const Text("Update required"),
+                  content: Text("This version of 10101 is not supported anymore.\n\n"
+                      "Please update 10101 to at least version $minAppVersion."),
+                  actions: [
+                    TextButton(
+                      onPressed: () => Navigator.pop(context, 'OK'),
+                      child: const Text('OK'),
+                    ),
+                  ]));
+    } else if (coordinatorVersion.version > clientVersion) {
       logger.w("Client out of date. Current version: ${clientVersion.toString()}");
       showDialog(
           context: rootNavigatorKey.currentContext!,
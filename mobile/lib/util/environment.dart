@@ -18,6 +18,12 @@ class Environment {
     String? rgsServerUrl = const bool.hasEnvironment("RGS_SERVER_URL")
         ? const String.fromEnvironment("RGS_SERVER_URL")
         : null;
+    String? watchtowerUrl = const bool.hasEnvironment("WATCHTOWER_URL")
+        ? const String.fromEnvironment("WATCHTOWER_URL")
+        : null;
+    // If not set, the backups are kept by the coordinator.
+    String? backupUrl =
+        const bool.hasEnvironment("BACKUP_URL") ? const String.fromEnvironment("BACKUP_URL") : null;
 
     String p2pEndpoint = const String.fromEnvironment('COORDINATOR_P2P_ENDPOINT');
     if (p2pEndpoint.contains("@")) {
@@ -29,8 +35,10 @@ class Environment {
       }
     }
 
-    int healthCheckIntervalSeconds =
-        const int.fromEnvironment('HEALTH_CHECK_INTERVAL_SECONDS', defaultValue: 10);
+    // If not set, the interval served by the coordinator is used.
+    int? healthCheckIntervalSeconds = const bool.hasEnvironment('HEALTH_CHECK_INTERVAL_SECONDS')
+        ? const int.fromEnvironment('HEALTH_CHECK_INTERVAL_SECONDS')
+        : null;
 
     return Config(
         host: host,
@@ -42,6 +50,8 @@ class Environment {
         oracleEndpoint: oracleEndpoint,
         oraclePubkey: oraclePubkey,
         healthCheckIntervalSecs: healthCheckIntervalSeconds,
-        rgsServerUrl: rgsServerUrl);
+        rgsServerUrl: rgsServerUrl,
+        watchtowerUrl: watchtowerUrl,
+        backupUrl: backupUrl);
   }
 }
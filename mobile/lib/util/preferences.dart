@@ -12,6 +12,8 @@ class Preferences {
   static const openPosition = "openPosition";
   static const fullBackup = "fullBackup";
   static const logLevelTrace = "logLevelTrace";
+  static const zeroConfChannels = "zeroConfChannels";
+  static const errorReporting = "errorReporting";
 
   Future<bool> setLogLevelTrace(bool trace) async {
     SharedPreferences preferences = await SharedPreferences.getInstance();
@@ -23,6 +25,26 @@ class Preferences {
     return preferences.getBool(logLevelTrace) ?? kDebugMode;
   }
 
+  Future<bool> setZeroConfChannelsEnabled(bool enabled) async {
+    SharedPreferences preferences = await SharedPreferences.getInstance();
+    return preferences.setBool(zeroConfChannels, enabled);
+  }
+
+  Future<bool> isZeroConfChannelsEnabled() async {
+    SharedPreferences preferences = await SharedPreferences.getInstance();
+    return preferences.getBool(zeroConfChannels) ?? true;
+  }
+
+  Future<bool> setErrorReportingEnabled(bool enabled) async {
+    SharedPreferences preferences = await SharedPreferences.getInstance();
+    return preferences.setBool(errorReporting, enabled);
+  }
+
+  Future<bool> isErrorReportingEnabled() async {
+    SharedPreferences preferences = await SharedPreferences.getInstance();
+    return preferences.getBool(errorReporting) ?? false;
+  }
+
   Future<bool> setFullBackupRequired(bool required) async {
     SharedPreferences preferences = await SharedPreferences.getInstance();
     return preferences.setBool(fullBackup, required);
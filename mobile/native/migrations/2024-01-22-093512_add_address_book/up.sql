@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE "address_book" (
+    address TEXT PRIMARY KEY NOT NULL,
+    label TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    last_used_at BIGINT
+);
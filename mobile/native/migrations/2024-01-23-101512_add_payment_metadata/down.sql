@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "payments" DROP COLUMN "expiry_timestamp";
+ALTER TABLE "payments" DROP COLUMN "payee_pubkey";
+ALTER TABLE "payments" DROP COLUMN "failure_reason";
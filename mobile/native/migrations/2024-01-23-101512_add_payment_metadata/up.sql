@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "payments" ADD COLUMN "expiry_timestamp" BIGINT;
+ALTER TABLE "payments" ADD COLUMN "payee_pubkey" TEXT;
+ALTER TABLE "payments" ADD COLUMN "failure_reason" TEXT;
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE "last_outbound_dlc_messages" (
+    peer_id TEXT PRIMARY KEY NOT NULL,
+    message_hash TEXT REFERENCES dlc_messages(message_hash) NOT NULL,
+    message TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
+
+INSERT OR REPLACE INTO "last_outbound_dlc_messages" (peer_id, message_hash, message, timestamp)
+SELECT peer_id, message_hash, message, timestamp FROM "outbound_dlc_messages" ORDER BY id;
+
+DROP TABLE "outbound_dlc_messages";
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE "outbound_dlc_messages" (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    message_hash TEXT UNIQUE REFERENCES dlc_messages(message_hash) NOT NULL,
+    peer_id TEXT NOT NULL,
+    message TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
+
+INSERT INTO "outbound_dlc_messages" (message_hash, peer_id, message, timestamp)
+SELECT message_hash, peer_id, message, timestamp FROM "last_outbound_dlc_messages";
+
+DROP TABLE "last_outbound_dlc_messages";
@@ -0,0 +1,37 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE positions_old (
+    contract_symbol TEXT PRIMARY KEY NOT NULL,
+    leverage FLOAT NOT NULL,
+    quantity FLOAT NOT NULL,
+    direction TEXT NOT NULL,
+    average_entry_price FLOAT NOT NULL,
+    liquidation_price FLOAT NOT NULL,
+    state TEXT NOT NULL,
+    collateral BIGINT NOT NULL,
+    creation_timestamp BIGINT NOT NULL,
+    expiry_timestamp BIGINT NOT NULL,
+    updated_timestamp BIGINT NOT NULL,
+    stable BOOLEAN NOT NULL DEFAULT false
+);
+
+-- Only the most recent position per contract symbol can be kept.
+INSERT OR REPLACE INTO positions_old
+SELECT
+    contract_symbol,
+    leverage,
+    quantity,
+    direction,
+    average_entry_price,
+    liquidation_price,
+    state,
+    collateral,
+    creation_timestamp,
+    expiry_timestamp,
+    updated_timestamp,
+    stable
+FROM positions
+ORDER BY creation_timestamp;
+
+DROP TABLE positions;
+
+ALTER TABLE positions_old RENAME TO positions;
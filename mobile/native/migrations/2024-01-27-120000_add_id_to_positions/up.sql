@@ -0,0 +1,43 @@
+-- Your SQL goes here
+-- Positions are identified by an id instead of their contract symbol, so that there can be more
+-- than one position per contract symbol.
+CREATE TABLE positions_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    contract_symbol TEXT NOT NULL,
+    leverage FLOAT NOT NULL,
+    quantity FLOAT NOT NULL,
+    direction TEXT NOT NULL,
+    average_entry_price FLOAT NOT NULL,
+    liquidation_price FLOAT NOT NULL,
+    state TEXT NOT NULL,
+    collateral BIGINT NOT NULL,
+    creation_timestamp BIGINT NOT NULL,
+    expiry_timestamp BIGINT NOT NULL,
+    updated_timestamp BIGINT NOT NULL,
+    stable BOOLEAN NOT NULL DEFAULT false
+);
+
+-- Existing positions get a random v4 UUID.
+INSERT INTO positions_new
+SELECT
+    lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+    substr(lower(hex(randomblob(2))), 2) || '-' ||
+    substr('89ab', 1 + (abs(random()) % 4), 1) || substr(lower(hex(randomblob(2))), 2) || '-' ||
+    lower(hex(randomblob(6))),
+    contract_symbol,
+    leverage,
+    quantity,
+    direction,
+    average_entry_price,
+    liquidation_price,
+    state,
+    collateral,
+    creation_timestamp,
+    expiry_timestamp,
+    updated_timestamp,
+    stable
+FROM positions;
+
+DROP TABLE positions;
+
+ALTER TABLE positions_new RENAME TO positions;
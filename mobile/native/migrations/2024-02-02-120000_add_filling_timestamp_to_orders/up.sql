@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE
+    orders
+    ADD
+        COLUMN "filling_timestamp" BIGINT;
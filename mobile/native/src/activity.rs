@@ -0,0 +1,83 @@
+use crate::api::ActivityPage;
+use crate::api::WalletHistoryItem;
+use parking_lot::const_rwlock;
+use parking_lot::RwLock;
+
+/// The activity of the wallet, i.e. on-chain transactions, lightning payments, trades and DLC
+/// channel funding transactions in a single timeline, ordered from newest to oldest.
+///
+/// The timeline is assembled whenever the wallet info is refreshed and handed out to the UI page
+/// by page.
+static ACTIVITY: RwLock<Vec<WalletHistoryItem>> = const_rwlock(Vec::new());
+
+/// Replace the timeline with the given items, which are sorted from newest to oldest.
+pub(crate) fn update(mut items: Vec<WalletHistoryItem>) {
+    // A stable sort keeps the order of items with the same timestamp, e.g. back-to-back trades.
+    items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    *ACTIVITY.write() = items;
+}
+
+/// Get `limit` items of the timeline, skipping the `offset` newest ones.
+pub(crate) fn page(offset: usize, limit: usize) -> ActivityPage {
+    let activity = ACTIVITY.read();
+
+    page_of(&activity, offset, limit)
+}
+
+fn page_of(activity: &[WalletHistoryItem], offset: usize, limit: usize) -> ActivityPage {
+    let items = activity
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    ActivityPage {
+        has_more: offset + items.len() < activity.len(),
+        total: activity.len() as u64,
+        items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PaymentFlow;
+    use crate::api::Status;
+    use crate::api::WalletHistoryItemType;
+
+    #[test]
+    fn pages_cover_the_whole_timeline() {
+        let activity = (0..5).rev().map(dummy_item).collect::<Vec<_>>();
+
+        let first = page_of(&activity, 0, 2);
+        assert_eq!(timestamps(&first), vec![4, 3]);
+        assert!(first.has_more);
+        assert_eq!(first.total, 5);
+
+        let last = page_of(&activity, 4, 2);
+        assert_eq!(timestamps(&last), vec![0]);
+        assert!(!last.has_more);
+
+        assert!(page_of(&activity, 5, 2).items.is_empty());
+    }
+
+    fn timestamps(page: &ActivityPage) -> Vec<u64> {
+        page.items.iter().map(|item| item.timestamp).collect()
+    }
+
+    fn dummy_item(timestamp: u64) -> WalletHistoryItem {
+        WalletHistoryItem {
+            flow: PaymentFlow::Inbound,
+            amount_sats: 1_000,
+            timestamp,
+            status: Status::Confirmed,
+            wallet_type: WalletHistoryItemType::OnChain {
+                txid: format!("{timestamp}"),
+                fee_sats: None,
+                confirmations: 6,
+            },
+        }
+    }
+}
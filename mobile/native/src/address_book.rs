@@ -0,0 +1,67 @@
+use crate::db;
+use crate::db::address_book::AddressBookEntry;
+use crate::state;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::Address;
+use ln_dlc_node::WalletAddress;
+
+/// A previously used receive address of our wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressReuse {
+    pub derivation_index: u32,
+    /// The number of transactions which already paid to the address.
+    pub usage_count: usize,
+}
+
+/// The receive addresses handed out by the on-chain wallet so far.
+pub fn list_receive_addresses() -> Result<Vec<WalletAddress>> {
+    state::get_node()
+        .inner
+        .ldk_wallet()
+        .list_receive_addresses()
+}
+
+/// The addresses the user has withdrawn to or saved, most recently added first.
+pub fn list_withdrawal_addresses() -> Result<Vec<AddressBookEntry>> {
+    let mut conn = db::connection()?;
+    let entries = AddressBookEntry::get_all(&mut conn)?;
+
+    Ok(entries)
+}
+
+pub fn save_withdrawal_address(address: &Address, label: &str) -> Result<()> {
+    let mut conn = db::connection()?;
+    AddressBookEntry::upsert_label(&mut conn, &address.to_string(), label)
+}
+
+pub fn delete_withdrawal_address(address: &Address) -> Result<()> {
+    let mut conn = db::connection()?;
+    AddressBookEntry::delete(&mut conn, &address.to_string())
+        .context("Failed to delete address from address book")?;
+
+    Ok(())
+}
+
+/// Remember that we withdrew to the given address.
+pub fn record_withdrawal(address: &Address) -> Result<()> {
+    let mut conn = db::connection()?;
+    AddressBookEntry::mark_used(&mut conn, &address.to_string())
+}
+
+/// Checks whether the given address is one of our receive addresses which has already received
+/// funds.
+///
+/// Reusing an address links the payments to it, so the user should be warned before doing so.
+pub fn check_address_reuse(address: &Address) -> Result<Option<AddressReuse>> {
+    let reuse = list_receive_addresses()?
+        .into_iter()
+        .find(|receive_address| &receive_address.address == address)
+        .filter(|receive_address| receive_address.usage_count > 0)
+        .map(|receive_address| AddressReuse {
+            derivation_index: receive_address.derivation_index,
+            usage_count: receive_address.usage_count,
+        });
+
+    Ok(reuse)
+}
@@ -1,4 +1,8 @@
+use crate::activity;
+use crate::address_book;
+use crate::backup;
 use crate::calculations;
+use crate::channel_backup;
 use crate::channel_trade_constraints;
 use crate::commons::api::ChannelInfo;
 use crate::commons::api::Price;
@@ -8,17 +12,34 @@ use crate::config::api::Directories;
 use crate::config::get_network;
 use crate::db;
 use crate::destination;
+use crate::device;
+use crate::diagnostics;
+use crate::error;
+use crate::error::AppError;
+use crate::error_report;
 use crate::event;
 use crate::event::api::FlutterSubscriber;
+use crate::fee_estimates;
 use crate::health;
+use crate::keystore;
 use crate::ln_dlc;
 use crate::ln_dlc::get_storage;
+use crate::ln_dlc::DlcProtocolState;
 use crate::ln_dlc::FUNDING_TX_WEIGHT_ESTIMATE;
+use crate::lnurl;
+use crate::local_backup;
+use crate::log_file;
 use crate::logger;
 use crate::orderbook;
+use crate::pending_action;
+use crate::pending_action::api::PendingAction;
+use crate::recovery;
+use crate::scheduler;
+use crate::trade::market_stats;
 use crate::trade::order;
 use crate::trade::order::api::NewOrder;
 use crate::trade::order::api::Order;
+use crate::trade::paper;
 use crate::trade::position;
 use crate::trade::position::api::Position;
 use crate::trade::users;
@@ -27,8 +48,10 @@ use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bdk::FeeRate;
+use bitcoin::secp256k1::SECP256K1;
+use bitcoin::Address;
 use bitcoin::Amount;
-use commons::order_matching_fee_taker;
+use bitcoin::OutPoint;
 use commons::OrderbookRequest;
 use flutter_rust_bridge::frb;
 use flutter_rust_bridge::StreamSink;
@@ -43,12 +66,15 @@ use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use std::backtrace::Backtrace;
 use std::fmt;
+use std::path::Path;
 use std::path::PathBuf;
-use time::OffsetDateTime;
+use std::str::FromStr;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::channel;
 pub use trade::ContractSymbol;
 pub use trade::Direction;
+use uuid::Uuid;
 
 /// Initialise logging infrastructure for Rust
 pub fn init_logging(sink: StreamSink<logger::LogEntry>) {
@@ -77,7 +103,6 @@ impl From<commons::LspConfig> for LspConfig {
 #[derive(Clone, Debug, Default)]
 pub struct WalletInfo {
     pub balances: Balances,
-    pub history: Vec<WalletHistoryItem>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -105,6 +130,24 @@ pub fn refresh_lightning_wallet() -> Result<()> {
     ln_dlc::refresh_lightning_wallet()
 }
 
+/// A page of the wallet activity, see [`get_activity`].
+#[derive(Clone, Debug)]
+pub struct ActivityPage {
+    pub items: Vec<WalletHistoryItem>,
+    /// The number of items in the whole timeline.
+    pub total: u64,
+    pub has_more: bool,
+}
+
+/// Get a page of the wallet activity, i.e. on-chain transactions, lightning payments, trades and
+/// DLC channel funding transactions in a single timeline, ordered from newest to oldest.
+///
+/// The timeline is updated whenever the wallet info is refreshed, which is announced by a
+/// [`event::api::Event::WalletInfoUpdateNotification`].
+pub fn get_activity(offset: u32, limit: u32) -> SyncReturn<ActivityPage> {
+    SyncReturn(activity::page(offset as usize, limit as usize))
+}
+
 #[derive(Clone, Debug)]
 pub struct WalletHistoryItem {
     pub flow: PaymentFlow,
@@ -129,6 +172,9 @@ pub enum WalletHistoryItemType {
         fee_msat: Option<u64>,
         expiry_timestamp: Option<u64>,
         funding_txid: Option<String>,
+        /// The node ID of the receiver of the payment.
+        payee: Option<String>,
+        failure_reason: Option<PaymentFailureReason>,
     },
     Trade {
         order_id: String,
@@ -173,6 +219,32 @@ pub enum Status {
     Failed,
 }
 
+/// Why a Lightning payment failed.
+#[derive(Clone, Copy, Debug)]
+pub enum PaymentFailureReason {
+    RecipientRejected,
+    UserAbandoned,
+    RetriesExhausted,
+    PaymentExpired,
+    RouteNotFound,
+    DuplicatePayment,
+    UnexpectedError,
+}
+
+impl From<ln_dlc_node::PaymentFailureReason> for PaymentFailureReason {
+    fn from(value: ln_dlc_node::PaymentFailureReason) -> Self {
+        match value {
+            ln_dlc_node::PaymentFailureReason::RecipientRejected => Self::RecipientRejected,
+            ln_dlc_node::PaymentFailureReason::UserAbandoned => Self::UserAbandoned,
+            ln_dlc_node::PaymentFailureReason::RetriesExhausted => Self::RetriesExhausted,
+            ln_dlc_node::PaymentFailureReason::PaymentExpired => Self::PaymentExpired,
+            ln_dlc_node::PaymentFailureReason::RouteNotFound => Self::RouteNotFound,
+            ln_dlc_node::PaymentFailureReason::DuplicatePayment => Self::DuplicatePayment,
+            ln_dlc_node::PaymentFailureReason::UnexpectedError => Self::UnexpectedError,
+        }
+    }
+}
+
 pub fn calculate_margin(price: f32, quantity: f32, leverage: f32) -> SyncReturn<u64> {
     SyncReturn(calculations::calculate_margin(price, quantity, leverage))
 }
@@ -230,21 +302,62 @@ pub fn calculate_pnl(
 /// Calculate the order matching fee that the app user will have to pay for if the corresponding
 /// trade gets executed.
 ///
-/// This is only an estimate as the price may change slightly. Also, the coordinator could choose to
-/// change the fee structure independently.
+/// This is only an estimate as the price may change slightly. The fee rate is the one served by the
+/// coordinator, which may have changed since the app config was fetched.
 pub fn order_matching_fee(quantity: f32, price: f32) -> SyncReturn<u64> {
     let price = Decimal::from_f32(price).expect("price to fit in Decimal");
 
-    let order_matching_fee = order_matching_fee_taker(quantity, price).to_sat();
+    let order_matching_fee =
+        commons::order_matching_fee(quantity, price, config::taker_fee_rate()).to_sat();
 
     SyncReturn(order_matching_fee)
 }
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn submit_order(order: NewOrder) -> Result<String> {
-    order::handler::submit_order(order.into())
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+
+    let order: order::Order = order.into();
+    order.validate().map_err(error::to_ffi)?;
+
+    order::handler::submit_order(order)
+        .await
+        .map_err(error::to_ffi)
+        .map(|id| id.to_string())
+}
+
+fn parse_position_id(position_id: &str) -> Result<Uuid> {
+    Uuid::parse_str(position_id).map_err(|e| {
+        error::to_ffi(AppError::InvalidInput(format!(
+            "Invalid position id {position_id}: {e}"
+        )))
+    })
+}
+
+/// Close the open position with the given id with a market order for the whole position.
+#[tokio::main(flavor = "current_thread")]
+pub async fn close_position(position_id: String) -> Result<String> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    let position_id = parse_position_id(&position_id)?;
+    position::handler::close_position(position_id)
         .await
-        .map_err(anyhow::Error::new)
+        .map_err(error::to_ffi)
+        .map(|id| id.to_string())
+}
+
+/// Resize the open position with the given id to `new_quantity` contracts at `new_leverage`, by
+/// trading the difference to the current quantity.
+#[tokio::main(flavor = "current_thread")]
+pub async fn resize_position(
+    position_id: String,
+    new_quantity: f32,
+    new_leverage: f32,
+) -> Result<String> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    let position_id = parse_position_id(&position_id)?;
+    position::handler::resize_position(position_id, new_quantity, new_leverage)
+        .await
+        .map_err(error::to_ffi)
         .map(|id| id.to_string())
 }
 
@@ -268,14 +381,93 @@ pub async fn get_async_order() -> Result<Option<Order>> {
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn get_positions() -> Result<Vec<Position>> {
-    let positions = position::handler::get_positions()?
+    let positions = position::handler::get_positions_for_ui()?
         .into_iter()
-        .map(|order| order.into())
+        .map(|position| position.into())
         .collect::<Vec<Position>>();
 
     Ok(positions)
 }
 
+/// Trading statistics of a market.
+///
+/// Please refer to [`commons::MarketStats`]
+#[derive(Debug, Clone)]
+pub struct MarketStats {
+    pub contract_symbol: ContractSymbol,
+    /// Traded volume of the last hour.
+    pub volume_hour: f32,
+    /// Traded volume of the last 24 hours.
+    pub volume_day: f32,
+    /// Traded volume of the last 7 days.
+    pub volume_week: f32,
+    pub high_day: Option<f32>,
+    pub low_day: Option<f32>,
+    pub last_price: Option<f32>,
+    pub open_interest: f32,
+}
+
+impl From<commons::MarketStats> for MarketStats {
+    fn from(value: commons::MarketStats) -> Self {
+        Self {
+            contract_symbol: value.contract_symbol,
+            volume_hour: value.volume_1h,
+            volume_day: value.volume_24h,
+            volume_week: value.volume_7d,
+            high_day: value.high_24h,
+            low_day: value.low_24h,
+            last_price: value.last_price,
+            open_interest: value.open_interest,
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn get_market_stats(contract_symbol: ContractSymbol) -> Result<MarketStats> {
+    let stats = market_stats::get_market_stats(contract_symbol).await?;
+
+    Ok(stats.into())
+}
+
+/// Returns all actions which are pending and might have to be retried or aborted by the user to
+/// recover from a stuck protocol state.
+pub fn get_pending_actions() -> Result<Vec<PendingAction>> {
+    let pending_actions = pending_action::get_pending_actions()?
+        .into_iter()
+        .map(PendingAction::from)
+        .collect();
+
+    Ok(pending_actions)
+}
+
+pub fn retry_pending_action(pending_action: PendingAction) -> Result<()> {
+    pending_action::retry(pending_action.try_into()?)
+}
+
+pub fn abort_pending_action(pending_action: PendingAction) -> Result<()> {
+    pending_action::abort(pending_action.try_into()?)
+}
+
+/// Returns the step of the DLC protocol the DLC channel with the coordinator is in and who has to
+/// act next.
+pub fn get_dlc_protocol_state() -> Result<DlcProtocolState> {
+    ln_dlc::dlc_protocol_state::get_dlc_protocol_state()
+}
+
+/// Accepts the DLC offer on the DLC channel with the given hex encoded ID, which was parked
+/// because it did not match any action of the user.
+pub fn accept_dlc_offer(channel_id: String) -> Result<()> {
+    let channel_id = pending_action::api::parse_dlc_channel_id(&channel_id)?;
+    pending_action::retry(pending_action::get_pending_dlc_offer(&channel_id)?)
+}
+
+/// Rejects the DLC offer on the DLC channel with the given hex encoded ID, which was parked
+/// because it did not match any action of the user.
+pub fn reject_dlc_offer(channel_id: String) -> Result<()> {
+    let channel_id = pending_action::api::parse_dlc_channel_id(&channel_id)?;
+    pending_action::abort(pending_action::get_pending_dlc_offer(&channel_id)?)
+}
+
 pub fn delete_network_graph() -> Result<()> {
     crate::state::get_storage()
         .ln_storage
@@ -295,6 +487,8 @@ pub fn subscribe(stream: StreamSink<event::api::Event>) {
 
 /// Wrapper for Flutter purposes - can throw an exception.
 pub fn run_in_flutter(seed_dir: String, fcm_token: String) -> Result<()> {
+    crate::state::set_fcm_token(fcm_token.clone());
+
     match crate::state::try_get_websocket() {
         None => {
             let (tx_websocket, _rx) = channel::<OrderbookRequest>(10);
@@ -314,10 +508,11 @@ pub fn run_in_flutter(seed_dir: String, fcm_token: String) -> Result<()> {
             // the coordinator and trigger a new user login event.
             tracing::info!("Re-sending authentication message");
 
+            let node_key = ln_dlc::get_node_key()?;
             let signature =
                 orderbook_client::create_auth_message_signature(move |msg| commons::Signature {
-                    pubkey: ln_dlc::get_node_pubkey(),
-                    signature: ln_dlc::get_node_key().sign_ecdsa(msg),
+                    pubkey: node_key.public_key(SECP256K1),
+                    signature: node_key.sign_ecdsa(msg),
                 });
 
             let runtime = crate::state::get_or_create_tokio_runtime()?;
@@ -325,6 +520,7 @@ pub fn run_in_flutter(seed_dir: String, fcm_token: String) -> Result<()> {
                 tx_websocket.send(OrderbookRequest::Authenticate {
                     fcm_token: Some(fcm_token),
                     signature,
+                    device: device::get_device().ok(),
                 })
             })?;
         }
@@ -333,7 +529,7 @@ pub fn run_in_flutter(seed_dir: String, fcm_token: String) -> Result<()> {
     Ok(())
 }
 
-/// Wrapper for the tests.
+/// Wrapper for the tests and the headless CLI.
 ///
 /// Needed as we do not want to have a hot restart handling in the tests and also can't expose a
 /// channel::Sender through frb.
@@ -358,10 +554,269 @@ pub fn set_config(config: Config, app_dir: String, seed_dir: String) -> Result<(
     Ok(())
 }
 
+/// Fetch the app config from the coordinator. If the coordinator can't be reached, the app keeps
+/// using its defaults.
+#[tokio::main(flavor = "current_thread")]
+pub async fn refresh_app_config() -> Result<()> {
+    config::remote::refresh_app_config().await
+}
+
+/// Allow trading with a channel opened by the coordinator before its funding transaction
+/// confirms.
+pub fn set_zero_conf_channels_enabled(enabled: bool) -> Result<()> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    runtime.block_on(ln_dlc::set_zero_conf_channels_enabled(enabled));
+
+    Ok(())
+}
+
+/// Route orders to an in-memory simulator, filling them at the current price against a fake
+/// balance, instead of trading with the coordinator.
+pub fn set_paper_trading(enabled: bool) -> Result<()> {
+    paper::set_enabled(enabled)
+}
+
+pub fn is_paper_trading() -> SyncReturn<bool> {
+    SyncReturn(config::is_paper_trading())
+}
+
+/// The fake balance available for paper trading, in sats.
+pub fn get_paper_trading_balance() -> SyncReturn<u64> {
+    SyncReturn(paper::get_balance())
+}
+
+/// Start paper trading over with the initial fake balance and without any positions.
+pub fn reset_paper_trading() -> Result<()> {
+    paper::reset();
+    Ok(())
+}
+
+/// Report errors and panics of the app to the coordinator, without anything identifying the user.
+pub fn set_error_reporting_enabled(enabled: bool, app_version: String) {
+    error_report::set_enabled(enabled, app_version)
+}
+
+pub fn is_error_reporting_enabled() -> SyncReturn<bool> {
+    SyncReturn(error_report::is_enabled())
+}
+
+/// Set by how much, relative to what we expect, the payout proposed by the coordinator to
+/// collaboratively close the DLC channel may deviate, e.g. `0.01` for 1%.
+pub fn set_collaborative_close_payout_tolerance(tolerance: f32) -> Result<()> {
+    let tolerance = Decimal::try_from(tolerance)?;
+    ensure!(
+        tolerance >= Decimal::ZERO,
+        "Collaborative close payout tolerance must not be negative"
+    );
+
+    config::set_collaborative_close_payout_tolerance(tolerance);
+
+    Ok(())
+}
+
+/// Upload the justice transactions for our channels to the watchtower at `url`, so that it can
+/// punish the coordinator for broadcasting a revoked commitment transaction while we are offline.
+pub fn register_watchtower(url: String) -> Result<()> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    runtime.block_on(ln_dlc::register_watchtower(url))
+}
+
+pub fn is_feature_enabled(feature: String) -> SyncReturn<bool> {
+    SyncReturn(config::is_feature_enabled(&feature))
+}
+
+/// The oldest app version still supported by the coordinator, if configured.
+pub fn get_min_app_version() -> SyncReturn<Option<String>> {
+    SyncReturn(config::get_min_app_version())
+}
+
 #[tokio::main(flavor = "current_thread")]
 pub async fn full_backup() -> Result<()> {
     db::init_db(&config::get_data_dir(), get_network())?;
-    get_storage().full_backup().await
+    get_storage()?.full_backup().await
+}
+
+/// The result of [`verify_backup`].
+#[derive(Clone, Debug)]
+pub struct BackupVerification {
+    pub entries: u64,
+    /// Keys which exist on the device but are missing in the backup.
+    pub missing_keys: Vec<String>,
+    /// Keys whose backed up value is corrupted.
+    pub corrupt_keys: Vec<String>,
+}
+
+impl From<backup::BackupVerification> for BackupVerification {
+    fn from(value: backup::BackupVerification) -> Self {
+        Self {
+            entries: value.entries as u64,
+            missing_keys: value.missing_keys,
+            corrupt_keys: value.corrupt_keys,
+        }
+    }
+}
+
+/// Downloads the remote backup and checks that it is complete and can be restored, without
+/// writing any files.
+#[tokio::main(flavor = "current_thread")]
+pub async fn verify_backup() -> Result<BackupVerification> {
+    let verification = get_storage()?.verify_backup().await?;
+    Ok(verification.into())
+}
+
+/// Whether the backups of the funds-recovery data are up to date.
+#[derive(Clone, Debug)]
+pub struct BackupStatus {
+    /// When the Lightning data has last been backed up, as unix timestamp.
+    pub last_ln_backup: Option<i64>,
+    /// When the DLC data has last been backed up, as unix timestamp.
+    pub last_dlc_backup: Option<i64>,
+    /// When the 10101 database has last been backed up, as unix timestamp.
+    pub last_db_backup: Option<i64>,
+    pub pending_uploads: u64,
+    pub failed_uploads: u64,
+    /// The size of the backup in bytes.
+    pub total_size: u64,
+}
+
+impl From<backup::BackupStatus> for BackupStatus {
+    fn from(value: backup::BackupStatus) -> Self {
+        let last_backup = |category| {
+            value
+                .last_backup
+                .get(&category)
+                .map(|timestamp| timestamp.unix_timestamp())
+        };
+
+        Self {
+            last_ln_backup: last_backup(backup::BackupCategory::Ln),
+            last_dlc_backup: last_backup(backup::BackupCategory::Dlc),
+            last_db_backup: last_backup(backup::BackupCategory::Db),
+            pending_uploads: value.pending as u64,
+            failed_uploads: value.failed as u64,
+            total_size: value.total_size,
+        }
+    }
+}
+
+/// The kind of data which can be restored separately, see [`restore_backup_categories`].
+#[derive(Clone, Copy, Debug)]
+pub enum BackupCategory {
+    /// The Lightning channel state.
+    Ln,
+    /// The DLC channel and contract state.
+    Dlc,
+    /// The 10101 database, i.e. trades, orders and positions.
+    Db,
+}
+
+impl From<BackupCategory> for backup::BackupCategory {
+    fn from(value: BackupCategory) -> Self {
+        match value {
+            BackupCategory::Ln => backup::BackupCategory::Ln,
+            BackupCategory::Dlc => backup::BackupCategory::Dlc,
+            BackupCategory::Db => backup::BackupCategory::Db,
+        }
+    }
+}
+
+/// The result of restoring from the backup.
+#[derive(Clone, Debug)]
+pub struct RestoreSummary {
+    pub restored_keys: Vec<String>,
+    pub failures: Vec<RestoreFailure>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RestoreFailure {
+    pub key: String,
+    pub reason: String,
+}
+
+impl From<backup::RestoreSummary> for RestoreSummary {
+    fn from(value: backup::RestoreSummary) -> Self {
+        Self {
+            restored_keys: value.restored,
+            failures: value
+                .failed
+                .into_iter()
+                .map(|(key, reason)| RestoreFailure { key, reason })
+                .collect(),
+        }
+    }
+}
+
+pub fn get_backup_status() -> Result<BackupStatus> {
+    Ok(get_storage()?.client.status().into())
+}
+
+#[derive(Clone, Debug)]
+pub struct BackgroundTaskStatus {
+    pub name: String,
+    /// How often the task runs, or `None` for a service which runs continuously.
+    pub interval_secs: Option<u64>,
+    pub state: BackgroundTaskState,
+    /// The unix timestamp of the last completed run.
+    pub last_run: Option<i64>,
+    /// The error of the last run, if it failed.
+    pub last_error: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum BackgroundTaskState {
+    Waiting,
+    Running,
+    Paused,
+    Stopped,
+}
+
+impl From<scheduler::TaskStatus> for BackgroundTaskStatus {
+    fn from(value: scheduler::TaskStatus) -> Self {
+        Self {
+            name: value.name.to_string(),
+            interval_secs: value.interval.map(|interval| interval.as_secs()),
+            state: value.state.into(),
+            last_run: value.last_run.map(|last_run| last_run.unix_timestamp()),
+            last_error: value.last_error,
+            runs: value.runs,
+            failures: value.failures,
+        }
+    }
+}
+
+impl From<scheduler::TaskState> for BackgroundTaskState {
+    fn from(value: scheduler::TaskState) -> Self {
+        match value {
+            scheduler::TaskState::Waiting => BackgroundTaskState::Waiting,
+            scheduler::TaskState::Running => BackgroundTaskState::Running,
+            scheduler::TaskState::Paused => BackgroundTaskState::Paused,
+            scheduler::TaskState::Stopped => BackgroundTaskState::Stopped,
+        }
+    }
+}
+
+/// The health of the tasks the node runs in the background, e.g. syncing the wallet.
+pub fn get_background_task_status() -> SyncReturn<Vec<BackgroundTaskStatus>> {
+    SyncReturn(
+        scheduler::status()
+            .into_iter()
+            .map(BackgroundTaskStatus::from)
+            .collect(),
+    )
+}
+
+/// Pauses the background tasks, to be called when the OS puts the app into the background.
+pub fn pause_background_tasks() -> SyncReturn<()> {
+    scheduler::pause();
+    SyncReturn(())
+}
+
+/// Resumes the background tasks, to be called when the app is back in the foreground.
+pub fn resume_background_tasks() -> SyncReturn<()> {
+    scheduler::resume();
+    SyncReturn(())
 }
 
 fn run_internal(
@@ -384,18 +839,44 @@ fn run_internal(
         );
     }
 
+    if let Err(e) = log_file::init(Path::new(&config::get_data_dir())) {
+        tracing::error!("Failed to open the log file: {e:#}");
+    }
+
+    if let Err(e) = error_report::init(Path::new(&config::get_data_dir())) {
+        tracing::error!("Failed to load pending error reports: {e:#}");
+    }
+
     db::init_db(&config::get_data_dir(), get_network())?;
 
+    // Loaded before the node is started, so that the node does not persist anything if another
+    // device has taken over our session.
+    let device = match device::get_device() {
+        Ok(device) => Some(device),
+        Err(e) => {
+            tracing::error!("Failed to load device registration: {e:#}");
+            None
+        }
+    };
+
     let runtime = crate::state::get_or_create_tokio_runtime()?;
     ln_dlc::run(seed_dir, runtime)?;
 
+    scheduler::spawn_periodic(
+        runtime,
+        "error_report",
+        error_report::UPLOAD_INTERVAL,
+        error_report::upload,
+    );
+
     let (_health, tx) = health::Health::new(runtime);
 
     orderbook::subscribe(
-        ln_dlc::get_node_key(),
+        ln_dlc::get_node_key()?,
         runtime,
         tx.orderbook,
         fcm_token,
+        device,
         tx_websocket,
     )
 }
@@ -404,14 +885,134 @@ pub fn get_unused_address() -> SyncReturn<String> {
     SyncReturn(ln_dlc::get_unused_address())
 }
 
+pub struct Utxo {
+    pub outpoint: String,
+    pub amount_sats: u64,
+    pub address: Option<String>,
+    pub is_confirmed: bool,
+    /// A frozen UTXO is never spent by the wallet until it is unfrozen again.
+    pub is_frozen: bool,
+    /// The UTXO is temporarily locked by an ongoing channel funding.
+    pub is_locked: bool,
+}
+
+impl From<ln_dlc_node::WalletUtxo> for Utxo {
+    fn from(value: ln_dlc_node::WalletUtxo) -> Self {
+        Self {
+            outpoint: value.outpoint.to_string(),
+            amount_sats: value.amount_sats,
+            address: value.address.map(|address| address.to_string()),
+            is_confirmed: value.is_confirmed,
+            is_frozen: value.is_frozen,
+            is_locked: value.is_locked,
+        }
+    }
+}
+
+pub fn list_utxos() -> Result<Vec<Utxo>> {
+    let utxos = ln_dlc::list_utxos()?;
+    Ok(utxos.into_iter().map(Utxo::from).collect())
+}
+
+pub fn freeze_utxo(outpoint: String) -> Result<()> {
+    let outpoint = OutPoint::from_str(&outpoint).context("Invalid outpoint")?;
+    ln_dlc::freeze_utxo(outpoint)
+}
+
+pub fn unfreeze_utxo(outpoint: String) -> Result<()> {
+    let outpoint = OutPoint::from_str(&outpoint).context("Invalid outpoint")?;
+    ln_dlc::unfreeze_utxo(outpoint)
+}
+
+/// A receive address handed out by the on-chain wallet.
+pub struct ReceiveAddress {
+    pub address: String,
+    pub derivation_index: u32,
+    pub balance_sats: u64,
+    /// The number of transactions which paid to the address.
+    pub usage_count: u32,
+}
+
+impl From<ln_dlc_node::WalletAddress> for ReceiveAddress {
+    fn from(value: ln_dlc_node::WalletAddress) -> Self {
+        Self {
+            address: value.address.to_string(),
+            derivation_index: value.derivation_index,
+            balance_sats: value.balance_sats,
+            usage_count: value.usage_count as u32,
+        }
+    }
+}
+
+/// An address the user has withdrawn to or saved for later withdrawals.
+pub struct AddressBookEntry {
+    pub address: String,
+    pub label: String,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+impl From<db::address_book::AddressBookEntry> for AddressBookEntry {
+    fn from(value: db::address_book::AddressBookEntry) -> Self {
+        Self {
+            address: value.address,
+            label: value.label,
+            created_at: value.created_at,
+            last_used_at: value.last_used_at,
+        }
+    }
+}
+
+/// Returned if the user is about to reuse one of our receive addresses which already received
+/// funds.
+pub struct AddressReuse {
+    pub derivation_index: u32,
+    pub usage_count: u32,
+}
+
+impl From<address_book::AddressReuse> for AddressReuse {
+    fn from(value: address_book::AddressReuse) -> Self {
+        Self {
+            derivation_index: value.derivation_index,
+            usage_count: value.usage_count as u32,
+        }
+    }
+}
+
+pub fn list_addresses() -> Result<Vec<ReceiveAddress>> {
+    let addresses = address_book::list_receive_addresses()?;
+    Ok(addresses.into_iter().map(ReceiveAddress::from).collect())
+}
+
+pub fn list_address_book() -> Result<Vec<AddressBookEntry>> {
+    let entries = address_book::list_withdrawal_addresses()?;
+    Ok(entries.into_iter().map(AddressBookEntry::from).collect())
+}
+
+pub fn save_address_book_entry(address: String, label: String) -> Result<()> {
+    let address = Address::from_str(&address).context("Invalid address")?;
+    address_book::save_withdrawal_address(&address, &label)
+}
+
+pub fn delete_address_book_entry(address: String) -> Result<()> {
+    let address = Address::from_str(&address).context("Invalid address")?;
+    address_book::delete_withdrawal_address(&address)
+}
+
+pub fn check_address_reuse(address: String) -> Result<Option<AddressReuse>> {
+    let address = Address::from_str(&address).context("Invalid address")?;
+    let reuse = address_book::check_address_reuse(&address)?;
+    Ok(reuse.map(AddressReuse::from))
+}
+
 #[tokio::main(flavor = "current_thread")]
 pub async fn close_channel() -> Result<()> {
-    ln_dlc::close_channel(false).await
+    ln_dlc::close_channel(false).await.map_err(error::to_ffi)
 }
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn force_close_channel() -> Result<()> {
-    ln_dlc::close_channel(true).await
+    ln_dlc::close_channel(true).await.map_err(error::to_ffi)
 }
 
 /// Returns channel info if we have a channel available already
@@ -506,12 +1107,42 @@ impl From<commons::LiquidityOption> for LiquidityOption {
     }
 }
 
+/// The terms under which the coordinator opens a channel to fund the wallet.
+pub struct JitChannelConfig {
+    pub min_channel_size_sats: u64,
+    pub max_channel_size_sats: Option<u64>,
+    pub liquidity_fee_ppm: u32,
+    /// The coordinator only opens channels while the on-chain fee rate is at most this value.
+    pub max_tx_fee_rate_sats_per_kwu: Option<u32>,
+}
+
+impl From<commons::JitChannelConfig> for JitChannelConfig {
+    fn from(value: commons::JitChannelConfig) -> Self {
+        JitChannelConfig {
+            min_channel_size_sats: value.min_channel_size_sats,
+            max_channel_size_sats: value.max_channel_size_sats,
+            liquidity_fee_ppm: value.liquidity_fee_ppm,
+            max_tx_fee_rate_sats_per_kwu: value.max_tx_fee_rate_sats_per_kwu,
+        }
+    }
+}
+
+pub fn get_jit_channel_config() -> Result<JitChannelConfig> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    let jit_channel_config = runtime.block_on(ln_dlc::fetch_jit_channel_config())?;
+
+    Ok(jit_channel_config.into())
+}
+
 pub fn create_onboarding_invoice(
     liquidity_option_id: i32,
     amount_sats: u64,
     fee_sats: u64,
 ) -> Result<String> {
-    Ok(ln_dlc::create_onboarding_invoice(liquidity_option_id, amount_sats, fee_sats)?.to_string())
+    let invoice = ln_dlc::create_onboarding_invoice(liquidity_option_id, amount_sats, fee_sats)
+        .map_err(error::to_ffi)?;
+
+    Ok(invoice.to_string())
 }
 
 pub struct PaymentRequest {
@@ -543,9 +1174,52 @@ pub enum SendPayment {
     },
     OnChain {
         address: String,
+        /// The amount in sats, `0` to send all the funds of the wallet.
         amount: u64,
         fee: Fee,
+        /// The inputs of a [`SendPreview`], to send exactly the previewed transaction.
+        inputs: Option<Vec<String>>,
+    },
+    /// Pay to a Lightning address by requesting an invoice for `amount` sats from its service.
+    LightningAddress {
+        address: String,
+        amount: u64,
+        comment: Option<String>,
+    },
+}
+
+/// How much to send on-chain.
+pub enum SendAmount {
+    Sats {
+        amount: u64,
     },
+    /// Send all the funds of the wallet, minus the fee.
+    Max,
+}
+
+/// The exact outcome of an on-chain payment, computed before broadcasting it.
+pub struct SendPreview {
+    /// The amount the recipient receives.
+    pub amount_sats: u64,
+    pub fee_sats: u64,
+    pub sats_per_vbyte: f32,
+    /// The outpoints of the UTXOs spent by the transaction.
+    pub inputs: Vec<String>,
+}
+
+impl From<ln_dlc_node::SendPreview> for SendPreview {
+    fn from(value: ln_dlc_node::SendPreview) -> Self {
+        Self {
+            amount_sats: value.amount_sats,
+            fee_sats: value.fee_sats,
+            sats_per_vbyte: value.fee_rate.as_sat_per_vb(),
+            inputs: value
+                .inputs
+                .iter()
+                .map(|outpoint| outpoint.to_string())
+                .collect(),
+        }
+    }
 }
 
 /// The choice of on-chain network fee
@@ -590,11 +1264,17 @@ impl From<ConfirmationTarget> for LnConfirmationTarget {
 pub struct FeeEstimation {
     pub sats_per_vbyte: u64,
     pub total_sats: u64,
+    /// The virtual size of the transactions in the mempool paying a higher fee rate, i.e. which
+    /// will be mined first. `None` if the coordinator could not give us the mempool.
+    pub mempool_vsize_ahead: Option<u64>,
 }
 
 /// Calculate the fees for an on-chain transaction, using the 3 default fee rates (background,
 /// normal, and high priority). This both estimates the fee rate and calculates the TX size to get
 /// the overall fee for a given TX.
+///
+/// The position of each fee rate in the mempool is taken from the (cached) fee histogram of the
+/// coordinator.
 pub fn calculate_all_fees_for_on_chain(address: String, amount: u64) -> Result<Vec<FeeEstimation>> {
     const TARGETS: [ConfirmationTarget; 4] = [
         ConfirmationTarget::Minimum,
@@ -607,16 +1287,28 @@ pub fn calculate_all_fees_for_on_chain(address: String, amount: u64) -> Result<V
     runtime.block_on(async {
         let mut fees = Vec::with_capacity(TARGETS.len());
 
+        let fee_estimates = fee_estimates::get_fee_estimates()
+            .await
+            .map_err(|e| tracing::warn!("Failed to get fee estimates: {e:#}"))
+            .ok()
+            .filter(|estimates| !estimates.histogram.is_empty());
+
         for confirmation_target in TARGETS {
             let payment = SendPayment::OnChain {
                 address: address.clone(),
                 amount,
                 fee: Fee::Priority(confirmation_target),
+                inputs: None,
             };
 
+            let sats_per_vbyte = fee_rate(confirmation_target)?;
+
             fees.push(FeeEstimation {
-                sats_per_vbyte: (fee_rate(confirmation_target)?.ceil()) as u64,
+                sats_per_vbyte: (sats_per_vbyte.ceil()) as u64,
                 total_sats: ln_dlc::estimate_payment_fee_msat(payment).await? / 1000,
+                mempool_vsize_ahead: fee_estimates
+                    .as_ref()
+                    .map(|estimates| estimates.vsize_ahead_of(sats_per_vbyte)),
             })
         }
 
@@ -628,14 +1320,44 @@ pub fn fee_rate(confirmation_target: ConfirmationTarget) -> Result<f32> {
     ln_dlc::get_fee_rate_for_target(confirmation_target.into()).map(|rate| rate.as_sat_per_vb())
 }
 
+/// Compute the exact fee and the amount received by the recipient of an on-chain payment, without
+/// broadcasting it.
+///
+/// Pass the inputs of the returned [`SendPreview`] to [`send_payment`] to send exactly the
+/// previewed transaction.
+pub fn estimate_send_fee(address: String, amount: SendAmount, fee: Fee) -> Result<SendPreview> {
+    let address = Address::from_str(&address)
+        .map_err(|e| error::to_ffi(AppError::InvalidInput(format!("Invalid address: {e}"))))?;
+    let amount_sat_or_drain = match amount {
+        SendAmount::Sats { amount } => {
+            if amount == 0 {
+                return Err(error::to_ffi(AppError::InvalidInput(
+                    "Amount must be positive, use the max amount to drain".to_string(),
+                )));
+            }
+            amount
+        }
+        SendAmount::Max => 0,
+    };
+
+    let preview =
+        ln_dlc::preview_send(&address, amount_sat_or_drain, fee.into()).map_err(error::to_ffi)?;
+    Ok(preview.into())
+}
+
 pub fn send_payment(payment: SendPayment) -> Result<()> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
     let runtime = crate::state::get_or_create_tokio_runtime()?;
-    runtime.block_on(async { ln_dlc::send_payment(payment).await })
+    runtime
+        .block_on(async { ln_dlc::send_payment(payment).await })
+        .map_err(error::to_ffi)
 }
 
 pub fn send_preflight_probe(payment: SendPayment) -> Result<u64> {
     let runtime = crate::state::get_or_create_tokio_runtime()?;
-    runtime.block_on(async { ln_dlc::estimate_payment_fee_msat(payment).await })
+    runtime
+        .block_on(async { ln_dlc::estimate_payment_fee_msat(payment).await })
+        .map_err(error::to_ffi)
 }
 
 pub struct LastLogin {
@@ -643,19 +1365,292 @@ pub struct LastLogin {
     pub date: String,
 }
 
-pub fn get_seed_phrase() -> SyncReturn<Vec<String>> {
-    SyncReturn(ln_dlc::get_seed_phrase())
+/// The parameters of an LNURL or Lightning address, with amounts in sats.
+pub enum LnUrlRequest {
+    Withdraw {
+        min_withdrawable_sats: u64,
+        max_withdrawable_sats: u64,
+        default_description: String,
+    },
+    Pay {
+        min_sendable_sats: u64,
+        max_sendable_sats: u64,
+        /// The plain text description of the payment from the metadata of the service.
+        description: String,
+        /// The maximum length of a comment the service accepts, `0` if it does not accept any.
+        comment_allowed: u16,
+    },
+}
+
+impl From<lnurl::LnUrlRequest> for LnUrlRequest {
+    fn from(value: lnurl::LnUrlRequest) -> Self {
+        match value {
+            lnurl::LnUrlRequest::Withdraw(withdraw) => LnUrlRequest::Withdraw {
+                // Round inwards, so that any amount in sats within the bounds can be withdrawn.
+                min_withdrawable_sats: (withdraw.min_withdrawable + 999) / 1000,
+                max_withdrawable_sats: withdraw.max_withdrawable / 1000,
+                default_description: withdraw.default_description,
+            },
+            lnurl::LnUrlRequest::Pay(pay) => LnUrlRequest::Pay {
+                min_sendable_sats: (pay.min_sendable + 999) / 1000,
+                max_sendable_sats: pay.max_sendable / 1000,
+                description: lnurl::plain_text_description(&pay.metadata).unwrap_or_default(),
+                comment_allowed: pay.comment_allowed,
+            },
+        }
+    }
+}
+
+/// Fetch the parameters of an LNURL-withdraw, LNURL-pay or Lightning address.
+pub fn decode_lnurl(request: String) -> Result<LnUrlRequest> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    let request = runtime.block_on(lnurl::fetch_request(&request))?;
+
+    Ok(request.into())
+}
+
+/// Withdraw from an LNURL-withdraw. The progress is published as `Event::LnUrlProgress`.
+pub fn lnurl_withdraw(request: String, amount_sats: u64) -> Result<()> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    runtime
+        .block_on(lnurl::withdraw(&request, amount_sats))
+        .map_err(error::to_ffi)
+}
+
+/// Pay to an LNURL-pay or Lightning address. The progress is published as
+/// `Event::LnUrlProgress`.
+pub fn lnurl_pay(request: String, amount_sats: u64, comment: Option<String>) -> Result<()> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    runtime
+        .block_on(lnurl::pay(&request, amount_sats, comment))
+        .map_err(error::to_ffi)?;
+
+    Ok(())
 }
 
+/// Fails if the keystore is locked, see [`unlock`].
+pub fn get_seed_phrase() -> Result<SyncReturn<Vec<String>>> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    Ok(SyncReturn(ln_dlc::get_seed_phrase()))
+}
+
+/// Whether the seed is protected by a PIN, see [`set_pin`].
+pub fn has_pin() -> SyncReturn<bool> {
+    SyncReturn(keystore::is_protected())
+}
+
+/// Whether the keystore has to be unlocked with [`unlock`] or [`unlock_with_biometrics`] before
+/// the node can be started, or funds can be sent.
+pub fn is_locked() -> SyncReturn<bool> {
+    SyncReturn(keystore::is_locked())
+}
+
+/// Encrypts the seed on disk with a key derived from `pin`, or changes the PIN if the seed is
+/// already protected.
+pub fn set_pin(pin: String) -> Result<()> {
+    keystore::set_pin(&pin).map_err(error::to_ffi)
+}
+
+pub fn unlock(pin: String) -> Result<()> {
+    keystore::unlock(&pin).map_err(error::to_ffi)
+}
+
+/// Unlocks the keystore with the 32 byte key which the platform has released after
+/// authenticating the user with their biometrics.
+pub fn unlock_with_biometrics(key: Vec<u8>) -> Result<()> {
+    keystore::unlock_with_biometrics(&key)
+}
+
+/// Allows unlocking the keystore with `key`, a random 32 byte key which the platform keeps in
+/// its secure storage and only releases after authenticating the user with their biometrics.
+pub fn enable_biometric_unlock(key: Vec<u8>) -> Result<()> {
+    keystore::enable_biometric_unlock(&key)
+}
+
+pub fn disable_biometric_unlock() -> Result<()> {
+    keystore::disable_biometric_unlock()
+}
+
+pub fn lock() -> SyncReturn<()> {
+    keystore::lock();
+    SyncReturn(())
+}
+
+/// Sets after how many seconds without activity the keystore locks itself.
+pub fn set_auto_lock_timeout(seconds: u64) -> Result<()> {
+    keystore::set_auto_lock_timeout(Duration::from_secs(seconds))
+}
+
+/// Restores the seed and the complete backup. The progress is published as
+/// `Event::RestoreProgress`.
 #[tokio::main(flavor = "current_thread")]
 pub async fn restore_from_seed_phrase(
     seed_phrase: String,
     target_seed_file_path: String,
-) -> Result<()> {
+) -> Result<RestoreSummary> {
     let file_path = PathBuf::from(target_seed_file_path);
     tracing::info!("Restoring seed from phrase to {:?}", file_path);
-    ln_dlc::restore_from_mnemonic(&seed_phrase, file_path.as_path()).await?;
-    Ok(())
+    let summary = ln_dlc::restore_from_mnemonic(&seed_phrase, file_path.as_path()).await?;
+    Ok(summary.into())
+}
+
+/// Restores only the backed up values of the given `categories`. The progress is published as
+/// `Event::RestoreProgress`.
+///
+/// Must be called after restoring the seed and before starting the node.
+#[tokio::main(flavor = "current_thread")]
+pub async fn restore_backup_categories(categories: Vec<BackupCategory>) -> Result<RestoreSummary> {
+    let categories = categories
+        .into_iter()
+        .map(backup::BackupCategory::from)
+        .collect::<Vec<_>>();
+
+    let summary = ln_dlc::restore_from_backup(&categories).await?;
+    Ok(summary.into())
+}
+
+/// Writes an encrypted static channel backup to the backup directory and returns its path.
+pub fn export_channel_backup() -> Result<String> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    channel_backup::export_channel_backup()
+}
+
+/// Writes an encrypted archive of the Lightning state, the DLC state and the 10101 database to
+/// `path`, independently of the backup kept by the coordinator.
+pub fn export_backup_to_file(path: String) -> Result<()> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    local_backup::export_to_file(Path::new(&path))
+}
+
+/// Returns the same encrypted archive as [`export_backup_to_file`], split into parts which can
+/// each be shown as a QR code.
+pub fn export_backup_to_qr_codes() -> Result<Vec<String>> {
+    keystore::ensure_unlocked().map_err(error::to_ffi)?;
+    local_backup::export_to_qr_parts()
+}
+
+/// Restores from an archive written by [`export_backup_to_file`].
+///
+/// Must be called after restoring the seed and before starting the node.
+pub fn import_backup_from_file(path: String) -> Result<RestoreSummary> {
+    let summary = local_backup::import_from_file(Path::new(&path))?;
+    Ok(summary.into())
+}
+
+/// Restores from the scanned QR codes of [`export_backup_to_qr_codes`], in any order.
+///
+/// Must be called after restoring the seed and before starting the node.
+pub fn import_backup_from_qr_codes(parts: Vec<String>) -> Result<RestoreSummary> {
+    let summary = local_backup::import_from_qr_parts(parts)?;
+    Ok(summary.into())
+}
+
+/// Writes a zip with the recent logs, the node info, the state of the channels, the latest DLC
+/// messages and the config, which the user can attach to a support ticket. Returns its path.
+///
+/// Signatures and credentials are left out, so the zip can be shared safely.
+pub fn export_diagnostics() -> Result<String> {
+    diagnostics::export()
+}
+
+/// Asks the coordinator to force-close the channels of the static channel backup at `file_path`.
+///
+/// Must be called after restoring the seed and before starting the node.
+#[tokio::main(flavor = "current_thread")]
+pub async fn recover_from_channel_backup(file_path: String) -> Result<()> {
+    let file_path = PathBuf::from(file_path);
+    tracing::info!("Recovering channels from backup at {:?}", file_path);
+    channel_backup::recover_from_channel_backup(file_path.as_path()).await
+}
+
+/// What could be recovered from the seed alone, see [`recover_without_backup`].
+#[derive(Clone, Debug)]
+pub struct RecoveryReport {
+    /// The balance of the on-chain wallet after rescanning the chain.
+    pub on_chain_balance_sats: u64,
+    pub channels: Vec<ChannelRecovery>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChannelRecovery {
+    pub channel_id: String,
+    pub kind: RecoveredChannelKind,
+    /// The funds in the channel, as far as the coordinator knows.
+    pub balance_sats: u64,
+    pub status: ChannelRecoveryStatus,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RecoveredChannelKind {
+    Lightning,
+    Dlc,
+}
+
+#[derive(Clone, Debug)]
+pub enum ChannelRecoveryStatus {
+    /// The channel is known to the node, so nothing had to be recovered.
+    Known,
+    /// The coordinator is force-closing the channel and the funds will be paid to the on-chain
+    /// wallet.
+    ForceCloseRequested,
+    ForceCloseFailed {
+        reason: String,
+    },
+    /// The funds in the channel cannot be recovered without a backup.
+    NotRecoverable,
+}
+
+impl From<recovery::RecoveryReport> for RecoveryReport {
+    fn from(value: recovery::RecoveryReport) -> Self {
+        Self {
+            on_chain_balance_sats: value.on_chain_balance_sats,
+            channels: value
+                .channels
+                .into_iter()
+                .map(ChannelRecovery::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<recovery::ChannelRecovery> for ChannelRecovery {
+    fn from(value: recovery::ChannelRecovery) -> Self {
+        let kind = match value.kind {
+            commons::RecoverableChannelKind::Lightning => RecoveredChannelKind::Lightning,
+            commons::RecoverableChannelKind::Dlc => RecoveredChannelKind::Dlc,
+        };
+
+        let status = match value.status {
+            recovery::ChannelRecoveryStatus::Known => ChannelRecoveryStatus::Known,
+            recovery::ChannelRecoveryStatus::ForceCloseRequested => {
+                ChannelRecoveryStatus::ForceCloseRequested
+            }
+            recovery::ChannelRecoveryStatus::ForceCloseFailed { reason } => {
+                ChannelRecoveryStatus::ForceCloseFailed { reason }
+            }
+            recovery::ChannelRecoveryStatus::NotRecoverable => {
+                ChannelRecoveryStatus::NotRecoverable
+            }
+        };
+
+        Self {
+            channel_id: value.channel_id,
+            kind,
+            balance_sats: value.balance_sats,
+            status,
+        }
+    }
+}
+
+/// Recovers what it can of the funds of a user who has lost their backup, using only the seed.
+///
+/// Must be called once the node has been started on top of the restored seed. The rescan of the
+/// chain is published as `Event::SyncProgress`.
+#[tokio::main(flavor = "current_thread")]
+pub async fn recover_without_backup() -> Result<RecoveryReport> {
+    let report = recovery::recover_without_backup().await?;
+    Ok(report.into())
 }
 
 pub fn init_new_mnemonic(target_seed_file_path: String) -> Result<()> {
@@ -685,6 +1680,14 @@ pub enum Destination {
         message: String,
         amount_sats: Option<u64>,
     },
+    /// A Lightning address (`user@domain`), resolved to the parameters of its LNURL-pay.
+    LightningAddress {
+        address: String,
+        description: String,
+        min_sendable_sats: u64,
+        max_sendable_sats: u64,
+        comment_allowed: u16,
+    },
 }
 
 pub fn decode_destination(destination: String) -> Result<Destination> {
@@ -692,8 +1695,8 @@ pub fn decode_destination(destination: String) -> Result<Destination> {
     destination::decode_destination(destination)
 }
 
-pub fn get_node_id() -> SyncReturn<String> {
-    SyncReturn(ln_dlc::get_node_pubkey().to_string())
+pub fn get_node_id() -> Result<SyncReturn<String>> {
+    Ok(SyncReturn(ln_dlc::get_node_pubkey()?.to_string()))
 }
 
 pub fn get_channel_open_fee_estimate_sat() -> Result<u64> {
@@ -705,5 +1708,52 @@ pub fn get_channel_open_fee_estimate_sat() -> Result<u64> {
 
 pub fn get_expiry_timestamp(network: String) -> SyncReturn<i64> {
     let network = config::api::parse_network(&network);
-    SyncReturn(commons::calculate_next_expiry(OffsetDateTime::now_utc(), network).unix_timestamp())
+    let now = crate::state::get_clock().now();
+    SyncReturn(commons::calculate_next_expiry(now, network).unix_timestamp())
+}
+
+/// The kind of an error returned by the API, so that the app can show a localized message or
+/// retry. Any error which is not known to the app is `Unknown`.
+pub enum ErrorCode {
+    InsufficientFunds,
+    CoordinatorUnreachable,
+    OrderRejected,
+    ChannelBusy,
+    KeystoreLocked,
+    WrongPin,
+    TooManyPinAttempts,
+    InvalidInput,
+    Unknown,
+}
+
+impl From<error::ErrorCode> for ErrorCode {
+    fn from(value: error::ErrorCode) -> Self {
+        match value {
+            error::ErrorCode::InsufficientFunds => ErrorCode::InsufficientFunds,
+            error::ErrorCode::CoordinatorUnreachable => ErrorCode::CoordinatorUnreachable,
+            error::ErrorCode::OrderRejected => ErrorCode::OrderRejected,
+            error::ErrorCode::ChannelBusy => ErrorCode::ChannelBusy,
+            error::ErrorCode::KeystoreLocked => ErrorCode::KeystoreLocked,
+            error::ErrorCode::WrongPin => ErrorCode::WrongPin,
+            error::ErrorCode::TooManyPinAttempts => ErrorCode::TooManyPinAttempts,
+            error::ErrorCode::InvalidInput => ErrorCode::InvalidInput,
+            error::ErrorCode::Unknown => ErrorCode::Unknown,
+        }
+    }
+}
+
+pub struct ErrorInfo {
+    pub code: ErrorCode,
+    /// The message without the code, for logging or if the app has no message for the code.
+    pub message: String,
+}
+
+/// Decodes the message of an error returned by the API into its code and message.
+pub fn decode_error(message: String) -> SyncReturn<ErrorInfo> {
+    let (code, message) = error::decode(&message);
+
+    SyncReturn(ErrorInfo {
+        code: code.into(),
+        message,
+    })
 }
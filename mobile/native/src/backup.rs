@@ -1,28 +1,57 @@
 use crate::cipher::AesCipher;
 use crate::config;
 use crate::db;
+use crate::device;
+use crate::event;
 use crate::event::subscriber::Subscriber;
 use crate::event::EventInternal;
 use crate::event::EventType;
 use anyhow::bail;
 use anyhow::ensure;
+use anyhow::Context;
 use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::PublicKey;
 use commons::Backup;
 use commons::DeleteBackup;
 use commons::Restore;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use futures::future::RemoteHandle;
 use futures::FutureExt;
 use ln_dlc_storage::sled::SledStorageProvider;
 use ln_dlc_storage::DlcStoreProvider;
+use parking_lot::Mutex;
 use reqwest::Client;
 use reqwest::StatusCode;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fs;
+use std::io::Read;
+use std::io::Write;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::oneshot;
 
 const BLACKLIST: [&str; 1] = ["ln/network_graph"];
 
+/// The time for which backups are collected before they are uploaded together.
+const BACKUP_BATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where the [`BackupStatusTracker`] keeps its state, relative to the backup dir.
+const BACKUP_STATUS_FILE: &str = "backup_status.json";
+
+/// Marks a backed up value which has been compressed before it has been encrypted. Values
+/// without this prefix have been backed up uncompressed.
+const COMPRESSED_VALUE_PREFIX: &[u8] = b"10101GZ";
+
 pub const DB_BACKUP_KEY: &str = "10101";
 pub const LN_BACKUP_KEY: &str = "ln";
 pub const DLC_BACKUP_KEY: &str = "dlc";
@@ -79,15 +108,29 @@ impl Subscriber for DBBackupSubscriber {
     }
 }
 
-#[derive(Clone)]
-pub struct RemoteBackupClient {
+/// A server keeping the encrypted backups of the app.
+///
+/// The values are encrypted and signed before they are handed to the client.
+#[async_trait]
+pub trait BackupClient: Send + Sync {
+    async fn upload(&self, node_id: PublicKey, backups: Vec<Backup>) -> Result<()>;
+
+    async fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()>;
+
+    /// Downloads all the backed up values, authenticated by a `signature` of the node id.
+    async fn download(&self, node_id: PublicKey, signature: Signature) -> Result<Vec<Restore>>;
+}
+
+/// A server implementing the backup API of the coordinator, i.e. the coordinator itself or a
+/// backup server provided by the user.
+pub struct HttpBackupClient {
     inner: Client,
+    /// The URL the paths of the backup API are appended to, e.g. `http://localhost:8000/api/v1`.
     endpoint: String,
-    cipher: AesCipher,
 }
 
-impl RemoteBackupClient {
-    pub fn new(cipher: AesCipher) -> RemoteBackupClient {
+impl HttpBackupClient {
+    pub fn new(endpoint: String) -> Self {
         let inner = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
@@ -95,21 +138,113 @@ impl RemoteBackupClient {
 
         Self {
             inner,
-            endpoint: format!("http://{}/api", config::get_http_endpoint()),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl BackupClient for HttpBackupClient {
+    async fn upload(&self, node_id: PublicKey, backups: Vec<Backup>) -> Result<()> {
+        let endpoint = format!("{}/backups/{node_id}", self.endpoint);
+        let response = self.inner.post(endpoint).json(&backups).send().await?;
+
+        tracing::debug!("Response status code {}", response.status());
+        if response.status() != StatusCode::OK {
+            let response = response.text().await?;
+            bail!("Failed to upload backups. {response}");
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, node_id: PublicKey, backup: DeleteBackup) -> Result<()> {
+        let endpoint = format!("{}/backup/{node_id}", self.endpoint);
+        self.inner.delete(endpoint).json(&backup).send().await?;
+
+        Ok(())
+    }
+
+    async fn download(&self, node_id: PublicKey, signature: Signature) -> Result<Vec<Restore>> {
+        let endpoint = format!("{}/restore/{node_id}", self.endpoint);
+        let response = self.inner.get(endpoint).json(&signature).send().await?;
+
+        tracing::debug!("Response status code {}", response.status());
+        if response.status() != StatusCode::OK {
+            let response = response.text().await?;
+            bail!("Failed to download backup. {response}");
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+#[derive(Clone)]
+pub struct RemoteBackupClient {
+    client: Arc<dyn BackupClient>,
+    cipher: AesCipher,
+    pending: Arc<Mutex<PendingBackups>>,
+    status: Arc<Mutex<BackupStatusTracker>>,
+}
+
+/// The backups which have been scheduled since the last upload.
+#[derive(Default)]
+struct PendingBackups {
+    /// The latest value of every key.
+    values: BTreeMap<String, Vec<u8>>,
+    /// Notified once the pending backups have been uploaded.
+    waiters: Vec<oneshot::Sender<()>>,
+}
+
+impl RemoteBackupClient {
+    /// Backs up to the server configured by the user, or to the coordinator otherwise.
+    pub fn new(cipher: AesCipher) -> RemoteBackupClient {
+        let client = HttpBackupClient::new(config::get_backup_endpoint());
+
+        Self::with_client(Arc::new(client), cipher)
+    }
+
+    pub fn with_client(client: Arc<dyn BackupClient>, cipher: AesCipher) -> RemoteBackupClient {
+        let status_file = Path::new(&config::get_backup_dir()).join(BACKUP_STATUS_FILE);
+
+        Self {
+            client,
             cipher,
+            pending: Arc::new(Mutex::new(PendingBackups::default())),
+            status: Arc::new(Mutex::new(BackupStatusTracker::load(status_file))),
         }
     }
+
+    pub fn status(&self) -> BackupStatus {
+        let pending = self.pending.lock().values.len();
+        self.status.lock().status(pending)
+    }
 }
 
 impl RemoteBackupClient {
     pub fn delete(&self, key: String) -> RemoteHandle<()> {
+        // A pending backup of the key, or of any key below it, must not be uploaded after the
+        // backup has been deleted.
+        self.pending
+            .lock()
+            .values
+            .retain(|pending_key| !is_below(pending_key, &key));
+        self.status.lock().record_deleted(&key);
+
         let (fut, remote_handle) = {
-            let client = self.inner.clone();
+            let client = self.client.clone();
             let node_id = self.cipher.public_key();
-            let endpoint = format!("{}/backup/{}", self.endpoint.clone(), node_id);
             let cipher = self.cipher.clone();
             let message = node_id.to_string().as_bytes().to_vec();
             async move {
+                if device::is_superseded() {
+                    tracing::warn!(
+                        key,
+                        "Not deleting backup as another device is using our seed"
+                    );
+                    return;
+                }
+
                 let signature = match cipher.sign(message) {
                     Ok(signature) => signature,
                     Err(e) => {
@@ -123,7 +258,7 @@ impl RemoteBackupClient {
                     signature,
                 };
 
-                if let Err(e) = client.delete(endpoint).json(&backup).send().await {
+                if let Err(e) = client.delete(node_id, backup).await {
                     tracing::error!("Failed to delete backup of {key}. {e:#}")
                 } else {
                     tracing::debug!("Successfully deleted backup of {key}");
@@ -139,153 +274,533 @@ impl RemoteBackupClient {
         remote_handle
     }
 
+    /// Schedules a backup of the value of `key`.
+    ///
+    /// Backups are collected for [`BACKUP_BATCH_INTERVAL`] and uploaded in a single request, only
+    /// the latest value of every key is uploaded. The returned handle resolves once the batch
+    /// containing the value has been uploaded.
     pub fn backup(&self, key: String, value: Vec<u8>) -> RemoteHandle<()> {
         let size_mb = value.len() as f64 / (1024.0 * 1024.0);
         tracing::trace!(%size_mb, "Creating backup for {key}");
 
-        let (fut, remote_handle) = {
-            let client = self.inner.clone();
-            let cipher = self.cipher.clone();
-            let node_id = cipher.public_key();
-            let endpoint = format!("{}/backup/{}", self.endpoint.clone(), node_id);
-            async move {
-                if BLACKLIST.contains(&key.as_str()) {
-                    tracing::debug!(key, "Skipping blacklisted backup");
-                    return;
+        let (sender, receiver) = oneshot::channel();
+        let is_first = {
+            let mut pending = self.pending.lock();
+            let is_first = pending.waiters.is_empty();
+            pending.values.insert(key, value);
+            pending.waiters.push(sender);
+            is_first
+        };
+
+        let runtime =
+            crate::state::get_or_create_tokio_runtime().expect("To be able to get a tokio runtime");
+
+        if is_first {
+            runtime.spawn({
+                let client = self.clone();
+                async move {
+                    tokio::time::sleep(BACKUP_BATCH_INTERVAL).await;
+                    client.upload_pending().await;
                 }
+            });
+        }
 
-                let encrypted_value = match cipher.encrypt(value) {
-                    Ok(encrypted_value) => encrypted_value,
-                    Err(e) => {
-                        tracing::error!(%key, "{e:#}");
-                        return;
-                    }
-                };
-                let signature = match cipher.sign(encrypted_value.clone()) {
-                    Ok(signature) => signature,
-                    Err(e) => {
-                        tracing::error!(%key, "{e:#}");
-                        return;
-                    }
-                };
+        let (fut, remote_handle) = async move {
+            // The sender is only dropped after the batch has been uploaded.
+            let _ = receiver.await;
+        }
+        .remote_handle();
+        runtime.spawn(fut);
 
-                let backup = Backup {
-                    key: key.clone(),
-                    value: encrypted_value,
-                    signature,
-                };
+        remote_handle
+    }
 
-                match client.post(endpoint).json(&backup).send().await {
-                    Ok(response) => {
-                        tracing::debug!("Response status code {}", response.status());
-                        if response.status() != StatusCode::OK {
-                            match response.text().await {
-                                Ok(response) => {
-                                    tracing::error!("Failed to upload backup. {response}")
-                                }
-                                Err(e) => tracing::error!("Failed to upload backup. {e}"),
-                            }
-                        } else {
-                            tracing::debug!("Successfully uploaded backup of {key}.");
-                        }
-                    }
-                    Err(e) => tracing::error!("Failed to create a backup of {key}. {e:#}"),
+    /// Uploads the pending backups in a single request.
+    async fn upload_pending(&self) {
+        let PendingBackups { values, waiters } = std::mem::take(&mut *self.pending.lock());
+
+        let mut backups = vec![];
+        for (key, value) in values {
+            if device::is_superseded() {
+                tracing::warn!(key, "Not backing up as another device is using our seed");
+                continue;
+            }
+
+            if BLACKLIST.contains(&key.as_str()) {
+                tracing::debug!(key, "Skipping blacklisted backup");
+                continue;
+            }
+
+            match self.encode(key.clone(), value) {
+                Ok(backup) => backups.push(backup),
+                Err(e) => {
+                    tracing::error!(%key, "Failed to create backup. {e:#}");
+                    self.status.lock().record_failed(vec![key]);
                 }
             }
         }
-        .remote_handle();
 
-        let runtime =
-            crate::state::get_or_create_tokio_runtime().expect("To be able to get a tokio runtime");
-        runtime.spawn(fut);
+        if !backups.is_empty() {
+            let keys = backups.len();
+            let sizes = backups
+                .iter()
+                .map(|backup| (backup.key.clone(), backup.value.len() as u64))
+                .collect::<Vec<_>>();
+
+            self.status.lock().uploading += keys;
+            let result = self.client.upload(self.cipher.public_key(), backups).await;
+
+            let mut status = self.status.lock();
+            status.uploading -= keys;
+            match result {
+                Ok(()) => {
+                    tracing::debug!(keys, "Successfully uploaded backups.");
+                    status.record_uploaded(sizes);
+                }
+                Err(e) => {
+                    tracing::error!(keys, "Failed to upload backups. {e:#}");
+                    status.record_failed(sizes.into_iter().map(|(key, _)| key).collect());
+                }
+            }
+        }
 
-        remote_handle
+        for waiter in waiters {
+            let _ = waiter.send(());
+        }
+    }
+
+    /// Compresses, encrypts and signs the value of `key`.
+    fn encode(&self, key: String, value: Vec<u8>) -> Result<Backup> {
+        let encrypted_value = self.cipher.encrypt(compress(&value)?)?;
+        let signature = self.cipher.sign(encrypted_value.clone())?;
+
+        Ok(Backup {
+            key,
+            checksum: Some(commons::checksum(&encrypted_value)),
+            value: encrypted_value,
+            signature,
+        })
     }
 
-    pub async fn restore(&self, dlc_storage: Arc<SledStorageProvider>) -> Result<()> {
+    /// Downloads the backup from the coordinator.
+    async fn download(&self) -> Result<Vec<Restore>> {
         let runtime = crate::state::get_or_create_tokio_runtime()?;
         runtime
             .spawn({
-                let client = self.inner.clone();
+                let client = self.client.clone();
                 let cipher = self.cipher.clone();
                 let node_id = cipher.public_key();
-                let endpoint = format!("{}/restore/{}", self.endpoint.clone(), node_id);
-                let data_dir = config::get_data_dir();
-                let network = config::get_network();
                 let message = node_id.to_string().as_bytes().to_vec();
                 async move {
                     let signature = cipher.sign(message)?;
 
-                    match client.get(endpoint).json(&signature).send().await {
-                        Ok(response) => {
-                            tracing::debug!("Response status code {}", response.status());
-                            if response.status() != StatusCode::OK {
-                                let response = response.text().await?;
-                                bail!("Failed to download backup. {response}");
-                            }
-
-                            let backup: Vec<Restore> = response.json().await?;
-                            tracing::debug!("Successfully downloaded backup.");
-
-                            for restore in backup.into_iter() {
-                                let decrypted_value = cipher.decrypt(restore.value)?;
-
-                                let keys = restore
-                                    .key
-                                    .split('/')
-                                    .map(|key| key.to_string())
-                                    .collect::<Vec<String>>();
-                                let (backup_key, key) =
-                                    keys.split_first().expect("keys to be long enough");
-                                let key = key.join("/");
-
-                                let backup_key = backup_key.as_str();
-
-                                match backup_key {
-                                    x if x == LN_BACKUP_KEY => {
-                                        tracing::debug!("Restoring {}", key);
-                                        let dest_file = Path::new(&data_dir)
-                                            .join(network.to_string())
-                                            .join(key.clone());
-
-                                        fs::create_dir_all(dest_file.parent().expect("parent"))?;
-                                        fs::write(dest_file.as_path(), decrypted_value)?;
-                                    }
-                                    x if x == DLC_BACKUP_KEY => {
-                                        tracing::debug!("Restoring {}", key);
-                                        let keys = key.split('/').collect::<Vec<&str>>();
-                                        ensure!(keys.len() == 2, "dlc key is too short");
-
-                                        let kind = *hex::decode(keys.first().expect("to exist"))?
-                                            .first()
-                                            .expect("to exist");
-
-                                        let key = hex::decode(keys.get(1).expect("to exist"))?;
-
-                                        dlc_storage.write(kind, key, decrypted_value)?;
-                                    }
-                                    x if x == DB_BACKUP_KEY => {
-                                        let data_dir = Path::new(&data_dir);
-                                        let db_file =
-                                            data_dir.join(format!("trades-{}.sqlite", network));
-                                        tracing::debug!(
-                                            "Restoring 10101 database backup into {}",
-                                            db_file.to_string_lossy().to_string()
-                                        );
-                                        fs::write(db_file.as_path(), decrypted_value)?;
-                                    }
-                                    _ => {
-                                        tracing::warn!(backup_key, "Received unknown backup key")
-                                    }
-                                }
-                            }
-                            tracing::info!("Successfully restored 10101 from backup!");
-                        }
-                        Err(e) => bail!("Failed to download backup. {e:#}"),
-                    }
-                    Ok(())
+                    let backup = client
+                        .download(node_id, signature)
+                        .await
+                        .context("Failed to download backup")?;
+                    tracing::debug!("Successfully downloaded backup.");
+
+                    Ok(backup)
                 }
             })
             .await?
     }
+
+    /// Verifies the checksum of the restored value, decrypts and decompresses it.
+    fn decrypt(&self, restore: Restore) -> Result<Vec<u8>> {
+        restore.verify_checksum()?;
+
+        let value = self
+            .cipher
+            .decrypt(restore.value)
+            .with_context(|| format!("Failed to decrypt {}", restore.key))?;
+
+        decompress(value).with_context(|| format!("Failed to decompress {}", restore.key))
+    }
+
+    /// Restores the backed up values of the given `categories`.
+    ///
+    /// A value which cannot be restored does not abort the restore, but is reported in the
+    /// returned [`RestoreSummary`]. The progress is published as
+    /// [`EventInternal::RestoreProgress`].
+    pub async fn restore(
+        &self,
+        dlc_storage: Arc<SledStorageProvider>,
+        categories: &[BackupCategory],
+    ) -> Result<RestoreSummary> {
+        let backup = self
+            .download()
+            .await?
+            .into_iter()
+            .filter(|restore| match BackupCategory::of(&restore.key) {
+                Some(category) => categories.contains(&category),
+                None => {
+                    tracing::warn!(key = restore.key, "Received unknown backup key");
+                    false
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let total = backup.len();
+        event::publish(&EventInternal::RestoreProgress { done: 0, total });
+
+        let mut summary = RestoreSummary::default();
+        for (i, restore) in backup.into_iter().enumerate() {
+            let key = restore.key.clone();
+            match self.restore_value(&dlc_storage, restore) {
+                Ok(()) => summary.restored.push(key),
+                Err(e) => {
+                    tracing::error!(key, "Failed to restore backed up value: {e:#}");
+                    summary.failed.push((key, format!("{e:#}")));
+                }
+            }
+
+            event::publish(&EventInternal::RestoreProgress { done: i + 1, total });
+        }
+
+        tracing::info!(
+            ?categories,
+            restored = summary.restored.len(),
+            failed = summary.failed.len(),
+            "Restored 10101 from backup"
+        );
+
+        Ok(summary)
+    }
+
+    fn restore_value(&self, dlc_storage: &SledStorageProvider, restore: Restore) -> Result<()> {
+        let key = restore.key.clone();
+        let value = self.decrypt(restore)?;
+
+        write_restored_value(dlc_storage, &key, value)
+    }
+
+    /// Downloads the backup and checks that it can be restored, without writing anything.
+    ///
+    /// The keys in `expected_keys` which are not part of the backup are reported as missing.
+    pub async fn verify(&self, expected_keys: Vec<String>) -> Result<BackupVerification> {
+        let backup = self.download().await?;
+
+        let mut verification = BackupVerification {
+            entries: backup.len(),
+            ..BackupVerification::default()
+        };
+
+        let keys = backup
+            .iter()
+            .map(|restore| restore.key.clone())
+            .collect::<HashSet<_>>();
+        verification.missing_keys = expected_keys
+            .into_iter()
+            .filter(|key| !BLACKLIST.contains(&key.as_str()) && !keys.contains(key))
+            .collect();
+
+        for restore in backup.into_iter() {
+            let key = restore.key.clone();
+            if let Err(e) = self.decrypt(restore) {
+                tracing::warn!(key, "Backup is corrupted: {e:#}");
+                verification.corrupt_keys.push(key);
+            }
+        }
+
+        tracing::info!(
+            entries = verification.entries,
+            missing = verification.missing_keys.len(),
+            corrupt = verification.corrupt_keys.len(),
+            "Verified backup"
+        );
+
+        Ok(verification)
+    }
+}
+
+/// The result of [`RemoteBackupClient::verify`].
+#[derive(Debug, Default)]
+pub struct BackupVerification {
+    /// The number of backed up entries.
+    pub entries: usize,
+    /// Keys which exist locally but have not been backed up.
+    pub missing_keys: Vec<String>,
+    /// Keys whose value does not match its checksum or cannot be decrypted.
+    pub corrupt_keys: Vec<String>,
+}
+
+/// Writes a backed up value, given in plaintext, to where it belongs locally.
+pub(crate) fn write_restored_value(
+    dlc_storage: &SledStorageProvider,
+    key: &str,
+    value: Vec<u8>,
+) -> Result<()> {
+    let data_dir = config::get_data_dir();
+    let network = config::get_network();
+
+    let keys = key
+        .split('/')
+        .map(|key| key.to_string())
+        .collect::<Vec<String>>();
+    let (backup_key, key) = keys.split_first().expect("keys to be long enough");
+    let key = key.join("/");
+
+    match backup_key.as_str() {
+        x if x == LN_BACKUP_KEY => {
+            tracing::debug!("Restoring {}", key);
+            let dest_file = Path::new(&data_dir)
+                .join(network.to_string())
+                .join(key.clone());
+
+            fs::create_dir_all(dest_file.parent().expect("parent"))?;
+            fs::write(dest_file.as_path(), value)?;
+        }
+        x if x == DLC_BACKUP_KEY => {
+            tracing::debug!("Restoring {}", key);
+            let keys = key.split('/').collect::<Vec<&str>>();
+            ensure!(keys.len() == 2, "dlc key is too short");
+
+            let kind = *hex::decode(keys.first().expect("to exist"))?
+                .first()
+                .expect("to exist");
+
+            let key = hex::decode(keys.get(1).expect("to exist"))?;
+
+            dlc_storage.write(kind, key, value)?;
+        }
+        x if x == DB_BACKUP_KEY => {
+            let data_dir = Path::new(&data_dir);
+            let db_file = data_dir.join(format!("trades-{}.sqlite", network));
+            tracing::debug!(
+                "Restoring 10101 database backup into {}",
+                db_file.to_string_lossy().to_string()
+            );
+            fs::write(db_file.as_path(), value)?;
+        }
+        backup_key => bail!("Unknown backup key {backup_key}"),
+    }
+
+    Ok(())
+}
+
+/// The result of [`RemoteBackupClient::restore`].
+#[derive(Debug, Default)]
+pub struct RestoreSummary {
+    pub restored: Vec<String>,
+    /// The keys which could not be restored, together with the reason.
+    pub failed: Vec<(String, String)>,
+}
+
+/// The kind of data a backed up key belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BackupCategory {
+    Ln,
+    Dlc,
+    Db,
+}
+
+impl BackupCategory {
+    pub const ALL: [BackupCategory; 3] =
+        [BackupCategory::Ln, BackupCategory::Dlc, BackupCategory::Db];
+
+    fn of(key: &str) -> Option<Self> {
+        match key.split('/').next() {
+            Some(LN_BACKUP_KEY) => Some(BackupCategory::Ln),
+            Some(DLC_BACKUP_KEY) => Some(BackupCategory::Dlc),
+            Some(DB_BACKUP_KEY) => Some(BackupCategory::Db),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the backups of the app are up to date, see [`RemoteBackupClient::status`].
+#[derive(Debug, Default)]
+pub struct BackupStatus {
+    /// When a value of each category has last been uploaded successfully.
+    pub last_backup: BTreeMap<BackupCategory, OffsetDateTime>,
+    /// The number of values waiting to be uploaded or being uploaded.
+    pub pending: usize,
+    /// The number of values whose last upload has failed.
+    pub failed: usize,
+    /// The size of all the backed up values in bytes, as uploaded.
+    pub total_size: u64,
+}
+
+/// Keeps track of the uploads of the [`RemoteBackupClient`].
+///
+/// The times of the last successful uploads and the sizes of the backed up values are persisted,
+/// so that they are known after a restart.
+struct BackupStatusTracker {
+    file: PathBuf,
+    state: BackupStatusState,
+    /// The keys whose last upload has failed.
+    failed: HashSet<String>,
+    /// The number of values currently being uploaded.
+    uploading: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct BackupStatusState {
+    #[serde(with = "time::serde::timestamp::option")]
+    last_ln_backup: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    last_dlc_backup: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    last_db_backup: Option<OffsetDateTime>,
+    /// The size of every backed up value.
+    sizes: BTreeMap<String, u64>,
+}
+
+impl BackupStatusTracker {
+    fn load(file: PathBuf) -> Self {
+        let state = match fs::read(&file) {
+            Ok(state) => serde_json::from_slice(&state).unwrap_or_else(|e| {
+                tracing::warn!("Failed to parse backup status. {e:#}");
+                BackupStatusState::default()
+            }),
+            Err(_) => BackupStatusState::default(),
+        };
+
+        Self {
+            file,
+            state,
+            failed: HashSet::new(),
+            uploading: 0,
+        }
+    }
+
+    fn record_uploaded(&mut self, sizes: Vec<(String, u64)>) {
+        let now = OffsetDateTime::now_utc();
+
+        for (key, size) in sizes {
+            match BackupCategory::of(&key) {
+                Some(BackupCategory::Ln) => self.state.last_ln_backup = Some(now),
+                Some(BackupCategory::Dlc) => self.state.last_dlc_backup = Some(now),
+                Some(BackupCategory::Db) => self.state.last_db_backup = Some(now),
+                None => {}
+            }
+
+            self.failed.remove(&key);
+            self.state.sizes.insert(key, size);
+        }
+
+        self.persist();
+    }
+
+    fn record_failed(&mut self, keys: Vec<String>) {
+        self.failed.extend(keys);
+    }
+
+    fn record_deleted(&mut self, key: &str) {
+        self.failed.retain(|failed_key| !is_below(failed_key, key));
+        self.state
+            .sizes
+            .retain(|backed_up_key, _| !is_below(backed_up_key, key));
+
+        self.persist();
+    }
+
+    fn status(&self, pending: usize) -> BackupStatus {
+        let last_backup = [
+            (BackupCategory::Ln, self.state.last_ln_backup),
+            (BackupCategory::Dlc, self.state.last_dlc_backup),
+            (BackupCategory::Db, self.state.last_db_backup),
+        ]
+        .into_iter()
+        .filter_map(|(category, timestamp)| Some((category, timestamp?)))
+        .collect();
+
+        BackupStatus {
+            last_backup,
+            pending: pending + self.uploading,
+            failed: self.failed.len(),
+            total_size: self.state.sizes.values().sum(),
+        }
+    }
+
+    fn persist(&self) {
+        let result = serde_json::to_vec(&self.state)
+            .map_err(anyhow::Error::from)
+            .and_then(|state| Ok(fs::write(&self.file, state)?));
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist backup status. {e:#}");
+        }
+    }
+}
+
+/// Whether `key` is `parent` or below it.
+fn is_below(key: &str, parent: &str) -> bool {
+    key == parent || key.starts_with(&format!("{parent}/"))
+}
+
+pub(crate) fn compress(value: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(COMPRESSED_VALUE_PREFIX.to_vec(), Compression::default());
+    encoder.write_all(value)?;
+
+    Ok(encoder.finish()?)
+}
+
+pub(crate) fn decompress(value: Vec<u8>) -> Result<Vec<u8>> {
+    if !value.starts_with(COMPRESSED_VALUE_PREFIX) {
+        return Ok(value);
+    }
+
+    let mut decompressed = vec![];
+    GzDecoder::new(&value[COMPRESSED_VALUE_PREFIX.len()..]).read_to_end(&mut decompressed)?;
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_value_can_be_decompressed() {
+        let value = b"channel monitor ".repeat(100);
+
+        let compressed = compress(&value).unwrap();
+        assert!(compressed.len() < value.len());
+
+        assert_eq!(decompress(compressed).unwrap(), value);
+    }
+
+    #[test]
+    fn deleted_keys_are_no_longer_counted() {
+        let mut tracker = BackupStatusTracker::load(
+            std::env::temp_dir().join(format!("backup-status-{}.json", uuid::Uuid::new_v4())),
+        );
+
+        tracker.record_uploaded(vec![
+            ("ln/manager".to_string(), 100),
+            ("dlc/01/abcd".to_string(), 10),
+            ("dlc/01/ef01".to_string(), 20),
+        ]);
+        tracker.record_failed(vec!["dlc/07/abcd".to_string()]);
+
+        let status = tracker.status(2);
+        assert_eq!(status.total_size, 130);
+        assert_eq!(status.failed, 1);
+        assert_eq!(status.pending, 2);
+        assert!(status.last_backup.contains_key(&BackupCategory::Ln));
+        assert!(status.last_backup.contains_key(&BackupCategory::Dlc));
+        assert!(!status.last_backup.contains_key(&BackupCategory::Db));
+
+        tracker.record_deleted("dlc/01");
+        tracker.record_deleted("dlc/07/abcd");
+
+        let status = tracker.status(0);
+        assert_eq!(status.total_size, 100);
+        assert_eq!(status.failed, 0);
+
+        // The state survives a restart.
+        let reloaded = BackupStatusTracker::load(tracker.file.clone());
+        assert_eq!(reloaded.status(0).total_size, 100);
+
+        fs::remove_file(&tracker.file).unwrap();
+    }
+
+    #[test]
+    fn uncompressed_value_is_left_alone() {
+        let value = b"backed up before values were compressed".to_vec();
+
+        assert_eq!(decompress(value.clone()).unwrap(), value);
+    }
 }
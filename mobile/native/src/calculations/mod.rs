@@ -2,13 +2,14 @@ use anyhow::Result;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use trade::cfd;
+use trade::pricing;
 use trade::Direction;
 use trade::Price;
 
 /// Calculate the collateral in BTC.
 pub fn calculate_margin(opening_price: f32, quantity: f32, leverage: f32) -> u64 {
     let opening_price = Decimal::try_from(opening_price).expect("price to fit into decimal");
-    cfd::calculate_margin(opening_price, quantity, leverage)
+    pricing::margin(opening_price, quantity, leverage)
 }
 
 /// Calculate the quantity from price, collateral and leverage
@@ -33,19 +34,16 @@ pub fn calculate_pnl(
         Direction::Short => (2.0, leverage),
     };
 
-    let long_margin = calculate_margin(opening_price, quantity, long_leverage);
-    let short_margin = calculate_margin(opening_price, quantity, short_leverage);
-
     let opening_price = Decimal::try_from(opening_price).expect("price to fit into decimal");
-    let closing_price = closing_price.get_price_for_direction(direction.opposite());
+    let mark_price = pricing::mark_price(closing_price, direction);
 
-    cfd::calculate_pnl(
+    pricing::unrealized_pnl(
         opening_price,
-        closing_price,
+        mark_price,
         quantity,
         direction,
-        long_margin,
-        short_margin,
+        long_leverage,
+        short_leverage,
     )
 }
 
@@ -54,13 +52,7 @@ pub fn calculate_liquidation_price(price: f32, leverage: f32, direction: Directi
 
     tracing::trace!("Initial price: {}", price);
 
-    let leverage = Decimal::try_from(leverage).expect("leverage to fix into decimal");
-
-    let liquidation_price = match direction {
-        Direction::Long => cfd::calculate_long_liquidation_price(leverage, initial_price),
-        Direction::Short => cfd::calculate_short_liquidation_price(leverage, initial_price),
-    };
-
+    let liquidation_price = pricing::liquidation_price(initial_price, leverage, direction);
     let liquidation_price = liquidation_price.to_f32().expect("price to fit into f32");
     tracing::trace!("Liquidation_price: {liquidation_price}");
 
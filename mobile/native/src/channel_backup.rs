@@ -0,0 +1,108 @@
+use crate::cipher::AesCipher;
+use crate::commons::reqwest_client;
+use crate::config;
+use crate::ln_dlc;
+use crate::state;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use commons::ForceCloseChannel;
+use ln_dlc_node::node::restore_channel_monitors;
+use ln_dlc_node::node::StaticChannelBackup;
+use std::fs;
+use std::path::Path;
+
+const CHANNEL_BACKUP_FILE_NAME: &str = "channel_backup.scb";
+
+/// Writes an encrypted static channel backup of all our channels to the backup directory.
+///
+/// Returns the path of the backup file, which the user should keep somewhere safe next to their
+/// seed.
+pub fn export_channel_backup() -> Result<String> {
+    let backup = state::get_node().inner.static_channel_backup()?;
+
+    let cipher = AesCipher::new(ln_dlc::get_node_key()?);
+    let encrypted_backup = cipher.encrypt(serde_json::to_vec(&backup)?)?;
+
+    let path = Path::new(&config::get_backup_dir()).join(CHANNEL_BACKUP_FILE_NAME);
+    fs::write(&path, encrypted_backup)
+        .with_context(|| format!("Failed to write channel backup to {}", path.display()))?;
+
+    tracing::info!(
+        path = %path.display(),
+        channels = backup.channels.len(),
+        "Exported static channel backup"
+    );
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Recovers the channels of a static channel backup.
+///
+/// Must be called after the seed has been restored and before the node is started. The channel
+/// monitors are restored, so that the node sweeps our outputs once the coordinator has
+/// force-closed the channels.
+pub async fn recover_from_channel_backup(path: &Path) -> Result<()> {
+    let cipher = AesCipher::new(ln_dlc::get_node_key()?);
+
+    let encrypted_backup = fs::read(path)
+        .with_context(|| format!("Failed to read channel backup from {}", path.display()))?;
+    let backup = cipher
+        .decrypt(encrypted_backup)
+        .context("Failed to decrypt channel backup")?;
+    let backup: StaticChannelBackup = serde_json::from_slice(&backup)?;
+
+    restore_channel_monitors(&ln_dlc::get_storage()?, &backup)?;
+
+    let coordinator = config::get_coordinator_info().pubkey;
+    for channel in backup.channels.iter() {
+        if channel.counterparty != coordinator {
+            tracing::warn!(
+                channel_id = channel.channel_id,
+                counterparty = %channel.counterparty,
+                "Cannot request force-close of channel with unknown counterparty"
+            );
+            continue;
+        }
+
+        if let Err(e) = request_force_close(&cipher, &channel.channel_id).await {
+            tracing::error!(
+                channel_id = channel.channel_id,
+                "Failed to request force-close of channel: {e:#}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn request_force_close(cipher: &AesCipher, channel_id: &str) -> Result<()> {
+    let request = ForceCloseChannel {
+        trader_id: cipher.public_key(),
+        signature: cipher.sign(channel_id.as_bytes().to_vec())?,
+    };
+
+    let response = reqwest_client()
+        .post(format!(
+            "http://{}/api/v1/channels/{channel_id}/force-close",
+            config::get_http_endpoint()
+        ))
+        .json(&request)
+        .send()
+        .await
+        .context("Failed to request force-close from coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        bail!("Coordinator did not force-close channel: {response_text}");
+    }
+
+    tracing::info!(channel_id, "Coordinator is force-closing channel");
+
+    Ok(())
+}
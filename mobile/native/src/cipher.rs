@@ -3,6 +3,7 @@ use aes_gcm_siv::Aes256GcmSiv;
 use aes_gcm_siv::KeyInit;
 use aes_gcm_siv::Nonce;
 use anyhow::anyhow;
+use anyhow::ensure;
 use anyhow::Result;
 use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::rand;
@@ -11,6 +12,9 @@ use bitcoin::secp256k1::PublicKey;
 use bitcoin::secp256k1::SecretKey;
 use bitcoin::secp256k1::SECP256K1;
 
+/// The size of the nonce, which precedes the cipher text.
+const NONCE_SIZE: usize = 12;
+
 #[derive(Clone)]
 pub struct AesCipher {
     secret_key: SecretKey,
@@ -45,10 +49,16 @@ impl AesCipher {
     }
 
     pub fn decrypt(&self, value: Vec<u8>) -> Result<Vec<u8>> {
-        let nonce = Nonce::from_slice(&value[0..12]);
+        ensure!(
+            value.len() >= NONCE_SIZE,
+            "Cipher text of {} bytes is too short to contain a nonce",
+            value.len()
+        );
+
+        let nonce = Nonce::from_slice(&value[0..NONCE_SIZE]);
 
         let mut buffer: Vec<u8> = Vec::new();
-        buffer.extend_from_slice(&value[12..]);
+        buffer.extend_from_slice(&value[NONCE_SIZE..]);
 
         // Decrypt `buffer` in-place, replacing its ciphertext context with the original plaintext
         self.inner
@@ -68,9 +78,9 @@ impl AesCipher {
     }
 }
 
-fn generate_nonce() -> [u8; 12] {
+fn generate_nonce() -> [u8; NONCE_SIZE] {
     let mut rng = rand::thread_rng();
-    let mut nonce = [0u8; 12];
+    let mut nonce = [0u8; NONCE_SIZE];
 
     rng.fill(&mut nonce);
     nonce
@@ -96,6 +106,18 @@ mod tests {
         assert_eq!(decrypted_message, message);
     }
 
+    #[test]
+    fn truncated_value_is_not_decrypted() {
+        let secret_key = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let cipher = AesCipher::new(secret_key);
+
+        let encrypted_message = cipher.encrypt(b"10101".to_vec()).unwrap();
+
+        assert!(cipher.decrypt(encrypted_message[..11].to_vec()).is_err());
+        assert!(cipher.decrypt(encrypted_message[..12].to_vec()).is_err());
+        assert!(cipher.decrypt(vec![]).is_err());
+    }
+
     #[test]
     fn sign_backup_value() {
         let secret_key = SecretKey::new(&mut secp256k1::rand::thread_rng());
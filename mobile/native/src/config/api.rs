@@ -1,6 +1,8 @@
 use crate::config::ConfigInternal;
+use crate::config::DEFAULT_COLLABORATIVE_CLOSE_PAYOUT_TOLERANCE;
 use bdk::bitcoin::Network;
 use bdk::bitcoin::XOnlyPublicKey;
+use commons::AppConfig;
 use flutter_rust_bridge::frb;
 use std::str::FromStr;
 
@@ -15,8 +17,12 @@ pub struct Config {
     pub network: String,
     pub oracle_endpoint: String,
     pub oracle_pubkey: String,
-    pub health_check_interval_secs: u64,
+    /// Overrides the health check interval served by the coordinator if set.
+    pub health_check_interval_secs: Option<u64>,
     pub rgs_server_url: Option<String>,
+    pub watchtower_url: Option<String>,
+    /// A backup server to use instead of the coordinator, implementing the same backup API.
+    pub backup_url: Option<String>,
 }
 
 pub struct Directories {
@@ -53,12 +59,18 @@ impl From<(Config, Directories)> for ConfigInternal {
             oracle_endpoint: config.oracle_endpoint,
             oracle_pubkey: XOnlyPublicKey::from_str(config.oracle_pubkey.as_str())
                 .expect("Valid oracle public key"),
-            health_check_interval: std::time::Duration::from_secs(
-                config.health_check_interval_secs,
-            ),
+            health_check_interval: config
+                .health_check_interval_secs
+                .map(std::time::Duration::from_secs),
             data_dir: dirs.app_dir,
             seed_dir: dirs.seed_dir,
             rgs_server_url,
+            watchtower_url: config.watchtower_url.filter(|url| !url.is_empty()),
+            backup_url: config.backup_url.filter(|url| !url.is_empty()),
+            app_config: AppConfig::default(),
+            zero_conf_channels_enabled: true,
+            collaborative_close_payout_tolerance: DEFAULT_COLLABORATIVE_CLOSE_PAYOUT_TOLERANCE,
+            paper_trading: false,
         }
     }
 }
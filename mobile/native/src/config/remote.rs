@@ -0,0 +1,41 @@
+use crate::commons::reqwest_client;
+use crate::config;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use commons::AppConfig;
+
+/// Fetch the [`AppConfig`] from the coordinator and merge it into our config.
+pub async fn refresh_app_config() -> Result<()> {
+    let app_config = fetch_app_config().await?;
+
+    tracing::info!(?app_config, "Received app config from coordinator");
+
+    config::set_app_config(app_config);
+
+    Ok(())
+}
+
+async fn fetch_app_config() -> Result<AppConfig> {
+    let client = reqwest_client();
+    let response = client
+        .get(format!(
+            "http://{}/api/v1/app-config",
+            config::get_http_endpoint()
+        ))
+        .send()
+        .await
+        .context("Failed to fetch app config from coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        bail!("Could not fetch app config from coordinator: {response_text}");
+    }
+
+    response.json().await.context("Failed to parse app config")
+}
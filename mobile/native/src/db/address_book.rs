@@ -0,0 +1,115 @@
+use crate::schema::address_book;
+use anyhow::ensure;
+use anyhow::Result;
+use diesel::AsChangeset;
+use diesel::ExpressionMethods;
+use diesel::Insertable;
+use diesel::QueryDsl;
+use diesel::QueryResult;
+use diesel::Queryable;
+use diesel::RunQueryDsl;
+use diesel::SqliteConnection;
+use time::OffsetDateTime;
+
+/// An address the user has withdrawn to, or saved to withdraw to later.
+#[derive(Insertable, Queryable, Debug, Clone, PartialEq, AsChangeset)]
+#[diesel(table_name = address_book)]
+pub struct AddressBookEntry {
+    pub address: String,
+    pub label: String,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+impl AddressBookEntry {
+    pub(crate) fn get_all(conn: &mut SqliteConnection) -> QueryResult<Vec<Self>> {
+        address_book::table
+            .order(address_book::created_at.desc())
+            .load(conn)
+    }
+
+    /// Saves the address with the given label, replacing the label if the address is already
+    /// known.
+    pub(crate) fn upsert_label(
+        conn: &mut SqliteConnection,
+        address: &str,
+        label: &str,
+    ) -> Result<()> {
+        let affected_rows = diesel::insert_into(address_book::table)
+            .values((
+                address_book::address.eq(address),
+                address_book::label.eq(label),
+                address_book::created_at.eq(OffsetDateTime::now_utc().unix_timestamp()),
+            ))
+            .on_conflict(address_book::address)
+            .do_update()
+            .set(address_book::label.eq(label))
+            .execute(conn)?;
+
+        ensure!(affected_rows > 0, "Could not save address {address}");
+
+        Ok(())
+    }
+
+    /// Records a withdrawal to the address, adding it without a label if it is not known yet.
+    pub(crate) fn mark_used(conn: &mut SqliteConnection, address: &str) -> Result<()> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        let affected_rows = diesel::insert_into(address_book::table)
+            .values((
+                address_book::address.eq(address),
+                address_book::label.eq(""),
+                address_book::created_at.eq(now),
+                address_book::last_used_at.eq(now),
+            ))
+            .on_conflict(address_book::address)
+            .do_update()
+            .set(address_book::last_used_at.eq(now))
+            .execute(conn)?;
+
+        ensure!(
+            affected_rows > 0,
+            "Could not mark address {address} as used"
+        );
+
+        Ok(())
+    }
+
+    pub(crate) fn delete(conn: &mut SqliteConnection, address: &str) -> QueryResult<usize> {
+        diesel::delete(address_book::table)
+            .filter(address_book::address.eq(address))
+            .execute(conn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::MIGRATIONS;
+    use diesel::Connection;
+    use diesel_migrations::MigrationHarness;
+
+    #[test]
+    fn marking_a_labelled_address_as_used_keeps_the_label() {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let address = "bcrt1qcv3yt2ydvqfxl4h4dddgq8j2ajz07cry0ha3rx";
+
+        AddressBookEntry::upsert_label(&mut connection, address, "cold storage").unwrap();
+        AddressBookEntry::mark_used(&mut connection, address).unwrap();
+
+        let entries = AddressBookEntry::get_all(&mut connection).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].label, "cold storage");
+        assert!(entries[0].last_used_at.is_some());
+
+        AddressBookEntry::mark_used(&mut connection, "bcrt1qunknown").unwrap();
+        assert_eq!(AddressBookEntry::get_all(&mut connection).unwrap().len(), 2);
+
+        AddressBookEntry::delete(&mut connection, address).unwrap();
+        let entries = AddressBookEntry::get_all(&mut connection).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].label, "");
+    }
+}
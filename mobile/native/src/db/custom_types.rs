@@ -69,6 +69,7 @@ impl ToSql<Text, Sqlite> for OrderState {
     fn to_sql(&self, out: &mut Output<Sqlite>) -> serialize::Result {
         let text = match *self {
             OrderState::Initial => "initial".to_string(),
+            OrderState::Queued => "queued".to_string(),
             OrderState::Rejected => "rejected".to_string(),
             OrderState::Open => "open".to_string(),
             OrderState::Failed => "failed".to_string(),
@@ -86,6 +87,7 @@ impl FromSql<Text, Sqlite> for OrderState {
 
         return match string.as_str() {
             "initial" => Ok(OrderState::Initial),
+            "queued" => Ok(OrderState::Queued),
             "rejected" => Ok(OrderState::Rejected),
             "open" => Ok(OrderState::Open),
             "failed" => Ok(OrderState::Failed),
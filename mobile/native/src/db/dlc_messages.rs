@@ -60,6 +60,30 @@ impl DlcMessage {
         Ok(result.map(|q| q.into()))
     }
 
+    /// The unix timestamp of the last message we received from or sent to `peer_id`.
+    pub(crate) fn get_last_timestamp(
+        conn: &mut SqliteConnection,
+        peer_id: &PublicKey,
+        inbound: bool,
+    ) -> QueryResult<Option<i64>> {
+        schema::dlc_messages::table
+            .filter(schema::dlc_messages::peer_id.eq(peer_id.to_string()))
+            .filter(schema::dlc_messages::inbound.eq(inbound))
+            .select(diesel::dsl::max(schema::dlc_messages::timestamp))
+            .first::<Option<i64>>(conn)
+    }
+
+    /// The last `limit` messages we received or sent, newest first.
+    pub(crate) fn get_latest(
+        conn: &mut SqliteConnection,
+        limit: i64,
+    ) -> QueryResult<Vec<DlcMessage>> {
+        schema::dlc_messages::table
+            .order_by(schema::dlc_messages::timestamp.desc())
+            .limit(limit)
+            .load(conn)
+    }
+
     pub(crate) fn insert(
         conn: &mut SqliteConnection,
         dlc_message: ln_dlc_node::dlc_message::DlcMessage,
@@ -0,0 +1,146 @@
+use anyhow::Context;
+use anyhow::Result;
+use rusqlite::Connection;
+use rusqlite::ErrorCode;
+use rusqlite::OpenFlags;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+/// Checks the integrity of the database at `db_path` and repairs it if it is corrupted.
+///
+/// A corrupted database is moved aside, together with its WAL files, and replaced by the backup at
+/// `backup_path`, provided the backup passes the integrity check itself. Otherwise the database is
+/// recreated from scratch by the migrations. The corrupted files are kept for manual recovery.
+pub(super) fn ensure_integrity(db_path: &Path, backup_path: &Path) -> Result<()> {
+    if !is_corrupted(db_path)? {
+        return Ok(());
+    }
+
+    move_aside(db_path)?;
+
+    if !backup_path.exists() || is_corrupted(backup_path)? {
+        tracing::error!(
+            db = %db_path.display(),
+            "No valid backup to restore the corrupted database from. Starting with an empty \
+             database"
+        );
+        return Ok(());
+    }
+
+    fs::copy(backup_path, db_path).context("Failed to restore database from backup")?;
+
+    tracing::warn!(
+        db = %db_path.display(),
+        backup = %backup_path.display(),
+        "Restored corrupted database from backup"
+    );
+
+    Ok(())
+}
+
+fn is_corrupted(db_path: &Path) -> Result<bool> {
+    if !db_path.exists() {
+        return Ok(false);
+    }
+
+    // The database has to be opened for writing, so that SQLite can recover the WAL.
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+
+    match conn.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0)) {
+        Ok(result) if result == "ok" => Ok(false),
+        Ok(result) => {
+            tracing::error!(db = %db_path.display(), %result, "Database failed integrity check");
+            Ok(true)
+        }
+        Err(rusqlite::Error::SqliteFailure(e, msg))
+            if matches!(e.code, ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase) =>
+        {
+            tracing::error!(db = %db_path.display(), ?msg, "Database is corrupted: {e}");
+            Ok(true)
+        }
+        Err(e) => Err(e).context("Failed to check database integrity"),
+    }
+}
+
+fn move_aside(db_path: &Path) -> Result<()> {
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+    for suffix in ["", "-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{suffix}", db_path.display()));
+        if path.exists() {
+            let corrupted = format!("{}.corrupted-{timestamp}", path.display());
+            fs::rename(&path, &corrupted)
+                .with_context(|| format!("Failed to move {} aside", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn corrupted_database_is_restored_from_backup() {
+        let dir = std::env::temp_dir().join(format!("db-integrity-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("trades.sqlite");
+        let backup_path = dir.join("backup.sqlite");
+
+        let backup = Connection::open(&backup_path).unwrap();
+        backup
+            .execute_batch("CREATE TABLE trades (id INTEGER); INSERT INTO trades VALUES (42);")
+            .unwrap();
+        drop(backup);
+
+        fs::write(&db_path, b"definitely not a sqlite database file").unwrap();
+
+        ensure_integrity(&db_path, &backup_path).unwrap();
+
+        let restored = Connection::open(&db_path).unwrap();
+        let id: i64 = restored
+            .query_row("SELECT id FROM trades", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(id, 42);
+
+        let corrupted_files = fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains("corrupted")
+            })
+            .count();
+        assert_eq!(corrupted_files, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn healthy_database_is_left_alone() {
+        let dir = std::env::temp_dir().join(format!("db-integrity-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("trades.sqlite");
+
+        let db = Connection::open(&db_path).unwrap();
+        db.execute_batch("CREATE TABLE trades (id INTEGER);")
+            .unwrap();
+        drop(db);
+
+        ensure_integrity(&db_path, &dir.join("backup.sqlite")).unwrap();
+
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
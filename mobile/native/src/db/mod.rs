@@ -14,13 +14,17 @@ use crate::db::models::Trade;
 use crate::db::models::Transaction;
 use crate::trade;
 use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use base64::Engine;
 use bdk::bitcoin;
 use bitcoin::secp256k1::PublicKey;
 use bitcoin::Txid;
+use diesel::connection::AnsiTransactionManager;
 use diesel::connection::SimpleConnection;
+use diesel::connection::TransactionManager;
 use diesel::r2d2;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
@@ -35,16 +39,21 @@ use rusqlite::backup::Backup;
 use rusqlite::Connection;
 use rusqlite::OpenFlags;
 use state::Storage;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::ops::DerefMut;
 use std::path::Path;
 use std::sync::Arc;
 use time::Duration;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+pub mod address_book;
 mod custom_types;
 pub mod dlc_messages;
-pub mod last_outbound_dlc_messages;
+mod integrity;
 pub mod models;
+pub mod outbound_dlc_messages;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
@@ -90,6 +99,10 @@ pub fn init_db(db_dir: &str, network: bitcoin::Network) -> Result<()> {
         return Ok(());
     }
 
+    let db_path = Path::new(db_dir).join(format!("trades-{network}.sqlite"));
+    let backup_path = Path::new(&config::get_backup_dir()).join("trades.sqlite");
+    integrity::ensure_integrity(&db_path, &backup_path)?;
+
     let database_url = format!("sqlite://{db_dir}/trades-{network}.sqlite");
     let manager = ConnectionManager::<SqliteConnection>::new(database_url);
     let pool = r2d2::Pool::builder()
@@ -123,6 +136,32 @@ pub fn init_db(db_dir: &str, network: bitcoin::Network) -> Result<()> {
     Ok(())
 }
 
+/// Initialises the database with an in-memory database, for tests of code which accesses the
+/// database through [`connection`].
+#[cfg(test)]
+pub(crate) fn init_test_db() {
+    if DB.try_get().is_some() {
+        return;
+    }
+
+    // The in-memory database lives as long as its only connection, hence the connection must not
+    // be recycled.
+    let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+    let pool = r2d2::Pool::builder()
+        .max_size(MAX_DB_POOL_SIZE)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .build(manager)
+        .unwrap();
+
+    pool.get()
+        .unwrap()
+        .run_pending_migrations(MIGRATIONS)
+        .unwrap();
+
+    DB.set(Arc::new(pool));
+}
+
 /// Creates a backup of the database
 ///
 /// Returns the path to the file of the database backup
@@ -139,13 +178,179 @@ pub fn back_up() -> Result<String> {
     Ok(dst_path.to_string_lossy().to_string())
 }
 
-pub fn connection() -> Result<PooledConnection<ConnectionManager<SqliteConnection>>> {
+type PooledSqliteConnection = PooledConnection<ConnectionManager<SqliteConnection>>;
+
+thread_local! {
+    /// The connection of the unit of work running on this thread, if any.
+    static UNIT_OF_WORK_CONNECTION: RefCell<UnitOfWorkConnection> =
+        RefCell::new(UnitOfWorkConnection::Inactive);
+}
+
+enum UnitOfWorkConnection {
+    Inactive,
+    Idle(PooledSqliteConnection),
+    InUse,
+}
+
+/// A connection to the database.
+///
+/// Within a unit of work, this is the connection holding the open transaction of the unit of
+/// work, which is handed back to the unit of work once dropped.
+pub struct DbConnection {
+    conn: Option<PooledSqliteConnection>,
+    is_unit_of_work: bool,
+}
+
+impl Deref for DbConnection {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &Self::Target {
+        self.conn
+            .as_ref()
+            .expect("connection to be set until dropped")
+    }
+}
+
+impl DerefMut for DbConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.conn
+            .as_mut()
+            .expect("connection to be set until dropped")
+    }
+}
+
+impl Drop for DbConnection {
+    fn drop(&mut self) {
+        if !self.is_unit_of_work {
+            return;
+        }
+
+        if let Some(conn) = self.conn.take() {
+            UNIT_OF_WORK_CONNECTION.with(|unit_of_work| {
+                *unit_of_work.borrow_mut() = UnitOfWorkConnection::Idle(conn);
+            });
+        }
+    }
+}
+
+pub fn connection() -> Result<DbConnection> {
+    let unit_of_work_conn = UNIT_OF_WORK_CONNECTION.with(|unit_of_work| {
+        let mut unit_of_work = unit_of_work.borrow_mut();
+        match std::mem::replace(&mut *unit_of_work, UnitOfWorkConnection::InUse) {
+            UnitOfWorkConnection::Idle(conn) => Ok(Some(conn)),
+            UnitOfWorkConnection::InUse => {
+                bail!("Database connection of unit of work is already in use")
+            }
+            UnitOfWorkConnection::Inactive => {
+                *unit_of_work = UnitOfWorkConnection::Inactive;
+                Ok(None)
+            }
+        }
+    })?;
+
+    if let Some(conn) = unit_of_work_conn {
+        return Ok(DbConnection {
+            conn: Some(conn),
+            is_unit_of_work: true,
+        });
+    }
+
+    Ok(DbConnection {
+        conn: Some(pooled_connection()?),
+        is_unit_of_work: false,
+    })
+}
+
+fn pooled_connection() -> Result<PooledSqliteConnection> {
     let pool = DB.try_get().context("DB uninitialised").cloned()?;
 
     pool.get()
         .map_err(|e| anyhow!("cannot acquire database connection: {e:#}"))
 }
 
+/// Opens a transaction which spans all database accesses on this thread until
+/// [`commit_unit_of_work`] or [`rollback_unit_of_work`] is called.
+///
+/// This relies on the pool having a single connection: while the transaction is open, the
+/// connection is held by the unit of work and other threads wait for it to be committed or rolled
+/// back.
+pub(crate) fn begin_unit_of_work() -> Result<()> {
+    let is_unit_of_work = UNIT_OF_WORK_CONNECTION
+        .with(|unit_of_work| !matches!(*unit_of_work.borrow(), UnitOfWorkConnection::Inactive));
+    if is_unit_of_work {
+        bail!("Unit of work already in progress");
+    }
+
+    let mut conn = pooled_connection()?;
+    begin_transaction(&mut conn).context("Failed to begin unit of work")?;
+
+    UNIT_OF_WORK_CONNECTION.with(|unit_of_work| {
+        *unit_of_work.borrow_mut() = UnitOfWorkConnection::Idle(conn);
+    });
+
+    Ok(())
+}
+
+pub(crate) fn commit_unit_of_work() -> Result<()> {
+    let mut conn = take_unit_of_work_connection()?;
+    commit_transaction(&mut conn).context("Failed to commit unit of work")
+}
+
+pub(crate) fn rollback_unit_of_work() -> Result<()> {
+    let mut conn = take_unit_of_work_connection()?;
+    rollback_transaction(&mut conn).context("Failed to roll back unit of work")
+}
+
+fn take_unit_of_work_connection() -> Result<PooledSqliteConnection> {
+    let unit_of_work = UNIT_OF_WORK_CONNECTION.with(|unit_of_work| {
+        std::mem::replace(
+            &mut *unit_of_work.borrow_mut(),
+            UnitOfWorkConnection::Inactive,
+        )
+    });
+
+    match unit_of_work {
+        UnitOfWorkConnection::Idle(conn) => Ok(conn),
+        UnitOfWorkConnection::InUse => bail!("Database connection of unit of work is still in use"),
+        UnitOfWorkConnection::Inactive => bail!("No unit of work in progress"),
+    }
+}
+
+/// Whether a transaction is open on `conn`, e.g. the transaction of a unit of work.
+pub(crate) fn is_in_transaction(conn: &mut SqliteConnection) -> Result<bool> {
+    let depth = AnsiTransactionManager::transaction_manager_status_mut(conn).transaction_depth()?;
+
+    Ok(depth.is_some())
+}
+
+/// Begins the transaction of a unit of work.
+///
+/// The transaction is opened through diesel's transaction manager, so that transactions opened
+/// within the unit of work, e.g. by [`Connection::transaction`], become savepoints.
+///
+/// [`Connection::transaction`]: diesel::Connection::transaction
+fn begin_transaction(conn: &mut SqliteConnection) -> Result<()> {
+    AnsiTransactionManager::begin_transaction_sql(conn, "BEGIN IMMEDIATE")?;
+
+    Ok(())
+}
+
+fn commit_transaction(conn: &mut SqliteConnection) -> Result<()> {
+    if let Err(e) = AnsiTransactionManager::commit_transaction(conn) {
+        // Make sure that the connection does not go back into the pool with an open transaction.
+        let _ = AnsiTransactionManager::rollback_transaction(conn);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+fn rollback_transaction(conn: &mut SqliteConnection) -> Result<()> {
+    AnsiTransactionManager::rollback_transaction(conn)?;
+
+    Ok(())
+}
+
 pub fn insert_order(order: trade::order::Order) -> Result<trade::order::Order> {
     let mut db = connection()?;
     let order = Order::insert(order.into(), &mut db)?;
@@ -213,6 +418,23 @@ pub fn get_filled_orders() -> Result<Vec<trade::order::Order>> {
     Ok(orders)
 }
 
+/// Returns the orders waiting for the coordinator to become reachable
+pub fn get_queued_orders() -> Result<Vec<trade::order::Order>> {
+    let mut db = connection()?;
+    let orders = Order::get_by_state(OrderState::Queued, &mut db)?;
+
+    let orders = orders
+        .into_iter()
+        .map(|order| {
+            order
+                .try_into()
+                .context("Failed to convert to trade::order::Order")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(orders)
+}
+
 /// Returns all open orders
 pub fn maybe_get_open_orders() -> Result<Vec<trade::order::Order>> {
     let mut db = connection()?;
@@ -276,6 +498,21 @@ pub fn get_order_in_filling() -> Result<Option<trade::order::Order>> {
     Ok(Some(order.clone().try_into()?))
 }
 
+/// Return when the order went into [`OrderState::Filling`].
+///
+/// Orders which went into filling before we started recording it do not have a filling timestamp.
+pub fn get_order_filling_since(order_id: Uuid) -> Result<Option<OffsetDateTime>> {
+    let mut db = connection()?;
+    let order = Order::get(order_id.to_string(), &mut db)?;
+
+    let filling_since = order
+        .filling_timestamp
+        .map(OffsetDateTime::from_unix_timestamp)
+        .transpose()?;
+
+    Ok(filling_since)
+}
+
 pub fn delete_order(order_id: Uuid) -> Result<()> {
     let mut db = connection()?;
     Order::delete(order_id.to_string(), &mut db)?;
@@ -301,19 +538,21 @@ pub fn get_positions() -> Result<Vec<trade::position::Position>> {
     Ok(positions)
 }
 
-pub fn delete_positions() -> Result<()> {
+pub fn delete_position(id: Uuid) -> Result<()> {
     let mut db = connection()?;
-    Position::delete_all(&mut db)?;
+    let affected_rows = Position::delete(&mut db, id.to_string())?;
+
+    ensure!(affected_rows > 0, "Could not delete position {id}");
 
     Ok(())
 }
 
 pub fn update_position_state(
-    contract_symbol: ::trade::ContractSymbol,
+    id: Uuid,
     position_state: trade::position::PositionState,
 ) -> Result<()> {
     let mut db = connection()?;
-    Position::update_state(contract_symbol.into(), position_state.into(), &mut db)
+    Position::update_state(id.to_string(), position_state.into(), &mut db)
         .context("Failed to update position state")?;
 
     Ok(())
@@ -327,12 +566,9 @@ pub fn update_position(resized_position: trade::position::Position) -> Result<()
     Ok(())
 }
 
-pub fn rollover_position(
-    contract_symbol: ::trade::ContractSymbol,
-    expiry_timestamp: OffsetDateTime,
-) -> Result<()> {
+pub fn rollover_position(id: Uuid, expiry_timestamp: OffsetDateTime) -> Result<()> {
     let mut db = connection()?;
-    Position::rollover(&mut db, contract_symbol.into(), expiry_timestamp)
+    Position::rollover(&mut db, id.to_string(), expiry_timestamp)
         .context("Failed to rollover position")?;
 
     Ok(())
@@ -351,6 +587,7 @@ pub fn insert_payment(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_payment(
     payment_hash: lightning::ln::PaymentHash,
     htlc_status: ln_dlc_node::HTLCStatus,
@@ -359,6 +596,7 @@ pub fn update_payment(
     preimage: Option<lightning::ln::PaymentPreimage>,
     secret: Option<lightning::ln::PaymentSecret>,
     funding_txid: Option<Txid>,
+    failure_reason: Option<ln_dlc_node::PaymentFailureReason>,
 ) -> Result<()> {
     tracing::info!(?payment_hash, "Updating payment");
 
@@ -369,6 +607,7 @@ pub fn update_payment(
     let preimage = preimage.map(|preimage| base64.encode(preimage.0));
     let secret = secret.map(|secret| base64.encode(secret.0));
     let funding_txid = funding_txid.map(|txid| txid.to_string());
+    let failure_reason = failure_reason.map(|reason| reason.to_string());
 
     PaymentInsertable::update(
         base64.encode(payment_hash.0),
@@ -378,6 +617,7 @@ pub fn update_payment(
         preimage,
         secret,
         funding_txid,
+        failure_reason,
         &mut db,
     )?;
 
@@ -579,3 +819,96 @@ pub fn insert_trade(trade: crate::trade::Trade) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::Connection as _;
+
+    #[test]
+    fn committed_unit_of_work_keeps_changes() {
+        let mut conn = test_connection();
+
+        begin_transaction(&mut conn).unwrap();
+        let order = Order::insert(dummy_order().into(), &mut conn).unwrap();
+        commit_transaction(&mut conn).unwrap();
+
+        assert!(Order::get(order.id, &mut conn).is_ok());
+    }
+
+    #[test]
+    fn rolled_back_unit_of_work_discards_changes() {
+        let mut conn = test_connection();
+
+        begin_transaction(&mut conn).unwrap();
+        let order = Order::insert(dummy_order().into(), &mut conn).unwrap();
+        rollback_transaction(&mut conn).unwrap();
+
+        assert_eq!(
+            Order::get(order.id, &mut conn).unwrap_err(),
+            diesel::result::Error::NotFound
+        );
+    }
+
+    #[test]
+    fn order_state_can_be_updated_within_unit_of_work() {
+        let mut conn = test_connection();
+        let order = Order::insert(dummy_order().into(), &mut conn).unwrap();
+
+        begin_transaction(&mut conn).unwrap();
+        Order::update_state(
+            order.id.clone(),
+            trade::order::OrderState::Filling {
+                execution_price: 100000.0,
+            }
+            .into(),
+            &mut conn,
+        )
+        .unwrap();
+        rollback_transaction(&mut conn).unwrap();
+
+        let order = Order::get(order.id.clone(), &mut conn).unwrap();
+        assert_eq!(order.state, OrderState::Open);
+
+        begin_transaction(&mut conn).unwrap();
+        Order::update_state(
+            order.id.clone(),
+            trade::order::OrderState::Filling {
+                execution_price: 100000.0,
+            }
+            .into(),
+            &mut conn,
+        )
+        .unwrap();
+        commit_transaction(&mut conn).unwrap();
+
+        let order = Order::get(order.id, &mut conn).unwrap();
+        assert_eq!(order.state, OrderState::Filling);
+    }
+
+    fn test_connection() -> SqliteConnection {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        conn
+    }
+
+    fn dummy_order() -> trade::order::Order {
+        trade::order::Order {
+            id: Uuid::new_v4(),
+            leverage: 2.0,
+            quantity: 100.0,
+            contract_symbol: ::trade::ContractSymbol::BtcUsd,
+            direction: ::trade::Direction::Long,
+            order_type: trade::order::OrderType::Market,
+            state: trade::order::OrderState::Open,
+            creation_timestamp: OffsetDateTime::UNIX_EPOCH,
+            order_expiry_timestamp: OffsetDateTime::UNIX_EPOCH,
+            reason: trade::order::OrderReason::Manual,
+            stable: false,
+            reduce_only: false,
+            position_id: None,
+            failure_reason: None,
+        }
+    }
+}
@@ -67,6 +67,11 @@ pub(crate) struct Order {
     pub order_expiry_timestamp: i64,
     pub reason: OrderReason,
     pub stable: bool,
+    pub reduce_only: bool,
+    /// When the order went into [`OrderState::Filling`], if it did.
+    pub filling_timestamp: Option<i64>,
+    /// The position which the order resizes or closes, if any.
+    pub position_id: Option<String>,
 }
 
 impl Order {
@@ -84,12 +89,15 @@ impl Order {
     }
 
     /// updates the status of the given order in the db
+    ///
+    /// Within a unit of work, the update is done in a savepoint of the transaction of the unit of
+    /// work. Otherwise, it is done in an exclusive transaction.
     pub fn update_state(
         order_id: String,
         status: (OrderState, Option<f32>, Option<FailureReason>),
         conn: &mut SqliteConnection,
     ) -> Result<Order> {
-        conn.exclusive_transaction::<Order, _, _>(|conn| {
+        let update_state = |conn: &mut SqliteConnection| -> Result<Order> {
             let order: Order = orders::table
                 .filter(schema::orders::id.eq(order_id.clone()))
                 .first(conn)?;
@@ -107,6 +115,16 @@ impl Order {
                         bail!("Could not update order state")
                     }
 
+                    if next_state == OrderState::Filling && current_state != OrderState::Filling {
+                        diesel::update(orders::table)
+                            .filter(schema::orders::id.eq(order_id.clone()))
+                            .set(
+                                schema::orders::filling_timestamp
+                                    .eq(OffsetDateTime::now_utc().unix_timestamp()),
+                            )
+                            .execute(conn)?;
+                    }
+
                     tracing::info!(new_state = ?next_state, %order_id, "Updated order state");
                 }
                 None => {
@@ -141,7 +159,13 @@ impl Order {
                 .first(conn)?;
 
             Ok(order)
-        })
+        };
+
+        if crate::db::is_in_transaction(conn)? {
+            conn.transaction::<Order, _, _>(update_state)
+        } else {
+            conn.exclusive_transaction::<Order, _, _>(update_state)
+        }
     }
 
     pub fn get(order_id: String, conn: &mut SqliteConnection) -> QueryResult<Order> {
@@ -213,6 +237,9 @@ impl From<crate::trade::order::Order> for Order {
             order_expiry_timestamp: value.order_expiry_timestamp.unix_timestamp(),
             reason: value.reason.into(),
             stable: value.stable,
+            reduce_only: value.reduce_only,
+            filling_timestamp: None,
+            position_id: value.position_id.map(|position_id| position_id.to_string()),
         }
     }
 }
@@ -260,6 +287,12 @@ impl TryFrom<Order> for crate::trade::order::Order {
             .expect("unix timestamp to fit in itself"),
             reason: value.reason.into(),
             stable: value.stable,
+            reduce_only: value.reduce_only,
+            position_id: value
+                .position_id
+                .map(|position_id| Uuid::parse_str(&position_id))
+                .transpose()
+                .map_err(Error::InvalidId)?,
             failure_reason: value.failure_reason.map(|reason| reason.into()),
         };
 
@@ -270,6 +303,7 @@ impl TryFrom<Order> for crate::trade::order::Order {
 #[derive(Queryable, QueryableByName, Insertable, Debug, Clone, PartialEq)]
 #[diesel(table_name = positions)]
 pub(crate) struct Position {
+    pub id: String,
     pub contract_symbol: ContractSymbol,
     pub leverage: f32,
     pub quantity: f32,
@@ -311,14 +345,14 @@ impl Position {
         positions::table.load(conn)
     }
 
-    /// updates the status of the given order in the db
+    /// updates the status of the given position in the db
     pub fn update_state(
-        contract_symbol: ContractSymbol,
+        id: String,
         state: PositionState,
         conn: &mut SqliteConnection,
     ) -> Result<()> {
         let affected_rows = diesel::update(positions::table)
-            .filter(schema::positions::contract_symbol.eq(contract_symbol))
+            .filter(schema::positions::id.eq(id))
             .set(schema::positions::state.eq(state))
             .execute(conn)?;
 
@@ -332,11 +366,11 @@ impl Position {
     // sets the position to rollover and updates the new expiry timestamp.
     pub fn rollover(
         conn: &mut SqliteConnection,
-        contract_symbol: ContractSymbol,
+        id: String,
         expiry_timestamp: OffsetDateTime,
     ) -> Result<()> {
         let affected_rows = diesel::update(positions::table)
-            .filter(schema::positions::contract_symbol.eq(contract_symbol))
+            .filter(schema::positions::id.eq(id))
             .set((
                 positions::expiry_timestamp.eq(expiry_timestamp.unix_timestamp()),
                 positions::state.eq(PositionState::Rollover),
@@ -352,7 +386,7 @@ impl Position {
     /// Updates the status of the given order in the DB.
     pub fn update_position(conn: &mut SqliteConnection, position: Position) -> Result<()> {
         let Position {
-            contract_symbol,
+            id,
             leverage,
             quantity,
             direction,
@@ -367,7 +401,7 @@ impl Position {
         } = position;
 
         let affected_rows = diesel::update(positions::table)
-            .filter(schema::positions::contract_symbol.eq(contract_symbol))
+            .filter(schema::positions::id.eq(id))
             .set((
                 positions::leverage.eq(leverage),
                 positions::quantity.eq(quantity),
@@ -388,16 +422,17 @@ impl Position {
         Ok(())
     }
 
-    // TODO: This is obviously only for the MVP :)
-    /// deletes all positions in the database
-    pub fn delete_all(conn: &mut SqliteConnection) -> QueryResult<usize> {
-        diesel::delete(positions::table).execute(conn)
+    pub fn delete(conn: &mut SqliteConnection, id: String) -> QueryResult<usize> {
+        diesel::delete(positions::table)
+            .filter(positions::id.eq(id))
+            .execute(conn)
     }
 }
 
 impl From<Position> for crate::trade::position::Position {
     fn from(value: Position) -> Self {
         Self {
+            id: Uuid::parse_str(&value.id).expect("to be a valid position id"),
             leverage: value.leverage,
             quantity: value.quantity,
             contract_symbol: value.contract_symbol.into(),
@@ -420,6 +455,7 @@ impl From<Position> for crate::trade::position::Position {
 impl From<crate::trade::position::Position> for Position {
     fn from(value: crate::trade::position::Position) -> Self {
         Self {
+            id: value.id.to_string(),
             contract_symbol: value.contract_symbol.into(),
             leverage: value.leverage,
             quantity: value.quantity,
@@ -548,6 +584,7 @@ pub enum OrderReason {
 #[diesel(sql_type = Text)]
 pub enum OrderState {
     Initial,
+    Queued,
     Rejected,
     Open,
     Filling,
@@ -566,13 +603,18 @@ impl OrderState {
         match (self, latest) {
             // We can go from `Initial` to any other state
             (OrderState::Initial, latest) => Some(latest),
+            // We cannot go back to `Initial` if the order is already `Queued`
+            (OrderState::Queued, OrderState::Initial) => None,
+            (OrderState::Queued, latest) => Some(latest),
             // `Rejected` is a final state
             (OrderState::Rejected, _) => None,
             // We cannnot go back to `Initial` if the order is already `Open`
-            (OrderState::Open, OrderState::Initial) => None,
+            (OrderState::Open, OrderState::Initial | OrderState::Queued) => None,
             (OrderState::Open, latest) => Some(latest),
             // We cannot go back to `Initial` or `Open` if the order is already `Filling`
-            (OrderState::Filling, OrderState::Initial | OrderState::Open) => None,
+            (OrderState::Filling, OrderState::Initial | OrderState::Queued | OrderState::Open) => {
+                None
+            }
             (OrderState::Filling, latest) => Some(latest),
             // `Failed` is a final state
             (OrderState::Failed, _) => None,
@@ -586,6 +628,7 @@ impl From<crate::trade::order::OrderState> for (OrderState, Option<f32>, Option<
     fn from(value: crate::trade::order::OrderState) -> Self {
         match value {
             crate::trade::order::OrderState::Initial => (OrderState::Initial, None, None),
+            crate::trade::order::OrderState::Queued => (OrderState::Queued, None, None),
             crate::trade::order::OrderState::Rejected => (OrderState::Rejected, None, None),
             crate::trade::order::OrderState::Open => (OrderState::Open, None, None),
             crate::trade::order::OrderState::Failed { reason } => {
@@ -609,6 +652,7 @@ impl TryFrom<(OrderState, Option<f32>, Option<FailureReason>)> for crate::trade:
     ) -> std::result::Result<Self, Self::Error> {
         let order_state = match value.0 {
             OrderState::Initial => crate::trade::order::OrderState::Initial,
+            OrderState::Queued => crate::trade::order::OrderState::Queued,
             OrderState::Rejected => crate::trade::order::OrderState::Rejected,
             OrderState::Open => crate::trade::order::OrderState::Open,
             OrderState::Failed => match value.2 {
@@ -644,10 +688,12 @@ pub enum FailureReason {
     CollabRevert,
     OrderNotAcceptable,
     TimedOut,
+    FillingTimedOut,
     SubchannelOfferOutdated,
     SubchannelOfferDateUndetermined,
     SubchannelOfferUnacceptable,
     OrderRejected,
+    Aborted,
     Unknown,
 }
 
@@ -666,6 +712,7 @@ impl From<FailureReason> for crate::trade::order::FailureReason {
                 crate::trade::order::FailureReason::OrderNotAcceptable
             }
             FailureReason::TimedOut => crate::trade::order::FailureReason::TimedOut,
+            FailureReason::FillingTimedOut => crate::trade::order::FailureReason::FillingTimedOut,
             FailureReason::SubchannelOfferOutdated => {
                 crate::trade::order::FailureReason::InvalidDlcOffer(
                     InvalidSubchannelOffer::Outdated,
@@ -682,6 +729,7 @@ impl From<FailureReason> for crate::trade::order::FailureReason {
                 )
             }
             FailureReason::OrderRejected => crate::trade::order::FailureReason::OrderRejected,
+            FailureReason::Aborted => crate::trade::order::FailureReason::Aborted,
             FailureReason::Unknown => crate::trade::order::FailureReason::Unknown,
         }
     }
@@ -702,6 +750,7 @@ impl From<crate::trade::order::FailureReason> for FailureReason {
                 FailureReason::OrderNotAcceptable
             }
             crate::trade::order::FailureReason::TimedOut => FailureReason::TimedOut,
+            crate::trade::order::FailureReason::FillingTimedOut => FailureReason::FillingTimedOut,
             crate::trade::order::FailureReason::InvalidDlcOffer(reason) => match reason {
                 InvalidSubchannelOffer::Outdated => FailureReason::SubchannelOfferOutdated,
                 InvalidSubchannelOffer::UndeterminedMaturityDate => {
@@ -710,6 +759,7 @@ impl From<crate::trade::order::FailureReason> for FailureReason {
                 InvalidSubchannelOffer::Unacceptable => FailureReason::SubchannelOfferUnacceptable,
             },
             crate::trade::order::FailureReason::OrderRejected => FailureReason::OrderRejected,
+            crate::trade::order::FailureReason::Aborted => FailureReason::Aborted,
             crate::trade::order::FailureReason::Unknown => FailureReason::Unknown,
         }
     }
@@ -738,6 +788,12 @@ pub(crate) struct PaymentInsertable {
     pub invoice: Option<String>,
     #[diesel(sql_type = Nullable<Text>)]
     pub funding_txid: Option<String>,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub expiry_timestamp: Option<i64>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub payee_pubkey: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub failure_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, FromSqlRow, AsExpression)]
@@ -775,6 +831,7 @@ impl PaymentInsertable {
         preimage: Option<String>,
         secret: Option<String>,
         funding_txid: Option<String>,
+        failure_reason: Option<String>,
         conn: &mut SqliteConnection,
     ) -> Result<i64> {
         let updated_at = OffsetDateTime::now_utc().unix_timestamp();
@@ -844,6 +901,17 @@ impl PaymentInsertable {
                 }
             }
 
+            if let Some(failure_reason) = failure_reason {
+                let affected_rows = diesel::update(payments::table)
+                    .filter(schema::payments::payment_hash.eq(&payment_hash))
+                    .set(schema::payments::failure_reason.eq(failure_reason))
+                    .execute(conn)?;
+
+                if affected_rows == 0 {
+                    bail!("Could not update payment failure_reason")
+                }
+            }
+
             let affected_rows = diesel::update(payments::table)
                 .filter(schema::payments::payment_hash.eq(&payment_hash))
                 .set(schema::payments::updated_at.eq(updated_at))
@@ -876,6 +944,9 @@ pub(crate) struct PaymentQueryable {
     pub invoice: Option<String>,
     pub fee_msat: Option<i64>,
     pub funding_txid: Option<String>,
+    pub expiry_timestamp: Option<i64>,
+    pub payee_pubkey: Option<String>,
+    pub failure_reason: Option<String>,
 }
 
 impl PaymentQueryable {
@@ -909,6 +980,9 @@ impl From<(lightning::ln::PaymentHash, ln_dlc_node::PaymentInfo)> for PaymentIns
             description: info.description,
             invoice: info.invoice,
             funding_txid: info.funding_txid.map(|txid| txid.to_string()),
+            expiry_timestamp: info.expiry.map(|expiry| expiry.unix_timestamp()),
+            payee_pubkey: info.payee.map(|payee| payee.to_string()),
+            failure_reason: info.failure_reason.map(|reason| reason.to_string()),
         }
     }
 }
@@ -966,6 +1040,19 @@ impl TryFrom<PaymentQueryable> for (lightning::ln::PaymentHash, ln_dlc_node::Pay
         let description = value.description;
         let invoice = value.invoice;
 
+        let expiry = value
+            .expiry_timestamp
+            .map(OffsetDateTime::from_unix_timestamp)
+            .transpose()?;
+        let failure_reason = value
+            .failure_reason
+            .map(|reason| ln_dlc_node::PaymentFailureReason::from_str(&reason))
+            .transpose()?;
+        let payee = value
+            .payee_pubkey
+            .map(|payee| PublicKey::from_str(&payee))
+            .transpose()?;
+
         Ok((
             payment_hash,
             ln_dlc_node::PaymentInfo {
@@ -979,6 +1066,9 @@ impl TryFrom<PaymentQueryable> for (lightning::ln::PaymentHash, ln_dlc_node::Pay
                 description,
                 invoice,
                 funding_txid,
+                expiry,
+                payee,
+                failure_reason,
             },
         ))
     }
@@ -1496,6 +1586,9 @@ pub mod test {
             order_expiry_timestamp: expiry_timestamp.unix_timestamp(),
             reason: OrderReason::Manual,
             stable: false,
+            reduce_only: false,
+            filling_timestamp: None,
+            position_id: None,
         };
 
         Order::insert(
@@ -1511,6 +1604,8 @@ pub mod test {
                 order_expiry_timestamp: expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
                 stable: false,
+                reduce_only: false,
+                position_id: None,
                 failure_reason: None,
             }
             .into(),
@@ -1532,6 +1627,8 @@ pub mod test {
                 order_expiry_timestamp: expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
                 stable: false,
+                reduce_only: false,
+                position_id: None,
                 failure_reason: None,
             }
             .into(),
@@ -1576,6 +1673,74 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn filling_order_records_since_when() {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let order = crate::trade::order::Order {
+            id: uuid::Uuid::new_v4(),
+            leverage: 2.0,
+            quantity: 100.0,
+            contract_symbol: trade::ContractSymbol::BtcUsd,
+            direction: trade::Direction::Long,
+            order_type: crate::trade::order::OrderType::Market,
+            state: crate::trade::order::OrderState::Open,
+            creation_timestamp: OffsetDateTime::UNIX_EPOCH,
+            order_expiry_timestamp: OffsetDateTime::UNIX_EPOCH,
+            reason: crate::trade::order::OrderReason::Manual,
+            stable: false,
+            reduce_only: false,
+            position_id: None,
+            failure_reason: None,
+        };
+        let order = Order::insert(order.into(), &mut connection).unwrap();
+        assert_eq!(order.filling_timestamp, None);
+
+        let before = OffsetDateTime::now_utc().unix_timestamp();
+        let filling = Order::update_state(
+            order.id.clone(),
+            (crate::trade::order::OrderState::Filling {
+                execution_price: 100000.0,
+            })
+            .into(),
+            &mut connection,
+        )
+        .unwrap();
+        let filling_timestamp = filling.filling_timestamp.unwrap();
+        assert!(filling_timestamp >= before);
+
+        // Repeated updates to `Filling` must not restart the clock.
+        let filling = Order::update_state(
+            order.id.clone(),
+            (crate::trade::order::OrderState::Filling {
+                execution_price: 100000.0,
+            })
+            .into(),
+            &mut connection,
+        )
+        .unwrap();
+        assert_eq!(filling.filling_timestamp, Some(filling_timestamp));
+    }
+
+    #[test]
+    fn queued_order_is_submitted_or_fails() {
+        assert_eq!(
+            OrderState::Initial.next_state(OrderState::Queued),
+            Some(OrderState::Queued)
+        );
+        assert_eq!(
+            OrderState::Queued.next_state(OrderState::Open),
+            Some(OrderState::Open)
+        );
+        assert_eq!(
+            OrderState::Queued.next_state(OrderState::Failed),
+            Some(OrderState::Failed)
+        );
+        assert_eq!(OrderState::Queued.next_state(OrderState::Initial), None);
+        assert_eq!(OrderState::Open.next_state(OrderState::Queued), None);
+    }
+
     #[test]
     fn given_several_orders_when_fetching_orders_for_ui_only_relevant_orders_are_loaded() {
         let mut connection = SqliteConnection::establish(":memory:").unwrap();
@@ -1602,6 +1767,8 @@ pub mod test {
                 order_expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
                 stable: false,
+                reduce_only: false,
+                position_id: None,
                 failure_reason: None,
             }
             .into(),
@@ -1626,6 +1793,8 @@ pub mod test {
                 order_expiry_timestamp,
                 reason: crate::trade::order::OrderReason::Manual,
                 stable: false,
+                reduce_only: false,
+                position_id: None,
                 failure_reason: None,
             }
             .into(),
@@ -1700,6 +1869,9 @@ pub mod test {
             description: description.clone(),
             invoice: invoice.clone(),
             funding_txid: None,
+            expiry_timestamp: Some(3700),
+            payee_pubkey: None,
+            failure_reason: None,
         };
 
         PaymentInsertable::insert(payment, &mut connection).unwrap();
@@ -1718,7 +1890,10 @@ pub mod test {
                 updated_at: 200,
                 description: "payment2".to_string(),
                 invoice: Some("invoice2".to_string()),
-                funding_txid: None
+                funding_txid: None,
+                expiry_timestamp: None,
+                payee_pubkey: None,
+                failure_reason: None,
             },
             &mut connection,
         )
@@ -1742,17 +1917,21 @@ pub mod test {
             description,
             invoice,
             funding_txid: None,
+            expiry_timestamp: Some(3700),
+            payee_pubkey: None,
+            failure_reason: None,
         };
 
         assert_eq!(expected_payment, loaded_payment);
 
         // Verify that we can update the payment
 
-        let new_htlc_status = HtlcStatus::Succeeded;
+        let new_htlc_status = HtlcStatus::Failed;
         let preimage = Some("preimage".to_string());
         let amount_msat = Some(1_000_000);
         let fee_msat = Some(150);
         let secret = Some("secret".to_string());
+        let failure_reason = Some("RetriesExhausted".to_string());
 
         let updated_at = PaymentInsertable::update(
             payment_hash.to_string(),
@@ -1762,6 +1941,7 @@ pub mod test {
             preimage.clone(),
             secret.clone(),
             None,
+            failure_reason.clone(),
             &mut connection,
         )
         .unwrap();
@@ -1776,6 +1956,7 @@ pub mod test {
             amount_msat,
             fee_msat,
             updated_at,
+            failure_reason,
             ..expected_payment
         };
 
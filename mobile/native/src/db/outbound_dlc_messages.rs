@@ -0,0 +1,82 @@
+use crate::db::dlc_messages::MessageType;
+use crate::schema::dlc_messages;
+use crate::schema::outbound_dlc_messages;
+use anyhow::Result;
+use bitcoin::secp256k1::PublicKey;
+use diesel::ExpressionMethods;
+use diesel::JoinOnDsl;
+use diesel::QueryDsl;
+use diesel::QueryResult;
+use diesel::RunQueryDsl;
+use diesel::SqliteConnection;
+use ln_dlc_node::dlc_message::SerializedDlcMessage;
+use time::OffsetDateTime;
+
+/// A queue of DLC messages sent to our peers, which have not been acknowledged yet.
+///
+/// The DLC protocol does not acknowledge messages explicitly. We consider a message acknowledged
+/// as soon as we receive a new message from the same peer, as the peer only continues with the
+/// protocol once it has processed our message.
+pub(crate) struct OutboundDlcMessage;
+
+impl OutboundDlcMessage {
+    /// Adds the message to the queue of unacknowledged messages to `peer_id`. Enqueuing the same
+    /// message twice has no effect.
+    pub(crate) fn enqueue(
+        conn: &mut SqliteConnection,
+        peer_id: &PublicKey,
+        sdm: SerializedDlcMessage,
+    ) -> Result<()> {
+        diesel::insert_into(outbound_dlc_messages::table)
+            .values((
+                outbound_dlc_messages::message_hash.eq(sdm.generate_hash().to_string()),
+                outbound_dlc_messages::peer_id.eq(peer_id.to_string()),
+                outbound_dlc_messages::message.eq(sdm.message),
+                outbound_dlc_messages::timestamp.eq(OffsetDateTime::now_utc().unix_timestamp()),
+            ))
+            .on_conflict(outbound_dlc_messages::message_hash)
+            .do_nothing()
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// All unacknowledged messages to `peer_id`, in the order in which they were sent.
+    pub(crate) fn get_unacknowledged(
+        conn: &mut SqliteConnection,
+        peer_id: &PublicKey,
+    ) -> QueryResult<Vec<SerializedDlcMessage>> {
+        let outbound_dlc_messages = outbound_dlc_messages::table
+            .inner_join(
+                dlc_messages::table
+                    .on(dlc_messages::message_hash.eq(outbound_dlc_messages::message_hash)),
+            )
+            .filter(outbound_dlc_messages::peer_id.eq(peer_id.to_string()))
+            .order_by(outbound_dlc_messages::id.asc())
+            .select((dlc_messages::message_type, outbound_dlc_messages::message))
+            .load::<(MessageType, String)>(conn)?;
+
+        let serialized_dlc_messages = outbound_dlc_messages
+            .into_iter()
+            .map(|(message_type, message)| SerializedDlcMessage {
+                message,
+                message_type: ln_dlc_node::dlc_message::DlcMessageType::from(message_type),
+            })
+            .collect();
+
+        Ok(serialized_dlc_messages)
+    }
+
+    /// Removes all messages to `peer_id` from the queue.
+    pub(crate) fn acknowledge(conn: &mut SqliteConnection, peer_id: &PublicKey) -> Result<()> {
+        let acknowledged = diesel::delete(outbound_dlc_messages::table)
+            .filter(outbound_dlc_messages::peer_id.eq(peer_id.to_string()))
+            .execute(conn)?;
+
+        if acknowledged > 0 {
+            tracing::debug!(%peer_id, acknowledged, "Acknowledged outbound dlc messages");
+        }
+
+        Ok(())
+    }
+}
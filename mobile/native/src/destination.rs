@@ -1,4 +1,5 @@
 use crate::api::Destination;
+use crate::lnurl;
 use anyhow::anyhow;
 use anyhow::ensure;
 use anyhow::Context;
@@ -13,12 +14,30 @@ use std::time::Duration;
 use std::time::SystemTime;
 
 pub fn decode_destination(destination: String) -> Result<Destination> {
+    if lnurl::is_lightning_address(&destination) {
+        return decode_lightning_address(&destination);
+    }
+
     decode_bip21(&destination)
         .or(decode_invoice(&destination))
         .or(decode_address(destination))
         .context("Failed to parse destination as Bolt11 invoice, Bip21 URI, or on chain address")
 }
 
+fn decode_lightning_address(request: &str) -> Result<Destination> {
+    let runtime = crate::state::get_or_create_tokio_runtime()?;
+    let pay = runtime.block_on(lnurl::fetch_pay_request(request))?;
+
+    Ok(Destination::LightningAddress {
+        address: lnurl::strip_lightning_prefix(request).to_string(),
+        description: lnurl::plain_text_description(&pay.metadata).unwrap_or_default(),
+        // Round inwards, so that any amount in sats within the bounds can be paid.
+        min_sendable_sats: (pay.min_sendable + 999) / 1000,
+        max_sendable_sats: pay.max_sendable / 1000,
+        comment_allowed: pay.comment_allowed,
+    })
+}
+
 fn decode_bip21(request: &str) -> Result<Destination> {
     let uri: bip21::Uri<'_, bip21::NoExtras> = request
         .try_into()
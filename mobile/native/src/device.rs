@@ -0,0 +1,108 @@
+use crate::config;
+use anyhow::Context;
+use anyhow::Result;
+use commons::Device;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Where the registration of this device is kept, relative to the data dir of the network.
+const DEVICE_FILE: &str = "device.json";
+
+/// Whether another device has taken over the session of our node ID, see [`is_superseded`].
+static SUPERSEDED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize)]
+struct DeviceRegistration {
+    id: String,
+    registered_at: i64,
+    /// Set once the coordinator has told us that another device is using our node ID.
+    superseded: bool,
+}
+
+/// Registers this device as a new user of the seed, e.g. after the seed has been restored.
+///
+/// The new registration takes over the session from any other device using the same seed once it
+/// authenticates with the coordinator.
+pub fn register_new_device() -> Result<()> {
+    let registration = DeviceRegistration {
+        id: Uuid::new_v4().to_string(),
+        registered_at: OffsetDateTime::now_utc().unix_timestamp(),
+        superseded: false,
+    };
+    persist(&registration)?;
+
+    SUPERSEDED.store(false, Ordering::Relaxed);
+
+    tracing::info!(device_id = registration.id, "Registered new device");
+
+    Ok(())
+}
+
+/// The device as it is presented to the coordinator.
+///
+/// Devices which have been set up before devices were registered are registered on the fly.
+pub fn get_device() -> Result<Device> {
+    let registration = match load()? {
+        Some(registration) => registration,
+        None => {
+            register_new_device()?;
+            load()?.context("Missing device registration")?
+        }
+    };
+
+    SUPERSEDED.store(registration.superseded, Ordering::Relaxed);
+
+    Ok(Device {
+        id: registration.id,
+        registered_at: registration.registered_at,
+    })
+}
+
+/// Remember that another device has taken over the session of our node ID.
+pub fn mark_superseded() -> Result<()> {
+    SUPERSEDED.store(true, Ordering::Relaxed);
+
+    let mut registration = load()?.context("Missing device registration")?;
+    registration.superseded = true;
+    persist(&registration)
+}
+
+/// Whether another device is using our node ID.
+///
+/// If so, this device must not persist its channel state anymore, neither locally nor in the
+/// backup, as it would otherwise corrupt the state of the other device. The app has to be
+/// restored from the seed again to take the session back.
+pub fn is_superseded() -> bool {
+    SUPERSEDED.load(Ordering::Relaxed)
+}
+
+fn load() -> Result<Option<DeviceRegistration>> {
+    let path = device_file();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let registration = fs::read(&path)
+        .with_context(|| format!("Failed to read device registration from {}", path.display()))?;
+
+    Ok(Some(serde_json::from_slice(&registration)?))
+}
+
+fn persist(registration: &DeviceRegistration) -> Result<()> {
+    let path = device_file();
+    fs::create_dir_all(path.parent().expect("parent"))?;
+    fs::write(&path, serde_json::to_vec(registration)?)
+        .with_context(|| format!("Failed to write device registration to {}", path.display()))
+}
+
+fn device_file() -> PathBuf {
+    PathBuf::from(config::get_data_dir())
+        .join(config::get_network().to_string())
+        .join(DEVICE_FILE)
+}
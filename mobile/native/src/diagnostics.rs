@@ -0,0 +1,156 @@
+use crate::config;
+use crate::db;
+use crate::db::dlc_messages::DlcMessage;
+use crate::log_file;
+use crate::state;
+use anyhow::Context;
+use anyhow::Result;
+use serde_json::json;
+use serde_json::Value;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use time::OffsetDateTime;
+use zip::write::FileOptions;
+use zip::CompressionMethod;
+use zip::ZipWriter;
+
+const DIAGNOSTICS_DIR: &str = "diagnostics";
+
+/// How many of the latest DLC messages are included in the diagnostics.
+const DLC_MESSAGES_LIMIT: i64 = 100;
+
+/// Writes a zip with everything needed to investigate an issue of the user, and returns its path.
+///
+/// The bundle contains the recent logs, the node info, a summary of the state of the channels,
+/// the metadata of the latest DLC messages and the config. The contents of the DLC messages and
+/// any credentials in the config are left out.
+///
+/// Only the latest bundle is kept.
+pub fn export() -> Result<String> {
+    let dir = Path::new(&config::get_data_dir()).join(DIAGNOSTICS_DIR);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).context("Failed to remove previous diagnostics")?;
+    }
+    fs::create_dir_all(&dir).context("Failed to create diagnostics dir")?;
+
+    let created_at = OffsetDateTime::now_utc();
+    let path = dir.join(format!(
+        "10101-diagnostics-{}.zip",
+        created_at.unix_timestamp()
+    ));
+
+    let file = File::create(&path)
+        .with_context(|| format!("Failed to create diagnostics at {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // A section which can't be collected, e.g. because the node is not running, should not keep
+    // the user from sending the others.
+    let sections: [(&str, fn() -> Result<Value>); 4] = [
+        ("node.json", node_info),
+        ("channels.json", channels),
+        ("dlc_messages.json", dlc_messages),
+        ("config.json", || Ok(config::get_redacted_config())),
+    ];
+    for (name, collect) in sections {
+        let section = collect().unwrap_or_else(|e| json!({ "error": format!("{e:#}") }));
+
+        zip.start_file(name, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&section)?)?;
+    }
+
+    for log_file in log_file::files() {
+        let name = log_file_name(&log_file)?;
+        let logs = fs::read(&log_file)
+            .with_context(|| format!("Failed to read log file {}", log_file.display()))?;
+
+        zip.start_file(format!("logs/{name}"), options)?;
+        zip.write_all(&logs)?;
+    }
+
+    zip.finish()?;
+
+    tracing::info!(path = %path.display(), "Exported diagnostics");
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn node_info() -> Result<Value> {
+    let node = state::try_get_node().context("Node is not running")?;
+
+    Ok(json!({
+        "node_id": node.inner.info.pubkey.to_string(),
+        "network": config::get_network().to_string(),
+        "created_at": OffsetDateTime::now_utc().unix_timestamp(),
+    }))
+}
+
+fn channels() -> Result<Value> {
+    let node = state::try_get_node().context("Node is not running")?;
+
+    let lightning_channels = node
+        .inner
+        .channel_manager
+        .list_channels()
+        .iter()
+        .map(|channel| {
+            json!({
+                "channel_id": hex::encode(channel.channel_id.0),
+                "counterparty": channel.counterparty.node_id.to_string(),
+                "value_sats": channel.channel_value_satoshis,
+                "outbound_capacity_msat": channel.outbound_capacity_msat,
+                "inbound_capacity_msat": channel.inbound_capacity_msat,
+                "is_channel_ready": channel.is_channel_ready,
+                "is_usable": channel.is_usable,
+                "confirmations": channel.confirmations,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let dlc_channels = node
+        .inner
+        .list_signed_dlc_channels()?
+        .iter()
+        .map(|channel| {
+            json!({
+                "channel_id": hex::encode(channel.channel_id),
+                "counterparty": channel.counter_party.to_string(),
+                "state": ln_dlc_node::node::signed_channel_state_name(channel),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "lightning_channels": lightning_channels,
+        "dlc_channels": dlc_channels,
+    }))
+}
+
+/// Only the metadata of the DLC messages is included, as they contain the signatures of the user.
+fn dlc_messages() -> Result<Value> {
+    let mut conn = db::connection()?;
+    let messages = DlcMessage::get_latest(&mut conn, DLC_MESSAGES_LIMIT)?
+        .into_iter()
+        .map(|message| {
+            json!({
+                "message_hash": message.message_hash,
+                "inbound": message.inbound,
+                "peer_id": message.peer_id,
+                "message_type": format!("{:?}", message.message_type),
+                "timestamp": message.timestamp,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Value::Array(messages))
+}
+
+fn log_file_name(path: &Path) -> Result<String> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("Invalid log file {}", path.display()))?;
+
+    Ok(name.to_string_lossy().to_string())
+}
@@ -3,6 +3,8 @@ use crate::event;
 use crate::event::BackgroundTask;
 use crate::event::EventInternal;
 use crate::event::TaskStatus;
+use crate::ln_dlc::interceptor;
+use crate::ln_dlc::interceptor::MessageDirection;
 use crate::ln_dlc::node::NodeStorage;
 use crate::storage::TenTenOneNodeStorage;
 use anyhow::Result;
@@ -11,6 +13,7 @@ use dlc_messages::Message;
 use ln_dlc_node::dlc_message::DlcMessage;
 use ln_dlc_node::dlc_message::SerializedDlcMessage;
 use ln_dlc_node::node::dlc_channel::send_dlc_message;
+use ln_dlc_node::node::dlc_message_name;
 use ln_dlc_node::node::event::NodeEvent;
 use ln_dlc_node::node::rust_dlc_manager::channel::offered_channel::OfferedChannel;
 use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannel;
@@ -25,7 +28,8 @@ use tokio::sync::broadcast::error::RecvError;
 /// processed. It's main purpose is to ensure the following.
 ///
 /// 1. Mark all received inbound messages as processed.
-/// 2. Save the last outbound dlc message, so it can be resend on the next reconnect.
+/// 2. Queue outbound dlc messages, so that the unacknowledged ones can be resend on the next
+///    reconnect.
 /// 3. Check if a receive message has already been processed and if so inform to skip the message.
 
 #[derive(Clone)]
@@ -54,6 +58,8 @@ pub async fn handle_dlc_messages(
                     tracing::error!(peer=%peer, "Failed to process end dlc message event. {e:#}");
                 }
             }
+            Ok(NodeEvent::SyncProgress(_)) => {} // ignored
+            Ok(NodeEvent::ChannelOpenStatus { .. }) => {} // ignored
             Err(RecvError::Lagged(skipped)) => {
                 tracing::warn!("Skipped {skipped} messages");
             }
@@ -73,18 +79,13 @@ impl DlcHandler {
         let outbound_msg = DlcMessage::new(peer, serialized_outbound_message.clone(), false)?;
 
         db::dlc_messages::DlcMessage::insert(&mut conn, outbound_msg)?;
-        db::last_outbound_dlc_messages::LastOutboundDlcMessage::upsert(
+        db::outbound_dlc_messages::OutboundDlcMessage::enqueue(
             &mut conn,
             &peer,
             serialized_outbound_message,
         )?;
 
-        send_dlc_message(
-            &self.node.dlc_message_handler,
-            &self.node.peer_manager,
-            peer,
-            msg,
-        );
+        self.deliver(peer, msg);
 
         Ok(())
     }
@@ -198,23 +199,34 @@ impl DlcHandler {
         }
 
         let mut conn = db::connection()?;
-        let last_outbound_serialized_dlc_message =
-            db::last_outbound_dlc_messages::LastOutboundDlcMessage::get(&mut conn, &peer)?;
-
-        if let Some(last_outbound_serialized_dlc_message) = last_outbound_serialized_dlc_message {
-            tracing::debug!(%peer, ?last_outbound_serialized_dlc_message.message_type, "Sending last dlc message");
-
-            let message = Message::try_from(&last_outbound_serialized_dlc_message)?;
-            send_dlc_message(
-                &self.node.dlc_message_handler,
-                &self.node.peer_manager,
-                peer,
-                message,
-            );
-        } else {
-            tracing::debug!(%peer, "No last dlc message found. Nothing todo.");
+        let unacknowledged_messages =
+            db::outbound_dlc_messages::OutboundDlcMessage::get_unacknowledged(&mut conn, &peer)?;
+
+        if unacknowledged_messages.is_empty() {
+            tracing::debug!(%peer, "No unacknowledged dlc messages found. Nothing todo.");
+        }
+
+        for serialized_message in unacknowledged_messages {
+            tracing::debug!(%peer, ?serialized_message.message_type, "Resending unacknowledged dlc message");
+
+            let message = Message::try_from(&serialized_message)?;
+            self.deliver(peer, message);
         }
 
         Ok(())
     }
+
+    fn deliver(&self, peer: PublicKey, msg: Message) {
+        let kind = dlc_message_name(&msg);
+        if !interceptor::should_deliver(peer, MessageDirection::Outbound, &kind) {
+            return;
+        }
+
+        send_dlc_message(
+            &self.node.dlc_message_handler,
+            &self.node.peer_manager,
+            peer,
+            msg,
+        );
+    }
 }
@@ -0,0 +1,195 @@
+use crate::lnurl::LnUrlPayError;
+use crate::trade::order::handler::SubmitOrderError;
+use anyhow::anyhow;
+use trade::validation::OrderValidationError;
+
+/// An error which the app handles specifically, e.g. by showing a localized message or by
+/// retrying.
+#[derive(thiserror::Error, Debug)]
+pub enum AppError {
+    #[error("Insufficient funds: {0}")]
+    InsufficientFunds(String),
+    #[error("Coordinator is unreachable: {0}")]
+    CoordinatorUnreachable(String),
+    #[error("Order rejected: {0}")]
+    OrderRejected(String),
+    #[error("Channel is busy: {0}")]
+    ChannelBusy(String),
+    #[error("The keystore is locked")]
+    KeystoreLocked,
+    #[error("Wrong PIN")]
+    WrongPin,
+    #[error("Too many wrong PINs, try again in {retry_in_secs} seconds")]
+    TooManyPinAttempts { retry_in_secs: u64 },
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+}
+
+/// Identifies the kind of an error returned by the API.
+///
+/// Errors are passed to Flutter as strings, so the code is put in front of the message as
+/// `[CODE] message`, see [`to_ffi`] and [`decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InsufficientFunds,
+    CoordinatorUnreachable,
+    OrderRejected,
+    ChannelBusy,
+    KeystoreLocked,
+    WrongPin,
+    TooManyPinAttempts,
+    InvalidInput,
+    /// Any error without a code.
+    Unknown,
+}
+
+impl ErrorCode {
+    const ALL: [ErrorCode; 8] = [
+        ErrorCode::InsufficientFunds,
+        ErrorCode::CoordinatorUnreachable,
+        ErrorCode::OrderRejected,
+        ErrorCode::ChannelBusy,
+        ErrorCode::KeystoreLocked,
+        ErrorCode::WrongPin,
+        ErrorCode::TooManyPinAttempts,
+        ErrorCode::InvalidInput,
+    ];
+
+    /// The code as it is sent to Flutter. Must never be changed, as the app relies on it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InsufficientFunds => "INSUFFICIENT_FUNDS",
+            ErrorCode::CoordinatorUnreachable => "COORDINATOR_UNREACHABLE",
+            ErrorCode::OrderRejected => "ORDER_REJECTED",
+            ErrorCode::ChannelBusy => "CHANNEL_BUSY",
+            ErrorCode::KeystoreLocked => "KEYSTORE_LOCKED",
+            ErrorCode::WrongPin => "WRONG_PIN",
+            ErrorCode::TooManyPinAttempts => "TOO_MANY_PIN_ATTEMPTS",
+            ErrorCode::InvalidInput => "INVALID_INPUT",
+            ErrorCode::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::InsufficientFunds(_) => ErrorCode::InsufficientFunds,
+            AppError::CoordinatorUnreachable(_) => ErrorCode::CoordinatorUnreachable,
+            AppError::OrderRejected(_) => ErrorCode::OrderRejected,
+            AppError::ChannelBusy(_) => ErrorCode::ChannelBusy,
+            AppError::KeystoreLocked => ErrorCode::KeystoreLocked,
+            AppError::WrongPin => ErrorCode::WrongPin,
+            AppError::TooManyPinAttempts { .. } => ErrorCode::TooManyPinAttempts,
+            AppError::InvalidInput(_) => ErrorCode::InvalidInput,
+        }
+    }
+}
+
+/// Determines the code of `error` from the first error in its chain which has a known kind.
+pub fn classify(error: &anyhow::Error) -> ErrorCode {
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<AppError>() {
+            return e.code();
+        }
+
+        if let Some(e) = cause.downcast_ref::<SubmitOrderError>() {
+            return match e {
+                SubmitOrderError::UnconfirmedChannel { .. }
+                | SubmitOrderError::OtherOrderInFilling { .. }
+                | SubmitOrderError::OtherOrderQueued { .. } => ErrorCode::ChannelBusy,
+                SubmitOrderError::Orderbook(e) => match classify(e) {
+                    ErrorCode::Unknown => ErrorCode::OrderRejected,
+                    code => code,
+                },
+                SubmitOrderError::PaperTrading(e) => classify(e),
+                SubmitOrderError::Storage(_) => ErrorCode::Unknown,
+            };
+        }
+
+        if cause.downcast_ref::<OrderValidationError>().is_some() {
+            return ErrorCode::InvalidInput;
+        }
+
+        if let Some(e) = cause.downcast_ref::<LnUrlPayError>() {
+            return match e {
+                LnUrlPayError::InvalidRequest(_)
+                | LnUrlPayError::NotAPayRequest
+                | LnUrlPayError::AmountOutOfRange { .. }
+                | LnUrlPayError::CommentTooLong { .. } => ErrorCode::InvalidInput,
+                LnUrlPayError::Payment(e) => classify(e),
+                _ => ErrorCode::Unknown,
+            };
+        }
+
+        if let Some(bdk::Error::InsufficientFunds { .. }) = cause.downcast_ref::<bdk::Error>() {
+            return ErrorCode::InsufficientFunds;
+        }
+
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if e.is_connect() || e.is_timeout() {
+                return ErrorCode::CoordinatorUnreachable;
+            }
+        }
+    }
+
+    ErrorCode::Unknown
+}
+
+/// Converts `error` into the error passed to Flutter, with its code in front of the message.
+pub fn to_ffi(error: impl Into<anyhow::Error>) -> anyhow::Error {
+    let error = error.into();
+    match classify(&error) {
+        ErrorCode::Unknown => error,
+        code => anyhow!("[{}] {error:#}", code.as_str()),
+    }
+}
+
+/// Splits an error message received by Flutter into its code and the message itself.
+pub fn decode(message: &str) -> (ErrorCode, String) {
+    let coded = message
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "));
+
+    if let Some((code, rest)) = coded {
+        if let Some(code) = ErrorCode::ALL.into_iter().find(|c| c.as_str() == code) {
+            return (code, rest.to_string());
+        }
+    }
+
+    (ErrorCode::Unknown, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn code_survives_the_bridge() {
+        let error = Err::<(), _>(AppError::ChannelBusy("Trade in progress".to_string()))
+            .context("Failed to close channel")
+            .unwrap_err();
+
+        let message = to_ffi(error).to_string();
+        let (code, message) = decode(&message);
+
+        assert_eq!(code, ErrorCode::ChannelBusy);
+        assert_eq!(
+            message,
+            "Failed to close channel: Channel is busy: Trade in progress"
+        );
+    }
+
+    #[test]
+    fn uncoded_error_is_unknown() {
+        let error = anyhow!("[Something] went wrong");
+
+        let message = to_ffi(error).to_string();
+
+        assert_eq!(
+            decode(&message),
+            (ErrorCode::Unknown, "[Something] went wrong".to_string())
+        );
+    }
+}
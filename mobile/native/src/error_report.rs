@@ -0,0 +1,333 @@
+//! Reports errors and panics to the coordinator, if the user opted in, so that we learn how the
+//! app fails in the wild.
+//!
+//! Errors logged by the app are queued and uploaded in batches by a periodic task. A panic usually
+//! ends the app before the next upload, hence the queue is written to a file on panic and
+//! uploaded after the next start.
+//!
+//! Everything which looks like a key, an invoice or an address is removed from the reports before
+//! they are queued.
+
+use crate::commons::reqwest_client;
+use crate::config;
+use crate::logger::Visitor;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use commons::ErrorReport;
+use commons::ErrorReportKind;
+use commons::ErrorReports;
+use commons::MAX_ERROR_REPORTS;
+use commons::MAX_ERROR_REPORT_MESSAGE_LEN;
+use parking_lot::const_mutex;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::panic;
+use std::panic::PanicInfo;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::Level;
+use tracing_subscriber::layer::Context as LayerContext;
+use tracing_subscriber::Layer;
+
+pub const UPLOAD_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The reports which could not be uploaded before a panic, relative to the data dir.
+const PENDING_REPORTS_FILE: &str = "error_reports.json";
+
+/// Words of at least this length containing a digit are removed from the reports. This covers
+/// keys, signatures, hashes, invoices and addresses, while keeping the error messages readable.
+const MIN_REDACTED_WORD_LEN: usize = 26;
+
+const REDACTED: &str = "<redacted>";
+
+/// The reporter, if the user opted in.
+static REPORTER: Mutex<Option<Reporter>> = const_mutex(None);
+
+static PENDING_REPORTS_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+struct Reporter {
+    app_version: String,
+    reports: Vec<ErrorReport>,
+}
+
+/// Start or stop reporting errors. Reports which have not been uploaded yet are dropped when
+/// reporting is stopped.
+pub fn set_enabled(enabled: bool, app_version: String) {
+    let mut reporter = REPORTER.lock();
+    match (enabled, reporter.as_mut()) {
+        (true, Some(reporter)) => reporter.app_version = app_version,
+        (true, None) => {
+            *reporter = Some(Reporter {
+                app_version,
+                reports: vec![],
+            })
+        }
+        (false, _) => *reporter = None,
+    }
+}
+
+pub fn is_enabled() -> bool {
+    REPORTER.lock().is_some()
+}
+
+/// Report panics, and queue the reports which were pending when the app last panicked.
+pub fn init(data_dir: &Path) -> Result<()> {
+    let path = data_dir.join(PENDING_REPORTS_FILE);
+
+    // Panics are reported before the previous hook runs, as it may abort the process.
+    if PENDING_REPORTS_PATH.set(path.clone()).is_ok() {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            report_panic(info);
+            previous_hook(info);
+        }));
+    }
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let pending = fs::read(&path).context("Failed to read pending error reports")?;
+    fs::remove_file(&path).context("Failed to remove pending error reports")?;
+
+    let pending = serde_json::from_slice::<Vec<ErrorReport>>(&pending)
+        .context("Failed to parse pending error reports")?;
+    if let Some(reporter) = REPORTER.lock().as_mut() {
+        for report in pending {
+            reporter.push(report);
+        }
+    }
+
+    Ok(())
+}
+
+/// Upload the queued reports to the coordinator.
+///
+/// If the upload fails, the reports are queued again to be retried with the next upload.
+pub async fn upload() -> Result<()> {
+    let reports = match REPORTER.lock().as_mut() {
+        Some(reporter) if !reporter.reports.is_empty() => ErrorReports {
+            app_version: reporter.app_version.clone(),
+            reports: std::mem::take(&mut reporter.reports),
+        },
+        _ => return Ok(()),
+    };
+
+    if let Err(e) = send(&reports).await {
+        if let Some(reporter) = REPORTER.lock().as_mut() {
+            let newer = std::mem::replace(&mut reporter.reports, reports.reports);
+            for report in newer {
+                reporter.push(report);
+            }
+        }
+
+        return Err(e);
+    }
+
+    tracing::debug!(count = reports.reports.len(), "Uploaded error reports");
+
+    Ok(())
+}
+
+async fn send(reports: &ErrorReports) -> Result<()> {
+    let response = reqwest_client()
+        .post(format!(
+            "http://{}/api/v1/error-reports",
+            config::get_http_endpoint()
+        ))
+        .json(reports)
+        .send()
+        .await
+        .context("Failed to upload error reports")?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        bail!("Failed to upload error reports: {status} {text}");
+    }
+
+    Ok(())
+}
+
+/// Queues every error logged by the app, if the user opted in to report them.
+pub struct ErrorReportLayer;
+
+impl<S> Layer<S> for ErrorReportLayer
+where
+    S: tracing::Subscriber,
+{
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: LayerContext<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+
+        let mut fields = BTreeMap::new();
+        event.record(&mut Visitor(&mut fields));
+
+        let mut message = fields.remove("message").unwrap_or_default();
+        for (name, value) in fields.iter().filter(|(name, _)| !name.starts_with("log.")) {
+            message.push_str(&format!(", {name}: {value}"));
+        }
+
+        report(
+            ErrorReportKind::Error,
+            event.metadata().target().to_string(),
+            &message,
+        );
+    }
+}
+
+fn report_panic(info: &PanicInfo) {
+    // The lock may be held by the panicking thread.
+    let mut reporter = match REPORTER.try_lock() {
+        Some(reporter) => reporter,
+        None => return,
+    };
+    let reporter = match reporter.as_mut() {
+        Some(reporter) => reporter,
+        None => return,
+    };
+
+    let location = info
+        .location()
+        .map(|location| format!("{}:{}", location.file(), location.line()))
+        .unwrap_or_default();
+    reporter.push(new_report(
+        ErrorReportKind::Panic,
+        location,
+        &info.to_string(),
+    ));
+
+    if let (Some(path), Ok(reports)) = (
+        PENDING_REPORTS_PATH.get(),
+        serde_json::to_vec(&reporter.reports),
+    ) {
+        let _ = fs::write(path, reports);
+    }
+}
+
+fn report(kind: ErrorReportKind, target: String, message: &str) {
+    if let Some(reporter) = REPORTER.lock().as_mut() {
+        reporter.push(new_report(kind, target, message));
+    }
+}
+
+fn new_report(kind: ErrorReportKind, target: String, message: &str) -> ErrorReport {
+    ErrorReport {
+        kind,
+        timestamp: OffsetDateTime::now_utc(),
+        target,
+        message: scrub(message),
+        count: 1,
+    }
+}
+
+impl Reporter {
+    /// Queue `report`, counting repetitions of the last report instead of queueing them again.
+    ///
+    /// Once the queue is full, the oldest reports are dropped.
+    fn push(&mut self, report: ErrorReport) {
+        if let Some(last) = self.reports.last_mut() {
+            if last.kind == report.kind
+                && last.target == report.target
+                && last.message == report.message
+            {
+                last.count = last.count.saturating_add(report.count);
+                return;
+            }
+        }
+
+        if self.reports.len() >= MAX_ERROR_REPORTS {
+            self.reports.remove(0);
+        }
+        self.reports.push(report);
+    }
+}
+
+/// Remove everything which looks like a key, an invoice or an address from `message`.
+fn scrub(message: &str) -> String {
+    let mut scrubbed = String::with_capacity(message.len());
+
+    let mut rest = message;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphanumeric()) {
+        scrubbed.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        if word.len() >= MIN_REDACTED_WORD_LEN && word.chars().any(|c| c.is_ascii_digit()) {
+            scrubbed.push_str(REDACTED);
+        } else {
+            scrubbed.push_str(word);
+        }
+
+        rest = &rest[end..];
+    }
+    scrubbed.push_str(rest);
+
+    scrubbed
+        .chars()
+        .take(MAX_ERROR_REPORT_MESSAGE_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_invoices_and_addresses_are_scrubbed() {
+        let message = "Failed to pay lnbcrt500u1pjw8dz9pp5xqz5dnhme4p8uy3j3kffwlmdrj0t5rl0hy \
+            to 02d5aa8fce495f6301b466594af056a46104dcdc6d735ec4793aa43108854cbd4a, \
+            change address bcrt1qxcrwhnf2j8a2gctr3ynhxk8j43msq2lqyv9zry";
+
+        assert_eq!(
+            scrub(message),
+            "Failed to pay <redacted> to <redacted>, change address <redacted>"
+        );
+    }
+
+    #[test]
+    fn error_messages_are_kept() {
+        let message = "Failed to process DLC message: Invalid state: \
+            SignedChannelStateMismatch, order 6b9b4c0e-3b5f-4a4e-9d1c-2f8e0f6a3c11";
+
+        assert_eq!(scrub(message), message);
+    }
+
+    #[test]
+    fn repeated_errors_are_counted() {
+        let mut reporter = Reporter {
+            app_version: "1.7.4".to_string(),
+            reports: vec![],
+        };
+        let error = |message: &str| ErrorReport {
+            kind: ErrorReportKind::Error,
+            timestamp: OffsetDateTime::now_utc(),
+            target: "native::ln_dlc".to_string(),
+            message: message.to_string(),
+            count: 1,
+        };
+
+        reporter.push(error("Failed to connect"));
+        reporter.push(error("Failed to connect"));
+        reporter.push(error("Failed to sync"));
+
+        assert_eq!(reporter.reports.len(), 2);
+        assert_eq!(reporter.reports[0].count, 2);
+
+        for i in 0..MAX_ERROR_REPORTS {
+            reporter.push(error(&format!("Error {i}")));
+        }
+
+        assert_eq!(reporter.reports.len(), MAX_ERROR_REPORTS);
+        assert_eq!(reporter.reports[0].message, "Error 0");
+    }
+}
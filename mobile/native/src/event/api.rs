@@ -6,12 +6,15 @@ use crate::event::EventInternal;
 use crate::event::EventType;
 use crate::health::ServiceUpdate;
 use crate::ln_dlc::ChannelStatus;
+use crate::lnurl;
+use crate::pending_action::api::PendingAction;
 use crate::trade::order::api::Order;
 use crate::trade::order::api::OrderReason;
 use crate::trade::position::api::Position;
 use core::convert::From;
 use flutter_rust_bridge::frb;
 use flutter_rust_bridge::StreamSink;
+use ln_dlc_node::node;
 use rust_decimal::prelude::ToPrimitive;
 use trade::ContractSymbol;
 
@@ -32,6 +35,16 @@ pub enum Event {
     PaymentSent,
     PaymentFailed,
     Authenticated(LspConfig),
+    SyncProgress(SyncProgress),
+    LnUrlProgress(LnUrlProgress),
+    RestoreProgress {
+        done: u64,
+        total: u64,
+    },
+    /// Another device has started using the seed. This device has to be restored from the seed
+    /// again before it can be used.
+    SessionSuperseded,
+    DlcOfferReceived(PendingAction),
 }
 
 #[frb]
@@ -47,6 +60,8 @@ pub enum BackgroundTask {
     RecoverDlc(TaskStatus),
     /// The coordinator wants to collaboratively close a ln channel with a stuck position.
     CollabRevert(TaskStatus),
+    /// The coordinator wants to collaboratively close the DLC channel.
+    CollaborativeClose(TaskStatus),
 }
 
 impl From<EventInternal> for Event {
@@ -66,8 +81,11 @@ impl From<EventInternal> for Event {
             EventInternal::PositionUpdateNotification(position) => {
                 Event::PositionUpdateNotification(position.into())
             }
-            EventInternal::PositionCloseNotification(contract_symbol) => {
-                Event::PositionClosedNotification(PositionClosed { contract_symbol })
+            EventInternal::PositionCloseNotification(position) => {
+                Event::PositionClosedNotification(PositionClosed {
+                    id: position.id.to_string(),
+                    contract_symbol: position.contract_symbol,
+                })
             }
             EventInternal::PriceUpdateNotification(prices) => {
                 let best_price = prices
@@ -94,6 +112,14 @@ impl From<EventInternal> for Event {
                 unreachable!("This internal event is not exposed to the UI")
             }
             EventInternal::Authenticated(lsp_config) => Event::Authenticated(lsp_config.into()),
+            EventInternal::SyncProgress(progress) => Event::SyncProgress(progress.into()),
+            EventInternal::LnUrlProgress(progress) => Event::LnUrlProgress(progress.into()),
+            EventInternal::RestoreProgress { done, total } => Event::RestoreProgress {
+                done: done as u64,
+                total: total as u64,
+            },
+            EventInternal::SessionSuperseded => Event::SessionSuperseded,
+            EventInternal::DlcOfferReceived(offer) => Event::DlcOfferReceived(offer.into()),
         }
     }
 }
@@ -103,8 +129,9 @@ impl From<EventInternal> for Event {
 /// The mirrored `ContractSymbol` does not get picked up correctly when using it directly as
 /// type in an enum variant, so we wrap it in a struct.
 #[frb]
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct PositionClosed {
+    pub id: String,
     pub contract_symbol: ContractSymbol,
 }
 
@@ -134,6 +161,11 @@ impl Subscriber for FlutterSubscriber {
             EventType::PaymentSent,
             EventType::PaymentFailed,
             EventType::Authenticated,
+            EventType::SyncProgress,
+            EventType::LnUrlProgress,
+            EventType::RestoreProgress,
+            EventType::SessionSuperseded,
+            EventType::DlcOfferReceived,
         ]
     }
 }
@@ -155,6 +187,9 @@ impl From<event::BackgroundTask> for BackgroundTask {
             event::BackgroundTask::CollabRevert(status) => {
                 BackgroundTask::CollabRevert(status.into())
             }
+            event::BackgroundTask::CollaborativeClose(status) => {
+                BackgroundTask::CollaborativeClose(status.into())
+            }
         }
     }
 }
@@ -177,6 +212,85 @@ impl From<event::TaskStatus> for TaskStatus {
     }
 }
 
+/// The progress of an on-chain wallet sync.
+#[frb]
+#[derive(Clone)]
+pub enum SyncProgress {
+    Started {
+        kind: SyncKind,
+    },
+    Scanning {
+        kind: SyncKind,
+        percent: f32,
+        message: Option<String>,
+    },
+    Finished {
+        kind: SyncKind,
+        height: u32,
+        scripts_scanned: u64,
+    },
+    UpToDate {
+        height: u32,
+    },
+    Cancelled {
+        kind: SyncKind,
+    },
+    Failed {
+        kind: SyncKind,
+        error: String,
+    },
+}
+
+#[frb]
+#[derive(Clone, Copy)]
+pub enum SyncKind {
+    /// Only the chain tip was checked for new blocks.
+    TipCheck,
+    /// The history of all the wallet scripts was rescanned.
+    Full,
+}
+
+impl From<node::SyncProgress> for SyncProgress {
+    fn from(value: node::SyncProgress) -> Self {
+        match value {
+            node::SyncProgress::Started { kind } => SyncProgress::Started { kind: kind.into() },
+            node::SyncProgress::Scanning {
+                kind,
+                percent,
+                message,
+            } => SyncProgress::Scanning {
+                kind: kind.into(),
+                percent,
+                message,
+            },
+            node::SyncProgress::Finished {
+                kind,
+                height,
+                scripts_scanned,
+            } => SyncProgress::Finished {
+                kind: kind.into(),
+                height,
+                scripts_scanned: scripts_scanned as u64,
+            },
+            node::SyncProgress::UpToDate { height } => SyncProgress::UpToDate { height },
+            node::SyncProgress::Cancelled { kind } => SyncProgress::Cancelled { kind: kind.into() },
+            node::SyncProgress::Failed { kind, error } => SyncProgress::Failed {
+                kind: kind.into(),
+                error,
+            },
+        }
+    }
+}
+
+impl From<node::SyncKind> for SyncKind {
+    fn from(value: node::SyncKind) -> Self {
+        match value {
+            node::SyncKind::TipCheck => SyncKind::TipCheck,
+            node::SyncKind::Full => SyncKind::Full,
+        }
+    }
+}
+
 /// The best bid and ask price for a contract.
 ///
 /// Best prices come from an orderbook. Contrary to the `Price` struct, we can have no price
@@ -200,3 +314,34 @@ impl From<commons::Price> for BestPrice {
         }
     }
 }
+
+/// The progress of paying to or withdrawing from an LNURL.
+#[frb]
+#[derive(Clone)]
+pub enum LnUrlProgress {
+    FetchingParameters,
+    RequestingInvoice { amount_sats: u64 },
+    Paying { amount_sats: u64 },
+    SubmittingInvoice { amount_sats: u64 },
+    WaitingForPayment { amount_sats: u64 },
+    Failed { reason: String },
+}
+
+impl From<lnurl::LnUrlProgress> for LnUrlProgress {
+    fn from(value: lnurl::LnUrlProgress) -> Self {
+        match value {
+            lnurl::LnUrlProgress::FetchingParameters => LnUrlProgress::FetchingParameters,
+            lnurl::LnUrlProgress::RequestingInvoice { amount_sats } => {
+                LnUrlProgress::RequestingInvoice { amount_sats }
+            }
+            lnurl::LnUrlProgress::Paying { amount_sats } => LnUrlProgress::Paying { amount_sats },
+            lnurl::LnUrlProgress::SubmittingInvoice { amount_sats } => {
+                LnUrlProgress::SubmittingInvoice { amount_sats }
+            }
+            lnurl::LnUrlProgress::WaitingForPayment { amount_sats } => {
+                LnUrlProgress::WaitingForPayment { amount_sats }
+            }
+            lnurl::LnUrlProgress::Failed { reason } => LnUrlProgress::Failed { reason },
+        }
+    }
+}
@@ -3,6 +3,8 @@ use crate::event::event_hub::get;
 use crate::event::subscriber::Subscriber;
 use crate::health::ServiceUpdate;
 use crate::ln_dlc::ChannelStatus;
+use crate::lnurl::LnUrlProgress;
+use crate::pending_action::PendingAction;
 use crate::trade::order::Order;
 use crate::trade::order::OrderReason;
 use crate::trade::position::Position;
@@ -11,9 +13,10 @@ use commons::Prices;
 use commons::TradeParams;
 use lightning::ln::ChannelId;
 use lightning::ln::PaymentHash;
+use ln_dlc_node::node::SyncProgress;
+use std::cell::RefCell;
 use std::fmt;
 use std::hash::Hash;
-use trade::ContractSymbol;
 
 mod event_hub;
 
@@ -24,8 +27,43 @@ pub fn subscribe(subscriber: impl Subscriber + 'static + Send + Sync + Clone) {
     get().subscribe(subscriber);
 }
 
+thread_local! {
+    /// The events published by the unit of work running on this thread, if any.
+    static STAGED_EVENTS: RefCell<Option<Vec<EventInternal>>> = RefCell::new(None);
+}
+
 pub fn publish(event: &EventInternal) {
-    get().publish(event);
+    let is_staged = STAGED_EVENTS.with(|staged_events| match staged_events.borrow_mut().as_mut() {
+        Some(staged_events) => {
+            staged_events.push(event.clone());
+            true
+        }
+        None => false,
+    });
+
+    if !is_staged {
+        get().publish(event);
+    }
+}
+
+/// Holds back all events published on this thread until [`publish_staged_events`] or
+/// [`discard_staged_events`] is called.
+pub(crate) fn stage_events() {
+    STAGED_EVENTS.with(|staged_events| *staged_events.borrow_mut() = Some(vec![]));
+}
+
+pub(crate) fn publish_staged_events() {
+    let staged_events = STAGED_EVENTS
+        .with(|staged_events| staged_events.borrow_mut().take())
+        .unwrap_or_default();
+
+    for event in staged_events.iter() {
+        publish(event);
+    }
+}
+
+pub(crate) fn discard_staged_events() {
+    STAGED_EVENTS.with(|staged_events| staged_events.borrow_mut().take());
 }
 
 #[derive(Clone, Debug)]
@@ -36,7 +74,7 @@ pub enum EventInternal {
     WalletInfoUpdateNotification(WalletInfo),
     OrderFilledWith(Box<TradeParams>),
     PositionUpdateNotification(Position),
-    PositionCloseNotification(ContractSymbol),
+    PositionCloseNotification(Position),
     PriceUpdateNotification(Prices),
     ChannelReady(ChannelId),
     PaymentClaimed(u64, PaymentHash),
@@ -47,6 +85,19 @@ pub enum EventInternal {
     Authenticated(LspConfig),
     BackgroundNotification(BackgroundTask),
     SpendableOutputs,
+    SyncProgress(SyncProgress),
+    LnUrlProgress(LnUrlProgress),
+    /// Another device has started using our seed, so this device does not persist its state
+    /// anymore.
+    SessionSuperseded,
+    /// The number of backed up values which have been restored so far, out of `total`.
+    RestoreProgress {
+        done: usize,
+        total: usize,
+    },
+    /// A DLC offer which does not match any action of the user. It is only accepted or rejected
+    /// once the user decides to.
+    DlcOfferReceived(PendingAction),
 }
 
 #[derive(Clone, Debug)]
@@ -55,6 +106,7 @@ pub enum BackgroundTask {
     Rollover(TaskStatus),
     CollabRevert(TaskStatus),
     RecoverDlc(TaskStatus),
+    CollaborativeClose(TaskStatus),
 }
 
 #[derive(Clone, Debug)]
@@ -84,6 +136,11 @@ impl fmt::Display for EventInternal {
             EventInternal::BackgroundNotification(_) => "BackgroundNotification",
             EventInternal::SpendableOutputs => "SpendableOutputs",
             EventInternal::Authenticated(_) => "Authenticated",
+            EventInternal::SyncProgress(_) => "SyncProgress",
+            EventInternal::LnUrlProgress(_) => "LnUrlProgress",
+            EventInternal::RestoreProgress { .. } => "RestoreProgress",
+            EventInternal::SessionSuperseded => "SessionSuperseded",
+            EventInternal::DlcOfferReceived(_) => "DlcOfferReceived",
         }
         .fmt(f)
     }
@@ -111,6 +168,11 @@ impl From<EventInternal> for EventType {
             EventInternal::BackgroundNotification(_) => EventType::BackgroundNotification,
             EventInternal::SpendableOutputs => EventType::SpendableOutputs,
             EventInternal::Authenticated(_) => EventType::Authenticated,
+            EventInternal::SyncProgress(_) => EventType::SyncProgress,
+            EventInternal::LnUrlProgress(_) => EventType::LnUrlProgress,
+            EventInternal::RestoreProgress { .. } => EventType::RestoreProgress,
+            EventInternal::SessionSuperseded => EventType::SessionSuperseded,
+            EventInternal::DlcOfferReceived(_) => EventType::DlcOfferReceived,
         }
     }
 }
@@ -134,4 +196,9 @@ pub enum EventType {
     BackgroundNotification,
     SpendableOutputs,
     Authenticated,
+    SyncProgress,
+    LnUrlProgress,
+    RestoreProgress,
+    SessionSuperseded,
+    DlcOfferReceived,
 }
@@ -0,0 +1,61 @@
+use crate::commons::reqwest_client;
+use crate::config;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use commons::FeeEstimates;
+use parking_lot::const_rwlock;
+use parking_lot::RwLock;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How long the [`FeeEstimates`] of the coordinator are reused before fetching them again.
+const FEE_ESTIMATES_TTL: Duration = Duration::from_secs(60);
+
+/// The latest [`FeeEstimates`] of the coordinator and when we fetched them.
+///
+/// The fee selection is recalculated on every change of the amount, which should not result in a
+/// request to the coordinator each time.
+static FEE_ESTIMATES: RwLock<Option<(Instant, FeeEstimates)>> = const_rwlock(None);
+
+/// Get the [`FeeEstimates`] of the coordinator, fetching them only if the cached ones are older
+/// than [`FEE_ESTIMATES_TTL`].
+pub async fn get_fee_estimates() -> Result<FeeEstimates> {
+    if let Some((fetched_at, estimates)) = FEE_ESTIMATES.read().as_ref() {
+        if fetched_at.elapsed() < FEE_ESTIMATES_TTL {
+            return Ok(estimates.clone());
+        }
+    }
+
+    let estimates = fetch_fee_estimates().await?;
+    *FEE_ESTIMATES.write() = Some((Instant::now(), estimates.clone()));
+
+    Ok(estimates)
+}
+
+async fn fetch_fee_estimates() -> Result<FeeEstimates> {
+    let client = reqwest_client();
+    let response = client
+        .get(format!(
+            "http://{}/api/v1/fee-estimates",
+            config::get_http_endpoint()
+        ))
+        .send()
+        .await
+        .context("Failed to fetch fee estimates from coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        bail!("Could not fetch fee estimates from coordinator: {response_text}");
+    }
+
+    response
+        .json()
+        .await
+        .context("Failed to parse fee estimates")
+}
@@ -1,12 +1,13 @@
 use crate::config;
 use crate::event;
 use crate::event::EventInternal;
+use crate::scheduler;
 use anyhow::Context;
 use anyhow::Result;
 use futures::future::RemoteHandle;
 use futures::FutureExt;
 use reqwest::StatusCode;
-use std::time::Duration;
+use std::sync::Arc;
 use tokio::runtime::Runtime;
 use tokio::sync::watch;
 
@@ -65,15 +66,18 @@ impl Health {
 
         let (coordinator_tx, coordinator_rx) = watch::channel(ServiceStatus::Unknown);
 
-        let check_coordinator = runtime
-            .spawn(check_health_endpoint(
-                config::coordinator_health_endpoint(),
-                coordinator_tx,
-                config::health_check_interval(),
-            ))
-            .remote_handle()
-            .1;
-        tasks.push(check_coordinator);
+        // The interval is read on every run, as it may be changed by the app config served by
+        // the coordinator after the app has started.
+        scheduler::spawn_periodic_with(
+            runtime,
+            "coordinator_health",
+            config::health_check_interval,
+            {
+                let endpoint = config::coordinator_health_endpoint();
+                let tx = Arc::new(coordinator_tx);
+                move || check_health_endpoint(endpoint.clone(), tx.clone())
+            },
+        );
         let coordinator_monitoring = runtime
             .spawn(publish_status_updates(Service::Coordinator, coordinator_rx))
             .remote_handle()
@@ -111,22 +115,20 @@ async fn publish_status_updates(service: Service, mut rx: watch::Receiver<Servic
     }
 }
 
-/// Periodically checks the health of a given service and updates the watch channel
+/// Checks the health of a given service and updates the watch channel
 async fn check_health_endpoint(
     endpoint: String,
-    tx: watch::Sender<ServiceStatus>,
-    interval: Duration,
-) {
-    loop {
-        let status = if send_request(&endpoint).await.is_ok() {
-            ServiceStatus::Online
-        } else {
-            ServiceStatus::Offline
-        };
-
-        tx.send(status).expect("Receiver not to be dropped");
-        tokio::time::sleep(interval).await;
-    }
+    tx: Arc<watch::Sender<ServiceStatus>>,
+) -> Result<()> {
+    let result = send_request(&endpoint).await;
+
+    let status = match result {
+        Ok(_) => ServiceStatus::Online,
+        Err(_) => ServiceStatus::Offline,
+    };
+    tx.send(status).expect("Receiver not to be dropped");
+
+    result.map(|_| ())
 }
 
 // Returns the status code of the health endpoint, returning an error if the request fails
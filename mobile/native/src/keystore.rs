@@ -0,0 +1,581 @@
+use crate::cipher::AesCipher;
+use crate::config;
+use crate::error::AppError;
+use crate::state;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use argon2::Algorithm;
+use argon2::Argon2;
+use argon2::Params;
+use argon2::Version;
+use bitcoin::secp256k1::rand;
+use bitcoin::secp256k1::rand::Rng;
+use bitcoin::secp256k1::SecretKey;
+use ln_dlc_node::seed::Bip39Seed;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use time::OffsetDateTime;
+
+/// The plaintext seed, relative to the seed dir of the network.
+const SEED_FILE: &str = "seed";
+
+/// The encrypted seed, relative to the seed dir of the network.
+const LOCKED_SEED_FILE: &str = "seed.locked";
+
+/// The memory in KiB and the number of passes of Argon2id, with which the key is derived from the
+/// PIN. Deriving the key being memory-hard makes guessing the PIN of a stolen seed file expensive,
+/// even on dedicated hardware.
+const PIN_KDF_MEMORY_KIB: u32 = 19 * 1024;
+const PIN_KDF_PASSES: u32 = 2;
+
+/// The number of wrong PINs which can be entered in a row before further attempts are delayed.
+const FREE_PIN_ATTEMPTS: u32 = 5;
+
+/// How long the user has to wait after the first delayed attempt, doubling with every further wrong
+/// PIN up to [`MAX_PIN_RETRY_DELAY`].
+const PIN_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+const MAX_PIN_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+const MIN_PIN_LENGTH: usize = 4;
+
+const DEFAULT_AUTO_LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+static SESSION: Mutex<Option<Session>> = parking_lot::const_mutex(None);
+
+/// The keystore while it is unlocked.
+struct Session {
+    data_key: SecretKey,
+    auto_lock_timeout: Duration,
+    last_activity: Instant,
+}
+
+/// The seed as it is kept on disk once it is protected by a PIN.
+///
+/// The seed is encrypted with a random data key, which is in turn encrypted with the key derived
+/// from the PIN and, if enabled, with the key released by the biometrics of the platform. That
+/// way, the PIN can be changed without re-encrypting the seed.
+#[derive(Serialize, Deserialize)]
+struct LockedSeed {
+    /// The salt of the key derived from the PIN.
+    salt: Vec<u8>,
+    pin_wrapped_key: Vec<u8>,
+    biometric_wrapped_key: Option<Vec<u8>>,
+    /// The entropy of the seed, encrypted with the data key.
+    encrypted_seed: Vec<u8>,
+    auto_lock_timeout_secs: u64,
+    /// The number of wrong PINs entered since the keystore has last been unlocked.
+    #[serde(default)]
+    failed_pin_attempts: u32,
+    /// When the last wrong PIN has been entered, as a unix timestamp.
+    #[serde(default)]
+    last_failed_pin_attempt: Option<i64>,
+}
+
+/// Whether the seed is protected by a PIN.
+pub fn is_protected() -> bool {
+    locked_seed_file().exists()
+}
+
+/// Whether the seed is protected by a PIN and the keystore has to be unlocked before the signing
+/// key can be used.
+pub fn is_locked() -> bool {
+    let session = SESSION.lock();
+    let unlocked = session
+        .as_ref()
+        .map(|session| session.last_activity.elapsed() < session.auto_lock_timeout)
+        .unwrap_or(false);
+
+    is_protected() && !unlocked
+}
+
+/// Protects the seed with `pin`, removing the plaintext seed from disk.
+///
+/// If the seed is already protected, the PIN is changed, which requires the keystore to be
+/// unlocked.
+pub fn set_pin(pin: &str) -> Result<()> {
+    ensure!(
+        pin.chars().count() >= MIN_PIN_LENGTH,
+        AppError::InvalidInput(format!(
+            "The PIN must have at least {MIN_PIN_LENGTH} characters"
+        ))
+    );
+
+    let salt = rand::thread_rng().gen::<[u8; 16]>().to_vec();
+
+    let locked_seed = match load()? {
+        Some(locked_seed) => {
+            let data_key = active_data_key()?;
+
+            LockedSeed {
+                pin_wrapped_key: wrap_key(&derive_pin_key(pin, &salt)?, &data_key)?,
+                salt,
+                ..locked_seed
+            }
+        }
+        None => {
+            let seed_file = seed_dir().join(SEED_FILE);
+            let entropy = fs::read(&seed_file)
+                .with_context(|| format!("Failed to read seed from {}", seed_file.display()))?;
+
+            let data_key = SecretKey::new(&mut rand::thread_rng());
+            let encrypted_seed = AesCipher::new(data_key).encrypt(entropy.clone())?;
+
+            ensure!(
+                AesCipher::new(data_key).decrypt(encrypted_seed.clone())? == entropy,
+                "Failed to encrypt seed"
+            );
+
+            let locked_seed = LockedSeed {
+                pin_wrapped_key: wrap_key(&derive_pin_key(pin, &salt)?, &data_key)?,
+                salt,
+                biometric_wrapped_key: None,
+                encrypted_seed,
+                auto_lock_timeout_secs: DEFAULT_AUTO_LOCK_TIMEOUT.as_secs(),
+                failed_pin_attempts: 0,
+                last_failed_pin_attempt: None,
+            };
+            persist(&locked_seed)?;
+
+            fs::remove_file(&seed_file).with_context(|| {
+                format!("Failed to remove plaintext seed {}", seed_file.display())
+            })?;
+
+            start_session(&locked_seed, data_key);
+
+            tracing::info!("Protected seed with PIN");
+
+            return Ok(());
+        }
+    };
+
+    persist(&locked_seed)?;
+
+    tracing::info!("Changed PIN");
+
+    Ok(())
+}
+
+/// Allows the keystore to be unlocked with `key`, which the platform only releases after
+/// authenticating the user with their biometrics.
+///
+/// The keystore has to be unlocked.
+pub fn enable_biometric_unlock(key: &[u8]) -> Result<()> {
+    let key = SecretKey::from_slice(key).context("Invalid biometric key")?;
+    let data_key = active_data_key()?;
+
+    let mut locked_seed = load()?.context("The seed is not protected by a PIN")?;
+    locked_seed.biometric_wrapped_key = Some(wrap_key(&key, &data_key)?);
+    persist(&locked_seed)
+}
+
+pub fn disable_biometric_unlock() -> Result<()> {
+    let mut locked_seed = load()?.context("The seed is not protected by a PIN")?;
+    locked_seed.biometric_wrapped_key = None;
+    persist(&locked_seed)
+}
+
+/// Sets after how long without activity the keystore locks itself.
+///
+/// The keystore has to be unlocked.
+pub fn set_auto_lock_timeout(timeout: Duration) -> Result<()> {
+    active_data_key()?;
+
+    let mut locked_seed = load()?.context("The seed is not protected by a PIN")?;
+    locked_seed.auto_lock_timeout_secs = timeout.as_secs();
+    persist(&locked_seed)?;
+
+    if let Some(session) = SESSION.lock().as_mut() {
+        session.auto_lock_timeout = timeout;
+    }
+
+    Ok(())
+}
+
+/// Unlocks the keystore with the PIN, loading the seed.
+///
+/// After [`FREE_PIN_ATTEMPTS`] wrong PINs in a row, the user has to wait before the next attempt.
+/// The attempts are counted in the locked seed, so that they are not reset by restarting the app.
+pub fn unlock(pin: &str) -> Result<()> {
+    let mut locked_seed = load()?.context("The seed is not protected by a PIN")?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let retry_in_secs = locked_seed.pin_retry_in_secs(now);
+    if retry_in_secs > 0 {
+        bail!(AppError::TooManyPinAttempts { retry_in_secs });
+    }
+
+    let pin_key = derive_pin_key(pin, &locked_seed.salt)?;
+    let data_key = match unwrap_key(&pin_key, &locked_seed.pin_wrapped_key) {
+        Ok(data_key) => data_key,
+        Err(_) => {
+            locked_seed.failed_pin_attempts += 1;
+            locked_seed.last_failed_pin_attempt = Some(now);
+            persist(&locked_seed)?;
+
+            tracing::warn!(
+                failed_pin_attempts = locked_seed.failed_pin_attempts,
+                "Wrong PIN"
+            );
+
+            bail!(AppError::WrongPin);
+        }
+    };
+
+    if locked_seed.failed_pin_attempts > 0 {
+        locked_seed.failed_pin_attempts = 0;
+        locked_seed.last_failed_pin_attempt = None;
+        persist(&locked_seed)?;
+    }
+
+    open(&locked_seed, data_key)
+}
+
+/// Unlocks the keystore with the key released by the biometrics of the platform, loading the
+/// seed.
+pub fn unlock_with_biometrics(key: &[u8]) -> Result<()> {
+    let mut locked_seed = load()?.context("The seed is not protected by a PIN")?;
+
+    let wrapped_key = locked_seed
+        .biometric_wrapped_key
+        .as_ref()
+        .context("Biometric unlock is not enabled")?;
+    let key = SecretKey::from_slice(key).context("Invalid biometric key")?;
+    let data_key = unwrap_key(&key, wrapped_key).context("Biometric key does not match")?;
+
+    if locked_seed.failed_pin_attempts > 0 {
+        locked_seed.failed_pin_attempts = 0;
+        locked_seed.last_failed_pin_attempt = None;
+        persist(&locked_seed)?;
+    }
+
+    open(&locked_seed, data_key)
+}
+
+/// Locks the keystore, so that it has to be unlocked before the signing key can be used on behalf
+/// of the user again.
+pub fn lock() {
+    if SESSION.lock().take().is_some() {
+        tracing::info!("Locked keystore");
+    }
+}
+
+/// Fails if the keystore is locked, either explicitly or because it has not been used for longer
+/// than the auto-lock timeout. Otherwise, the timeout starts over.
+pub fn ensure_unlocked() -> Result<()> {
+    if !is_protected() {
+        return Ok(());
+    }
+
+    let mut session = SESSION.lock();
+    match session.as_mut() {
+        Some(session) if session.last_activity.elapsed() < session.auto_lock_timeout => {
+            session.last_activity = Instant::now();
+            Ok(())
+        }
+        Some(_) => {
+            *session = None;
+            tracing::info!("Locked keystore after inactivity");
+            Err(AppError::KeystoreLocked.into())
+        }
+        None => Err(AppError::KeystoreLocked.into()),
+    }
+}
+
+/// The seed which has been loaded by unlocking the keystore.
+pub fn unlocked_seed() -> Result<Bip39Seed> {
+    ensure_unlocked()?;
+    state::try_get_seed().ok_or_else(|| AppError::KeystoreLocked.into())
+}
+
+/// Forgets the protected seed, e.g. because a new seed is created or restored.
+pub fn reset() -> Result<()> {
+    lock();
+
+    let path = locked_seed_file();
+    if path.exists() {
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove locked seed {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+fn open(locked_seed: &LockedSeed, data_key: SecretKey) -> Result<()> {
+    let entropy = AesCipher::new(data_key)
+        .decrypt(locked_seed.encrypted_seed.clone())
+        .context("Failed to decrypt seed")?;
+    let seed = Bip39Seed::try_from(entropy)?;
+
+    state::set_seed(seed);
+    start_session(locked_seed, data_key);
+
+    tracing::info!("Unlocked keystore");
+
+    Ok(())
+}
+
+fn start_session(locked_seed: &LockedSeed, data_key: SecretKey) {
+    *SESSION.lock() = Some(Session {
+        data_key,
+        auto_lock_timeout: Duration::from_secs(locked_seed.auto_lock_timeout_secs),
+        last_activity: Instant::now(),
+    });
+}
+
+fn active_data_key() -> Result<SecretKey> {
+    ensure_unlocked()?;
+
+    SESSION
+        .lock()
+        .as_ref()
+        .map(|session| session.data_key)
+        .ok_or_else(|| AppError::KeystoreLocked.into())
+}
+
+fn derive_pin_key(pin: &str, salt: &[u8]) -> Result<SecretKey> {
+    let params = Params::new(PIN_KDF_MEMORY_KIB, PIN_KDF_PASSES, 1, Some(32))
+        .map_err(|e| anyhow!("Invalid key derivation parameters: {e}"))?;
+
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(pin.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive key from PIN: {e}"))?;
+
+    SecretKey::from_slice(&key).context("Failed to derive key from PIN")
+}
+
+impl LockedSeed {
+    /// How many seconds the user has to wait at `now` before entering the PIN again.
+    fn pin_retry_in_secs(&self, now: i64) -> u64 {
+        let last_failed_pin_attempt = match self.last_failed_pin_attempt {
+            Some(last_failed_pin_attempt) => last_failed_pin_attempt,
+            None => return 0,
+        };
+
+        let delay = pin_retry_delay(self.failed_pin_attempts).as_secs() as i64;
+        let elapsed = now.saturating_sub(last_failed_pin_attempt).max(0);
+
+        delay.saturating_sub(elapsed).max(0) as u64
+    }
+}
+
+/// How long the user has to wait after having entered `failed_attempts` wrong PINs in a row.
+fn pin_retry_delay(failed_attempts: u32) -> Duration {
+    match failed_attempts.checked_sub(FREE_PIN_ATTEMPTS) {
+        Some(delayed_attempts) => PIN_RETRY_DELAY
+            .saturating_mul(2u32.saturating_pow(delayed_attempts))
+            .min(MAX_PIN_RETRY_DELAY),
+        None => Duration::ZERO,
+    }
+}
+
+fn wrap_key(key: &SecretKey, data_key: &SecretKey) -> Result<Vec<u8>> {
+    AesCipher::new(*key).encrypt(data_key.secret_bytes().to_vec())
+}
+
+fn unwrap_key(key: &SecretKey, wrapped_key: &[u8]) -> Result<SecretKey> {
+    ensure!(wrapped_key.len() > 12, "Malformed wrapped key");
+
+    let data_key = AesCipher::new(*key)
+        .decrypt(wrapped_key.to_vec())
+        .map_err(|e| anyhow!("Failed to decrypt data key: {e:#}"))?;
+
+    Ok(SecretKey::from_slice(&data_key)?)
+}
+
+fn load() -> Result<Option<LockedSeed>> {
+    let path = locked_seed_file();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let locked_seed = fs::read(&path)
+        .with_context(|| format!("Failed to read locked seed from {}", path.display()))?;
+
+    Ok(Some(serde_json::from_slice(&locked_seed)?))
+}
+
+fn persist(locked_seed: &LockedSeed) -> Result<()> {
+    let path = locked_seed_file();
+    fs::create_dir_all(path.parent().expect("parent"))?;
+
+    // Write to a temporary file first, so that the seed is not lost if the app is killed while
+    // writing.
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec(locked_seed)?)
+        .with_context(|| format!("Failed to write locked seed to {}", tmp.display()))?;
+    fs::rename(&tmp, &path)
+        .with_context(|| format!("Failed to move locked seed to {}", path.display()))
+}
+
+fn seed_dir() -> PathBuf {
+    PathBuf::from(config::get_seed_dir()).join(config::get_network().to_string())
+}
+
+fn locked_seed_file() -> PathBuf {
+    seed_dir().join(LOCKED_SEED_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::api::Config;
+    use crate::config::api::Directories;
+    use parking_lot::const_mutex;
+    use uuid::Uuid;
+
+    /// The keystore is global, hence tests using it must not run concurrently.
+    static KEYSTORE: Mutex<()> = const_mutex(());
+
+    #[test]
+    fn data_key_is_only_unwrapped_with_correct_pin() {
+        let salt = [7u8; 16];
+        let data_key = SecretKey::new(&mut rand::thread_rng());
+
+        let wrapped_key = wrap_key(&derive_pin_key("1234", &salt).unwrap(), &data_key).unwrap();
+
+        let unwrapped_key =
+            unwrap_key(&derive_pin_key("1234", &salt).unwrap(), &wrapped_key).unwrap();
+        assert_eq!(unwrapped_key, data_key);
+
+        assert!(unwrap_key(&derive_pin_key("4321", &salt).unwrap(), &wrapped_key).is_err());
+        assert!(unwrap_key(&derive_pin_key("1234", &[8u8; 16]).unwrap(), &wrapped_key).is_err());
+    }
+
+    #[test]
+    fn locked_keystore_does_not_release_the_seed() {
+        let _keystore = KEYSTORE.lock();
+        let seed = init_seed();
+
+        set_pin("1234").unwrap();
+        assert!(is_protected());
+        assert!(!seed_dir().join(SEED_FILE).exists());
+        assert_eq!(
+            unlocked_seed().unwrap().get_seed_phrase(),
+            seed.get_seed_phrase()
+        );
+
+        lock();
+
+        assert!(is_locked());
+        assert!(matches!(
+            unlocked_seed().unwrap_err().downcast::<AppError>(),
+            Ok(AppError::KeystoreLocked)
+        ));
+    }
+
+    #[test]
+    fn keystore_is_not_unlocked_with_wrong_pin() {
+        let _keystore = KEYSTORE.lock();
+        init_seed();
+
+        set_pin("1234").unwrap();
+        lock();
+
+        assert!(matches!(
+            unlock("4321").unwrap_err().downcast::<AppError>(),
+            Ok(AppError::WrongPin)
+        ));
+        assert!(is_locked());
+        assert!(unlocked_seed().is_err());
+    }
+
+    #[test]
+    fn keystore_is_unlocked_with_pin() {
+        let _keystore = KEYSTORE.lock();
+        let seed = init_seed();
+
+        set_pin("1234").unwrap();
+        lock();
+
+        unlock("1234").unwrap();
+
+        assert!(!is_locked());
+        assert_eq!(
+            unlocked_seed().unwrap().get_seed_phrase(),
+            seed.get_seed_phrase()
+        );
+    }
+
+    #[test]
+    fn keystore_is_not_unlocked_after_too_many_wrong_pins() {
+        let _keystore = KEYSTORE.lock();
+        init_seed();
+
+        set_pin("1234").unwrap();
+        lock();
+
+        for _ in 0..FREE_PIN_ATTEMPTS {
+            assert!(matches!(
+                unlock("4321").unwrap_err().downcast::<AppError>(),
+                Ok(AppError::WrongPin)
+            ));
+        }
+
+        assert!(matches!(
+            unlock("1234").unwrap_err().downcast::<AppError>(),
+            Ok(AppError::TooManyPinAttempts { retry_in_secs: 30 })
+        ));
+        assert!(is_locked());
+    }
+
+    #[test]
+    fn pin_attempts_are_delayed_increasingly() {
+        assert_eq!(pin_retry_delay(0), Duration::ZERO);
+        assert_eq!(pin_retry_delay(FREE_PIN_ATTEMPTS - 1), Duration::ZERO);
+        assert_eq!(pin_retry_delay(FREE_PIN_ATTEMPTS), PIN_RETRY_DELAY);
+        assert_eq!(pin_retry_delay(FREE_PIN_ATTEMPTS + 1), PIN_RETRY_DELAY * 2);
+        assert_eq!(pin_retry_delay(FREE_PIN_ATTEMPTS + 2), PIN_RETRY_DELAY * 4);
+        assert_eq!(pin_retry_delay(u32::MAX), MAX_PIN_RETRY_DELAY);
+    }
+
+    /// Points the config to a new seed dir containing a plaintext seed.
+    fn init_seed() -> Bip39Seed {
+        lock();
+
+        let dir = std::env::temp_dir().join(format!("10101-keystore-{}", Uuid::new_v4()));
+        let dir = dir.to_string_lossy().to_string();
+
+        state::set_config(
+            (
+                Config {
+                    coordinator_pubkey:
+                        "02dd6abec97f9a748bf76ad502b004ce05d1b2d1f43a9e76bd7d85e767ffb022c9"
+                            .to_string(),
+                    esplora_endpoint: "http://localhost:3000".to_string(),
+                    host: "127.0.0.1".to_string(),
+                    p2p_port: 9045,
+                    http_port: 8000,
+                    network: "regtest".to_string(),
+                    oracle_endpoint: "http://localhost:8081".to_string(),
+                    oracle_pubkey:
+                        "16f88cf7d21e6c0f46bcbc983a4e3b19726c6c98858cc31c83551a88fde171c0"
+                            .to_string(),
+                    health_check_interval_secs: None,
+                    rgs_server_url: None,
+                    watchtower_url: None,
+                    backup_url: None,
+                },
+                Directories {
+                    app_dir: dir.clone(),
+                    seed_dir: dir,
+                },
+            )
+                .into(),
+        );
+
+        let seed = Bip39Seed::initialize(&seed_dir().join(SEED_FILE)).unwrap();
+        state::set_seed(seed.clone());
+
+        seed
+    }
+}
@@ -10,11 +10,22 @@ pub mod config;
 pub mod event;
 pub mod health;
 pub mod logger;
+pub mod pending_action;
 pub mod schema;
 pub mod state;
 
+mod activity;
+mod address_book;
 mod backup;
+mod channel_backup;
+mod fee_estimates;
+mod keystore;
+mod lnurl;
+mod local_backup;
+mod log_file;
 mod orderbook;
+mod recovery;
+mod scheduler;
 
 #[allow(
     clippy::all,
@@ -26,5 +37,10 @@ mod bridge_generated;
 mod channel_trade_constraints;
 mod cipher;
 mod destination;
+mod device;
+mod diagnostics;
 mod dlc_handler;
+mod error;
+mod error_report;
 mod storage;
+mod unit_of_work;
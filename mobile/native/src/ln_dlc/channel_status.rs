@@ -1,17 +1,30 @@
+use crate::config;
 use crate::event;
 use crate::ln_dlc::node::Node;
+use crate::scheduler;
 use anyhow::Result;
+use ln_dlc_node::node::event::NodeEvent;
 use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannel;
 use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannelState;
 use ln_dlc_node::node::rust_dlc_manager::subchannel::SubChannel;
+use ln_dlc_node::node::ChannelOpenStatus;
 use std::borrow::Borrow;
 use std::time::Duration;
+use tokio::sync::broadcast;
 
 const UPDATE_CHANNEL_STATUS_INTERVAL: Duration = Duration::from_secs(5);
 
+/// The name of the background service tracking the channel status.
+pub const TASK_NAME: &str = "channel_status";
+
 /// The status of the app channel
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChannelStatus {
+    /// The channel with the coordinator is waiting for its funding transaction to confirm.
+    Opening {
+        confirmations: u32,
+        required_confirmations: u32,
+    },
     /// No channel is open.
     ///
     /// This means that it is possible to open a new DLC channel. This does _not_ indicate if
@@ -32,17 +45,26 @@ pub enum ChannelStatus {
     Unknown,
 }
 
-pub async fn track_channel_status(node: impl Borrow<Node>) {
+pub async fn track_channel_status(
+    node: impl Borrow<Node>,
+    mut node_events: broadcast::Receiver<NodeEvent>,
+) {
     let mut cached_status = ChannelStatus::Unknown;
+    let mut opening: Option<ChannelStatus> = None;
     loop {
+        scheduler::wait_until_resumed(TASK_NAME).await;
+
         tracing::trace!("Tracking channel status");
 
-        let status = channel_status(node.borrow())
-            .await
-            .map_err(|e| {
-                tracing::error!("Could not compute LN-DLC channel status: {e:#}");
-            })
-            .unwrap_or(ChannelStatus::Unknown);
+        let status = match opening {
+            Some(status) => status,
+            None => channel_status(node.borrow())
+                .await
+                .map_err(|e| {
+                    tracing::error!("Could not compute LN-DLC channel status: {e:#}");
+                })
+                .unwrap_or(ChannelStatus::Unknown),
+        };
 
         if status != cached_status {
             tracing::info!(?status, "Channel status update");
@@ -50,7 +72,41 @@ pub async fn track_channel_status(node: impl Borrow<Node>) {
             cached_status = status;
         }
 
-        tokio::time::sleep(UPDATE_CHANNEL_STATUS_INTERVAL).await;
+        tokio::select! {
+            _ = tokio::time::sleep(UPDATE_CHANNEL_STATUS_INTERVAL) => {}
+            event = node_events.recv() => match event {
+                Ok(NodeEvent::ChannelOpenStatus {
+                    counterparty,
+                    status,
+                    ..
+                }) if counterparty == config::get_coordinator_info().pubkey => {
+                    opening = match status {
+                        ChannelOpenStatus::Broadcast {
+                            required_confirmations,
+                        } => Some(ChannelStatus::Opening {
+                            confirmations: 0,
+                            required_confirmations,
+                        }),
+                        ChannelOpenStatus::Confirming {
+                            confirmations,
+                            required_confirmations,
+                        } => Some(ChannelStatus::Opening {
+                            confirmations,
+                            required_confirmations,
+                        }),
+                        ChannelOpenStatus::Ready => None,
+                    };
+                }
+                Ok(_) => {} // ignored
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Skipped {skipped} node events");
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    // Without node events we fall back to polling the channel status.
+                    tokio::time::sleep(UPDATE_CHANNEL_STATUS_INTERVAL).await;
+                }
+            }
+        }
     }
 }
 
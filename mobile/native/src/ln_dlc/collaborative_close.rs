@@ -0,0 +1,99 @@
+use crate::calculations::calculate_pnl;
+use crate::db;
+use crate::state;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannel;
+use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannelState;
+use ln_dlc_node::node::rust_dlc_manager::channel::Channel;
+use rust_decimal::Decimal;
+
+/// The payout we expect when collaboratively closing the given DLC channel.
+///
+/// If the DLC channel is settled, this is our settled balance. Otherwise it is our collateral plus
+/// the unrealized PnL of all our positions at the latest price.
+///
+/// Must be called before the collaborative close offer is processed, since the DLC channel no
+/// longer knows our settled balance afterwards.
+pub(crate) fn expected_payout(channel: &Channel) -> Result<u64> {
+    let (state, own_params) = match channel {
+        Channel::Signed(SignedChannel {
+            state, own_params, ..
+        }) => (state, own_params),
+        _ => bail!("Cannot collaboratively close DLC channel which is not signed"),
+    };
+
+    let payout = match state {
+        SignedChannelState::Settled { own_payout, .. } => *own_payout,
+        SignedChannelState::Established { .. } => {
+            let positions = db::get_positions()?;
+            ensure!(
+                !positions.is_empty(),
+                "No position for established DLC channel"
+            );
+
+            let mut pnl = 0;
+            for position in positions {
+                let price = state::try_get_prices()
+                    .and_then(|prices| prices.get(&position.contract_symbol).cloned())
+                    .and_then(|price| {
+                        Some(trade::Price {
+                            bid: price.bid?,
+                            ask: price.ask?,
+                        })
+                    })
+                    .context("No price to value the position")?;
+
+                pnl += calculate_pnl(
+                    position.average_entry_price,
+                    price,
+                    position.quantity,
+                    position.leverage,
+                    position.direction,
+                )?;
+            }
+
+            (own_params.collateral as i64 + pnl).max(0) as u64
+        }
+        state => bail!("Cannot collaboratively close DLC channel in state {state}"),
+    };
+
+    Ok(payout)
+}
+
+/// Checks that the `proposed` payout deviates from the `expected` payout by at most `tolerance`,
+/// relative to the `expected` payout.
+pub(crate) fn validate_payout(expected: u64, proposed: u64, tolerance: Decimal) -> Result<()> {
+    let deviation = (Decimal::from(proposed) - Decimal::from(expected)).abs();
+    let max_deviation = Decimal::from(expected) * tolerance;
+
+    ensure!(
+        deviation <= max_deviation,
+        "Proposed payout of {proposed} sats deviates from expected payout of {expected} sats by \
+         more than {tolerance}"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn payout_within_tolerance_is_valid() {
+        assert!(validate_payout(100_000, 100_000, dec!(0)).is_ok());
+        assert!(validate_payout(100_000, 99_000, dec!(0.01)).is_ok());
+        assert!(validate_payout(100_000, 101_000, dec!(0.01)).is_ok());
+    }
+
+    #[test]
+    fn payout_outside_tolerance_is_invalid() {
+        assert!(validate_payout(100_000, 98_999, dec!(0.01)).is_err());
+        assert!(validate_payout(100_000, 101_001, dec!(0.01)).is_err());
+        assert!(validate_payout(0, 1, dec!(0.01)).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::config;
+use crate::db;
+use crate::state;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannel;
+use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannelState;
+use ln_dlc_node::node::rust_dlc_manager::channel::Channel;
+
+/// The step of the DLC protocol the DLC channel with the coordinator is in.
+#[derive(Debug, Clone)]
+pub struct DlcProtocolState {
+    /// The hex encoded ID of the DLC channel, if we have one.
+    pub channel_id: Option<String>,
+    pub step: DlcProtocolStep,
+    /// Who has to act for the protocol to continue, if the protocol is in progress.
+    pub next_actor: Option<DlcProtocolActor>,
+    /// When we last sent a DLC message to the coordinator, as unix timestamp.
+    pub last_message_sent_at: Option<i64>,
+    /// When we last received a DLC message from the coordinator, as unix timestamp.
+    pub last_message_received_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DlcProtocolStep {
+    /// There is no DLC channel with the coordinator.
+    NoChannel,
+    /// The DLC channel has been offered, but not accepted yet.
+    ChannelOffered,
+    /// The DLC channel offer has been accepted, but not signed yet.
+    ChannelAccepted,
+    /// The DLC channel is established with a position.
+    Established,
+    /// The DLC channel is established without a position.
+    Settled,
+    SettleOffered,
+    SettleAccepted,
+    SettleConfirmed,
+    RenewOffered,
+    RenewAccepted,
+    RenewConfirmed,
+    RenewFinalized,
+    CollaborativeCloseOffered,
+    /// The DLC channel is being closed on-chain.
+    Closing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DlcProtocolActor {
+    /// The user has to accept or reject an offer of the coordinator.
+    User,
+    /// We are waiting for the coordinator to respond.
+    Coordinator,
+}
+
+pub fn get_dlc_protocol_state() -> Result<DlcProtocolState> {
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+    let coordinator = config::get_coordinator_info().pubkey;
+
+    let channel = node
+        .inner
+        .list_dlc_channels()?
+        .into_iter()
+        .filter(|channel| channel.get_counter_party_id() == coordinator)
+        .find(|channel| {
+            matches!(
+                channel,
+                Channel::Offered(_) | Channel::Accepted(_) | Channel::Signed(_)
+            )
+        });
+
+    let (step, next_actor) = match &channel {
+        Some(channel) => protocol_step(channel),
+        None => (DlcProtocolStep::NoChannel, None),
+    };
+
+    let mut conn = db::connection()?;
+    let last_message_sent_at =
+        db::dlc_messages::DlcMessage::get_last_timestamp(&mut conn, &coordinator, false)?;
+    let last_message_received_at =
+        db::dlc_messages::DlcMessage::get_last_timestamp(&mut conn, &coordinator, true)?;
+
+    Ok(DlcProtocolState {
+        channel_id: channel.map(|channel| channel.get_id().to_hex()),
+        step,
+        next_actor,
+        last_message_sent_at,
+        last_message_received_at,
+    })
+}
+
+/// The app never offers, so every offer has been received from the coordinator and waits for the
+/// user to act upon it.
+fn protocol_step(channel: &Channel) -> (DlcProtocolStep, Option<DlcProtocolActor>) {
+    use DlcProtocolActor::Coordinator;
+    use DlcProtocolActor::User;
+
+    let state = match channel {
+        Channel::Offered(_) => return (DlcProtocolStep::ChannelOffered, Some(User)),
+        Channel::Accepted(_) => return (DlcProtocolStep::ChannelAccepted, Some(Coordinator)),
+        Channel::Signed(SignedChannel { state, .. }) => state,
+        _ => return (DlcProtocolStep::NoChannel, None),
+    };
+
+    match state {
+        SignedChannelState::Established { .. } => (DlcProtocolStep::Established, None),
+        SignedChannelState::Settled { .. } => (DlcProtocolStep::Settled, None),
+        SignedChannelState::SettledOffered { .. } => {
+            (DlcProtocolStep::SettleOffered, Some(Coordinator))
+        }
+        SignedChannelState::SettledReceived { .. } => (DlcProtocolStep::SettleOffered, Some(User)),
+        SignedChannelState::SettledAccepted { .. } => {
+            (DlcProtocolStep::SettleAccepted, Some(Coordinator))
+        }
+        SignedChannelState::SettledConfirmed { .. } => {
+            (DlcProtocolStep::SettleConfirmed, Some(Coordinator))
+        }
+        SignedChannelState::RenewOffered { is_offer, .. } => {
+            let actor = if *is_offer { Coordinator } else { User };
+            (DlcProtocolStep::RenewOffered, Some(actor))
+        }
+        SignedChannelState::RenewAccepted { .. } => {
+            (DlcProtocolStep::RenewAccepted, Some(Coordinator))
+        }
+        SignedChannelState::RenewConfirmed { .. } => {
+            (DlcProtocolStep::RenewConfirmed, Some(Coordinator))
+        }
+        SignedChannelState::RenewFinalized { .. } => {
+            (DlcProtocolStep::RenewFinalized, Some(Coordinator))
+        }
+        SignedChannelState::CollaborativeCloseOffered { .. } => {
+            (DlcProtocolStep::CollaborativeCloseOffered, Some(User))
+        }
+        SignedChannelState::Closing { .. } => (DlcProtocolStep::Closing, None),
+    }
+}
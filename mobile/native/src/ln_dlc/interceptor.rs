@@ -0,0 +1,55 @@
+//! A hook into the DLC messages exchanged with peers.
+//!
+//! This lets the e2e tests interfere with the DLC protocol at specific steps, so that its recovery
+//! paths are exercised. Without an interceptor, every message is delivered.
+
+use bitcoin::secp256k1::PublicKey;
+use parking_lot::const_rwlock;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+static INTERCEPTOR: RwLock<Option<Arc<dyn DlcMessageInterceptor>>> = const_rwlock(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDirection {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Deliver,
+    /// Act as if the message was lost in transit.
+    ///
+    /// An outbound message is still queued for being resent on the next reconnect.
+    Drop,
+}
+
+pub trait DlcMessageInterceptor: Send + Sync {
+    /// Decide whether the DLC message of the given `kind` (e.g. `ChannelRenewConfirm`) is
+    /// delivered.
+    fn intercept(&self, peer: PublicKey, direction: MessageDirection, kind: &str) -> Verdict;
+}
+
+pub fn set(interceptor: Arc<dyn DlcMessageInterceptor>) {
+    *INTERCEPTOR.write() = Some(interceptor);
+}
+
+pub fn clear() {
+    *INTERCEPTOR.write() = None;
+}
+
+pub(crate) fn should_deliver(peer: PublicKey, direction: MessageDirection, kind: &str) -> bool {
+    let interceptor = match INTERCEPTOR.read().clone() {
+        Some(interceptor) => interceptor,
+        None => return true,
+    };
+
+    match interceptor.intercept(peer, direction, kind) {
+        Verdict::Deliver => true,
+        Verdict::Drop => {
+            tracing::warn!(%peer, ?direction, kind, "Dropping intercepted DLC message");
+            false
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::activity;
+use crate::address_book;
 use crate::api;
 use crate::api::Fee;
 use crate::api::PaymentFlow;
@@ -5,19 +7,25 @@ use crate::api::SendPayment;
 use crate::api::Status;
 use crate::api::WalletHistoryItem;
 use crate::api::WalletHistoryItemType;
+use crate::backup::BackupCategory;
 use crate::backup::DBBackupSubscriber;
+use crate::backup::RestoreSummary;
 use crate::commons::reqwest_client;
 use crate::config;
 use crate::config::get_rgs_server_url;
 use crate::db;
+use crate::device;
 use crate::dlc_handler;
 use crate::dlc_handler::DlcHandler;
 use crate::event;
 use crate::event::EventInternal;
+use crate::keystore;
 use crate::ln_dlc::channel_status::track_channel_status;
 use crate::ln_dlc::node::Node;
 use crate::ln_dlc::node::NodeStorage;
 use crate::ln_dlc::node::WalletHistories;
+use crate::lnurl;
+use crate::scheduler;
 use crate::state;
 use crate::storage::TenTenOneNodeStorage;
 use crate::trade::order;
@@ -29,6 +37,7 @@ use crate::trade::order::OrderType;
 use crate::trade::position;
 use anyhow::anyhow;
 use anyhow::bail;
+use anyhow::ensure;
 use anyhow::Context;
 use anyhow::Result;
 use bdk::bitcoin::secp256k1::rand::thread_rng;
@@ -49,12 +58,15 @@ use bitcoin::Amount;
 use bitcoin::OutPoint;
 pub use channel_status::ChannelStatus;
 use commons::CollaborativeRevertTraderResponse;
+use commons::JitChannelConfig;
 use commons::OnboardingParam;
 use commons::RouteHintHop;
 use commons::TradeParams;
 use dlc::PartyParams;
+pub use dlc_protocol_state::DlcProtocolActor;
+pub use dlc_protocol_state::DlcProtocolState;
+pub use dlc_protocol_state::DlcProtocolStep;
 use itertools::chain;
-use itertools::Itertools;
 use lightning::chain::chaininterface::ConfirmationTarget;
 use lightning::events::Event;
 use lightning::ln::channelmanager::ChannelDetails;
@@ -64,6 +76,7 @@ use ln_dlc_node::channel::Channel;
 use ln_dlc_node::channel::UserChannelId;
 use ln_dlc_node::config::app_config;
 use ln_dlc_node::lightning_invoice::Bolt11Invoice;
+use ln_dlc_node::node::event::NodeEvent;
 use ln_dlc_node::node::event::NodeEventHandler;
 use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannel;
 use ln_dlc_node::node::rust_dlc_manager::channel::ClosedChannel;
@@ -74,13 +87,19 @@ use ln_dlc_node::node::rust_dlc_manager::Storage as DlcStorage;
 use ln_dlc_node::node::GossipSourceConfig;
 use ln_dlc_node::node::LnDlcNodeSettings;
 use ln_dlc_node::node::Storage as LnDlcNodeStorage;
+use ln_dlc_node::node::SyncKind;
+use ln_dlc_node::node::ZeroConfTrustPolicy;
 use ln_dlc_node::scorer;
 use ln_dlc_node::seed::Bip39Seed;
 use ln_dlc_node::util;
 use ln_dlc_node::AppEventHandler;
+use ln_dlc_node::ChainSourceConfig;
 use ln_dlc_node::HTLCStatus;
+use ln_dlc_node::SendPreview;
 use ln_dlc_node::WalletSettings;
+use ln_dlc_node::WalletUtxo;
 use ln_dlc_node::CONFIRMATION_TARGET;
+use parking_lot::Mutex;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::net::IpAddr;
@@ -91,21 +110,29 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 use std::time::SystemTime;
 use time::OffsetDateTime;
 use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
 use tokio::sync::watch;
 use tokio::task::spawn_blocking;
-use trade::ContractSymbol;
 
 pub mod channel_status;
+mod collaborative_close;
+pub mod dlc_protocol_state;
+pub mod interceptor;
 mod lightning_subscriber;
 pub mod node;
 
 const PROCESS_INCOMING_DLC_MESSAGES_INTERVAL: Duration = Duration::from_millis(200);
 const UPDATE_WALLET_HISTORY_INTERVAL: Duration = Duration::from_secs(5);
 const CHECK_OPEN_ORDERS_INTERVAL: Duration = Duration::from_secs(60);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+const SUBMIT_QUEUED_ORDERS_INTERVAL: Duration = Duration::from_secs(10);
 const ON_CHAIN_SYNC_INTERVAL: Duration = Duration::from_secs(300);
+/// How often we check whether a new block was mined in between full on-chain syncs.
+const ON_CHAIN_TIP_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Defines a constant from which we treat a transaction as confirmed
 const NUMBER_OF_CONFIRMATION_FOR_BEING_CONFIRMED: u64 = 1;
@@ -139,13 +166,15 @@ pub fn refresh_lightning_wallet() -> Result<()> {
 /// asynchronously on the UI.
 pub async fn refresh_wallet_info() -> Result<()> {
     let node = state::get_node();
-    let wallet = node.inner.ldk_wallet();
 
     // Spawn into the blocking thread pool of the dedicated backend runtime to avoid blocking the UI
     // thread.
     let runtime = state::get_or_create_tokio_runtime()?;
     runtime.spawn_blocking(move || {
-        if let Err(e) = wallet.sync() {
+        if let Err(e) = node
+            .inner
+            .sync_on_chain_wallet_with_progress(SyncKind::Full)
+        {
             tracing::error!("Manually triggered on-chain sync failed: {e:#}");
         }
 
@@ -185,30 +214,42 @@ pub fn get_seed_phrase() -> Vec<String> {
     state::get_seed().get_seed_phrase()
 }
 
-/// Gets the seed from the storage or from disk. However it will panic if the seed can not be found.
-/// No new seed will be created.
-fn get_seed() -> Bip39Seed {
-    match state::try_get_seed() {
-        Some(seed) => seed,
-        None => {
-            let seed_dir = config::get_seed_dir();
-
-            let network = config::get_network();
-            let seed_path = Path::new(&seed_dir).join(network.to_string()).join("seed");
-            assert!(seed_path.exists());
+/// Gets the seed from the storage or from disk. No new seed will be created.
+///
+/// Fails if the seed can not be found or if it is protected by a PIN and the keystore is locked.
+fn get_seed() -> Result<Bip39Seed> {
+    if let Some(seed) = state::try_get_seed() {
+        return Ok(seed);
+    }
 
-            let seed = Bip39Seed::initialize(&seed_path).expect("to read seed file");
-            state::set_seed(seed.clone());
-            seed
-        }
+    if keystore::is_protected() {
+        return keystore::unlocked_seed();
     }
+
+    let seed_dir = config::get_seed_dir();
+
+    let network = config::get_network();
+    let seed_path = Path::new(&seed_dir).join(network.to_string()).join("seed");
+    ensure!(
+        seed_path.exists(),
+        "Seed file {} does not exist",
+        seed_path.display()
+    );
+
+    let seed = Bip39Seed::initialize(&seed_path).context("Failed to read seed file")?;
+    state::set_seed(seed.clone());
+
+    Ok(seed)
 }
 
-pub fn get_node_key() -> SecretKey {
+/// The node key, which is derived from the seed if the node is not running yet.
+///
+/// Fails if the node is not running and the seed is not available, see [`get_seed`].
+pub fn get_node_key() -> Result<SecretKey> {
     match state::try_get_node() {
-        Some(node) => node.inner.node_key(),
+        Some(node) => Ok(node.inner.node_key()),
         None => {
-            let seed = get_seed();
+            let seed = get_seed()?;
             let time_since_unix_epoch = SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .expect("unix epos to not be earlier than now");
@@ -217,13 +258,13 @@ pub fn get_node_key() -> SecretKey {
                 time_since_unix_epoch.as_secs(),
                 time_since_unix_epoch.subsec_nanos(),
             );
-            keys_manger.get_node_secret_key()
+            Ok(keys_manger.get_node_secret_key())
         }
     }
 }
 
-pub fn get_node_pubkey() -> PublicKey {
-    get_node_key().public_key(SECP256K1)
+pub fn get_node_pubkey() -> Result<PublicKey> {
+    Ok(get_node_key()?.public_key(SECP256K1))
 }
 
 pub async fn update_node_settings(settings: LnDlcNodeSettings) {
@@ -257,20 +298,32 @@ pub fn get_funding_transaction(channel_id: &ChannelId) -> Result<Txid> {
 }
 
 /// Gets the 10101 node storage, initializes the storage if not found yet.
-pub fn get_storage() -> TenTenOneNodeStorage {
+///
+/// Fails if the storage has to be initialized but the seed is not available, see [`get_seed`].
+pub fn get_storage() -> Result<TenTenOneNodeStorage> {
     match state::try_get_storage() {
-        Some(storage) => storage,
+        Some(storage) => Ok(storage),
         None => {
             // storage is only initialized before the node is started if a new wallet is created
             // or restored.
             let storage = TenTenOneNodeStorage::new(
                 config::get_data_dir(),
                 config::get_network(),
-                get_node_key(),
+                get_node_key()?,
+                get_seed()?.storage_encryption_key(),
             );
             tracing::info!("Initialized 10101 storage!");
+
+            if let Err(e) = storage.encrypt_plaintext_values() {
+                tracing::error!("Failed to encrypt storage: {e:#}");
+            }
+
+            if let Err(e) = storage.compact_dlc_storage() {
+                tracing::warn!("Failed to compact DLC storage: {e:#}");
+            }
+
             state::set_storage(storage.clone());
-            storage
+            Ok(storage)
         }
     }
 }
@@ -297,15 +350,20 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
         };
 
         let seed_dir = Path::new(&seed_dir).join(network.to_string());
-        let seed_path = seed_dir.join("seed");
-        let seed = Bip39Seed::initialize(&seed_path)?;
-        state::set_seed(seed.clone());
+        let seed = if keystore::is_protected() {
+            keystore::unlocked_seed().context("Unlock the keystore before starting the node")?
+        } else {
+            let seed_path = seed_dir.join("seed");
+            let seed = Bip39Seed::initialize(&seed_path)?;
+            state::set_seed(seed.clone());
+            seed
+        };
 
         let (event_sender, event_receiver) = watch::channel::<Option<Event>>(None);
 
         let node_storage = Arc::new(NodeStorage);
 
-        let storage = get_storage();
+        let storage = get_storage()?;
 
         event::subscribe(DBBackupSubscriber::new(storage.clone().client));
 
@@ -321,7 +379,9 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
             address,
             SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), address.port()),
             util::into_socket_addresses(address),
-            config::get_esplora_endpoint(),
+            ChainSourceConfig::Esplora {
+                urls: vec![config::get_esplora_endpoint()],
+            },
             seed,
             ephemeral_randomness,
             ln_dlc_node_settings(),
@@ -333,13 +393,14 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
         let node = Arc::new(node);
 
         let dlc_handler = DlcHandler::new(node.clone());
+        let dlc_message_receiver = node_event_handler.subscribe();
         runtime.spawn(async move {
             // this handles sending outbound dlc messages as well as keeping track of what
             // dlc messages have already been processed and what was the last outbound dlc message
             // so it can be resend on reconnect.
             //
             // this does not handle the incoming dlc messages!
-            dlc_handler::handle_dlc_messages(dlc_handler, node_event_handler.subscribe()).await
+            dlc_handler::handle_dlc_messages(dlc_handler, dlc_message_receiver).await
         });
 
         let event_handler = AppEventHandler::new(node.clone(), Some(event_sender));
@@ -358,32 +419,66 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
         .await
         .expect("task to complete")?;
 
-        runtime.spawn({
+        scheduler::spawn_periodic(runtime, "wallet_history", UPDATE_WALLET_HISTORY_INTERVAL, {
             let node = node.clone();
+            move || {
+                let node = node.clone();
+                async move {
+                    spawn_blocking(move || keep_wallet_balance_and_history_up_to_date(&node))
+                        .await
+                        .expect("To spawn blocking task")
+                        .context("Failed to sync balance and wallet history")
+                }
+            }
+        });
+
+        runtime.spawn({
+            let mut receiver = node_event_handler.subscribe();
             async move {
                 loop {
-                    tokio::time::sleep(UPDATE_WALLET_HISTORY_INTERVAL).await;
-
-                    let node = node.clone();
-                    if let Err(e) =
-                        spawn_blocking(move || keep_wallet_balance_and_history_up_to_date(&node))
-                            .await
-                            .expect("To spawn blocking task")
-                    {
-                        tracing::error!("Failed to sync balance and wallet history: {e:#}");
+                    match receiver.recv().await {
+                        Ok(NodeEvent::SyncProgress(progress)) => {
+                            event::publish(&EventInternal::SyncProgress(progress))
+                        }
+                        Ok(_) => {} // ignored
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Skipped {skipped} node events");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
                     }
                 }
             }
         });
 
-        std::thread::spawn({
+        scheduler::spawn_periodic(runtime, "on_chain_sync", ON_CHAIN_TIP_CHECK_INTERVAL, {
             let node = node.clone();
-            move || loop {
-                if let Err(e) = node.inner.sync_on_chain_wallet() {
-                    tracing::error!("Failed on-chain sync: {e:#}");
+            // Start with a full sync, so that unconfirmed transactions are picked up on startup.
+            let last_full_sync: Arc<Mutex<Option<Instant>>> = Arc::default();
+            move || {
+                let node = node.clone();
+                let last_full_sync = last_full_sync.clone();
+                async move {
+                    spawn_blocking(move || {
+                        let kind = {
+                            let mut last_full_sync = last_full_sync.lock();
+                            match *last_full_sync {
+                                Some(last) if last.elapsed() < ON_CHAIN_SYNC_INTERVAL => {
+                                    SyncKind::TipCheck
+                                }
+                                _ => {
+                                    *last_full_sync = Some(Instant::now());
+                                    SyncKind::Full
+                                }
+                            }
+                        };
+
+                        node.inner
+                            .sync_on_chain_wallet_with_progress(kind)
+                            .with_context(|| format!("Failed on-chain sync: {kind:?}"))
+                    })
+                    .await
+                    .expect("To spawn blocking task")
                 }
-
-                std::thread::sleep(ON_CHAIN_SYNC_INTERVAL);
             }
         });
 
@@ -393,39 +488,67 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
         });
 
         let coordinator_info = config::get_coordinator_info();
-        runtime.spawn({
+        scheduler::spawn_periodic(runtime, "coordinator_connection", RECONNECT_INTERVAL, {
             let node = node.clone();
-            async move { node.keep_connected(coordinator_info).await }
+            move || {
+                let node = node.clone();
+                async move { node.connect_until_closed(coordinator_info).await }
+            }
         });
 
-        runtime.spawn({
-            let node = node.clone();
-            async move {
-                loop {
+        scheduler::spawn_periodic(
+            runtime,
+            "incoming_dlc_messages",
+            PROCESS_INCOMING_DLC_MESSAGES_INTERVAL,
+            {
+                let node = node.clone();
+                move || {
                     let node = node.clone();
-                    spawn_blocking(move || node.process_incoming_dlc_messages())
-                        .await
-                        .expect("To spawn blocking thread");
-                    tokio::time::sleep(PROCESS_INCOMING_DLC_MESSAGES_INTERVAL).await;
+                    async move {
+                        spawn_blocking(move || node.process_incoming_dlc_messages())
+                            .await
+                            .expect("To spawn blocking thread");
+                        Ok(())
+                    }
                 }
-            }
-        });
+            },
+        );
 
-        runtime.spawn(async move {
-            loop {
-                if let Err(e) = spawn_blocking(order::handler::check_open_orders)
+        scheduler::spawn_periodic(
+            runtime,
+            "open_orders",
+            CHECK_OPEN_ORDERS_INTERVAL,
+            || async {
+                spawn_blocking(order::handler::check_open_orders)
                     .await
                     .expect("To spawn blocking task")
-                {
-                    tracing::error!("Error while checking open orders: {e:#}");
-                }
+                    .context("Error while checking open orders")?;
 
-                tokio::time::sleep(CHECK_OPEN_ORDERS_INTERVAL).await;
+                order::handler::check_order_in_filling()
+                    .await
+                    .context("Error while checking order in filling")
+            },
+        );
+
+        scheduler::spawn_periodic(
+            runtime,
+            "queued_orders",
+            SUBMIT_QUEUED_ORDERS_INTERVAL,
+            order::handler::submit_queued_orders,
+        );
+
+        scheduler::spawn_service(
+            runtime,
+            channel_status::TASK_NAME,
+            track_channel_status(node.clone(), node_event_handler.subscribe()),
+        );
+
+        runtime.spawn(async move {
+            if let Err(e) = position::handler::reconcile_positions_with_coordinator().await {
+                tracing::error!("Failed to reconcile positions with coordinator: {e:#}");
             }
         });
 
-        runtime.spawn(track_channel_status(node.clone()));
-
         state::set_node(node);
 
         event::publish(&EventInternal::Init("10101 is ready.".to_string()));
@@ -435,23 +558,53 @@ pub fn run(seed_dir: String, runtime: &Runtime) -> Result<()> {
 }
 
 pub fn init_new_mnemonic(target_seed_file: &Path) -> Result<()> {
+    keystore::reset()?;
     let seed = Bip39Seed::initialize(target_seed_file)?;
     state::set_seed(seed);
+    device::register_new_device()?;
     Ok(())
 }
 
-pub async fn restore_from_mnemonic(seed_words: &str, target_seed_file: &Path) -> Result<()> {
+pub async fn restore_from_mnemonic(
+    seed_words: &str,
+    target_seed_file: &Path,
+) -> Result<RestoreSummary> {
+    keystore::reset()?;
     let seed = Bip39Seed::restore_from_mnemonic(seed_words, target_seed_file)?;
     state::set_seed(seed);
+    // Any other device using the seed has to stop persisting its state once we are online.
+    device::register_new_device()?;
 
     let storage = TenTenOneNodeStorage::new(
         config::get_data_dir(),
         config::get_network(),
-        get_node_key(),
+        get_node_key()?,
+        get_seed()?.storage_encryption_key(),
     );
     tracing::info!("Initialized 10101 storage!");
-    state::set_storage(storage.clone());
-    storage.client.restore(storage.dlc_storage).await
+    state::set_storage(storage);
+    restore_from_backup(&BackupCategory::ALL).await
+}
+
+/// Restores the backed up values of the given `categories`, overwriting the local ones.
+///
+/// Must be called before the node is started.
+pub async fn restore_from_backup(categories: &[BackupCategory]) -> Result<RestoreSummary> {
+    ensure!(
+        state::try_get_node().is_none(),
+        "Cannot restore from backup while the node is running"
+    );
+
+    let storage = get_storage()?;
+    let summary = storage
+        .client
+        .restore(storage.dlc_storage.clone(), categories)
+        .await?;
+
+    // The backup is restored in plaintext.
+    storage.encrypt_plaintext_values()?;
+
+    Ok(summary)
 }
 
 fn keep_wallet_balance_and_history_up_to_date(node: &Node) -> Result<()> {
@@ -588,9 +741,14 @@ fn keep_wallet_balance_and_history_up_to_date(node: &Node) -> Result<()> {
 
         let payment_hash = hex::encode(details.payment_hash.0);
 
-        let expiry_timestamp = decoded_invoice
-            .and_then(|inv| inv.timestamp().checked_add(inv.expiry_time()))
-            .map(|time| OffsetDateTime::from(time).unix_timestamp() as u64);
+        let expiry_timestamp = details
+            .expiry
+            .or_else(|| {
+                decoded_invoice
+                    .and_then(|inv| inv.timestamp().checked_add(inv.expiry_time()))
+                    .map(OffsetDateTime::from)
+            })
+            .map(|time| time.unix_timestamp() as u64);
 
         let wallet_type = WalletHistoryItemType::Lightning {
             payment_hash,
@@ -600,6 +758,8 @@ fn keep_wallet_balance_and_history_up_to_date(node: &Node) -> Result<()> {
             fee_msat: details.fee_msat,
             expiry_timestamp,
             funding_txid: details.funding_txid.clone(),
+            payee: details.payee.clone(),
+            failure_reason: details.failure_reason.map(Into::into),
         };
 
         Some(WalletHistoryItem {
@@ -647,13 +807,10 @@ fn keep_wallet_balance_and_history_up_to_date(node: &Node) -> Result<()> {
         }
     });
 
-    let history = chain![on_chain, off_chain, trades, dlc_channel_funding_tx_details]
-        .sorted_by(|a, b| b.timestamp.cmp(&a.timestamp))
-        .collect();
+    activity::update(chain![on_chain, off_chain, trades, dlc_channel_funding_tx_details].collect());
 
     let wallet_info = api::WalletInfo {
         balances: wallet_balances.into(),
-        history,
     };
 
     event::publish(&EventInternal::WalletInfoUpdateNotification(wallet_info));
@@ -692,6 +849,28 @@ pub fn get_unused_address() -> String {
     state::get_node().inner.get_unused_address().to_string()
 }
 
+pub fn list_utxos() -> Result<Vec<WalletUtxo>> {
+    state::get_node().inner.ldk_wallet().list_utxos()
+}
+
+pub fn preview_send(
+    address: &Address,
+    amount_sat_or_drain: u64,
+    fee: ln_dlc_node::node::Fee,
+) -> Result<SendPreview> {
+    state::get_node()
+        .inner
+        .preview_send_to_address(address, amount_sat_or_drain, fee, None)
+}
+
+pub fn freeze_utxo(outpoint: OutPoint) -> Result<()> {
+    state::get_node().inner.ldk_wallet().freeze_utxo(outpoint)
+}
+
+pub fn unfreeze_utxo(outpoint: OutPoint) -> Result<()> {
+    state::get_node().inner.ldk_wallet().unfreeze_utxo(outpoint)
+}
+
 pub async fn close_channel(is_force_close: bool) -> Result<()> {
     tracing::info!(force = is_force_close, "Offering to close a channel");
     let node = state::try_get_node().context("failed to get ln dlc node")?;
@@ -789,7 +968,7 @@ pub fn collaborative_revert_channel(
         async move {
             match client
                 .post(format!(
-                    "http://{}/api/channels/revertconfirm",
+                    "http://{}/api/v1/channels/revertconfirm",
                     config::get_http_endpoint(),
                 ))
                 .json(&data)
@@ -868,6 +1047,8 @@ fn update_state_after_collab_revert(
                 order_expiry_timestamp: OffsetDateTime::now_utc(),
                 reason: OrderReason::Expired,
                 stable: position.stable,
+                reduce_only: false,
+                position_id: Some(position.id),
                 failure_reason: None,
             };
             db::insert_order(order.clone())?;
@@ -878,17 +1059,6 @@ fn update_state_after_collab_revert(
 
     position::handler::update_position_after_dlc_closure(Some(filled_order))?;
 
-    match db::delete_positions() {
-        Ok(_) => {
-            event::publish(&EventInternal::PositionCloseNotification(
-                ContractSymbol::BtcUsd,
-            ));
-        }
-        Err(error) => {
-            tracing::error!("Could not delete position : {error:#}");
-        }
-    }
-
     let node = node.inner.clone();
 
     node.dlc_manager
@@ -971,6 +1141,25 @@ pub fn contract_tx_fee_rate() -> Result<Option<u64>> {
     Ok(fee_rate_per_vb)
 }
 
+/// Fetch the terms under which the coordinator opens JIT channels.
+pub async fn fetch_jit_channel_config() -> Result<JitChannelConfig> {
+    let response = reqwest_client()
+        .get(format!(
+            "http://{}/api/v1/lsp/config",
+            config::get_http_endpoint()
+        ))
+        .send()
+        .await
+        .context("Failed to fetch JIT channel config from coordinator")?
+        .error_for_status()
+        .context("Could not fetch JIT channel config from coordinator")?;
+
+    response
+        .json()
+        .await
+        .context("Failed to parse JIT channel config")
+}
+
 pub fn create_onboarding_invoice(
     liquidity_option_id: i32,
     amount_sats: u64,
@@ -1015,7 +1204,7 @@ pub fn create_onboarding_invoice(
 
         let final_route_hint_hop : RouteHintHop = match client
             .post(format!(
-                "http://{}/api/prepare_onboarding_payment",
+                "http://{}/api/v1/prepare_onboarding_payment",
                 config::get_http_endpoint(),
             ))
             .json(&OnboardingParam {
@@ -1044,19 +1233,50 @@ pub fn create_onboarding_invoice(
     })
 }
 
-pub fn create_invoice(amount_sats: Option<u64>, description: String) -> Result<Bolt11Invoice> {
+pub async fn create_invoice(
+    amount_sats: Option<u64>,
+    description: String,
+) -> Result<Bolt11Invoice> {
     let node = state::get_node();
 
-    let final_route_hint_hop = node
-        .inner
-        .prepare_payment_with_route_hint(config::get_coordinator_info().pubkey)?;
+    // Prefer a route hint through which the coordinator holds the payment until we are online.
+    let final_route_hint_hop = match fetch_async_payment_route_hint(node.inner.info.pubkey).await {
+        Ok(route_hint_hop) => route_hint_hop.into(),
+        Err(e) => {
+            tracing::warn!(
+                "Payment to the invoice will fail if we are offline. Could not prepare async \
+                 payment: {e:#}"
+            );
+
+            node.inner
+                .prepare_payment_with_route_hint(config::get_coordinator_info().pubkey)?
+        }
+    };
 
     node.inner
         .create_invoice_with_route_hint(amount_sats, None, description, final_route_hint_hop)
 }
 
-pub fn create_usdp_invoice(amount_sats: Option<u64>, description: String) -> Result<Bolt11Invoice> {
-    let invoice = create_invoice(amount_sats, description)?;
+async fn fetch_async_payment_route_hint(node_id: PublicKey) -> Result<RouteHintHop> {
+    let route_hint_hop = reqwest_client()
+        .post(format!(
+            "http://{}/api/v1/prepare_async_payment/{node_id}",
+            config::get_http_endpoint(),
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(route_hint_hop)
+}
+
+pub async fn create_usdp_invoice(
+    amount_sats: Option<u64>,
+    description: String,
+) -> Result<Bolt11Invoice> {
+    let invoice = create_invoice(amount_sats, description).await?;
 
     let node = state::get_node();
     let mut write_guard = node.pending_usdp_invoices.lock();
@@ -1074,80 +1294,114 @@ pub fn is_usdp_payment(payment_hash: String) -> bool {
         .any(|hash| hash.to_string() == payment_hash)
 }
 
-pub async fn send_payment(payment: SendPayment) -> Result<()> {
-    match payment {
-        SendPayment::Lightning { invoice, amount } => {
-            let invoice = Bolt11Invoice::from_str(&invoice)?;
-            let amount = amount.map(Amount::from_sat);
-            let node = state::get_node().inner.clone();
-
-            match node.pay_invoice(&invoice, amount) {
-                Ok(()) => tracing::info!("Successfully triggered payment"),
-                Err(e) => {
-                    // TODO(holzeis): This has been added to debug a users channel details in case
-                    // of a failed payment. Remove the logs if not needed anymore.
-                    for channel in node.channel_manager.list_channels().iter() {
-                        tracing::debug!(
-                            channel_id = channel.channel_id.to_hex(),
-                            short_channel_id = channel.short_channel_id,
-                            unspendable_punishment_reserve = channel.unspendable_punishment_reserve,
-                            balance_msat = channel.balance_msat,
-                            feerate_sat_per_1000_weight = channel.feerate_sat_per_1000_weight,
-                            inbound_capacity_msat = channel.inbound_capacity_msat,
-                            inbound_htlc_maximum_msat = channel.inbound_htlc_maximum_msat,
-                            inbound_htlc_minimum_msat = channel.inbound_htlc_minimum_msat,
-                            is_usable = channel.is_usable,
-                            outbound_capacity_msat = channel.outbound_capacity_msat,
-                            next_outbound_htlc_limit_msat = channel.next_outbound_htlc_limit_msat,
-                            next_outbound_htlc_minimum_msat =
-                                channel.next_outbound_htlc_minimum_msat,
-                            is_channel_ready = channel.is_channel_ready,
-                            "Channel Details"
-                        );
+pub fn pay_invoice(invoice: &Bolt11Invoice, amount: Option<Amount>) -> Result<()> {
+    let node = state::get_node().inner.clone();
+
+    match node.pay_invoice(invoice, amount, config::payment_config()) {
+        Ok(()) => tracing::info!("Successfully triggered payment"),
+        Err(e) => {
+            // TODO(holzeis): This has been added to debug a users channel details in case
+            // of a failed payment. Remove the logs if not needed anymore.
+            for channel in node.channel_manager.list_channels().iter() {
+                tracing::debug!(
+                    channel_id = channel.channel_id.to_hex(),
+                    short_channel_id = channel.short_channel_id,
+                    unspendable_punishment_reserve = channel.unspendable_punishment_reserve,
+                    balance_msat = channel.balance_msat,
+                    feerate_sat_per_1000_weight = channel.feerate_sat_per_1000_weight,
+                    inbound_capacity_msat = channel.inbound_capacity_msat,
+                    inbound_htlc_maximum_msat = channel.inbound_htlc_maximum_msat,
+                    inbound_htlc_minimum_msat = channel.inbound_htlc_minimum_msat,
+                    is_usable = channel.is_usable,
+                    outbound_capacity_msat = channel.outbound_capacity_msat,
+                    next_outbound_htlc_limit_msat = channel.next_outbound_htlc_limit_msat,
+                    next_outbound_htlc_minimum_msat = channel.next_outbound_htlc_minimum_msat,
+                    is_channel_ready = channel.is_channel_ready,
+                    "Channel Details"
+                );
 
-                        let counterparty = channel.counterparty.clone();
-                        tracing::debug!(
-                            counterparty = %counterparty.node_id,
-                            counterparty_unspendable_punishement_reserve =
-                                counterparty.unspendable_punishment_reserve,
-                            counterparty.outbound_htlc_maximum_msat,
-                            counterparty.outbound_htlc_minimum_msat,
-                            "Counterparty");
-
-                        if let Some(forwarding_info) = counterparty.forwarding_info {
-                            tracing::debug!(
-                                forwarding_info.cltv_expiry_delta,
-                                forwarding_info.fee_base_msat,
-                                forwarding_info.fee_proportional_millionths,
-                                "Forwarding info"
-                            );
-                        }
+                let counterparty = channel.counterparty.clone();
+                tracing::debug!(
+                    counterparty = %counterparty.node_id,
+                    counterparty_unspendable_punishement_reserve =
+                        counterparty.unspendable_punishment_reserve,
+                    counterparty.outbound_htlc_maximum_msat,
+                    counterparty.outbound_htlc_minimum_msat,
+                    "Counterparty");
+
+                if let Some(forwarding_info) = counterparty.forwarding_info {
+                    tracing::debug!(
+                        forwarding_info.cltv_expiry_delta,
+                        forwarding_info.fee_base_msat,
+                        forwarding_info.fee_proportional_millionths,
+                        "Forwarding info"
+                    );
+                }
 
-                        if let Some(config) = channel.config {
-                            tracing::debug!(
-                                config.cltv_expiry_delta,
-                                config.forwarding_fee_base_msat,
-                                config.forwarding_fee_proportional_millionths,
-                                config.force_close_avoidance_max_fee_satoshis,
-                                max_dust_htlc_exposure=?config.max_dust_htlc_exposure,
-                                "Channel config"
-                            )
-                        }
-                    }
-                    tracing::error!("{e:#}");
-                    bail!(e)
+                if let Some(config) = channel.config {
+                    tracing::debug!(
+                        config.cltv_expiry_delta,
+                        config.forwarding_fee_base_msat,
+                        config.forwarding_fee_proportional_millionths,
+                        config.force_close_avoidance_max_fee_satoshis,
+                        max_dust_htlc_exposure=?config.max_dust_htlc_exposure,
+                        "Channel config"
+                    )
                 }
             }
+            tracing::error!("{e:#}");
+            bail!(e)
+        }
+    }
+    Ok(())
+}
+
+pub async fn send_payment(payment: SendPayment) -> Result<()> {
+    match payment {
+        SendPayment::Lightning { invoice, amount } => {
+            let invoice = Bolt11Invoice::from_str(&invoice)?;
+            pay_invoice(&invoice, amount.map(Amount::from_sat))?;
+        }
+        SendPayment::LightningAddress {
+            address,
+            amount,
+            comment,
+        } => {
+            lnurl::pay(&address, amount, comment).await?;
         }
         SendPayment::OnChain {
             address,
             amount,
             fee,
+            inputs,
         } => {
             let address = Address::from_str(&address)?;
+            let inputs = inputs
+                .map(|inputs| {
+                    inputs
+                        .iter()
+                        .map(|input| OutPoint::from_str(input))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()
+                .context("Invalid input")?;
+
+            if let Some(reuse) = address_book::check_address_reuse(&address)? {
+                tracing::warn!(
+                    %address,
+                    derivation_index = reuse.derivation_index,
+                    usage_count = reuse.usage_count,
+                    "Sending to a previously used receive address of our own wallet"
+                );
+            }
+
             state::get_node()
                 .inner
-                .send_to_address(&address, amount, fee.into())?;
+                .send_to_address(&address, amount, fee.into(), inputs)?;
+
+            if let Err(e) = address_book::record_withdrawal(&address) {
+                tracing::error!(%address, "Failed to add address to address book: {e:#}");
+            }
         }
     }
     Ok(())
@@ -1168,6 +1422,7 @@ pub async fn estimate_payment_fee_msat(payment: SendPayment) -> Result<u64> {
             address,
             amount,
             fee,
+            ..
         } => {
             let address = address.parse()?;
 
@@ -1181,13 +1436,19 @@ pub async fn estimate_payment_fee_msat(payment: SendPayment) -> Result<u64> {
 
             Ok(fee * 1000)
         }
+        SendPayment::LightningAddress { .. } => {
+            bail!("The fee of a payment to a Lightning address can only be estimated once the invoice has been requested")
+        }
     }
 }
 
 pub async fn trade(trade_params: TradeParams) -> Result<(), (FailureReason, anyhow::Error)> {
     let client = reqwest_client();
     let response = client
-        .post(format!("http://{}/api/trade", config::get_http_endpoint()))
+        .post(format!(
+            "http://{}/api/v1/trade",
+            config::get_http_endpoint()
+        ))
         .json(&trade_params)
         .send()
         .await
@@ -1208,7 +1469,12 @@ pub async fn trade(trade_params: TradeParams) -> Result<(), (FailureReason, anyh
         ));
     }
 
-    tracing::info!("Sent trade request to coordinator successfully");
+    if response.status() == reqwest::StatusCode::ACCEPTED {
+        // The order stays in filling, as the coordinator will propose the trade once it can.
+        tracing::warn!("Coordinator could not execute our trade request yet and will retry");
+    } else {
+        tracing::info!("Sent trade request to coordinator successfully");
+    }
 
     Ok(())
 }
@@ -1239,7 +1505,7 @@ pub async fn rollover(contract_id: Option<String>) -> Result<()> {
     let client = reqwest_client();
     let response = client
         .post(format!(
-            "http://{}/api/rollover/{}",
+            "http://{}/api/v1/rollover/{}",
             config::get_http_endpoint(),
             dlc_channel_id.to_hex()
         ))
@@ -1266,6 +1532,30 @@ pub async fn rollover(contract_id: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Accept channels from the coordinator as zero-conf channels if the user allows it, so that they
+/// can trade before the funding transaction confirms.
+fn zero_conf_trust_policy() -> ZeroConfTrustPolicy {
+    if config::zero_conf_channels_enabled() {
+        ZeroConfTrustPolicy::TrustPeers(vec![config::get_coordinator_info().pubkey])
+    } else {
+        ZeroConfTrustPolicy::TrustNobody
+    }
+}
+
+/// Change whether channels from the coordinator are accepted as zero-conf channels. Takes effect
+/// for the next channel opened by the coordinator.
+pub async fn set_zero_conf_channels_enabled(enabled: bool) {
+    config::set_zero_conf_channels_enabled(enabled);
+
+    if let Some(node) = state::try_get_node() {
+        node.inner.update_settings(ln_dlc_node_settings()).await;
+    }
+}
+
+pub async fn register_watchtower(url: String) -> Result<()> {
+    state::get_node().inner.register_watchtower(url).await
+}
+
 fn ln_dlc_node_settings() -> LnDlcNodeSettings {
     let gossip_source_config = match get_rgs_server_url() {
         Some(server_url) => GossipSourceConfig::RapidGossipSync { server_url },
@@ -1283,5 +1573,10 @@ fn ln_dlc_node_settings() -> LnDlcNodeSettings {
         bdk_client_stop_gap: 20,
         bdk_client_concurrency: 4,
         gossip_source_config,
+        mempool_space_url: None,
+        zero_conf_trust_policy: zero_conf_trust_policy(),
+        watchtower_url: config::get_watchtower_url(),
+        // The app does not hold payments for anyone.
+        async_payment_hold_timeout: Duration::from_secs(0),
     }
 }
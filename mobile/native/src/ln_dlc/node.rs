@@ -1,8 +1,14 @@
+use crate::config;
 use crate::db;
 use crate::event;
 use crate::event::BackgroundTask;
 use crate::event::EventInternal;
 use crate::event::TaskStatus;
+use crate::ln_dlc::collaborative_close;
+use crate::ln_dlc::interceptor;
+use crate::ln_dlc::interceptor::MessageDirection;
+use crate::pending_action::PendingAction;
+use crate::state;
 use crate::storage::TenTenOneNodeStorage;
 use crate::trade::order;
 use crate::trade::order::FailureReason;
@@ -11,6 +17,7 @@ use crate::trade::position;
 use crate::trade::position::handler::update_position_after_dlc_channel_creation_or_update;
 use crate::trade::position::handler::update_position_after_dlc_closure;
 use crate::trade::position::PositionState;
+use crate::unit_of_work;
 use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
@@ -41,11 +48,11 @@ use ln_dlc_node::node::RunningNode;
 use ln_dlc_node::transaction::Transaction;
 use ln_dlc_node::HTLCStatus;
 use ln_dlc_node::MillisatAmount;
+use ln_dlc_node::PaymentFailureReason;
 use ln_dlc_node::PaymentFlow;
 use ln_dlc_node::PaymentInfo;
 use std::collections::HashSet;
 use std::sync::Arc;
-use std::time::Duration;
 use time::OffsetDateTime;
 use tracing::instrument;
 
@@ -132,6 +139,10 @@ impl Node {
 
         for (node_id, msg) in messages {
             let msg_name = dlc_message_name(&msg);
+            if !interceptor::should_deliver(node_id, MessageDirection::Inbound, &msg_name) {
+                continue;
+            }
+
             if let Err(e) = self.process_dlc_message(node_id, msg) {
                 tracing::error!(
                     from = %node_id,
@@ -147,16 +158,17 @@ impl Node {
     /// - Any other message will be ignored.
     /// - Any dlc channel message that has already been processed will be skipped.
     ///
-    /// If an offer is received [`ChannelMessage::Offer`], [`ChannelMessage::SettleOffer`],
-    /// [`ChannelMessage::CollaborativeCloseOffer`], [`ChannelMessage::RenewOffer`] will get
-    /// automatically accepted. Unless the maturity date of the offer is already outdated.
+    /// An offer ([`ChannelMessage::Offer`], [`ChannelMessage::SettleOffer`],
+    /// [`ChannelMessage::RenewOffer`]) is only accepted automatically if it belongs to our order
+    /// in `Filling` or, in the case of a [`ChannelMessage::RenewOffer`], to an expected rollover.
+    /// Unless the maturity date of the offer is already outdated. Any other offer, including every
+    /// [`ChannelMessage::CollaborativeCloseOffer`], is parked until the user accepts or rejects it
+    /// through [`crate::api::accept_dlc_offer`] or [`crate::api::reject_dlc_offer`].
     ///
-    /// FIXME(holzeis): This function manipulates different data objects in different data sources
-    /// and should use a transaction to make all changes atomic. Not doing so risks of ending up in
-    /// an inconsistent state. One way of fixing that could be to
-    /// (1) use a single data source for the 10101 data and the rust-dlc data.
-    /// (2) wrap the function into a db transaction which can be atomically rolled back on error or
-    /// committed on success.
+    /// A dlc channel message is processed as a single [`crate::unit_of_work::UnitOfWork`], so that
+    /// the changes to the 10101 data, the rust-dlc data and the published events are either all
+    /// applied or, if the processing fails, not at all. The response and any other message sent
+    /// while processing, e.g. the `RenewAccept`, are only sent once the changes are committed.
     fn process_dlc_message(&self, node_id: PublicKey, msg: Message) -> Result<()> {
         tracing::info!(
             from = %node_id,
@@ -169,7 +181,7 @@ impl Node {
                 tracing::warn!("Ignoring unexpected dlc message.");
                 None
             }
-            Message::Channel(channel_msg) => {
+            Message::Channel(channel_msg) => unit_of_work::run(state::get_storage(), || {
                 let inbound_msg = {
                     let mut conn = db::connection()?;
                     let serialized_inbound_message = SerializedDlcMessage::try_from(&msg)?;
@@ -177,12 +189,41 @@ impl Node {
                     match db::dlc_messages::DlcMessage::get(&mut conn, &inbound_msg.message_hash)? {
                         Some(_) => {
                             tracing::debug!(%node_id, kind=%dlc_message_name(&msg), "Received message that has already been processed, skipping.");
-                            return Ok(());
+                            return Ok(None);
+                        }
+                        None => {
+                            // A new message from the peer implies that they have processed the
+                            // messages we sent them before.
+                            db::outbound_dlc_messages::OutboundDlcMessage::acknowledge(
+                                &mut conn, &node_id,
+                            )?;
+
+                            inbound_msg
                         }
-                        None => inbound_msg,
                     }
                 };
 
+                // Once the collaborative close offer is processed, the DLC channel no longer knows
+                // our balance. Hence, we have to compute the payout we expect beforehand.
+                let expected_close_payout = match channel_msg {
+                    ChannelMessage::CollaborativeCloseOffer(close_offer) => {
+                        match self
+                            .inner
+                            .get_dlc_channel_by_id(&close_offer.channel_id)
+                            .and_then(|channel| collaborative_close::expected_payout(&channel))
+                        {
+                            Ok(payout) => Some(payout),
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Cannot compute expected collaborative close payout: {e:#}"
+                                );
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+
                 let resp = self
                     .inner
                     .dlc_manager
@@ -199,7 +240,14 @@ impl Node {
                     db::dlc_messages::DlcMessage::insert(&mut conn, inbound_msg)?;
                 }
 
+                let is_order_in_filling = db::get_order_in_filling()?.is_some();
+
                 match channel_msg {
+                    ChannelMessage::Offer(offer) if !is_order_in_filling => {
+                        self.park_dlc_offer(PendingAction::DlcChannelOffer {
+                            channel_id: offer.temporary_channel_id,
+                        });
+                    }
                     ChannelMessage::Offer(offer) => {
                         let action = decide_subchannel_offer_action(
                             OffsetDateTime::from_unix_timestamp(
@@ -209,6 +257,11 @@ impl Node {
                         );
                         self.process_dlc_channel_offer(offer.temporary_channel_id, action)?;
                     }
+                    ChannelMessage::SettleOffer(offer) if !is_order_in_filling => {
+                        self.park_dlc_offer(PendingAction::DlcChannelSettleOffer {
+                            channel_id: offer.channel_id,
+                        });
+                    }
                     ChannelMessage::SettleOffer(offer) => {
                         self.inner
                             .accept_dlc_channel_collaborative_settlement(&offer.channel_id)
@@ -219,29 +272,23 @@ impl Node {
                                 )
                             })?;
                     }
+                    ChannelMessage::RenewOffer(r)
+                        if !is_order_in_filling && !is_rollover_expected()? =>
+                    {
+                        self.park_dlc_offer(PendingAction::DlcChannelRenewOffer {
+                            channel_id: r.channel_id,
+                        });
+                    }
                     ChannelMessage::RenewOffer(r) => {
-                        let channel_id_hex = r.channel_id.to_hex();
-
                         tracing::info!(
-                            channel_id = %channel_id_hex,
+                            channel_id = %r.channel_id.to_hex(),
                             "Automatically accepting renew offer"
                         );
 
-                        // TODO: This is generally not safe. The coordinator will even send
-                        // `RenewOffer`s in order to roll over, and these will not even be triggered
-                        // by a user action.
-                        let (accept_renew_offer, counterparty_pubkey) =
-                            self.inner.dlc_manager.accept_renew_offer(&r.channel_id)?;
-
-                        self.send_dlc_message(
-                            counterparty_pubkey,
-                            Message::Channel(ChannelMessage::RenewAccept(accept_renew_offer)),
-                        )?;
-
                         let expiry_timestamp = OffsetDateTime::from_unix_timestamp(
                             r.contract_info.get_closest_maturity_date() as i64,
                         )?;
-                        position::handler::handle_channel_renewal_offer(expiry_timestamp)?;
+                        self.accept_dlc_channel_renew_offer(&r.channel_id, expiry_timestamp)?;
                     }
                     ChannelMessage::RenewRevoke(r) => {
                         let channel_id_hex = r.channel_id.to_hex();
@@ -273,7 +320,10 @@ impl Node {
                                     "Finished rolling over position"
                                 );
 
-                                position::handler::set_position_state(PositionState::Open)?;
+                                position::handler::set_positions_state(
+                                    PositionState::Rollover,
+                                    PositionState::Open,
+                                )?;
 
                                 event::publish(&EventInternal::BackgroundNotification(
                                     BackgroundTask::Rollover(TaskStatus::Success),
@@ -331,15 +381,44 @@ impl Node {
                             "Received an offer to collaboratively close a channel"
                         );
 
-                        // TODO(bonomat): we should verify that the proposed amount is acceptable
-                        self.inner
-                            .accept_dlc_channel_collaborative_close(&close_offer.channel_id)?;
+                        let validation = expected_close_payout.map(|expected_payout| {
+                            collaborative_close::validate_payout(
+                                expected_payout,
+                                close_offer.counter_payout,
+                                config::collaborative_close_payout_tolerance(),
+                            )
+                        });
+
+                        match validation {
+                            Some(Err(e)) => {
+                                tracing::warn!(
+                                    channel_id = channel_id_hex_string,
+                                    "Rejecting collaborative close offer: {e:#}"
+                                );
+
+                                self.inner
+                                    .reject_dlc_channel_offer(&close_offer.channel_id)?;
+
+                                event::publish(&EventInternal::BackgroundNotification(
+                                    BackgroundTask::CollaborativeClose(TaskStatus::Failed),
+                                ));
+                            }
+                            // Even if the proposed amount is acceptable, the user still has to
+                            // agree to close the channel.
+                            Some(Ok(())) | None => {
+                                self.park_dlc_offer(
+                                    PendingAction::DlcChannelCollaborativeCloseOffer {
+                                        channel_id: close_offer.channel_id,
+                                    },
+                                );
+                            }
+                        }
                     }
                     _ => (),
                 }
 
-                resp
-            }
+                Ok(resp)
+            })?,
         };
 
         if let Some(msg) = resp {
@@ -349,6 +428,37 @@ impl Node {
         Ok(())
     }
 
+    /// Leave the DLC offer untouched until the user decides to accept or reject it.
+    fn park_dlc_offer(&self, offer: PendingAction) {
+        tracing::info!(
+            ?offer,
+            "Waiting for user to accept or reject unexpected DLC offer"
+        );
+
+        event::publish(&EventInternal::DlcOfferReceived(offer));
+    }
+
+    /// Accept a pending renew offer for the given DLC channel and move the position into
+    /// rollover with the new `expiry_timestamp`.
+    #[instrument(fields(channel_id = channel_id.to_hex()),skip_all, err(Debug))]
+    pub fn accept_dlc_channel_renew_offer(
+        &self,
+        channel_id: &DlcChannelId,
+        expiry_timestamp: OffsetDateTime,
+    ) -> Result<()> {
+        let (accept_renew_offer, counterparty_pubkey) =
+            self.inner.dlc_manager.accept_renew_offer(channel_id)?;
+
+        self.send_dlc_message(
+            counterparty_pubkey,
+            Message::Channel(ChannelMessage::RenewAccept(accept_renew_offer)),
+        )?;
+
+        position::handler::handle_channel_renewal_offer(expiry_timestamp)?;
+
+        Ok(())
+    }
+
     #[instrument(fields(channel_id = channel_id.to_hex()),skip_all, err(Debug))]
     fn process_dlc_channel_offer(
         &self,
@@ -482,40 +592,37 @@ impl Node {
         Ok(())
     }
 
-    pub async fn keep_connected(&self, peer: NodeInfo) {
-        let reconnect_interval = Duration::from_secs(1);
-        loop {
-            let connection_closed_future = match self.inner.connect(peer).await {
-                Ok(fut) => fut,
-                Err(e) => {
-                    tracing::warn!(
-                        %peer,
-                        ?reconnect_interval,
-                        "Connection failed: {e:#}; reconnecting"
-                    );
-
-                    tokio::time::sleep(reconnect_interval).await;
-                    continue;
-                }
-            };
+    /// Connects to `peer` and waits until the connection is closed.
+    pub async fn connect_until_closed(&self, peer: NodeInfo) -> Result<()> {
+        let connection_closed_future = self
+            .inner
+            .connect(peer)
+            .await
+            .with_context(|| format!("Connection to {peer} failed"))?;
 
-            connection_closed_future.await;
-            tracing::debug!(
-                %peer,
-                ?reconnect_interval,
-                "Connection lost; reconnecting"
-            );
+        connection_closed_future.await;
+        tracing::debug!(%peer, "Connection lost");
 
-            tokio::time::sleep(reconnect_interval).await;
-        }
+        Ok(())
     }
 }
 
+/// Whether the coordinator is expected to roll over our position, i.e. we have an open position
+/// and we are within the rollover window.
+fn is_rollover_expected() -> Result<bool> {
+    let has_open_position = db::get_positions()?
+        .iter()
+        .any(|position| position.position_state == PositionState::Open);
+
+    Ok(has_open_position
+        && commons::is_eligible_for_rollover(state::get_clock().now(), config::get_network()))
+}
+
 pub(crate) fn decide_subchannel_offer_action(
     maturity_timestamp: OffsetDateTime,
 ) -> SubchannelOfferAction {
     let mut action = SubchannelOfferAction::Accept;
-    if OffsetDateTime::now_utc().gt(&maturity_timestamp) {
+    if state::get_clock().now().gt(&maturity_timestamp) {
         action = SubchannelOfferAction::RejectOutdated;
     }
     action
@@ -550,6 +657,7 @@ impl node::Storage for NodeStorage {
         preimage: Option<PaymentPreimage>,
         secret: Option<PaymentSecret>,
         funding_txid: Option<Txid>,
+        failure_reason: Option<PaymentFailureReason>,
     ) -> Result<()> {
         match db::get_payment(*payment_hash)? {
             Some(_) => {
@@ -561,6 +669,7 @@ impl node::Storage for NodeStorage {
                     preimage,
                     secret,
                     funding_txid,
+                    failure_reason,
                 )?;
             }
             None => {
@@ -577,6 +686,9 @@ impl node::Storage for NodeStorage {
                         description: "".to_string(),
                         invoice: None,
                         funding_txid,
+                        expiry: None,
+                        payee: None,
+                        failure_reason,
                     },
                 )?;
             }
@@ -0,0 +1,476 @@
+//! Support for [LNURL](https://github.com/lnurl/luds): paying to and withdrawing from other
+//! Lightning wallets and services.
+//!
+//! Implements LUD-01 (bech32 encoded URLs), LUD-03 (withdraw), LUD-06 (pay), LUD-12 (comments),
+//! LUD-16 (Lightning addresses) and LUD-17 (`lnurlw://` and `lnurlp://` schemes).
+
+use crate::commons::reqwest_client;
+use crate::event;
+use crate::event::EventInternal;
+use crate::ln_dlc;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::bech32;
+use bitcoin::bech32::FromBase32;
+use bitcoin::hashes::sha256;
+use bitcoin::hashes::Hash;
+use lightning_invoice::Bolt11Invoice;
+use lightning_invoice::Bolt11InvoiceDescription;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The parameters of an LNURL, as returned by the service behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LnUrlRequest {
+    Withdraw(WithdrawRequest),
+    Pay(PayRequest),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawRequest {
+    pub callback: String,
+    pub k1: String,
+    #[serde(default)]
+    pub default_description: String,
+    pub min_withdrawable: u64,
+    pub max_withdrawable: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayRequest {
+    pub callback: String,
+    pub min_sendable: u64,
+    pub max_sendable: u64,
+    /// A JSON array of `[mime type, content]` pairs describing the payment.
+    pub metadata: String,
+    /// The maximum length of a comment the service accepts, if any.
+    #[serde(default)]
+    pub comment_allowed: u16,
+}
+
+/// The progress of an LNURL flow, published as [`EventInternal::LnUrlProgress`].
+///
+/// The completion of a flow is signalled by the usual payment events, i.e.
+/// [`EventInternal::PaymentSent`] for a pay and [`EventInternal::PaymentClaimed`] for a withdraw.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LnUrlProgress {
+    /// Fetching the parameters of the LNURL from the service.
+    FetchingParameters,
+    /// Requesting the invoice of an LNURL-pay from the service.
+    RequestingInvoice {
+        amount_sats: u64,
+    },
+    /// Paying the invoice of an LNURL-pay.
+    Paying {
+        amount_sats: u64,
+    },
+    /// Handing our invoice to the service of an LNURL-withdraw.
+    SubmittingInvoice {
+        amount_sats: u64,
+    },
+    /// The service accepted our invoice and is going to pay it.
+    WaitingForPayment {
+        amount_sats: u64,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+/// Why paying to an LNURL-pay or Lightning address failed.
+#[derive(thiserror::Error, Debug)]
+pub enum LnUrlPayError {
+    #[error("Invalid LNURL or Lightning address: {0:#}")]
+    InvalidRequest(anyhow::Error),
+    #[error("Failed to reach the service: {0:#}")]
+    Service(anyhow::Error),
+    #[error("LNURL is not a pay request")]
+    NotAPayRequest,
+    #[error("Amount must be between {min_sats} and {max_sats} sats")]
+    AmountOutOfRange { min_sats: u64, max_sats: u64 },
+    #[error("Comment is longer than the {allowed} characters allowed")]
+    CommentTooLong { allowed: u16 },
+    #[error("Service sent an invalid invoice: {0:#}")]
+    InvalidInvoice(anyhow::Error),
+    #[error(
+        "Invoice is for {actual_msats:?} msats instead of the requested {requested_msats} msats"
+    )]
+    AmountMismatch {
+        requested_msats: u64,
+        actual_msats: Option<u64>,
+    },
+    #[error("Invoice does not commit to the metadata of the LNURL")]
+    DescriptionHashMismatch,
+    #[error("Failed to pay invoice: {0:#}")]
+    Payment(anyhow::Error),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "tag")]
+enum Parameters {
+    #[serde(rename = "withdrawRequest")]
+    Withdraw(WithdrawRequest),
+    #[serde(rename = "payRequest")]
+    Pay(PayRequest),
+}
+
+#[derive(Debug, Deserialize)]
+struct PayResponse {
+    pr: String,
+}
+
+/// The response of a service reporting an error, respectively the success of a callback.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE", tag = "status")]
+enum Status {
+    Ok,
+    Error { reason: String },
+}
+
+/// Decode an LNURL, a `lnurlw://` or `lnurlp://` URL, or a Lightning address into the URL of the
+/// service.
+pub fn decode_url(request: &str) -> Result<String> {
+    let request = strip_lightning_prefix(request);
+
+    if request.to_lowercase().starts_with("lnurl1") {
+        let (hrp, data, _) = bech32::decode(request).context("Invalid bech32 encoded LNURL")?;
+        ensure!(hrp == "lnurl", "Unexpected human-readable part {hrp}");
+
+        let url = Vec::<u8>::from_base32(&data).context("Invalid bech32 encoded LNURL")?;
+        let url = String::from_utf8(url).context("LNURL is not a valid URL")?;
+
+        return Ok(url);
+    }
+
+    for scheme in ["lnurlw://", "lnurlp://"] {
+        if let Some(rest) = request.strip_prefix(scheme) {
+            let protocol = match rest.split('/').next() {
+                Some(host) if host.ends_with(".onion") => "http",
+                _ => "https",
+            };
+
+            return Ok(format!("{protocol}://{rest}"));
+        }
+    }
+
+    if let Some((user, domain)) = request.split_once('@') {
+        ensure!(
+            !user.is_empty()
+                && user
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.+".contains(c)),
+            "Invalid user in Lightning address"
+        );
+        ensure!(
+            !domain.is_empty() && !domain.contains('/'),
+            "Invalid domain in Lightning address"
+        );
+
+        return Ok(format!("https://{domain}/.well-known/lnurlp/{user}"));
+    }
+
+    bail!("Request is neither an LNURL nor a Lightning address")
+}
+
+/// Trim the request and remove its `lightning:` URI scheme, if any.
+pub fn strip_lightning_prefix(request: &str) -> &str {
+    let request = request.trim();
+    match request.get(..10) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("lightning:") => &request[10..],
+        _ => request,
+    }
+}
+
+/// Whether the request is a Lightning address, i.e. `user@domain`, rather than an LNURL.
+pub fn is_lightning_address(request: &str) -> bool {
+    let request = strip_lightning_prefix(request);
+
+    match request.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty() && !domain.is_empty() && !request.contains([':', '/', '?'])
+        }
+        None => false,
+    }
+}
+
+/// Fetch the parameters of the given LNURL or Lightning address.
+pub async fn fetch_request(request: &str) -> Result<LnUrlRequest> {
+    let url = decode_url(request)?;
+
+    let parameters = get_json::<Parameters>(&url, &[])
+        .await
+        .context("Failed to fetch LNURL parameters")?;
+
+    let request = match parameters {
+        Parameters::Withdraw(withdraw) => LnUrlRequest::Withdraw(withdraw),
+        Parameters::Pay(pay) => LnUrlRequest::Pay(pay),
+    };
+
+    Ok(request)
+}
+
+/// Withdraw `amount_sats` from the service of the given LNURL-withdraw.
+///
+/// Returns once the service accepted our invoice. The payment itself arrives asynchronously.
+pub async fn withdraw(request: &str, amount_sats: u64) -> Result<()> {
+    let result = withdraw_inner(request, amount_sats).await;
+    publish_if_failed(&result);
+
+    result
+}
+
+/// Fetch the parameters of the given LNURL-pay or Lightning address.
+pub async fn fetch_pay_request(request: &str) -> Result<PayRequest, LnUrlPayError> {
+    let url = decode_url(request).map_err(LnUrlPayError::InvalidRequest)?;
+
+    match get_json::<Parameters>(&url, &[])
+        .await
+        .map_err(LnUrlPayError::Service)?
+    {
+        Parameters::Pay(pay) => Ok(pay),
+        Parameters::Withdraw(_) => Err(LnUrlPayError::NotAPayRequest),
+    }
+}
+
+/// Pay `amount_sats` to the service of the given LNURL-pay or Lightning address.
+pub async fn pay(
+    request: &str,
+    amount_sats: u64,
+    comment: Option<String>,
+) -> Result<(), LnUrlPayError> {
+    let result = pay_inner(request, amount_sats, comment).await;
+    publish_if_failed(&result);
+
+    result
+}
+
+async fn withdraw_inner(request: &str, amount_sats: u64) -> Result<()> {
+    publish(LnUrlProgress::FetchingParameters);
+    let withdraw = match fetch_request(request).await? {
+        LnUrlRequest::Withdraw(withdraw) => withdraw,
+        LnUrlRequest::Pay(_) => bail!("LNURL is not a withdraw request"),
+    };
+
+    let amount_msats = amount_sats * 1000;
+    ensure!(
+        (withdraw.min_withdrawable..=withdraw.max_withdrawable).contains(&amount_msats),
+        "Amount must be between {} and {} sats",
+        withdraw.min_withdrawable / 1000,
+        withdraw.max_withdrawable / 1000
+    );
+
+    publish(LnUrlProgress::SubmittingInvoice { amount_sats });
+    let invoice = ln_dlc::create_invoice(Some(amount_sats), withdraw.default_description).await?;
+
+    let status = get_json::<Status>(
+        &withdraw.callback,
+        &[("k1", withdraw.k1), ("pr", invoice.to_string())],
+    )
+    .await
+    .context("Failed to submit invoice")?;
+    if let Status::Error { reason } = status {
+        bail!("Service refused to pay our invoice: {reason}");
+    }
+
+    tracing::info!(amount_sats, payment_hash = %invoice.payment_hash(), "LNURL-withdraw accepted");
+    publish(LnUrlProgress::WaitingForPayment { amount_sats });
+
+    Ok(())
+}
+
+async fn pay_inner(
+    request: &str,
+    amount_sats: u64,
+    comment: Option<String>,
+) -> Result<(), LnUrlPayError> {
+    publish(LnUrlProgress::FetchingParameters);
+    let pay = fetch_pay_request(request).await?;
+
+    let amount_msats = amount_sats * 1000;
+    if !(pay.min_sendable..=pay.max_sendable).contains(&amount_msats) {
+        return Err(LnUrlPayError::AmountOutOfRange {
+            min_sats: pay.min_sendable / 1000,
+            max_sats: pay.max_sendable / 1000,
+        });
+    }
+
+    let mut query = vec![("amount", amount_msats.to_string())];
+    if let Some(comment) = comment.filter(|comment| !comment.is_empty()) {
+        if comment.chars().count() > pay.comment_allowed as usize {
+            return Err(LnUrlPayError::CommentTooLong {
+                allowed: pay.comment_allowed,
+            });
+        }
+        query.push(("comment", comment));
+    }
+
+    publish(LnUrlProgress::RequestingInvoice { amount_sats });
+    let response = get_json::<PayResponse>(&pay.callback, &query)
+        .await
+        .map_err(LnUrlPayError::Service)?;
+    let invoice = Bolt11Invoice::from_str(&response.pr)
+        .map_err(|e| LnUrlPayError::InvalidInvoice(e.into()))?;
+
+    verify_invoice(&invoice, amount_msats, &pay.metadata)?;
+
+    publish(LnUrlProgress::Paying { amount_sats });
+    ln_dlc::pay_invoice(&invoice, None).map_err(LnUrlPayError::Payment)
+}
+
+/// The `text/plain` description in the metadata of an LNURL-pay.
+pub fn plain_text_description(metadata: &str) -> Option<String> {
+    let entries = serde_json::from_str::<Vec<(String, serde_json::Value)>>(metadata).ok()?;
+
+    entries
+        .into_iter()
+        .find(|(mime_type, _)| mime_type == "text/plain")
+        .and_then(|(_, content)| content.as_str().map(str::to_string))
+}
+
+/// Ensure that the invoice of an LNURL-pay is for the requested amount and commits to the
+/// metadata we were shown, as required by LUD-06.
+fn verify_invoice(
+    invoice: &Bolt11Invoice,
+    amount_msats: u64,
+    metadata: &str,
+) -> Result<(), LnUrlPayError> {
+    if invoice.amount_milli_satoshis() != Some(amount_msats) {
+        return Err(LnUrlPayError::AmountMismatch {
+            requested_msats: amount_msats,
+            actual_msats: invoice.amount_milli_satoshis(),
+        });
+    }
+
+    match invoice.description() {
+        Bolt11InvoiceDescription::Hash(hash)
+            if hash.0 == sha256::Hash::hash(metadata.as_bytes()) =>
+        {
+            Ok(())
+        }
+        _ => Err(LnUrlPayError::DescriptionHashMismatch),
+    }
+}
+
+/// GET the given URL with the additional query parameters, failing if the service responds with
+/// an error status.
+async fn get_json<T: DeserializeOwned>(url: &str, query: &[(&str, String)]) -> Result<T> {
+    let response = reqwest_client()
+        .get(url)
+        .query(query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+
+    if let Ok(Status::Error { reason }) = serde_json::from_value::<Status>(response.clone()) {
+        return Err(anyhow!("Service responded with an error: {reason}"));
+    }
+
+    let response = serde_json::from_value(response).context("Unexpected response")?;
+
+    Ok(response)
+}
+
+fn publish(progress: LnUrlProgress) {
+    event::publish(&EventInternal::LnUrlProgress(progress));
+}
+
+fn publish_if_failed<E: Display>(result: &Result<(), E>) {
+    if let Err(e) = result {
+        tracing::error!("LNURL flow failed: {e:#}");
+        publish(LnUrlProgress::Failed {
+            reason: format!("{e:#}"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_bech32_lnurl() {
+        // Example from LUD-01.
+        let lnurl = "LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS";
+
+        assert_eq!(
+            decode_url(lnurl).unwrap(),
+            "https://service.com/api?q=3fc3645b439ce8e7f2553a69e5267081d96dcd340693afabe04be7b0ccd178df"
+        );
+        assert_eq!(
+            decode_url(&format!("lightning:{lnurl}")).unwrap(),
+            decode_url(lnurl).unwrap()
+        );
+    }
+
+    #[test]
+    fn decodes_lud17_schemes() {
+        assert_eq!(
+            decode_url("lnurlw://service.com/withdraw?k1=abc").unwrap(),
+            "https://service.com/withdraw?k1=abc"
+        );
+        assert_eq!(
+            decode_url("lnurlp://abcdef.onion/pay").unwrap(),
+            "http://abcdef.onion/pay"
+        );
+    }
+
+    #[test]
+    fn decodes_lightning_address() {
+        assert_eq!(
+            decode_url("satoshi@service.com").unwrap(),
+            "https://service.com/.well-known/lnurlp/satoshi"
+        );
+        assert!(decode_url("Satoshi Nakamoto@service.com").is_err());
+    }
+
+    #[test]
+    fn recognises_lightning_address() {
+        assert!(is_lightning_address("satoshi@service.com"));
+        assert!(is_lightning_address("lightning:satoshi@service.com"));
+        assert!(!is_lightning_address("lnurlp://service.com/pay"));
+        assert!(!is_lightning_address(
+            "bitcoin:bc1qxyz?message=satoshi@service.com"
+        ));
+        assert!(!is_lightning_address("@service.com"));
+    }
+
+    #[test]
+    fn extracts_plain_text_description_from_metadata() {
+        let metadata = r#"[["text/plain","Pay to satoshi"],["image/png;base64","iVBORw0KGgo="]]"#;
+
+        assert_eq!(
+            plain_text_description(metadata).as_deref(),
+            Some("Pay to satoshi")
+        );
+        assert_eq!(plain_text_description("not json"), None);
+    }
+
+    #[test]
+    fn parses_parameters_by_tag() {
+        let parameters = serde_json::from_str::<Parameters>(
+            r#"{
+                "tag": "withdrawRequest",
+                "callback": "https://service.com/withdraw",
+                "k1": "abc",
+                "defaultDescription": "Withdrawal",
+                "minWithdrawable": 1000,
+                "maxWithdrawable": 100000
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(parameters, Parameters::Withdraw(withdraw) if withdraw.k1 == "abc"));
+
+        let status = serde_json::from_str::<Status>(r#"{"status":"ERROR","reason":"expired"}"#);
+        assert!(matches!(status, Ok(Status::Error { reason }) if reason == "expired"));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::backup;
+use crate::backup::RestoreSummary;
+use crate::cipher::AesCipher;
+use crate::ln_dlc;
+use crate::state;
+use anyhow::bail;
+use anyhow::ensure;
+use anyhow::Context;
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use time::OffsetDateTime;
+
+/// Identifies a backup archive and the version of its format.
+const ARCHIVE_PREFIX: &[u8] = b"10101BAK1";
+
+/// Identifies a QR code holding a part of a backup archive, as `10101BAK:<index>/<count>:<data>`.
+const QR_PART_PREFIX: &str = "10101BAK";
+
+/// The number of base64 characters per QR code part, so that each QR code can still be scanned
+/// reliably from a screen.
+const QR_PART_SIZE: usize = 1_000;
+
+/// A copy of everything which is backed up remotely, which the user can keep independently of
+/// the coordinator.
+#[derive(Serialize, Deserialize)]
+struct BackupArchive {
+    #[serde(with = "time::serde::timestamp")]
+    created_at: OffsetDateTime,
+    /// The plaintext values under their backup key, i.e. the Lightning state, the DLC state and the
+    /// 10101 database.
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+/// Writes an encrypted archive of the complete app state to `path`.
+pub fn export_to_file(path: &Path) -> Result<()> {
+    let archive = export()?;
+
+    fs::write(path, archive)
+        .with_context(|| format!("Failed to write backup archive to {}", path.display()))?;
+
+    tracing::info!(path = %path.display(), "Exported backup archive");
+
+    Ok(())
+}
+
+/// Returns an encrypted archive of the complete app state, split into parts which each fit into
+/// a QR code.
+pub fn export_to_qr_parts() -> Result<Vec<String>> {
+    let archive = BASE64.encode(export()?);
+
+    let chunks = archive
+        .as_bytes()
+        .chunks(QR_PART_SIZE)
+        .map(|chunk| String::from_utf8_lossy(chunk).to_string())
+        .collect::<Vec<_>>();
+
+    let count = chunks.len();
+    let parts = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("{QR_PART_PREFIX}:{}/{count}:{chunk}", i + 1))
+        .collect();
+
+    tracing::info!(parts = count, "Exported backup archive as QR codes");
+
+    Ok(parts)
+}
+
+/// Restores the app state from the backup archive at `path`.
+///
+/// Must be called after restoring the seed and before starting the node.
+pub fn import_from_file(path: &Path) -> Result<RestoreSummary> {
+    let archive = fs::read(path)
+        .with_context(|| format!("Failed to read backup archive from {}", path.display()))?;
+
+    import(archive)
+}
+
+/// Restores the app state from the scanned QR codes of a backup archive, in any order.
+///
+/// Must be called after restoring the seed and before starting the node.
+pub fn import_from_qr_parts(parts: Vec<String>) -> Result<RestoreSummary> {
+    let archive = join_qr_parts(parts)?;
+    let archive = BASE64
+        .decode(archive)
+        .context("Failed to decode backup archive")?;
+
+    import(archive)
+}
+
+fn export() -> Result<Vec<u8>> {
+    let entries = ln_dlc::get_storage()?.backup_entries()?;
+    let archive = BackupArchive {
+        created_at: OffsetDateTime::now_utc(),
+        entries,
+    };
+
+    let archive = backup::compress(&serde_json::to_vec(&archive)?)?;
+    let archive = cipher()?.encrypt(archive)?;
+
+    Ok([ARCHIVE_PREFIX, archive.as_slice()].concat())
+}
+
+fn import(archive: Vec<u8>) -> Result<RestoreSummary> {
+    ensure!(
+        state::try_get_node().is_none(),
+        "Cannot import backup archive while the node is running"
+    );
+
+    let archive = match archive.strip_prefix(ARCHIVE_PREFIX) {
+        Some(archive) => archive.to_vec(),
+        None => bail!("Not a 10101 backup archive"),
+    };
+
+    let archive = cipher()?
+        .decrypt(archive)
+        .context("Failed to decrypt backup archive. Was it created with a different seed?")?;
+    let archive: BackupArchive = serde_json::from_slice(&backup::decompress(archive)?)?;
+
+    tracing::info!(
+        created_at = %archive.created_at,
+        entries = archive.entries.len(),
+        "Importing backup archive"
+    );
+
+    let storage = ln_dlc::get_storage()?;
+
+    let mut summary = RestoreSummary::default();
+    for (key, value) in archive.entries {
+        match backup::write_restored_value(&storage.dlc_storage, &key, value) {
+            Ok(()) => summary.restored.push(key),
+            Err(e) => {
+                tracing::error!(key, "Failed to import backed up value: {e:#}");
+                summary.failed.push((key, format!("{e:#}")));
+            }
+        }
+    }
+
+    // The archive is restored in plaintext.
+    storage.encrypt_plaintext_values()?;
+
+    Ok(summary)
+}
+
+fn cipher() -> Result<AesCipher> {
+    Ok(AesCipher::new(ln_dlc::get_node_key()?))
+}
+
+/// Reassembles the base64 encoded archive from its QR code parts.
+fn join_qr_parts(parts: Vec<String>) -> Result<String> {
+    let mut chunks: Vec<Option<String>> = vec![];
+
+    for part in parts {
+        let mut fields = part.splitn(3, ':');
+        let (prefix, position, chunk) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(prefix), Some(position), Some(chunk)) => (prefix, position, chunk),
+            _ => bail!("Malformed backup QR code"),
+        };
+        ensure!(prefix == QR_PART_PREFIX, "Not a 10101 backup QR code");
+
+        let (index, count) = position
+            .split_once('/')
+            .context("Malformed backup QR code position")?;
+        let index = index.parse::<usize>()?;
+        let count = count.parse::<usize>()?;
+
+        if chunks.is_empty() {
+            chunks = vec![None; count];
+        }
+        ensure!(
+            chunks.len() == count,
+            "Backup QR codes belong to different archives"
+        );
+        ensure!(
+            (1..=count).contains(&index),
+            "Invalid backup QR code position {index}/{count}"
+        );
+
+        chunks[index - 1] = Some(chunk.to_string());
+    }
+
+    ensure!(!chunks.is_empty(), "No backup QR codes");
+
+    let missing = chunks.iter().filter(|chunk| chunk.is_none()).count();
+    ensure!(missing == 0, "{missing} backup QR codes are missing");
+
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qr_parts_are_joined_in_order() {
+        let parts = vec![
+            format!("{QR_PART_PREFIX}:2/3:def"),
+            format!("{QR_PART_PREFIX}:3/3:gh"),
+            format!("{QR_PART_PREFIX}:1/3:abc"),
+        ];
+
+        assert_eq!(join_qr_parts(parts).unwrap(), "abcdefgh");
+    }
+
+    #[test]
+    fn missing_qr_part_is_detected() {
+        let parts = vec![
+            format!("{QR_PART_PREFIX}:1/3:abc"),
+            format!("{QR_PART_PREFIX}:3/3:gh"),
+        ];
+
+        assert!(join_qr_parts(parts).is_err());
+    }
+}
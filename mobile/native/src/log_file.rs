@@ -0,0 +1,173 @@
+//! The log file of the native part of the app, which is rotated so that the logs don't eat up the
+//! storage of the phone.
+//!
+//! The logs are kept in `logs/native.log` of the data dir. Once the file exceeds
+//! [`MAX_LOG_FILE_SIZE`] it is moved to `native.log.1`, shifting the older files by one and
+//! dropping the oldest beyond [`MAX_ROTATED_LOG_FILES`].
+
+use anyhow::Context;
+use anyhow::Result;
+use parking_lot::const_mutex;
+use parking_lot::Mutex;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE: &str = "native.log";
+
+/// The size at which the log file is rotated.
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// How many rotated log files are kept next to the current one.
+const MAX_ROTATED_LOG_FILES: usize = 4;
+
+/// The log file is only known once the data dir is configured. Until then, logs are only sent to
+/// Flutter and stderr.
+static LOG_FILE_WRITER: Mutex<Option<RotatingFile>> = const_mutex(None);
+
+/// Start writing the logs to the log file in `data_dir`.
+pub fn init(data_dir: &Path) -> Result<()> {
+    let file = RotatingFile::open(data_dir.join(LOG_DIR), MAX_LOG_FILE_SIZE)?;
+    *LOG_FILE_WRITER.lock() = Some(file);
+
+    Ok(())
+}
+
+/// The log files, newest first.
+pub fn files() -> Vec<PathBuf> {
+    match LOG_FILE_WRITER.lock().as_ref() {
+        Some(file) => file.files(),
+        None => vec![],
+    }
+}
+
+/// Writes to the log file, if it has been initialised.
+pub struct LogFileWriter;
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match LOG_FILE_WRITER.lock().as_mut() {
+            Some(file) => file.append(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match LOG_FILE_WRITER.lock().as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+struct RotatingFile {
+    dir: PathBuf,
+    max_size: u64,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: PathBuf, max_size: u64) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create log dir {}", dir.display()))?;
+
+        let path = dir.join(LOG_FILE);
+        let file = open_append(&path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            dir,
+            max_size,
+            file,
+            size,
+        })
+    }
+
+    fn append(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let oldest = self.rotated_path(MAX_ROTATED_LOG_FILES);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+
+        for i in (1..MAX_ROTATED_LOG_FILES).rev() {
+            let path = self.rotated_path(i);
+            if path.exists() {
+                fs::rename(path, self.rotated_path(i + 1))?;
+            }
+        }
+
+        let current = self.dir.join(LOG_FILE);
+        fs::rename(&current, self.rotated_path(1))?;
+
+        self.file = open_append(&current)?;
+        self.size = 0;
+
+        Ok(())
+    }
+
+    fn rotated_path(&self, i: usize) -> PathBuf {
+        self.dir.join(format!("{LOG_FILE}.{i}"))
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        std::iter::once(self.dir.join(LOG_FILE))
+            .chain((1..=MAX_ROTATED_LOG_FILES).map(|i| self.rotated_path(i)))
+            .filter(|path| path.exists())
+            .collect()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn log_file_is_rotated_and_old_files_are_dropped() {
+        let dir = std::env::temp_dir().join(format!("10101-logs-{}", Uuid::new_v4()));
+        let mut file = RotatingFile::open(dir.clone(), 10).unwrap();
+
+        for i in 0..(MAX_ROTATED_LOG_FILES + 3) {
+            file.append(format!("line {i:05}\n").as_bytes()).unwrap();
+        }
+
+        let files = file.files();
+        assert_eq!(files.len(), MAX_ROTATED_LOG_FILES + 1);
+        assert_eq!(
+            fs::read_to_string(&files[0]).unwrap(),
+            format!("line {:05}\n", MAX_ROTATED_LOG_FILES + 2)
+        );
+        assert_eq!(
+            fs::read_to_string(&files[1]).unwrap(),
+            format!("line {:05}\n", MAX_ROTATED_LOG_FILES + 1)
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
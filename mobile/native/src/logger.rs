@@ -1,3 +1,5 @@
+use crate::error_report::ErrorReportLayer;
+use crate::log_file::LogFileWriter;
 use anyhow::Context;
 use anyhow::Result;
 use flutter_rust_bridge::StreamSink;
@@ -94,7 +96,7 @@ where
     }
 }
 
-struct Visitor<'a>(&'a mut BTreeMap<String, String>);
+pub(crate) struct Visitor<'a>(pub(crate) &'a mut BTreeMap<String, String>);
 
 impl<'a> tracing::field::Visit for Visitor<'a> {
     fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
@@ -161,10 +163,18 @@ pub fn init_tracing(level: LevelFilter, json_format: bool) -> Result<()> {
         fmt_layer.with_timer(time::UtcTime::rfc_3339()).boxed()
     };
 
+    // The log file is attached to the diagnostics, hence it is always plain text.
+    let log_file_layer = tracing_subscriber::fmt::layer()
+        .with_writer(|| LogFileWriter)
+        .with_ansi(false)
+        .with_timer(UtcTime::rfc_3339());
+
     tracing_subscriber::registry()
         .with(filter)
         .with(DartSendLayer)
         .with(fmt_layer)
+        .with(log_file_layer)
+        .with(ErrorReportLayer)
         .try_init()
         .context("Failed to init tracing")?;
 
@@ -1,4 +1,5 @@
 use crate::config;
+use crate::device;
 use crate::event;
 use crate::event::BackgroundTask;
 use crate::event::EventInternal;
@@ -6,7 +7,10 @@ use crate::event::TaskStatus;
 use crate::health::ServiceStatus;
 use crate::ln_dlc;
 use crate::state;
+use crate::trade::order;
+use crate::trade::order::FailureReason;
 use crate::trade::position;
+use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
@@ -14,6 +18,7 @@ use bdk::bitcoin::secp256k1::SecretKey;
 use bdk::bitcoin::secp256k1::SECP256K1;
 use bitcoin::hashes::hex::ToHex;
 use commons::best_current_price;
+use commons::Device;
 use commons::Message;
 use commons::Order;
 use commons::OrderbookRequest;
@@ -25,7 +30,6 @@ use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use time::OffsetDateTime;
 use tokio::runtime::Runtime;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::error::RecvError;
@@ -47,11 +51,12 @@ pub fn subscribe(
     runtime: &Runtime,
     orderbook_status: watch::Sender<ServiceStatus>,
     fcm_token: String,
+    device: Option<Device>,
     tx_websocket: broadcast::Sender<OrderbookRequest>,
 ) -> Result<()> {
     runtime.spawn(async move {
         let url = format!(
-            "ws://{}/api/orderbook/websocket",
+            "ws://{}/api/v1/orderbook/websocket",
             config::get_http_endpoint()
         );
 
@@ -70,11 +75,12 @@ pub fn subscribe(
                 loop {
                     {
                         tracing::debug!("Pruning expired orders");
+                        let now = state::get_clock().now();
                         let mut orders = orders.lock();
                         let orders_before_pruning = orders.len();
                         *orders = orders
                             .iter()
-                            .filter(|order| order.expiry >= OffsetDateTime::now_utc())
+                            .filter(|order| order.expiry >= now)
                             .cloned()
                             .collect::<Vec<_>>();
                         let orders_after_pruning = orders.len();
@@ -112,8 +118,14 @@ pub fn subscribe(
         loop {
             let url = url.clone();
             let fcm_token = fcm_token.clone();
-            match orderbook_client::subscribe_with_authentication(url, authenticate, fcm_token)
-                .await
+            let device = device.clone();
+            match orderbook_client::subscribe_with_authentication(
+                url,
+                authenticate,
+                fcm_token,
+                device,
+            )
+            .await
             {
                 Ok((mut sink, mut stream)) => {
                     if let Err(e) = orderbook_status.send(ServiceStatus::Online) {
@@ -333,7 +345,41 @@ async fn handle_orderbook_message(
                 ));
             }
         }
-        msg @ Message::LimitOrderFilledMatches { .. } | msg @ Message::InvalidAuthentication(_) => {
+        Message::MatchReverted { order_id, reason } => {
+            tracing::warn!(%order_id, %reason, "Coordinator reverted match");
+
+            order::handler::order_failed(
+                Some(order_id),
+                FailureReason::FillingTimedOut,
+                anyhow!("Match was reverted by the coordinator: {reason}"),
+            )?;
+        }
+        Message::TradingHalted(reason) => {
+            // New orders will be rejected by the coordinator with the same reason.
+            tracing::warn!(%reason, "Trading has been halted by the coordinator");
+        }
+        Message::TradingResumed => {
+            tracing::info!("Trading has been resumed by the coordinator");
+        }
+        Message::SessionSuperseded => {
+            tracing::error!(
+                "Another device is using our seed. Not persisting our channel state anymore"
+            );
+
+            if let Err(e) = device::mark_superseded() {
+                tracing::error!("Failed to remember that our session has been superseded: {e:#}");
+            }
+
+            event::publish(&EventInternal::SessionSuperseded);
+        }
+        Message::ChannelOpenStatus { channel_id, status } => {
+            // Our own node tracks the confirmations of the channel as well, see
+            // `track_channel_status`.
+            tracing::info!(%channel_id, ?status, "Coordinator reported channel open status");
+        }
+        msg @ Message::LimitOrderFilledMatches { .. }
+        | msg @ Message::InvalidAuthentication(_)
+        | msg @ Message::QuoteUpdate(_) => {
             tracing::debug!(?msg, "Skipping message from orderbook");
         }
     };
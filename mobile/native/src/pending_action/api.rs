@@ -0,0 +1,107 @@
+use crate::pending_action;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use flutter_rust_bridge::frb;
+use ln_dlc_node::node::rust_dlc_manager::DlcChannelId;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// A pending action the user can either retry or abort.
+///
+/// Please refer to [`crate::pending_action::PendingAction`]
+#[frb]
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    DlcChannelOffer { channel_id: String },
+    DlcChannelSettleOffer { channel_id: String },
+    DlcChannelRenewOffer { channel_id: String },
+    DlcChannelCollaborativeCloseOffer { channel_id: String },
+    UnprocessedMatch { order_id: String },
+    OrderInFilling { order_id: String },
+}
+
+impl From<pending_action::PendingAction> for PendingAction {
+    fn from(value: pending_action::PendingAction) -> Self {
+        match value {
+            pending_action::PendingAction::DlcChannelOffer { channel_id } => {
+                PendingAction::DlcChannelOffer {
+                    channel_id: channel_id.to_hex(),
+                }
+            }
+            pending_action::PendingAction::DlcChannelSettleOffer { channel_id } => {
+                PendingAction::DlcChannelSettleOffer {
+                    channel_id: channel_id.to_hex(),
+                }
+            }
+            pending_action::PendingAction::DlcChannelRenewOffer { channel_id } => {
+                PendingAction::DlcChannelRenewOffer {
+                    channel_id: channel_id.to_hex(),
+                }
+            }
+            pending_action::PendingAction::DlcChannelCollaborativeCloseOffer { channel_id } => {
+                PendingAction::DlcChannelCollaborativeCloseOffer {
+                    channel_id: channel_id.to_hex(),
+                }
+            }
+            pending_action::PendingAction::UnprocessedMatch { order_id } => {
+                PendingAction::UnprocessedMatch {
+                    order_id: order_id.to_string(),
+                }
+            }
+            pending_action::PendingAction::OrderInFilling { order_id } => {
+                PendingAction::OrderInFilling {
+                    order_id: order_id.to_string(),
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<PendingAction> for pending_action::PendingAction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: PendingAction) -> Result<Self> {
+        let pending_action = match value {
+            PendingAction::DlcChannelOffer { channel_id } => {
+                pending_action::PendingAction::DlcChannelOffer {
+                    channel_id: parse_dlc_channel_id(&channel_id)?,
+                }
+            }
+            PendingAction::DlcChannelSettleOffer { channel_id } => {
+                pending_action::PendingAction::DlcChannelSettleOffer {
+                    channel_id: parse_dlc_channel_id(&channel_id)?,
+                }
+            }
+            PendingAction::DlcChannelRenewOffer { channel_id } => {
+                pending_action::PendingAction::DlcChannelRenewOffer {
+                    channel_id: parse_dlc_channel_id(&channel_id)?,
+                }
+            }
+            PendingAction::DlcChannelCollaborativeCloseOffer { channel_id } => {
+                pending_action::PendingAction::DlcChannelCollaborativeCloseOffer {
+                    channel_id: parse_dlc_channel_id(&channel_id)?,
+                }
+            }
+            PendingAction::UnprocessedMatch { order_id } => {
+                pending_action::PendingAction::UnprocessedMatch {
+                    order_id: Uuid::from_str(&order_id)?,
+                }
+            }
+            PendingAction::OrderInFilling { order_id } => {
+                pending_action::PendingAction::OrderInFilling {
+                    order_id: Uuid::from_str(&order_id)?,
+                }
+            }
+        };
+
+        Ok(pending_action)
+    }
+}
+
+pub(crate) fn parse_dlc_channel_id(channel_id: &str) -> Result<DlcChannelId> {
+    hex::decode(channel_id)?
+        .try_into()
+        .ok()
+        .with_context(|| format!("Invalid DLC channel id {channel_id}"))
+}
@@ -0,0 +1,289 @@
+use crate::config;
+use crate::db;
+use crate::dlc_handler::DlcHandler;
+use crate::state;
+use crate::trade::order;
+use crate::trade::order::FailureReason;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::secp256k1::SECP256K1;
+use commons::OrderbookRequest;
+use ln_dlc_node::node::rust_dlc_manager::channel::offered_channel::OfferedChannel;
+use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannel;
+use ln_dlc_node::node::rust_dlc_manager::channel::signed_channel::SignedChannelState;
+use ln_dlc_node::node::rust_dlc_manager::channel::Channel;
+use ln_dlc_node::node::rust_dlc_manager::contract::Contract;
+use ln_dlc_node::node::rust_dlc_manager::DlcChannelId;
+use ln_dlc_node::node::rust_dlc_manager::Storage as DlcStorage;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub mod api;
+
+/// Something that has to happen before the app can continue trading, but which got stuck.
+///
+/// Every pending action can be retried or aborted through [`retry`] and [`abort`] respectively,
+/// which allows the user to recover from a wedged protocol state without having to reinstall the
+/// app.
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    /// A DLC channel offer from the coordinator which we have not accepted yet.
+    DlcChannelOffer { channel_id: DlcChannelId },
+    /// An offer from the coordinator to settle the DLC channel, i.e. to close the position.
+    DlcChannelSettleOffer { channel_id: DlcChannelId },
+    /// An offer from the coordinator to renew the DLC channel, e.g. to roll over the position.
+    DlcChannelRenewOffer { channel_id: DlcChannelId },
+    /// An offer from the coordinator to collaboratively close the DLC channel.
+    DlcChannelCollaborativeCloseOffer { channel_id: DlcChannelId },
+    /// An order got matched, but the DLC protocol to execute the trade was never started.
+    UnprocessedMatch { order_id: Uuid },
+    /// An order is in `Filling` while the DLC protocol is waiting for the coordinator.
+    OrderInFilling { order_id: Uuid },
+}
+
+impl PendingAction {
+    /// The ID of the DLC channel the action refers to, if any.
+    pub fn dlc_channel_id(&self) -> Option<DlcChannelId> {
+        match self {
+            PendingAction::DlcChannelOffer { channel_id }
+            | PendingAction::DlcChannelSettleOffer { channel_id }
+            | PendingAction::DlcChannelRenewOffer { channel_id }
+            | PendingAction::DlcChannelCollaborativeCloseOffer { channel_id } => Some(*channel_id),
+            PendingAction::UnprocessedMatch { .. } | PendingAction::OrderInFilling { .. } => None,
+        }
+    }
+}
+
+/// Collect all [`PendingAction`]s the user might have to act upon.
+pub fn get_pending_actions() -> Result<Vec<PendingAction>> {
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+
+    let dlc_channels = node.inner.list_dlc_channels()?;
+
+    let mut pending_actions = dlc_channels
+        .iter()
+        .filter_map(pending_dlc_channel_offer)
+        .collect::<Vec<_>>();
+
+    if let Some(order) = db::get_order_in_filling()? {
+        let is_protocol_in_progress = dlc_channels.iter().any(is_dlc_protocol_in_progress);
+
+        if !is_protocol_in_progress {
+            pending_actions.push(PendingAction::UnprocessedMatch { order_id: order.id });
+        } else if pending_actions.is_empty() {
+            pending_actions.push(PendingAction::OrderInFilling { order_id: order.id });
+        }
+    }
+
+    tracing::debug!(?pending_actions, "Collected pending actions");
+
+    Ok(pending_actions)
+}
+
+/// Find the DLC offer on the DLC channel with the given ID, which is waiting for the user to
+/// accept or reject it.
+pub fn get_pending_dlc_offer(channel_id: &DlcChannelId) -> Result<PendingAction> {
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+
+    node.inner
+        .list_dlc_channels()?
+        .iter()
+        .filter_map(pending_dlc_channel_offer)
+        .find(|pending_action| pending_action.dlc_channel_id() == Some(*channel_id))
+        .with_context(|| format!("No pending DLC offer on channel {}", channel_id.to_hex()))
+}
+
+/// Retry the given [`PendingAction`], i.e. continue with the protocol where it got stuck.
+pub fn retry(pending_action: PendingAction) -> Result<()> {
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+
+    tracing::info!(?pending_action, "Retrying pending action");
+
+    match pending_action {
+        PendingAction::DlcChannelOffer { channel_id } => {
+            node.inner.accept_dlc_channel_offer(&channel_id)?;
+        }
+        PendingAction::DlcChannelSettleOffer { channel_id } => {
+            node.inner
+                .accept_dlc_channel_collaborative_settlement(&channel_id)?;
+        }
+        PendingAction::DlcChannelRenewOffer { channel_id } => {
+            let expiry_timestamp = get_expiry_of_renew_offer(&channel_id)?;
+            node.accept_dlc_channel_renew_offer(&channel_id, expiry_timestamp)?;
+        }
+        PendingAction::DlcChannelCollaborativeCloseOffer { channel_id } => {
+            node.inner
+                .accept_dlc_channel_collaborative_close(&channel_id)?;
+        }
+        PendingAction::UnprocessedMatch { .. } => {
+            // The coordinator sends all pending matches to the user after a successful
+            // authentication.
+            reauthenticate()?;
+        }
+        PendingAction::OrderInFilling { .. } => {
+            let coordinator = config::get_coordinator_info().pubkey;
+            DlcHandler::new(node.inner.clone()).on_connect(coordinator)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Abort the given [`PendingAction`].
+///
+/// Only actions which have not (yet) changed the DLC channel can be aborted. Offers on an
+/// established DLC channel can only be retried.
+pub fn abort(pending_action: PendingAction) -> Result<()> {
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+
+    tracing::info!(?pending_action, "Aborting pending action");
+
+    match pending_action {
+        PendingAction::DlcChannelOffer { channel_id } => {
+            node.inner.reject_dlc_channel_offer(&channel_id)?;
+
+            order::handler::order_failed(
+                None,
+                FailureReason::Aborted,
+                anyhow!("User rejected pending DLC channel offer"),
+            )?;
+        }
+        PendingAction::DlcChannelSettleOffer { channel_id }
+        | PendingAction::DlcChannelRenewOffer { channel_id }
+        | PendingAction::DlcChannelCollaborativeCloseOffer { channel_id } => {
+            bail!(
+                "Cannot abort pending offer on established DLC channel {}",
+                channel_id.to_hex()
+            );
+        }
+        PendingAction::UnprocessedMatch { order_id }
+        | PendingAction::OrderInFilling { order_id } => {
+            order::handler::order_failed(
+                Some(order_id),
+                FailureReason::Aborted,
+                anyhow!("User aborted order stuck in filling"),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn pending_dlc_channel_offer(channel: &Channel) -> Option<PendingAction> {
+    let pending_action = match channel {
+        Channel::Offered(OfferedChannel {
+            temporary_channel_id,
+            is_offer_party: false,
+            ..
+        }) => PendingAction::DlcChannelOffer {
+            channel_id: *temporary_channel_id,
+        },
+        Channel::Signed(SignedChannel {
+            channel_id,
+            state: SignedChannelState::SettledReceived { .. },
+            ..
+        }) => PendingAction::DlcChannelSettleOffer {
+            channel_id: *channel_id,
+        },
+        Channel::Signed(SignedChannel {
+            channel_id,
+            state:
+                SignedChannelState::RenewOffered {
+                    is_offer: false, ..
+                },
+            ..
+        }) => PendingAction::DlcChannelRenewOffer {
+            channel_id: *channel_id,
+        },
+        Channel::Signed(SignedChannel {
+            channel_id,
+            state: SignedChannelState::CollaborativeCloseOffered { .. },
+            ..
+        }) => PendingAction::DlcChannelCollaborativeCloseOffer {
+            channel_id: *channel_id,
+        },
+        _ => return None,
+    };
+
+    Some(pending_action)
+}
+
+/// Whether the DLC channel is in the midst of a protocol execution.
+pub(crate) fn is_dlc_protocol_in_progress(channel: &Channel) -> bool {
+    match channel {
+        Channel::Offered(_) | Channel::Accepted(_) => true,
+        Channel::Signed(SignedChannel { state, .. }) => !matches!(
+            state,
+            SignedChannelState::Established { .. }
+                | SignedChannelState::Settled { .. }
+                | SignedChannelState::Closing { .. }
+        ),
+        _ => false,
+    }
+}
+
+fn get_expiry_of_renew_offer(channel_id: &DlcChannelId) -> Result<OffsetDateTime> {
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+
+    let channel = node.inner.get_dlc_channel_by_id(channel_id)?;
+    let offered_contract_id = match channel {
+        Channel::Signed(SignedChannel {
+            state:
+                SignedChannelState::RenewOffered {
+                    offered_contract_id,
+                    ..
+                },
+            ..
+        }) => offered_contract_id,
+        _ => bail!("DLC channel {} is not renew offered", channel_id.to_hex()),
+    };
+
+    let contract = node
+        .inner
+        .dlc_manager
+        .get_store()
+        .get_contract(&offered_contract_id)?
+        .context("Could not find offered contract of renew offer")?;
+
+    let offered_contract = match contract {
+        Contract::Offered(offered_contract) => offered_contract,
+        _ => bail!("Contract of renew offer is not in offered state"),
+    };
+
+    let maturity = offered_contract
+        .contract_info
+        .first()
+        .and_then(|contract_info| contract_info.oracle_announcements.first())
+        .map(|announcement| announcement.oracle_event.event_maturity_epoch)
+        .context("Offered contract without oracle announcement")?;
+
+    let expiry_timestamp = OffsetDateTime::from_unix_timestamp(maturity as i64)?;
+
+    Ok(expiry_timestamp)
+}
+
+/// Authenticate with the orderbook again to trigger the coordinator to send us any pending
+/// matches.
+fn reauthenticate() -> Result<()> {
+    let tx_websocket = state::try_get_websocket().context("Not connected to orderbook")?;
+
+    let node_key = crate::ln_dlc::get_node_key()?;
+    let signature =
+        orderbook_client::create_auth_message_signature(move |msg| commons::Signature {
+            pubkey: node_key.public_key(SECP256K1),
+            signature: node_key.sign_ecdsa(msg),
+        });
+
+    tx_websocket
+        .send(OrderbookRequest::Authenticate {
+            fcm_token: state::try_get_fcm_token().filter(|token| !token.is_empty()),
+            signature,
+            device: crate::device::get_device().ok(),
+        })
+        .map_err(|e| anyhow!("Failed to send authentication message: {e:#}"))?;
+
+    Ok(())
+}
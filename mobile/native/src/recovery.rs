@@ -0,0 +1,171 @@
+use crate::channel_backup;
+use crate::cipher::AesCipher;
+use crate::commons::reqwest_client;
+use crate::config;
+use crate::ln_dlc;
+use crate::state;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use commons::RecoverableChannelKind;
+use commons::RecoveryInfo;
+use ln_dlc_node::node::SyncKind;
+use std::collections::HashSet;
+
+/// What could be recovered of the funds of a user who has lost their backup.
+#[derive(Debug)]
+pub struct RecoveryReport {
+    /// The balance of the on-chain wallet after rescanning the chain.
+    pub on_chain_balance_sats: u64,
+    pub channels: Vec<ChannelRecovery>,
+}
+
+#[derive(Debug)]
+pub struct ChannelRecovery {
+    /// The hex encoded ID of the channel.
+    pub channel_id: String,
+    pub kind: RecoverableChannelKind,
+    /// The funds in the channel, as far as the coordinator knows.
+    pub balance_sats: u64,
+    pub status: ChannelRecoveryStatus,
+}
+
+#[derive(Debug)]
+pub enum ChannelRecoveryStatus {
+    /// The channel is known to our node, so nothing has to be recovered.
+    Known,
+    /// The coordinator is force-closing the channel. Our funds will be paid to our on-chain
+    /// wallet.
+    ForceCloseRequested,
+    /// The coordinator could not be asked to force-close the channel.
+    ForceCloseFailed { reason: String },
+    /// The state of the channel is lost, so our node cannot claim our funds once the channel is
+    /// closed.
+    NotRecoverable,
+}
+
+/// Recovers the funds of a user who has lost their backup, from the seed only.
+///
+/// Must be called once the node has been started on top of the restored seed. The chain is
+/// rescanned for the funds of our on-chain wallet, and the coordinator is asked for the channels
+/// it has with us.
+///
+/// Only the DLC channels can be reconstructed, by asking the coordinator to force-close them, as
+/// their payout goes to our on-chain wallet. Lightning channels cannot be recovered without their
+/// channel monitors, so they are only reported.
+pub async fn recover_without_backup() -> Result<RecoveryReport> {
+    let node = state::get_node();
+
+    tracing::info!("Rescanning the chain to recover on-chain funds");
+
+    tokio::task::spawn_blocking({
+        let node = node.clone();
+        move || {
+            node.inner
+                .sync_on_chain_wallet_with_progress(SyncKind::Full)
+        }
+    })
+    .await
+    .expect("task to complete")
+    .context("Failed to rescan on-chain wallet")?;
+
+    let on_chain_balance_sats = node.inner.get_on_chain_balance()?.get_total();
+
+    let known_channels = node
+        .inner
+        .list_channels()
+        .into_iter()
+        .map(|channel| hex::encode(channel.channel_id.0))
+        .chain(
+            node.inner
+                .list_signed_dlc_channels()?
+                .into_iter()
+                .map(|channel| hex::encode(channel.channel_id)),
+        )
+        .collect::<HashSet<_>>();
+
+    let cipher = AesCipher::new(ln_dlc::get_node_key()?);
+
+    let recovery_info = fetch_recovery_info().await?;
+
+    let mut channels = vec![];
+    for channel in recovery_info.channels {
+        let status = if known_channels.contains(&channel.channel_id) {
+            ChannelRecoveryStatus::Known
+        } else {
+            match channel.kind {
+                RecoverableChannelKind::Dlc => {
+                    match channel_backup::request_force_close(&cipher, &channel.channel_id).await {
+                        Ok(()) => ChannelRecoveryStatus::ForceCloseRequested,
+                        Err(e) => {
+                            tracing::error!(
+                                channel_id = channel.channel_id,
+                                "Failed to request force-close of DLC channel: {e:#}"
+                            );
+                            ChannelRecoveryStatus::ForceCloseFailed {
+                                reason: format!("{e:#}"),
+                            }
+                        }
+                    }
+                }
+                RecoverableChannelKind::Lightning => {
+                    tracing::warn!(
+                        channel_id = channel.channel_id,
+                        balance_sats = channel.balance_sats,
+                        "Cannot recover Lightning channel without backup"
+                    );
+                    ChannelRecoveryStatus::NotRecoverable
+                }
+            }
+        };
+
+        channels.push(ChannelRecovery {
+            channel_id: channel.channel_id,
+            kind: channel.kind,
+            balance_sats: channel.balance_sats,
+            status,
+        });
+    }
+
+    tracing::info!(
+        on_chain_balance_sats,
+        channels = channels.len(),
+        "Recovered from seed without backup"
+    );
+
+    Ok(RecoveryReport {
+        on_chain_balance_sats,
+        channels,
+    })
+}
+
+async fn fetch_recovery_info() -> Result<RecoveryInfo> {
+    let node_id = ln_dlc::get_node_pubkey()?;
+    let message = commons::create_sign_message(node_id.to_string().as_bytes().to_vec());
+    let signature = ln_dlc::get_node_key()?.sign_ecdsa(message);
+
+    let response = reqwest_client()
+        .get(format!(
+            "http://{}/api/v1/recovery/{node_id}",
+            config::get_http_endpoint()
+        ))
+        .json(&signature)
+        .send()
+        .await
+        .context("Failed to fetch recovery information from coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        bail!("Could not fetch recovery information from coordinator: {response_text}");
+    }
+
+    response
+        .json()
+        .await
+        .context("Failed to parse recovery information")
+}
@@ -0,0 +1,211 @@
+use anyhow::Result;
+use bitcoin::secp256k1::rand;
+use bitcoin::secp256k1::rand::Rng;
+use parking_lot::const_mutex;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::runtime::Runtime;
+use tokio::sync::watch;
+
+/// The maximum fraction of its interval by which the next run of a task is delayed at random, so
+/// that tasks with the same interval do not all run at once.
+const MAX_JITTER: f64 = 0.1;
+
+static TASKS: Mutex<BTreeMap<&'static str, TaskStatus>> = const_mutex(BTreeMap::new());
+
+/// Whether the background tasks are paused, see [`pause`].
+static PAUSED: OnceLock<watch::Sender<bool>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct TaskStatus {
+    pub name: &'static str,
+    /// How often the task runs, or `None` for a service which runs continuously.
+    pub interval: Option<Duration>,
+    pub state: TaskState,
+    pub last_run: Option<OffsetDateTime>,
+    /// The error of the last run, if it failed.
+    pub last_error: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    /// The task waits for its next run.
+    Waiting,
+    Running,
+    Paused,
+    /// The service has stopped and will not run again.
+    Stopped,
+}
+
+/// Runs `task` every `interval`, plus some jitter, while the background tasks are not paused.
+///
+/// A failed run is recorded in the status of the task, which is run again after the interval.
+pub fn spawn_periodic<F, Fut>(runtime: &Runtime, name: &'static str, interval: Duration, task: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    spawn_periodic_with(runtime, name, move || interval, task)
+}
+
+/// Like [`spawn_periodic`], but the interval is looked up again after every run, e.g. so that it
+/// follows changes to the config.
+pub fn spawn_periodic_with<I, F, Fut>(runtime: &Runtime, name: &'static str, interval: I, task: F)
+where
+    I: Fn() -> Duration + Send + 'static,
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    register(name, Some(interval()));
+
+    runtime.spawn(async move {
+        loop {
+            wait_until_resumed(name).await;
+
+            set_state(name, TaskState::Running);
+            let result = task().await;
+            record_run(name, result);
+
+            let interval = interval();
+            set_interval(name, interval);
+
+            tokio::time::sleep(with_jitter(interval)).await;
+        }
+    });
+}
+
+/// Runs `service` until it completes.
+///
+/// A service runs continuously, e.g. to react to events, and is expected to call
+/// [`wait_until_resumed`] to stop doing work while the background tasks are paused.
+pub fn spawn_service<Fut>(runtime: &Runtime, name: &'static str, service: Fut)
+where
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    register(name, None);
+    set_state(name, TaskState::Running);
+
+    runtime.spawn(async move {
+        service.await;
+
+        tracing::warn!(task = name, "Background service stopped");
+        set_state(name, TaskState::Stopped);
+    });
+}
+
+/// Returns immediately unless the background tasks are paused, in which case it waits until
+/// they are resumed.
+pub async fn wait_until_resumed(name: &'static str) {
+    let mut paused = paused().subscribe();
+    if !*paused.borrow_and_update() {
+        return;
+    }
+
+    tracing::debug!(task = name, "Pausing background task");
+    set_state(name, TaskState::Paused);
+
+    while *paused.borrow_and_update() {
+        if paused.changed().await.is_err() {
+            break;
+        }
+    }
+
+    tracing::debug!(task = name, "Resuming background task");
+    set_state(name, TaskState::Running);
+}
+
+/// Pauses the background tasks, e.g. because the OS has put the app into the background.
+///
+/// Runs which have already started are completed.
+pub fn pause() {
+    tracing::info!("Pausing background tasks");
+    paused().send_replace(true);
+}
+
+pub fn resume() {
+    tracing::info!("Resuming background tasks");
+    paused().send_replace(false);
+}
+
+/// The status of all background tasks, ordered by name.
+pub fn status() -> Vec<TaskStatus> {
+    TASKS.lock().values().cloned().collect()
+}
+
+fn paused() -> &'static watch::Sender<bool> {
+    PAUSED.get_or_init(|| watch::channel(false).0)
+}
+
+fn register(name: &'static str, interval: Option<Duration>) {
+    TASKS.lock().insert(
+        name,
+        TaskStatus {
+            name,
+            interval,
+            state: TaskState::Waiting,
+            last_run: None,
+            last_error: None,
+            runs: 0,
+            failures: 0,
+        },
+    );
+}
+
+fn set_state(name: &'static str, state: TaskState) {
+    if let Some(task) = TASKS.lock().get_mut(name) {
+        task.state = state;
+    }
+}
+
+fn set_interval(name: &'static str, interval: Duration) {
+    if let Some(task) = TASKS.lock().get_mut(name) {
+        task.interval = Some(interval);
+    }
+}
+
+fn record_run(name: &'static str, result: Result<()>) {
+    let mut tasks = TASKS.lock();
+    let task = match tasks.get_mut(name) {
+        Some(task) => task,
+        None => return,
+    };
+
+    task.state = TaskState::Waiting;
+    task.last_run = Some(OffsetDateTime::now_utc());
+    task.runs += 1;
+
+    match result {
+        Ok(()) => task.last_error = None,
+        Err(e) => {
+            tracing::error!(task = name, "Background task failed: {e:#}");
+            task.failures += 1;
+            task.last_error = Some(format!("{e:#}"));
+        }
+    }
+}
+
+fn with_jitter(interval: Duration) -> Duration {
+    interval.mul_f64(1.0 + rand::thread_rng().gen_range(0.0..MAX_JITTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let interval = Duration::from_secs(10);
+
+        for _ in 0..100 {
+            let delay = with_jitter(interval);
+            assert!(delay >= interval);
+            assert!(delay < interval.mul_f64(1.0 + MAX_JITTER));
+        }
+    }
+}
@@ -1,5 +1,14 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    address_book (address) {
+        address -> Text,
+        label -> Text,
+        created_at -> BigInt,
+        last_used_at -> Nullable<BigInt>,
+    }
+}
+
 diesel::table! {
     channels (user_channel_id) {
         user_channel_id -> Text,
@@ -27,15 +36,6 @@ diesel::table! {
     }
 }
 
-diesel::table! {
-    last_outbound_dlc_messages (peer_id) {
-        peer_id -> Text,
-        message_hash -> Text,
-        message -> Text,
-        timestamp -> BigInt,
-    }
-}
-
 diesel::table! {
     orders (id) {
         id -> Text,
@@ -52,6 +52,19 @@ diesel::table! {
         order_expiry_timestamp -> BigInt,
         reason -> Text,
         stable -> Bool,
+        reduce_only -> Bool,
+        filling_timestamp -> Nullable<BigInt>,
+        position_id -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    outbound_dlc_messages (id) {
+        id -> Integer,
+        message_hash -> Text,
+        peer_id -> Text,
+        message -> Text,
+        timestamp -> BigInt,
     }
 }
 
@@ -70,11 +83,15 @@ diesel::table! {
         invoice -> Nullable<Text>,
         fee_msat -> Nullable<BigInt>,
         funding_txid -> Nullable<Text>,
+        expiry_timestamp -> Nullable<BigInt>,
+        payee_pubkey -> Nullable<Text>,
+        failure_reason -> Nullable<Text>,
     }
 }
 
 diesel::table! {
-    positions (contract_symbol) {
+    positions (id) {
+        id -> Text,
         contract_symbol -> Text,
         leverage -> Float,
         quantity -> Float,
@@ -123,13 +140,14 @@ diesel::table! {
     }
 }
 
-diesel::joinable!(last_outbound_dlc_messages -> dlc_messages (message_hash));
+diesel::joinable!(outbound_dlc_messages -> dlc_messages (message_hash));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    address_book,
     channels,
     dlc_messages,
-    last_outbound_dlc_messages,
     orders,
+    outbound_dlc_messages,
     payments,
     positions,
     spendable_outputs,
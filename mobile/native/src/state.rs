@@ -3,8 +3,11 @@ use crate::ln_dlc::node::Node;
 use crate::logger::LogEntry;
 use crate::storage::TenTenOneNodeStorage;
 use anyhow::Result;
+use commons::Clock;
 use commons::LspConfig;
 use commons::OrderbookRequest;
+use commons::Prices;
+use commons::SystemClock;
 use flutter_rust_bridge::StreamSink;
 use ln_dlc_node::seed::Bip39Seed;
 use parking_lot::RwLock;
@@ -26,6 +29,9 @@ static RUNTIME: Storage<Runtime> = Storage::new();
 static WEBSOCKET: Storage<RwLock<Sender<OrderbookRequest>>> = Storage::new();
 static LOG_STREAM_SINK: Storage<RwLock<Arc<StreamSink<LogEntry>>>> = Storage::new();
 static LSP_CONFIG: Storage<RwLock<LspConfig>> = Storage::new();
+static FCM_TOKEN: Storage<RwLock<String>> = Storage::new();
+static PRICES: Storage<RwLock<Prices>> = Storage::new();
+static CLOCK: Storage<RwLock<Arc<dyn Clock>>> = Storage::new();
 
 pub fn set_config(config: ConfigInternal) {
     match CONFIG.try_get() {
@@ -146,3 +152,54 @@ pub fn set_lsp_config(lsp_config: LspConfig) {
 pub fn try_get_lsp_config() -> Option<LspConfig> {
     LSP_CONFIG.try_get().map(|w| w.read().clone())
 }
+
+pub fn set_fcm_token(fcm_token: String) {
+    match FCM_TOKEN.try_get() {
+        None => {
+            FCM_TOKEN.set(RwLock::new(fcm_token));
+        }
+        Some(s) => {
+            *s.write() = fcm_token;
+        }
+    }
+}
+
+pub fn try_get_fcm_token() -> Option<String> {
+    FCM_TOKEN.try_get().map(|w| w.read().clone())
+}
+
+pub fn set_prices(prices: Prices) {
+    match PRICES.try_get() {
+        None => {
+            PRICES.set(RwLock::new(prices));
+        }
+        Some(p) => {
+            *p.write() = prices;
+        }
+    }
+}
+
+pub fn try_get_prices() -> Option<Prices> {
+    PRICES.try_get().map(|p| p.read().clone())
+}
+
+/// Replace the clock used for expiries and rollovers, e.g. to fast-forward time in tests.
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    match CLOCK.try_get() {
+        None => {
+            CLOCK.set(RwLock::new(clock));
+        }
+        Some(c) => {
+            *c.write() = clock;
+        }
+    }
+}
+
+/// The clock used for expiries and rollovers, which is the system time unless it has been
+/// replaced with [`set_clock`].
+pub fn get_clock() -> Arc<dyn Clock> {
+    CLOCK
+        .try_get()
+        .map(|c| c.read().clone())
+        .unwrap_or_else(|| Arc::new(SystemClock))
+}
@@ -1,3 +1,4 @@
+use crate::backup::BackupVerification;
 use crate::backup::RemoteBackupClient;
 use crate::backup::DB_BACKUP_KEY;
 use crate::backup::DB_BACKUP_NAME;
@@ -5,20 +6,60 @@ use crate::backup::DLC_BACKUP_KEY;
 use crate::backup::LN_BACKUP_KEY;
 use crate::cipher::AesCipher;
 use crate::db;
+use crate::device;
+use anyhow::anyhow;
+use anyhow::Context;
 use anyhow::Result;
 use bitcoin::secp256k1::SecretKey;
 use bitcoin::Network;
 use lightning::util::persist::KVStore;
+use lightning::util::persist::CHANNEL_MANAGER_PERSISTENCE_KEY;
+use lightning::util::persist::CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE;
+use lightning::util::persist::CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE;
+use lightning::util::persist::CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE;
+use lightning::util::persist::CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE;
 use lightning_persister::fs_store::FilesystemStore;
+use ln_dlc_node::seed::StorageEncryptionKey;
 use ln_dlc_storage::sled::SledStorageProvider;
+use ln_dlc_storage::DlcStorageProvider;
 use ln_dlc_storage::DlcStoreProvider;
 use ln_dlc_storage::KeyValue;
+use std::cell::RefCell;
 use std::fs;
 use std::io::Error;
+use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+/// Marks a value which has been encrypted with the storage encryption key.
+///
+/// Values without this prefix have been written before the storage was encrypted, or restored from
+/// a backup, and are read as they are.
+const ENCRYPTED_VALUE_PREFIX: &[u8] = b"10101ENC";
+
+thread_local! {
+    /// The previous values of the DLC storage entries changed by the unit of work running on this
+    /// thread, if any.
+    static DLC_UNDO_LOG: RefCell<Option<Vec<UndoEntry>>> = RefCell::new(None);
+
+    /// The remote backups of the DLC storage entries changed by the unit of work running on this
+    /// thread, if any. They are only sent once the unit of work has been committed.
+    static PENDING_DLC_BACKUPS: RefCell<Option<Vec<PendingBackup>>> = RefCell::new(None);
+}
+
+struct UndoEntry {
+    kind: u8,
+    key: Vec<u8>,
+    /// The value before the unit of work started, [`None`] if the entry did not exist.
+    value: Option<Vec<u8>>,
+}
+
+enum PendingBackup {
+    Backup { key: String, value: Vec<u8> },
+    Delete { key: String },
+}
+
 #[derive(Clone)]
 pub struct TenTenOneNodeStorage {
     pub client: RemoteBackupClient,
@@ -27,10 +68,17 @@ pub struct TenTenOneNodeStorage {
     pub data_dir: String,
     pub backup_dir: String,
     pub network: Network,
+    /// Encrypts the values of the Lightning and DLC storage at rest.
+    cipher: AesCipher,
 }
 
 impl TenTenOneNodeStorage {
-    pub fn new(data_dir: String, network: Network, secret_key: SecretKey) -> TenTenOneNodeStorage {
+    pub fn new(
+        data_dir: String,
+        network: Network,
+        secret_key: SecretKey,
+        encryption_key: StorageEncryptionKey,
+    ) -> TenTenOneNodeStorage {
         let mut data_dir = PathBuf::from(data_dir);
         data_dir.push(network.to_string());
 
@@ -51,6 +99,9 @@ impl TenTenOneNodeStorage {
         let data_dir = data_dir.to_string_lossy().to_string();
         let dlc_storage = Arc::new(SledStorageProvider::new(&data_dir));
         let client = RemoteBackupClient::new(AesCipher::new(secret_key));
+        let cipher = AesCipher::new(
+            SecretKey::from_slice(&encryption_key).expect("encryption key to be a valid key"),
+        );
 
         TenTenOneNodeStorage {
             ln_storage,
@@ -59,20 +110,102 @@ impl TenTenOneNodeStorage {
             backup_dir,
             network,
             client,
+            cipher,
         }
     }
 
+    /// Encrypts the values which have been written before the storage was encrypted, or which have
+    /// been restored from a backup.
+    pub fn encrypt_plaintext_values(&self) -> Result<()> {
+        encrypt_plaintext_values(&self.cipher, &self.ln_storage, &self.dlc_storage)
+    }
+
+    /// Removes orphaned contracts from the DLC storage and flushes it.
+    ///
+    /// The contracts are removed through this storage, as the values of the DLC storage are
+    /// encrypted.
+    pub fn compact_dlc_storage(&self) -> Result<()> {
+        let removed = DlcStorageProvider::new(self.clone())
+            .remove_orphaned_contracts()
+            .map_err(|e| anyhow!("{e}"))?;
+
+        self.dlc_storage.flush()?;
+
+        tracing::info!(removed, "Compacted DLC storage");
+
+        Ok(())
+    }
+
     /// Creates a full backup of the lightning and dlc data.
     pub async fn full_backup(&self) -> Result<()> {
         tracing::info!("Running full backup");
-        let mut handles = vec![];
 
+        let handles = self
+            .backup_entries()?
+            .into_iter()
+            .map(|(key, value)| self.client.backup(key, value))
+            .collect::<Vec<_>>();
+
+        futures::future::join_all(handles).await;
+
+        tracing::info!("Successfully created a full backup!");
+
+        Ok(())
+    }
+
+    /// Downloads the backup and checks that it is complete and can be restored, without writing
+    /// anything.
+    pub async fn verify_backup(&self) -> Result<BackupVerification> {
+        let mut keys = vec![format!("{DB_BACKUP_KEY}/{DB_BACKUP_NAME}")];
+
+        for (primary_namespace, secondary_namespace, key) in ln_keys(&self.ln_storage)? {
+            match self
+                .ln_storage
+                .read(primary_namespace, secondary_namespace, &key)
+            {
+                Ok(_) => keys.push(ln_backup_key(primary_namespace, secondary_namespace, &key)),
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        for entry in self.dlc_storage.export() {
+            keys.push(
+                [
+                    DLC_BACKUP_KEY,
+                    &hex::encode([entry.kind]),
+                    &hex::encode(entry.key),
+                ]
+                .join("/"),
+            );
+        }
+
+        self.client.verify(keys).await
+    }
+
+    /// All the values which have to be backed up, in plaintext and under their backup key.
+    ///
+    /// The network graph and the scorer are not backed up, as they can be rebuilt.
+    pub fn backup_entries(&self) -> Result<Vec<(String, Vec<u8>)>> {
         let db_backup = db::back_up()?;
-        let value = fs::read(db_backup)?;
-        let handle = self
-            .client
-            .backup(format!("{DB_BACKUP_KEY}/{DB_BACKUP_NAME}"), value);
-        handles.push(handle);
+        let mut entries = vec![(
+            format!("{DB_BACKUP_KEY}/{DB_BACKUP_NAME}"),
+            fs::read(db_backup)?,
+        )];
+
+        for (primary_namespace, secondary_namespace, key) in ln_keys(&self.ln_storage)? {
+            let value = match KVStore::read(self, primary_namespace, secondary_namespace, &key) {
+                Ok(value) => value,
+                // E.g. the channel manager has not been persisted yet.
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            entries.push((
+                ln_backup_key(primary_namespace, secondary_namespace, &key),
+                value,
+            ));
+        }
 
         for dlc_backup in self.dlc_storage.export().into_iter() {
             let key = [
@@ -81,37 +214,268 @@ impl TenTenOneNodeStorage {
                 &hex::encode(dlc_backup.key),
             ]
             .join("/");
-            let handle = self.client.backup(key, dlc_backup.value);
-            handles.push(handle);
+            entries.push((key, self.decrypt(dlc_backup.value)?));
         }
 
-        futures::future::join_all(handles).await;
+        Ok(entries)
+    }
 
-        tracing::info!("Successfully created a full backup!");
+    fn encrypt(&self, value: Vec<u8>) -> Result<Vec<u8>> {
+        encrypt(&self.cipher, value)
+    }
+
+    fn decrypt(&self, value: Vec<u8>) -> Result<Vec<u8>> {
+        decrypt(&self.cipher, value)
+    }
+}
+
+/// Encrypts the plaintext values of the given storages, see
+/// [`TenTenOneNodeStorage::encrypt_plaintext_values`].
+fn encrypt_plaintext_values(
+    cipher: &AesCipher,
+    ln_storage: &FilesystemStore,
+    dlc_storage: &SledStorageProvider,
+) -> Result<()> {
+    let mut encrypted = 0;
+
+    for entry in dlc_storage.export() {
+        if !is_encrypted(&entry.value) {
+            let value = encrypt(cipher, entry.value)?;
+            dlc_storage.write(entry.kind, entry.key, value)?;
+            encrypted += 1;
+        }
+    }
+
+    // The network graph and the scorer are encrypted the next time they are persisted.
+    for (primary_namespace, secondary_namespace, key) in ln_keys(ln_storage)? {
+        let value = match ln_storage.read(primary_namespace, secondary_namespace, &key) {
+            Ok(value) => value,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if !is_encrypted(&value) {
+            let value = encrypt(cipher, value)?;
+            ln_storage.write(primary_namespace, secondary_namespace, &key, &value)?;
+            encrypted += 1;
+        }
+    }
+
+    if encrypted > 0 {
+        tracing::info!(encrypted, "Encrypted plaintext storage values");
+    }
+
+    Ok(())
+}
+
+/// The keys of the channel manager and the channel monitors.
+fn ln_keys(ln_storage: &FilesystemStore) -> Result<Vec<(&'static str, &'static str, String)>> {
+    let mut ln_keys = vec![(
+        CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
+        CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+        CHANNEL_MANAGER_PERSISTENCE_KEY.to_string(),
+    )];
+    for key in ln_storage.list(
+        CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+        CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+    )? {
+        ln_keys.push((
+            CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+            CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+            key,
+        ));
+    }
+
+    Ok(ln_keys)
+}
+
+fn encrypt(cipher: &AesCipher, value: Vec<u8>) -> Result<Vec<u8>> {
+    let mut encrypted = ENCRYPTED_VALUE_PREFIX.to_vec();
+    encrypted.extend(cipher.encrypt(value)?);
+
+    Ok(encrypted)
+}
+
+/// Decrypts a value read from the storage, values without the [`ENCRYPTED_VALUE_PREFIX`] are
+/// returned as they are.
+fn decrypt(cipher: &AesCipher, value: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_encrypted(&value) {
+        return Ok(value);
+    }
+
+    cipher
+        .decrypt(value[ENCRYPTED_VALUE_PREFIX.len()..].to_vec())
+        .context("Failed to decrypt storage value")
+}
+
+fn is_encrypted(value: &[u8]) -> bool {
+    value.starts_with(ENCRYPTED_VALUE_PREFIX)
+}
+
+impl TenTenOneNodeStorage {
+    /// Starts recording the previous values of all DLC storage entries changed on this thread, so
+    /// that they can be restored by [`TenTenOneNodeStorage::rollback_unit_of_work`].
+    ///
+    /// The remote backups of these entries are held back until
+    /// [`TenTenOneNodeStorage::commit_unit_of_work`].
+    pub(crate) fn begin_unit_of_work(&self) {
+        DLC_UNDO_LOG.with(|undo_log| *undo_log.borrow_mut() = Some(vec![]));
+        PENDING_DLC_BACKUPS.with(|backups| *backups.borrow_mut() = Some(vec![]));
+    }
+
+    pub(crate) fn commit_unit_of_work(&self) {
+        DLC_UNDO_LOG.with(|undo_log| undo_log.borrow_mut().take());
+
+        let backups = PENDING_DLC_BACKUPS
+            .with(|backups| backups.borrow_mut().take())
+            .unwrap_or_default();
+
+        for backup in backups {
+            match backup {
+                PendingBackup::Backup { key, value } => self.client.backup(key, value).forget(),
+                PendingBackup::Delete { key } => self.client.delete(key).forget(),
+            }
+        }
+    }
+
+    /// Restores the DLC storage entries changed on this thread since
+    /// [`TenTenOneNodeStorage::begin_unit_of_work`].
+    ///
+    /// The held back remote backups are discarded, as they contain state which has never been
+    /// committed.
+    pub(crate) fn rollback_unit_of_work(&self) -> Result<()> {
+        PENDING_DLC_BACKUPS.with(|backups| backups.borrow_mut().take());
+
+        let undo_log = DLC_UNDO_LOG
+            .with(|undo_log| undo_log.borrow_mut().take())
+            .unwrap_or_default();
+
+        for entry in undo_log.into_iter().rev() {
+            match entry.value {
+                Some(value) => self.write(entry.kind, entry.key, value)?,
+                None => self.delete(entry.kind, Some(entry.key))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remembers the current values of the given entries, unless they have been changed within the
+    /// unit of work before or there is no unit of work in progress.
+    fn record_previous_values(&self, kind: u8, key: Option<Vec<u8>>) -> Result<()> {
+        let is_unit_of_work = DLC_UNDO_LOG.with(|undo_log| undo_log.borrow().is_some());
+        if !is_unit_of_work {
+            return Ok(());
+        }
+
+        let previous_values = match key {
+            Some(key) => {
+                let value = DlcStoreProvider::read(self, kind, Some(key.clone()))?
+                    .into_iter()
+                    .next()
+                    .map(|key_value| key_value.value);
+                vec![(key, value)]
+            }
+            None => DlcStoreProvider::read(self, kind, None)?
+                .into_iter()
+                .map(|key_value| (key_value.key, Some(key_value.value)))
+                .collect(),
+        };
+
+        DLC_UNDO_LOG.with(|undo_log| {
+            if let Some(undo_log) = undo_log.borrow_mut().as_mut() {
+                for (key, value) in previous_values {
+                    let is_recorded = undo_log
+                        .iter()
+                        .any(|entry| entry.kind == kind && entry.key == key);
+                    if !is_recorded {
+                        undo_log.push(UndoEntry { kind, key, value });
+                    }
+                }
+            }
+        });
 
         Ok(())
     }
+
+    /// Backs up the DLC storage entry remotely, or holds the backup back if a unit of work is in
+    /// progress.
+    fn backup_dlc_entry(&self, key: String, value: Vec<u8>) {
+        let backup = PENDING_DLC_BACKUPS.with(|backups| match backups.borrow_mut().as_mut() {
+            Some(backups) => {
+                backups.push(PendingBackup::Backup { key, value });
+                None
+            }
+            None => Some((key, value)),
+        });
+
+        if let Some((key, value)) = backup {
+            self.client.backup(key, value).forget();
+        }
+    }
+
+    /// Deletes the remote backup of the DLC storage entries, or holds the deletion back if a unit
+    /// of work is in progress.
+    fn delete_dlc_backup(&self, key: String) {
+        let key = PENDING_DLC_BACKUPS.with(|backups| match backups.borrow_mut().as_mut() {
+            Some(backups) => {
+                backups.push(PendingBackup::Delete { key });
+                None
+            }
+            None => Some(key),
+        });
+
+        if let Some(key) = key {
+            self.client.delete(key).forget();
+        }
+    }
 }
 
 impl DlcStoreProvider for TenTenOneNodeStorage {
     fn read(&self, kind: u8, key: Option<Vec<u8>>) -> Result<Vec<KeyValue>> {
-        self.dlc_storage.read(kind, key)
+        self.dlc_storage
+            .read(kind, key)?
+            .into_iter()
+            .map(|KeyValue { key, value }| {
+                let value = self.decrypt(value)?;
+                Ok(KeyValue { key, value })
+            })
+            .collect()
     }
 
     fn write(&self, kind: u8, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
-        self.dlc_storage.write(kind, key.clone(), value.clone())?;
+        if device::is_superseded() {
+            tracing::warn!(
+                kind,
+                "Not persisting DLC state as another device is using our seed"
+            );
+            return Ok(());
+        }
+
+        self.record_previous_values(kind, Some(key.clone()))?;
+        self.dlc_storage
+            .write(kind, key.clone(), self.encrypt(value.clone())?)?;
 
         let key = [DLC_BACKUP_KEY, &hex::encode([kind]), &hex::encode(key)].join("/");
 
         // Let the backup run asynchronously we don't really care if it is successful or not as the
         // next write may fix the issue. Note, if we want to handle failed backup attempts we
         // would need to remember those remote handles and handle a failure accordingly.
-        self.client.backup(key, value).forget();
+        self.backup_dlc_entry(key, value);
 
         Ok(())
     }
 
     fn delete(&self, kind: u8, key: Option<Vec<u8>>) -> Result<()> {
+        if device::is_superseded() {
+            tracing::warn!(
+                kind,
+                "Not persisting DLC state as another device is using our seed"
+            );
+            return Ok(());
+        }
+
+        self.record_previous_values(kind, key.clone())?;
         self.dlc_storage.delete(kind, key.clone())?;
 
         let key = match key {
@@ -123,7 +487,7 @@ impl DlcStoreProvider for TenTenOneNodeStorage {
         // We may end up with a key that should have been deleted. That should hopefully not
         // be a problem. Note, if we want to handle failed backup attempts we would need to
         // remember those remote handles and handle a failure accordingly.
-        self.client.delete(key).forget();
+        self.delete_dlc_backup(key);
         Ok(())
     }
 }
@@ -135,8 +499,12 @@ impl KVStore for TenTenOneNodeStorage {
         secondary_namespace: &str,
         key: &str,
     ) -> std::result::Result<Vec<u8>, Error> {
-        self.ln_storage
-            .read(primary_namespace, secondary_namespace, key)
+        let value = self
+            .ln_storage
+            .read(primary_namespace, secondary_namespace, key)?;
+
+        self.decrypt(value)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{e:#}")))
     }
 
     fn write(
@@ -146,23 +514,28 @@ impl KVStore for TenTenOneNodeStorage {
         key: &str,
         value: &[u8],
     ) -> std::result::Result<(), Error> {
+        if device::is_superseded() {
+            tracing::warn!(
+                key,
+                "Not persisting Lightning state as another device is using our seed"
+            );
+            return Ok(());
+        }
+
+        let encrypted = self
+            .encrypt(value.to_vec())
+            .map_err(|e| Error::new(ErrorKind::Other, format!("{e:#}")))?;
         self.ln_storage
-            .write(primary_namespace, secondary_namespace, key, value)?;
+            .write(primary_namespace, secondary_namespace, key, &encrypted)?;
 
         let value = value.to_vec();
-        let key = [primary_namespace, secondary_namespace, key]
-            .into_iter()
-            .filter(|&k| !k.is_empty())
-            .collect::<Vec<&str>>()
-            .join("/");
+        let key = ln_backup_key(primary_namespace, secondary_namespace, key);
         tracing::trace!("Creating a backup of {:?}", key);
 
         // Let the backup run asynchronously we don't really care if it is successful or not as the
         // next persist will fix the issue. Note, if we want to handle failed backup attempts we
         // would need to remember those remote handles and handle a failure accordingly.
-        self.client
-            .backup([LN_BACKUP_KEY, &key].join("/"), value)
-            .forget();
+        self.client.backup(key, value).forget();
 
         Ok(())
     }
@@ -174,6 +547,14 @@ impl KVStore for TenTenOneNodeStorage {
         key: &str,
         lazy: bool,
     ) -> std::result::Result<(), Error> {
+        if device::is_superseded() {
+            tracing::warn!(
+                key,
+                "Not persisting Lightning state as another device is using our seed"
+            );
+            return Ok(());
+        }
+
         self.ln_storage
             .remove(primary_namespace, secondary_namespace, key, lazy)
     }
@@ -186,3 +567,154 @@ impl KVStore for TenTenOneNodeStorage {
         self.ln_storage.list(primary_namespace, secondary_namespace)
     }
 }
+
+/// The backup key of a value of the Lightning storage.
+///
+/// On restore, the key without the [`LN_BACKUP_KEY`] prefix is the path of the file relative to
+/// the data directory of the network, as written by the [`FilesystemStore`].
+fn ln_backup_key(primary_namespace: &str, secondary_namespace: &str, key: &str) -> String {
+    [LN_BACKUP_KEY, primary_namespace, secondary_namespace, key]
+        .into_iter()
+        .filter(|k| !k.is_empty())
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::rand;
+    use uuid::Uuid;
+
+    const MONITOR_KEY: &str = "deadbeef_0";
+
+    #[test]
+    fn values_are_encrypted_at_rest() {
+        let cipher = AesCipher::new(SecretKey::new(&mut rand::thread_rng()));
+        let value = b"channel monitor".to_vec();
+
+        let encrypted = encrypt(&cipher, value.clone()).unwrap();
+        assert!(encrypted.starts_with(ENCRYPTED_VALUE_PREFIX));
+        assert!(!encrypted.windows(value.len()).any(|window| window == value));
+
+        assert_eq!(decrypt(&cipher, encrypted).unwrap(), value);
+    }
+
+    #[test]
+    fn values_without_prefix_are_read_as_they_are() {
+        let cipher = AesCipher::new(SecretKey::new(&mut rand::thread_rng()));
+        let value = b"written before the storage was encrypted".to_vec();
+
+        assert_eq!(decrypt(&cipher, value.clone()).unwrap(), value);
+    }
+
+    #[test]
+    fn corrupted_encrypted_values_are_not_read() {
+        let cipher = AesCipher::new(SecretKey::new(&mut rand::thread_rng()));
+        let encrypted = encrypt(&cipher, b"channel monitor".to_vec()).unwrap();
+
+        // Truncated right after the prefix, i.e. without a nonce.
+        assert!(decrypt(&cipher, ENCRYPTED_VALUE_PREFIX.to_vec()).is_err());
+        assert!(decrypt(&cipher, encrypted[..encrypted.len() - 1].to_vec()).is_err());
+
+        let other_cipher = AesCipher::new(SecretKey::new(&mut rand::thread_rng()));
+        assert!(decrypt(&other_cipher, encrypted).is_err());
+    }
+
+    #[test]
+    fn plaintext_values_are_encrypted_once() {
+        let cipher = AesCipher::new(SecretKey::new(&mut rand::thread_rng()));
+        let dir = std::env::temp_dir().join(format!("10101-storage-{}", Uuid::new_v4()));
+        let ln_storage = FilesystemStore::new(dir.join("ln"));
+        let dlc_storage = SledStorageProvider::new(&dir.join("dlc").to_string_lossy());
+
+        let manager = b"channel manager".to_vec();
+        let monitor = b"channel monitor".to_vec();
+        let contract = b"contract".to_vec();
+        ln_storage
+            .write(
+                CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
+                CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+                CHANNEL_MANAGER_PERSISTENCE_KEY,
+                &manager,
+            )
+            .unwrap();
+        ln_storage
+            .write(
+                CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+                CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+                MONITOR_KEY,
+                &monitor,
+            )
+            .unwrap();
+        dlc_storage
+            .write(1, b"key".to_vec(), contract.clone())
+            .unwrap();
+
+        encrypt_plaintext_values(&cipher, &ln_storage, &dlc_storage).unwrap();
+
+        let read_ln = |primary_namespace, secondary_namespace, key| {
+            ln_storage
+                .read(primary_namespace, secondary_namespace, key)
+                .unwrap()
+        };
+        let read_dlc = || {
+            DlcStoreProvider::read(&dlc_storage, 1, Some(b"key".to_vec()))
+                .unwrap()
+                .remove(0)
+                .value
+        };
+
+        let encrypted_manager = read_ln(
+            CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
+            CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+            CHANNEL_MANAGER_PERSISTENCE_KEY,
+        );
+        let encrypted_monitor = read_ln(
+            CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+            CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+            MONITOR_KEY,
+        );
+        let encrypted_contract = read_dlc();
+
+        assert!(is_encrypted(&encrypted_manager));
+        assert!(is_encrypted(&encrypted_monitor));
+        assert!(is_encrypted(&encrypted_contract));
+        assert_eq!(
+            decrypt(&cipher, encrypted_manager.clone()).unwrap(),
+            manager
+        );
+        assert_eq!(
+            decrypt(&cipher, encrypted_monitor.clone()).unwrap(),
+            monitor
+        );
+        assert_eq!(
+            decrypt(&cipher, encrypted_contract.clone()).unwrap(),
+            contract
+        );
+
+        // Values which are encrypted already are left alone.
+        encrypt_plaintext_values(&cipher, &ln_storage, &dlc_storage).unwrap();
+
+        assert_eq!(
+            read_ln(
+                CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
+                CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+                CHANNEL_MANAGER_PERSISTENCE_KEY,
+            ),
+            encrypted_manager
+        );
+        assert_eq!(
+            read_ln(
+                CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+                CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+                MONITOR_KEY,
+            ),
+            encrypted_monitor
+        );
+        assert_eq!(read_dlc(), encrypted_contract);
+
+        drop(dlc_storage);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+use crate::commons::reqwest_client;
+use crate::config;
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use commons::MarketStats;
+use trade::ContractSymbol;
+
+/// Fetch the trading statistics of the given market from the coordinator.
+pub async fn get_market_stats(contract_symbol: ContractSymbol) -> Result<MarketStats> {
+    let client = reqwest_client();
+    let response = client
+        .get(format!(
+            "http://{}/api/v1/markets/{}/stats",
+            config::get_http_endpoint(),
+            contract_symbol.label()
+        ))
+        .send()
+        .await
+        .context("Failed to fetch market stats from coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        bail!("Could not fetch market stats from coordinator: {response_text}");
+    }
+
+    response
+        .json()
+        .await
+        .context("Failed to parse market stats")
+}
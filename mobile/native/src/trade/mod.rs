@@ -6,7 +6,9 @@ use trade::ContractSymbol;
 use trade::Direction;
 use uuid::Uuid;
 
+pub mod market_stats;
 pub mod order;
+pub mod paper;
 pub mod position;
 pub mod users;
 
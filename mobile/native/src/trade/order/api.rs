@@ -18,6 +18,8 @@ pub enum OrderType {
 #[frb]
 #[derive(Debug, Clone, Copy)]
 pub enum OrderState {
+    /// Waiting for the coordinator to become reachable, to be submitted to the orderbook.
+    Queued,
     Open,
     Filling,
     Filled,
@@ -43,8 +45,10 @@ pub enum FailureReason {
     CollabRevert,
     OrderNotAcceptable,
     TimedOut,
+    FillingTimedOut,
     InvalidDlcOffer,
     OrderRejected,
+    Aborted,
     Unknown,
 }
 
@@ -143,8 +147,10 @@ impl From<order::FailureReason> for FailureReason {
             order::FailureReason::TradeResponse(details) => FailureReason::TradeResponse(details),
             order::FailureReason::OrderNotAcceptable => FailureReason::OrderNotAcceptable,
             order::FailureReason::TimedOut => FailureReason::TimedOut,
+            order::FailureReason::FillingTimedOut => FailureReason::FillingTimedOut,
             order::FailureReason::InvalidDlcOffer(_) => FailureReason::InvalidDlcOffer,
             order::FailureReason::OrderRejected => FailureReason::OrderRejected,
+            order::FailureReason::Aborted => FailureReason::Aborted,
             order::FailureReason::CollabRevert => FailureReason::CollabRevert,
             order::FailureReason::Unknown => FailureReason::Unknown,
         }
@@ -163,6 +169,7 @@ impl From<OrderType> for order::OrderType {
 impl From<order::OrderState> for OrderState {
     fn from(value: order::OrderState) -> Self {
         match value {
+            order::OrderState::Queued => OrderState::Queued,
             order::OrderState::Open => OrderState::Open,
             order::OrderState::Filled { .. } => OrderState::Filled,
             order::OrderState::Failed { .. } => OrderState::Failed,
@@ -186,10 +193,13 @@ impl From<NewOrder> for order::Order {
             order_type: (*value.order_type).into(),
             state: order::OrderState::Initial,
             creation_timestamp: OffsetDateTime::now_utc(),
-            // We do not support setting order expiry from the frontend for now
+            // We do not support setting order expiry from the frontend for now. The expiry is
+            // renewed when the order is submitted to the orderbook.
             order_expiry_timestamp: OffsetDateTime::now_utc() + time::Duration::minutes(1),
             reason: order::OrderReason::Manual,
             stable: value.stable,
+            reduce_only: false,
+            position_id: None,
             failure_reason: None,
         }
     }
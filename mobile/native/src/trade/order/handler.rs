@@ -2,13 +2,19 @@ use crate::config;
 use crate::db;
 use crate::db::get_order_in_filling;
 use crate::db::maybe_get_open_orders;
+use crate::error;
+use crate::error::ErrorCode;
 use crate::event;
 use crate::event::EventInternal;
+use crate::ln_dlc;
 use crate::ln_dlc::is_dlc_channel_confirmed;
+use crate::pending_action::is_dlc_protocol_in_progress;
+use crate::state;
 use crate::trade::order::orderbook_client::OrderbookClient;
 use crate::trade::order::FailureReason;
 use crate::trade::order::Order;
 use crate::trade::order::OrderState;
+use crate::trade::paper;
 use crate::trade::position;
 use crate::trade::position::handler::update_position_after_order_submitted;
 use crate::trade::position::PositionState;
@@ -16,6 +22,10 @@ use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
+use commons::create_sign_message;
+use commons::RollbackMatch;
+use ln_dlc_node::node::rust_dlc_manager::channel::offered_channel::OfferedChannel;
+use ln_dlc_node::node::rust_dlc_manager::channel::Channel;
 use reqwest::Url;
 use time::Duration;
 use time::OffsetDateTime;
@@ -24,6 +34,15 @@ use uuid::Uuid;
 
 const ORDER_OUTDATED_AFTER: Duration = Duration::minutes(5);
 
+/// How long an order waits for the coordinator to become reachable before it fails.
+const QUEUED_ORDER_EXPIRY: Duration = Duration::minutes(5);
+
+/// How long an order submitted to the orderbook is valid, see [`crate::trade::order::api`].
+const ORDER_EXPIRY: Duration = Duration::minutes(1);
+
+/// Orders which are stuck in `Filling` for longer than this are failed.
+const ORDER_FILLING_TIMEOUT: Duration = Duration::minutes(10);
+
 #[derive(thiserror::Error, Debug)]
 pub enum SubmitOrderError {
     /// Generic problem related to the storage layer (sqlite, sled).
@@ -42,11 +61,22 @@ pub enum SubmitOrderError {
         direction: Direction,
         leverage: f32,
     },
+    #[error("Another order is waiting for the coordinator to become reachable: {contracts} contracts {direction}")]
+    OtherOrderQueued {
+        contracts: f32,
+        direction: Direction,
+    },
     #[error("Failed to post order to orderbook: {0}")]
     Orderbook(anyhow::Error),
+    #[error("Failed to fill paper trading order: {0}")]
+    PaperTrading(anyhow::Error),
 }
 
 pub async fn submit_order(order: Order) -> Result<Uuid, SubmitOrderError> {
+    if config::is_paper_trading() {
+        return paper::submit_order(order).map_err(SubmitOrderError::PaperTrading);
+    }
+
     // If we have an open position, we should not allow any further trading until the current DLC
     // channel is confirmed on-chain. Otherwise we can run into pesky DLC protocol failures.
     if position::handler::get_positions()
@@ -78,15 +108,34 @@ pub async fn submit_order(order: Order) -> Result<Uuid, SubmitOrderError> {
         });
     }
 
-    let url = format!("http://{}", config::get_http_endpoint());
-    let url = Url::parse(&url).expect("correct URL");
-    let orderbook_client = OrderbookClient::new(url);
+    // The queued order would be submitted on top of the new one.
+    if let Some(queued_order) = db::get_queued_orders()
+        .map_err(SubmitOrderError::Storage)?
+        .first()
+    {
+        return Err(SubmitOrderError::OtherOrderQueued {
+            contracts: queued_order.quantity,
+            direction: queued_order.direction,
+        });
+    }
 
     db::insert_order(order.clone()).map_err(SubmitOrderError::Storage)?;
 
-    if let Err(err) = orderbook_client.post_new_order(order.clone().into()).await {
+    if let Err(err) = post_order(&order).await {
         let order_id = order.id.clone().to_string();
 
+        if error::classify(&err) == ErrorCode::CoordinatorUnreachable {
+            tracing::warn!(
+                order_id,
+                "Coordinator is unreachable, queueing order: {err:#}"
+            );
+
+            update_order_state_in_db_and_ui(order.id, OrderState::Queued)
+                .map_err(SubmitOrderError::Storage)?;
+
+            return Ok(order.id);
+        }
+
         tracing::error!(order_id, "Failed to post new order: {err:#}");
 
         update_order_state_in_db_and_ui(
@@ -97,9 +146,11 @@ pub async fn submit_order(order: Order) -> Result<Uuid, SubmitOrderError> {
         )
         .map_err(SubmitOrderError::Storage)?;
 
-        position::handler::set_position_state(PositionState::Open)
-            .context("Could not reset position to open")
-            .map_err(SubmitOrderError::Storage)?;
+        if let Some(position_id) = order.position_id {
+            position::handler::set_position_state(position_id, PositionState::Open)
+                .context("Could not reset position to open")
+                .map_err(SubmitOrderError::Storage)?;
+        }
 
         return Err(SubmitOrderError::Orderbook(err));
     }
@@ -111,6 +162,65 @@ pub async fn submit_order(order: Order) -> Result<Uuid, SubmitOrderError> {
     Ok(order.id)
 }
 
+/// Submits the orders which have been queued while the coordinator was unreachable, or fails them
+/// once they have been queued for longer than [`QUEUED_ORDER_EXPIRY`].
+pub async fn submit_queued_orders() -> Result<()> {
+    for order in db::get_queued_orders()? {
+        if order.creation_timestamp + QUEUED_ORDER_EXPIRY < OffsetDateTime::now_utc() {
+            order_failed(
+                Some(order.id),
+                FailureReason::TimedOut,
+                anyhow!("Coordinator was unreachable for {QUEUED_ORDER_EXPIRY:?}"),
+            )?;
+            continue;
+        }
+
+        match post_order(&order).await {
+            Ok(()) => {
+                tracing::info!(order_id = %order.id, "Submitted queued order");
+
+                update_order_state_in_db_and_ui(order.id, OrderState::Open)?;
+                update_position_after_order_submitted(&order)?;
+            }
+            Err(e) if error::classify(&e) == ErrorCode::CoordinatorUnreachable => {
+                tracing::debug!(order_id = %order.id, "Coordinator is still unreachable");
+            }
+            Err(e) => {
+                tracing::error!(order_id = %order.id, "Failed to post queued order: {e:#}");
+
+                update_order_state_in_db_and_ui(
+                    order.id,
+                    OrderState::Failed {
+                        reason: FailureReason::OrderRejected,
+                    },
+                )?;
+                if let Some(position_id) = order.position_id {
+                    position::handler::set_position_state(position_id, PositionState::Open)
+                        .context("Could not reset position to open")?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Posts `order` to the orderbook, valid for [`ORDER_EXPIRY`] from now, as it may have been
+/// queued for a while.
+async fn post_order(order: &Order) -> Result<()> {
+    let url = format!("http://{}", config::get_http_endpoint());
+    let url = Url::parse(&url).expect("correct URL");
+    let orderbook_client = OrderbookClient::new(url);
+
+    let order = Order {
+        order_expiry_timestamp: state::get_clock().now() + ORDER_EXPIRY,
+        ..order.clone()
+    };
+    orderbook_client.post_new_order(order.try_into()?).await?;
+
+    Ok(())
+}
+
 /// Update order to state [`OrderState::Filling`].
 pub(crate) fn order_filling(order_id: Uuid, execution_price: f32) -> Result<()> {
     let state = OrderState::Filling { execution_price };
@@ -172,13 +282,13 @@ pub(crate) fn order_failed(
 ) -> Result<()> {
     tracing::error!(?order_id, ?reason, "Failed to execute trade: {error:#}");
 
-    let order_id = match order_id {
-        None => get_order_in_filling()?.map(|order| order.id),
-        Some(order_id) => Some(order_id),
+    let order = match order_id {
+        None => get_order_in_filling()?,
+        Some(order_id) => Some(db::get_order(order_id)?),
     };
 
-    if let Some(order_id) = order_id {
-        update_order_state_in_db_and_ui(order_id, OrderState::Failed { reason })?;
+    if let Some(order) = &order {
+        update_order_state_in_db_and_ui(order.id, OrderState::Failed { reason })?;
     }
 
     // TODO: fixme. this so ugly, even a Sphynx cat is beautiful against this.
@@ -188,7 +298,22 @@ pub(crate) fn order_failed(
     // in various places where (most of the time) we only want to set the order to failed. If we
     // were to introduce a `PostionState::Closed` the below code would be wrong and would
     // accidentally set a closed position to open again. This should be cleaned up.
-    if let Err(e) = position::handler::set_position_state(PositionState::Open) {
+    let reset_position = match order {
+        Some(Order {
+            position_id: Some(position_id),
+            ..
+        }) => position::handler::set_position_state(position_id, PositionState::Open),
+        // The order would have opened a new position.
+        Some(_) => Ok(()),
+        // Without an order we can't tell which position was affected.
+        None => position::handler::get_positions().and_then(|positions| {
+            positions.into_iter().try_for_each(|position| {
+                position::handler::set_position_state(position.id, PositionState::Open)
+            })
+        }),
+    };
+
+    if let Err(e) = reset_position {
         bail!("Could not reset position to open because of {e:#}");
     }
 
@@ -196,6 +321,10 @@ pub(crate) fn order_failed(
 }
 
 pub async fn get_orders_for_ui() -> Result<Vec<Order>> {
+    if config::is_paper_trading() {
+        return Ok(paper::get_orders());
+    }
+
     db::get_orders_for_ui()
 }
 
@@ -227,6 +356,89 @@ pub fn check_open_orders() -> Result<()> {
     Ok(())
 }
 
+/// Fail the order in `Filling` if the DLC protocol did not complete within
+/// [`ORDER_FILLING_TIMEOUT`], so that it does not block all future orders, and roll back the
+/// match with the coordinator.
+///
+/// If the DLC channel is already in the midst of an update, we keep waiting, as the protocol
+/// cannot be aborted safely at that point.
+pub async fn check_order_in_filling() -> Result<()> {
+    let order = match get_order_in_filling()? {
+        Some(order) => order,
+        None => return Ok(()),
+    };
+
+    // The time is taken from the database, so that the timeout keeps running across restarts of
+    // the app. Orders which went into filling before it was recorded fall back to their creation.
+    let filling_since = db::get_order_filling_since(order.id)?.unwrap_or(order.creation_timestamp);
+
+    let now = OffsetDateTime::now_utc();
+    if filling_since + ORDER_FILLING_TIMEOUT > now {
+        return Ok(());
+    }
+
+    let node = state::try_get_node().context("Failed to get ln dlc node")?;
+    let dlc_channels = node.inner.list_dlc_channels()?;
+
+    if dlc_channels
+        .iter()
+        .filter(|channel| !matches!(channel, Channel::Offered(_)))
+        .any(is_dlc_protocol_in_progress)
+    {
+        tracing::warn!(
+            order_id = %order.id,
+            "Order is stuck in filling, but the DLC protocol cannot be aborted anymore"
+        );
+        return Ok(());
+    }
+
+    for channel in dlc_channels.iter() {
+        if let Channel::Offered(OfferedChannel {
+            temporary_channel_id,
+            is_offer_party: false,
+            ..
+        }) = channel
+        {
+            tracing::info!(
+                order_id = %order.id,
+                "Rejecting DLC channel offer of order stuck in filling"
+            );
+            node.inner.reject_dlc_channel_offer(temporary_channel_id)?;
+        }
+    }
+
+    order_failed(
+        Some(order.id),
+        FailureReason::FillingTimedOut,
+        anyhow!("Order was not filled within {ORDER_FILLING_TIMEOUT:?}"),
+    )?;
+
+    if let Err(e) = rollback_match(order.id).await {
+        tracing::error!(order_id = %order.id, "Failed to roll back match: {e:#}");
+    }
+
+    Ok(())
+}
+
+async fn rollback_match(order_id: Uuid) -> Result<()> {
+    let message = create_sign_message(order_id.to_string().as_bytes().to_vec());
+    let signature = commons::Signature {
+        pubkey: ln_dlc::get_node_pubkey()?,
+        signature: ln_dlc::get_node_key()?.sign_ecdsa(message),
+    };
+
+    let url = format!("http://{}", config::get_http_endpoint());
+    let url = Url::parse(&url).expect("correct URL");
+    let orderbook_client = OrderbookClient::new(url);
+
+    orderbook_client
+        .rollback_match(RollbackMatch {
+            order_id,
+            signature,
+        })
+        .await
+}
+
 fn update_order_state_in_db_and_ui(order_id: Uuid, state: OrderState) -> Result<Order> {
     let order = db::update_order_state(order_id, state.clone())
         .with_context(|| format!("Failed to update order {order_id} with state {state:?}"))?;
@@ -1,7 +1,10 @@
 use crate::calculations::calculate_margin;
 use crate::ln_dlc;
+use anyhow::Result;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
+use trade::validation;
+use trade::validation::OrderValidationError;
 use trade::ContractSymbol;
 use trade::Direction;
 use uuid::Uuid;
@@ -35,9 +38,13 @@ pub enum FailureReason {
     OrderNotAcceptable,
     /// The order timed out, i.e. we did not receive a match in time
     TimedOut,
+    /// The order was stuck in filling, i.e. the DLC protocol did not complete in time
+    FillingTimedOut,
     InvalidDlcOffer(InvalidSubchannelOffer),
     /// The order has been rejected by the orderbook
     OrderRejected,
+    /// The user aborted the order while it was stuck in a pending protocol state
+    Aborted,
     Unknown,
 }
 
@@ -57,9 +64,19 @@ pub enum OrderState {
     /// database and update it once the orderbook returns success.
     /// Transitions:
     /// - Initial->Open
+    /// - Initial->Queued
     /// - Initial->Rejected
     Initial,
 
+    /// Not submitted to orderbook yet, because the coordinator was unreachable
+    ///
+    /// The submission is retried until the coordinator is reachable again, or the order has been
+    /// queued for too long.
+    /// Transitions:
+    /// - Queued->Open
+    /// - Queued->Failed (if the orderbook rejects the order, or the order expires in the queue)
+    Queued,
+
     /// Rejected by the orderbook upon submission
     ///
     /// If the orderbook returns failure upon submission.
@@ -150,10 +167,27 @@ pub struct Order {
     pub order_expiry_timestamp: OffsetDateTime,
     pub reason: OrderReason,
     pub stable: bool,
+    /// Whether the coordinator must reject the order unless it reduces our open position.
+    pub reduce_only: bool,
+    /// The position which the order resizes or closes, [`None`] if the order opens a new
+    /// position.
+    pub position_id: Option<Uuid>,
     pub failure_reason: Option<FailureReason>,
 }
 
 impl Order {
+    /// Checks the order against the rules of its contract, which are also enforced by the
+    /// coordinator.
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        let quantity = Decimal::try_from(self.quantity).unwrap_or_default();
+        let limit_price = match self.order_type {
+            OrderType::Market => None,
+            OrderType::Limit { price } => Some(Decimal::try_from(price).unwrap_or_default()),
+        };
+
+        validation::validate_order(self.contract_symbol, self.leverage, quantity, limit_price)
+    }
+
     /// This returns the executed price once known
     ///
     /// Logs an error if this function is called on a state where the execution price is not know
@@ -184,11 +218,13 @@ impl Order {
     }
 }
 
-impl From<Order> for commons::NewOrder {
-    fn from(order: Order) -> Self {
+impl TryFrom<Order> for commons::NewOrder {
+    type Error = anyhow::Error;
+
+    fn try_from(order: Order) -> Result<Self> {
         let quantity = Decimal::try_from(order.quantity).expect("to parse into decimal");
-        let trader_id = ln_dlc::get_node_pubkey();
-        commons::NewOrder {
+        let trader_id = ln_dlc::get_node_pubkey()?;
+        Ok(commons::NewOrder {
             id: order.id,
             contract_symbol: order.contract_symbol,
             // todo: this is left out intentionally as market orders do not set a price. this field
@@ -201,7 +237,8 @@ impl From<Order> for commons::NewOrder {
             order_type: order.order_type.into(),
             expiry: order.order_expiry_timestamp,
             stable: order.stable,
-        }
+            reduce_only: order.reduce_only,
+        })
     }
 }
 
@@ -3,6 +3,7 @@ use anyhow::bail;
 use anyhow::Result;
 use commons::NewOrder;
 use commons::OrderResponse;
+use commons::RollbackMatch;
 use reqwest::Url;
 
 pub struct OrderbookClient {
@@ -15,7 +16,7 @@ impl OrderbookClient {
     }
 
     pub(crate) async fn post_new_order(&self, order: NewOrder) -> Result<OrderResponse> {
-        let url = self.url.join("/api/orderbook/orders")?;
+        let url = self.url.join("/api/v1/orderbook/orders")?;
         let client = reqwest_client();
 
         let response = client.post(url).json(&order).send().await?;
@@ -28,4 +29,22 @@ impl OrderbookClient {
             bail!("Could not create new order: {response:?}")
         }
     }
+
+    /// Ask the coordinator to roll back the match of an order which we failed to execute.
+    pub(crate) async fn rollback_match(&self, rollback: RollbackMatch) -> Result<()> {
+        let url = self.url.join(&format!(
+            "/api/v1/orderbook/orders/{}/rollback",
+            rollback.order_id
+        ))?;
+        let client = reqwest_client();
+
+        let response = client.post(url).json(&rollback).send().await?;
+
+        if !response.status().is_success() {
+            let response_text = response.text().await?;
+            bail!("Could not roll back match: {response_text}")
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,363 @@
+//! Paper trading: orders are filled by an in-memory simulator instead of the coordinator.
+//!
+//! Market orders are filled immediately at the current price of the orderbook, against a fake
+//! balance. No DLC is set up and nothing is persisted, but orders, positions and trades are the
+//! same models as for real trading and the same events are published, so that new users can try
+//! the app before funding a channel.
+//!
+//! Paper positions are neither rolled over nor liquidated.
+
+use crate::calculations::calculate_margin;
+use crate::config;
+use crate::error::AppError;
+use crate::event;
+use crate::event::EventInternal;
+use crate::state;
+use crate::trade::order::Order;
+use crate::trade::order::OrderState;
+use crate::trade::order::OrderType;
+use crate::trade::position;
+use crate::trade::position::Position;
+use crate::trade::Trade;
+use anyhow::Context;
+use anyhow::Result;
+use commons::Prices;
+use parking_lot::const_mutex;
+use parking_lot::Mutex;
+use rust_decimal::prelude::ToPrimitive;
+use time::OffsetDateTime;
+use trade::Direction;
+use uuid::Uuid;
+
+/// The fake balance every paper trading session starts with, in sats.
+pub const INITIAL_BALANCE_SATS: u64 = 1_000_000;
+
+static SIMULATOR: Mutex<Simulator> = const_mutex(Simulator::new());
+
+struct Simulator {
+    /// The fake balance, excluding the margin locked in positions, in sats.
+    balance: u64,
+    orders: Vec<Order>,
+    positions: Vec<Position>,
+    trades: Vec<Trade>,
+}
+
+/// What changed by filling an order.
+struct Fill {
+    order: Order,
+    position: Option<Position>,
+    /// The position which was closed by the order, if any.
+    closed_position: Option<Position>,
+}
+
+impl Simulator {
+    const fn new() -> Self {
+        Self {
+            balance: INITIAL_BALANCE_SATS,
+            orders: Vec::new(),
+            positions: Vec::new(),
+            trades: Vec::new(),
+        }
+    }
+
+    /// Fill the market `order` at the current `prices`. A new position expires at `expiry`.
+    ///
+    /// Nothing changes if the order can't be filled.
+    fn fill(&mut self, order: Order, prices: &Prices, expiry: OffsetDateTime) -> Result<Fill> {
+        if order.order_type != OrderType::Market {
+            return Err(AppError::OrderRejected(
+                "Only market orders are supported in paper trading".to_string(),
+            )
+            .into());
+        }
+
+        let execution_price = execution_price(&order, prices)?;
+
+        let order = Order {
+            state: OrderState::Filled { execution_price },
+            ..order
+        };
+
+        let current_position = self
+            .positions
+            .iter()
+            .find(|position| position.contract_symbol == order.contract_symbol)
+            .cloned();
+
+        let (position, trades) = match &current_position {
+            None => {
+                if order.reduce_only {
+                    return Err(AppError::OrderRejected(
+                        "There is no position to reduce".to_string(),
+                    )
+                    .into());
+                }
+
+                let margin = calculate_margin(execution_price, order.quantity, order.leverage);
+                let (position, trade) = Position::new_open(order.clone(), margin, expiry);
+
+                (Some(position), vec![trade])
+            }
+            Some(position) => {
+                if order.reduce_only
+                    && (position.direction == order.direction || order.quantity > position.quantity)
+                {
+                    return Err(AppError::OrderRejected(
+                        "The order would not reduce the position".to_string(),
+                    )
+                    .into());
+                }
+
+                // Without a DLC there is no actual collateral to compare the calculated one with.
+                position
+                    .clone()
+                    .apply_order(order.clone(), position.expiry, position.collateral)?
+            }
+        };
+
+        let trade_cost = trades
+            .iter()
+            .map(|trade| trade.trade_cost.to_sat())
+            .sum::<i64>();
+
+        let balance = i64::try_from(self.balance)? - trade_cost;
+        let balance = u64::try_from(balance).map_err(|_| {
+            AppError::InsufficientFunds(format!(
+                "The paper trading balance of {} sats does not cover {trade_cost} sats",
+                self.balance
+            ))
+        })?;
+
+        self.balance = balance;
+        self.orders.push(order.clone());
+        self.trades.extend(trades);
+        self.positions
+            .retain(|position| position.contract_symbol != order.contract_symbol);
+        if let Some(position) = &position {
+            self.positions.push(position.clone());
+        }
+
+        let closed_position = match position {
+            Some(_) => None,
+            None => current_position,
+        };
+
+        Ok(Fill {
+            order,
+            position,
+            closed_position,
+        })
+    }
+}
+
+/// Takers buy at the best ask and sell at the best bid.
+fn execution_price(order: &Order, prices: &Prices) -> Result<f32> {
+    let price = prices
+        .get(&order.contract_symbol)
+        .and_then(|price| match order.direction {
+            Direction::Long => price.ask,
+            Direction::Short => price.bid,
+        });
+
+    let price = price.ok_or_else(|| {
+        AppError::OrderRejected(format!(
+            "No price to fill a {} order in {}",
+            order.direction, order.contract_symbol
+        ))
+    })?;
+
+    price.to_f32().context("Price does not fit into f32")
+}
+
+/// Fill the `order` with the simulator and publish the resulting updates.
+pub fn submit_order(order: Order) -> Result<Uuid> {
+    let prices = state::try_get_prices().unwrap_or_default();
+    let now = state::get_clock().now();
+    let expiry = commons::calculate_next_expiry(now, config::get_network());
+
+    let fill = SIMULATOR.lock().fill(order, &prices, expiry)?;
+
+    tracing::info!(order = ?fill.order, "Filled paper trading order");
+
+    let order_id = fill.order.id;
+
+    event::publish(&EventInternal::OrderUpdateNotification(fill.order));
+    if let Some(position) = fill.position {
+        event::publish(&EventInternal::PositionUpdateNotification(position));
+    }
+    if let Some(position) = fill.closed_position {
+        event::publish(&EventInternal::PositionCloseNotification(position));
+    }
+
+    Ok(order_id)
+}
+
+pub fn get_orders() -> Vec<Order> {
+    SIMULATOR.lock().orders.clone()
+}
+
+pub fn get_positions() -> Vec<Position> {
+    SIMULATOR.lock().positions.clone()
+}
+
+/// The fake balance which is not locked in positions, in sats.
+pub fn get_balance() -> u64 {
+    SIMULATOR.lock().balance
+}
+
+/// Switch between paper trading and real trading.
+///
+/// The positions of the mode which is left are removed from the UI, and those of the mode which
+/// is entered are shown instead.
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    if config::is_paper_trading() == enabled {
+        return Ok(());
+    }
+
+    let positions_before = position::handler::get_positions_for_ui()?;
+    config::set_paper_trading(enabled);
+    let positions_after = position::handler::get_positions_for_ui()?;
+
+    tracing::info!(enabled, "Switched paper trading");
+
+    for position in positions_before {
+        event::publish(&EventInternal::PositionCloseNotification(position));
+    }
+    for position in positions_after {
+        event::publish(&EventInternal::PositionUpdateNotification(position));
+    }
+
+    Ok(())
+}
+
+/// Start over with the initial balance and without any orders or positions.
+pub fn reset() {
+    let positions = std::mem::replace(&mut *SIMULATOR.lock(), Simulator::new()).positions;
+
+    if config::is_paper_trading() {
+        for position in positions {
+            event::publish(&EventInternal::PositionCloseNotification(position));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trade::order::OrderReason;
+    use commons::Price;
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+    use trade::ContractSymbol;
+
+    fn prices() -> Prices {
+        HashMap::from([(
+            ContractSymbol::BtcUsd,
+            Price {
+                bid: Some(dec!(29_990)),
+                ask: Some(dec!(30_010)),
+            },
+        )])
+    }
+
+    fn market_order(direction: Direction, quantity: f32, reduce_only: bool) -> Order {
+        let now = OffsetDateTime::now_utc();
+        Order {
+            id: Uuid::new_v4(),
+            leverage: 2.0,
+            quantity,
+            contract_symbol: ContractSymbol::BtcUsd,
+            direction,
+            order_type: OrderType::Market,
+            state: OrderState::Initial,
+            creation_timestamp: now,
+            order_expiry_timestamp: now + time::Duration::minutes(1),
+            reason: OrderReason::Manual,
+            stable: false,
+            reduce_only,
+            position_id: None,
+            failure_reason: None,
+        }
+    }
+
+    fn fill(
+        simulator: &mut Simulator,
+        direction: Direction,
+        quantity: f32,
+        reduce_only: bool,
+    ) -> Result<Fill> {
+        simulator.fill(
+            market_order(direction, quantity, reduce_only),
+            &prices(),
+            OffsetDateTime::now_utc() + time::Duration::days(7),
+        )
+    }
+
+    #[test]
+    fn opening_position_locks_margin_and_fee() {
+        let mut simulator = Simulator::new();
+
+        let fill = fill(&mut simulator, Direction::Long, 100.0, false).unwrap();
+
+        let position = fill.position.unwrap();
+        assert_eq!(position.average_entry_price, 30_010.0);
+        assert_eq!(position.direction, Direction::Long);
+        assert!(fill.closed_position.is_none());
+
+        let trade = &simulator.trades[0];
+        assert_eq!(
+            simulator.balance,
+            INITIAL_BALANCE_SATS - trade.trade_cost.to_sat() as u64
+        );
+        assert_eq!(
+            trade.trade_cost.to_sat() as u64,
+            position.collateral + trade.fee.to_sat()
+        );
+    }
+
+    #[test]
+    fn closing_position_returns_margin_minus_fees_and_loss() {
+        let mut simulator = Simulator::new();
+        fill(&mut simulator, Direction::Long, 100.0, false).unwrap();
+
+        let fill = fill(&mut simulator, Direction::Short, 100.0, true).unwrap();
+
+        assert!(fill.position.is_none());
+        assert!(fill.closed_position.is_some());
+        assert!(simulator.positions.is_empty());
+
+        // Sold at the bid after buying at the ask.
+        let trade = simulator.trades.last().unwrap();
+        assert!(trade.pnl.unwrap().to_sat() < 0);
+        assert!(simulator.balance < INITIAL_BALANCE_SATS);
+    }
+
+    #[test]
+    fn reduce_only_order_without_position_is_rejected() {
+        let mut simulator = Simulator::new();
+
+        let error = fill(&mut simulator, Direction::Short, 100.0, true)
+            .err()
+            .unwrap();
+
+        assert!(error.downcast_ref::<AppError>().is_some());
+        assert_eq!(simulator.balance, INITIAL_BALANCE_SATS);
+        assert!(simulator.orders.is_empty());
+    }
+
+    #[test]
+    fn order_exceeding_balance_is_rejected() {
+        let mut simulator = Simulator::new();
+        fill(&mut simulator, Direction::Long, 100.0, false).unwrap();
+
+        let error = fill(&mut simulator, Direction::Long, 1_000_000.0, false)
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            error.downcast_ref::<AppError>(),
+            Some(AppError::InsufficientFunds(_))
+        ));
+        assert_eq!(simulator.positions[0].quantity, 100.0);
+    }
+}
@@ -43,6 +43,7 @@ pub enum PositionState {
 #[frb]
 #[derive(Debug, Clone)]
 pub struct Position {
+    pub id: String,
     pub leverage: f32,
     pub quantity: f32,
     pub contract_symbol: ContractSymbol,
@@ -69,6 +70,7 @@ impl From<position::PositionState> for PositionState {
 impl From<position::Position> for Position {
     fn from(value: position::Position) -> Self {
         Position {
+            id: value.id.to_string(),
             leverage: value.leverage,
             quantity: value.quantity,
             contract_symbol: value.contract_symbol,
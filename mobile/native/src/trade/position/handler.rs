@@ -1,12 +1,18 @@
 use crate::calculations::calculate_margin;
+use crate::commons::reqwest_client;
+use crate::config;
 use crate::db;
+use crate::error::AppError;
 use crate::event;
 use crate::event::EventInternal;
 use crate::ln_dlc;
+use crate::state;
 use crate::trade::order;
 use crate::trade::order::Order;
+use crate::trade::order::OrderReason;
 use crate::trade::order::OrderState;
 use crate::trade::order::OrderType;
+use crate::trade::paper;
 use crate::trade::position::compute_relative_contracts;
 use crate::trade::position::Position;
 use crate::trade::position::PositionState;
@@ -16,10 +22,12 @@ use anyhow::Result;
 use commons::FilledWith;
 use commons::Prices;
 use commons::TradeParams;
+use commons::TraderPosition;
+use commons::TraderPositionState;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
-use trade::ContractSymbol;
+use uuid::Uuid;
 
 /// Sets up a trade with the counterparty
 ///
@@ -32,7 +40,7 @@ pub async fn trade(filled: FilledWith) -> Result<()> {
     tracing::debug!(?order, ?filled, "Filling order with id: {}", order.id);
 
     let trade_params = TradeParams {
-        pubkey: ln_dlc::get_node_pubkey(),
+        pubkey: ln_dlc::get_node_pubkey()?,
         contract_symbol: order.contract_symbol,
         leverage: order.leverage,
         quantity: order.quantity,
@@ -47,25 +55,23 @@ pub async fn trade(filled: FilledWith) -> Result<()> {
     order::handler::order_filling(order.id, execution_price)
         .context("Could not update order to filling")?;
 
-    // If we have a position _and_ the order is not closing the position (i.e. the contracts between
-    // position and order do not match), we must be resizing the position.
+    // If the order is for one of our positions _and_ it is not closing the position (i.e. the
+    // contracts between position and order do not match), we must be resizing the position.
 
     if let Some(Position {
+        id,
         quantity,
         position_state: PositionState::Open,
         direction,
-        contract_symbol,
         ..
-    }) = get_positions()?.first()
+    }) = get_position_of_order(&order)?
     {
-        let position_contracts_relative = compute_relative_contracts(*quantity, *direction);
+        let position_contracts_relative = compute_relative_contracts(quantity, direction);
         let trade_contracts_relative =
             compute_relative_contracts(trade_params.quantity, trade_params.direction);
 
-        if *contract_symbol == trade_params.contract_symbol
-            && position_contracts_relative + trade_contracts_relative != Decimal::ZERO
-        {
-            set_position_state(PositionState::Resizing)?;
+        if position_contracts_relative + trade_contracts_relative != Decimal::ZERO {
+            set_position_state(id, PositionState::Resizing)?;
         }
     }
 
@@ -79,7 +85,16 @@ pub async fn trade(filled: FilledWith) -> Result<()> {
 
 /// Executes an async trade from the orderbook / coordinator. e.g. this will happen if the position
 /// expires.
+///
+/// The order is applied to our position in its contract which expires first, as that is the one
+/// the coordinator is closing.
 pub async fn async_trade(order: commons::Order, filled_with: FilledWith) -> Result<()> {
+    let position_id = get_positions()?
+        .into_iter()
+        .filter(|position| position.contract_symbol == order.contract_symbol)
+        .min_by_key(|position| position.expiry)
+        .map(|position| position.id);
+
     let order_type = match order.order_type {
         commons::OrderType::Market => OrderType::Market,
         commons::OrderType::Limit => OrderType::Limit {
@@ -103,6 +118,8 @@ pub async fn async_trade(order: commons::Order, filled_with: FilledWith) -> Resu
         order_expiry_timestamp: order.expiry,
         reason: order.order_reason.into(),
         stable: order.stable,
+        reduce_only: false,
+        position_id,
         failure_reason: None,
     };
 
@@ -111,7 +128,7 @@ pub async fn async_trade(order: commons::Order, filled_with: FilledWith) -> Resu
     event::publish(&EventInternal::OrderUpdateNotification(order.clone()));
 
     let trade_params = TradeParams {
-        pubkey: ln_dlc::get_node_pubkey(),
+        pubkey: ln_dlc::get_node_pubkey()?,
         contract_symbol: order.contract_symbol,
         leverage: order.leverage,
         quantity: order.quantity,
@@ -132,18 +149,284 @@ pub async fn rollover(contract_id: Option<String>) -> Result<()> {
     ln_dlc::rollover(contract_id).await
 }
 
+/// Fetch the coordinator's view of our positions.
+pub async fn fetch_positions_from_coordinator() -> Result<Vec<TraderPosition>> {
+    let node_id = ln_dlc::get_node_pubkey()?;
+    let message = commons::create_sign_message(node_id.to_string().as_bytes().to_vec());
+    let signature = ln_dlc::get_node_key()?.sign_ecdsa(message);
+
+    let client = reqwest_client();
+    let response = client
+        .get(format!(
+            "http://{}/api/v1/positions/{node_id}",
+            config::get_http_endpoint()
+        ))
+        .json(&signature)
+        .send()
+        .await
+        .context("Failed to fetch positions from coordinator")?;
+
+    if !response.status().is_success() {
+        let response_text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                format!("could not decode response {err:#}")
+            }
+        };
+        bail!("Could not fetch positions from coordinator: {response_text}");
+    }
+
+    response.json().await.context("Failed to parse positions")
+}
+
+/// Reconcile our positions with the coordinator's view of them, e.g. after restoring from a
+/// backup which does not contain the latest state of the positions.
+///
+/// Positions are matched by their contract symbol. We only take over the coordinator's view if
+/// the position is open on the coordinator's side, as any other state indicates that a protocol
+/// is still in progress. If we hold several positions in the contract, we can't tell which one the
+/// coordinator's position corresponds to, hence we keep them as they are.
+pub async fn reconcile_positions_with_coordinator() -> Result<()> {
+    let coordinator_positions = fetch_positions_from_coordinator().await?;
+
+    for position in db::get_positions()? {
+        if !coordinator_positions.iter().any(|coordinator_position| {
+            coordinator_position.contract_symbol == position.contract_symbol
+        }) {
+            tracing::warn!(
+                ?position,
+                "Coordinator does not know about our position. Keeping local position"
+            );
+        }
+    }
+
+    for coordinator_position in coordinator_positions {
+        let mut positions = db::get_positions()?
+            .into_iter()
+            .filter(|position| position.contract_symbol == coordinator_position.contract_symbol)
+            .collect::<Vec<_>>();
+
+        if positions.len() > 1 {
+            tracing::warn!(
+                ?coordinator_position,
+                "Holding several positions in the contract. Keeping local positions"
+            );
+            continue;
+        }
+
+        reconcile_position(positions.pop(), coordinator_position)?;
+    }
+
+    Ok(())
+}
+
+fn reconcile_position(
+    position: Option<Position>,
+    coordinator_position: TraderPosition,
+) -> Result<()> {
+    match (position, coordinator_position) {
+        (None, coordinator_position) if coordinator_position.state == TraderPositionState::Open => {
+            tracing::info!(?coordinator_position, "Restoring position from coordinator");
+
+            let now = OffsetDateTime::now_utc();
+            let position = db::insert_position(Position {
+                id: Uuid::new_v4(),
+                leverage: coordinator_position.leverage,
+                quantity: coordinator_position.quantity,
+                contract_symbol: coordinator_position.contract_symbol,
+                direction: coordinator_position.direction,
+                average_entry_price: coordinator_position.average_entry_price,
+                liquidation_price: coordinator_position.liquidation_price,
+                position_state: PositionState::Open,
+                collateral: coordinator_position.collateral,
+                expiry: coordinator_position.expiry_timestamp,
+                updated: now,
+                created: now,
+                stable: coordinator_position.stable,
+            })?;
+
+            event::publish(&EventInternal::PositionUpdateNotification(position));
+        }
+        (Some(position), coordinator_position)
+            if coordinator_position.state == TraderPositionState::Open
+                && position.position_state == PositionState::Open
+                && (position.expiry != coordinator_position.expiry_timestamp
+                    || position.quantity != coordinator_position.quantity
+                    || position.direction != coordinator_position.direction) =>
+        {
+            tracing::info!(
+                ?position,
+                ?coordinator_position,
+                "Local position is out of sync with the coordinator. Updating position"
+            );
+
+            let position = Position {
+                leverage: coordinator_position.leverage,
+                quantity: coordinator_position.quantity,
+                direction: coordinator_position.direction,
+                average_entry_price: coordinator_position.average_entry_price,
+                liquidation_price: coordinator_position.liquidation_price,
+                collateral: coordinator_position.collateral,
+                expiry: coordinator_position.expiry_timestamp,
+                updated: OffsetDateTime::now_utc(),
+                ..position
+            };
+            db::update_position(position.clone())?;
+
+            event::publish(&EventInternal::PositionUpdateNotification(position));
+        }
+        (_, coordinator_position) => {
+            tracing::debug!(
+                ?coordinator_position,
+                "Position is in sync with the coordinator or a protocol is still in progress"
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// Fetch the positions from the database
 pub fn get_positions() -> Result<Vec<Position>> {
     db::get_positions()
 }
 
+/// The positions to show to the user, i.e. the simulated ones while paper trading.
+pub fn get_positions_for_ui() -> Result<Vec<Position>> {
+    if config::is_paper_trading() {
+        return Ok(paper::get_positions());
+    }
+
+    get_positions()
+}
+
+/// Fetch the position with the given id, if any.
+pub fn get_position(position_id: Uuid) -> Result<Option<Position>> {
+    let position = db::get_positions()?
+        .into_iter()
+        .find(|position| position.id == position_id);
+
+    Ok(position)
+}
+
+/// Fetch the position which the order resizes or closes, if any.
+///
+/// An order without a position opens a new one, even if we already hold a position in the same
+/// contract.
+fn get_position_of_order(order: &Order) -> Result<Option<Position>> {
+    match order.position_id {
+        Some(position_id) => get_position(position_id),
+        None => Ok(None),
+    }
+}
+
+fn get_open_position(position_id: Uuid) -> Result<Position> {
+    get_positions_for_ui()?
+        .into_iter()
+        .find(|position| position.id == position_id)
+        .filter(|position| position.position_state == PositionState::Open)
+        .with_context(|| format!("No open position with id {position_id}"))
+}
+
+/// Close our open position with a reduce-only market order for the whole position.
+///
+/// The order being reduce-only guarantees that it can't open a position in the opposite direction
+/// if the position has changed in the meantime.
+pub async fn close_position(position_id: Uuid) -> Result<Uuid> {
+    let position = get_open_position(position_id)?;
+
+    let now = OffsetDateTime::now_utc();
+    let order = Order {
+        id: Uuid::new_v4(),
+        leverage: position.leverage,
+        quantity: position.quantity,
+        contract_symbol: position.contract_symbol,
+        direction: position.direction.opposite(),
+        order_type: OrderType::Market,
+        state: OrderState::Initial,
+        creation_timestamp: now,
+        // The expiry is renewed when the order is submitted to the orderbook.
+        order_expiry_timestamp: now + time::Duration::minutes(1),
+        reason: OrderReason::Manual,
+        stable: position.stable,
+        reduce_only: true,
+        position_id: Some(position.id),
+        failure_reason: None,
+    };
+
+    let order_id = order::handler::submit_order(order).await?;
+
+    Ok(order_id)
+}
+
+/// Resize our open position to `new_quantity` contracts at `new_leverage`.
+///
+/// The difference to the current quantity is traded with a market order, which renews the DLC
+/// with the combined contract terms once it is filled. Reducing the position uses a reduce-only
+/// order, so that the coordinator rejects it if it would flip the position.
+pub async fn resize_position(
+    position_id: Uuid,
+    new_quantity: f32,
+    new_leverage: f32,
+) -> Result<Uuid> {
+    let position = get_open_position(position_id)?;
+
+    if new_quantity <= 0.0 {
+        return Err(AppError::InvalidInput(
+            "Quantity must be positive, use close_position to close the position".to_string(),
+        )
+        .into());
+    }
+
+    let quantity_diff = new_quantity - position.quantity;
+    if quantity_diff == 0.0 {
+        // A DLC renew is only triggered by a trade, hence the leverage can only be changed
+        // together with the quantity.
+        return Err(AppError::InvalidInput(format!(
+            "Position already has a quantity of {new_quantity}"
+        ))
+        .into());
+    }
+
+    let (direction, reduce_only) = if quantity_diff > 0.0 {
+        (position.direction, false)
+    } else {
+        (position.direction.opposite(), true)
+    };
+
+    let now = OffsetDateTime::now_utc();
+    let order = Order {
+        id: Uuid::new_v4(),
+        leverage: new_leverage,
+        quantity: quantity_diff.abs(),
+        contract_symbol: position.contract_symbol,
+        direction,
+        order_type: OrderType::Market,
+        state: OrderState::Initial,
+        creation_timestamp: now,
+        // The expiry is renewed when the order is submitted to the orderbook.
+        order_expiry_timestamp: now + time::Duration::minutes(1),
+        reason: OrderReason::Manual,
+        stable: position.stable,
+        reduce_only,
+        position_id: Some(position.id),
+        failure_reason: None,
+    };
+
+    order.validate()?;
+
+    let order_id = order::handler::submit_order(order).await?;
+
+    Ok(order_id)
+}
+
 /// Update the position once an order was submitted
 ///
 /// If the new order submitted is an order that closes the current position, then the position will
 /// be updated to `Closing` state.
 pub fn update_position_after_order_submitted(submitted_order: &Order) -> Result<()> {
     if let Some(position) = get_position_matching_order(submitted_order)? {
-        db::update_position_state(position.contract_symbol, PositionState::Closing)?;
+        db::update_position_state(position.id, PositionState::Closing)?;
         let mut position = position;
         position.position_state = PositionState::Closing;
         event::publish(&EventInternal::PositionUpdateNotification(position));
@@ -151,23 +434,22 @@ pub fn update_position_after_order_submitted(submitted_order: &Order) -> Result<
     Ok(())
 }
 
-/// If the submitted order would close the current [`Position`], return the [`position`].
+/// If the submitted order would close its [`Position`], return the [`position`].
 pub fn get_position_matching_order(order: &Order) -> Result<Option<Position>> {
-    match db::get_positions()?.first() {
+    match get_position_of_order(order)? {
         Some(position)
             if position.direction != order.direction && position.quantity == order.quantity =>
         {
-            Ok(Some(position.clone()))
+            Ok(Some(position))
         }
         _ => Ok(None),
     }
 }
 
-/// Sets the position to the given state
-pub fn set_position_state(state: PositionState) -> Result<()> {
-    if let Some(position) = db::get_positions()?.first() {
-        db::update_position_state(position.contract_symbol, state)?;
-        let mut position = position.clone();
+/// Sets the position with the given id to the given state
+pub fn set_position_state(position_id: Uuid, state: PositionState) -> Result<()> {
+    if let Some(mut position) = get_position(position_id)? {
+        db::update_position_state(position.id, state)?;
         position.position_state = state;
         event::publish(&EventInternal::PositionUpdateNotification(position));
     }
@@ -175,29 +457,54 @@ pub fn set_position_state(state: PositionState) -> Result<()> {
     Ok(())
 }
 
+/// Sets all positions in the given state to `new_state`.
+pub fn set_positions_state(state: PositionState, new_state: PositionState) -> Result<()> {
+    for mut position in get_positions()?
+        .into_iter()
+        .filter(|position| position.position_state == state)
+    {
+        db::update_position_state(position.id, new_state)?;
+        position.position_state = new_state;
+        event::publish(&EventInternal::PositionUpdateNotification(position));
+    }
+
+    Ok(())
+}
+
 /// A channel renewal could be triggered for:
 ///
 /// - Rolling over (no offer associated).
 /// - Opening a new position.
 /// - Resizing a new position.
 pub fn handle_channel_renewal_offer(expiry_timestamp: OffsetDateTime) -> Result<()> {
-    // FIXME: This won't always be true once we reintroduce position resizing.
-    if let Some(position) = db::get_positions()?.first() {
-        tracing::debug!("Setting position to rollover");
-        db::rollover_position(position.contract_symbol, expiry_timestamp)?;
-        let mut position = position.clone();
+    if let Some(order) = db::get_order_in_filling()? {
+        // The positions are updated with the new contract terms once the renewed DLC channel is
+        // confirmed, see `update_position_after_dlc_channel_creation_or_update`.
+        match get_position_of_order(&order)? {
+            Some(_) => tracing::info!("Received channel renewal proposal to resize position"),
+            None => tracing::info!("Received channel renewal proposal to open new position"),
+        }
+
+        return Ok(());
+    }
+
+    // Without an order in filling we must be rolling over, which applies to all positions in the
+    // DLC channel.
+    for mut position in get_positions()? {
+        tracing::debug!(position_id = %position.id, "Setting position to rollover");
+        db::rollover_position(position.id, expiry_timestamp)?;
         position.position_state = PositionState::Rollover;
         position.expiry = expiry_timestamp;
         event::publish(&EventInternal::PositionUpdateNotification(position));
-    } else {
-        // If we have no position we must be opening a new one.
-        tracing::info!("Received channel renewal proposal to open new position");
     }
 
     Ok(())
 }
 
 /// Create a position after creating or updating a DLC channel.
+///
+/// If the filled order is for one of our positions, the position is resized, or removed if the
+/// order closes it. Otherwise, a new position is created next to the ones we already hold.
 pub fn update_position_after_dlc_channel_creation_or_update(
     filled_order: Order,
     expiry: OffsetDateTime,
@@ -214,7 +521,7 @@ pub fn update_position_after_dlc_channel_creation_or_update(
         filled_order.leverage,
     );
 
-    let (position, trades) = match db::get_positions()?.first() {
+    let (position, trades) = match get_position_of_order(&filled_order)? {
         None => {
             tracing::debug!(
                 order = ?filled_order,
@@ -232,17 +539,36 @@ pub fn update_position_after_dlc_channel_creation_or_update(
         }
         Some(
             position @ Position {
-                position_state: PositionState::Resizing,
+                position_state: PositionState::Resizing | PositionState::Closing,
                 ..
             },
         ) => {
-            tracing::info!("Calculating new position after DLC channel has been resized");
+            tracing::info!("Calculating new position after DLC channel has been updated");
 
-            let (position, trades) = position.clone().apply_order(filled_order, expiry, margin)?;
+            let closed_position = position.clone();
+            let (position, trades) = position.apply_order(filled_order, expiry, margin)?;
 
-            let position = position.context("Resized position has vanished")?;
+            let position = match position {
+                Some(position) => {
+                    db::update_position(position.clone())?;
+                    position
+                }
+                None => {
+                    tracing::info!(
+                        position_id = %closed_position.id,
+                        "Position has been closed while keeping the DLC channel"
+                    );
 
-            db::update_position(position.clone())?;
+                    for trade in trades {
+                        db::insert_trade(trade)?;
+                    }
+
+                    db::delete_position(closed_position.id)?;
+                    event::publish(&EventInternal::PositionCloseNotification(closed_position));
+
+                    return Ok(());
+                }
+            };
 
             (position, trades)
         }
@@ -263,23 +589,30 @@ pub fn update_position_after_dlc_channel_creation_or_update(
     Ok(())
 }
 
-/// Delete a position after closing a DLC channel.
+/// Delete the positions after closing a DLC channel.
+///
+/// The `filled_order` is applied to its position to record the closing trades. All other positions
+/// in the DLC channel are closed with it too.
 pub fn update_position_after_dlc_closure(filled_order: Option<Order>) -> Result<()> {
-    tracing::debug!(?filled_order, "Removing position after DLC channel closure");
+    tracing::debug!(
+        ?filled_order,
+        "Removing positions after DLC channel closure"
+    );
 
-    let position = match db::get_positions()?.as_slice() {
-        [position] => position.clone(),
-        [position, ..] => {
-            tracing::warn!("Found more than one position. Taking the first one");
-            position.clone()
-        }
-        [] => {
-            tracing::warn!("No position to remove");
-            return Ok(());
-        }
-    };
+    let positions = db::get_positions()?;
+    if positions.is_empty() {
+        tracing::warn!("No position to remove");
+        return Ok(());
+    }
 
-    if let Some(filled_order) = filled_order {
+    let position = filled_order.as_ref().and_then(|filled_order| {
+        positions
+            .iter()
+            .find(|position| Some(position.id) == filled_order.position_id)
+            .cloned()
+    });
+
+    if let (Some(position), Some(filled_order)) = (position, filled_order) {
         tracing::debug!(
             ?position,
             ?filled_order,
@@ -307,17 +640,130 @@ pub fn update_position_after_dlc_closure(filled_order: Option<Order>) -> Result<
         }
     }
 
-    db::delete_positions()?;
+    for position in positions {
+        db::delete_position(position.id)?;
 
-    event::publish(&EventInternal::PositionCloseNotification(
-        ContractSymbol::BtcUsd,
-    ));
+        event::publish(&EventInternal::PositionCloseNotification(position));
+    }
 
     Ok(())
 }
 
 pub fn price_update(prices: Prices) -> Result<()> {
     tracing::debug!(?prices, "Updating prices");
+    state::set_prices(prices.clone());
     event::publish(&EventInternal::PriceUpdateNotification(prices));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trade::ContractSymbol;
+    use trade::Direction;
+
+    #[test]
+    fn two_concurrent_positions_are_opened_reduced_and_closed_independently() {
+        db::init_test_db();
+
+        let expiry = OffsetDateTime::from_unix_timestamp(1_706_745_600).unwrap();
+        let later_expiry = expiry + time::Duration::days(7);
+
+        // Open two positions in the same contract with different expiries.
+        update_position_after_dlc_channel_creation_or_update(
+            filled_order(Direction::Long, 100.0, None),
+            expiry,
+        )
+        .unwrap();
+        update_position_after_dlc_channel_creation_or_update(
+            filled_order(Direction::Short, 50.0, None),
+            later_expiry,
+        )
+        .unwrap();
+
+        let positions = get_positions().unwrap();
+        assert_eq!(positions.len(), 2);
+
+        let long = position_in_direction(&positions, Direction::Long);
+        assert_eq!(long.quantity, 100.0);
+        assert_eq!(long.expiry, expiry);
+
+        let short = position_in_direction(&positions, Direction::Short);
+        assert_eq!(short.quantity, 50.0);
+        assert_eq!(short.expiry, later_expiry);
+
+        // Reduce the long position.
+        set_position_state(long.id, PositionState::Resizing).unwrap();
+        update_position_after_dlc_channel_creation_or_update(
+            filled_order(Direction::Short, 40.0, Some(long.id)),
+            expiry,
+        )
+        .unwrap();
+
+        let reduced_long = get_position(long.id).unwrap().unwrap();
+        assert_eq!(reduced_long.quantity, 60.0);
+        assert_eq!(reduced_long.direction, Direction::Long);
+        assert_eq!(reduced_long.position_state, PositionState::Open);
+
+        let untouched_short = get_position(short.id).unwrap().unwrap();
+        assert_eq!(untouched_short.quantity, 50.0);
+        assert_eq!(untouched_short.position_state, PositionState::Open);
+
+        // Close the short position, while the DLC channel is kept for the long position.
+        let close_short = filled_order(Direction::Long, 50.0, Some(short.id));
+        update_position_after_order_submitted(&close_short).unwrap();
+        assert_eq!(
+            get_position(short.id).unwrap().unwrap().position_state,
+            PositionState::Closing
+        );
+
+        update_position_after_dlc_channel_creation_or_update(close_short, expiry).unwrap();
+
+        assert!(get_position(short.id).unwrap().is_none());
+        assert_eq!(get_position(long.id).unwrap().unwrap().quantity, 60.0);
+
+        // Close the long position together with the DLC channel.
+        update_position_after_dlc_closure(Some(filled_order(
+            Direction::Short,
+            60.0,
+            Some(long.id),
+        )))
+        .unwrap();
+
+        assert!(get_positions().unwrap().is_empty());
+
+        // Two opening trades, one reducing trade and two closing trades.
+        assert_eq!(db::get_all_trades().unwrap().len(), 5);
+    }
+
+    fn filled_order(direction: Direction, quantity: f32, position_id: Option<Uuid>) -> Order {
+        let now = OffsetDateTime::now_utc();
+
+        Order {
+            id: Uuid::new_v4(),
+            leverage: 2.0,
+            quantity,
+            contract_symbol: ContractSymbol::BtcUsd,
+            direction,
+            order_type: OrderType::Market,
+            state: OrderState::Filled {
+                execution_price: 30_000.0,
+            },
+            creation_timestamp: now,
+            order_expiry_timestamp: now,
+            reason: OrderReason::Manual,
+            stable: false,
+            reduce_only: position_id.is_some(),
+            position_id,
+            failure_reason: None,
+        }
+    }
+
+    fn position_in_direction(positions: &[Position], direction: Direction) -> Position {
+        positions
+            .iter()
+            .find(|position| position.direction == direction)
+            .cloned()
+            .unwrap()
+    }
+}
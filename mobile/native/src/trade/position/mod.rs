@@ -1,4 +1,5 @@
 use crate::calculations::calculate_liquidation_price;
+use crate::calculations::calculate_margin;
 use crate::calculations::calculate_pnl;
 use crate::trade::order::Order;
 use crate::trade::order::OrderState;
@@ -18,6 +19,7 @@ use serde::Serialize;
 use time::OffsetDateTime;
 use trade::ContractSymbol;
 use trade::Direction;
+use uuid::Uuid;
 
 pub mod api;
 pub mod handler;
@@ -60,6 +62,7 @@ pub enum PositionState {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Position {
+    pub id: Uuid,
     pub leverage: f32,
     pub quantity: f32,
     pub contract_symbol: ContractSymbol,
@@ -119,6 +122,7 @@ impl Position {
         }
 
         let position = Self {
+            id: Uuid::new_v4(),
             leverage: order.leverage,
             quantity: order.quantity,
             contract_symbol: order.contract_symbol,
@@ -179,9 +183,18 @@ impl Position {
             "Cannot apply limit order to position"
         );
 
+        let leverage = order.leverage;
+
         let mut trades = Vec::new();
         let position = self.apply_order_recursive(order, expiry, &mut trades)?;
 
+        let position = match position {
+            Some(position) if position.leverage != leverage => {
+                Some(position.change_leverage(leverage, &mut trades))
+            }
+            position => position,
+        };
+
         {
             let calculated_collateral_sat =
                 position.as_ref().map(|p| p.collateral).unwrap_or_default();
@@ -203,14 +216,10 @@ impl Position {
     /// contracts or (3) increase the position. By combining (2) and (3) through recursion we are
     /// able to apply orders which change the direction of the position.
     ///
-    /// NOTE: The order's leverage is ignored when applying an order to an existing position. It
-    /// does not seem to make much sense to allow a trader to both change the number and/or
-    /// direction of contracts (the point of creating a new order) _and_ to change the leverage.
-    /// Also, it's not so straightforward to calculate combined leverages, particularly when
-    /// reducing a position.
-    ///
-    /// TODO: This highlights the fact that orders really shouldn't have a leverage associated with
-    /// them!
+    /// NOTE: The order's leverage is ignored when applying an order to an existing position, as
+    /// it's not so straightforward to calculate combined leverages, particularly when reducing a
+    /// position. Instead, the leverage of the resulting position is changed afterwards, see
+    /// [`Position::change_leverage`].
     fn apply_order_recursive(
         self,
         order: Order,
@@ -341,6 +350,7 @@ impl Position {
                 };
 
                 let position = Position {
+                    id: self.id,
                     leverage: f32_from_decimal(starting_leverage),
                     quantity: contract_diff,
                     contract_symbol: self.contract_symbol,
@@ -517,6 +527,7 @@ impl Position {
             let stable = self.stable && order.stable && self.direction == Direction::Short;
 
             let position = Position {
+                id: self.id,
                 leverage: f32_from_decimal(starting_leverage),
                 quantity: f32_from_decimal(total_contracts_relative.abs()),
                 contract_symbol: self.contract_symbol,
@@ -574,6 +585,29 @@ impl Position {
 
         position.apply_order_recursive(order, expiry, trades)
     }
+
+    /// Change the leverage of the position to the leverage of the order which resized it, as the
+    /// coordinator sets up the resized position with that leverage.
+    ///
+    /// The resulting change in collateral is added to the cost of the last trade, since it is
+    /// moved between the position and the wallet in the same protocol run.
+    fn change_leverage(self, leverage: f32, trades: &mut [Trade]) -> Self {
+        let collateral = calculate_margin(self.average_entry_price, self.quantity, leverage);
+        let liquidation_price =
+            calculate_liquidation_price(self.average_entry_price, leverage, self.direction);
+
+        if let Some(trade) = trades.last_mut() {
+            let margin_diff = SignedAmount::from_sat(collateral as i64 - self.collateral as i64);
+            trade.trade_cost += margin_diff;
+        }
+
+        Position {
+            leverage,
+            collateral,
+            liquidation_price,
+            ..self
+        }
+    }
 }
 
 /// The _cost_ of a trade is computed as the change in margin (positive if the margin _increases_),
@@ -637,6 +671,8 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: true,
+            reduce_only: false,
+            position_id: None,
             failure_reason: None,
         };
 
@@ -670,6 +706,7 @@ mod tests {
         let now = OffsetDateTime::now_utc();
 
         let position = Position {
+            id: Uuid::new_v4(),
             leverage: 2.0,
             quantity: 10.0,
             contract_symbol: ContractSymbol::BtcUsd,
@@ -698,6 +735,8 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            reduce_only: false,
+            position_id: None,
             failure_reason: None,
         };
 
@@ -732,6 +771,7 @@ mod tests {
         let now = OffsetDateTime::now_utc();
 
         let position = Position {
+            id: Uuid::new_v4(),
             leverage: 2.0,
             quantity: 10.0,
             contract_symbol: ContractSymbol::BtcUsd,
@@ -760,6 +800,8 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            reduce_only: false,
+            position_id: None,
             failure_reason: None,
         };
 
@@ -798,11 +840,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extend_position_with_different_leverage() {
+        let now = OffsetDateTime::now_utc();
+
+        let position = Position {
+            id: Uuid::new_v4(),
+            leverage: 2.0,
+            quantity: 10.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            direction: Direction::Long,
+            average_entry_price: 36_469.5,
+            liquidation_price: 24_313.0,
+            position_state: PositionState::Resizing,
+            collateral: 13_710,
+            expiry: now,
+            updated: now,
+            created: now,
+            stable: false,
+        };
+
+        let order = Order {
+            id: Uuid::new_v4(),
+            leverage: 1.0,
+            quantity: 5.0,
+            contract_symbol: ContractSymbol::BtcUsd,
+            direction: Direction::Long,
+            order_type: OrderType::Market,
+            state: OrderState::Filled {
+                execution_price: 36_401.5,
+            },
+            creation_timestamp: now,
+            order_expiry_timestamp: now,
+            reason: OrderReason::Manual,
+            stable: false,
+            reduce_only: false,
+            position_id: None,
+            failure_reason: None,
+        };
+
+        let expected_collateral = calculate_margin(36_446.805, 15.0, 1.0);
+
+        let (updated_position, trades) = position
+            .apply_order(order, now, expected_collateral)
+            .unwrap();
+        let updated_position = updated_position.unwrap();
+
+        assert_eq!(updated_position.leverage, 1.0);
+        assert_eq!(updated_position.quantity, 15.0);
+        assert_eq!(updated_position.average_entry_price, 36_446.805);
+        assert_eq!(
+            updated_position.liquidation_price,
+            calculate_liquidation_price(36_446.805, 1.0, Direction::Long)
+        );
+        assert_eq!(updated_position.collateral, expected_collateral);
+
+        let trade = match trades.as_slice() {
+            [trade] => trade,
+            trades => panic!("Unexpected number of trades: {}", trades.len()),
+        };
+
+        // The same trade as when extending the position without changing the leverage, plus the
+        // additional collateral.
+        assert_eq!(
+            trade.trade_cost,
+            SignedAmount::from_sat(6_909 + expected_collateral as i64 - 20_578)
+        );
+    }
+
     #[test]
     fn reduce_position() {
         let now = OffsetDateTime::now_utc();
 
         let position = Position {
+            id: Uuid::new_v4(),
             leverage: 2.0,
             quantity: 10.0,
             contract_symbol: ContractSymbol::BtcUsd,
@@ -831,6 +942,8 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            reduce_only: false,
+            position_id: None,
             failure_reason: None,
         };
 
@@ -880,6 +993,7 @@ mod tests {
         let now = OffsetDateTime::now_utc();
 
         let position = Position {
+            id: Uuid::new_v4(),
             leverage: 2.0,
             quantity: 10.0,
             contract_symbol: ContractSymbol::BtcUsd,
@@ -908,6 +1022,8 @@ mod tests {
             order_expiry_timestamp: now,
             reason: OrderReason::Manual,
             stable: false,
+            reduce_only: false,
+            position_id: None,
             failure_reason: None,
         };
 
@@ -9,7 +9,7 @@ use commons::RegisterParams;
 /// Enroll the user in the beta program
 pub async fn register_beta(email: String) -> Result<()> {
     let register = RegisterParams {
-        pubkey: ln_dlc::get_node_pubkey(),
+        pubkey: ln_dlc::get_node_pubkey()?,
         email: Some(email),
         nostr: None,
     };
@@ -17,7 +17,7 @@ pub async fn register_beta(email: String) -> Result<()> {
     let client = reqwest_client();
     let response = client
         .post(format!(
-            "http://{}/api/register",
+            "http://{}/api/v1/register",
             config::get_http_endpoint()
         ))
         .json(&register)
@@ -0,0 +1,104 @@
+use crate::db;
+use crate::event;
+use crate::storage::TenTenOneNodeStorage;
+use anyhow::Result;
+use ln_dlc_node::node::event as node_event;
+
+/// Changes to the 10101 database, the DLC storage and the published events which have to be
+/// applied together.
+///
+/// All database writes on the current thread happen within a single transaction, the previous
+/// values of changed DLC storage entries are remembered, and events, messages to our peers and
+/// remote backups are held back. On [`UnitOfWork::commit`] the transaction is committed and the
+/// held back side effects are released. On [`UnitOfWork::rollback`], or if the unit of work is
+/// dropped before being committed, the transaction is rolled back, the DLC storage entries are
+/// restored and the held back side effects are discarded.
+///
+/// Note, changes made on other threads are not part of the unit of work.
+pub(crate) struct UnitOfWork {
+    storage: TenTenOneNodeStorage,
+    is_finished: bool,
+}
+
+impl UnitOfWork {
+    pub fn begin(storage: TenTenOneNodeStorage) -> Result<Self> {
+        db::begin_unit_of_work()?;
+        storage.begin_unit_of_work();
+        event::stage_events();
+        node_event::stage_events();
+
+        Ok(Self {
+            storage,
+            is_finished: false,
+        })
+    }
+
+    pub fn commit(mut self) -> Result<()> {
+        self.is_finished = true;
+
+        if let Err(e) = db::commit_unit_of_work() {
+            self.restore_dlc_storage();
+            event::discard_staged_events();
+            node_event::discard_staged_events();
+
+            return Err(e);
+        }
+
+        self.storage.commit_unit_of_work();
+        event::publish_staged_events();
+        node_event::publish_staged_events();
+
+        Ok(())
+    }
+
+    pub fn rollback(mut self) -> Result<()> {
+        self.is_finished = true;
+
+        event::discard_staged_events();
+        node_event::discard_staged_events();
+        self.restore_dlc_storage();
+        db::rollback_unit_of_work()
+    }
+
+    fn restore_dlc_storage(&self) {
+        if let Err(e) = self.storage.rollback_unit_of_work() {
+            tracing::error!("Failed to restore DLC storage: {e:#}");
+        }
+    }
+}
+
+impl Drop for UnitOfWork {
+    fn drop(&mut self) {
+        if self.is_finished {
+            return;
+        }
+
+        tracing::warn!("Rolling back unfinished unit of work");
+
+        event::discard_staged_events();
+        node_event::discard_staged_events();
+        self.restore_dlc_storage();
+        if let Err(e) = db::rollback_unit_of_work() {
+            tracing::error!("Failed to roll back unit of work: {e:#}");
+        }
+    }
+}
+
+/// Runs `f` within a [`UnitOfWork`], which is committed if `f` succeeds and rolled back otherwise.
+pub(crate) fn run<T>(storage: TenTenOneNodeStorage, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let unit_of_work = UnitOfWork::begin(storage)?;
+
+    match f() {
+        Ok(value) => {
+            unit_of_work.commit()?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(e) = unit_of_work.rollback() {
+                tracing::error!("Failed to roll back unit of work: {e:#}");
+            }
+
+            Err(e)
+        }
+    }
+}
@@ -150,8 +150,8 @@ void main() {
     ], child: const TestWrapperWithTradeTheme(child: TradeScreen())));
 
     // We have to pretend that we have a balance, because otherwise the trade bottom sheet validation will not allow us to go to the confirmation screen
-    walletChangeNotifier.update(WalletInfo(
-        balances: WalletBalances(onChain: Amount(0), offChain: Amount(10000)), history: []));
+    walletChangeNotifier.update(
+        WalletInfo(balances: WalletBalances(onChain: Amount(0), offChain: Amount(10000))));
 
     await tester.pumpAndSettle();
 
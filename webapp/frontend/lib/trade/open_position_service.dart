@@ -19,6 +19,7 @@ class OpenPositionsService {
 }
 
 class Position {
+  final String id;
   final Leverage leverage;
   final Usd quantity;
   final String contractSymbol;
@@ -33,6 +34,7 @@ class Position {
   final Amount pnlSats;
 
   Position({
+    required this.id,
     required this.leverage,
     required this.quantity,
     required this.contractSymbol,
@@ -49,6 +51,7 @@ class Position {
 
   factory Position.fromJson(Map<String, dynamic> json) {
     return Position(
+      id: json['id'] as String,
       leverage: Leverage(json['leverage'] as double),
       quantity: Usd(json['quantity'] as double),
       contractSymbol: json['contract_symbol'] as String,
@@ -144,6 +144,7 @@ pub async fn send_payment(params: Json<Payment>) -> Result<(), AppError> {
         address: params.0.address,
         amount: params.0.amount,
         fee: Fee::FeeRate { sats: params.0.fee },
+        inputs: None,
     })
     .await?;
 
@@ -195,6 +196,7 @@ impl TryFrom<NewOrderParams> for native::trade::order::Order {
             order_expiry_timestamp: OffsetDateTime::now_utc() + time::Duration::minutes(1),
             reason: native::trade::order::OrderReason::Manual,
             stable: false,
+            reduce_only: false,
             failure_reason: None,
         })
     }
@@ -214,6 +216,7 @@ pub async fn post_new_order(params: Json<NewOrderParams>) -> Result<Json<OrderId
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Position {
+    pub id: Uuid,
     pub leverage: f32,
     pub quantity: f32,
     pub contract_symbol: ContractSymbol,
@@ -249,6 +252,7 @@ impl From<(native::trade::position::Position, Option<Price>)> for Position {
             .and_then(|pnl| pnl);
 
         Position {
+            id: position.id,
             leverage: position.leverage,
             quantity: position.quantity,
             contract_symbol: position.contract_symbol,
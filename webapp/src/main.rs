@@ -71,8 +71,9 @@ async fn main() -> Result<()> {
         network: network.to_string(),
         oracle_endpoint,
         oracle_pubkey,
-        health_check_interval_secs: 60,
+        health_check_interval_secs: Some(60),
         rgs_server_url: None,
+        watchtower_url: None,
     };
 
     let seed_dir = data_dir.clone();